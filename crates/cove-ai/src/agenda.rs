@@ -0,0 +1,224 @@
+//! Agenda drafts for new meetings, built from recent threads with the
+//! attendees.
+//!
+//! A thread is an open item when the attendees spoke last or its latest
+//! message asks something. Without AI the open threads' subjects become the
+//! agenda; with AI their recent messages are condensed into discussion
+//! points, which never go beyond what the threads say.
+
+use crate::{AiError, AiService, DigestThread};
+use cove_core::{AgendaSource, AiMode, CloudAiProvider, MeetingAgenda};
+use uuid::Uuid;
+
+/// How far back threads with the attendees are looked for.
+pub const AGENDA_LOOKBACK_DAYS: i64 = 30;
+
+/// Recent messages with the attendees considered at most.
+pub const AGENDA_SCAN_LIMIT: i64 = 200;
+
+/// Agenda items suggested at most.
+const MAX_ITEMS: usize = 8;
+
+/// Latest messages of each open thread sent to the AI.
+const PROMPT_MESSAGES_PER_THREAD: usize = 3;
+
+/// Reply and forward prefixes stripped from agenda items.
+const SUBJECT_PREFIXES: &[&str] = &["re:", "fwd:", "fw:", "aw:", "wg:"];
+
+/// Open threads among `threads`, newest first, as agenda sources.
+/// `own_addresses` are the user's addresses; threads the user answered
+/// last, without a question pending, are settled.
+pub fn open_agenda_threads<'a>(
+    threads: &'a [DigestThread],
+    own_addresses: &[String],
+) -> Vec<(AgendaSource, &'a DigestThread)> {
+    let mut open: Vec<(AgendaSource, &'a DigestThread)> = threads
+        .iter()
+        .filter_map(|thread| {
+            let latest = thread.latest()?;
+            let sender = latest.from.first()?;
+            let from_self = own_addresses
+                .iter()
+                .any(|own| own.eq_ignore_ascii_case(sender.address.trim()));
+            let body = latest.body_text.as_deref().unwrap_or(&latest.preview);
+            if from_self && !body.contains('?') {
+                return None;
+            }
+            let source = AgendaSource {
+                thread_id: thread.thread_id.clone(),
+                subject: agenda_subject(&latest.subject),
+                last_sender: sender
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| sender.address.clone()),
+                last_message_at: latest.received_at,
+            };
+            Some((source, thread))
+        })
+        .collect();
+    open.sort_by_key(|(source, _)| std::cmp::Reverse(source.last_message_at));
+    open.truncate(MAX_ITEMS);
+    open
+}
+
+/// Agenda of the open threads' subjects, without AI.
+pub fn draft_meeting_agenda(threads: &[DigestThread], own_addresses: &[String]) -> MeetingAgenda {
+    let sources: Vec<AgendaSource> = open_agenda_threads(threads, own_addresses)
+        .into_iter()
+        .map(|(source, _)| source)
+        .collect();
+    let mut items: Vec<String> = Vec::new();
+    for source in &sources {
+        if !items
+            .iter()
+            .any(|item| item.eq_ignore_ascii_case(&source.subject))
+        {
+            items.push(source.subject.clone());
+        }
+    }
+    MeetingAgenda {
+        markdown: render_agenda(&items),
+        items,
+        sources,
+        provenance: None,
+    }
+}
+
+/// The agenda as a bullet list for an event description.
+pub fn render_agenda(items: &[String]) -> String {
+    if items.is_empty() {
+        return String::new();
+    }
+    let mut out = "Agenda:\n".to_string();
+    for item in items {
+        out.push_str(&format!("- {item}\n"));
+    }
+    out
+}
+
+fn agenda_subject(subject: &str) -> String {
+    let mut rest = subject.trim();
+    while let Some(prefix) = SUBJECT_PREFIXES.iter().find(|prefix| {
+        rest.get(..prefix.len())
+            .is_some_and(|head| head.eq_ignore_ascii_case(prefix))
+    }) {
+        rest = rest[prefix.len()..].trim_start();
+    }
+    if rest.is_empty() {
+        "(no subject)".to_string()
+    } else {
+        rest.to_string()
+    }
+}
+
+impl AiService {
+    /// Condense the open threads with the attendees into discussion points
+    /// for a meeting called `title`. Nothing is sent when no thread is open.
+    pub async fn suggest_meeting_agenda(
+        &self,
+        account_id: Uuid,
+        title: &str,
+        threads: &[DigestThread],
+        own_addresses: &[String],
+        mode: AiMode,
+        cloud_provider: Option<CloudAiProvider>,
+    ) -> Result<MeetingAgenda, AiError> {
+        let open = open_agenda_threads(threads, own_addresses);
+        if open.is_empty() {
+            return Ok(MeetingAgenda::default());
+        }
+
+        let mut context = String::new();
+        for (source, thread) in &open {
+            context.push_str(&format!("\n## {}\n", source.subject));
+            let messages = thread.prompt_messages();
+            let skip = messages.len().saturating_sub(PROMPT_MESSAGES_PER_THREAD);
+            for (sender, _, body) in messages.into_iter().skip(skip) {
+                context.push_str(&format!("{sender}: {}\n", body.trim()));
+            }
+        }
+        let prompt = format!(
+            "Draft an agenda for the meeting \"{title}\" from these recent email threads \
+             with its attendees. List the open items to discuss, one short line each, most \
+             important first, at most {MAX_ITEMS}. Do not invent items.\n{context}"
+        );
+        let (response, provenance) = self
+            .run_feature(Some(account_id), "meeting_agenda", &prompt, mode, cloud_provider)
+            .await?;
+
+        let items: Vec<String> = response
+            .output
+            .lines()
+            .map(|line| {
+                line.trim()
+                    .trim_start_matches(|c: char| c == '-' || c == '*' || c.is_ascii_digit())
+                    .trim_start_matches(['.', ')'])
+                    .trim()
+                    .to_string()
+            })
+            .filter(|line| !line.is_empty() && !line.eq_ignore_ascii_case("agenda:"))
+            .take(MAX_ITEMS)
+            .collect();
+        Ok(MeetingAgenda {
+            markdown: render_agenda(&items),
+            items,
+            sources: open.into_iter().map(|(source, _)| source).collect(),
+            provenance: Some(provenance),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cove_core::{MailAddress, MailMessage};
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn message(thread: &str, from: &str, subject: &str, body: &str, minute: i64) -> MailMessage {
+        let received_at =
+            Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap() + Duration::minutes(minute);
+        MailMessage {
+            remote_id: format!("{thread}-{minute}"),
+            thread_id: thread.to_string(),
+            from: vec![MailAddress {
+                name: None,
+                address: from.to_string(),
+            }],
+            subject: subject.to_string(),
+            preview: body.to_string(),
+            body_text: Some(body.to_string()),
+            received_at,
+            created_at: received_at,
+            updated_at: received_at,
+            ..MailMessage::fixture()
+        }
+    }
+
+    #[test]
+    fn settled_threads_are_left_out() {
+        let me = vec!["me@example.com".to_string()];
+        let threads = DigestThread::group(vec![
+            message("a", "ana@example.com", "Budget", "Can we cut travel?", 0),
+            message("a", "me@example.com", "Re: Budget", "Yes, done.", 5),
+            message("b", "ana@example.com", "Launch date", "We slip a week.", 1),
+            message("c", "li@example.com", "Hiring", "Thoughts on the loop?", 2),
+            message("c", "me@example.com", "RE: Fwd: Hiring", "Who owns it?", 3),
+        ]);
+        let agenda = draft_meeting_agenda(&threads, &me);
+        assert_eq!(agenda.items, ["Hiring", "Launch date"]);
+        assert_eq!(agenda.markdown, "Agenda:\n- Hiring\n- Launch date\n");
+        assert!(agenda.provenance.is_none());
+    }
+
+    #[test]
+    fn strips_reply_prefixes() {
+        assert_eq!(agenda_subject("Re: FW: re:Q3 plan"), "Q3 plan");
+        assert_eq!(agenda_subject("Re:"), "(no subject)");
+        assert_eq!(agenda_subject("Resourcing"), "Resourcing");
+    }
+
+    #[test]
+    fn empty_agenda_renders_nothing() {
+        assert_eq!(render_agenda(&[]), "");
+    }
+}
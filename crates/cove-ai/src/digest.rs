@@ -0,0 +1,306 @@
+//! Batch summarization of unread mail into one digest.
+//!
+//! Threads are summarized concurrently but paced to a requests-per-minute
+//! budget, and the run stops queueing work once an estimated prompt size
+//! cap is reached, so a large backlog cannot run up a cloud bill. Threads
+//! whose stored summary already covers their newest message are reused.
+
+use crate::{AiError, AiService};
+use cove_core::{AiMode, CloudAiProvider, InboxDigest, MailMessage, ThreadDigestEntry};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{Mutex, Semaphore};
+use tokio::task::JoinSet;
+use tokio::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Characters of each message body sent for summarization.
+const BODY_CHARS: usize = 500;
+
+#[derive(Debug, Clone)]
+pub struct DigestLimits {
+    /// Summaries in flight at once.
+    pub max_concurrency: usize,
+    /// Requests started per minute; 0 for no pacing.
+    pub requests_per_minute: u32,
+    /// Threads summarized per run; the newest win.
+    pub max_threads: usize,
+    /// Prompt characters sent per run, a stand-in for token cost.
+    pub max_prompt_chars: usize,
+}
+
+impl Default for DigestLimits {
+    fn default() -> Self {
+        Self {
+            max_concurrency: 2,
+            requests_per_minute: 20,
+            max_threads: 50,
+            max_prompt_chars: 60_000,
+        }
+    }
+}
+
+/// Unread messages of one thread, oldest first.
+#[derive(Debug, Clone)]
+pub struct DigestThread {
+    pub thread_id: String,
+    pub messages: Vec<MailMessage>,
+}
+
+impl DigestThread {
+    /// Group messages by account and thread, threads in order of first
+    /// appearance and messages oldest first.
+    pub fn group(messages: Vec<MailMessage>) -> Vec<Self> {
+        let mut index: HashMap<(Uuid, String), usize> = HashMap::new();
+        let mut threads: Vec<Self> = Vec::new();
+        for message in messages {
+            let key = (message.account_id, message.thread_id.clone());
+            let slot = *index.entry(key).or_insert_with(|| {
+                threads.push(Self {
+                    thread_id: message.thread_id.clone(),
+                    messages: Vec::new(),
+                });
+                threads.len() - 1
+            });
+            threads[slot].messages.push(message);
+        }
+        for thread in &mut threads {
+            thread.messages.sort_by_key(|message| message.received_at);
+        }
+        threads
+    }
+
+    pub(crate) fn latest(&self) -> Option<&MailMessage> {
+        self.messages.iter().max_by_key(|message| message.received_at)
+    }
+
+    pub(crate) fn prompt_messages(&self) -> Vec<(String, String, String)> {
+        self.messages
+            .iter()
+            .map(|message| {
+                let sender = message
+                    .from
+                    .first()
+                    .map(|from| from.name.clone().unwrap_or_else(|| from.address.clone()))
+                    .unwrap_or_default();
+                let body = message.body_text.as_deref().unwrap_or(&message.preview);
+                (
+                    sender,
+                    message.subject.clone(),
+                    body.chars().take(BODY_CHARS).collect(),
+                )
+            })
+            .collect()
+    }
+
+    fn senders(&self) -> Vec<String> {
+        let mut senders: Vec<String> = Vec::new();
+        for (sender, _, _) in self.prompt_messages() {
+            if !sender.is_empty() && !senders.contains(&sender) {
+                senders.push(sender);
+            }
+        }
+        senders
+    }
+}
+
+/// Spaces request starts evenly across a minute.
+struct Pacer {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl Pacer {
+    fn new(requests_per_minute: u32) -> Self {
+        let interval = match requests_per_minute {
+            0 => Duration::ZERO,
+            rpm => Duration::from_secs(60) / rpm,
+        };
+        Self {
+            interval,
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    async fn wait(&self) {
+        let start = {
+            let mut next = self.next.lock().await;
+            let start = (*next).max(Instant::now());
+            *next = start + self.interval;
+            start
+        };
+        tokio::time::sleep_until(start).await;
+    }
+}
+
+impl AiService {
+    /// Summarize `threads` into a digest. `cached` holds stored entries;
+    /// one is reused while no unread message is newer than it. Store the
+    /// returned entries so the next run can do the same.
+    pub async fn build_inbox_digest(
+        &self,
+        mut threads: Vec<DigestThread>,
+        cached: &[ThreadDigestEntry],
+        limits: &DigestLimits,
+        mode: AiMode,
+        cloud_provider: Option<CloudAiProvider>,
+    ) -> InboxDigest {
+        // Threads from accounts whose policy forbids this mode are left
+        // out entirely, cached summaries included.
+        threads.retain(|thread| {
+            thread.latest().is_some_and(|latest| {
+                self.ensure_account_allows(Some(latest.account_id), &mode)
+                    .is_ok()
+            })
+        });
+        threads.sort_by_key(|thread| std::cmp::Reverse(thread.latest().map(|m| m.received_at)));
+        let cached: HashMap<(Uuid, &str), &ThreadDigestEntry> = cached
+            .iter()
+            .map(|entry| ((entry.account_id, entry.thread_id.as_str()), entry))
+            .collect();
+
+        let mut entries = Vec::new();
+        let mut skipped = Vec::new();
+        let mut to_summarize = Vec::new();
+        let mut prompt_chars = 0_usize;
+        for thread in threads {
+            let Some(latest) = thread.latest() else {
+                continue;
+            };
+            if let Some(entry) = cached.get(&(latest.account_id, thread.thread_id.as_str())) {
+                if entry.latest_received_at >= latest.received_at {
+                    let mut entry = (*entry).clone();
+                    entry.unread_count = thread.messages.len() as u32;
+                    entries.push(entry);
+                    continue;
+                }
+            }
+            let size: usize = thread
+                .prompt_messages()
+                .iter()
+                .map(|(sender, subject, body)| sender.len() + subject.len() + body.len())
+                .sum();
+            if to_summarize.len() >= limits.max_threads
+                || prompt_chars + size > limits.max_prompt_chars
+            {
+                skipped.push(latest.subject.clone());
+                continue;
+            }
+            prompt_chars += size;
+            to_summarize.push(thread);
+        }
+        let reused = entries.len();
+
+        let semaphore = Arc::new(Semaphore::new(limits.max_concurrency.max(1)));
+        let pacer = Arc::new(Pacer::new(limits.requests_per_minute));
+        let mut jobs = JoinSet::new();
+        for thread in to_summarize {
+            let service = self.clone();
+            let semaphore = semaphore.clone();
+            let pacer = pacer.clone();
+            let mode = mode.clone();
+            let cloud_provider = cloud_provider.clone();
+            jobs.spawn(async move {
+                let result = match (semaphore.acquire_owned().await, thread.latest()) {
+                    (Ok(_permit), Some(latest)) => {
+                        pacer.wait().await;
+                        service
+                            .summarize_thread(
+                                latest.account_id,
+                                &thread.prompt_messages(),
+                                mode,
+                                cloud_provider,
+                            )
+                            .await
+                    }
+                    (Ok(_), None) => Err(AiError::Inference("thread has no messages".to_string())),
+                    (Err(err), _) => Err(AiError::Inference(err.to_string())),
+                };
+                (thread, result)
+            });
+        }
+
+        let mut summarized = 0;
+        let mut failed = Vec::new();
+        let now = Utc::now();
+        while let Some(joined) = jobs.join_next().await {
+            let Ok((thread, result)) = joined else {
+                continue;
+            };
+            let Some(latest) = thread.latest() else {
+                continue;
+            };
+            match result {
+                Ok((response, provenance)) => {
+                    summarized += 1;
+                    entries.push(ThreadDigestEntry {
+                        account_id: latest.account_id,
+                        thread_id: thread.thread_id.clone(),
+                        subject: latest.subject.clone(),
+                        senders: thread.senders(),
+                        unread_count: thread.messages.len() as u32,
+                        summary: response.output.trim().to_string(),
+                        latest_received_at: latest.received_at,
+                        provenance,
+                        created_at: now,
+                    });
+                }
+                Err(err) => failed.push((latest.subject.clone(), err.to_string())),
+            }
+        }
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.latest_received_at));
+
+        let mut digest = InboxDigest {
+            generated_at: now,
+            entries,
+            summarized,
+            reused,
+            skipped,
+            failed,
+            markdown: String::new(),
+        };
+        digest.markdown = render_digest(&digest);
+        digest
+    }
+}
+
+/// The digest as a Markdown document.
+pub fn render_digest(digest: &InboxDigest) -> String {
+    let unread: u32 = digest.entries.iter().map(|entry| entry.unread_count).sum();
+    let mut out = format!(
+        "# Unread mail digest\n\n_{}: {} thread(s), {} unread message(s)._\n",
+        format_time(digest.generated_at),
+        digest.entries.len(),
+        unread
+    );
+    for entry in &digest.entries {
+        out.push_str(&format!("\n## {}\n\n", entry.subject.trim()));
+        if !entry.senders.is_empty() {
+            out.push_str(&format!(
+                "From {} · {} unread\n\n",
+                entry.senders.join(", "),
+                entry.unread_count
+            ));
+        }
+        out.push_str(entry.summary.trim());
+        out.push('\n');
+    }
+    if !digest.skipped.is_empty() {
+        out.push_str(&format!(
+            "\n---\n\nNot summarized (limit reached): {}\n",
+            digest.skipped.join("; ")
+        ));
+    }
+    if !digest.failed.is_empty() {
+        out.push_str("\nFailed:\n");
+        for (subject, err) in &digest.failed {
+            out.push_str(&format!("- {subject}: {err}\n"));
+        }
+    }
+    out
+}
+
+fn format_time(at: DateTime<Utc>) -> String {
+    at.format("%Y-%m-%d %H:%M UTC").to_string()
+}
@@ -16,4 +16,6 @@ pub enum AiError {
     Inference(String),
     #[error("cloud ai feature not allowed: {0}")]
     CloudOptInRequired(String),
+    #[error("blocked by account ai policy: {0}")]
+    AccountPolicy(String),
 }
@@ -1,5 +1,13 @@
+mod agenda;
+mod digest;
 mod error;
+mod redaction;
 mod service;
 
+pub use agenda::{
+    draft_meeting_agenda, render_agenda, AGENDA_LOOKBACK_DAYS, AGENDA_SCAN_LIMIT,
+};
+pub use digest::{render_digest, DigestLimits, DigestThread};
 pub use error::AiError;
+pub use redaction::{RedactionMap, RedactionPolicy, Redactor};
 pub use service::{AiRuntimeConfig, AiService, CloudProviderRuntime, LocalRuntime};
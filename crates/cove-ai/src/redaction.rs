@@ -0,0 +1,164 @@
+//! Redaction pass for cloud prompts: personal identifiers are swapped for
+//! stable placeholders before the request leaves the device and swapped
+//! back into the model output afterwards.
+
+use crate::AiError;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionPolicy {
+    pub emails: bool,
+    pub phone_numbers: bool,
+    pub numeric_ids: bool,
+    /// Extra user-supplied regular expressions, redacted as `[REDACTED_n]`.
+    pub custom_patterns: Vec<String>,
+}
+
+impl Default for RedactionPolicy {
+    fn default() -> Self {
+        Self {
+            emails: true,
+            phone_numbers: true,
+            numeric_ids: true,
+            custom_patterns: Vec::new(),
+        }
+    }
+}
+
+pub struct Redactor {
+    rules: Vec<(&'static str, Regex)>,
+}
+
+/// Placeholder -> original value pairs produced by one `Redactor::redact` call.
+#[derive(Debug, Default)]
+pub struct RedactionMap {
+    entries: Vec<(String, String)>,
+}
+
+impl Redactor {
+    pub fn new(policy: &RedactionPolicy) -> Result<Self, AiError> {
+        let compile = |pattern: &str| {
+            Regex::new(pattern)
+                .map_err(|err| AiError::Config(format!("invalid redaction pattern `{pattern}`: {err}")))
+        };
+
+        // Order matters: emails before phone numbers/ids so digits inside an
+        // address are not split out first.
+        let mut rules = Vec::new();
+        if policy.emails {
+            rules.push(("EMAIL", compile(r"[A-Za-z0-9._%+\-]+@[A-Za-z0-9.\-]+\.[A-Za-z]{2,}")?));
+        }
+        for pattern in &policy.custom_patterns {
+            if !pattern.trim().is_empty() {
+                rules.push(("REDACTED", compile(pattern)?));
+            }
+        }
+        if policy.phone_numbers {
+            rules.push((
+                "PHONE",
+                compile(r"\+?\(?\d{1,4}\)?(?:[ .\-]\(?\d{2,4}\)?){1,3}[ .\-]\d{3,4}\b")?,
+            ));
+        }
+        if policy.numeric_ids {
+            rules.push(("ID", compile(r"\b\d{5,}\b")?));
+        }
+
+        Ok(Self { rules })
+    }
+
+    pub fn redact(&self, text: &str) -> (String, RedactionMap) {
+        let mut map = RedactionMap::default();
+        let mut output = text.to_string();
+
+        for (label, regex) in &self.rules {
+            let mut counter = map
+                .entries
+                .iter()
+                .filter(|(placeholder, _)| placeholder.starts_with(&format!("[{label}_")))
+                .count();
+
+            output = regex
+                .replace_all(&output, |caps: &regex::Captures<'_>| {
+                    let value = &caps[0];
+                    if let Some((placeholder, _)) =
+                        map.entries.iter().find(|(_, original)| original == value)
+                    {
+                        return placeholder.clone();
+                    }
+                    counter += 1;
+                    let placeholder = format!("[{label}_{counter}]");
+                    map.entries.push((placeholder.clone(), value.to_string()));
+                    placeholder
+                })
+                .into_owned();
+        }
+
+        (output, map)
+    }
+}
+
+impl RedactionMap {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Put the original values back wherever the model echoed a placeholder.
+    pub fn restore(&self, text: &str) -> String {
+        // Longest placeholders first so `[ID_1]` never clobbers `[ID_10]`.
+        let mut entries = self.entries.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(placeholder, _)| std::cmp::Reverse(placeholder.len()));
+
+        entries
+            .into_iter()
+            .fold(text.to_string(), |acc, (placeholder, original)| {
+                acc.replace(placeholder.as_str(), original)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_and_restores_identifiers() {
+        let redactor = Redactor::new(&RedactionPolicy::default()).unwrap();
+        let (redacted, map) = redactor.redact(
+            "Ping jane.doe@example.com or call +1 415-555-0134 about invoice 20931847. \
+             cc jane.doe@example.com",
+        );
+
+        assert!(!redacted.contains("jane.doe@example.com"));
+        assert!(!redacted.contains("555-0134"));
+        assert!(!redacted.contains("20931847"));
+        assert_eq!(redacted.matches("[EMAIL_1]").count(), 2);
+        assert_eq!(map.len(), 3);
+
+        let restored = map.restore("Reply to [EMAIL_1] regarding [ID_1].");
+        assert_eq!(restored, "Reply to jane.doe@example.com regarding 20931847.");
+    }
+
+    #[test]
+    fn custom_patterns_and_invalid_regex() {
+        let policy = RedactionPolicy {
+            emails: false,
+            phone_numbers: false,
+            numeric_ids: false,
+            custom_patterns: vec![r"ACME-\w+".to_string()],
+        };
+        let (redacted, map) = Redactor::new(&policy).unwrap().redact("Project ACME-Falcon ships");
+        assert_eq!(redacted, "Project [REDACTED_1] ships");
+        assert_eq!(map.restore(&redacted), "Project ACME-Falcon ships");
+
+        let invalid = RedactionPolicy {
+            custom_patterns: vec!["(".to_string()],
+            ..RedactionPolicy::default()
+        };
+        assert!(Redactor::new(&invalid).is_err());
+    }
+}
@@ -1,10 +1,12 @@
+use crate::redaction::{RedactionPolicy, Redactor};
 use crate::AiError;
-use cove_core::{AiMode, AiResponse, CloudAiProvider, DataProvenance};
+use cove_core::{AccountAiPolicy, AiMode, AiResponse, CloudAiProvider, DataProvenance};
 use cove_security::{SecretKey, SecretStore};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
 use tokio::process::Command;
+use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LocalRuntime {
@@ -28,6 +30,12 @@ pub struct AiRuntimeConfig {
     pub cloud_enabled: bool,
     pub cloud_feature_opt_in: BTreeSet<String>,
     pub cloud: BTreeMap<CloudAiProvider, CloudProviderRuntime>,
+    /// When set, cloud prompts are redacted with this policy.
+    #[serde(default)]
+    pub cloud_redaction: Option<RedactionPolicy>,
+    /// Per-account limits on AI use; accounts not listed allow cloud AI.
+    #[serde(default)]
+    pub account_policies: BTreeMap<Uuid, AccountAiPolicy>,
 }
 
 impl Default for AiRuntimeConfig {
@@ -101,6 +109,8 @@ impl Default for AiRuntimeConfig {
             cloud_enabled: false,
             cloud_feature_opt_in: BTreeSet::new(),
             cloud,
+            cloud_redaction: None,
+            account_policies: BTreeMap::new(),
         }
     }
 }
@@ -127,6 +137,7 @@ impl AiService {
 
     pub async fn summarize_email(
         &self,
+        account_id: Uuid,
         subject: &str,
         body: &str,
         mode: AiMode,
@@ -136,13 +147,14 @@ impl AiService {
         let prompt =
             format!("Summarize this email in 4 bullet points. Subject: {subject}\nBody:\n{body}");
 
-        self.run_feature(feature, &prompt, mode, cloud_provider)
+        self.run_feature(Some(account_id), feature, &prompt, mode, cloud_provider)
             .await
     }
 
     /// Summarize an entire email thread (multiple messages).
     pub async fn summarize_thread(
         &self,
+        account_id: Uuid,
         messages: &[(String, String, String)], // (sender, subject, body_snippet)
         mode: AiMode,
         cloud_provider: Option<CloudAiProvider>,
@@ -153,12 +165,13 @@ impl AiService {
             let snippet: String = body.chars().take(500).collect();
             prompt.push_str(&format!("Message {}: From: {sender}, Subject: {subject}\n{snippet}\n\n", i + 1));
         }
-        self.run_feature(feature, &prompt, mode, cloud_provider).await
+        self.run_feature(Some(account_id), feature, &prompt, mode, cloud_provider).await
     }
 
     /// Suggest a draft reply to the latest message.
     pub async fn draft_reply_suggestion(
         &self,
+        account_id: Uuid,
         sender: &str,
         subject: &str,
         body: &str,
@@ -171,7 +184,7 @@ impl AiService {
             "Draft a brief, professional reply to this email. Only output the reply body.\n\
              From: {sender}\nSubject: {subject}\n\n{snippet}"
         );
-        self.run_feature(feature, &prompt, mode, cloud_provider).await
+        self.run_feature(Some(account_id), feature, &prompt, mode, cloud_provider).await
     }
 
     pub async fn generate_message(
@@ -193,12 +206,13 @@ impl AiService {
              User Prompt: {prompt}"
         );
 
-        self.run_feature(feature, &system_prompt, mode, cloud_provider)
+        self.run_feature(None, feature, &system_prompt, mode, cloud_provider)
             .await
     }
 
     pub async fn suggest_reply(
         &self,
+        account_id: Uuid,
         subject: &str,
         body: &str,
         mode: AiMode,
@@ -209,12 +223,13 @@ impl AiService {
             "Draft a concise, polite reply. Never send automatically. Subject: {subject}\nBody:\n{body}"
         );
 
-        self.run_feature(feature, &prompt, mode, cloud_provider)
+        self.run_feature(Some(account_id), feature, &prompt, mode, cloud_provider)
             .await
     }
 
     pub async fn extract_action_items(
         &self,
+        account_id: Uuid,
         body: &str,
         mode: AiMode,
         cloud_provider: Option<CloudAiProvider>,
@@ -224,7 +239,7 @@ impl AiService {
             format!("Extract action items from this email as one short line each:\n{body}");
 
         let (response, provenance) = self
-            .run_feature(feature, &prompt, mode, cloud_provider)
+            .run_feature(Some(account_id), feature, &prompt, mode, cloud_provider)
             .await?;
         let lines = response
             .output
@@ -236,6 +251,50 @@ impl AiService {
         Ok((lines, provenance))
     }
 
+    /// Second opinion for borderline reply-needed candidates.
+    pub async fn needs_reply(
+        &self,
+        account_id: Uuid,
+        sender: &str,
+        subject: &str,
+        body: &str,
+        mode: AiMode,
+        cloud_provider: Option<CloudAiProvider>,
+    ) -> Result<(bool, DataProvenance), AiError> {
+        let feature = "reply_detection";
+        let prompt = format!(
+            "Does this email expect a personal reply from the recipient? Answer only YES or NO.\nFrom: {sender}\nSubject: {subject}\n\n{body}"
+        );
+
+        let (response, provenance) = self
+            .run_feature(Some(account_id), feature, &prompt, mode, cloud_provider)
+            .await?;
+        let verdict = response
+            .output
+            .trim_start()
+            .to_ascii_uppercase()
+            .starts_with("YES");
+
+        Ok((verdict, provenance))
+    }
+
+    /// Turn a plain-text daily agenda into a short friendly morning briefing.
+    /// The agenda is not tied to one account, so callers must leave out
+    /// content from accounts whose policy forbids `mode`.
+    pub async fn agenda_narrative(
+        &self,
+        agenda: &str,
+        mode: AiMode,
+        cloud_provider: Option<CloudAiProvider>,
+    ) -> Result<(AiResponse, DataProvenance), AiError> {
+        let feature = "daily_agenda";
+        let prompt = format!(
+            "Write a brief morning briefing (3-4 sentences) from this agenda. \
+             Mention the most time-sensitive items first. Do not invent items.\n\n{agenda}"
+        );
+        self.run_feature(None, feature, &prompt, mode, cloud_provider).await
+    }
+
     pub fn importance_score(&self, subject: &str, body: &str) -> u8 {
         let text = format!("{subject} {body}").to_ascii_lowercase();
         let high = [
@@ -300,13 +359,18 @@ impl AiService {
         }
     }
 
-    async fn run_feature(
+    /// Run `feature` for content owned by `account_id`, after checking the
+    /// account's policy. `None` is content not tied to an account, such as
+    /// a prompt the user wrote; mail content always names its account.
+    pub(crate) async fn run_feature(
         &self,
+        account_id: Option<Uuid>,
         feature: &str,
         prompt: &str,
         mode: AiMode,
         cloud_provider: Option<CloudAiProvider>,
     ) -> Result<(AiResponse, DataProvenance), AiError> {
+        self.ensure_account_allows(account_id, &mode)?;
         match mode {
             AiMode::Local => {
                 let output = self.run_local(prompt).await?;
@@ -330,7 +394,23 @@ impl AiService {
                 self.ensure_cloud_allowed(feature)?;
                 let provider = cloud_provider
                     .ok_or_else(|| AiError::Config("cloud provider is required".to_string()))?;
-                let output = self.run_cloud(prompt, provider.clone()).await?;
+                let (output, reason) = match &self.config.cloud_redaction {
+                    Some(policy) => {
+                        let (redacted, map) = Redactor::new(policy)?.redact(prompt);
+                        let output = self.run_cloud(&redacted, provider.clone()).await?;
+                        (
+                            map.restore(&output),
+                            format!(
+                                "User opted in for cloud inference ({} value(s) redacted)",
+                                map.len()
+                            ),
+                        )
+                    }
+                    None => (
+                        self.run_cloud(prompt, provider.clone()).await?,
+                        "User opted in for cloud inference".to_string(),
+                    ),
+                };
 
                 Ok((
                     AiResponse {
@@ -344,13 +424,41 @@ impl AiService {
                         feature: feature.to_string(),
                         mode: AiMode::Cloud,
                         destination: format!("{:?} API", provider),
-                        reason: "User opted in for cloud inference".to_string(),
+                        reason,
                     },
                 ))
             }
         }
     }
 
+    pub fn account_policy(&self, account_id: Uuid) -> AccountAiPolicy {
+        self.config
+            .account_policies
+            .get(&account_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Whether content owned by `account_id` may be processed in `mode`.
+    pub fn ensure_account_allows(
+        &self,
+        account_id: Option<Uuid>,
+        mode: &AiMode,
+    ) -> Result<(), AiError> {
+        let Some(account_id) = account_id else {
+            return Ok(());
+        };
+        match (self.account_policy(account_id), mode) {
+            (AccountAiPolicy::Disabled, _) => Err(AiError::AccountPolicy(
+                "AI is disabled for this account".to_string(),
+            )),
+            (AccountAiPolicy::LocalOnly, AiMode::Cloud) => Err(AiError::AccountPolicy(
+                "this account only allows local AI".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
     fn ensure_cloud_allowed(&self, feature: &str) -> Result<(), AiError> {
         if !self.config.cloud_enabled {
             return Err(AiError::CloudOptInRequired(
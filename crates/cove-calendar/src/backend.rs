@@ -1,8 +1,8 @@
+use crate::tz::{local_to_utc, parse_ics_datetime, resolve_tzid, IcsTimeZones};
 use crate::CalendarError;
-use cove_core::{Account, CalendarAlarm, CalendarEvent};
+use cove_core::{Account, CalendarAlarm, CalendarEvent, HttpFailure, HttpFailureKind};
 use async_trait::async_trait;
-use chrono::{DateTime, Duration, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Utc};
-use chrono_tz::Tz;
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
 use regex::Regex;
 use reqwest::Method;
 use serde::{Deserialize, Serialize};
@@ -15,6 +15,18 @@ pub struct CalendarSettings {
     pub calendar_id: String,
 }
 
+/// Changes since the last sync token.
+#[derive(Debug, Default)]
+pub struct CalendarDelta {
+    /// New or modified events.
+    pub events: Vec<CalendarEvent>,
+    /// Remote ids deleted on the server.
+    pub removed: Vec<String>,
+    /// Token to pass to the next [`CalendarBackend::sync_changes`] call;
+    /// `None` when the backend has no incremental sync.
+    pub delta_token: Option<String>,
+}
+
 #[async_trait]
 pub trait CalendarBackend: Send + Sync {
     async fn sync_range(
@@ -25,12 +37,31 @@ pub trait CalendarBackend: Send + Sync {
         to: DateTime<Utc>,
     ) -> Result<Vec<CalendarEvent>, CalendarError>;
 
+    /// Incremental sync. Backends without server-side change tracking fall
+    /// back to a full range sync and never return a token.
+    async fn sync_changes(
+        &self,
+        account: &Account,
+        settings: &CalendarSettings,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        delta_token: Option<&str>,
+    ) -> Result<CalendarDelta, CalendarError> {
+        let _ = delta_token;
+        Ok(CalendarDelta {
+            events: self.sync_range(account, settings, from, to).await?,
+            ..CalendarDelta::default()
+        })
+    }
+
+    /// Push an event and return it as the server stored it (remote id,
+    /// online meeting link).
     async fn create_or_update_event(
         &self,
         account: &Account,
         settings: &CalendarSettings,
         event: &CalendarEvent,
-    ) -> Result<(), CalendarError>;
+    ) -> Result<CalendarEvent, CalendarError>;
 }
 
 #[derive(Debug, Default)]
@@ -90,10 +121,7 @@ impl CalendarBackend for CalDavBackend {
 
         let response = request.send().await?;
         if !response.status().is_success() {
-            return Err(CalendarError::Data(format!(
-                "CalDAV sync failed with status {}",
-                response.status()
-            )));
+            return Err(http_status_error("CalDAV sync", response.status()));
         }
 
         let body = response.text().await?;
@@ -109,7 +137,7 @@ impl CalendarBackend for CalDavBackend {
         _account: &Account,
         settings: &CalendarSettings,
         event: &CalendarEvent,
-    ) -> Result<(), CalendarError> {
+    ) -> Result<CalendarEvent, CalendarError> {
         let mut endpoint = settings.endpoint.trim_end_matches('/').to_string();
         endpoint.push('/');
         endpoint.push_str(&format!("{}.ics", event.remote_id));
@@ -127,13 +155,10 @@ impl CalendarBackend for CalDavBackend {
 
         let response = request.send().await?;
         if !response.status().is_success() {
-            return Err(CalendarError::Data(format!(
-                "CalDAV event upsert failed with status {}",
-                response.status()
-            )));
+            return Err(http_status_error("CalDAV event upsert", response.status()));
         }
 
-        Ok(())
+        Ok(event.clone())
     }
 }
 
@@ -221,10 +246,7 @@ impl CalendarBackend for GoogleCalendarBackend {
             .await?;
 
         if !response.status().is_success() {
-            return Err(CalendarError::Data(format!(
-                "Google Calendar sync failed with status {}",
-                response.status()
-            )));
+            return Err(http_status_error("Google Calendar sync", response.status()));
         }
 
         let payload: GoogleCalendarEventsResponse = response.json().await?;
@@ -286,6 +308,7 @@ impl CalendarBackend for GoogleCalendarBackend {
                     message: Some("Upcoming event".to_string()),
                 }],
                 rsvp_status: cove_core::RsvpStatus::NeedsAction,
+                online_meeting_url: None,
                 updated_at: raw
                     .updated
                     .as_deref()
@@ -302,17 +325,15 @@ impl CalendarBackend for GoogleCalendarBackend {
         _account: &Account,
         settings: &CalendarSettings,
         event: &CalendarEvent,
-    ) -> Result<(), CalendarError> {
+    ) -> Result<CalendarEvent, CalendarError> {
         let token = settings
             .access_token
             .as_ref()
             .ok_or_else(|| CalendarError::Data("missing Google access token".to_string()))?;
 
         let calendar_id = settings.calendar_id.replace('/', "%2F");
-        let endpoint = format!(
-            "https://www.googleapis.com/calendar/v3/calendars/{calendar_id}/events/{}",
-            event.remote_id
-        );
+        let events_url =
+            format!("https://www.googleapis.com/calendar/v3/calendars/{calendar_id}/events");
 
         let body = serde_json::json!({
             "summary": event.title,
@@ -332,22 +353,26 @@ impl CalendarBackend for GoogleCalendarBackend {
                 .collect::<Vec<_>>(),
         });
 
-        let response = self
-            .http
-            .patch(endpoint)
-            .bearer_auth(token)
-            .json(&body)
-            .send()
-            .await?;
+        // New events have no remote id yet; Google assigns one on insert.
+        let request = if event.remote_id.is_empty() {
+            self.http.post(events_url)
+        } else {
+            self.http.patch(format!("{events_url}/{}", event.remote_id))
+        };
+        let response = request.bearer_auth(token).json(&body).send().await?;
 
         if !response.status().is_success() {
-            return Err(CalendarError::Data(format!(
-                "Google Calendar upsert failed with status {}",
-                response.status()
-            )));
+            return Err(http_status_error("Google Calendar upsert", response.status()));
         }
 
-        Ok(())
+        let mut remote = event.clone();
+        if remote.remote_id.is_empty() {
+            let created: GoogleCalendarEvent = response.json().await?;
+            remote.remote_id = created.id.ok_or_else(|| {
+                CalendarError::Parse("Google Calendar insert returned no event id".to_string())
+            })?;
+        }
+        Ok(remote)
     }
 }
 
@@ -367,6 +392,10 @@ impl MicrosoftGraphCalendarBackend {
 #[derive(Debug, Deserialize)]
 struct GraphCalendarResponse {
     value: Option<Vec<GraphCalendarEvent>>,
+    #[serde(rename = "@odata.nextLink")]
+    next_link: Option<String>,
+    #[serde(rename = "@odata.deltaLink")]
+    delta_link: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -385,6 +414,17 @@ struct GraphCalendarEvent {
     #[serde(rename = "lastModifiedDateTime")]
     last_modified: Option<String>,
     recurrence: Option<serde_json::Value>,
+    #[serde(rename = "onlineMeeting")]
+    online_meeting: Option<GraphOnlineMeeting>,
+    /// Present on delta results for events deleted since the last token.
+    #[serde(rename = "@removed")]
+    removed: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphOnlineMeeting {
+    #[serde(rename = "joinUrl")]
+    join_url: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -447,74 +487,97 @@ impl CalendarBackend for MicrosoftGraphCalendarBackend {
             .await?;
 
         if !response.status().is_success() {
-            return Err(CalendarError::Data(format!(
-                "Graph calendar sync failed with status {}",
-                response.status()
-            )));
+            return Err(http_status_error("Graph calendar sync", response.status()));
         }
 
         let payload: GraphCalendarResponse = response.json().await?;
-        let mut events = Vec::new();
+        payload
+            .value
+            .unwrap_or_default()
+            .into_iter()
+            .map(|raw| graph_event_to_calendar_event(account, settings, raw))
+            .collect()
+    }
 
-        for raw in payload.value.unwrap_or_default() {
-            let starts_at = raw
-                .start
-                .as_ref()
-                .and_then(parse_graph_datetime)
-                .ok_or_else(|| CalendarError::Parse("Graph event missing start".to_string()))?;
-            let ends_at = raw
-                .end
-                .as_ref()
-                .and_then(parse_graph_datetime)
-                .ok_or_else(|| CalendarError::Parse("Graph event missing end".to_string()))?;
+    /// Graph `calendarView/delta`: the first call (no token) walks every page
+    /// of the window; later calls resume from the stored delta link and only
+    /// return changes.
+    async fn sync_changes(
+        &self,
+        account: &Account,
+        settings: &CalendarSettings,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        delta_token: Option<&str>,
+    ) -> Result<CalendarDelta, CalendarError> {
+        let token = settings
+            .access_token
+            .as_ref()
+            .ok_or_else(|| CalendarError::Data("missing Graph access token".to_string()))?;
 
-            let attendees = raw
-                .attendees
-                .unwrap_or_default()
-                .into_iter()
-                .filter_map(|attendee| attendee.email_address.and_then(|email| email.address))
-                .collect::<Vec<_>>();
+        let mut request = match delta_token {
+            Some(link) => self.http.get(link),
+            None => self
+                .http
+                .get(format!(
+                    "https://graph.microsoft.com/v1.0/me/calendars/{}/calendarView/delta",
+                    settings.calendar_id
+                ))
+                .query(&[
+                    ("startDateTime", from.to_rfc3339()),
+                    ("endDateTime", to.to_rfc3339()),
+                ]),
+        };
 
-            events.push(CalendarEvent {
-                id: Uuid::new_v4(),
-                account_id: account.id,
-                calendar_id: settings.calendar_id.clone(),
-                remote_id: raw.id.unwrap_or_else(|| Uuid::new_v4().to_string()),
-                title: raw.subject.unwrap_or_else(|| "Untitled event".to_string()),
-                description: raw.body_preview,
-                location: raw.location.and_then(|value| value.display_name),
-                timezone: raw.start.and_then(|start| start.time_zone),
-                starts_at,
-                ends_at,
-                all_day: raw.is_all_day.unwrap_or(false),
-                recurrence_rule: raw.recurrence.map(|value| value.to_string()),
-                attendees,
-                organizer: raw
-                    .organizer
-                    .and_then(|org| org.email_address)
-                    .and_then(|addr| addr.address),
-                alarms: vec![CalendarAlarm {
-                    minutes_before: 10,
-                    message: Some("Upcoming event".to_string()),
-                }],
-                rsvp_status: cove_core::RsvpStatus::NeedsAction,
-                updated_at: raw
-                    .last_modified
-                    .as_deref()
-                    .and_then(parse_rfc3339_to_utc)
-                    .unwrap_or_else(Utc::now),
-            });
+        let mut delta = CalendarDelta::default();
+        loop {
+            let response = request
+                .bearer_auth(token)
+                .header("Prefer", "odata.maxpagesize=100")
+                .send()
+                .await?;
+
+            // An expired or invalidated token means starting over.
+            if response.status() == reqwest::StatusCode::GONE && delta_token.is_some() {
+                return self.sync_changes(account, settings, from, to, None).await;
+            }
+            if !response.status().is_success() {
+                return Err(http_status_error("Graph calendar delta sync", response.status()));
+            }
+
+            let payload: GraphCalendarResponse = response.json().await?;
+            for raw in payload.value.unwrap_or_default() {
+                if raw.removed.is_some() {
+                    if let Some(id) = raw.id {
+                        delta.removed.push(id);
+                    }
+                    continue;
+                }
+                delta
+                    .events
+                    .push(graph_event_to_calendar_event(account, settings, raw)?);
+            }
+
+            match (payload.next_link, payload.delta_link) {
+                (Some(next), _) => request = self.http.get(next),
+                (None, delta_link) => {
+                    delta.delta_token = delta_link;
+                    break;
+                }
+            }
         }
 
-        Ok(events)
+        Ok(delta)
     }
 
+    /// New events with attendees are created as Teams meetings so the
+    /// invitation carries a join link.
     async fn create_or_update_event(
         &self,
-        _account: &Account,
+        account: &Account,
         settings: &CalendarSettings,
         event: &CalendarEvent,
-    ) -> Result<(), CalendarError> {
+    ) -> Result<CalendarEvent, CalendarError> {
         let token = settings
             .access_token
             .as_ref()
@@ -532,7 +595,7 @@ impl CalendarBackend for MicrosoftGraphCalendarBackend {
             )
         };
 
-        let payload = serde_json::json!({
+        let mut payload = serde_json::json!({
             "subject": event.title,
             "body": {
                 "contentType": "text",
@@ -559,6 +622,10 @@ impl CalendarBackend for MicrosoftGraphCalendarBackend {
                 .collect::<Vec<_>>(),
             "isAllDay": event.all_day
         });
+        if event.remote_id.is_empty() && !event.attendees.is_empty() {
+            payload["isOnlineMeeting"] = serde_json::Value::Bool(true);
+            payload["onlineMeetingProvider"] = serde_json::json!("teamsForBusiness");
+        }
 
         let response = if event.remote_id.is_empty() {
             self.http
@@ -577,13 +644,15 @@ impl CalendarBackend for MicrosoftGraphCalendarBackend {
         };
 
         if !response.status().is_success() {
-            return Err(CalendarError::Data(format!(
-                "Graph event upsert failed with status {}",
-                response.status()
-            )));
+            return Err(http_status_error("Graph event upsert", response.status()));
         }
 
-        Ok(())
+        let stored: GraphCalendarEvent = response.json().await?;
+        let mut remote = graph_event_to_calendar_event(account, settings, stored)?;
+        remote.id = event.id;
+        remote.alarms = event.alarms.clone();
+        remote.rsvp_status = event.rsvp_status.clone();
+        Ok(remote)
     }
 }
 
@@ -612,6 +681,10 @@ fn parse_caldav_calendar_data(
 fn parse_ical_events(account_id: Uuid, calendar_id: &str, ics_payload: &str) -> Vec<CalendarEvent> {
     let mut events = Vec::new();
     let lines = unfold_ical_lines(ics_payload);
+    let zones = IcsTimeZones::from_ics(ics_payload);
+    let parse_datetime = |property: &str, value: &str| {
+        parse_ics_datetime(value, property_tzid(property).as_deref(), &zones)
+    };
 
     let mut in_event = false;
     let mut uid: Option<String> = None;
@@ -683,6 +756,7 @@ fn parse_ical_events(account_id: Uuid, calendar_id: &str, ics_payload: &str) ->
                         message: Some("Upcoming event".to_string()),
                     }],
                     rsvp_status: cove_core::RsvpStatus::NeedsAction,
+                    online_meeting_url: None,
                     updated_at: updated_at.unwrap_or_else(Utc::now),
                 });
             }
@@ -736,17 +810,17 @@ fn parse_ical_events(account_id: Uuid, calendar_id: &str, ics_payload: &str) ->
         }
 
         if property_upper.starts_with("DTSTART") {
-            starts_at = parse_ical_datetime_with_property(property, value);
+            starts_at = parse_datetime(property, value);
             all_day = property_has_value_date(property)
                 || (value.len() == 8 && value.chars().all(|ch| ch.is_ascii_digit()));
             if let Some(tzid) = property_tzid(property) {
-                timezone = Some(tzid);
+                timezone = Some(zones.zone_name(&tzid));
             }
             continue;
         }
 
         if property_upper.starts_with("DTEND") {
-            ends_at = parse_ical_datetime_with_property(property, value);
+            ends_at = parse_datetime(property, value);
             continue;
         }
 
@@ -774,7 +848,7 @@ fn parse_ical_events(account_id: Uuid, calendar_id: &str, ics_payload: &str) ->
         }
 
         if property_upper.starts_with("LAST-MODIFIED") || property_upper.starts_with("DTSTAMP") {
-            if let Some(parsed) = parse_ical_datetime_with_property(property, value) {
+            if let Some(parsed) = parse_datetime(property, value) {
                 updated_at = Some(parsed);
             }
         }
@@ -784,6 +858,13 @@ fn parse_ical_events(account_id: Uuid, calendar_id: &str, ics_payload: &str) ->
 }
 
 fn render_single_event_ics(event: &CalendarEvent) -> String {
+    render_event_ics(event, None)
+}
+
+/// Render one event. With an iTIP `method` the object is a scheduling
+/// message for attendees; without one it is the copy stored on the server,
+/// marked `SCHEDULE-AGENT=CLIENT` because invitations go out by email.
+pub(crate) fn render_event_ics(event: &CalendarEvent, method: Option<&str>) -> String {
     let uid = if event.remote_id.trim().is_empty() {
         Uuid::new_v4().to_string()
     } else {
@@ -791,6 +872,9 @@ fn render_single_event_ics(event: &CalendarEvent) -> String {
     };
 
     let mut out = String::from("BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//Cove Mail//EN\r\n");
+    if let Some(method) = method {
+        out.push_str(&format!("METHOD:{method}\r\n"));
+    }
     out.push_str("BEGIN:VEVENT\r\n");
     out.push_str(&format!("UID:{}\r\n", escape_ical_text(&uid)));
     out.push_str(&format!(
@@ -844,17 +928,80 @@ fn render_single_event_ics(event: &CalendarEvent) -> String {
             escape_ical_text(organizer)
         ));
     }
+    let attendee_params = if method.is_some() {
+        ";ROLE=REQ-PARTICIPANT;PARTSTAT=NEEDS-ACTION;RSVP=TRUE"
+    } else {
+        ";SCHEDULE-AGENT=CLIENT"
+    };
     for attendee in &event.attendees {
         out.push_str(&format!(
-            "ATTENDEE:mailto:{}\r\n",
+            "ATTENDEE{attendee_params}:mailto:{}\r\n",
             escape_ical_text(attendee)
         ));
     }
+    if method.is_some() {
+        out.push_str("SEQUENCE:0\r\nSTATUS:CONFIRMED\r\n");
+    }
 
     out.push_str("END:VEVENT\r\nEND:VCALENDAR\r\n");
     out
 }
 
+fn graph_event_to_calendar_event(
+    account: &Account,
+    settings: &CalendarSettings,
+    raw: GraphCalendarEvent,
+) -> Result<CalendarEvent, CalendarError> {
+    let starts_at = raw
+        .start
+        .as_ref()
+        .and_then(parse_graph_datetime)
+        .ok_or_else(|| CalendarError::Parse("Graph event missing start".to_string()))?;
+    let ends_at = raw
+        .end
+        .as_ref()
+        .and_then(parse_graph_datetime)
+        .ok_or_else(|| CalendarError::Parse("Graph event missing end".to_string()))?;
+
+    let attendees = raw
+        .attendees
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|attendee| attendee.email_address.and_then(|email| email.address))
+        .collect::<Vec<_>>();
+
+    Ok(CalendarEvent {
+        id: Uuid::new_v4(),
+        account_id: account.id,
+        calendar_id: settings.calendar_id.clone(),
+        remote_id: raw.id.unwrap_or_else(|| Uuid::new_v4().to_string()),
+        title: raw.subject.unwrap_or_else(|| "Untitled event".to_string()),
+        description: raw.body_preview,
+        location: raw.location.and_then(|value| value.display_name),
+        timezone: raw.start.and_then(|start| start.time_zone),
+        starts_at,
+        ends_at,
+        all_day: raw.is_all_day.unwrap_or(false),
+        recurrence_rule: raw.recurrence.map(|value| value.to_string()),
+        attendees,
+        organizer: raw
+            .organizer
+            .and_then(|org| org.email_address)
+            .and_then(|addr| addr.address),
+        alarms: vec![CalendarAlarm {
+            minutes_before: 10,
+            message: Some("Upcoming event".to_string()),
+        }],
+        rsvp_status: cove_core::RsvpStatus::NeedsAction,
+        online_meeting_url: raw.online_meeting.and_then(|meeting| meeting.join_url),
+        updated_at: raw
+            .last_modified
+            .as_deref()
+            .and_then(parse_rfc3339_to_utc)
+            .unwrap_or_else(Utc::now),
+    })
+}
+
 fn parse_graph_datetime(value: &GraphDateTime) -> Option<DateTime<Utc>> {
     let raw = value.date_time.as_deref()?;
     if let Some(parsed) = parse_rfc3339_to_utc(raw) {
@@ -865,20 +1012,15 @@ fn parse_graph_datetime(value: &GraphDateTime) -> Option<DateTime<Utc>> {
         .or_else(|_| NaiveDateTime::parse_from_str(raw, "%Y-%m-%dT%H:%M:%S"))
         .ok()?;
 
-    if let Some(zone_name) = value.time_zone.as_deref() {
-        if let Ok(zone) = zone_name.parse::<Tz>() {
-            return match zone.from_local_datetime(&naive) {
-                LocalResult::Single(datetime) => Some(datetime.with_timezone(&Utc)),
-                LocalResult::Ambiguous(first, _) => Some(first.with_timezone(&Utc)),
-                LocalResult::None => Some(Utc.from_utc_datetime(&naive)),
-            };
-        }
+    // Graph names zones the Windows way ("Pacific Standard Time") unless
+    // asked otherwise; anything unrecognised is its default, UTC.
+    match value.time_zone.as_deref().and_then(resolve_tzid) {
+        Some(zone) => Some(local_to_utc(&zone, naive)),
+        None => Some(Utc.from_utc_datetime(&naive)),
     }
-
-    Some(Utc.from_utc_datetime(&naive))
 }
 
-fn unfold_ical_lines(payload: &str) -> Vec<String> {
+pub(crate) fn unfold_ical_lines(payload: &str) -> Vec<String> {
     let normalized = payload.replace("\r\n", "\n").replace('\r', "\n");
     let mut unfolded: Vec<String> = Vec::new();
     for raw_line in normalized.lines() {
@@ -919,34 +1061,16 @@ fn property_has_value_date(property: &str) -> bool {
     false
 }
 
-fn parse_ical_datetime_with_property(property: &str, value: &str) -> Option<DateTime<Utc>> {
-    if let Some(parsed) = parse_ical_like(value) {
-        return Some(parsed);
-    }
-
-    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y%m%d") {
-        let midnight = date.and_hms_opt(0, 0, 0)?;
-        return Some(Utc.from_utc_datetime(&midnight));
-    }
-
-    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
-        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M"))
-        .ok()?;
-
-    if let Some(zone_name) = property_tzid(property) {
-        if let Ok(zone) = zone_name.parse::<Tz>() {
-            return match zone.from_local_datetime(&naive) {
-                LocalResult::Single(datetime) => Some(datetime.with_timezone(&Utc)),
-                LocalResult::Ambiguous(first, _) => Some(first.with_timezone(&Utc)),
-                LocalResult::None => Some(Utc.from_utc_datetime(&naive)),
-            };
-        }
-    }
-
-    Some(Utc.from_utc_datetime(&naive))
+/// Parse a date-time property outside a full calendar, so only zones known
+/// by name resolve; see [`IcsTimeZones`] for files with `VTIMEZONE` blocks.
+pub(crate) fn parse_ical_datetime_with_property(
+    property: &str,
+    value: &str,
+) -> Option<DateTime<Utc>> {
+    parse_ics_datetime(value, property_tzid(property).as_deref(), &IcsTimeZones::default())
 }
 
-fn parse_ical_mail_address(value: &str) -> Option<String> {
+pub(crate) fn parse_ical_mail_address(value: &str) -> Option<String> {
     let lowered = value.to_ascii_lowercase();
     if lowered.starts_with("mailto:") {
         return Some(unescape_ical_text(&value[7..]));
@@ -1013,13 +1137,10 @@ fn parse_rfc3339_to_utc(raw: &str) -> Option<DateTime<Utc>> {
         .map(|value| value.with_timezone(&Utc))
 }
 
-#[allow(dead_code)]
-fn parse_ical_like(raw: &str) -> Option<DateTime<Utc>> {
-    if let Ok(value) = DateTime::parse_from_rfc3339(raw) {
-        return Some(value.with_timezone(&Utc));
-    }
-    if let Ok(value) = NaiveDateTime::parse_from_str(raw, "%Y%m%dT%H%M%SZ") {
-        return Some(Utc.from_utc_datetime(&value));
+fn http_status_error(what: &str, status: reqwest::StatusCode) -> CalendarError {
+    let failure = HttpFailure::from_status(what, status.as_u16());
+    match failure.kind {
+        HttpFailureKind::Unauthorized => CalendarError::Auth(failure.message),
+        HttpFailureKind::Rejected => CalendarError::Data(failure.message),
     }
-    None
 }
@@ -8,8 +8,19 @@ pub enum CalendarError {
     Http(#[from] reqwest::Error),
     #[error("parse error: {0}")]
     Parse(String),
+    /// The provider rejected the credentials or access token.
+    #[error("authentication failed: {0}")]
+    Auth(String),
     #[error("invalid data: {0}")]
     Data(String),
     #[error("unimplemented: {0}")]
     Unimplemented(String),
 }
+
+impl CalendarError {
+    /// Whether the provider was unreachable rather than refusing the
+    /// request.
+    pub fn is_network(&self) -> bool {
+        matches!(self, Self::Http(err) if err.is_connect() || err.is_timeout())
+    }
+}
@@ -0,0 +1,143 @@
+//! iTIP scheduling messages (RFC 5546) carried over email (iMIP, RFC 6047).
+//!
+//! Google and Microsoft send invitations from their own servers. For CalDAV
+//! accounts the organizer's client does it: a `METHOD:REQUEST` object goes
+//! out with the invitation email and attendees answer with `METHOD:REPLY`.
+
+use crate::backend::{
+    parse_ical_datetime_with_property, parse_ical_mail_address, render_event_ics,
+    unfold_ical_lines,
+};
+use cove_core::{CalendarEvent, RsvpStatus};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// MIME type for the calendar part of an invitation email.
+pub const IMIP_REQUEST_MIME: &str = "text/calendar; method=REQUEST; charset=UTF-8";
+
+/// An invitation email ready to hand to the mail backend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImipInvitation {
+    pub recipients: Vec<String>,
+    pub subject: String,
+    pub body_text: String,
+    /// `METHOD:REQUEST` calendar object for the `text/calendar` part.
+    pub ics: String,
+}
+
+/// An attendee's answer parsed from a `METHOD:REPLY` calendar object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItipReply {
+    pub uid: String,
+    pub attendee: String,
+    pub status: RsvpStatus,
+    pub replied_at: DateTime<Utc>,
+}
+
+/// Build the invitation for `event`, or `None` when there is nobody to
+/// invite or no organizer to reply to.
+pub fn invitation_for(event: &CalendarEvent) -> Option<ImipInvitation> {
+    let organizer = event.organizer.as_deref()?;
+    let recipients: Vec<String> = event
+        .attendees
+        .iter()
+        .filter(|attendee| !attendee.eq_ignore_ascii_case(organizer))
+        .cloned()
+        .collect();
+    if recipients.is_empty() {
+        return None;
+    }
+
+    let when = if event.all_day {
+        event.starts_at.format("%A, %B %-d, %Y").to_string()
+    } else {
+        format!(
+            "{} – {} UTC",
+            event.starts_at.format("%A, %B %-d, %Y %H:%M"),
+            event.ends_at.format("%H:%M")
+        )
+    };
+    let mut body_text = format!(
+        "{organizer} has invited you to \"{}\".\n\nWhen: {when}\n",
+        event.title
+    );
+    if let Some(location) = event.location.as_deref() {
+        body_text.push_str(&format!("Where: {location}\n"));
+    }
+    body_text.push_str(&format!("Attendees: {}\n", event.attendees.join(", ")));
+    if let Some(description) = event.description.as_deref() {
+        body_text.push('\n');
+        body_text.push_str(description);
+        body_text.push('\n');
+    }
+
+    Some(ImipInvitation {
+        recipients,
+        subject: format!("Invitation: {} @ {when}", event.title),
+        body_text,
+        ics: render_event_ics(event, Some("REQUEST")),
+    })
+}
+
+/// Parse a `METHOD:REPLY` object. Anything else (requests, cancellations,
+/// delegations) yields `None`.
+pub fn parse_reply(ics: &str) -> Option<ItipReply> {
+    let mut is_reply = false;
+    let mut in_event = false;
+    let mut uid = None;
+    let mut attendee = None;
+    let mut replied_at = None;
+
+    for line in unfold_ical_lines(ics) {
+        let trimmed = line.trim();
+        let Some((property, value)) = trimmed.split_once(':') else {
+            continue;
+        };
+        let property_upper = property.to_ascii_uppercase();
+        let name = property_upper.split(';').next().unwrap_or_default();
+
+        match name {
+            "METHOD" if !in_event => is_reply = value.trim().eq_ignore_ascii_case("REPLY"),
+            "BEGIN" if value.trim().eq_ignore_ascii_case("VEVENT") => in_event = true,
+            "END" if value.trim().eq_ignore_ascii_case("VEVENT") => break,
+            "UID" if in_event => uid = Some(value.trim().to_string()),
+            "ATTENDEE" if in_event && attendee.is_none() => {
+                let status = property_param(&property_upper, "PARTSTAT").and_then(partstat_to_rsvp);
+                attendee = parse_ical_mail_address(value.trim()).zip(status);
+            }
+            "DTSTAMP" if in_event => {
+                replied_at = parse_ical_datetime_with_property(property, value.trim());
+            }
+            _ => {}
+        }
+    }
+
+    if !is_reply {
+        return None;
+    }
+    let (attendee, status) = attendee?;
+    Some(ItipReply {
+        uid: uid.filter(|uid| !uid.is_empty())?,
+        attendee: attendee.to_lowercase(),
+        status,
+        replied_at: replied_at.unwrap_or_else(Utc::now),
+    })
+}
+
+fn property_param<'a>(property: &'a str, param: &str) -> Option<&'a str> {
+    property.split(';').skip(1).find_map(|part| {
+        let (key, value) = part.split_once('=')?;
+        key.eq_ignore_ascii_case(param)
+            .then(|| value.trim_matches('"'))
+    })
+}
+
+fn partstat_to_rsvp(partstat: &str) -> Option<RsvpStatus> {
+    match partstat {
+        "ACCEPTED" => Some(RsvpStatus::Accepted),
+        "DECLINED" => Some(RsvpStatus::Declined),
+        "TENTATIVE" => Some(RsvpStatus::Tentative),
+        "NEEDS-ACTION" => Some(RsvpStatus::NeedsAction),
+        _ => None,
+    }
+}
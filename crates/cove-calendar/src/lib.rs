@@ -1,10 +1,15 @@
 mod backend;
 mod error;
+mod itip;
+mod poll;
 mod service;
+mod tz;
 
 pub use backend::{
-    CalDavBackend, CalendarBackend, CalendarSettings, GoogleCalendarBackend,
+    CalDavBackend, CalendarBackend, CalendarDelta, CalendarSettings, GoogleCalendarBackend,
     MicrosoftGraphCalendarBackend,
 };
 pub use error::CalendarError;
-pub use service::CalendarService;
+pub use itip::{ImipInvitation, ItipReply, IMIP_REQUEST_MIME};
+pub use poll::{format_option, parse_poll_reply, tally, PollEmail, PollOptionTally};
+pub use service::{CalendarService, MeetingRequest, PollRequest};
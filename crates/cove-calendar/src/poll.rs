@@ -0,0 +1,185 @@
+//! "Propose times" scheduling polls carried over plain email.
+//!
+//! The poll email numbers each candidate slot and asks participants to
+//! reply with the numbers that suit them, so answering needs no link or
+//! web service. Replies are matched by a token in the subject line and the
+//! first unquoted lines of the reply are read for option numbers.
+
+use cove_core::{PollOption, PollVote, SchedulingPoll};
+use serde::{Deserialize, Serialize};
+
+/// A poll email ready to hand to the mail backend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PollEmail {
+    pub recipients: Vec<String>,
+    pub subject: String,
+    pub body_text: String,
+}
+
+/// Who picked one option.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PollOptionTally {
+    pub index: usize,
+    pub option: PollOption,
+    pub available: Vec<String>,
+}
+
+/// Subject marker that ties replies to a poll, e.g. `[poll:7f3a9c21]`.
+pub fn poll_marker(token: &str) -> String {
+    format!("[poll:{token}]")
+}
+
+/// The invitation asking participants to pick times.
+pub fn poll_email(poll: &SchedulingPoll, organizer: &str) -> PollEmail {
+    let mut body_text = format!(
+        "{organizer} is looking for a time for \"{}\".\n\n",
+        poll.title
+    );
+    if let Some(description) = poll.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        body_text.push_str(description.trim());
+        body_text.push_str("\n\n");
+    }
+    body_text.push_str("Proposed times:\n");
+    for (index, option) in poll.options.iter().enumerate() {
+        body_text.push_str(&format!("  {}) {}\n", index + 1, format_option(option)));
+    }
+    body_text.push_str(
+        "\nReply with the numbers of every time that works for you, for example \"1, 3\". \
+         Reply \"none\" if none of them work.\n",
+    );
+
+    PollEmail {
+        recipients: poll.participants.clone(),
+        subject: format!("Scheduling: {} {}", poll.title, poll_marker(&poll.token)),
+        body_text,
+    }
+}
+
+/// The note telling participants which time was chosen.
+pub fn confirmation_email(poll: &SchedulingPoll, option: &PollOption) -> PollEmail {
+    PollEmail {
+        recipients: poll.participants.clone(),
+        subject: format!("Re: Scheduling: {} {}", poll.title, poll_marker(&poll.token)),
+        body_text: format!(
+            "Thanks for answering. \"{}\" is confirmed for {}.\n\n\
+             A calendar invitation is on its way.\n",
+            poll.title,
+            format_option(option)
+        ),
+    }
+}
+
+pub fn format_option(option: &PollOption) -> String {
+    if option.starts_at.date_naive() == option.ends_at.date_naive() {
+        format!(
+            "{} – {} UTC",
+            option.starts_at.format("%a, %b %-d %Y %H:%M"),
+            option.ends_at.format("%H:%M")
+        )
+    } else {
+        format!(
+            "{} – {} UTC",
+            option.starts_at.format("%a, %b %-d %Y %H:%M"),
+            option.ends_at.format("%a, %b %-d %H:%M")
+        )
+    }
+}
+
+/// Read the options a reply picks, as indexes into a poll with
+/// `option_count` options. Quoted text and everything after the reply
+/// header ("On … wrote:") is ignored. `Some(vec![])` means "none of them";
+/// `None` means the reply did not answer.
+pub fn parse_poll_reply(body: &str, option_count: usize) -> Option<Vec<usize>> {
+    let mut choices = Vec::new();
+    let mut declined = false;
+    for line in body.lines() {
+        let line = line.trim();
+        if line.starts_with('>') {
+            continue;
+        }
+        let lower = line.to_ascii_lowercase();
+        if is_reply_header(&lower) {
+            break;
+        }
+        if matches!(
+            lower.trim_end_matches(['.', '!']),
+            "none" | "none of them" | "none work" | "none of these work" | "no"
+        ) {
+            declined = true;
+        }
+        for word in lower.split(|c: char| !c.is_ascii_digit()) {
+            let Ok(number) = word.parse::<usize>() else {
+                continue;
+            };
+            if (1..=option_count).contains(&number) && !choices.contains(&(number - 1)) {
+                choices.push(number - 1);
+            }
+        }
+    }
+    choices.sort_unstable();
+    if choices.is_empty() && !declined {
+        return None;
+    }
+    Some(choices)
+}
+
+fn is_reply_header(line: &str) -> bool {
+    (line.starts_with("on ") && line.ends_with("wrote:"))
+        || line.starts_with("-----original message")
+        || line.starts_with("from:")
+        || line.starts_with("sent from my")
+}
+
+/// Count availability per option, best supported first; ties keep the
+/// earlier option first.
+pub fn tally(poll: &SchedulingPoll, votes: &[PollVote]) -> Vec<PollOptionTally> {
+    let mut tallies: Vec<PollOptionTally> = poll
+        .options
+        .iter()
+        .enumerate()
+        .map(|(index, option)| PollOptionTally {
+            index,
+            option: *option,
+            available: votes
+                .iter()
+                .filter(|vote| vote.choices.contains(&index))
+                .map(|vote| vote.participant.clone())
+                .collect(),
+        })
+        .collect();
+    tallies.sort_by(|a, b| {
+        b.available
+            .len()
+            .cmp(&a.available.len())
+            .then(a.index.cmp(&b.index))
+    });
+    tallies
+}
+
+/// A short random token for the subject marker.
+pub(crate) fn new_token() -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..8].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_numbers_from_the_top_of_the_reply() {
+        let body = "1 and 3 work for me, thanks!\n\nOn Tue, Oct 20, Ana wrote:\n> 2) Wed\n";
+        assert_eq!(parse_poll_reply(body, 3), Some(vec![0, 2]));
+    }
+
+    #[test]
+    fn ignores_quoted_text_and_out_of_range_numbers() {
+        let body = "> 1) Mon\n> 2) Tue\nOption 2, or 7\n";
+        assert_eq!(parse_poll_reply(body, 3), Some(vec![1]));
+    }
+
+    #[test]
+    fn none_is_an_answer_and_silence_is_not() {
+        assert_eq!(parse_poll_reply("None.\n", 3), Some(vec![]));
+        assert_eq!(parse_poll_reply("Let me check and get back to you.", 3), None);
+    }
+}
@@ -1,14 +1,49 @@
+use crate::itip;
+use crate::poll::{self, PollEmail};
+use crate::tz::{parse_ics_datetime, IcsTimeZones};
 use crate::{
     CalDavBackend, CalendarBackend, CalendarError, CalendarSettings, GoogleCalendarBackend,
-    MicrosoftGraphCalendarBackend,
+    ImipInvitation, MicrosoftGraphCalendarBackend,
+};
+use cove_core::{
+    Account, AttendeeResponse, CalendarAlarm, CalendarEvent, PendingOperation,
+    PendingOperationKind, PendingOperationStatus, PollOption, PollStatus, PollVote, Provider,
+    ReminderTask, ReplayOutcome, SchedulingPoll, TaskTimeBlock,
 };
-use cove_core::{Account, CalendarAlarm, CalendarEvent, Provider};
 use cove_storage::Storage;
-use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
 use std::io::Cursor;
 use std::sync::Arc;
 use uuid::Uuid;
 
+/// Block length used when a task has no estimate.
+const DEFAULT_TIME_BLOCK_MINUTES: u32 = 30;
+/// Title prefix marking the time block of a completed task.
+const COMPLETED_BLOCK_PREFIX: &str = "✓ ";
+/// A fresh delta window extends this far past the requested end, so the
+/// token keeps being reused as "now" moves forward between syncs.
+const DELTA_WINDOW_SLACK_DAYS: i64 = 7;
+
+/// A meeting to create with [`CalendarService::schedule_meeting`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingRequest {
+    pub title: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub attendees: Vec<String>,
+    pub description: Option<String>,
+}
+
+/// A scheduling poll to create with [`CalendarService::create_poll`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PollRequest {
+    pub title: String,
+    pub description: Option<String>,
+    pub options: Vec<PollOption>,
+    pub participants: Vec<String>,
+}
+
 #[derive(Clone)]
 pub struct CalendarService {
     storage: Storage,
@@ -34,108 +69,501 @@ impl CalendarService {
         from: DateTime<Utc>,
         to: DateTime<Utc>,
     ) -> Result<Vec<CalendarEvent>, CalendarError> {
+        // Reuse the stored delta token only while the requested window is
+        // inside the one it was issued for.
+        let stored = self
+            .storage
+            .calendar_delta_token(account.id, &settings.calendar_id)
+            .await?;
+        let (delta_token, window) = match stored {
+            Some((token, start, end)) if start <= from && to <= end => (Some(token), (start, end)),
+            _ => (None, (from, to + Duration::days(DELTA_WINDOW_SLACK_DAYS))),
+        };
+
         let backend = self.backend_for(account);
-        let events = backend.sync_range(account, settings, from, to).await?;
-        for event in &events {
-            self.storage.upsert_calendar_event(event).await?;
+        let delta = backend
+            .sync_changes(account, settings, window.0, window.1, delta_token.as_deref())
+            .await?;
+
+        for remote_id in &delta.removed {
+            self.storage
+                .delete_calendar_event_by_remote(account.id, &settings.calendar_id, remote_id)
+                .await?;
         }
+
+        let mut events = delta.events;
+        for event in &mut events {
+            self.store_synced_event(event).await?;
+        }
+
+        self.storage
+            .set_calendar_delta_token(
+                account.id,
+                &settings.calendar_id,
+                delta.delta_token.as_deref(),
+                window.0,
+                window.1,
+            )
+            .await?;
         Ok(events)
     }
 
-    pub async fn import_ics(
+    /// Store an event locally and push it to the provider. Moving a task's
+    /// time block through here also moves the task's due date. Returns the
+    /// event as the provider stored it (remote id, meeting link). When the
+    /// provider is unreachable the push is queued for replay and the local
+    /// event is returned.
+    pub async fn upsert_event(
         &self,
-        account_id: Uuid,
-        calendar_id: &str,
-        ics_payload: &str,
-    ) -> Result<Vec<CalendarEvent>, CalendarError> {
-        let reader = Cursor::new(ics_payload.as_bytes());
-        let parser = ical::IcalParser::new(reader);
-
-        let mut imported = Vec::new();
-        for calendar in parser {
-            let calendar = calendar.map_err(|err| CalendarError::Parse(err.to_string()))?;
-            for event in calendar.events {
-                let uid = property_value(&event.properties, "UID")
-                    .unwrap_or_else(|| Uuid::new_v4().to_string());
-                let title = property_value(&event.properties, "SUMMARY")
-                    .unwrap_or_else(|| "Untitled Event".to_string());
-                let description = property_value(&event.properties, "DESCRIPTION");
-                let location = property_value(&event.properties, "LOCATION");
-                let recurrence_rule = property_value(&event.properties, "RRULE");
-
-                let starts_at_raw = property_value(&event.properties, "DTSTART")
-                    .ok_or_else(|| CalendarError::Data("VEVENT missing DTSTART".to_string()))?;
-                let ends_at_raw = property_value(&event.properties, "DTEND")
-                    .ok_or_else(|| CalendarError::Data("VEVENT missing DTEND".to_string()))?;
-
-                let starts_at = parse_ical_datetime(&starts_at_raw)?;
-                let ends_at = parse_ical_datetime(&ends_at_raw)?;
-
-                let imported_event = CalendarEvent {
-                    id: Uuid::new_v4(),
-                    account_id,
-                    calendar_id: calendar_id.to_string(),
-                    remote_id: uid,
-                    title,
-                    description,
-                    location,
-                    timezone: None,
-                    starts_at,
-                    ends_at,
-                    all_day: is_all_day(&starts_at_raw),
-                    recurrence_rule,
-                    attendees: vec![],
-                    organizer: property_value(&event.properties, "ORGANIZER"),
-                    alarms: vec![CalendarAlarm {
-                        minutes_before: 10,
-                        message: Some("Upcoming event".to_string()),
-                    }],
-                    rsvp_status: cove_core::RsvpStatus::NeedsAction,
-                    updated_at: Utc::now(),
-                };
-
-                self.storage.upsert_calendar_event(&imported_event).await?;
-                imported.push(imported_event);
+        account: &Account,
+        settings: &CalendarSettings,
+        event: &CalendarEvent,
+    ) -> Result<CalendarEvent, CalendarError> {
+        self.storage.upsert_calendar_event(event).await?;
+        self.storage.sync_task_due_from_block(event).await?;
+        let remote = match self
+            .backend_for(account)
+            .create_or_update_event(account, settings, event)
+            .await
+        {
+            Ok(remote) => remote,
+            Err(err) if err.is_network() => {
+                self.queue_event_push(event, &err).await?;
+                return Ok(event.clone());
             }
-        }
-
-        Ok(imported)
+            Err(err) => return Err(err),
+        };
+        self.store_pushed_event(event, &remote).await?;
+        Ok(remote)
     }
 
-    pub fn export_ics(&self, events: &[CalendarEvent]) -> String {
-        let mut output =
-            String::from("BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//Cove Mail//EN\r\n");
+    async fn queue_event_push(
+        &self,
+        event: &CalendarEvent,
+        err: &CalendarError,
+    ) -> Result<(), CalendarError> {
+        let now = Utc::now();
+        let op = PendingOperation {
+            id: Uuid::new_v4(),
+            account_id: event.account_id,
+            kind: PendingOperationKind::UpsertEvent,
+            status: PendingOperationStatus::Pending,
+            target_id: Some(event.id),
+            summary: format!("Update event \"{}\"", event.title),
+            payload_json: serde_json::to_value(event)
+                .map_err(|err| CalendarError::Data(err.to_string()))?,
+            attempt_count: 1,
+            last_error: Some(err.to_string()),
+            created_at: now,
+            updated_at: now,
+        };
+        self.storage.enqueue_pending_operation(&op).await?;
+        Ok(())
+    }
 
-        for event in events {
-            output.push_str("BEGIN:VEVENT\r\n");
-            output.push_str(&format!("UID:{}\r\n", event.remote_id));
-            output.push_str(&format!(
-                "DTSTAMP:{}\r\n",
-                Utc::now().format("%Y%m%dT%H%M%SZ")
-            ));
-            output.push_str(&format!(
-                "DTSTART:{}\r\n",
-                event.starts_at.format("%Y%m%dT%H%M%SZ")
+    /// Push a queued event edit. The edit is dropped as a conflict when the
+    /// event was deleted since, or when a sync brought in a newer version
+    /// from the provider.
+    pub async fn replay_event(
+        &self,
+        account: &Account,
+        settings: &CalendarSettings,
+        op: &PendingOperation,
+    ) -> Result<ReplayOutcome, CalendarError> {
+        let event: CalendarEvent = serde_json::from_value(op.payload_json.clone())
+            .map_err(|err| CalendarError::Data(format!("queued event: {err}")))?;
+        let Some(current) = self.storage.get_calendar_event(event.id).await? else {
+            return Ok(ReplayOutcome::Conflict(
+                "The event was deleted before the change could be sent.".to_string(),
             ));
-            output.push_str(&format!(
-                "DTEND:{}\r\n",
-                event.ends_at.format("%Y%m%dT%H%M%SZ")
+        };
+        if current.updated_at > event.updated_at {
+            return Ok(ReplayOutcome::Conflict(
+                "The event was changed on the server while you were offline.".to_string(),
             ));
-            output.push_str(&format!("SUMMARY:{}\r\n", escape_ical(&event.title)));
-            if let Some(desc) = &event.description {
-                output.push_str(&format!("DESCRIPTION:{}\r\n", escape_ical(desc)));
+        }
+        let remote = self
+            .backend_for(account)
+            .create_or_update_event(account, settings, &event)
+            .await?;
+        self.store_pushed_event(&event, &remote).await?;
+        Ok(ReplayOutcome::Applied)
+    }
+
+    async fn store_pushed_event(
+        &self,
+        event: &CalendarEvent,
+        remote: &CalendarEvent,
+    ) -> Result<(), CalendarError> {
+        if remote.remote_id != event.remote_id
+            || remote.online_meeting_url != event.online_meeting_url
+        {
+            self.storage.upsert_calendar_event(remote).await?;
+        }
+        Ok(())
+    }
+
+    /// Create a meeting with attendees. On Microsoft 365 accounts the event
+    /// is created as a Teams meeting and the join link is stored with it.
+    pub async fn schedule_meeting(
+        &self,
+        account: &Account,
+        settings: &CalendarSettings,
+        meeting: MeetingRequest,
+    ) -> Result<CalendarEvent, CalendarError> {
+        if meeting.ends_at <= meeting.starts_at {
+            return Err(CalendarError::Data("meeting must end after it starts".to_string()));
+        }
+
+        let event = CalendarEvent {
+            id: Uuid::new_v4(),
+            account_id: account.id,
+            calendar_id: settings.calendar_id.clone(),
+            remote_id: new_remote_id(account),
+            title: meeting.title,
+            description: meeting.description,
+            location: None,
+            timezone: None,
+            starts_at: meeting.starts_at,
+            ends_at: meeting.ends_at,
+            all_day: false,
+            recurrence_rule: None,
+            attendees: meeting.attendees,
+            organizer: Some(account.email_address.clone()),
+            alarms: vec![CalendarAlarm {
+                minutes_before: 10,
+                message: Some("Upcoming meeting".to_string()),
+            }],
+            rsvp_status: cove_core::RsvpStatus::Accepted,
+            online_meeting_url: None,
+            updated_at: Utc::now(),
+        };
+
+        self.upsert_event(account, settings, &event).await
+    }
+
+    /// The iMIP invitation to email for a new event. Only CalDAV accounts
+    /// need one; Google and Microsoft send invitations themselves.
+    pub fn imip_invitation(
+        &self,
+        account: &Account,
+        event: &CalendarEvent,
+    ) -> Option<ImipInvitation> {
+        if !uses_caldav(account) {
+            return None;
+        }
+        itip::invitation_for(event)
+    }
+
+    /// Record attendee replies found in mail received since `since`.
+    /// Returns how many attendee statuses changed.
+    pub async fn apply_imip_replies(
+        &self,
+        account: &Account,
+        since: DateTime<Utc>,
+    ) -> Result<usize, CalendarError> {
+        if !uses_caldav(account) {
+            return Ok(0);
+        }
+
+        let mut applied = 0;
+        for part in self.storage.list_calendar_parts_since(account.id, since).await? {
+            let Some(reply) = std::str::from_utf8(&part).ok().and_then(itip::parse_reply) else {
+                continue;
+            };
+            let Some(event_id) = self
+                .storage
+                .calendar_event_id_by_uid(account.id, &reply.uid)
+                .await?
+            else {
+                continue;
+            };
+
+            let changed = self
+                .storage
+                .record_attendee_response(&AttendeeResponse {
+                    event_id,
+                    email: reply.attendee,
+                    status: reply.status,
+                    updated_at: reply.replied_at,
+                })
+                .await?;
+            if changed {
+                applied += 1;
             }
-            if let Some(location) = &event.location {
-                output.push_str(&format!("LOCATION:{}\r\n", escape_ical(location)));
+        }
+        Ok(applied)
+    }
+
+    /// Store a new poll and build the email that asks participants to
+    /// pick times. Sending it is up to the caller.
+    pub async fn create_poll(
+        &self,
+        account: &Account,
+        request: PollRequest,
+    ) -> Result<(SchedulingPoll, PollEmail), CalendarError> {
+        if request.title.trim().is_empty() {
+            return Err(CalendarError::Data("poll needs a title".to_string()));
+        }
+        if request.options.is_empty() {
+            return Err(CalendarError::Data("poll needs at least one time".to_string()));
+        }
+        if request.options.iter().any(|option| option.ends_at <= option.starts_at) {
+            return Err(CalendarError::Data("each time must end after it starts".to_string()));
+        }
+        let mut participants: Vec<String> = Vec::new();
+        for participant in &request.participants {
+            let participant = participant.trim().to_lowercase();
+            if !participant.is_empty()
+                && participant != account.email_address.to_lowercase()
+                && !participants.contains(&participant)
+            {
+                participants.push(participant);
             }
-            if let Some(rrule) = &event.recurrence_rule {
-                output.push_str(&format!("RRULE:{}\r\n", rrule));
+        }
+        if participants.is_empty() {
+            return Err(CalendarError::Data("poll needs at least one participant".to_string()));
+        }
+
+        let mut options = request.options;
+        options.sort_by_key(|option| option.starts_at);
+        let now = Utc::now();
+        let poll = SchedulingPoll {
+            id: Uuid::new_v4(),
+            account_id: account.id,
+            title: request.title.trim().to_string(),
+            description: request.description.filter(|d| !d.trim().is_empty()),
+            options,
+            participants,
+            token: poll::new_token(),
+            status: PollStatus::Open,
+            confirmed_option: None,
+            event_id: None,
+            created_at: now,
+            updated_at: now,
+        };
+        self.storage.upsert_scheduling_poll(&poll).await?;
+        let email = poll::poll_email(&poll, &account.email_address);
+        Ok((poll, email))
+    }
+
+    /// Read answers from replies to an open poll and return every
+    /// participant's latest vote.
+    pub async fn collect_poll_votes(
+        &self,
+        account: &Account,
+        poll_id: Uuid,
+    ) -> Result<Vec<PollVote>, CalendarError> {
+        let poll = self.poll(poll_id).await?;
+        if poll.status == PollStatus::Open {
+            let organizer = account.email_address.to_lowercase();
+            let replies = self
+                .storage
+                .list_poll_reply_messages(account.id, &poll::poll_marker(&poll.token), poll.created_at)
+                .await?;
+            for message in replies {
+                let Some(sender) = message.from.first().map(|from| from.address.to_lowercase())
+                else {
+                    continue;
+                };
+                if sender == organizer {
+                    continue;
+                }
+                let body = message.body_text.as_deref().unwrap_or(&message.preview);
+                let Some(choices) = poll::parse_poll_reply(body, poll.options.len()) else {
+                    continue;
+                };
+                self.storage
+                    .record_poll_vote(&PollVote {
+                        poll_id,
+                        participant: sender,
+                        choices,
+                        voted_at: message.received_at,
+                    })
+                    .await?;
             }
-            output.push_str("END:VEVENT\r\n");
+        }
+        Ok(self.storage.list_poll_votes(poll_id).await?)
+    }
+
+    /// Settle a poll on one of its options: create the meeting and build
+    /// the confirmation email for participants. Invitations follow the
+    /// usual path (provider-sent, or [`Self::imip_invitation`] on CalDAV).
+    pub async fn confirm_poll(
+        &self,
+        account: &Account,
+        settings: &CalendarSettings,
+        poll_id: Uuid,
+        option_index: usize,
+    ) -> Result<(SchedulingPoll, CalendarEvent, PollEmail), CalendarError> {
+        let mut poll = self.poll(poll_id).await?;
+        if poll.status != PollStatus::Open {
+            return Err(CalendarError::Data("poll is no longer open".to_string()));
+        }
+        let option = *poll
+            .options
+            .get(option_index)
+            .ok_or_else(|| CalendarError::Data("no such poll option".to_string()))?;
+
+        let event = self
+            .schedule_meeting(
+                account,
+                settings,
+                MeetingRequest {
+                    title: poll.title.clone(),
+                    starts_at: option.starts_at,
+                    ends_at: option.ends_at,
+                    attendees: poll.participants.clone(),
+                    description: poll.description.clone(),
+                },
+            )
+            .await?;
+
+        poll.status = PollStatus::Confirmed;
+        poll.confirmed_option = Some(option_index);
+        poll.event_id = Some(event.id);
+        poll.updated_at = Utc::now();
+        self.storage.upsert_scheduling_poll(&poll).await?;
+
+        let email = poll::confirmation_email(&poll, &option);
+        Ok((poll, event, email))
+    }
+
+    pub async fn cancel_poll(&self, poll_id: Uuid) -> Result<SchedulingPoll, CalendarError> {
+        let mut poll = self.poll(poll_id).await?;
+        if poll.status == PollStatus::Open {
+            poll.status = PollStatus::Cancelled;
+            poll.updated_at = Utc::now();
+            self.storage.upsert_scheduling_poll(&poll).await?;
+        }
+        Ok(poll)
+    }
+
+    async fn poll(&self, poll_id: Uuid) -> Result<SchedulingPoll, CalendarError> {
+        self.storage
+            .get_scheduling_poll(poll_id)
+            .await?
+            .ok_or_else(|| CalendarError::Data("poll not found".to_string()))
+    }
+
+    /// Keep the local id of an already-known event so updates land on the
+    /// existing row.
+    async fn store_synced_event(&self, event: &mut CalendarEvent) -> Result<(), CalendarError> {
+        if let Some(existing) = self
+            .storage
+            .calendar_event_id_by_remote(event.account_id, &event.calendar_id, &event.remote_id)
+            .await?
+        {
+            event.id = existing;
+        }
+        self.storage.upsert_calendar_event(event).await?;
+        self.storage.sync_task_due_from_block(event).await?;
+        Ok(())
+    }
+
+    /// Reserve calendar time for a task, sized from its estimate, and link the
+    /// block to the task so completion and moves stay in sync. The event is
+    /// created on the provider first and only linked once it has its remote
+    /// id, so a failed push leaves no dangling link.
+    pub async fn time_block_task(
+        &self,
+        account: &Account,
+        settings: &CalendarSettings,
+        task: &ReminderTask,
+        starts_at: DateTime<Utc>,
+    ) -> Result<CalendarEvent, CalendarError> {
+        let minutes = task
+            .estimate_minutes
+            .filter(|minutes| *minutes > 0)
+            .unwrap_or(DEFAULT_TIME_BLOCK_MINUTES);
+        let now = Utc::now();
+
+        let event = CalendarEvent {
+            id: Uuid::new_v4(),
+            account_id: account.id,
+            calendar_id: settings.calendar_id.clone(),
+            remote_id: new_remote_id(account),
+            title: format!("Focus: {}", task.title),
+            description: task.notes.clone(),
+            location: None,
+            timezone: None,
+            starts_at,
+            ends_at: starts_at + Duration::minutes(i64::from(minutes)),
+            all_day: false,
+            recurrence_rule: None,
+            attendees: vec![],
+            organizer: None,
+            alarms: vec![CalendarAlarm {
+                minutes_before: 5,
+                message: Some(format!("Time block: {}", task.title)),
+            }],
+            rsvp_status: cove_core::RsvpStatus::Accepted,
+            online_meeting_url: None,
+            updated_at: now,
+        };
+
+        let remote = self
+            .backend_for(account)
+            .create_or_update_event(account, settings, &event)
+            .await?;
+        self.storage.upsert_calendar_event(&remote).await?;
+        self.storage
+            .upsert_task_time_block(&TaskTimeBlock {
+                task_id: task.id,
+                account_id: account.id,
+                calendar_id: remote.calendar_id.clone(),
+                event_remote_id: remote.remote_id.clone(),
+                completed: task.completed_at.is_some(),
+                created_at: now,
+            })
+            .await?;
+
+        Ok(remote)
+    }
+
+    /// Mirror a task's completion onto its time block: the block's event gets
+    /// a check-mark title prefix while the task is done, pushed like any
+    /// other event edit. Returns the updated event, or `None` when the task
+    /// has no block or the title already matches.
+    pub async fn sync_time_block_completion(
+        &self,
+        account: &Account,
+        settings: &CalendarSettings,
+        task: &ReminderTask,
+    ) -> Result<Option<CalendarEvent>, CalendarError> {
+        let completed = task.completed_at.is_some();
+        let Some(mut event) = self
+            .storage
+            .set_task_time_block_completed(task.id, completed)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let title = time_block_title(&event.title, completed);
+        if title == event.title {
+            return Ok(None);
+        }
+        event.title = title;
+        event.updated_at = Utc::now();
+        self.upsert_event(account, settings, &event).await.map(Some)
+    }
+
+    pub async fn import_ics(
+        &self,
+        account_id: Uuid,
+        calendar_id: &str,
+        ics_payload: &str,
+    ) -> Result<Vec<CalendarEvent>, CalendarError> {
+        let imported = parse_ics_import(account_id, calendar_id, ics_payload)?;
+        for event in &imported {
+            self.storage.upsert_calendar_event(event).await?;
         }
 
-        output.push_str("END:VCALENDAR\r\n");
-        output
+        Ok(imported)
+    }
+
+    pub fn export_ics(&self, events: &[CalendarEvent]) -> String {
+        render_ics(events)
     }
 
     pub async fn detect_conflicts(
@@ -176,30 +604,180 @@ impl CalendarService {
     }
 }
 
+/// The remote id for an event that has not been pushed yet. Google and
+/// Graph assign ids when the event is created; CalDAV stores the event
+/// under a UID we choose.
+fn new_remote_id(account: &Account) -> String {
+    if uses_caldav(account) {
+        Uuid::new_v4().to_string()
+    } else {
+        String::new()
+    }
+}
+
+/// A time block's title with the completion mark added or removed.
+fn time_block_title(title: &str, completed: bool) -> String {
+    let bare = title.strip_prefix(COMPLETED_BLOCK_PREFIX).unwrap_or(title);
+    if completed {
+        format!("{COMPLETED_BLOCK_PREFIX}{bare}")
+    } else {
+        bare.to_string()
+    }
+}
+
+fn uses_caldav(account: &Account) -> bool {
+    !matches!(
+        account.provider,
+        Provider::Gmail | Provider::Outlook | Provider::Exchange
+    )
+}
+
 fn property_value(properties: &[ical::property::Property], key: &str) -> Option<String> {
+    find_property(properties, key).and_then(|property| property.value.clone())
+}
+
+fn property_param(property: &ical::property::Property, key: &str) -> Option<String> {
+    property
+        .params
+        .as_ref()?
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(key))
+        .and_then(|(_, values)| values.first().cloned())
+}
+
+/// Parse the `VEVENT`s of an ICS file, resolving each `TZID` against the
+/// file's own `VTIMEZONE` blocks before falling back to known zone names.
+fn parse_ics_import(
+    account_id: Uuid,
+    calendar_id: &str,
+    ics_payload: &str,
+) -> Result<Vec<CalendarEvent>, CalendarError> {
+    let zones = IcsTimeZones::from_ics(ics_payload);
+    let reader = Cursor::new(ics_payload.as_bytes());
+    let parser = ical::IcalParser::new(reader);
+
+    let mut imported = Vec::new();
+    for calendar in parser {
+        let calendar = calendar.map_err(|err| CalendarError::Parse(err.to_string()))?;
+        for event in calendar.events {
+            let uid = property_value(&event.properties, "UID")
+                .unwrap_or_else(|| Uuid::new_v4().to_string());
+            let title = property_value(&event.properties, "SUMMARY")
+                .unwrap_or_else(|| "Untitled Event".to_string());
+            let description = property_value(&event.properties, "DESCRIPTION");
+            let location = property_value(&event.properties, "LOCATION");
+            let recurrence_rule = property_value(&event.properties, "RRULE");
+
+            let dtstart = find_property(&event.properties, "DTSTART")
+                .ok_or_else(|| CalendarError::Data("VEVENT missing DTSTART".to_string()))?;
+            let dtend = find_property(&event.properties, "DTEND")
+                .ok_or_else(|| CalendarError::Data("VEVENT missing DTEND".to_string()))?;
+            let start_tzid = property_param(dtstart, "TZID");
+
+            let starts_at = parse_property_datetime(dtstart, &zones)?;
+            let ends_at = parse_property_datetime(dtend, &zones)?;
+            let starts_at_raw = dtstart.value.as_deref().unwrap_or_default();
+            let all_day = property_param(dtstart, "VALUE")
+                .is_some_and(|value| value.eq_ignore_ascii_case("DATE"))
+                || is_all_day(starts_at_raw);
+
+            imported.push(CalendarEvent {
+                id: Uuid::new_v4(),
+                account_id,
+                calendar_id: calendar_id.to_string(),
+                remote_id: uid,
+                title,
+                description,
+                location,
+                timezone: start_tzid.map(|tzid| zones.zone_name(&tzid)),
+                starts_at,
+                ends_at,
+                all_day,
+                recurrence_rule,
+                attendees: vec![],
+                organizer: property_value(&event.properties, "ORGANIZER"),
+                alarms: vec![CalendarAlarm {
+                    minutes_before: 10,
+                    message: Some("Upcoming event".to_string()),
+                }],
+                rsvp_status: cove_core::RsvpStatus::NeedsAction,
+                online_meeting_url: None,
+                updated_at: Utc::now(),
+            });
+        }
+    }
+
+    Ok(imported)
+}
+
+fn find_property<'a>(
+    properties: &'a [ical::property::Property],
+    key: &str,
+) -> Option<&'a ical::property::Property> {
     properties
         .iter()
         .find(|property| property.name.eq_ignore_ascii_case(key))
-        .and_then(|property| property.value.clone())
 }
 
-fn parse_ical_datetime(raw: &str) -> Result<DateTime<Utc>, CalendarError> {
-    if let Ok(value) = DateTime::parse_from_rfc3339(raw) {
-        return Ok(value.with_timezone(&Utc));
-    }
-    if let Ok(value) = NaiveDateTime::parse_from_str(raw, "%Y%m%dT%H%M%SZ") {
-        return Ok(Utc.from_utc_datetime(&value));
-    }
-    if let Ok(value) = NaiveDate::parse_from_str(raw, "%Y%m%d") {
-        let datetime = value
-            .and_hms_opt(0, 0, 0)
-            .ok_or_else(|| CalendarError::Parse(format!("invalid all-day date: {raw}")))?;
-        return Ok(Utc.from_utc_datetime(&datetime));
+fn parse_property_datetime(
+    property: &ical::property::Property,
+    zones: &IcsTimeZones,
+) -> Result<DateTime<Utc>, CalendarError> {
+    let raw = property.value.as_deref().unwrap_or_default();
+    let tzid = property_param(property, "TZID");
+    parse_ics_datetime(raw, tzid.as_deref(), zones)
+        .ok_or_else(|| {
+            CalendarError::Parse(format!("unsupported datetime format in ICS: {raw}"))
+        })
+}
+
+/// Render events as an ICS file. Timed events are written in UTC; all-day
+/// events keep `VALUE=DATE` so they stay all-day when read back.
+fn render_ics(events: &[CalendarEvent]) -> String {
+    let mut output =
+        String::from("BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//Cove Mail//EN\r\n");
+
+    for event in events {
+        output.push_str("BEGIN:VEVENT\r\n");
+        output.push_str(&format!("UID:{}\r\n", event.remote_id));
+        output.push_str(&format!(
+            "DTSTAMP:{}\r\n",
+            Utc::now().format("%Y%m%dT%H%M%SZ")
+        ));
+        if event.all_day {
+            output.push_str(&format!(
+                "DTSTART;VALUE=DATE:{}\r\n",
+                event.starts_at.format("%Y%m%d")
+            ));
+            output.push_str(&format!(
+                "DTEND;VALUE=DATE:{}\r\n",
+                event.ends_at.format("%Y%m%d")
+            ));
+        } else {
+            output.push_str(&format!(
+                "DTSTART:{}\r\n",
+                event.starts_at.format("%Y%m%dT%H%M%SZ")
+            ));
+            output.push_str(&format!(
+                "DTEND:{}\r\n",
+                event.ends_at.format("%Y%m%dT%H%M%SZ")
+            ));
+        }
+        output.push_str(&format!("SUMMARY:{}\r\n", escape_ical(&event.title)));
+        if let Some(desc) = &event.description {
+            output.push_str(&format!("DESCRIPTION:{}\r\n", escape_ical(desc)));
+        }
+        if let Some(location) = &event.location {
+            output.push_str(&format!("LOCATION:{}\r\n", escape_ical(location)));
+        }
+        if let Some(rrule) = &event.recurrence_rule {
+            output.push_str(&format!("RRULE:{}\r\n", rrule));
+        }
+        output.push_str("END:VEVENT\r\n");
     }
 
-    Err(CalendarError::Parse(format!(
-        "unsupported datetime format in ICS: {raw}"
-    )))
+    output.push_str("END:VCALENDAR\r\n");
+    output
 }
 
 fn is_all_day(raw: &str) -> bool {
@@ -213,3 +791,162 @@ fn escape_ical(value: &str) -> String {
         .replace(',', "\\,")
         .replace('\n', "\\n")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, NaiveDate, TimeZone};
+
+    const GOOGLE: &str = "BEGIN:VCALENDAR\r\n\
+PRODID:-//Google Inc//Google Calendar 70.9054//EN\r\n\
+VERSION:2.0\r\n\
+BEGIN:VTIMEZONE\r\n\
+TZID:America/New_York\r\n\
+X-LIC-LOCATION:America/New_York\r\n\
+BEGIN:DAYLIGHT\r\n\
+TZOFFSETFROM:-0500\r\n\
+TZOFFSETTO:-0400\r\n\
+TZNAME:EDT\r\n\
+DTSTART:19700308T020000\r\n\
+RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=2SU\r\n\
+END:DAYLIGHT\r\n\
+BEGIN:STANDARD\r\n\
+TZOFFSETFROM:-0400\r\n\
+TZOFFSETTO:-0500\r\n\
+TZNAME:EST\r\n\
+DTSTART:19701101T020000\r\n\
+RRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=1SU\r\n\
+END:STANDARD\r\n\
+END:VTIMEZONE\r\n\
+BEGIN:VEVENT\r\n\
+DTSTART;TZID=America/New_York:20250310T093000\r\n\
+DTEND;TZID=America/New_York:20250310T103000\r\n\
+UID:4q2v1c9d@google.com\r\n\
+SUMMARY:Standup\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+DTSTART;VALUE=DATE:20250704\r\n\
+DTEND;VALUE=DATE:20250705\r\n\
+UID:holiday@google.com\r\n\
+SUMMARY:Independence Day\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    const OUTLOOK: &str = "BEGIN:VCALENDAR\r\n\
+PRODID:-//Microsoft Corporation//Outlook 16.0 MIMEDIR//EN\r\n\
+VERSION:2.0\r\n\
+BEGIN:VTIMEZONE\r\n\
+TZID:Pacific Standard Time\r\n\
+BEGIN:STANDARD\r\n\
+DTSTART:16011104T020000\r\n\
+RRULE:FREQ=YEARLY;BYDAY=1SU;BYMONTH=11\r\n\
+TZOFFSETFROM:-0700\r\n\
+TZOFFSETTO:-0800\r\n\
+END:STANDARD\r\n\
+BEGIN:DAYLIGHT\r\n\
+DTSTART:16010311T020000\r\n\
+RRULE:FREQ=YEARLY;BYDAY=2SU;BYMONTH=3\r\n\
+TZOFFSETFROM:-0800\r\n\
+TZOFFSETTO:-0700\r\n\
+END:DAYLIGHT\r\n\
+END:VTIMEZONE\r\n\
+BEGIN:VEVENT\r\n\
+UID:040000008200E00074C5B7101A82E008\r\n\
+SUMMARY:Quarterly review\r\n\
+DTSTART;TZID=Pacific Standard Time:20251103T090000\r\n\
+DTEND;TZID=Pacific Standard Time:20251103T100000\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    const APPLE: &str = "BEGIN:VCALENDAR\r\n\
+VERSION:2.0\r\n\
+PRODID:-//Apple Inc.//macOS 14.5//EN\r\n\
+CALSCALE:GREGORIAN\r\n\
+BEGIN:VEVENT\r\n\
+UID:7B2E4A51-0C1D-4E7B-9A0E-2F1C3D4E5F60\r\n\
+DTSTART;TZID=Europe/Berlin:20251026T023000\r\n\
+DTEND;TZID=Europe/Berlin:20251026T033000\r\n\
+SUMMARY:Night shift handover\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:1D2C3B4A-5F6E-7D8C-9B0A-112233445566\r\n\
+DTSTART:20250615T080000\r\n\
+DTEND:20250615T083000\r\n\
+SUMMARY:Morning run\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn import(payload: &str) -> Vec<CalendarEvent> {
+        parse_ics_import(Uuid::nil(), "default", payload).unwrap()
+    }
+
+    fn assert_round_trips(events: &[CalendarEvent]) {
+        let reparsed = import(&render_ics(events));
+        assert_eq!(reparsed.len(), events.len());
+        for (before, after) in events.iter().zip(&reparsed) {
+            assert_eq!(after.remote_id, before.remote_id);
+            assert_eq!(after.title, before.title);
+            assert_eq!(after.starts_at, before.starts_at);
+            assert_eq!(after.ends_at, before.ends_at);
+            assert_eq!(after.all_day, before.all_day);
+        }
+    }
+
+    #[test]
+    fn imports_google_events_across_daylight_saving() {
+        let events = import(GOOGLE);
+        // Clocks went forward on 9 March, so 09:30 is EDT.
+        assert_eq!(events[0].starts_at, utc("2025-03-10T13:30:00Z"));
+        assert_eq!(events[0].ends_at, utc("2025-03-10T14:30:00Z"));
+        assert_eq!(events[0].timezone.as_deref(), Some("America/New_York"));
+        assert!(!events[0].all_day);
+        assert!(events[1].all_day);
+        assert_eq!(events[1].starts_at, utc("2025-07-04T00:00:00Z"));
+        assert_round_trips(&events);
+    }
+
+    #[test]
+    fn imports_outlook_windows_zone_names() {
+        let events = import(OUTLOOK);
+        // Clocks went back on 2 November, so 09:00 is PST.
+        assert_eq!(events[0].starts_at, utc("2025-11-03T17:00:00Z"));
+        assert_eq!(events[0].timezone.as_deref(), Some("America/Los_Angeles"));
+        assert_round_trips(&events);
+    }
+
+    #[test]
+    fn imports_apple_zones_and_floating_times() {
+        let events = import(APPLE);
+        // 02:30 happens twice on 26 October; the first (CEST) one is meant.
+        assert_eq!(events[0].starts_at, utc("2025-10-26T00:30:00Z"));
+        assert_eq!(events[0].timezone.as_deref(), Some("Europe/Berlin"));
+
+        let morning = NaiveDate::from_ymd_opt(2025, 6, 15)
+            .unwrap()
+            .and_hms_opt(8, 0, 0)
+            .unwrap();
+        let floating = Local
+            .from_local_datetime(&morning)
+            .earliest()
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(events[1].starts_at, floating);
+        assert_eq!(events[1].timezone, None);
+        assert_round_trips(&events);
+    }
+
+    #[test]
+    fn completion_mark_is_added_once_and_removed() {
+        let done = time_block_title("Focus: Draft report", true);
+        assert_eq!(done, "✓ Focus: Draft report");
+        assert_eq!(time_block_title(&done, true), done);
+        assert_eq!(time_block_title(&done, false), "Focus: Draft report");
+        assert_eq!(time_block_title("Focus: Draft report", false), "Focus: Draft report");
+    }
+}
@@ -0,0 +1,484 @@
+//! Time zones for ICS date-times.
+//!
+//! A `TZID` names either an IANA zone (Google, Apple), a Windows zone name
+//! (Outlook, Exchange) or a zone only described by the file's own
+//! `VTIMEZONE` block. Times without `Z` or `TZID` are floating and mean the
+//! same wall-clock time wherever the user is (RFC 5545 §3.3.5).
+
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, LocalResult, NaiveDate, NaiveDateTime, Offset,
+    TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+use std::collections::HashMap;
+
+/// Windows zone names used by Outlook and Exchange, with their IANA zone.
+const WINDOWS_ZONES: &[(&str, &str)] = &[
+    ("Dateline Standard Time", "Etc/GMT+12"),
+    ("UTC", "Etc/UTC"),
+    ("Coordinated Universal Time", "Etc/UTC"),
+    ("Hawaiian Standard Time", "Pacific/Honolulu"),
+    ("Alaskan Standard Time", "America/Anchorage"),
+    ("Pacific Standard Time", "America/Los_Angeles"),
+    ("US Mountain Standard Time", "America/Phoenix"),
+    ("Mountain Standard Time", "America/Denver"),
+    ("Central Standard Time", "America/Chicago"),
+    ("Central America Standard Time", "America/Guatemala"),
+    ("Canada Central Standard Time", "America/Regina"),
+    ("Central Standard Time (Mexico)", "America/Mexico_City"),
+    ("Eastern Standard Time", "America/New_York"),
+    ("US Eastern Standard Time", "America/Indiana/Indianapolis"),
+    ("Atlantic Standard Time", "America/Halifax"),
+    ("Newfoundland Standard Time", "America/St_Johns"),
+    ("SA Pacific Standard Time", "America/Bogota"),
+    ("Pacific SA Standard Time", "America/Santiago"),
+    ("E. South America Standard Time", "America/Sao_Paulo"),
+    ("Argentina Standard Time", "America/Argentina/Buenos_Aires"),
+    ("GMT Standard Time", "Europe/London"),
+    ("Greenwich Standard Time", "Atlantic/Reykjavik"),
+    ("W. Europe Standard Time", "Europe/Berlin"),
+    ("Romance Standard Time", "Europe/Paris"),
+    ("Central Europe Standard Time", "Europe/Budapest"),
+    ("Central European Standard Time", "Europe/Warsaw"),
+    ("GTB Standard Time", "Europe/Bucharest"),
+    ("FLE Standard Time", "Europe/Kyiv"),
+    ("E. Europe Standard Time", "Europe/Chisinau"),
+    ("Russian Standard Time", "Europe/Moscow"),
+    ("Turkey Standard Time", "Europe/Istanbul"),
+    ("Israel Standard Time", "Asia/Jerusalem"),
+    ("Egypt Standard Time", "Africa/Cairo"),
+    ("South Africa Standard Time", "Africa/Johannesburg"),
+    ("Arab Standard Time", "Asia/Riyadh"),
+    ("Arabian Standard Time", "Asia/Dubai"),
+    ("Iran Standard Time", "Asia/Tehran"),
+    ("Pakistan Standard Time", "Asia/Karachi"),
+    ("India Standard Time", "Asia/Kolkata"),
+    ("Nepal Standard Time", "Asia/Kathmandu"),
+    ("Bangladesh Standard Time", "Asia/Dhaka"),
+    ("SE Asia Standard Time", "Asia/Bangkok"),
+    ("China Standard Time", "Asia/Shanghai"),
+    ("Singapore Standard Time", "Asia/Singapore"),
+    ("Taipei Standard Time", "Asia/Taipei"),
+    ("Tokyo Standard Time", "Asia/Tokyo"),
+    ("Korea Standard Time", "Asia/Seoul"),
+    ("W. Australia Standard Time", "Australia/Perth"),
+    ("AUS Central Standard Time", "Australia/Darwin"),
+    ("Cen. Australia Standard Time", "Australia/Adelaide"),
+    ("E. Australia Standard Time", "Australia/Brisbane"),
+    ("AUS Eastern Standard Time", "Australia/Sydney"),
+    ("Tasmania Standard Time", "Australia/Hobart"),
+    ("New Zealand Standard Time", "Pacific/Auckland"),
+];
+
+/// Resolve a `TZID` to an IANA zone: the name itself, a prefixed path such
+/// as `/mozilla.org/20050126_1/America/New_York`, or a Windows zone name.
+pub(crate) fn resolve_tzid(tzid: &str) -> Option<Tz> {
+    let name = tzid.trim().trim_matches('"');
+    if let Ok(zone) = name.parse::<Tz>() {
+        return Some(zone);
+    }
+    if let Some((_, iana)) = WINDOWS_ZONES
+        .iter()
+        .find(|(windows, _)| windows.eq_ignore_ascii_case(name))
+    {
+        return iana.parse().ok();
+    }
+    name.match_indices('/')
+        .find_map(|(at, _)| name[at + 1..].parse::<Tz>().ok())
+}
+
+/// One `STANDARD` or `DAYLIGHT` block of a `VTIMEZONE`.
+#[derive(Debug, Clone)]
+struct Observance {
+    /// First onset, in the local time in force before it.
+    start: NaiveDateTime,
+    offset_to: FixedOffset,
+    /// Yearly `BYMONTH` and `BYDAY` (e.g. `2SU`, `-1SU`) of the onset.
+    rule: Option<(u32, i32, Weekday)>,
+}
+
+impl Observance {
+    fn onset_in(&self, year: i32) -> Option<NaiveDateTime> {
+        let onset = match self.rule {
+            Some((month, nth, weekday)) => {
+                nth_weekday(year, month, nth, weekday)?.and_time(self.start.time())
+            }
+            None if self.start.year() == year => self.start,
+            None => return None,
+        };
+        (onset >= self.start).then_some(onset)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum ZoneDef {
+    Named(Tz),
+    Rules(Vec<Observance>),
+}
+
+/// Zones referenced by one ICS payload: its `VTIMEZONE` blocks, falling
+/// back to [`resolve_tzid`] for names the file does not define.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct IcsTimeZones {
+    zones: HashMap<String, ZoneDef>,
+}
+
+impl IcsTimeZones {
+    pub(crate) fn from_ics(payload: &str) -> Self {
+        let mut zones = HashMap::new();
+        let mut tzid: Option<String> = None;
+        let mut location: Option<String> = None;
+        let mut observances: Vec<Observance> = Vec::new();
+        let mut current: Option<(Option<NaiveDateTime>, Option<FixedOffset>, Option<String>)> =
+            None;
+        let mut in_zone = false;
+
+        for line in unfold(payload) {
+            let Some((name, value)) = line.split_once(':') else {
+                continue;
+            };
+            let name = name.split(';').next().unwrap_or(name).to_ascii_uppercase();
+            let value = value.trim();
+            match (name.as_str(), value.to_ascii_uppercase().as_str()) {
+                ("BEGIN", "VTIMEZONE") => {
+                    in_zone = true;
+                    tzid = None;
+                    location = None;
+                    observances.clear();
+                }
+                ("END", "VTIMEZONE") => {
+                    in_zone = false;
+                    let Some(id) = tzid.take() else {
+                        continue;
+                    };
+                    let named = location
+                        .take()
+                        .and_then(|location| resolve_tzid(&location))
+                        .or_else(|| resolve_tzid(&id));
+                    let def = match named {
+                        Some(zone) => ZoneDef::Named(zone),
+                        None if !observances.is_empty() => {
+                            ZoneDef::Rules(std::mem::take(&mut observances))
+                        }
+                        None => continue,
+                    };
+                    zones.insert(id, def);
+                }
+                ("BEGIN", "STANDARD" | "DAYLIGHT") if in_zone => current = Some((None, None, None)),
+                ("END", "STANDARD" | "DAYLIGHT") if in_zone => {
+                    if let Some((Some(start), Some(offset_to), rule)) = current.take() {
+                        observances.push(Observance {
+                            start,
+                            offset_to,
+                            rule: rule.as_deref().and_then(parse_yearly_rule),
+                        });
+                    }
+                }
+                _ if !in_zone => {}
+                _ => match (&mut current, name.as_str()) {
+                    (Some((start, _, _)), "DTSTART") => {
+                        *start = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok();
+                    }
+                    (Some((_, offset, _)), "TZOFFSETTO") => *offset = parse_utc_offset(value),
+                    (Some((_, _, rule)), "RRULE") => *rule = Some(value.to_string()),
+                    (None, "TZID") => tzid = Some(value.to_string()),
+                    (None, "X-LIC-LOCATION") => location = Some(value.to_string()),
+                    _ => {}
+                },
+            }
+        }
+
+        Self { zones }
+    }
+
+    /// IANA name for `tzid` when one is known, otherwise `tzid` itself.
+    pub(crate) fn zone_name(&self, tzid: &str) -> String {
+        match self.zones.get(tzid) {
+            Some(ZoneDef::Named(zone)) => zone.name().to_string(),
+            Some(ZoneDef::Rules(_)) => tzid.to_string(),
+            None => resolve_tzid(tzid)
+                .map(|zone| zone.name().to_string())
+                .unwrap_or_else(|| tzid.to_string()),
+        }
+    }
+
+    /// The instant `local` names in `tzid`. An unknown zone is treated like
+    /// a floating time rather than as UTC.
+    pub(crate) fn to_utc(&self, tzid: Option<&str>, local: NaiveDateTime) -> DateTime<Utc> {
+        let Some(tzid) = tzid else {
+            return local_to_utc(&chrono::Local, local);
+        };
+        match self.zones.get(tzid) {
+            Some(ZoneDef::Named(zone)) => local_to_utc(zone, local),
+            Some(ZoneDef::Rules(observances)) => {
+                let offset = rule_offset(observances, local);
+                Utc.from_utc_datetime(&(local - Duration::seconds(offset.local_minus_utc().into())))
+            }
+            None => match resolve_tzid(tzid) {
+                Some(zone) => local_to_utc(&zone, local),
+                None => local_to_utc(&chrono::Local, local),
+            },
+        }
+    }
+}
+
+/// Parse an ICS date-time value. Dates (`VALUE=DATE`) are midnight UTC,
+/// `Z` values are UTC, and the rest are local to `tzid` or floating.
+pub(crate) fn parse_ics_datetime(
+    value: &str,
+    tzid: Option<&str>,
+    zones: &IcsTimeZones,
+) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(parsed) = DateTime::parse_from_rfc3339(value) {
+        return Some(parsed.with_timezone(&Utc));
+    }
+    if let Ok(parsed) = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%SZ") {
+        return Some(Utc.from_utc_datetime(&parsed));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y%m%d") {
+        return Some(Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0)?));
+    }
+    let local = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M"))
+        .ok()?;
+    Some(zones.to_utc(tzid, local))
+}
+
+/// Map a wall-clock time in `zone` to UTC. A time repeated when clocks go
+/// back is its first occurrence; a time skipped when they go forward uses
+/// the offset from before the gap, as RFC 5545 specifies.
+pub(crate) fn local_to_utc<Z: TimeZone>(zone: &Z, local: NaiveDateTime) -> DateTime<Utc> {
+    match zone.from_local_datetime(&local) {
+        LocalResult::Single(datetime) => datetime.with_timezone(&Utc),
+        LocalResult::Ambiguous(first, _) => first.with_timezone(&Utc),
+        LocalResult::None => {
+            let before = zone
+                .offset_from_utc_datetime(&(local - Duration::days(1)))
+                .fix();
+            Utc.from_utc_datetime(&(local - Duration::seconds(before.local_minus_utc().into())))
+        }
+    }
+}
+
+/// Offset of the observance whose latest onset is at or before `local`.
+fn rule_offset(observances: &[Observance], local: NaiveDateTime) -> FixedOffset {
+    observances
+        .iter()
+        .flat_map(|observance| {
+            [local.year() - 1, local.year()]
+                .into_iter()
+                .filter_map(|year| observance.onset_in(year))
+                .filter(|onset| *onset <= local)
+                .map(|onset| (onset, observance.offset_to))
+        })
+        .max_by_key(|(onset, _)| *onset)
+        .map(|(_, offset)| offset)
+        .or_else(|| observances.first().map(|observance| observance.offset_to))
+        .unwrap_or_else(|| Utc.fix())
+}
+
+/// `FREQ=YEARLY;BYMONTH=3;BYDAY=2SU` as (month, nth, weekday).
+fn parse_yearly_rule(rule: &str) -> Option<(u32, i32, Weekday)> {
+    let mut month = None;
+    let mut by_day = None;
+    for part in rule.split(';') {
+        match part.split_once('=') {
+            Some((key, value)) if key.eq_ignore_ascii_case("BYMONTH") => {
+                month = value.parse::<u32>().ok();
+            }
+            Some((key, value)) if key.eq_ignore_ascii_case("BYDAY") => {
+                let split = value.len().checked_sub(2)?;
+                let nth = match &value[..split] {
+                    "" => 1,
+                    digits => digits.trim_start_matches('+').parse::<i32>().ok()?,
+                };
+                let weekday = match value[split..].to_ascii_uppercase().as_str() {
+                    "MO" => Weekday::Mon,
+                    "TU" => Weekday::Tue,
+                    "WE" => Weekday::Wed,
+                    "TH" => Weekday::Thu,
+                    "FR" => Weekday::Fri,
+                    "SA" => Weekday::Sat,
+                    "SU" => Weekday::Sun,
+                    _ => return None,
+                };
+                by_day = Some((nth, weekday));
+            }
+            _ => {}
+        }
+    }
+    let (nth, weekday) = by_day?;
+    Some((month?, nth, weekday))
+}
+
+/// The `nth` `weekday` of a month; negative counts from the end.
+fn nth_weekday(year: i32, month: u32, nth: i32, weekday: Weekday) -> Option<NaiveDate> {
+    if nth > 0 {
+        let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+        let lead =
+            (7 + weekday.num_days_from_monday() - first.weekday().num_days_from_monday()) % 7;
+        first.checked_add_signed(Duration::days(i64::from(lead) + 7 * i64::from(nth - 1)))
+    } else {
+        let next_month = if month == 12 {
+            NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+        } else {
+            NaiveDate::from_ymd_opt(year, month + 1, 1)?
+        };
+        let last = next_month.pred_opt()?;
+        let lag = (7 + last.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+        last.checked_sub_signed(Duration::days(i64::from(lag) + 7 * i64::from(-nth - 1)))
+    }
+}
+
+/// `+0100`, `-0800` or `+053000`.
+fn parse_utc_offset(value: &str) -> Option<FixedOffset> {
+    let (sign, digits) = match value.as_bytes().first()? {
+        b'+' => (1, &value[1..]),
+        b'-' => (-1, &value[1..]),
+        _ => return None,
+    };
+    if digits.len() < 4 || !digits.chars().all(|ch| ch.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i32 = digits[0..2].parse().ok()?;
+    let minutes: i32 = digits[2..4].parse().ok()?;
+    let seconds: i32 = digits.get(4..6).and_then(|s| s.parse().ok()).unwrap_or(0);
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60 + seconds))
+}
+
+fn unfold(payload: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in payload.lines() {
+        let raw = raw.trim_end_matches('\r');
+        match lines.last_mut() {
+            Some(last) if raw.starts_with(' ') || raw.starts_with('\t') => {
+                last.push_str(&raw[1..]);
+            }
+            _ => lines.push(raw.to_string()),
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").unwrap()
+    }
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        Utc.from_utc_datetime(&local(value))
+    }
+
+    #[test]
+    fn resolves_iana_prefixed_and_windows_names() {
+        assert_eq!(
+            resolve_tzid("Europe/Berlin"),
+            Some(chrono_tz::Europe::Berlin)
+        );
+        assert_eq!(
+            resolve_tzid("/mozilla.org/20050126_1/America/New_York"),
+            Some(chrono_tz::America::New_York)
+        );
+        assert_eq!(
+            resolve_tzid("Pacific Standard Time"),
+            Some(chrono_tz::America::Los_Angeles)
+        );
+        assert_eq!(resolve_tzid("Customized Time Zone"), None);
+    }
+
+    #[test]
+    fn named_zones_follow_daylight_saving() {
+        let zones = IcsTimeZones::default();
+        let ny = Some("America/New_York");
+        assert_eq!(
+            zones.to_utc(ny, local("20250115T090000")),
+            utc("20250115T140000")
+        );
+        assert_eq!(
+            zones.to_utc(ny, local("20250701T090000")),
+            utc("20250701T130000")
+        );
+    }
+
+    #[test]
+    fn skipped_and_repeated_times_follow_rfc_5545() {
+        let zones = IcsTimeZones::default();
+        let ny = Some("America/New_York");
+        // 02:30 does not exist on 9 March; it is read with the EST offset.
+        assert_eq!(
+            zones.to_utc(ny, local("20250309T023000")),
+            utc("20250309T073000")
+        );
+        // 01:30 happens twice on 2 November; the first (EDT) one is meant.
+        assert_eq!(
+            zones.to_utc(ny, local("20251102T013000")),
+            utc("20251102T053000")
+        );
+    }
+
+    #[test]
+    fn custom_vtimezone_rules_apply_by_season() {
+        let payload = "BEGIN:VCALENDAR\r\n\
+BEGIN:VTIMEZONE\r\n\
+TZID:Customized Time Zone\r\n\
+BEGIN:STANDARD\r\n\
+DTSTART:16010101T030000\r\n\
+TZOFFSETFROM:+0200\r\n\
+TZOFFSETTO:+0100\r\n\
+RRULE:FREQ=YEARLY;BYDAY=-1SU;BYMONTH=10\r\n\
+END:STANDARD\r\n\
+BEGIN:DAYLIGHT\r\n\
+DTSTART:16010101T020000\r\n\
+TZOFFSETFROM:+0100\r\n\
+TZOFFSETTO:+0200\r\n\
+RRULE:FREQ=YEARLY;BYDAY=-1SU;BYMONTH=3\r\n\
+END:DAYLIGHT\r\n\
+END:VTIMEZONE\r\n\
+END:VCALENDAR\r\n";
+        let zones = IcsTimeZones::from_ics(payload);
+        let custom = Some("Customized Time Zone");
+        assert_eq!(
+            zones.to_utc(custom, local("20250115T100000")),
+            utc("20250115T090000")
+        );
+        assert_eq!(
+            zones.to_utc(custom, local("20250615T100000")),
+            utc("20250615T080000")
+        );
+        assert_eq!(
+            zones.zone_name("Customized Time Zone"),
+            "Customized Time Zone"
+        );
+    }
+
+    #[test]
+    fn floating_times_stay_on_the_local_wall_clock() {
+        let zones = IcsTimeZones::default();
+        let value = local("20250615T090000");
+        let expected = chrono::Local
+            .from_local_datetime(&value)
+            .earliest()
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            parse_ics_datetime("20250615T090000", None, &zones),
+            Some(expected)
+        );
+    }
+
+    #[test]
+    fn finds_nth_and_last_weekdays() {
+        assert_eq!(
+            nth_weekday(2025, 3, 2, Weekday::Sun),
+            NaiveDate::from_ymd_opt(2025, 3, 9)
+        );
+        assert_eq!(
+            nth_weekday(2025, 10, -1, Weekday::Sun),
+            NaiveDate::from_ymd_opt(2025, 10, 26)
+        );
+    }
+}
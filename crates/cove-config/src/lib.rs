@@ -1,7 +1,17 @@
 mod error;
+mod location;
 mod manager;
 mod model;
+mod onboarding;
 
 pub use error::ConfigError;
+pub use location::{
+    data_dir_from_args, DataLocation, DATA_DIR_ENV, DATA_DIR_FLAG, PORTABLE_DIR_NAME,
+    PORTABLE_MARKER,
+};
 pub use manager::ConfigManager;
 pub use model::*;
+pub use onboarding::{
+    sample_data_requested, OnboardingFacts, OnboardingItem, OnboardingStep, SAMPLE_DATA_ENV,
+    SAMPLE_DATA_FLAG,
+};
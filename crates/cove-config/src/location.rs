@@ -0,0 +1,59 @@
+use std::path::{Path, PathBuf};
+
+/// Command-line flag selecting a portable data directory.
+pub const DATA_DIR_FLAG: &str = "--data-dir";
+/// Environment variable selecting a portable data directory.
+pub const DATA_DIR_ENV: &str = "COVE_MAIL_DATA_DIR";
+/// A file with this name next to the executable turns on portable mode
+/// with data in [`PORTABLE_DIR_NAME`] beside it, for USB-stick installs.
+pub const PORTABLE_MARKER: &str = "portable";
+pub const PORTABLE_DIR_NAME: &str = "CoveMailData";
+
+/// Where config, database, cache and secrets live.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DataLocation {
+    /// Per-user OS directories, secrets in the OS keychain.
+    System,
+    /// Everything under one relocatable directory, secrets in a file there.
+    Portable(PathBuf),
+}
+
+impl DataLocation {
+    /// The `--data-dir` flag wins over `COVE_MAIL_DATA_DIR`, which wins over
+    /// a portable marker next to the executable.
+    pub fn resolve() -> Self {
+        if let Some(dir) = data_dir_from_args(std::env::args().skip(1)) {
+            return Self::Portable(dir);
+        }
+        if let Some(dir) = std::env::var_os(DATA_DIR_ENV).filter(|value| !value.is_empty()) {
+            return Self::Portable(PathBuf::from(dir));
+        }
+        let exe_dir = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf));
+        match exe_dir {
+            Some(dir) if dir.join(PORTABLE_MARKER).exists() => {
+                Self::Portable(dir.join(PORTABLE_DIR_NAME))
+            }
+            _ => Self::System,
+        }
+    }
+
+    pub fn is_portable(&self) -> bool {
+        matches!(self, Self::Portable(_))
+    }
+}
+
+/// The value of `--data-dir <path>` or `--data-dir=<path>`, if given.
+pub fn data_dir_from_args(args: impl IntoIterator<Item = String>) -> Option<PathBuf> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == DATA_DIR_FLAG {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(value) = arg.strip_prefix(DATA_DIR_FLAG).and_then(|rest| rest.strip_prefix('=')) {
+            return Some(PathBuf::from(value));
+        }
+    }
+    None
+}
@@ -1,4 +1,4 @@
-use crate::{AppConfig, ConfigError};
+use crate::{AppConfig, ConfigError, DataLocation};
 use directories::ProjectDirs;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -8,27 +8,48 @@ const AUTHOR: &str = "Cove Mail";
 const APP: &str = "Cove Mail";
 const LEGACY_AUTHOR: &str = "Aether";
 const LEGACY_APP: &str = "Aether";
+const ENCRYPTED_SECRETS_FILE: &str = "secrets.age";
 
 #[derive(Debug, Clone)]
 pub struct ConfigManager {
+    location: DataLocation,
     config_path: PathBuf,
     data_dir: PathBuf,
     cache_dir: PathBuf,
 }
 
 impl ConfigManager {
+    /// Open the data location chosen by [`DataLocation::resolve`].
     pub fn new() -> Result<Self, ConfigError> {
-        let dirs = ProjectDirs::from(ORG, AUTHOR, APP).ok_or(ConfigError::MissingDirectories)?;
-        let config_dir = dirs.config_dir().to_path_buf();
-        let data_dir = dirs.data_dir().to_path_buf();
-        let cache_dir = dirs.cache_dir().to_path_buf();
+        Self::with_location(DataLocation::resolve())
+    }
+
+    pub fn with_location(location: DataLocation) -> Result<Self, ConfigError> {
+        let (config_dir, data_dir, cache_dir) = match &location {
+            DataLocation::System => {
+                let dirs =
+                    ProjectDirs::from(ORG, AUTHOR, APP).ok_or(ConfigError::MissingDirectories)?;
+                (
+                    dirs.config_dir().to_path_buf(),
+                    dirs.data_dir().to_path_buf(),
+                    dirs.cache_dir().to_path_buf(),
+                )
+            }
+            DataLocation::Portable(root) => {
+                fs::create_dir_all(root)?;
+                let root = root.canonicalize()?;
+                (root.join("config"), root.join("data"), root.join("cache"))
+            }
+        };
 
         fs::create_dir_all(&config_dir)?;
         fs::create_dir_all(&data_dir)?;
         fs::create_dir_all(&cache_dir)?;
 
         let config_path = config_dir.join("config.toml");
-        migrate_legacy_layout(&config_path, &data_dir, &cache_dir)?;
+        if !location.is_portable() {
+            migrate_legacy_layout(&config_path, &data_dir, &cache_dir)?;
+        }
         if !config_path.exists() {
             let initial = AppConfig::default();
             let content = toml::to_string_pretty(&initial)?;
@@ -36,6 +57,7 @@ impl ConfigManager {
         }
 
         Ok(Self {
+            location,
             config_path,
             data_dir,
             cache_dir,
@@ -64,6 +86,16 @@ impl ConfigManager {
     pub fn cache_dir(&self) -> &Path {
         &self.cache_dir
     }
+
+    pub fn location(&self) -> &DataLocation {
+        &self.location
+    }
+
+    /// Encrypted secrets file, used in portable mode and whenever the OS
+    /// keychain is unavailable.
+    pub fn encrypted_secrets_file(&self) -> PathBuf {
+        self.data_dir.join(ENCRYPTED_SECRETS_FILE)
+    }
 }
 
 fn migrate_legacy_layout(
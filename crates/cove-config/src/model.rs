@@ -1,6 +1,11 @@
-use cove_core::{AiMode, CloudAiProvider};
+use cove_core::{
+    AccountAiPolicy, AiMode, CloudAiProvider, DiskSpacePolicy, RetentionPolicy, SanitizerPolicy,
+    SendGuardrails, WeeklyReportPolicy,
+};
+use directories::UserDirs;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::path::PathBuf;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
@@ -13,6 +18,29 @@ pub struct AppConfig {
     pub ui: UiConfig,
     #[serde(default)]
     pub notifications: NotificationConfig,
+    #[serde(default)]
+    pub agenda: AgendaConfig,
+    #[serde(default)]
+    pub spellcheck: SpellcheckConfig,
+    #[serde(default)]
+    pub onboarding: OnboardingConfig,
+    #[serde(default)]
+    pub attachments: AttachmentConfig,
+    /// Automatic purging of old Trash and Junk mail.
+    #[serde(default)]
+    pub retention: RetentionPolicy,
+    /// Working-hours deferral and send throttling for the outbox.
+    #[serde(default)]
+    pub send_guardrails: SendGuardrails,
+    /// Lighter sync while the disk is nearly full.
+    #[serde(default)]
+    pub disk_space: DiskSpacePolicy,
+    /// Weekly analytics summary mailed to yourself.
+    #[serde(default)]
+    pub weekly_report: WeeklyReportPolicy,
+    /// How incoming HTML mail is cleaned before it is stored or shown.
+    #[serde(default)]
+    pub sanitizer: SanitizerPolicy,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,6 +69,29 @@ pub struct SyncConfig {
 pub struct AiConfig {
     pub local: LocalAiConfig,
     pub cloud: CloudAiConfig,
+    #[serde(default)]
+    pub digest: AiDigestConfig,
+    /// AI policy by account id; accounts not listed allow cloud AI.
+    #[serde(default)]
+    pub account_policies: BTreeMap<String, AccountAiPolicy>,
+}
+
+impl AiConfig {
+    pub fn account_policy(&self, account_id: &str) -> AccountAiPolicy {
+        self.account_policies
+            .get(account_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Set an account's policy; the default is not stored.
+    pub fn set_account_policy(&mut self, account_id: &str, policy: AccountAiPolicy) {
+        if policy == AccountAiPolicy::default() {
+            self.account_policies.remove(account_id);
+        } else {
+            self.account_policies.insert(account_id.to_string(), policy);
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -59,6 +110,52 @@ pub struct CloudAiConfig {
     pub feature_opt_in: Vec<String>,
     pub default_provider: Option<CloudAiProvider>,
     pub providers: BTreeMap<String, CloudProviderConfig>,
+    #[serde(default)]
+    pub redaction: AiRedactionConfig,
+}
+
+/// Placeholder substitution applied to prompts before they reach a cloud
+/// provider; originals are restored in the response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiRedactionConfig {
+    pub enabled: bool,
+    pub redact_emails: bool,
+    pub redact_phone_numbers: bool,
+    pub redact_numeric_ids: bool,
+    pub custom_patterns: Vec<String>,
+}
+
+impl Default for AiRedactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            redact_emails: true,
+            redact_phone_numbers: true,
+            redact_numeric_ids: true,
+            custom_patterns: Vec::new(),
+        }
+    }
+}
+
+/// Limits for "Summarize my unread mail", which may send many threads at once.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiDigestConfig {
+    pub max_concurrency: usize,
+    pub requests_per_minute: u32,
+    pub max_threads: usize,
+    /// Prompt characters per run, a rough cap on cloud cost.
+    pub max_prompt_chars: usize,
+}
+
+impl Default for AiDigestConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrency: 2,
+            requests_per_minute: 20,
+            max_threads: 50,
+            max_prompt_chars: 60_000,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -84,6 +181,45 @@ pub struct NotificationConfig {
     pub quiet_hours_enabled: bool,
     pub quiet_hours_start: String,
     pub quiet_hours_end: String,
+    /// Global mute: no sounds for any event, notifications still show.
+    #[serde(default)]
+    pub sounds_muted: bool,
+    #[serde(default)]
+    pub sounds: NotificationSounds,
+}
+
+/// A bundled tone or a user-provided audio file (wav/ogg/flac/mp3).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationSound {
+    Silent,
+    Chime,
+    Bell,
+    Pop,
+    Alert,
+    File(String),
+}
+
+/// Sound played for each notification event type.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationSounds {
+    pub new_mail: NotificationSound,
+    pub vip_mail: NotificationSound,
+    pub calendar_reminder: NotificationSound,
+    pub task_due: NotificationSound,
+    pub sync_error: NotificationSound,
+}
+
+impl Default for NotificationSounds {
+    fn default() -> Self {
+        Self {
+            new_mail: NotificationSound::Pop,
+            vip_mail: NotificationSound::Chime,
+            calendar_reminder: NotificationSound::Bell,
+            task_due: NotificationSound::Bell,
+            sync_error: NotificationSound::Alert,
+        }
+    }
 }
 
 impl Default for NotificationConfig {
@@ -96,7 +232,99 @@ impl Default for NotificationConfig {
             quiet_hours_enabled: false,
             quiet_hours_start: "22:00".to_string(),
             quiet_hours_end: "08:00".to_string(),
+            sounds_muted: false,
+            sounds: NotificationSounds::default(),
+        }
+    }
+}
+
+/// Morning agenda delivered once per day at `delivery_time` (local, `HH:MM`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgendaConfig {
+    pub enabled: bool,
+    pub delivery_time: String,
+    /// Also send the agenda as a self-addressed email from the primary account.
+    pub send_email: bool,
+    /// Prepend an AI-written narrative when local or cloud AI is available.
+    pub ai_narrative: bool,
+    /// VIP senders from before VIP became a contact flag. Moved onto the
+    /// contacts at startup and then dropped from the file; the agenda lists
+    /// unread mail from VIP contacts.
+    #[serde(default, rename = "vip_senders", skip_serializing_if = "Vec::is_empty")]
+    pub legacy_vip_senders: Vec<String>,
+}
+
+impl Default for AgendaConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            delivery_time: "07:30".to_string(),
+            send_email: false,
+            ai_narrative: true,
+            legacy_vip_senders: Vec::new(),
+        }
+    }
+}
+
+/// Compose spell checking; `language` names a Hunspell dictionary such as
+/// `en_US` (looked up as `en_US.aff` + `en_US.dic`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpellcheckConfig {
+    pub enabled: bool,
+    pub language: String,
+}
+
+impl Default for SpellcheckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            language: "en_US".to_string(),
+        }
+    }
+}
+
+/// Progress through the Get Started checklist. Steps that can be read from
+/// the rest of the config or from storage are not recorded here.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OnboardingConfig {
+    /// Keys of steps the user confirmed by hand.
+    pub confirmed: Vec<String>,
+    /// The checklist was hidden before it was finished.
+    pub dismissed: bool,
+}
+
+/// Which received attachments may be saved or opened. The built-in list of
+/// executable types is always blocked unless an extension is allowed here.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AttachmentConfig {
+    /// Extensions blocked on top of the built-in list, without the dot.
+    pub blocked_extensions: Vec<String>,
+    /// Extensions never blocked, the built-in list included.
+    pub allowed_extensions: Vec<String>,
+    /// Refuse password-protected archives instead of asking first.
+    pub block_encrypted_archives: bool,
+    /// Offer "Open anyway" for blocked files, behind a confirmation.
+    pub allow_open_anyway: bool,
+    /// Where "Save all" writes attachments; the OS downloads folder when
+    /// unset.
+    #[serde(default)]
+    pub download_dir: Option<String>,
+}
+
+impl AttachmentConfig {
+    /// The configured download directory, else the user's downloads folder,
+    /// else their home directory.
+    pub fn download_dir(&self) -> Option<PathBuf> {
+        if let Some(dir) = self.download_dir.as_deref().filter(|dir| !dir.trim().is_empty()) {
+            return Some(PathBuf::from(dir));
         }
+        let user_dirs = UserDirs::new()?;
+        Some(
+            user_dirs
+                .download_dir()
+                .unwrap_or_else(|| user_dirs.home_dir())
+                .to_path_buf(),
+        )
     }
 }
 
@@ -193,7 +421,10 @@ impl Default for AppConfig {
                     feature_opt_in: Vec::new(),
                     default_provider: None,
                     providers,
+                    redaction: AiRedactionConfig::default(),
                 },
+                digest: AiDigestConfig::default(),
+                account_policies: BTreeMap::new(),
             },
             ui: UiConfig {
                 compact_density: false,
@@ -201,6 +432,15 @@ impl Default for AppConfig {
                 timezone: None,
             },
             notifications: NotificationConfig::default(),
+            agenda: AgendaConfig::default(),
+            spellcheck: SpellcheckConfig::default(),
+            onboarding: OnboardingConfig::default(),
+            attachments: AttachmentConfig::default(),
+            retention: RetentionPolicy::default(),
+            send_guardrails: SendGuardrails::default(),
+            disk_space: DiskSpacePolicy::default(),
+            weekly_report: WeeklyReportPolicy::default(),
+            sanitizer: SanitizerPolicy::default(),
         }
     }
 }
@@ -0,0 +1,113 @@
+use crate::AppConfig;
+use serde::{Deserialize, Serialize};
+
+/// Command-line flag that loads the sample mailbox at startup.
+pub const SAMPLE_DATA_FLAG: &str = "--sample-data";
+/// Environment variable (`1`/`true`) that loads the sample mailbox at startup.
+pub const SAMPLE_DATA_ENV: &str = "COVE_MAIL_SAMPLE_DATA";
+
+/// Whether `--sample-data` or `COVE_MAIL_SAMPLE_DATA` asks for sample data.
+pub fn sample_data_requested() -> bool {
+    std::env::args().skip(1).any(|arg| arg == SAMPLE_DATA_FLAG)
+        || std::env::var(SAMPLE_DATA_ENV)
+            .is_ok_and(|value| matches!(value.trim(), "1" | "true" | "yes"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingStep {
+    AddAccount,
+    EnableNotifications,
+    ConfigureAi,
+}
+
+impl OnboardingStep {
+    pub const ALL: [Self; 3] = [Self::AddAccount, Self::EnableNotifications, Self::ConfigureAi];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::AddAccount => "add_account",
+            Self::EnableNotifications => "enable_notifications",
+            Self::ConfigureAi => "configure_ai",
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::AddAccount => "Add an email account",
+            Self::EnableNotifications => "Turn on notifications",
+            Self::ConfigureAi => "Set up AI assistance",
+        }
+    }
+
+    pub fn hint(self) -> &'static str {
+        match self {
+            Self::AddAccount => "Connect Gmail, Outlook or any IMAP account. The sample mailbox does not count.",
+            Self::EnableNotifications => "Get alerts for new mail and reminders, with quiet hours if you want them.",
+            Self::ConfigureAi => "Point Cove Mail at a local model, or add a cloud provider key.",
+        }
+    }
+}
+
+/// Setup state that lives outside the config file.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OnboardingFacts {
+    /// At least one account other than the sample account.
+    pub has_account: bool,
+    /// An API key is stored for the default cloud provider.
+    pub ai_key_configured: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OnboardingItem {
+    pub step: OnboardingStep,
+    pub done: bool,
+}
+
+impl AppConfig {
+    pub fn onboarding_checklist(&self, facts: &OnboardingFacts) -> Vec<OnboardingItem> {
+        OnboardingStep::ALL
+            .into_iter()
+            .map(|step| OnboardingItem {
+                step,
+                done: self.onboarding_step_done(step, facts),
+            })
+            .collect()
+    }
+
+    /// The checklist shows until every step is done or it is dismissed.
+    pub fn onboarding_pending(&self, facts: &OnboardingFacts) -> bool {
+        !self.onboarding.dismissed
+            && OnboardingStep::ALL
+                .into_iter()
+                .any(|step| !self.onboarding_step_done(step, facts))
+    }
+
+    /// Mark a step done. Turning on notifications also enables them.
+    pub fn confirm_onboarding_step(&mut self, step: OnboardingStep) {
+        if step == OnboardingStep::EnableNotifications {
+            self.notifications.new_mail_enabled = true;
+            self.notifications.reminder_enabled = true;
+        }
+        if !self.onboarding.confirmed.iter().any(|key| key == step.key()) {
+            self.onboarding.confirmed.push(step.key().to_string());
+        }
+    }
+
+    fn onboarding_step_done(&self, step: OnboardingStep, facts: &OnboardingFacts) -> bool {
+        let confirmed = self.onboarding.confirmed.iter().any(|key| key == step.key());
+        match step {
+            OnboardingStep::AddAccount => facts.has_account,
+            // On by default, so only an explicit confirmation counts.
+            OnboardingStep::EnableNotifications => {
+                confirmed
+                    && (self.notifications.new_mail_enabled || self.notifications.reminder_enabled)
+            }
+            OnboardingStep::ConfigureAi => {
+                confirmed
+                    || (self.ai.local.enabled && self.ai.local.model_path.is_some())
+                    || (self.ai.cloud.enabled && facts.ai_key_configured)
+            }
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, Utc, Weekday};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use url::Url;
@@ -64,6 +64,47 @@ pub struct Account {
     pub updated_at: DateTime<Utc>,
 }
 
+/// An account whose provider revoked its OAuth grant. Sync stops failing
+/// quietly and the user is asked to sign in again instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountReauth {
+    pub account_id: Uuid,
+    pub reason: String,
+    pub detected_at: DateTime<Utc>,
+}
+
+/// How a provider answered an HTTP API call that did not succeed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpFailureKind {
+    /// 401: the bearer token was rejected, which refreshing it may fix.
+    Unauthorized,
+    /// Any other error status; retrying with the same token will not help.
+    Rejected,
+}
+
+/// A failed HTTP API call, classified once so every provider crate maps it
+/// into its own error type the same way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpFailure {
+    pub kind: HttpFailureKind,
+    pub message: String,
+}
+
+impl HttpFailure {
+    /// Failure for `what` answered with the HTTP status code `status`.
+    pub fn from_status(what: &str, status: u16) -> Self {
+        let kind = if status == 401 {
+            HttpFailureKind::Unauthorized
+        } else {
+            HttpFailureKind::Rejected
+        };
+        Self {
+            kind,
+            message: format!("{what} failed with status {status}"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MailFolder {
     pub account_id: Uuid,
@@ -134,6 +175,42 @@ pub struct MailMessage {
     pub send_at: Option<DateTime<Utc>>,
 }
 
+#[cfg(any(test, feature = "test-support"))]
+impl MailMessage {
+    /// A blank INBOX message for tests: fresh id, nil account, received now.
+    /// Set the fields a test cares about with struct update syntax.
+    pub fn fixture() -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            account_id: Uuid::nil(),
+            remote_id: "1".to_string(),
+            thread_id: "1".to_string(),
+            folder_path: "INBOX".to_string(),
+            from: Vec::new(),
+            to: Vec::new(),
+            cc: Vec::new(),
+            bcc: Vec::new(),
+            reply_to: Vec::new(),
+            subject: String::new(),
+            preview: String::new(),
+            body_text: None,
+            body_html: None,
+            flags: MailFlags::default(),
+            labels: Vec::new(),
+            headers: BTreeMap::new(),
+            attachments: Vec::new(),
+            sent_at: None,
+            received_at: now,
+            created_at: now,
+            updated_at: now,
+            snoozed_until: None,
+            pinned: false,
+            send_at: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MailThreadSummary {
     pub thread_id: String,
@@ -142,6 +219,52 @@ pub struct MailThreadSummary {
     pub message_count: usize,
     pub unread_count: usize,
     pub most_recent_at: DateTime<Utc>,
+    /// Preview of the most recent message.
+    #[serde(default)]
+    pub snippet: String,
+    #[serde(default)]
+    pub has_attachments: bool,
+    /// Section of the message list, from `most_recent_at` in local time.
+    #[serde(default)]
+    pub date_bucket: DateBucket,
+}
+
+/// Recency section a thread is listed under.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DateBucket {
+    Today,
+    Yesterday,
+    ThisWeek,
+    #[default]
+    Earlier,
+}
+
+impl DateBucket {
+    /// Bucket for mail last active on `day`, seen from `today`. Weeks start
+    /// on Monday; future dates (clock skew) count as today.
+    pub fn for_day(day: NaiveDate, today: NaiveDate) -> Self {
+        let into_week = today.weekday().num_days_from_monday();
+        let week_start = today - chrono::Days::new(u64::from(into_week));
+        if day >= today {
+            Self::Today
+        } else if today.pred_opt() == Some(day) {
+            Self::Yesterday
+        } else if day >= week_start {
+            Self::ThisWeek
+        } else {
+            Self::Earlier
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Today => "Today",
+            Self::Yesterday => "Yesterday",
+            Self::ThisWeek => "This week",
+            Self::Earlier => "Earlier",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -175,6 +298,59 @@ impl Default for RsvpStatus {
     }
 }
 
+/// One attendee's answer to an invitation, taken from an iTIP REPLY.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AttendeeResponse {
+    pub event_id: Uuid,
+    pub email: String,
+    pub status: RsvpStatus,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One candidate time in a scheduling poll.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PollOption {
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PollStatus {
+    Open,
+    Confirmed,
+    Cancelled,
+}
+
+/// "Propose times": candidate slots emailed to participants, who answer by
+/// replying with the numbers of the options that suit them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchedulingPoll {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub options: Vec<PollOption>,
+    pub participants: Vec<String>,
+    /// Short code in the poll subject that ties replies to the poll.
+    pub token: String,
+    pub status: PollStatus,
+    /// Index into `options` once confirmed.
+    pub confirmed_option: Option<usize>,
+    pub event_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A participant's latest answer to a poll; `choices` index `options`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PollVote {
+    pub poll_id: Uuid,
+    pub participant: String,
+    pub choices: Vec<usize>,
+    pub voted_at: DateTime<Utc>,
+}
+
 /// Human-readable recurrence frequencies for the recurrence editor.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -203,6 +379,9 @@ pub struct CalendarEvent {
     pub organizer: Option<String>,
     pub alarms: Vec<CalendarAlarm>,
     pub rsvp_status: RsvpStatus,
+    /// Join link when the event is an online meeting (e.g. Teams).
+    #[serde(default)]
+    pub online_meeting_url: Option<String>,
     pub updated_at: DateTime<Utc>,
 }
 
@@ -229,6 +408,9 @@ pub struct ReminderTask {
     pub id: Uuid,
     pub account_id: Uuid,
     pub list_id: String,
+    /// Display name of the provider list, recorded at sync time.
+    #[serde(default)]
+    pub list_name: Option<String>,
     pub remote_id: Option<String>,
     pub title: String,
     pub notes: Option<String>,
@@ -241,6 +423,34 @@ pub struct ReminderTask {
     pub snoozed_until: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Estimated effort, used as the length of calendar time blocks.
+    #[serde(default)]
+    pub estimate_minutes: Option<u32>,
+}
+
+/// A task list on the provider (Google Tasks list, Microsoft To Do list or
+/// CalDAV VTODO collection).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TaskList {
+    pub account_id: Uuid,
+    pub list_id: String,
+    pub name: String,
+    /// The provider's default list; it cannot be renamed or deleted.
+    pub is_default: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Links a task to the calendar event reserved for working on it.
+/// Events are matched by remote id because local event ids are not stable
+/// across provider syncs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskTimeBlock {
+    pub task_id: Uuid,
+    pub account_id: Uuid,
+    pub calendar_id: String,
+    pub event_remote_id: String,
+    pub completed: bool,
+    pub created_at: DateTime<Utc>,
 }
 
 // ---- Signatures & Templates ----
@@ -264,6 +474,17 @@ pub struct EmailTemplate {
     pub body_text: String,
 }
 
+/// Compose shortcut: typing `abbreviation` (e.g. `;sig1`) followed by a
+/// space or newline expands to `body`. A `{cursor}` marker in the body
+/// sets where the caret lands.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextSnippet {
+    pub id: Uuid,
+    pub abbreviation: String,
+    pub body: String,
+    pub updated_at: DateTime<Utc>,
+}
+
 // ---- Rules / Filters ----
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -299,6 +520,8 @@ pub struct RuleCondition {
 pub enum RuleAction {
     MoveTo(String),
     Label(String),
+    /// Tag the whole conversation; later replies in the thread keep it.
+    LabelThread(String),
     MarkRead,
     Archive,
     Delete,
@@ -319,6 +542,93 @@ pub struct MailRule {
     pub order: i32,
 }
 
+// ---- Dry runs ----
+
+/// A message a dry run found, with the actions that would change it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DryRunSample {
+    pub message_id: Uuid,
+    pub account_id: Uuid,
+    pub thread_id: String,
+    pub folder_path: String,
+    pub subject: String,
+    pub sender: String,
+    pub received_at: DateTime<Utc>,
+    pub actions: Vec<RuleAction>,
+}
+
+/// How many messages one action would change.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DryRunActionCount {
+    pub action: RuleAction,
+    pub messages: usize,
+}
+
+/// What applying a rule or bulk action would do, worked out without
+/// changing anything.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DryRunReport {
+    /// Messages looked at.
+    pub scanned: usize,
+    /// Messages the rule matched, or every selected message for a bulk
+    /// action.
+    pub matched: usize,
+    /// Matched messages at least one action would change.
+    pub affected: usize,
+    /// In action order.
+    pub per_action: Vec<DryRunActionCount>,
+    /// Affected messages, newest first; a sample when there are many.
+    pub samples: Vec<DryRunSample>,
+}
+
+// ---- Labels / triage bundles ----
+
+/// A label in the shared taxonomy. Messages and rules refer to labels by
+/// name; labels in use but never defined have no color or description.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MailLabel {
+    pub name: String,
+    pub color: Option<String>,
+    pub description: Option<String>,
+}
+
+/// A project smart folder: every thread carrying one thread label.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ThreadLabelFolder {
+    pub label: String,
+    pub color: Option<String>,
+    pub thread_count: usize,
+    pub unread_count: usize,
+}
+
+pub const TRIAGE_BUNDLE_VERSION: u32 = 1;
+
+/// Labels, rules and templates as portable JSON so a team can share one
+/// triage setup. Holds no secrets or account data: rules are exported
+/// without their account binding and apply to all accounts on import.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriageBundle {
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    #[serde(default)]
+    pub labels: Vec<MailLabel>,
+    #[serde(default)]
+    pub rules: Vec<MailRule>,
+    #[serde(default)]
+    pub templates: Vec<EmailTemplate>,
+}
+
+/// What a bundle import changed. Rules and templates are matched to
+/// existing ones by name.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TriageImportSummary {
+    pub labels: u32,
+    pub rules_added: u32,
+    pub rules_updated: u32,
+    pub templates_added: u32,
+    pub templates_updated: u32,
+}
+
 // ---- Contacts ----
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -332,6 +642,359 @@ pub struct Contact {
     pub notes: Option<String>,
     pub last_contacted: Option<DateTime<Utc>>,
     pub contact_count: u32,
+    /// VIP senders notify even when mail notifications are muted.
+    #[serde(default)]
+    pub vip: bool,
+}
+
+/// A named list of contacts that can be sent to as one recipient. Members
+/// are expanded into concrete addresses when the message is sent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContactGroup {
+    pub id: Uuid,
+    pub name: String,
+    pub members: Vec<MailAddress>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecipientField {
+    #[default]
+    To,
+    Cc,
+    Bcc,
+}
+
+/// A group added to a message at compose time, minus the members the
+/// sender left out of this one message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupSend {
+    pub group_id: Uuid,
+    #[serde(default)]
+    pub field: RecipientField,
+    /// Member addresses excluded from this message.
+    #[serde(default)]
+    pub excluded: Vec<String>,
+}
+
+/// Who a group send actually went to, kept with the sent message's
+/// Message-ID so searches by recipient find it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupExpansion {
+    pub group_id: Uuid,
+    pub group_name: String,
+    pub field: RecipientField,
+    pub recipients: Vec<MailAddress>,
+    pub excluded: Vec<String>,
+}
+
+// ---- Reply triage ----
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplyQueueStatus {
+    Open,
+    Snoozed,
+    Dismissed,
+    Replied,
+}
+
+/// An inbound message the reply heuristic thinks is waiting on the user.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplyQueueItem {
+    pub message_id: Uuid,
+    pub account_id: Uuid,
+    pub thread_id: String,
+    /// Heuristic confidence, 0-100.
+    pub score: u8,
+    pub reasons: Vec<String>,
+    /// AI verdict for borderline scores; `None` until checked.
+    pub ai_confirmed: Option<bool>,
+    pub status: ReplyQueueStatus,
+    pub snoozed_until: Option<DateTime<Utc>>,
+    pub detected_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplyQueueStats {
+    pub open: u32,
+    pub snoozed: u32,
+    pub replied: u32,
+    pub dismissed: u32,
+    /// Mean time from receipt to reply for replied items.
+    pub avg_reply_hours: Option<f64>,
+    pub oldest_open_hours: Option<f64>,
+}
+
+// ---- Notes ----
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Note {
+    pub id: Uuid,
+    pub account_id: Option<Uuid>,
+    pub title: String,
+    pub body_markdown: String,
+    /// Set when the note was clipped from an email.
+    pub source_message_id: Option<Uuid>,
+    pub source_thread_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteAttachment {
+    pub id: Uuid,
+    pub note_id: Uuid,
+    pub file_name: String,
+    pub mime_type: String,
+    pub size: u64,
+}
+
+// ---- Audit log ----
+
+/// Append-only record of an outbound operation (sends, remote changes) kept
+/// for troubleshooting and the user's own review.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: Uuid,
+    pub account_id: Option<Uuid>,
+    /// Dotted event name, e.g. `mail.send`.
+    pub kind: String,
+    pub success: bool,
+    pub summary: String,
+    /// Event-specific telemetry (durations, server responses).
+    pub detail: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+// ---- Plus-address aliases ----
+
+/// A `user+tag@domain` address given to one sender or site, so mail that
+/// arrives from anyone else shows where the address leaked.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlusAlias {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub address: String,
+    pub tag: String,
+    /// Domain (or address) the alias was handed out to.
+    pub issued_to: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Mail received on an alias, per sending address.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlusAliasSender {
+    pub address: String,
+    pub message_count: u32,
+    pub last_received_at: DateTime<Utc>,
+    /// False when the sender is not the party the alias was issued to.
+    pub expected: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlusAliasUsage {
+    pub alias: PlusAlias,
+    pub senders: Vec<PlusAliasSender>,
+}
+
+impl PlusAliasUsage {
+    pub fn message_count(&self) -> u32 {
+        self.senders.iter().map(|sender| sender.message_count).sum()
+    }
+
+    /// Mail from unexpected senders suggests the address was leaked or sold.
+    pub fn is_leaked(&self) -> bool {
+        self.senders.iter().any(|sender| !sender.expected)
+    }
+}
+
+// ---- Reply identities ----
+
+/// An address mail can be sent from: an account's own address, or one of
+/// its plus-addresses.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SendIdentity {
+    pub account_id: Uuid,
+    pub name: String,
+    pub address: String,
+}
+
+/// Which identity to reply to a message from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplyIdentity {
+    /// The identity the original was delivered to, or the receiving
+    /// account's own address when that is unknown.
+    pub suggested: SendIdentity,
+    /// Where the original was delivered, from its delivery headers or
+    /// else its recipients.
+    pub delivered_to: Option<String>,
+    /// The receiving account's own identity.
+    pub received_by: SendIdentity,
+}
+
+impl ReplyIdentity {
+    /// A warning when replying as `from` would send from another account
+    /// than the one that received the original.
+    pub fn account_warning(&self, from: &SendIdentity) -> Option<String> {
+        (from.account_id != self.received_by.account_id).then(|| {
+            format!(
+                "This message was received by {}; you are replying from {}.",
+                self.received_by.address, from.address
+            )
+        })
+    }
+}
+
+// ---- Mail merge ----
+
+/// One template sent to many recipients, personalised from CSV fields and
+/// drained through the outbox at no more than `per_minute` messages.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MailMergeCampaign {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub name: String,
+    /// Subject and body with `{{field}}` variables, copied from the template
+    /// so later template edits do not change a running merge.
+    pub subject: String,
+    pub body_text: String,
+    pub per_minute: u32,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeRecipientStatus {
+    Pending,
+    Sent,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MailMergeRecipient {
+    pub id: Uuid,
+    pub campaign_id: Uuid,
+    pub email: String,
+    /// CSV columns for this row, keyed by header.
+    pub fields: BTreeMap<String, String>,
+    pub status: MergeRecipientStatus,
+    pub error: Option<String>,
+    pub sent_at: Option<DateTime<Utc>>,
+}
+
+// ---- Related messages ----
+
+/// Context for an open message: other threads from its sender, and mail
+/// about the same subject or entities (order numbers, ticket ids, ...).
+/// Both lists hold one message per thread.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RelatedMessages {
+    /// Latest message of each thread, newest first.
+    pub from_sender: Vec<MailMessage>,
+    /// Best match first.
+    pub same_topic: Vec<MailMessage>,
+}
+
+// ---- Inbox digest ----
+
+/// AI summary of one thread's unread mail. Kept so a later digest only
+/// summarizes threads that received newer messages.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadDigestEntry {
+    pub account_id: Uuid,
+    pub thread_id: String,
+    pub subject: String,
+    pub senders: Vec<String>,
+    pub unread_count: u32,
+    pub summary: String,
+    /// Newest unread message covered by `summary`.
+    pub latest_received_at: DateTime<Utc>,
+    pub provenance: DataProvenance,
+    pub created_at: DateTime<Utc>,
+}
+
+/// "Summarize my unread mail": one entry per unread thread, newest first,
+/// plus a Markdown rendering of the whole digest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InboxDigest {
+    pub generated_at: DateTime<Utc>,
+    pub entries: Vec<ThreadDigestEntry>,
+    /// Threads summarized by this run.
+    pub summarized: usize,
+    /// Threads whose stored summary was still current.
+    pub reused: usize,
+    /// Subjects of threads left out by the thread or cost cap.
+    pub skipped: Vec<String>,
+    /// Subjects and errors of threads whose summary failed.
+    pub failed: Vec<(String, String)>,
+    pub markdown: String,
+}
+
+// ---- Meeting agendas ----
+
+/// A recent thread with meeting attendees that an agenda item came from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgendaSource {
+    pub thread_id: String,
+    pub subject: String,
+    pub last_sender: String,
+    pub last_message_at: DateTime<Utc>,
+}
+
+/// Suggested agenda for a new meeting. `markdown` is the bullet list to put
+/// into the event description; `provenance` is set when AI drafted it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MeetingAgenda {
+    pub items: Vec<String>,
+    pub sources: Vec<AgendaSource>,
+    pub provenance: Option<DataProvenance>,
+    pub markdown: String,
+}
+
+// ---- Message export ----
+
+/// Which messages an NDJSON export writes and what it leaves out. Unset
+/// fields match everything.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MessageExportFilter {
+    #[serde(default)]
+    pub account_id: Option<Uuid>,
+    #[serde(default)]
+    pub folder: Option<String>,
+    /// Received at or after.
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    /// Received before.
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub redaction: ExportRedaction,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ExportRedaction {
+    /// Replace addresses and display names with pseudonyms that are stable
+    /// within one export, so who-talks-to-whom survives but identities do
+    /// not.
+    #[serde(default)]
+    pub addresses: bool,
+    /// Leave out previews and bodies.
+    #[serde(default)]
+    pub bodies: bool,
+}
+
+// ---- Sample data ----
+
+/// What was written by seeding the sample mailbox.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SampleDataSummary {
+    pub messages: usize,
+    pub events: usize,
+    pub tasks: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -366,6 +1029,524 @@ pub struct SyncJob {
     pub updated_at: DateTime<Utc>,
 }
 
+// ---- Initial sync ----
+
+/// How far backfilling older mail has got in one folder. Pages are fetched
+/// newest first and `cursor` marks where the next one starts, so backfill
+/// picks up where it stopped after a restart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackfillCursor {
+    pub account_id: Uuid,
+    pub folder_path: String,
+    /// Order folders are backfilled in; the inbox comes first.
+    pub position: u32,
+    /// Backend-specific: the oldest UID fetched for IMAP, a page token for
+    /// Gmail, an offset for EWS and JMAP. `None` before the first page.
+    pub cursor: Option<String>,
+    pub fetched: u64,
+    /// Messages in the folder as reported by the server, when known.
+    pub total: Option<u64>,
+    pub completed: bool,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Overall progress of an account's initial sync and backfill.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InitialSyncProgress {
+    pub account_id: Uuid,
+    pub messages_fetched: u64,
+    /// Sum of the folder totals the server reported; `None` until one has.
+    pub messages_total: Option<u64>,
+    pub folders_done: u32,
+    pub folders_total: u32,
+    pub paused: bool,
+    /// True once every folder is backfilled; false before planning too.
+    pub completed: bool,
+    /// Estimated seconds left at the rate seen so far.
+    pub eta_seconds: Option<u64>,
+    /// Folder the next page comes from.
+    pub current_folder: Option<String>,
+}
+
+impl InitialSyncProgress {
+    pub fn from_cursors(
+        account_id: Uuid,
+        cursors: &[BackfillCursor],
+        paused: bool,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let messages_fetched = cursors.iter().map(|cursor| cursor.fetched).sum::<u64>();
+        let messages_total = cursors
+            .iter()
+            .filter_map(|cursor| cursor.total)
+            .reduce(|sum, total| sum + total)
+            // Servers may count a little less than was fetched.
+            .map(|total| total.max(messages_fetched));
+        let folders_done = cursors.iter().filter(|cursor| cursor.completed).count() as u32;
+        let completed = !cursors.is_empty() && cursors.iter().all(|cursor| cursor.completed);
+
+        let elapsed = cursors
+            .iter()
+            .map(|cursor| cursor.started_at)
+            .min()
+            .map(|started| (now - started).num_seconds())
+            .unwrap_or(0);
+        let eta_seconds = match messages_total {
+            Some(total) if !completed && messages_fetched > 0 && elapsed > 0 => {
+                let remaining = total - messages_fetched;
+                Some(remaining * elapsed as u64 / messages_fetched)
+            }
+            _ => None,
+        };
+
+        Self {
+            account_id,
+            messages_fetched,
+            messages_total,
+            folders_done,
+            folders_total: cursors.len() as u32,
+            paused,
+            completed,
+            eta_seconds,
+            current_folder: cursors
+                .iter()
+                .find(|cursor| !cursor.completed)
+                .map(|cursor| cursor.folder_path.clone()),
+        }
+    }
+}
+
+// ---- Offline queue ----
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum PendingOperationKind {
+    /// Payload: the outgoing mail.
+    SendMail,
+    /// Payload: the calendar event as edited locally.
+    UpsertEvent,
+    /// Payload: the folder and remote ids of messages archived locally.
+    ArchiveMessages,
+    /// Payload: the folder and remote ids of messages deleted locally, with
+    /// the folder's IMAP UIDVALIDITY at the time.
+    DeleteMessages,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PendingOperationStatus {
+    /// Waiting for the provider to be reachable again.
+    Pending,
+    /// The provider refused it on replay; kept until retried or discarded.
+    Failed,
+    /// Not replayed because its target changed or disappeared meanwhile.
+    Conflict,
+}
+
+/// A change made while its provider was unreachable, replayed oldest first
+/// once the provider is back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingOperation {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub kind: PendingOperationKind,
+    pub status: PendingOperationStatus,
+    /// The item an update applies to. A later change to the same item
+    /// replaces the queued one instead of queueing behind it.
+    pub target_id: Option<Uuid>,
+    pub summary: String,
+    pub payload_json: serde_json::Value,
+    pub attempt_count: u32,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// How replaying one pending operation went when the provider was reached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayOutcome {
+    Applied,
+    /// Left unapplied; the reason is shown to the user.
+    Conflict(String),
+}
+
+/// Queued offline changes for the "Offline — N pending changes" banner.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OfflineQueueStatus {
+    /// Changes still waiting for their provider.
+    pub pending: usize,
+    /// Failed or conflicting changes the user should look at.
+    pub needs_attention: usize,
+    /// Changes replayed by this run.
+    pub replayed: usize,
+    /// Some provider was still unreachable.
+    pub offline: bool,
+}
+
+// ---- Retention ----
+
+/// How long Trash and Junk keep mail before it is purged locally and on the
+/// server. A day count of 0 keeps mail forever.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    pub enabled: bool,
+    pub trash_days: u32,
+    pub junk_days: u32,
+    /// Days by folder path, taking precedence over the Trash and Junk
+    /// defaults; any folder may be listed.
+    pub folder_overrides: BTreeMap<String, u32>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            trash_days: 30,
+            junk_days: 30,
+            folder_overrides: BTreeMap::new(),
+        }
+    }
+}
+
+/// Prefixes under which providers keep their system folders, so that
+/// `[Gmail]/Trash` and `INBOX.Junk` count as Trash and Junk.
+const SYSTEM_FOLDER_PREFIXES: &[&str] = &["[gmail]/", "[google mail]/", "inbox.", "inbox/"];
+
+const TRASH_FOLDER_NAMES: &[&str] = &["trash", "bin", "deleted items", "deleted messages"];
+const JUNK_FOLDER_NAMES: &[&str] = &["junk", "spam", "junk e-mail", "junk email", "bulk mail"];
+
+impl RetentionPolicy {
+    /// Days mail in `folder_path` is kept, or `None` when it is never purged.
+    /// Only top-level Trash/Junk folders, or those under a provider's system
+    /// prefix, get the defaults; a user folder such as `Clients/Bin` is
+    /// purged only when listed in `folder_overrides`.
+    pub fn days_for(&self, folder_path: &str) -> Option<u32> {
+        let days = match self
+            .folder_overrides
+            .iter()
+            .find(|(path, _)| path.eq_ignore_ascii_case(folder_path))
+        {
+            Some((_, days)) => *days,
+            None => {
+                let path = folder_path.to_ascii_lowercase();
+                let name = SYSTEM_FOLDER_PREFIXES
+                    .iter()
+                    .find_map(|prefix| path.strip_prefix(prefix))
+                    .unwrap_or(&path);
+                if name.contains(['/', '.']) {
+                    0
+                } else if TRASH_FOLDER_NAMES.contains(&name) {
+                    self.trash_days
+                } else if JUNK_FOLDER_NAMES.contains(&name) {
+                    self.junk_days
+                } else {
+                    0
+                }
+            }
+        };
+        (days > 0).then_some(days)
+    }
+}
+
+/// A message old enough to be purged by the retention policy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PurgeCandidate {
+    pub message_id: Uuid,
+    pub account_id: Uuid,
+    pub remote_id: String,
+    pub folder_path: String,
+    pub subject: String,
+    pub received_at: DateTime<Utc>,
+}
+
+// ---- Disk space ----
+
+/// When to fall back to lighter sync because the data directory's disk is
+/// running out of space.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiskSpacePolicy {
+    /// Switch to headers-only sync and stop caching attachments while free
+    /// space is below `min_free_mb`.
+    pub degrade_when_low: bool,
+    pub min_free_mb: u64,
+}
+
+impl Default for DiskSpacePolicy {
+    fn default() -> Self {
+        Self {
+            degrade_when_low: true,
+            min_free_mb: 2048,
+        }
+    }
+}
+
+/// How much of each message sync downloads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncFidelity {
+    /// Bodies and attachments.
+    #[default]
+    Full,
+    /// Headers and flags only; bodies already stored are kept.
+    HeadersOnly,
+}
+
+/// Free space on the data directory's disk and what local mail needs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskSpaceReport {
+    pub available_bytes: u64,
+    pub total_bytes: u64,
+    /// Message bodies and cached attachments stored locally.
+    pub mail_bytes: u64,
+    /// Average daily growth of `mail_bytes` over the last week.
+    pub daily_growth_bytes: u64,
+    /// Space full sync is expected to need over the next 30 days.
+    pub projected_need_bytes: u64,
+    pub fidelity: SyncFidelity,
+}
+
+impl DiskSpaceReport {
+    pub fn is_degraded(&self) -> bool {
+        self.fidelity == SyncFidelity::HeadersOnly
+    }
+}
+
+// ---- Attachment downloads ----
+
+/// An attachment written to disk, listed under recent downloads.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachmentDownload {
+    pub id: Uuid,
+    pub account_id: Uuid,
+    pub message_id: Uuid,
+    pub attachment_id: Uuid,
+    pub file_name: String,
+    /// Where the file was written; differs from `file_name` when a file of
+    /// that name already existed.
+    pub path: String,
+    pub saved_at: DateTime<Utc>,
+}
+
+// ---- Reminders ----
+
+/// What a scheduled reminder is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReminderKind {
+    /// A snoozed message is due back in the inbox.
+    MailSnooze,
+    /// A message snoozed in the needs-reply queue is due for a reply.
+    FollowUp,
+    /// An event is about to start.
+    EventAlert,
+    /// A task is about to fall due.
+    TaskDue,
+}
+
+/// A future trigger held by the reminder scheduler. Reminders are stored,
+/// so one that fell due while the app was closed fires on the next poll.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reminder {
+    pub id: Uuid,
+    pub kind: ReminderKind,
+    /// The message, event or task the reminder is about.
+    pub target_id: Uuid,
+    pub account_id: Uuid,
+    pub trigger_at: DateTime<Utc>,
+    /// Dropped without firing after this, e.g. once the event has started.
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Minutes ahead of the event start or task due time; 0 otherwise.
+    pub lead_minutes: i64,
+    pub title: String,
+    pub detail: Option<String>,
+}
+
+impl Reminder {
+    /// "now" or "in N min", for alert text.
+    pub fn lead_label(&self) -> String {
+        if self.lead_minutes == 0 {
+            "now".to_string()
+        } else {
+            format!("in {} min", self.lead_minutes)
+        }
+    }
+}
+
+// ---- HTML sanitizer ----
+
+/// How much markup survives when incoming HTML mail is sanitized. Scripts,
+/// event handlers and forms are removed at every level.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SanitizerLevel {
+    /// Text formatting and links only; no images, tables or CSS.
+    Strict,
+    /// Common layout markup and images, with inline CSS that loads nothing.
+    #[default]
+    Standard,
+    /// Also keeps legacy presentational markup such as `font` and `bgcolor`.
+    Permissive,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SanitizerPolicy {
+    pub level: SanitizerLevel,
+    /// Drop all inline CSS whatever the level.
+    #[serde(default)]
+    pub strip_css: bool,
+}
+
+// ---- Send guardrails ----
+
+/// Optional limits on when and how fast the outbox sends.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SendGuardrails {
+    /// Hold mail sent outside working hours until the next workday starts,
+    /// unless the sender overrides it.
+    pub defer_outside_working_hours: bool,
+    /// Local hours, start inclusive and end exclusive.
+    pub workday_start_hour: u32,
+    pub workday_end_hour: u32,
+    pub skip_weekends: bool,
+    /// Cap sends per account per minute so bulk sending does not look like
+    /// spam to the provider.
+    pub throttle_sends: bool,
+    pub max_sends_per_minute: u32,
+}
+
+impl Default for SendGuardrails {
+    fn default() -> Self {
+        Self {
+            defer_outside_working_hours: false,
+            workday_start_hour: 8,
+            workday_end_hour: 18,
+            skip_weekends: true,
+            throttle_sends: false,
+            max_sends_per_minute: 20,
+        }
+    }
+}
+
+// ---- Analytics ----
+
+/// Mail received and sent on one local calendar day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DailyVolume {
+    pub date: NaiveDate,
+    pub received: u32,
+    pub sent: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CorrespondentStats {
+    pub address: String,
+    pub name: Option<String>,
+    /// Messages from them.
+    pub received: u32,
+    /// Messages to them, counting To and Cc.
+    pub sent: u32,
+}
+
+/// Mail metrics over `[since, until)`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MailAnalytics {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub received: u32,
+    pub sent: u32,
+    /// One entry per day with mail, oldest first.
+    pub daily: Vec<DailyVolume>,
+    /// Reply-queue items answered in the period.
+    pub replies: u32,
+    pub avg_response_hours: Option<f64>,
+    pub median_response_hours: Option<f64>,
+    /// Busiest correspondents first.
+    pub top_correspondents: Vec<CorrespondentStats>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalyticsFormat {
+    Csv,
+    Json,
+}
+
+impl AnalyticsFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+        }
+    }
+}
+
+/// A weekly analytics summary mailed to the primary account.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WeeklyReportPolicy {
+    pub enabled: bool,
+    pub weekday: Weekday,
+    /// Local hour the report goes out.
+    pub hour: u32,
+    pub top_correspondents: u32,
+}
+
+impl Default for WeeklyReportPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            weekday: Weekday::Mon,
+            hour: 8,
+            top_correspondents: 5,
+        }
+    }
+}
+
+// ---- Sender mutes ----
+
+/// How long "Mute this sender" lasts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MuteDuration {
+    Week,
+    Month,
+    Forever,
+}
+
+impl MuteDuration {
+    /// When a mute starting at `now` ends, or `None` for forever.
+    pub fn until(self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Week => Some(now + chrono::Duration::days(7)),
+            Self::Month => Some(now + chrono::Duration::days(30)),
+            Self::Forever => None,
+        }
+    }
+}
+
+/// A sender whose mail to one account raises no notifications, and is
+/// optionally archived out of that account's inbox, until `muted_until`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SenderMute {
+    pub account_id: Uuid,
+    /// Lowercased address.
+    pub email: String,
+    /// `None` mutes forever.
+    pub muted_until: Option<DateTime<Utc>>,
+    pub auto_archive: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl SenderMute {
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.muted_until.map_or(true, |until| until > now)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum AiMode {
@@ -385,6 +1566,30 @@ pub enum CloudAiProvider {
     OpenRouter,
 }
 
+/// Where an account's message content may be sent for AI processing.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountAiPolicy {
+    /// Local inference, or cloud providers where the user opted in.
+    #[default]
+    CloudAllowed,
+    /// Content never leaves the device.
+    LocalOnly,
+    /// No AI features for this account.
+    Disabled,
+}
+
+impl AccountAiPolicy {
+    pub const ALL: [Self; 3] = [Self::CloudAllowed, Self::LocalOnly, Self::Disabled];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::CloudAllowed => "Cloud allowed",
+            Self::LocalOnly => "Local only",
+            Self::Disabled => "Disabled",
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AiRequest {
@@ -418,6 +1623,194 @@ pub struct SearchResult<T> {
     pub items: Vec<T>,
 }
 
+// ---- Search operators ----
+
+/// Operators understood by search, in the order they are suggested. `on:`
+/// and `attendee:` search calendar events, `due:` searches tasks.
+pub const SEARCH_OPERATORS: &[&str] = &[
+    "from:", "to:", "subject:", "label:", "has:", "is:", "after:", "before:", "on:",
+    "attendee:", "due:",
+];
+
+/// A search broken into its operators. Each text field may hold several
+/// words; every word must match.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchQueryDraft {
+    pub text: String,
+    pub from: String,
+    pub to: String,
+    pub subject: String,
+    pub label: String,
+    pub has_attachment: bool,
+    pub unread: bool,
+    pub pinned: bool,
+    pub after: Option<chrono::NaiveDate>,
+    pub before: Option<chrono::NaiveDate>,
+    /// Calendar day an event must overlap.
+    #[serde(default)]
+    pub on: Option<chrono::NaiveDate>,
+    #[serde(default)]
+    pub attendee: String,
+    /// Day a task must be due on.
+    #[serde(default)]
+    pub due: Option<chrono::NaiveDate>,
+}
+
+impl SearchQueryDraft {
+    /// Split a typed query into operators; words that are not a known
+    /// operator stay in `text`.
+    pub fn parse(query: &str) -> Self {
+        fn push(field: &mut String, word: &str) {
+            if !word.is_empty() {
+                if !field.is_empty() {
+                    field.push(' ');
+                }
+                field.push_str(word);
+            }
+        }
+        let date = |value: &str| chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").ok();
+
+        let mut draft = Self::default();
+        for token in query.split_whitespace() {
+            let lower = token.to_lowercase();
+            match lower.split_once(':') {
+                Some(("from", value)) => push(&mut draft.from, value),
+                Some(("to", value)) => push(&mut draft.to, value),
+                Some(("subject", value)) => push(&mut draft.subject, value),
+                Some(("label", value)) => push(&mut draft.label, value),
+                Some(("attendee", value)) => push(&mut draft.attendee, value),
+                Some(("has", "attachment")) => draft.has_attachment = true,
+                Some(("is", "unread")) => draft.unread = true,
+                Some(("is", "pinned")) => draft.pinned = true,
+                Some(("after", value)) if date(value).is_some() => draft.after = date(value),
+                Some(("before", value)) if date(value).is_some() => draft.before = date(value),
+                Some(("on", value)) if date(value).is_some() => draft.on = date(value),
+                Some(("due", value)) if date(value).is_some() => draft.due = date(value),
+                _ => push(&mut draft.text, token),
+            }
+        }
+        draft
+    }
+
+    /// The query string a user would type for this draft.
+    pub fn to_query(&self) -> String {
+        let mut tokens: Vec<String> = Vec::new();
+        for (operator, value) in [
+            ("from:", &self.from),
+            ("to:", &self.to),
+            ("subject:", &self.subject),
+            ("label:", &self.label),
+            ("attendee:", &self.attendee),
+        ] {
+            tokens.extend(value.split_whitespace().map(|word| format!("{operator}{word}")));
+        }
+        if self.has_attachment {
+            tokens.push("has:attachment".to_string());
+        }
+        if self.unread {
+            tokens.push("is:unread".to_string());
+        }
+        if self.pinned {
+            tokens.push("is:pinned".to_string());
+        }
+        if let Some(after) = self.after {
+            tokens.push(format!("after:{}", after.format("%Y-%m-%d")));
+        }
+        if let Some(before) = self.before {
+            tokens.push(format!("before:{}", before.format("%Y-%m-%d")));
+        }
+        if let Some(on) = self.on {
+            tokens.push(format!("on:{}", on.format("%Y-%m-%d")));
+        }
+        if let Some(due) = self.due {
+            tokens.push(format!("due:{}", due.format("%Y-%m-%d")));
+        }
+        tokens.extend(self.text.split_whitespace().map(str::to_string));
+        tokens.join(" ")
+    }
+
+    pub fn has_operators(&self) -> bool {
+        let plain = Self {
+            text: self.text.clone(),
+            ..Self::default()
+        };
+        *self != plain
+    }
+
+    /// Whether the query asks for calendar events (`on:`, `attendee:`).
+    pub fn targets_events(&self) -> bool {
+        self.on.is_some() || !self.attendee.is_empty()
+    }
+
+    /// Whether the query asks for tasks (`due:`).
+    pub fn targets_tasks(&self) -> bool {
+        self.due.is_some()
+    }
+}
+
+/// Results of one search-bar query. Mail is searched unless the query
+/// only uses calendar or task operators.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UnifiedSearchResults {
+    pub messages: Vec<MailMessage>,
+    pub events: Vec<CalendarEvent>,
+    pub tasks: Vec<ReminderTask>,
+}
+
+/// One autocomplete entry: `completion` replaces the whole query.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchSuggestion {
+    pub label: String,
+    pub completion: String,
+}
+
+// ---- Protocol debug log ----
+
+/// Wire protocol of a recorded exchange.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProtocolKind {
+    Imap,
+    Jmap,
+    Ews,
+    Gmail,
+}
+
+impl ProtocolKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Imap => "IMAP",
+            Self::Jmap => "JMAP",
+            Self::Ews => "EWS",
+            Self::Gmail => "Gmail API",
+        }
+    }
+}
+
+/// One request to an account's server and how it ended, with credentials
+/// removed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtocolExchange {
+    /// Login the exchange was made as.
+    pub account: String,
+    pub protocol: ProtocolKind,
+    pub started_at: DateTime<Utc>,
+    /// IMAP command line, or HTTP method, URL, headers and body.
+    pub request: String,
+    /// Tagged IMAP completion or HTTP status; `None` while unanswered.
+    pub response: Option<String>,
+    pub duration_ms: Option<u64>,
+}
+
+/// Recorded protocol exchanges packaged for a support request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SupportBundle {
+    pub generated_at: DateTime<Utc>,
+    pub app_version: String,
+    pub os: String,
+    pub exchanges: Vec<ProtocolExchange>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Pagination {
     pub limit: usize,
@@ -432,3 +1825,96 @@ impl Default for Pagination {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn date_bucket_for_day_midweek() {
+        // Wednesday; the week started on Monday the 12th.
+        let today = date("2026-10-14");
+        let cases = [
+            ("2026-10-15", DateBucket::Today),
+            ("2026-10-14", DateBucket::Today),
+            ("2026-10-13", DateBucket::Yesterday),
+            ("2026-10-12", DateBucket::ThisWeek),
+            ("2026-10-11", DateBucket::Earlier),
+            ("2025-10-14", DateBucket::Earlier),
+        ];
+        for (day, expected) in cases {
+            assert_eq!(DateBucket::for_day(date(day), today), expected, "{day}");
+        }
+    }
+
+    #[test]
+    fn date_bucket_for_day_on_monday() {
+        // Sunday is yesterday even though it falls in the previous week.
+        let today = date("2026-10-12");
+        assert_eq!(DateBucket::for_day(date("2026-10-11"), today), DateBucket::Yesterday);
+        assert_eq!(DateBucket::for_day(date("2026-10-10"), today), DateBucket::Earlier);
+    }
+
+    #[test]
+    fn date_bucket_defaults_to_earlier() {
+        assert_eq!(DateBucket::default(), DateBucket::Earlier);
+    }
+
+    #[test]
+    fn retention_applies_to_system_trash_and_junk_only() {
+        let policy = RetentionPolicy {
+            enabled: true,
+            trash_days: 30,
+            junk_days: 14,
+            folder_overrides: BTreeMap::new(),
+        };
+        let cases = [
+            ("Trash", Some(30)),
+            ("Deleted Items", Some(30)),
+            ("[Gmail]/Trash", Some(30)),
+            ("[Google Mail]/Bin", Some(30)),
+            ("INBOX.Trash", Some(30)),
+            ("Junk E-mail", Some(14)),
+            ("SPAM", Some(14)),
+            ("[Gmail]/Spam", Some(14)),
+            ("INBOX/Junk", Some(14)),
+            ("INBOX", None),
+            ("Clients/Bin", None),
+            ("Archive.Spam", None),
+            ("INBOX.Clients.Trash", None),
+            ("[Gmail]/All Mail", None),
+            ("Trash Talk", None),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(policy.days_for(path), expected, "{path}");
+        }
+    }
+
+    #[test]
+    fn retention_overrides_take_precedence() {
+        let mut policy = RetentionPolicy::default();
+        policy.folder_overrides.insert("clients/bin".to_string(), 7);
+        policy.folder_overrides.insert("Trash".to_string(), 0);
+        assert_eq!(policy.days_for("Clients/Bin"), Some(7));
+        assert_eq!(policy.days_for("Trash"), None);
+        assert_eq!(policy.days_for("Junk"), Some(30));
+    }
+
+    #[test]
+    fn http_failure_flags_only_401_as_unauthorized() {
+        let failure = HttpFailure::from_status("Graph list", 401);
+        assert_eq!(failure.kind, HttpFailureKind::Unauthorized);
+        assert_eq!(failure.message, "Graph list failed with status 401");
+        for status in [400, 403, 404, 429, 500] {
+            assert_eq!(
+                HttpFailure::from_status("Graph list", status).kind,
+                HttpFailureKind::Rejected,
+                "{status}"
+            );
+        }
+    }
+}
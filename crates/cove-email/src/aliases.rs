@@ -0,0 +1,85 @@
+//! Plus-addressing (`user+tag@domain`) aliases for sign-up forms.
+//!
+//! Each alias is issued to one sender's domain. Mail reaching the alias from
+//! any other domain means the address was shared, leaked or sold.
+
+use crate::EmailError;
+
+/// Second-level labels that are part of a public suffix (`example.co.uk`).
+const COMPOUND_SUFFIX_LABELS: &[&str] = &["co", "com", "net", "org", "ac", "gov", "edu"];
+
+/// `base` with `tag` after a `+` in the local part. Any existing tag on
+/// `base` is replaced.
+pub fn plus_address(base: &str, tag: &str) -> Result<String, EmailError> {
+    let (local, domain) = base
+        .trim()
+        .rsplit_once('@')
+        .filter(|(local, domain)| !local.is_empty() && !domain.is_empty())
+        .ok_or_else(|| EmailError::Data(format!("not an email address: {base}")))?;
+    let local = local.split_once('+').map_or(local, |(user, _)| user);
+    let tag = normalize_alias_tag(tag);
+    if tag.is_empty() {
+        return Err(EmailError::Data("alias tag is empty".to_string()));
+    }
+    Ok(format!("{local}+{tag}@{domain}"))
+}
+
+/// Lowercase letters, digits and single `-`; anything else becomes `-`.
+pub fn normalize_alias_tag(tag: &str) -> String {
+    let mut normalized = String::new();
+    for ch in tag.trim().chars().flat_map(char::to_lowercase) {
+        if ch.is_ascii_alphanumeric() {
+            normalized.push(ch);
+        } else if !normalized.ends_with('-') {
+            normalized.push('-');
+        }
+    }
+    normalized.trim_matches('-').chars().take(32).collect()
+}
+
+/// The organisation domain of an address or domain:
+/// `news@mail.shop.example.co.uk` gives `example.co.uk`.
+pub fn sender_domain(address_or_domain: &str) -> String {
+    let domain = address_or_domain
+        .rsplit_once('@')
+        .map_or(address_or_domain, |(_, domain)| domain)
+        .trim()
+        .trim_end_matches('.')
+        .to_lowercase();
+    let labels: Vec<&str> = domain.split('.').filter(|label| !label.is_empty()).collect();
+    let keep = match labels.as_slice() {
+        [.., second, _] if labels.len() >= 3 && COMPOUND_SUFFIX_LABELS.contains(second) => 3,
+        _ => 2,
+    };
+    labels[labels.len().saturating_sub(keep)..].join(".")
+}
+
+/// Tag suggested for mail from `sender`: the name part of its domain.
+pub fn alias_tag_for_sender(sender: &str) -> String {
+    let domain = sender_domain(sender);
+    normalize_alias_tag(domain.split('.').next().unwrap_or_default())
+}
+
+/// Whether mail from `sender` is expected on an alias issued to `issued_to`.
+pub fn is_expected_alias_sender(issued_to: &str, sender: &str) -> bool {
+    sender_domain(issued_to) == sender_domain(sender)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_alias_and_matches_senders() {
+        assert_eq!(
+            plus_address("me+old@example.com", "Shop Co!").unwrap(),
+            "me+shop-co@example.com"
+        );
+        assert!(plus_address("me@example.com", "!!").is_err());
+
+        assert_eq!(sender_domain("news@mail.shop.example.co.uk"), "example.co.uk");
+        assert_eq!(alias_tag_for_sender("deals@em.bigstore.com"), "bigstore");
+        assert!(is_expected_alias_sender("bigstore.com", "orders@em.bigstore.com"));
+        assert!(!is_expected_alias_sender("bigstore.com", "promo@spam.example"));
+    }
+}
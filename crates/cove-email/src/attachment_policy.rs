@@ -0,0 +1,312 @@
+//! What may be saved or opened from received attachments.
+//!
+//! Both shells ask the same [`AttachmentPolicy`]: file types on the
+//! blocklist are refused, and ZIP archives are looked into so a blocked
+//! file cannot ride along inside one. Password-protected archives cannot be
+//! looked into and need a confirmation (or are refused outright). When the
+//! policy allows it, a refused file can still be released after an
+//! explicit "open anyway".
+
+use serde::{Deserialize, Serialize};
+
+/// Extensions refused unless configured otherwise: executables, scripts
+/// and installers that run code when opened.
+pub const DEFAULT_BLOCKED_EXTENSIONS: &[&str] = &[
+    "app", "bat", "cmd", "com", "cpl", "exe", "hta", "jar", "js", "jse", "lnk", "msi", "pif",
+    "ps1", "reg", "scpt", "scr", "sh", "vbe", "vbs", "wsf", "wsh",
+];
+
+/// Central directory entries read from one archive at most.
+const MAX_ARCHIVE_ENTRIES: usize = 4096;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttachmentPolicy {
+    blocked_extensions: Vec<String>,
+    allowed_extensions: Vec<String>,
+    /// Refuse password-protected archives instead of asking.
+    pub block_encrypted_archives: bool,
+    /// Offer "open anyway" for refused files.
+    pub allow_open_anyway: bool,
+}
+
+impl Default for AttachmentPolicy {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_BLOCKED_EXTENSIONS,
+            Vec::<String>::new(),
+            false,
+            false,
+        )
+    }
+}
+
+/// What a policy decided about one attachment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "disposition", rename_all = "snake_case")]
+pub enum AttachmentVerdict {
+    Allowed,
+    /// Released once the user confirms they understand `reason`.
+    Confirm {
+        reason: String,
+    },
+    /// Refused; `can_override` offers "open anyway".
+    Blocked {
+        reason: String,
+        can_override: bool,
+    },
+}
+
+impl AttachmentVerdict {
+    /// Whether the attachment may be released, `confirmed` being the
+    /// user's explicit go-ahead.
+    pub fn permits(&self, confirmed: bool) -> bool {
+        match self {
+            Self::Allowed => true,
+            Self::Confirm { .. } => confirmed,
+            Self::Blocked { can_override, .. } => *can_override && confirmed,
+        }
+    }
+
+    pub fn reason(&self) -> Option<&str> {
+        match self {
+            Self::Allowed => None,
+            Self::Confirm { reason } | Self::Blocked { reason, .. } => Some(reason),
+        }
+    }
+}
+
+/// Attachment bytes or the verdict holding them back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum AttachmentRelease {
+    Released {
+        content: Vec<u8>,
+    },
+    Held {
+        verdict: AttachmentVerdict,
+    },
+    /// The content was never downloaded.
+    Unavailable,
+}
+
+/// What could be read from a ZIP archive without a password.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArchiveSummary {
+    pub entries: Vec<String>,
+    pub encrypted: bool,
+}
+
+impl AttachmentPolicy {
+    /// A policy from extension lists; entries may carry a leading dot and
+    /// any case. The allowlist wins over the blocklist.
+    pub fn new<B, A>(
+        blocked: B,
+        allowed: A,
+        block_encrypted_archives: bool,
+        allow_open_anyway: bool,
+    ) -> Self
+    where
+        B: IntoIterator,
+        B::Item: AsRef<str>,
+        A: IntoIterator,
+        A::Item: AsRef<str>,
+    {
+        let normalize = |ext: &str| ext.trim().trim_start_matches('.').to_ascii_lowercase();
+        Self {
+            blocked_extensions: blocked
+                .into_iter()
+                .map(|ext| normalize(ext.as_ref()))
+                .filter(|ext| !ext.is_empty())
+                .collect(),
+            allowed_extensions: allowed
+                .into_iter()
+                .map(|ext| normalize(ext.as_ref()))
+                .filter(|ext| !ext.is_empty())
+                .collect(),
+            block_encrypted_archives,
+            allow_open_anyway,
+        }
+    }
+
+    /// Judge an attachment by name alone, before its content is at hand.
+    pub fn check_name(&self, file_name: &str) -> AttachmentVerdict {
+        match self.blocked_extension(file_name) {
+            Some(ext) => AttachmentVerdict::Blocked {
+                reason: format!(".{ext} files can run programs on this computer."),
+                can_override: self.allow_open_anyway,
+            },
+            None => AttachmentVerdict::Allowed,
+        }
+    }
+
+    /// Judge an attachment by name and content; ZIP archives are checked
+    /// for blocked entries and password protection.
+    pub fn evaluate(&self, file_name: &str, content: &[u8]) -> AttachmentVerdict {
+        let verdict = self.check_name(file_name);
+        if verdict != AttachmentVerdict::Allowed {
+            return verdict;
+        }
+        let Some(archive) = inspect_zip(content) else {
+            return AttachmentVerdict::Allowed;
+        };
+        if let Some(entry) = archive
+            .entries
+            .iter()
+            .find(|entry| self.blocked_extension(entry).is_some())
+        {
+            return AttachmentVerdict::Blocked {
+                reason: format!("The archive contains {entry}, a file type that can run programs."),
+                can_override: self.allow_open_anyway,
+            };
+        }
+        if archive.encrypted {
+            let reason = "The archive is password-protected, so its contents could not be checked."
+                .to_string();
+            return if self.block_encrypted_archives {
+                AttachmentVerdict::Blocked {
+                    reason,
+                    can_override: self.allow_open_anyway,
+                }
+            } else {
+                AttachmentVerdict::Confirm { reason }
+            };
+        }
+        AttachmentVerdict::Allowed
+    }
+
+    fn blocked_extension(&self, file_name: &str) -> Option<String> {
+        // Windows ignores trailing dots and spaces, so "setup.exe." runs.
+        let name = file_name.trim_end_matches(['.', ' ']);
+        let base = name.rsplit(['/', '\\']).next().unwrap_or(name);
+        let (_, ext) = base.rsplit_once('.')?;
+        let ext = ext.to_ascii_lowercase();
+        (!self.allowed_extensions.contains(&ext) && self.blocked_extensions.contains(&ext))
+            .then_some(ext)
+    }
+}
+
+/// Entry names and the encryption flag from a ZIP central directory, or
+/// `None` when `content` is not a readable ZIP archive.
+pub fn inspect_zip(content: &[u8]) -> Option<ArchiveSummary> {
+    if !content.starts_with(b"PK\x03\x04") {
+        return None;
+    }
+    // End of central directory: 22 bytes plus a comment of up to 64 KiB.
+    let search_from = content.len().saturating_sub(22 + u16::MAX as usize);
+    let eocd = (search_from..=content.len().checked_sub(22)?)
+        .rev()
+        .find(|&at| content[at..].starts_with(b"PK\x05\x06"))?;
+    let entry_count = read_u16(content, eocd + 10)? as usize;
+    let mut at = read_u32(content, eocd + 16)? as usize;
+
+    let mut summary = ArchiveSummary::default();
+    for _ in 0..entry_count.min(MAX_ARCHIVE_ENTRIES) {
+        if !content.get(at..)?.starts_with(b"PK\x01\x02") {
+            break;
+        }
+        let flags = read_u16(content, at + 8)?;
+        let name_len = read_u16(content, at + 28)? as usize;
+        let extra_len = read_u16(content, at + 30)? as usize;
+        let comment_len = read_u16(content, at + 32)? as usize;
+        let name = content.get(at + 46..at + 46 + name_len)?;
+        // Bit 0 of the general purpose flags marks an encrypted entry.
+        summary.encrypted |= flags & 1 != 0;
+        summary
+            .entries
+            .push(String::from_utf8_lossy(name).into_owned());
+        at += 46 + name_len + extra_len + comment_len;
+    }
+    Some(summary)
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A stored (uncompressed) single-entry archive.
+    fn zip(name: &str, flags: u16) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(b"PK\x03\x04");
+        out.extend_from_slice(&[20, 0]);
+        out.extend_from_slice(&flags.to_le_bytes());
+        out.extend_from_slice(&[0; 18]);
+        out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        out.extend_from_slice(&[0, 0]);
+        out.extend_from_slice(name.as_bytes());
+
+        let directory = out.len() as u32;
+        out.extend_from_slice(b"PK\x01\x02");
+        out.extend_from_slice(&[20, 0, 20, 0]);
+        out.extend_from_slice(&flags.to_le_bytes());
+        out.extend_from_slice(&[0; 18]);
+        out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        out.extend_from_slice(&[0; 16]);
+        out.extend_from_slice(name.as_bytes());
+        let directory_len = out.len() as u32 - directory;
+
+        out.extend_from_slice(b"PK\x05\x06");
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(&1u16.to_le_bytes());
+        out.extend_from_slice(&1u16.to_le_bytes());
+        out.extend_from_slice(&directory_len.to_le_bytes());
+        out.extend_from_slice(&directory.to_le_bytes());
+        out.extend_from_slice(&[0, 0]);
+        out
+    }
+
+    #[test]
+    fn blocks_by_extension_unless_allowed() {
+        let policy = AttachmentPolicy::default();
+        assert!(!policy.check_name("Invoice.pdf.EXE").permits(true));
+        assert!(!policy.check_name("setup.exe. ").permits(true));
+        assert!(policy.check_name("report.pdf").permits(false));
+
+        let policy = AttachmentPolicy::new(["exe"], [".EXE"], false, false);
+        assert_eq!(policy.check_name("tool.exe"), AttachmentVerdict::Allowed);
+    }
+
+    #[test]
+    fn looks_inside_zip_archives() {
+        let policy = AttachmentPolicy::default();
+        assert_eq!(
+            policy.evaluate("photos.zip", &zip("beach.jpg", 0)),
+            AttachmentVerdict::Allowed
+        );
+        assert!(matches!(
+            policy.evaluate("photos.zip", &zip("beach.jpg.scr", 0)),
+            AttachmentVerdict::Blocked {
+                can_override: false,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn password_protected_archives_need_confirmation() {
+        let archive = zip("contract.docx", 1);
+        assert!(inspect_zip(&archive).is_some_and(|summary| summary.encrypted));
+
+        let verdict = AttachmentPolicy::default().evaluate("contract.zip", &archive);
+        assert!(matches!(verdict, AttachmentVerdict::Confirm { .. }));
+        assert!(!verdict.permits(false) && verdict.permits(true));
+
+        let strict =
+            AttachmentPolicy::new(DEFAULT_BLOCKED_EXTENSIONS, Vec::<String>::new(), true, true);
+        let verdict = strict.evaluate("contract.zip", &archive);
+        assert!(matches!(
+            verdict,
+            AttachmentVerdict::Blocked {
+                can_override: true,
+                ..
+            }
+        ));
+    }
+}
@@ -1,21 +1,24 @@
-use crate::EmailError;
+use crate::imap_pool::ImapPool;
+use crate::protocol_log::send_logged;
+use crate::smtp_pool::{SmtpKey, SmtpPool};
+use crate::{sanitize_html, EmailError};
 use cove_core::{
-    Account, MailAddress, MailAttachment, MailFlags, MailFolder, MailMessage, Provider,
+    Account, HttpFailure, HttpFailureKind, MailAddress, MailAttachment, MailFlags, MailFolder,
+    MailMessage, ProtocolKind, Provider,
 };
 use async_trait::async_trait;
 use base64::engine::general_purpose::{STANDARD, URL_SAFE, URL_SAFE_NO_PAD};
 use base64::Engine;
 use chrono::{TimeZone, Utc};
 use lettre::message::{header, Attachment, Mailbox, MultiPart, SinglePart};
-use lettre::{
-    transport::smtp::authentication::Credentials, AsyncSmtpTransport, AsyncTransport, Message,
-    Tokio1Executor,
-};
+use lettre::Message;
 use mailparse::{parse_mail, ParsedMail};
 use regex::Regex;
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::task;
 use uuid::Uuid;
@@ -31,6 +34,13 @@ pub struct ProtocolSettings {
     pub access_token: Option<String>,
     pub password: Option<String>,
     pub offline_sync_limit: Option<cove_core::OfflineSyncLimit>,
+    /// Fetch headers and flags only, set by the service while disk space
+    /// is low; never stored with the account.
+    #[serde(skip)]
+    pub headers_only: bool,
+    /// How fetched HTML bodies are cleaned, set by the service from config.
+    #[serde(skip)]
+    pub sanitizer: cove_core::SanitizerPolicy,
 }
 
 impl std::fmt::Debug for ProtocolSettings {
@@ -45,6 +55,8 @@ impl std::fmt::Debug for ProtocolSettings {
             .field("access_token", &self.access_token.as_ref().map(|_| "[REDACTED]"))
             .field("password", &self.password.as_ref().map(|_| "[REDACTED]"))
             .field("offline_sync_limit", &self.offline_sync_limit)
+            .field("headers_only", &self.headers_only)
+            .field("sanitizer", &self.sanitizer)
             .finish()
     }
 }
@@ -57,6 +69,23 @@ pub struct OutgoingAttachment {
     pub inline: bool,
 }
 
+impl OutgoingAttachment {
+    pub fn from_bytes(file_name: &str, mime_type: &str, bytes: &[u8]) -> Self {
+        Self {
+            file_name: file_name.to_string(),
+            mime_type: mime_type.to_string(),
+            content_base64: STANDARD.encode(bytes),
+            inline: false,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EmailError> {
+        STANDARD
+            .decode(self.content_base64.as_bytes())
+            .map_err(|err| EmailError::Build(format!("invalid attachment base64: {err}")))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OutgoingMail {
     pub from: MailAddress,
@@ -68,6 +97,21 @@ pub struct OutgoingMail {
     pub body_text: String,
     pub body_html: Option<String>,
     pub attachments: Vec<OutgoingAttachment>,
+    /// Message-ID to send with, angle brackets included; generated by the
+    /// transport when `None`. EWS always assigns its own.
+    #[serde(default)]
+    pub message_id: Option<String>,
+}
+
+/// What the server said when it accepted a message, for send telemetry.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SendReceipt {
+    pub server_response: String,
+    /// True when an open, authenticated SMTP connection was reused instead
+    /// of connecting for this send.
+    pub reused_connection: bool,
+    /// True when MAIL FROM, RCPT TO and DATA went out as one pipelined batch.
+    pub pipelined: bool,
 }
 
 /// Messages plus pre-extracted attachment content returned by [`EmailBackend::fetch_recent`].
@@ -75,10 +119,40 @@ pub struct FetchResult {
     pub messages: Vec<MailMessage>,
     /// `(attachment_id, message_id, raw_bytes)` for every attachment whose content was available.
     pub attachment_content: Vec<(Uuid, Uuid, Vec<u8>)>,
+    /// The IMAP folder's UIDVALIDITY at fetch time; `None` for other protocols.
+    pub uid_validity: Option<u32>,
+}
+
+/// One page of a folder's older mail, returned by [`EmailBackend::fetch_page`].
+pub struct FetchPage {
+    pub result: FetchResult,
+    /// Where the following page starts; `None` once the folder is exhausted.
+    pub next_cursor: Option<String>,
+    /// Messages in the folder, when the server reports it.
+    pub total: Option<u64>,
+}
+
+/// Operations a backend carries out on the server for an account, so the
+/// UI can hide or grey out the rest instead of failing at runtime.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackendCapabilities {
+    /// Move messages between folders, which archiving relies on.
+    pub move_messages: bool,
+    /// Labels live on the server; otherwise they are local to this device.
+    pub server_labels: bool,
+    /// Snoozed mail is hidden on the server; otherwise snooze is local.
+    pub server_snooze: bool,
+    /// New mail is pushed (IMAP IDLE) rather than found by polling.
+    pub push: bool,
+    /// Sync can fetch headers and flags only while disk space is low.
+    pub headers_only_sync: bool,
 }
 
 #[async_trait]
 pub trait EmailBackend: Send + Sync {
+    /// What this backend supports for `account`; some depend on the provider.
+    fn capabilities(&self, account: &Account) -> BackendCapabilities;
+
     async fn sync_folders(
         &self,
         account: &Account,
@@ -93,12 +167,23 @@ pub trait EmailBackend: Send + Sync {
         limit: usize,
     ) -> Result<FetchResult, EmailError>;
 
+    /// Fetch up to `limit` messages older than `cursor`, newest first; the
+    /// first page (`cursor` of `None`) holds the newest mail in the folder.
+    async fn fetch_page(
+        &self,
+        account: &Account,
+        settings: &ProtocolSettings,
+        folder_path: &str,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<FetchPage, EmailError>;
+
     async fn send_mail(
         &self,
         account: &Account,
         settings: &ProtocolSettings,
         outgoing: &OutgoingMail,
-    ) -> Result<(), EmailError>;
+    ) -> Result<SendReceipt, EmailError>;
 
     async fn start_idle(
         &self,
@@ -106,13 +191,81 @@ pub trait EmailBackend: Send + Sync {
         settings: &ProtocolSettings,
         folder_path: &str,
     ) -> Result<(), EmailError>;
+
+    /// Permanently delete messages in `folder_path` by remote id. IMAP needs
+    /// the folder's `uid_validity` as recorded when the ids were fetched and
+    /// refuses to delete when the server's differs; other protocols ignore it.
+    async fn delete_messages(
+        &self,
+        account: &Account,
+        settings: &ProtocolSettings,
+        folder_path: &str,
+        remote_ids: &[String],
+        uid_validity: Option<u32>,
+    ) -> Result<(), EmailError>;
+
+    /// Take messages out of `folder_path` and into the account's archive.
+    /// Gmail archives by dropping the folder's label and ignores `archive`;
+    /// every other protocol needs the archive folder. IMAP checks
+    /// `uid_validity` the same way [`Self::delete_messages`] does.
+    async fn archive_messages(
+        &self,
+        account: &Account,
+        settings: &ProtocolSettings,
+        folder_path: &str,
+        remote_ids: &[String],
+        archive: Option<&MailFolder>,
+        uid_validity: Option<u32>,
+    ) -> Result<(), EmailError>;
 }
 
-#[derive(Debug, Default)]
-pub struct ImapSmtpBackend;
+/// IMAP sessions are drawn from a per-account pool shared by every clone of
+/// the backend; IDLE always uses a dedicated connection. Authenticated SMTP
+/// connections are pooled the same way so bulk sends (scheduled queue,
+/// digests) reuse them and pipeline each envelope.
+#[derive(Default)]
+pub struct ImapSmtpBackend {
+    pool: Arc<ImapPool>,
+    smtp: SmtpPool,
+}
+
+fn smtp_key(settings: &ProtocolSettings, provider: &Provider) -> Result<SmtpKey, EmailError> {
+    let host = settings
+        .smtp_host
+        .clone()
+        .ok_or_else(|| EmailError::Data("missing smtp_host".to_string()))?;
+    let xoauth2 = uses_smtp_xoauth2(settings, provider);
+    let secret = if xoauth2 {
+        settings.access_token.clone()
+    } else {
+        settings
+            .password
+            .clone()
+            .or_else(|| settings.access_token.clone())
+    };
+    Ok(SmtpKey {
+        host,
+        port: settings.smtp_port.unwrap_or(465),
+        username: settings.username.clone(),
+        secret,
+        xoauth2,
+    })
+}
 
 #[async_trait]
 impl EmailBackend for ImapSmtpBackend {
+    fn capabilities(&self, account: &Account) -> BackendCapabilities {
+        let gmail = account.provider == Provider::Gmail;
+        BackendCapabilities {
+            move_messages: true,
+            server_labels: gmail,
+            server_snooze: false,
+            // Gmail is polled; `start_idle` skips it.
+            push: !gmail,
+            headers_only_sync: true,
+        }
+    }
+
     async fn sync_folders(
         &self,
         account: &Account,
@@ -125,8 +278,9 @@ impl EmailBackend for ImapSmtpBackend {
         let account_id = account.id;
         let provider = account.provider.clone();
         let settings = settings.clone();
+        let pool = self.pool.clone();
 
-        task::spawn_blocking(move || sync_folders_imap(account_id, provider, &settings))
+        task::spawn_blocking(move || sync_folders_imap(&pool, account_id, provider, &settings))
             .await
             .map_err(|err| EmailError::Data(format!("imap folder sync task failed: {err}")))?
     }
@@ -146,9 +300,42 @@ impl EmailBackend for ImapSmtpBackend {
         let provider = account.provider.clone();
         let folder = folder_path.to_string();
         let settings = settings.clone();
+        let pool = self.pool.clone();
+
+        task::spawn_blocking(move || {
+            fetch_recent_imap(&pool, account_id, provider, &settings, &folder, limit)
+        })
+        .await
+        .map_err(|err| EmailError::Data(format!("imap fetch task failed: {err}")))?
+    }
+
+    async fn fetch_page(
+        &self,
+        account: &Account,
+        settings: &ProtocolSettings,
+        folder_path: &str,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<FetchPage, EmailError> {
+        if account.provider == Provider::Gmail {
+            return fetch_gmail_page(account, settings, folder_path, cursor, limit).await;
+        }
+
+        let before_uid = cursor
+            .map(|cursor| {
+                cursor.parse::<u32>().map_err(|_| {
+                    EmailError::Data(format!("invalid IMAP backfill cursor: {cursor}"))
+                })
+            })
+            .transpose()?;
+        let account_id = account.id;
+        let provider = account.provider.clone();
+        let folder = folder_path.to_string();
+        let settings = settings.clone();
+        let pool = self.pool.clone();
 
         task::spawn_blocking(move || {
-            fetch_recent_imap(account_id, provider, &settings, &folder, limit)
+            fetch_page_imap(&pool, account_id, provider, &settings, &folder, before_uid, limit)
         })
         .await
         .map_err(|err| EmailError::Data(format!("imap fetch task failed: {err}")))?
@@ -156,16 +343,10 @@ impl EmailBackend for ImapSmtpBackend {
 
     async fn send_mail(
         &self,
-        _account: &Account,
+        account: &Account,
         settings: &ProtocolSettings,
         outgoing: &OutgoingMail,
-    ) -> Result<(), EmailError> {
-        let smtp_host = settings
-            .smtp_host
-            .as_deref()
-            .ok_or_else(|| EmailError::Data("missing smtp_host".to_string()))?;
-        let smtp_port = settings.smtp_port.unwrap_or(465);
-
+    ) -> Result<SendReceipt, EmailError> {
         let from = to_mailbox(&outgoing.from)?;
         let to = outgoing
             .to
@@ -175,7 +356,8 @@ impl EmailBackend for ImapSmtpBackend {
 
         let mut builder = Message::builder()
             .from(from)
-            .subject(outgoing.subject.clone());
+            .subject(outgoing.subject.clone())
+            .message_id(outgoing.message_id.clone());
 
         for mailbox in to {
             builder = builder.to(mailbox);
@@ -209,11 +391,7 @@ impl EmailBackend for ImapSmtpBackend {
             let mut mixed = MultiPart::mixed().multipart(alternative);
             for attachment in &outgoing.attachments {
                 let _ = attachment.inline;
-                let bytes = STANDARD
-                    .decode(attachment.content_base64.as_bytes())
-                    .map_err(|err| {
-                        EmailError::Build(format!("invalid attachment base64: {err}"))
-                    })?;
+                let bytes = attachment.to_bytes()?;
                 let mime = attachment.mime_type.parse().map_err(|err| {
                     EmailError::Build(format!("invalid attachment mime type: {err}"))
                 })?;
@@ -227,46 +405,115 @@ impl EmailBackend for ImapSmtpBackend {
             .multipart(payload)
             .map_err(|err| EmailError::Build(err.to_string()))?;
 
-        let mut transport = AsyncSmtpTransport::<Tokio1Executor>::relay(smtp_host)
-            .map_err(|err| EmailError::Smtp(err.to_string()))?
-            .port(smtp_port);
+        let mut connection = self
+            .smtp
+            .checkout(smtp_key(settings, &account.provider)?)
+            .await?;
+        let result = connection
+            .send(message.envelope(), &message.formatted())
+            .await;
+        let reused_connection = connection.reused;
+        let pipelined = connection.pipelining();
+        self.smtp.checkin(connection);
+        let response = result?;
+
+        Ok(SendReceipt {
+            server_response: format!(
+                "{} {}",
+                response.code(),
+                response.message().collect::<Vec<_>>().join(" ")
+            ),
+            reused_connection,
+            pipelined,
+        })
+    }
 
-        let auth_secret = settings
-            .password
-            .as_ref()
-            .or(settings.access_token.as_ref());
-        if let Some(secret) = auth_secret {
-            transport =
-                transport.credentials(Credentials::new(settings.username.clone(), secret.clone()));
+    async fn start_idle(
+        &self,
+        account: &Account,
+        settings: &ProtocolSettings,
+        folder_path: &str,
+    ) -> Result<(), EmailError> {
+        if account.provider == Provider::Gmail {
+            return Ok(());
         }
 
-        transport
-            .build()
-            .send(message)
+        let provider = account.provider.clone();
+        let folder = folder_path.to_string();
+        let settings = settings.clone();
+        task::spawn_blocking(move || start_idle_imap(provider, &settings, &folder))
             .await
-            .map_err(|err| EmailError::Smtp(err.to_string()))?;
+            .map_err(|err| EmailError::Data(format!("imap idle task failed: {err}")))??;
 
         Ok(())
     }
 
-    async fn start_idle(
+    async fn delete_messages(
         &self,
         account: &Account,
         settings: &ProtocolSettings,
         folder_path: &str,
+        remote_ids: &[String],
+        uid_validity: Option<u32>,
     ) -> Result<(), EmailError> {
+        if remote_ids.is_empty() {
+            return Ok(());
+        }
         if account.provider == Provider::Gmail {
+            return delete_gmail_messages(settings, remote_ids).await;
+        }
+
+        let provider = account.provider.clone();
+        let folder = folder_path.to_string();
+        let uids = remote_ids.join(",");
+        let settings = settings.clone();
+        let pool = self.pool.clone();
+
+        task::spawn_blocking(move || {
+            delete_imap_messages(&pool, provider, &settings, &folder, &uids, uid_validity)
+        })
+        .await
+        .map_err(|err| EmailError::Data(format!("imap delete task failed: {err}")))?
+    }
+
+    async fn archive_messages(
+        &self,
+        account: &Account,
+        settings: &ProtocolSettings,
+        folder_path: &str,
+        remote_ids: &[String],
+        archive: Option<&MailFolder>,
+        uid_validity: Option<u32>,
+    ) -> Result<(), EmailError> {
+        if remote_ids.is_empty() {
             return Ok(());
         }
+        if account.provider == Provider::Gmail {
+            return archive_gmail_messages(settings, folder_path, remote_ids).await;
+        }
+        let archive = archive
+            .ok_or_else(|| EmailError::Data("account has no archive folder".to_string()))?;
 
         let provider = account.provider.clone();
         let folder = folder_path.to_string();
+        let target = archive.path.clone();
+        let uids = remote_ids.join(",");
         let settings = settings.clone();
-        task::spawn_blocking(move || start_idle_imap(provider, &settings, &folder))
-            .await
-            .map_err(|err| EmailError::Data(format!("imap idle task failed: {err}")))??;
+        let pool = self.pool.clone();
 
-        Ok(())
+        task::spawn_blocking(move || {
+            move_imap_messages(
+                &pool,
+                provider,
+                &settings,
+                &folder,
+                &uids,
+                &target,
+                uid_validity,
+            )
+        })
+        .await
+        .map_err(|err| EmailError::Data(format!("imap archive task failed: {err}")))?
     }
 }
 
@@ -281,61 +528,16 @@ impl EwsBackend {
             http: reqwest::Client::new(),
         }
     }
-}
 
-#[async_trait]
-impl EmailBackend for EwsBackend {
-    async fn sync_folders(
-        &self,
-        account: &Account,
-        settings: &ProtocolSettings,
-    ) -> Result<Vec<MailFolder>, EmailError> {
-        let endpoint = settings
-            .endpoint
-            .as_deref()
-            .ok_or_else(|| EmailError::Data("missing EWS endpoint".to_string()))?;
-
-        let mut request = self
-            .http
-            .post(endpoint)
-            .header("Content-Type", "text/xml")
-            .body(
-                r#"<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/" xmlns:t="http://schemas.microsoft.com/exchange/services/2006/types">
-<soap:Body>
-  <FindFolder xmlns="http://schemas.microsoft.com/exchange/services/2006/messages" Traversal="Shallow">
-    <FolderShape><t:BaseShape>Default</t:BaseShape></FolderShape>
-    <ParentFolderIds><t:DistinguishedFolderId Id="msgfolderroot"/></ParentFolderIds>
-  </FindFolder>
-</soap:Body>
-</soap:Envelope>"#,
-            );
-
-        request = apply_ews_auth(request, settings);
-        let response = request.send().await?;
-
-        if response.status() != StatusCode::OK {
-            return Err(EmailError::Data(format!(
-                "EWS sync failed with status {}",
-                response.status()
-            )));
-        }
-
-        let text = response.text().await?;
-        let folders = parse_ews_folders(account.id, &text);
-        if folders.is_empty() {
-            return Ok(default_ews_folders(account.id));
-        }
-
-        Ok(folders)
-    }
-
-    async fn fetch_recent(
+    /// `FindItem` returns newest first; `offset` skips that many items.
+    async fn find_items(
         &self,
         account: &Account,
         settings: &ProtocolSettings,
         folder_path: &str,
+        offset: usize,
         limit: usize,
-    ) -> Result<FetchResult, EmailError> {
+    ) -> Result<FetchPage, EmailError> {
         let endpoint = settings
             .endpoint
             .as_deref()
@@ -363,8 +565,11 @@ impl EmailBackend for EwsBackend {
 <soap:Body>
   <FindItem xmlns="http://schemas.microsoft.com/exchange/services/2006/messages" Traversal="Shallow">
     <ItemShape><t:BaseShape>AllProperties</t:BaseShape></ItemShape>
-    <IndexedPageItemView MaxEntriesReturned="{limit}" Offset="0" BasePoint="Beginning"/>
+    <IndexedPageItemView MaxEntriesReturned="{limit}" Offset="{offset}" BasePoint="Beginning"/>
 {restriction_block}
+    <SortOrder>
+      <t:FieldOrder Order="Descending"><t:FieldURI FieldURI="item:DateTimeReceived"/></t:FieldOrder>
+    </SortOrder>
     <ParentFolderIds><t:DistinguishedFolderId Id="{folder}"/></ParentFolderIds>
   </FindItem>
 </soap:Body>
@@ -377,28 +582,107 @@ impl EmailBackend for EwsBackend {
             .header("Content-Type", "text/xml")
             .body(soap);
         request = apply_ews_auth(request, settings);
-        let response = request.send().await?;
+        let response = send_logged(request, ProtocolKind::Ews, &settings.username).await?;
 
         if response.status() != StatusCode::OK {
-            return Err(EmailError::Data(format!(
-                "EWS fetch failed with status {}",
-                response.status()
-            )));
+            return Err(http_status_error("EWS fetch", response.status()));
         }
 
         let text = response.text().await?;
-        Ok(FetchResult {
-            messages: parse_ews_messages(account.id, folder_path, &text),
-            attachment_content: Vec::new(), // EWS attachment content not yet implemented
+        let messages = parse_ews_messages(account.id, folder_path, &text);
+        let next_cursor = (messages.len() >= limit).then(|| (offset + limit).to_string());
+        Ok(FetchPage {
+            result: FetchResult {
+                messages,
+                attachment_content: Vec::new(), // EWS attachment content not yet implemented
+                uid_validity: None,
+            },
+            next_cursor,
+            total: None,
         })
     }
+}
+
+#[async_trait]
+impl EmailBackend for EwsBackend {
+    fn capabilities(&self, _account: &Account) -> BackendCapabilities {
+        BackendCapabilities {
+            move_messages: true,
+            ..BackendCapabilities::default()
+        }
+    }
+
+    async fn sync_folders(
+        &self,
+        account: &Account,
+        settings: &ProtocolSettings,
+    ) -> Result<Vec<MailFolder>, EmailError> {
+        let endpoint = settings
+            .endpoint
+            .as_deref()
+            .ok_or_else(|| EmailError::Data("missing EWS endpoint".to_string()))?;
+
+        let mut request = self
+            .http
+            .post(endpoint)
+            .header("Content-Type", "text/xml")
+            .body(
+                r#"<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/" xmlns:t="http://schemas.microsoft.com/exchange/services/2006/types">
+<soap:Body>
+  <FindFolder xmlns="http://schemas.microsoft.com/exchange/services/2006/messages" Traversal="Shallow">
+    <FolderShape><t:BaseShape>Default</t:BaseShape></FolderShape>
+    <ParentFolderIds><t:DistinguishedFolderId Id="msgfolderroot"/></ParentFolderIds>
+  </FindFolder>
+</soap:Body>
+</soap:Envelope>"#,
+            );
+
+        request = apply_ews_auth(request, settings);
+        let response = send_logged(request, ProtocolKind::Ews, &settings.username).await?;
+
+        if response.status() != StatusCode::OK {
+            return Err(http_status_error("EWS sync", response.status()));
+        }
+
+        let text = response.text().await?;
+        let folders = parse_ews_folders(account.id, &text);
+        if folders.is_empty() {
+            return Ok(default_ews_folders(account.id));
+        }
+
+        Ok(folders)
+    }
+
+    async fn fetch_recent(
+        &self,
+        account: &Account,
+        settings: &ProtocolSettings,
+        folder_path: &str,
+        limit: usize,
+    ) -> Result<FetchResult, EmailError> {
+        self.find_items(account, settings, folder_path, 0, limit)
+            .await
+            .map(|page| page.result)
+    }
+
+    async fn fetch_page(
+        &self,
+        account: &Account,
+        settings: &ProtocolSettings,
+        folder_path: &str,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<FetchPage, EmailError> {
+        let offset = parse_offset_cursor(cursor)?;
+        self.find_items(account, settings, folder_path, offset, limit).await
+    }
 
     async fn send_mail(
         &self,
         _account: &Account,
         settings: &ProtocolSettings,
         outgoing: &OutgoingMail,
-    ) -> Result<(), EmailError> {
+    ) -> Result<SendReceipt, EmailError> {
         let endpoint = settings
             .endpoint
             .as_deref()
@@ -452,16 +736,17 @@ impl EmailBackend for EwsBackend {
             .header("Content-Type", "text/xml")
             .body(soap);
         request = apply_ews_auth(request, settings);
-        let response = request.send().await?;
+        let response = send_logged(request, ProtocolKind::Ews, &settings.username).await?;
 
         if response.status() != StatusCode::OK {
-            return Err(EmailError::Data(format!(
-                "EWS send failed with status {}",
-                response.status()
-            )));
+            return Err(http_status_error("EWS send", response.status()));
         }
 
-        Ok(())
+        Ok(SendReceipt {
+            server_response: response.status().to_string(),
+            reused_connection: false,
+            pipelined: false,
+        })
     }
 
     async fn start_idle(
@@ -472,6 +757,104 @@ impl EmailBackend for EwsBackend {
     ) -> Result<(), EmailError> {
         Ok(())
     }
+
+    async fn delete_messages(
+        &self,
+        _account: &Account,
+        settings: &ProtocolSettings,
+        _folder_path: &str,
+        remote_ids: &[String],
+        _uid_validity: Option<u32>,
+    ) -> Result<(), EmailError> {
+        if remote_ids.is_empty() {
+            return Ok(());
+        }
+        let endpoint = settings
+            .endpoint
+            .as_deref()
+            .ok_or_else(|| EmailError::Data("missing EWS endpoint".to_string()))?;
+
+        let item_ids = remote_ids
+            .iter()
+            .map(|id| format!(r#"<t:ItemId Id="{}"/>"#, escape_xml(id)))
+            .collect::<Vec<_>>()
+            .join("");
+        let soap = format!(
+            r#"<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/" xmlns:t="http://schemas.microsoft.com/exchange/services/2006/types">
+<soap:Body>
+  <DeleteItem xmlns="http://schemas.microsoft.com/exchange/services/2006/messages" DeleteType="HardDelete">
+    <ItemIds>{item_ids}</ItemIds>
+  </DeleteItem>
+</soap:Body>
+</soap:Envelope>"#
+        );
+
+        let mut request = self
+            .http
+            .post(endpoint)
+            .header("Content-Type", "text/xml")
+            .body(soap);
+        request = apply_ews_auth(request, settings);
+        let response = send_logged(request, ProtocolKind::Ews, &settings.username).await?;
+
+        if response.status() != StatusCode::OK {
+            return Err(http_status_error("EWS delete", response.status()));
+        }
+        Ok(())
+    }
+
+    async fn archive_messages(
+        &self,
+        _account: &Account,
+        settings: &ProtocolSettings,
+        _folder_path: &str,
+        remote_ids: &[String],
+        archive: Option<&MailFolder>,
+        _uid_validity: Option<u32>,
+    ) -> Result<(), EmailError> {
+        if remote_ids.is_empty() {
+            return Ok(());
+        }
+        let endpoint = settings
+            .endpoint
+            .as_deref()
+            .ok_or_else(|| EmailError::Data("missing EWS endpoint".to_string()))?;
+        // A discovered archive folder is addressed by its id; otherwise fall
+        // back to the mailbox's well-known Archive folder.
+        let target = match archive {
+            Some(folder) => format!(r#"<t:FolderId Id="{}"/>"#, escape_xml(&folder.remote_id)),
+            None => r#"<t:DistinguishedFolderId Id="archive"/>"#.to_string(),
+        };
+
+        let item_ids = remote_ids
+            .iter()
+            .map(|id| format!(r#"<t:ItemId Id="{}"/>"#, escape_xml(id)))
+            .collect::<Vec<_>>()
+            .join("");
+        let soap = format!(
+            r#"<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/" xmlns:t="http://schemas.microsoft.com/exchange/services/2006/types">
+<soap:Body>
+  <MoveItem xmlns="http://schemas.microsoft.com/exchange/services/2006/messages">
+    <ToFolderId>{target}</ToFolderId>
+    <ItemIds>{item_ids}</ItemIds>
+  </MoveItem>
+</soap:Body>
+</soap:Envelope>"#
+        );
+
+        let mut request = self
+            .http
+            .post(endpoint)
+            .header("Content-Type", "text/xml")
+            .body(soap);
+        request = apply_ews_auth(request, settings);
+        let response = send_logged(request, ProtocolKind::Ews, &settings.username).await?;
+
+        if response.status() != StatusCode::OK {
+            return Err(http_status_error("EWS archive", response.status()));
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Default)]
@@ -485,43 +868,16 @@ impl JmapBackend {
             http: reqwest::Client::new(),
         }
     }
-}
-
-#[async_trait]
-impl EmailBackend for JmapBackend {
-    async fn sync_folders(
-        &self,
-        account: &Account,
-        settings: &ProtocolSettings,
-    ) -> Result<Vec<MailFolder>, EmailError> {
-        let (api_url, mail_account, _) = jmap_session(&self.http, settings).await?;
-        let response = jmap_request(
-            &self.http,
-            &api_url,
-            settings,
-            serde_json::json!({
-                "using": ["urn:ietf:params:jmap:core", "urn:ietf:params:jmap:mail"],
-                "methodCalls": [
-                    ["Mailbox/query", {"accountId": mail_account, "sort": [{"property":"name"}]}, "m1"],
-                    ["Mailbox/get", {
-                        "accountId": mail_account,
-                        "#ids": {"resultOf": "m1", "name": "Mailbox/query", "path": "/ids"}
-                    }, "m2"]
-                ]
-            }),
-        )
-        .await?;
-
-        Ok(parse_jmap_folders(account.id, &response))
-    }
 
-    async fn fetch_recent(
+    /// Query newest first, starting `position` messages in.
+    async fn query_emails(
         &self,
         account: &Account,
         settings: &ProtocolSettings,
         folder_path: &str,
+        position: usize,
         limit: usize,
-    ) -> Result<FetchResult, EmailError> {
+    ) -> Result<FetchPage, EmailError> {
         let (api_url, mail_account, _) = jmap_session(&self.http, settings).await?;
         
         let mut filter = serde_json::json!({
@@ -548,12 +904,14 @@ impl EmailBackend for JmapBackend {
                         "accountId": mail_account,
                         "filter": filter,
                         "sort": [{"property":"receivedAt", "isAscending": false}],
-                        "limit": limit
+                        "position": position,
+                        "limit": limit,
+                        "calculateTotal": true
                     }, "m2"],
                     ["Email/get", {
                         "accountId": mail_account,
                         "#ids": {"resultOf":"m2", "name":"Email/query", "path":"/ids"},
-                        "properties": ["id","threadId","subject","from","to","cc","bcc","replyTo","preview","keywords","receivedAt","sentAt","textBody","htmlBody","bodyValues"],
+                        "properties": ["id","threadId","subject","from","to","cc","bcc","replyTo","preview","keywords","receivedAt","sentAt","textBody","htmlBody","bodyValues","header:Delivered-To:asText","header:X-Original-To:asText"],
                         "fetchTextBodyValues": true,
                         "fetchHTMLBodyValues": true
                     }, "m3"]
@@ -562,18 +920,91 @@ impl EmailBackend for JmapBackend {
         )
         .await?;
 
-        Ok(FetchResult {
-            messages: parse_jmap_messages(account.id, folder_path, &response),
-            attachment_content: Vec::new(), // JMAP attachment content not yet implemented
+        let messages =
+            parse_jmap_messages(account.id, folder_path, &response, &settings.sanitizer);
+        let total = jmap_query_total(&response);
+        let fetched = position + messages.len();
+        let more = match total {
+            Some(total) => (fetched as u64) < total,
+            None => messages.len() >= limit,
+        };
+        Ok(FetchPage {
+            result: FetchResult {
+                messages,
+                attachment_content: Vec::new(), // JMAP attachment content not yet implemented
+                uid_validity: None,
+            },
+            next_cursor: more.then(|| fetched.to_string()),
+            total,
         })
     }
+}
+
+#[async_trait]
+impl EmailBackend for JmapBackend {
+    fn capabilities(&self, _account: &Account) -> BackendCapabilities {
+        BackendCapabilities {
+            move_messages: true,
+            ..BackendCapabilities::default()
+        }
+    }
+
+    async fn sync_folders(
+        &self,
+        account: &Account,
+        settings: &ProtocolSettings,
+    ) -> Result<Vec<MailFolder>, EmailError> {
+        let (api_url, mail_account, _) = jmap_session(&self.http, settings).await?;
+        let response = jmap_request(
+            &self.http,
+            &api_url,
+            settings,
+            serde_json::json!({
+                "using": ["urn:ietf:params:jmap:core", "urn:ietf:params:jmap:mail"],
+                "methodCalls": [
+                    ["Mailbox/query", {"accountId": mail_account, "sort": [{"property":"name"}]}, "m1"],
+                    ["Mailbox/get", {
+                        "accountId": mail_account,
+                        "#ids": {"resultOf": "m1", "name": "Mailbox/query", "path": "/ids"}
+                    }, "m2"]
+                ]
+            }),
+        )
+        .await?;
+
+        Ok(parse_jmap_folders(account.id, &response))
+    }
+
+    async fn fetch_recent(
+        &self,
+        account: &Account,
+        settings: &ProtocolSettings,
+        folder_path: &str,
+        limit: usize,
+    ) -> Result<FetchResult, EmailError> {
+        self.query_emails(account, settings, folder_path, 0, limit)
+            .await
+            .map(|page| page.result)
+    }
+
+    async fn fetch_page(
+        &self,
+        account: &Account,
+        settings: &ProtocolSettings,
+        folder_path: &str,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<FetchPage, EmailError> {
+        let position = parse_offset_cursor(cursor)?;
+        self.query_emails(account, settings, folder_path, position, limit).await
+    }
 
     async fn send_mail(
         &self,
         _account: &Account,
         settings: &ProtocolSettings,
         outgoing: &OutgoingMail,
-    ) -> Result<(), EmailError> {
+    ) -> Result<SendReceipt, EmailError> {
         let (api_url, mail_account, submission_account) =
             jmap_session(&self.http, settings).await?;
 
@@ -629,23 +1060,29 @@ impl EmailBackend for JmapBackend {
         let mut mailbox_ids = serde_json::Map::new();
         mailbox_ids.insert(draft_mailbox.clone(), serde_json::Value::Bool(true));
 
+        let mut draft = serde_json::json!({
+            "mailboxIds": serde_json::Value::Object(mailbox_ids),
+            "from": [{"name": outgoing.from.name.clone().unwrap_or_default(), "email": outgoing.from.address}],
+            "to": to_addresses,
+            "cc": cc_addresses,
+            "bcc": bcc_addresses,
+            "subject": outgoing.subject,
+            "textBody": [{"partId": "1", "type":"text/plain", "value": outgoing.body_text}],
+            "htmlBody": outgoing.body_html.as_ref().map(|html| vec![serde_json::json!({"partId":"2","type":"text/html","value": html})]).unwrap_or_default(),
+            "keywords": {"$draft": true}
+        });
+        if let Some(message_id) = &outgoing.message_id {
+            // JMAP takes the id without angle brackets.
+            draft["messageId"] = serde_json::json!([message_id.trim_matches(['<', '>'])]);
+        }
+
         let payload = serde_json::json!({
             "using": ["urn:ietf:params:jmap:core", "urn:ietf:params:jmap:mail", "urn:ietf:params:jmap:submission"],
             "methodCalls": [
                 ["Email/set", {
                     "accountId": mail_account,
                     "create": {
-                        "draft1": {
-                            "mailboxIds": serde_json::Value::Object(mailbox_ids),
-                            "from": [{"name": outgoing.from.name.clone().unwrap_or_default(), "email": outgoing.from.address}],
-                            "to": to_addresses,
-                            "cc": cc_addresses,
-                            "bcc": bcc_addresses,
-                            "subject": outgoing.subject,
-                            "textBody": [{"partId": "1", "type":"text/plain", "value": outgoing.body_text}],
-                            "htmlBody": outgoing.body_html.as_ref().map(|html| vec![serde_json::json!({"partId":"2","type":"text/html","value": html})]).unwrap_or_default(),
-                            "keywords": {"$draft": true}
-                        }
+                        "draft1": draft
                     }
                 }, "m1"],
                 ["EmailSubmission/set", {
@@ -667,7 +1104,11 @@ impl EmailBackend for JmapBackend {
             ));
         }
 
-        Ok(())
+        Ok(SendReceipt {
+            server_response: "EmailSubmission/set accepted".to_string(),
+            reused_connection: false,
+            pipelined: false,
+        })
     }
 
     async fn start_idle(
@@ -678,6 +1119,83 @@ impl EmailBackend for JmapBackend {
     ) -> Result<(), EmailError> {
         Ok(())
     }
+
+    async fn delete_messages(
+        &self,
+        _account: &Account,
+        settings: &ProtocolSettings,
+        _folder_path: &str,
+        remote_ids: &[String],
+        _uid_validity: Option<u32>,
+    ) -> Result<(), EmailError> {
+        if remote_ids.is_empty() {
+            return Ok(());
+        }
+        let (api_url, mail_account, _) = jmap_session(&self.http, settings).await?;
+        let response = jmap_request(
+            &self.http,
+            &api_url,
+            settings,
+            serde_json::json!({
+                "using": ["urn:ietf:params:jmap:core", "urn:ietf:params:jmap:mail"],
+                "methodCalls": [
+                    ["Email/set", {"accountId": mail_account, "destroy": remote_ids}, "m1"]
+                ]
+            }),
+        )
+        .await?;
+        if jmap_has_error(&response) {
+            return Err(EmailError::Data(
+                "JMAP delete returned method error".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn archive_messages(
+        &self,
+        _account: &Account,
+        settings: &ProtocolSettings,
+        _folder_path: &str,
+        remote_ids: &[String],
+        archive: Option<&MailFolder>,
+        _uid_validity: Option<u32>,
+    ) -> Result<(), EmailError> {
+        if remote_ids.is_empty() {
+            return Ok(());
+        }
+        let archive = archive
+            .ok_or_else(|| EmailError::Data("account has no archive mailbox".to_string()))?;
+        let (api_url, mail_account, _) = jmap_session(&self.http, settings).await?;
+
+        let update: serde_json::Map<String, serde_json::Value> = remote_ids
+            .iter()
+            .map(|id| {
+                (
+                    id.clone(),
+                    serde_json::json!({ "mailboxIds": { archive.remote_id.clone(): true } }),
+                )
+            })
+            .collect();
+        let response = jmap_request(
+            &self.http,
+            &api_url,
+            settings,
+            serde_json::json!({
+                "using": ["urn:ietf:params:jmap:core", "urn:ietf:params:jmap:mail"],
+                "methodCalls": [
+                    ["Email/set", {"accountId": mail_account, "update": update}, "m1"]
+                ]
+            }),
+        )
+        .await?;
+        if jmap_has_error(&response) {
+            return Err(EmailError::Data(
+                "JMAP archive returned method error".to_string(),
+            ));
+        }
+        Ok(())
+    }
 }
 
 pub fn default_protocol_for_provider(provider: &Provider) -> &'static str {
@@ -711,6 +1229,10 @@ struct GmailLabel {
 #[derive(Debug, Deserialize)]
 struct GmailListMessagesResponse {
     messages: Option<Vec<GmailMessageRef>>,
+    #[serde(rename = "nextPageToken")]
+    next_page_token: Option<String>,
+    #[serde(rename = "resultSizeEstimate")]
+    result_size_estimate: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -740,17 +1262,13 @@ async fn sync_gmail_folders(
         .as_ref()
         .ok_or_else(|| EmailError::Data("missing Gmail access token".to_string()))?;
 
-    let response = reqwest::Client::new()
+    let request = reqwest::Client::new()
         .get("https://gmail.googleapis.com/gmail/v1/users/me/labels")
-        .bearer_auth(token)
-        .send()
-        .await?;
+        .bearer_auth(token);
+    let response = send_logged(request, ProtocolKind::Gmail, &settings.username).await?;
 
     if !response.status().is_success() {
-        return Err(EmailError::Data(format!(
-            "Gmail labels sync failed with status {}",
-            response.status()
-        )));
+        return Err(http_status_error("Gmail labels sync", response.status()));
     }
 
     let payload: GmailLabelListResponse = response.json().await?;
@@ -771,12 +1289,71 @@ async fn sync_gmail_folders(
     Ok(folders)
 }
 
+async fn delete_gmail_messages(
+    settings: &ProtocolSettings,
+    remote_ids: &[String],
+) -> Result<(), EmailError> {
+    let token = settings
+        .access_token
+        .as_ref()
+        .ok_or_else(|| EmailError::Data("missing Gmail access token".to_string()))?;
+
+    let request = reqwest::Client::new()
+        .post("https://gmail.googleapis.com/gmail/v1/users/me/messages/batchDelete")
+        .bearer_auth(token)
+        .json(&serde_json::json!({ "ids": remote_ids }));
+    let response = send_logged(request, ProtocolKind::Gmail, &settings.username).await?;
+
+    if !response.status().is_success() {
+        return Err(http_status_error("Gmail delete messages", response.status()));
+    }
+    Ok(())
+}
+
+async fn archive_gmail_messages(
+    settings: &ProtocolSettings,
+    folder_path: &str,
+    remote_ids: &[String],
+) -> Result<(), EmailError> {
+    let token = settings
+        .access_token
+        .as_ref()
+        .ok_or_else(|| EmailError::Data("missing Gmail access token".to_string()))?;
+
+    let request = reqwest::Client::new()
+        .post("https://gmail.googleapis.com/gmail/v1/users/me/messages/batchModify")
+        .bearer_auth(token)
+        .json(&serde_json::json!({
+            "ids": remote_ids,
+            "removeLabelIds": [folder_path],
+        }));
+    let response = send_logged(request, ProtocolKind::Gmail, &settings.username).await?;
+
+    if !response.status().is_success() {
+        return Err(http_status_error("Gmail archive messages", response.status()));
+    }
+    Ok(())
+}
+
 async fn fetch_recent_gmail(
     account: &Account,
     settings: &ProtocolSettings,
     folder_path: &str,
     limit: usize,
 ) -> Result<FetchResult, EmailError> {
+    fetch_gmail_page(account, settings, folder_path, None, limit)
+        .await
+        .map(|page| page.result)
+}
+
+/// Gmail lists messages newest first; `page_token` continues a listing.
+async fn fetch_gmail_page(
+    account: &Account,
+    settings: &ProtocolSettings,
+    folder_path: &str,
+    page_token: Option<&str>,
+    limit: usize,
+) -> Result<FetchPage, EmailError> {
     let token = settings
         .access_token
         .as_ref()
@@ -793,35 +1370,35 @@ async fn fetch_recent_gmail(
     }
     
     query.push(("q", q_string));
+    if let Some(page_token) = page_token {
+        query.push(("pageToken", page_token.to_string()));
+    }
 
-    let list = client
+    let request = client
         .get("https://gmail.googleapis.com/gmail/v1/users/me/messages")
         .bearer_auth(token)
-        .query(&query)
-        .send()
-        .await?;
+        .query(&query);
+    let list = send_logged(request, ProtocolKind::Gmail, &settings.username).await?;
 
     if !list.status().is_success() {
-        return Err(EmailError::Data(format!(
-            "Gmail list messages failed with status {}",
-            list.status()
-        )));
+        return Err(http_status_error("Gmail list messages", list.status()));
     }
 
     let list_payload: GmailListMessagesResponse = list.json().await?;
+    let next_cursor = list_payload.next_page_token;
+    let total = list_payload.result_size_estimate;
     let mut messages = Vec::new();
     let mut all_attachment_content: Vec<(Uuid, Uuid, Vec<u8>)> = Vec::new();
 
     for item in list_payload.messages.unwrap_or_default() {
-        let detail = client
+        let request = client
             .get(format!(
                 "https://gmail.googleapis.com/gmail/v1/users/me/messages/{}",
                 item.id
             ))
             .bearer_auth(token)
-            .query(&[("format", "raw")])
-            .send()
-            .await?;
+            .query(&[("format", "raw")]);
+        let detail = send_logged(request, ProtocolKind::Gmail, &settings.username).await?;
 
         if !detail.status().is_success() {
             continue;
@@ -842,7 +1419,8 @@ async fn fetch_recent_gmail(
             header_value(&parsed, "Subject").unwrap_or_else(|| "(No subject)".to_string());
         let message_id = header_value(&parsed, "Message-ID").unwrap_or_else(|| payload.id.clone());
         let body_text = extract_text_body(&parsed);
-        let body_html = extract_html_body(&parsed).map(|html| ammonia::clean(&html));
+        let body_html =
+            extract_html_body(&parsed).map(|html| sanitize_html(&html, &settings.sanitizer));
         let preview = payload.snippet.clone().unwrap_or_else(|| {
             body_text
                 .clone()
@@ -907,9 +1485,14 @@ async fn fetch_recent_gmail(
         messages.push(message);
     }
 
-    Ok(FetchResult {
-        messages,
-        attachment_content: all_attachment_content,
+    Ok(FetchPage {
+        result: FetchResult {
+            messages,
+            attachment_content: all_attachment_content,
+            uid_validity: None,
+        },
+        next_cursor,
+        total,
     })
 }
 
@@ -1028,15 +1611,21 @@ fn collect_attachments(
             .unwrap_or_default()
             .to_ascii_lowercase();
         let name = header_filename(&disposition).or_else(|| mail.ctype.params.get("name").cloned());
+        // Invitations and replies often carry their calendar object as a bare
+        // alternative part; keep it so iTIP processing can read it later.
+        let is_calendar = mail.ctype.mimetype.eq_ignore_ascii_case("text/calendar");
         let is_attachment = disposition.contains("attachment")
-            || (disposition.contains("inline") && name.is_some());
+            || (disposition.contains("inline") && name.is_some())
+            || is_calendar;
 
         if is_attachment {
             let raw_body = mail.get_body_raw().unwrap_or_default();
             let id = Uuid::new_v4();
             attachments.push(MailAttachment {
                 id,
-                file_name: name.unwrap_or_else(|| "attachment.bin".to_string()),
+                file_name: name.unwrap_or_else(|| {
+                    if is_calendar { "invite.ics" } else { "attachment.bin" }.to_string()
+                }),
                 mime_type: mail.ctype.mimetype.clone(),
                 size: raw_body.len() as u64,
                 inline: disposition.contains("inline"),
@@ -1108,11 +1697,28 @@ impl imap::Authenticator for XOAuth2Authenticator {
 }
 
 fn sync_folders_imap(
+    pool: &Arc<ImapPool>,
     account_id: Uuid,
     provider: Provider,
     settings: &ProtocolSettings,
 ) -> Result<Vec<MailFolder>, EmailError> {
+    if ImapPool::supports(settings) {
+        let mut checkout = pool.checkout(settings, &provider)?;
+        let result = list_imap_folders(&mut checkout.session, account_id);
+        pool.checkin(checkout, result.is_ok());
+        return result;
+    }
+
     let mut session = connect_imap_session(settings, &provider)?;
+    let result = list_imap_folders(&mut session, account_id);
+    let _ = session.logout();
+    result
+}
+
+fn list_imap_folders<T: Read + Write>(
+    session: &mut imap::Session<T>,
+    account_id: Uuid,
+) -> Result<Vec<MailFolder>, EmailError> {
     let names = session.list(None, Some("*")).map_err(imap_error_to_email)?;
 
     let mut folders = Vec::new();
@@ -1139,20 +1745,39 @@ fn sync_folders_imap(
         });
     }
 
-    let _ = session.logout();
     Ok(folders)
 }
 
 fn fetch_recent_imap(
+    pool: &Arc<ImapPool>,
     account_id: Uuid,
     provider: Provider,
     settings: &ProtocolSettings,
     folder_path: &str,
     limit: usize,
 ) -> Result<FetchResult, EmailError> {
+    if ImapPool::supports(settings) {
+        let mut checkout = pool.checkout(settings, &provider)?;
+        let result =
+            fetch_imap_folder(&mut checkout.session, account_id, settings, folder_path, limit);
+        pool.checkin(checkout, result.is_ok());
+        return result;
+    }
+
     let mut session = connect_imap_session(settings, &provider)?;
+    let result = fetch_imap_folder(&mut session, account_id, settings, folder_path, limit);
+    let _ = session.logout();
+    result
+}
+
+fn fetch_imap_folder<T: Read + Write>(
+    session: &mut imap::Session<T>,
+    account_id: Uuid,
+    settings: &ProtocolSettings,
+    folder_path: &str,
+    limit: usize,
+) -> Result<FetchResult, EmailError> {
     let mailbox = session.select(folder_path).map_err(imap_error_to_email)?;
-    let _ = mailbox; // Kept to ensure mailbox selection succeeded
 
     let sequence = if let Some(cove_core::OfflineSyncLimit::Days(days)) = settings.offline_sync_limit {
         let after_date = Utc::now() - chrono::Duration::days(days as i64);
@@ -1161,8 +1786,11 @@ fn fetch_recent_imap(
         // Search for message UIDs since the given date
         let uids = session.uid_search(format!("SINCE {}", date_str)).map_err(imap_error_to_email)?;
         if uids.is_empty() {
-            let _ = session.logout();
-            return Ok(FetchResult { messages: Vec::new(), attachment_content: Vec::new() });
+            return Ok(FetchResult {
+                messages: Vec::new(),
+                attachment_content: Vec::new(),
+                uid_validity: None,
+            });
         }
         
         // Convert the set of UIDs to a comma-separated string, taking up to `limit` many
@@ -1174,8 +1802,11 @@ fn fetch_recent_imap(
         uid_vec.into_iter().map(|uid| uid.to_string()).collect::<Vec<String>>().join(",")
     } else {
         if mailbox.exists == 0 {
-            let _ = session.logout();
-            return Ok(FetchResult { messages: Vec::new(), attachment_content: Vec::new() });
+            return Ok(FetchResult {
+                messages: Vec::new(),
+                attachment_content: Vec::new(),
+                uid_validity: None,
+            });
         }
         let start = if mailbox.exists > limit as u32 {
             mailbox.exists - limit as u32 + 1
@@ -1185,14 +1816,120 @@ fn fetch_recent_imap(
         format!("{start}:{}", mailbox.exists)
     };
 
+    let mut result = fetch_imap_messages(session, account_id, settings, folder_path, &sequence)?;
+    result.uid_validity = mailbox.uid_validity;
+    Ok(result)
+}
+
+fn fetch_page_imap(
+    pool: &Arc<ImapPool>,
+    account_id: Uuid,
+    provider: Provider,
+    settings: &ProtocolSettings,
+    folder_path: &str,
+    before_uid: Option<u32>,
+    limit: usize,
+) -> Result<FetchPage, EmailError> {
+    if ImapPool::supports(settings) {
+        let mut checkout = pool.checkout(settings, &provider)?;
+        let result = fetch_imap_page(
+            &mut checkout.session,
+            account_id,
+            settings,
+            folder_path,
+            before_uid,
+            limit,
+        );
+        pool.checkin(checkout, result.is_ok());
+        return result;
+    }
+
+    let mut session = connect_imap_session(settings, &provider)?;
+    let result =
+        fetch_imap_page(&mut session, account_id, settings, folder_path, before_uid, limit);
+    let _ = session.logout();
+    result
+}
+
+/// The `limit` highest UIDs below `before_uid`, honouring the offline sync
+/// limit; the cursor for the next page is the lowest UID fetched.
+fn fetch_imap_page<T: Read + Write>(
+    session: &mut imap::Session<T>,
+    account_id: Uuid,
+    settings: &ProtocolSettings,
+    folder_path: &str,
+    before_uid: Option<u32>,
+    limit: usize,
+) -> Result<FetchPage, EmailError> {
+    let mailbox = session.select(folder_path).map_err(imap_error_to_email)?;
+    let total = Some(u64::from(mailbox.exists));
+    let mut criteria = match before_uid {
+        Some(uid) if uid <= 1 => {
+            return Ok(FetchPage {
+                result: FetchResult {
+                    messages: Vec::new(),
+                    attachment_content: Vec::new(),
+                    uid_validity: None,
+                },
+                next_cursor: None,
+                total,
+            });
+        }
+        Some(uid) => format!("UID 1:{}", uid - 1),
+        None => "ALL".to_string(),
+    };
+    if let Some(cove_core::OfflineSyncLimit::Days(days)) = settings.offline_sync_limit {
+        let after_date = Utc::now() - chrono::Duration::days(days as i64);
+        criteria.push_str(&format!(" SINCE {}", after_date.format("%d-%b-%Y")));
+    }
+
+    let mut uids: Vec<u32> = session
+        .uid_search(criteria)
+        .map_err(imap_error_to_email)?
+        .into_iter()
+        .collect();
+    uids.sort_unstable();
+    let page = uids.split_off(uids.len().saturating_sub(limit));
+    let Some(oldest) = page.first() else {
+        return Ok(FetchPage {
+            result: FetchResult {
+                messages: Vec::new(),
+                attachment_content: Vec::new(),
+                uid_validity: None,
+            },
+            next_cursor: None,
+            total,
+        });
+    };
+    let next_cursor = (!uids.is_empty()).then(|| oldest.to_string());
+
+    let sequence = page.iter().map(u32::to_string).collect::<Vec<_>>().join(",");
+    let mut result = fetch_imap_messages(session, account_id, settings, folder_path, &sequence)?;
+    result.uid_validity = mailbox.uid_validity;
+    Ok(FetchPage { result, next_cursor, total })
+}
+
+/// Fetch and parse the messages with the given UIDs.
+fn fetch_imap_messages<T: Read + Write>(
+    session: &mut imap::Session<T>,
+    account_id: Uuid,
+    settings: &ProtocolSettings,
+    folder_path: &str,
+    sequence: &str,
+) -> Result<FetchResult, EmailError> {
+    let query = if settings.headers_only {
+        "(UID FLAGS INTERNALDATE RFC822.HEADER)"
+    } else {
+        "(UID FLAGS INTERNALDATE RFC822)"
+    };
     let fetches = session
-        .uid_fetch(sequence, "(UID FLAGS INTERNALDATE RFC822)")
+        .uid_fetch(sequence, query)
         .map_err(imap_error_to_email)?;
 
     let mut messages = Vec::new();
     let mut all_attachment_content = Vec::new();
     for fetched in fetches.iter() {
-        let body = match fetched.body() {
+        let body = match fetched.body().or_else(|| fetched.header()) {
             Some(body) => body,
             None => continue,
         };
@@ -1201,14 +1938,12 @@ fn fetch_recent_imap(
         let headers = headers_map(&parsed);
         let subject =
             header_value(&parsed, "Subject").unwrap_or_else(|| "(No subject)".to_string());
-        let message_id = header_value(&parsed, "Message-ID").unwrap_or_else(|| {
-            fetched
-                .uid
-                .map(|uid| uid.to_string())
-                .unwrap_or_else(|| fetched.message.to_string())
-        });
+        let remote_id = imap_remote_id(fetched.uid, fetched.message);
+        let message_id =
+            header_value(&parsed, "Message-ID").unwrap_or_else(|| remote_id.clone());
         let body_text = extract_text_body(&parsed);
-        let body_html = extract_html_body(&parsed).map(|html| ammonia::clean(&html));
+        let body_html =
+            extract_html_body(&parsed).map(|html| sanitize_html(&html, &settings.sanitizer));
         let preview = body_text
             .as_deref()
             .unwrap_or_default()
@@ -1244,10 +1979,7 @@ fn fetch_recent_imap(
         messages.push(MailMessage {
             id: msg_id,
             account_id,
-            remote_id: fetched
-                .uid
-                .map(|uid| uid.to_string())
-                .unwrap_or_else(|| fetched.message.to_string()),
+            remote_id,
             thread_id: thread_id_from_headers(&headers, &message_id),
             folder_path: folder_path.to_string(),
             from: parse_address_list(header_value(&parsed, "From")),
@@ -1273,13 +2005,35 @@ fn fetch_recent_imap(
         });
     }
 
-    let _ = session.logout();
     Ok(FetchResult {
         messages,
         attachment_content: all_attachment_content,
+        uid_validity: None,
     })
 }
 
+/// The remote id stored for an IMAP message: its UID, or `seq:<n>` when the
+/// server sent none. Sequence numbers shift whenever mail is expunged, so the
+/// prefix keeps one from ever being sent where a UID is expected.
+fn imap_remote_id(uid: Option<u32>, sequence: u32) -> String {
+    match uid {
+        Some(uid) => uid.to_string(),
+        None => format!("seq:{sequence}"),
+    }
+}
+
+/// Whether `remote_id` is a real IMAP UID rather than a `seq:` placeholder or
+/// another protocol's id.
+pub fn is_imap_uid(remote_id: &str) -> bool {
+    remote_id.parse::<u32>().is_ok_and(|uid| uid > 0)
+}
+
+/// Whether the provider's messages are addressed by IMAP UID. Gmail syncs
+/// and deletes through its API, with Gmail message ids.
+pub fn uses_imap_uids(provider: &Provider) -> bool {
+    default_protocol_for_provider(provider) == "imap_smtp" && *provider != Provider::Gmail
+}
+
 fn start_idle_imap(
     provider: Provider,
     settings: &ProtocolSettings,
@@ -1300,6 +2054,127 @@ fn start_idle_imap(
     Ok(())
 }
 
+fn delete_imap_messages(
+    pool: &Arc<ImapPool>,
+    provider: Provider,
+    settings: &ProtocolSettings,
+    folder_path: &str,
+    uids: &str,
+    uid_validity: Option<u32>,
+) -> Result<(), EmailError> {
+    if ImapPool::supports(settings) {
+        let mut checkout = pool.checkout(settings, &provider)?;
+        let result = expunge_imap_uids(&mut checkout.session, folder_path, uids, uid_validity);
+        pool.checkin(checkout, result.is_ok());
+        return result;
+    }
+
+    let mut session = connect_imap_session(settings, &provider)?;
+    let result = expunge_imap_uids(&mut session, folder_path, uids, uid_validity);
+    let _ = session.logout();
+    result
+}
+
+fn expunge_imap_uids<T: Read + Write>(
+    session: &mut imap::Session<T>,
+    folder_path: &str,
+    uids: &str,
+    uid_validity: Option<u32>,
+) -> Result<(), EmailError> {
+    select_with_uid_validity(session, folder_path, uid_validity)?;
+    require_uidplus(session)?;
+    session
+        .uid_store(uids, "+FLAGS.SILENT (\\Deleted)")
+        .map_err(imap_error_to_email)?;
+    session.uid_expunge(uids).map_err(imap_error_to_email)?;
+    Ok(())
+}
+
+/// Select `folder_path`, refusing when its UIDVALIDITY is not the one the
+/// stored UIDs were fetched under. UIDs only name the same messages while
+/// UIDVALIDITY is unchanged; after a mailbox reset they may belong to other
+/// mail.
+fn select_with_uid_validity<T: Read + Write>(
+    session: &mut imap::Session<T>,
+    folder_path: &str,
+    uid_validity: Option<u32>,
+) -> Result<(), EmailError> {
+    let mailbox = session.select(folder_path).map_err(imap_error_to_email)?;
+    if uid_validity.is_none() || mailbox.uid_validity != uid_validity {
+        return Err(EmailError::Data(format!(
+            "UIDVALIDITY of {folder_path} is unknown or has changed; sync the folder first"
+        )));
+    }
+    Ok(())
+}
+
+/// UID EXPUNGE (UIDPLUS) removes only the given messages. Without it the
+/// only option is a folder-wide EXPUNGE, which also removes every other
+/// message marked \Deleted, other clients' included, so refuse instead.
+fn require_uidplus<T: Read + Write>(session: &mut imap::Session<T>) -> Result<(), EmailError> {
+    let supported = session
+        .capabilities()
+        .map(|caps| caps.has_str("UIDPLUS"))
+        .map_err(imap_error_to_email)?;
+    if supported {
+        Ok(())
+    } else {
+        Err(EmailError::Unimplemented(
+            "server does not support UID EXPUNGE (UIDPLUS)".to_string(),
+        ))
+    }
+}
+
+fn move_imap_messages(
+    pool: &Arc<ImapPool>,
+    provider: Provider,
+    settings: &ProtocolSettings,
+    folder_path: &str,
+    uids: &str,
+    target: &str,
+    uid_validity: Option<u32>,
+) -> Result<(), EmailError> {
+    if ImapPool::supports(settings) {
+        let mut checkout = pool.checkout(settings, &provider)?;
+        let result =
+            move_imap_uids(&mut checkout.session, folder_path, uids, target, uid_validity);
+        pool.checkin(checkout, result.is_ok());
+        return result;
+    }
+
+    let mut session = connect_imap_session(settings, &provider)?;
+    let result = move_imap_uids(&mut session, folder_path, uids, target, uid_validity);
+    let _ = session.logout();
+    result
+}
+
+fn move_imap_uids<T: Read + Write>(
+    session: &mut imap::Session<T>,
+    folder_path: &str,
+    uids: &str,
+    target: &str,
+    uid_validity: Option<u32>,
+) -> Result<(), EmailError> {
+    select_with_uid_validity(session, folder_path, uid_validity)?;
+    let has_move = session
+        .capabilities()
+        .map(|caps| caps.has_str("MOVE"))
+        .map_err(imap_error_to_email)?;
+    if has_move {
+        session.uid_mv(uids, target).map_err(imap_error_to_email)?;
+        return Ok(());
+    }
+    // Servers without MOVE (RFC 6851) get the copy, flag and expunge it
+    // stands for, provided only the moved messages can be expunged.
+    require_uidplus(session)?;
+    session.uid_copy(uids, target).map_err(imap_error_to_email)?;
+    session
+        .uid_store(uids, "+FLAGS.SILENT (\\Deleted)")
+        .map_err(imap_error_to_email)?;
+    session.uid_expunge(uids).map_err(imap_error_to_email)?;
+    Ok(())
+}
+
 fn connect_imap_session(
     settings: &ProtocolSettings,
     provider: &Provider,
@@ -1314,11 +2189,11 @@ fn connect_imap_session(
     login_imap_client(client, settings, provider)
 }
 
-fn login_imap_client(
-    client: imap::Client<imap::Connection>,
+pub(crate) fn login_imap_client<T: Read + Write>(
+    client: imap::Client<T>,
     settings: &ProtocolSettings,
     provider: &Provider,
-) -> Result<imap::Session<imap::Connection>, EmailError> {
+) -> Result<imap::Session<T>, EmailError> {
     if let Some(token) = settings.access_token.as_ref() {
         let auth = XOAuth2Authenticator {
             user: settings.username.clone(),
@@ -1329,18 +2204,18 @@ fn login_imap_client(
             Ok(session) => return Ok(session),
             Err((auth_err, fallback_client)) => {
                 if matches!(provider, Provider::Gmail) {
-                    return Err(imap_error_to_email(auth_err));
+                    return Err(imap_auth_error(auth_err));
                 }
 
                 if let Some(password) = settings.password.as_ref() {
                     return fallback_client
                         .login(settings.username.clone(), password.clone())
-                        .map_err(|err| imap_error_to_email(err.0));
+                        .map_err(|err| imap_auth_error(err.0));
                 }
 
                 return fallback_client
                     .login(settings.username.clone(), token.clone())
-                    .map_err(|err| imap_error_to_email(err.0));
+                    .map_err(|err| imap_auth_error(err.0));
             }
         }
     }
@@ -1348,7 +2223,7 @@ fn login_imap_client(
     if let Some(password) = settings.password.as_ref() {
         return client
             .login(settings.username.clone(), password.clone())
-            .map_err(|err| imap_error_to_email(err.0));
+            .map_err(|err| imap_auth_error(err.0));
     }
 
     Err(EmailError::Data(
@@ -1356,8 +2231,64 @@ fn login_imap_client(
     ))
 }
 
-fn imap_error_to_email(error: imap::Error) -> EmailError {
-    EmailError::Data(format!("imap error: {error}"))
+/// Gmail and Outlook only accept OAuth on SMTP through SASL XOAUTH2; other
+/// providers get XOAUTH2 when a token is the only credential available.
+fn uses_smtp_xoauth2(settings: &ProtocolSettings, provider: &Provider) -> bool {
+    settings.access_token.is_some()
+        && (matches!(provider, Provider::Gmail | Provider::Outlook) || settings.password.is_none())
+}
+
+/// Map an SMTP failure. `sending` is set once the server answered: a
+/// connection lost after that may have come after DATA, when the server can
+/// already have accepted the message, so it is not reported as a network
+/// error that would queue a second copy.
+pub(crate) fn smtp_error_to_email(error: lettre::transport::smtp::Error, sending: bool) -> EmailError {
+    let message = error.to_string();
+    // 530/534/535: authentication required, mechanism too weak, or
+    // credentials rejected (an expired OAuth token lands here).
+    if error.is_permanent() && ["(530)", "(534)", "(535)"].iter().any(|code| message.contains(code))
+    {
+        return EmailError::Auth(message);
+    }
+    // No reply code and not a local mistake: the server was never reached
+    // or the connection dropped.
+    if error.is_timeout() || (error.status().is_none() && !error.is_client()) {
+        if sending {
+            return EmailError::Smtp(format!(
+                "connection lost while sending; the message may have been delivered: {message}"
+            ));
+        }
+        return EmailError::Network(message);
+    }
+    EmailError::Smtp(message)
+}
+
+fn imap_auth_error(error: imap::Error) -> EmailError {
+    match error {
+        imap::Error::No(_) | imap::Error::Bad(_) => {
+            EmailError::Auth(format!("imap login rejected: {error}"))
+        }
+        other => imap_error_to_email(other),
+    }
+}
+
+fn http_status_error(what: &str, status: StatusCode) -> EmailError {
+    let failure = HttpFailure::from_status(what, status.as_u16());
+    match failure.kind {
+        HttpFailureKind::Unauthorized => EmailError::Auth(failure.message),
+        HttpFailureKind::Rejected => EmailError::Data(failure.message),
+    }
+}
+
+/// IMAP failures; an unreachable or dropped connection counts as a network
+/// error so the change can be queued for replay.
+pub(crate) fn imap_error_to_email(error: imap::Error) -> EmailError {
+    match error {
+        imap::Error::Io(_) | imap::Error::ConnectionLost => {
+            EmailError::Network(format!("imap error: {error}"))
+        }
+        error => EmailError::Data(format!("imap error: {error}")),
+    }
 }
 
 fn apply_ews_auth(
@@ -1575,12 +2506,10 @@ async fn jmap_session(
         .as_deref()
         .ok_or_else(|| EmailError::Data("missing JMAP access token".to_string()))?;
 
-    let response = http.get(endpoint).bearer_auth(token).send().await?;
+    let request = http.get(endpoint).bearer_auth(token);
+    let response = send_logged(request, ProtocolKind::Jmap, &settings.username).await?;
     if !response.status().is_success() {
-        return Err(EmailError::Data(format!(
-            "JMAP session failed with status {}",
-            response.status()
-        )));
+        return Err(http_status_error("JMAP session", response.status()));
     }
 
     let payload: serde_json::Value = response.json().await?;
@@ -1622,17 +2551,13 @@ async fn jmap_request(
         .as_deref()
         .ok_or_else(|| EmailError::Data("missing JMAP access token".to_string()))?;
 
-    let response = http
+    let request = http
         .post(api_url)
         .bearer_auth(token)
-        .json(&payload)
-        .send()
-        .await?;
+        .json(&payload);
+    let response = send_logged(request, ProtocolKind::Jmap, &settings.username).await?;
     if !response.status().is_success() {
-        return Err(EmailError::Data(format!(
-            "JMAP method call failed with status {}",
-            response.status()
-        )));
+        return Err(http_status_error("JMAP method call", response.status()));
     }
 
     response
@@ -1695,10 +2620,36 @@ fn parse_jmap_folders(account_id: Uuid, payload: &serde_json::Value) -> Vec<Mail
     Vec::new()
 }
 
+/// `total` from the `Email/query` response, present when it was asked for.
+fn jmap_query_total(payload: &serde_json::Value) -> Option<u64> {
+    payload
+        .get("methodResponses")?
+        .as_array()?
+        .iter()
+        .filter_map(|method| method.as_array())
+        .find(|parts| parts.first().and_then(|value| value.as_str()) == Some("Email/query"))?
+        .get(1)?
+        .get("total")?
+        .as_u64()
+}
+
+/// EWS and JMAP page by offset; no cursor means the first page.
+fn parse_offset_cursor(cursor: Option<&str>) -> Result<usize, EmailError> {
+    cursor
+        .map(|cursor| {
+            cursor
+                .parse()
+                .map_err(|_| EmailError::Data(format!("invalid backfill cursor: {cursor}")))
+        })
+        .transpose()
+        .map(Option::unwrap_or_default)
+}
+
 fn parse_jmap_messages(
     account_id: Uuid,
     folder_path: &str,
     payload: &serde_json::Value,
+    sanitizer: &cove_core::SanitizerPolicy,
 ) -> Vec<MailMessage> {
     let methods = payload
         .get("methodResponses")
@@ -1742,7 +2693,17 @@ fn parse_jmap_messages(
                     .and_then(|value| value.as_str())
                     .unwrap_or("")
                     .to_string();
-                let headers = BTreeMap::new();
+                // Only the delivery headers are requested; reply identities
+                // read them.
+                let mut headers = BTreeMap::new();
+                for name in ["Delivered-To", "X-Original-To"] {
+                    if let Some(value) = entry
+                        .get(format!("header:{name}:asText"))
+                        .and_then(|value| value.as_str())
+                    {
+                        headers.insert(name.to_string(), value.trim().to_string());
+                    }
+                }
                 let sent_at = entry
                     .get("sentAt")
                     .and_then(|value| value.as_str())
@@ -1761,7 +2722,8 @@ fn parse_jmap_messages(
                     .unwrap_or_default();
 
                 let body_text = pick_jmap_body(entry.get("textBody"), &body_values);
-                let body_html = pick_jmap_body(entry.get("htmlBody"), &body_values);
+                let body_html = pick_jmap_body(entry.get("htmlBody"), &body_values)
+                    .map(|html| sanitize_html(&html, sanitizer));
 
                 let mut flags = MailFlags::default();
                 if let Some(keywords) = entry.get("keywords").and_then(|value| value.as_object()) {
@@ -0,0 +1,95 @@
+//! Writing received attachments into the download directory without
+//! overwriting anything already there.
+
+use cove_core::AttachmentDownload;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Outcome of "Save all attachments" on one or more messages.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SaveAllReport {
+    pub saved: Vec<AttachmentDownload>,
+    /// Attachments left out, with the reason.
+    pub skipped: Vec<SkippedAttachment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedAttachment {
+    pub message_id: Uuid,
+    pub file_name: String,
+    pub reason: String,
+}
+
+/// `name` reduced to a plain file name: no directories, no characters
+/// Windows refuses, and never empty or hidden.
+pub fn safe_file_name(name: &str) -> String {
+    let base = name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let cleaned: String = base
+        .chars()
+        .map(|ch| match ch {
+            '<' | '>' | ':' | '"' | '|' | '?' | '*' => '_',
+            ch if ch.is_control() => '_',
+            ch => ch,
+        })
+        .collect();
+    let cleaned = cleaned.trim().trim_start_matches('.').trim_end_matches(['.', ' ']);
+    if cleaned.is_empty() {
+        "attachment".to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+/// A path in `dir` for `file_name` that does not exist yet: `report.pdf`,
+/// then `report (1).pdf`, `report (2).pdf` and so on.
+pub fn unique_download_path(dir: &Path, file_name: &str) -> PathBuf {
+    let file_name = safe_file_name(file_name);
+    let candidate = dir.join(&file_name);
+    if !candidate.exists() {
+        return candidate;
+    }
+
+    let (stem, extension) = match file_name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, Some(extension)),
+        _ => (file_name.as_str(), None),
+    };
+    (1..)
+        .map(|n| match extension {
+            Some(extension) => dir.join(format!("{stem} ({n}).{extension}")),
+            None => dir.join(format!("{stem} ({n})")),
+        })
+        .find(|path| !path.exists())
+        .expect("some numbered name is free")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_names_cannot_escape_the_directory() {
+        assert_eq!(safe_file_name("../../etc/passwd"), "passwd");
+        assert_eq!(safe_file_name("C:\\Users\\me\\report.pdf"), "report.pdf");
+        assert_eq!(safe_file_name("a<b>:c?.txt"), "a_b__c_.txt");
+        assert_eq!(safe_file_name(".bashrc"), "bashrc");
+        assert_eq!(safe_file_name("  "), "attachment");
+    }
+
+    #[test]
+    fn numbers_colliding_names() {
+        let dir = std::env::temp_dir().join(format!("cove-downloads-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        assert_eq!(unique_download_path(&dir, "report.pdf"), dir.join("report.pdf"));
+        std::fs::write(dir.join("report.pdf"), b"one").unwrap();
+        assert_eq!(unique_download_path(&dir, "report.pdf"), dir.join("report (1).pdf"));
+        std::fs::write(dir.join("report (1).pdf"), b"two").unwrap();
+        assert_eq!(unique_download_path(&dir, "report.pdf"), dir.join("report (2).pdf"));
+
+        std::fs::write(dir.join("README"), b"three").unwrap();
+        assert_eq!(unique_download_path(&dir, "README"), dir.join("README (1)"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
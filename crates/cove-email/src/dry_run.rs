@@ -0,0 +1,156 @@
+//! Dry runs: which messages a rule or bulk action would change, worked out
+//! from stored mail without touching it.
+//!
+//! A message counts as affected only when at least one action would leave
+//! it different, so marking read mail read or moving mail into the folder
+//! it is already in does not inflate the numbers.
+
+use crate::service::is_archive_folder;
+use cove_core::{DryRunActionCount, DryRunReport, DryRunSample, MailMessage, RuleAction};
+
+/// Affected messages listed in a dry run report at most.
+pub const DRY_RUN_SAMPLE_SIZE: usize = 25;
+
+/// Builds a [`DryRunReport`] from messages seen newest first.
+pub(crate) struct DryRunTally {
+    report: DryRunReport,
+}
+
+impl DryRunTally {
+    pub(crate) fn new(actions: &[RuleAction]) -> Self {
+        Self {
+            report: DryRunReport {
+                per_action: actions
+                    .iter()
+                    .map(|action| DryRunActionCount {
+                        action: action.clone(),
+                        messages: 0,
+                    })
+                    .collect(),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Count `message`, which `matched` decides whether the actions apply
+    /// to at all.
+    pub(crate) fn record(&mut self, message: &MailMessage, matched: bool) {
+        self.report.scanned += 1;
+        if !matched {
+            return;
+        }
+        self.report.matched += 1;
+
+        let mut changing = Vec::new();
+        for count in &mut self.report.per_action {
+            if action_changes(&count.action, message) {
+                count.messages += 1;
+                changing.push(count.action.clone());
+            }
+        }
+        if changing.is_empty() {
+            return;
+        }
+        self.report.affected += 1;
+        if self.report.samples.len() < DRY_RUN_SAMPLE_SIZE {
+            self.report.samples.push(DryRunSample {
+                message_id: message.id,
+                account_id: message.account_id,
+                thread_id: message.thread_id.clone(),
+                folder_path: message.folder_path.clone(),
+                subject: message.subject.clone(),
+                sender: message
+                    .from
+                    .first()
+                    .map(|from| from.name.clone().unwrap_or_else(|| from.address.clone()))
+                    .unwrap_or_default(),
+                received_at: message.received_at,
+                actions: changing,
+            });
+        }
+    }
+
+    pub(crate) fn finish(self) -> DryRunReport {
+        self.report
+    }
+}
+
+/// Whether applying `action` would leave `message` different. Thread
+/// labels live outside the message, so they always count.
+pub fn action_changes(action: &RuleAction, message: &MailMessage) -> bool {
+    match action {
+        RuleAction::MoveTo(folder) => message.folder_path != *folder,
+        RuleAction::Label(label) => !message.labels.iter().any(|existing| existing == label),
+        RuleAction::LabelThread(_) => true,
+        RuleAction::MarkRead => !message.flags.seen,
+        RuleAction::Archive => !is_archive_folder(&message.folder_path),
+        RuleAction::Delete => true,
+        RuleAction::Pin => !message.pinned,
+        RuleAction::Flag => !message.flags.flagged,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cove_core::{MailAddress, MailFlags};
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn message(minute: i64, folder: &str, seen: bool) -> MailMessage {
+        let received_at =
+            Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap() - Duration::minutes(minute);
+        MailMessage {
+            remote_id: minute.to_string(),
+            thread_id: minute.to_string(),
+            folder_path: folder.to_string(),
+            from: vec![MailAddress {
+                name: Some("Ana".to_string()),
+                address: "ana@example.com".to_string(),
+            }],
+            subject: format!("Message {minute}"),
+            flags: MailFlags {
+                seen,
+                ..Default::default()
+            },
+            received_at,
+            created_at: received_at,
+            updated_at: received_at,
+            ..MailMessage::fixture()
+        }
+    }
+
+    #[test]
+    fn counts_only_messages_an_action_would_change() {
+        let actions = [RuleAction::MarkRead, RuleAction::MoveTo("News".to_string())];
+        let mut tally = DryRunTally::new(&actions);
+        tally.record(&message(0, "INBOX", false), true);
+        tally.record(&message(1, "News", true), true);
+        tally.record(&message(2, "INBOX", true), false);
+        let report = tally.finish();
+
+        assert_eq!((report.scanned, report.matched, report.affected), (3, 2, 1));
+        let counts: Vec<usize> = report
+            .per_action
+            .iter()
+            .map(|count| count.messages)
+            .collect();
+        assert_eq!(counts, [1, 1]);
+        assert_eq!(report.samples.len(), 1);
+        assert_eq!(report.samples[0].sender, "Ana");
+        assert_eq!(report.samples[0].actions, actions);
+    }
+
+    #[test]
+    fn samples_are_capped() {
+        let mut tally = DryRunTally::new(&[RuleAction::Archive]);
+        for minute in 0..(DRY_RUN_SAMPLE_SIZE as i64 + 5) {
+            tally.record(&message(minute, "INBOX", true), true);
+        }
+        tally.record(&message(99, "Archive", true), true);
+        let report = tally.finish();
+        assert_eq!(report.affected, DRY_RUN_SAMPLE_SIZE + 5);
+        assert_eq!(report.matched, DRY_RUN_SAMPLE_SIZE + 6);
+        assert_eq!(report.samples.len(), DRY_RUN_SAMPLE_SIZE);
+        assert_eq!(report.samples[0].subject, "Message 0");
+    }
+}
@@ -6,14 +6,35 @@ pub enum EmailError {
     Storage(#[from] cove_storage::StorageError),
     #[error("smtp transport error: {0}")]
     Smtp(String),
+    /// The server could not be reached at all.
+    #[error("network error: {0}")]
+    Network(String),
+    /// The server rejected the credentials; OAuth accounts should refresh
+    /// their access token and retry.
+    #[error("authentication failed: {0}")]
+    Auth(String),
     #[error("message build error: {0}")]
     Build(String),
     #[error("http error: {0}")]
     Http(#[from] reqwest::Error),
     #[error("mail parse error: {0}")]
     Parse(#[from] mailparse::MailParseError),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
     #[error("invalid data: {0}")]
     Data(String),
     #[error("unimplemented: {0}")]
     Unimplemented(String),
 }
+
+impl EmailError {
+    /// Whether the server was unreachable rather than refusing the request;
+    /// such operations are worth queueing until the connection is back.
+    pub fn is_network(&self) -> bool {
+        match self {
+            Self::Network(_) => true,
+            Self::Http(err) => err.is_connect() || err.is_timeout(),
+            _ => false,
+        }
+    }
+}
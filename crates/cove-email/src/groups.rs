@@ -0,0 +1,179 @@
+//! Contact groups as send lists.
+//!
+//! A group added at compose time is expanded into concrete addresses when
+//! the message is sent, minus the members left out of that one message.
+//! The expansion is recorded under the message's Message-ID so a search
+//! for a member finds the message even when the group went out as Bcc.
+
+use crate::{EmailError, OutgoingMail};
+use cove_core::{ContactGroup, GroupExpansion, GroupSend, MailAddress, RecipientField};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Add the members of each group in `sends` to the matching recipient
+/// field of `outgoing`. A member already addressed, directly or through an
+/// earlier group, is not added twice but still counts as a recipient of
+/// the group.
+pub fn expand_group_sends(
+    outgoing: &mut OutgoingMail,
+    groups: &[ContactGroup],
+    sends: &[GroupSend],
+) -> Result<Vec<GroupExpansion>, EmailError> {
+    let mut addressed: HashSet<String> = outgoing
+        .to
+        .iter()
+        .chain(&outgoing.cc)
+        .chain(&outgoing.bcc)
+        .map(|recipient| recipient.address.to_lowercase())
+        .collect();
+
+    let mut expansions = Vec::with_capacity(sends.len());
+    for send in sends {
+        let group = groups
+            .iter()
+            .find(|group| group.id == send.group_id)
+            .ok_or_else(|| {
+                EmailError::Data(format!("contact group {} not found", send.group_id))
+            })?;
+        let excluded: HashSet<String> = send
+            .excluded
+            .iter()
+            .map(|address| address.trim().to_lowercase())
+            .collect();
+
+        let recipients: Vec<MailAddress> = group
+            .members
+            .iter()
+            .filter(|member| !excluded.contains(&member.address.to_lowercase()))
+            .cloned()
+            .collect();
+        let field = match send.field {
+            RecipientField::To => &mut outgoing.to,
+            RecipientField::Cc => &mut outgoing.cc,
+            RecipientField::Bcc => &mut outgoing.bcc,
+        };
+        for member in &recipients {
+            if addressed.insert(member.address.to_lowercase()) {
+                field.push(member.clone());
+            }
+        }
+
+        expansions.push(GroupExpansion {
+            group_id: group.id,
+            group_name: group.name.clone(),
+            field: send.field,
+            recipients,
+            excluded: group
+                .members
+                .iter()
+                .filter(|member| excluded.contains(&member.address.to_lowercase()))
+                .map(|member| member.address.clone())
+                .collect(),
+        });
+    }
+    Ok(expansions)
+}
+
+/// A fresh Message-ID on the sender's domain, angle brackets included.
+pub fn new_message_id(from: &MailAddress) -> String {
+    let domain = from
+        .address
+        .rsplit_once('@')
+        .map(|(_, domain)| domain)
+        .filter(|domain| !domain.is_empty())
+        .unwrap_or("localhost");
+    format!("<{}@{domain}>", Uuid::new_v4())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn address(address: &str) -> MailAddress {
+        MailAddress {
+            name: None,
+            address: address.to_string(),
+        }
+    }
+
+    fn outgoing(to: &[&str]) -> OutgoingMail {
+        OutgoingMail {
+            from: address("me@example.com"),
+            to: to.iter().map(|value| address(value)).collect(),
+            cc: Vec::new(),
+            bcc: Vec::new(),
+            reply_to: Vec::new(),
+            subject: "Offsite".to_string(),
+            body_text: String::new(),
+            body_html: None,
+            attachments: Vec::new(),
+            message_id: None,
+        }
+    }
+
+    fn group(name: &str, members: &[&str]) -> ContactGroup {
+        ContactGroup {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            members: members.iter().map(|value| address(value)).collect(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn expands_groups_without_excluded_or_duplicate_members() {
+        let team = group(
+            "Team",
+            &["dana@example.com", "lee@example.com", "sam@example.com"],
+        );
+        let leads = group("Leads", &["Lee@example.com", "kim@example.com"]);
+        let mut mail = outgoing(&["sam@example.com"]);
+        let sends = [
+            GroupSend {
+                group_id: team.id,
+                field: RecipientField::Bcc,
+                excluded: vec!["LEE@example.com".to_string()],
+            },
+            GroupSend {
+                group_id: leads.id,
+                field: RecipientField::Cc,
+                excluded: Vec::new(),
+            },
+        ];
+
+        let expansions = expand_group_sends(&mut mail, &[team, leads], &sends).unwrap();
+
+        let addresses = |list: &[MailAddress]| {
+            list.iter()
+                .map(|item| item.address.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(addresses(&mail.to), ["sam@example.com"]);
+        assert_eq!(addresses(&mail.bcc), ["dana@example.com"]);
+        assert_eq!(addresses(&mail.cc), ["Lee@example.com", "kim@example.com"]);
+        assert_eq!(
+            addresses(&expansions[0].recipients),
+            ["dana@example.com", "sam@example.com"]
+        );
+        assert_eq!(expansions[0].excluded, ["lee@example.com"]);
+        assert_eq!(expansions[1].recipients.len(), 2);
+    }
+
+    #[test]
+    fn rejects_unknown_groups() {
+        let send = GroupSend {
+            group_id: Uuid::new_v4(),
+            field: RecipientField::To,
+            excluded: Vec::new(),
+        };
+        assert!(expand_group_sends(&mut outgoing(&[]), &[], &[send]).is_err());
+    }
+
+    #[test]
+    fn message_ids_use_the_sender_domain() {
+        let id = new_message_id(&address("me@example.com"));
+        assert!(id.starts_with('<') && id.ends_with("@example.com>"));
+    }
+}
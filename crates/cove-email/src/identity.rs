@@ -0,0 +1,236 @@
+//! Picking the identity to reply from.
+//!
+//! Mail is usually delivered to one of the user's addresses without naming
+//! it in To or Cc (lists, Bcc, forwarding), so the delivery headers servers
+//! add are checked first and the visible recipients only after them.
+
+use cove_core::{Account, MailMessage, PlusAlias, ReplyIdentity, SendIdentity};
+
+/// Headers that record the address a message was delivered to, most
+/// specific first.
+pub const DELIVERED_TO_HEADERS: &[&str] = &[
+    "Delivered-To",
+    "X-Original-To",
+    "X-Delivered-To",
+    "Envelope-To",
+];
+
+/// Every address the user can send from: each account's own address and
+/// its plus-address aliases.
+pub fn send_identities(accounts: &[Account], aliases: &[PlusAlias]) -> Vec<SendIdentity> {
+    let mut identities: Vec<SendIdentity> = accounts
+        .iter()
+        .map(|account| SendIdentity {
+            account_id: account.id,
+            name: account.display_name.clone(),
+            address: account.email_address.clone(),
+        })
+        .collect();
+    for alias in aliases {
+        let Some(account) = accounts
+            .iter()
+            .find(|account| account.id == alias.account_id)
+        else {
+            continue;
+        };
+        if !identities
+            .iter()
+            .any(|identity| identity.address.eq_ignore_ascii_case(&alias.address))
+        {
+            identities.push(SendIdentity {
+                account_id: account.id,
+                name: account.display_name.clone(),
+                address: alias.address.clone(),
+            });
+        }
+    }
+    identities
+}
+
+/// Addresses `message` was delivered to according to its headers,
+/// lowercased, in [`DELIVERED_TO_HEADERS`] order.
+pub fn delivered_to(message: &MailMessage) -> Vec<String> {
+    let mut addresses = Vec::new();
+    for name in DELIVERED_TO_HEADERS {
+        for (key, value) in &message.headers {
+            if !key.eq_ignore_ascii_case(name) {
+                continue;
+            }
+            for address in value.split(',').filter_map(header_address) {
+                if !addresses.contains(&address) {
+                    addresses.push(address);
+                }
+            }
+        }
+    }
+    addresses
+}
+
+/// The identity to reply to `message` from. `None` when the account that
+/// received it is not among `identities`.
+pub fn reply_identity(message: &MailMessage, identities: &[SendIdentity]) -> Option<ReplyIdentity> {
+    let received_by = identities
+        .iter()
+        .find(|identity| identity.account_id == message.account_id)?
+        .clone();
+
+    let recipients = message
+        .to
+        .iter()
+        .chain(&message.cc)
+        .map(|recipient| recipient.address.trim().to_lowercase());
+    for address in delivered_to(message).into_iter().chain(recipients) {
+        if let Some(identity) = identity_for(&address, identities, message) {
+            return Some(ReplyIdentity {
+                suggested: identity,
+                delivered_to: Some(address),
+                received_by,
+            });
+        }
+    }
+    Some(ReplyIdentity {
+        suggested: received_by.clone(),
+        delivered_to: None,
+        received_by,
+    })
+}
+
+/// The identity for `address`, preferring the receiving account when more
+/// than one account shares it. An unknown `+tag` on a known address keeps
+/// the tag, so the reply goes out from the address the sender used.
+fn identity_for(
+    address: &str,
+    identities: &[SendIdentity],
+    message: &MailMessage,
+) -> Option<SendIdentity> {
+    let mut matches: Vec<&SendIdentity> = identities
+        .iter()
+        .filter(|identity| identity.address.eq_ignore_ascii_case(address))
+        .collect();
+    if matches.is_empty() {
+        let base = strip_plus_tag(address)?;
+        matches = identities
+            .iter()
+            .filter(|identity| identity.address.eq_ignore_ascii_case(&base))
+            .collect();
+        matches.sort_by_key(|identity| identity.account_id != message.account_id);
+        return matches.first().map(|identity| SendIdentity {
+            address: address.to_string(),
+            ..(*identity).clone()
+        });
+    }
+    matches.sort_by_key(|identity| identity.account_id != message.account_id);
+    matches.first().map(|identity| (*identity).clone())
+}
+
+fn strip_plus_tag(address: &str) -> Option<String> {
+    let (local, domain) = address.rsplit_once('@')?;
+    let (user, _) = local.split_once('+')?;
+    Some(format!("{user}@{domain}"))
+}
+
+/// The bare address in a header value such as `"Ana" <ana@example.com>`.
+fn header_address(value: &str) -> Option<String> {
+    let value = value.trim();
+    let address = match (value.rfind('<'), value.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &value[start + 1..end],
+        _ => value,
+    };
+    let address = address.trim();
+    address.contains('@').then(|| address.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cove_core::{MailAddress, Provider};
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn account(address: &str) -> Account {
+        Account {
+            id: Uuid::new_v4(),
+            provider: Provider::Generic,
+            protocols: Vec::new(),
+            display_name: "Me".to_string(),
+            email_address: address.to_string(),
+            oauth_profile: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn message(account_id: Uuid, to: &str, headers: &[(&str, &str)]) -> MailMessage {
+        MailMessage {
+            account_id,
+            to: vec![MailAddress {
+                name: None,
+                address: to.to_string(),
+            }],
+            headers: headers
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            ..MailMessage::fixture()
+        }
+    }
+
+    #[test]
+    fn delivery_headers_win_over_recipients() {
+        let work = account("me@work.example");
+        let home = account("me@home.example");
+        let identities = send_identities(&[work.clone(), home.clone()], &[]);
+        // Delivered to the home address through a list addressed elsewhere.
+        let list_mail = message(
+            home.id,
+            "team@lists.example",
+            &[("delivered-to", "Me <ME@home.example>")],
+        );
+        let reply = reply_identity(&list_mail, &identities).unwrap();
+        assert_eq!(reply.suggested.address, "me@home.example");
+        assert_eq!(reply.delivered_to.as_deref(), Some("me@home.example"));
+        assert!(reply.account_warning(&reply.suggested).is_none());
+        assert!(reply.account_warning(&identities[0]).is_some());
+    }
+
+    #[test]
+    fn aliases_and_plus_tags_are_kept() {
+        let work = account("me@work.example");
+        let alias = PlusAlias {
+            id: Uuid::new_v4(),
+            account_id: work.id,
+            address: "me+shop@work.example".to_string(),
+            tag: "shop".to_string(),
+            issued_to: "shop.example".to_string(),
+            created_at: Utc::now(),
+        };
+        let identities = send_identities(std::slice::from_ref(&work), &[alias]);
+        assert_eq!(identities.len(), 2);
+
+        let shop = message(work.id, "me+shop@work.example", &[]);
+        let reply = reply_identity(&shop, &identities).unwrap();
+        assert_eq!(reply.suggested.address, "me+shop@work.example");
+
+        let tagged = message(
+            work.id,
+            "x@y.example",
+            &[("X-Original-To", "me+news@work.example")],
+        );
+        let reply = reply_identity(&tagged, &identities).unwrap();
+        assert_eq!(reply.suggested.address, "me+news@work.example");
+        assert_eq!(reply.suggested.account_id, work.id);
+    }
+
+    #[test]
+    fn falls_back_to_the_receiving_account() {
+        let work = account("me@work.example");
+        let identities = send_identities(std::slice::from_ref(&work), &[]);
+        let bcc = message(work.id, "someone@else.example", &[]);
+        let reply = reply_identity(&bcc, &identities).unwrap();
+        assert_eq!(reply.suggested, reply.received_by);
+        assert_eq!(reply.delivered_to, None);
+        assert!(
+            reply_identity(&message(Uuid::new_v4(), "a@b.example", &[]), &identities).is_none()
+        );
+    }
+}
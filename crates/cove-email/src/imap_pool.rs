@@ -0,0 +1,472 @@
+//! Per-account pool of logged-in IMAP sessions.
+//!
+//! Opening a session costs a TCP connect, a TLS handshake and a LOGIN, and
+//! providers throttle accounts that do this for every folder. Sessions are
+//! checked out for one operation and handed back afterwards; idle sessions
+//! are kept alive with NOOP and negotiate COMPRESS=DEFLATE (RFC 4978) when
+//! the server offers it.
+
+use crate::backend::{imap_error_to_email, login_imap_client, ProtocolSettings};
+use crate::protocol_log::{ImapTap, ProtocolLog};
+use crate::EmailError;
+use cove_core::Provider;
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress, Status};
+use rustls_connector::{RustlsConnector, TlsStream};
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+/// Idle sessions kept per account. Most providers cap concurrent IMAP
+/// connections per user at a handful, so stay well below that.
+const MAX_IDLE_PER_ACCOUNT: usize = 2;
+/// Sessions idle longer than this are logged out rather than reused; servers
+/// must keep an idle session for at least 30 minutes (RFC 9051 5.4).
+const MAX_IDLE: Duration = Duration::from_secs(20 * 60);
+/// Sessions idle longer than this are probed with NOOP before reuse.
+const PROBE_AFTER: Duration = Duration::from_secs(60);
+/// How often the background keepalive touches idle sessions.
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(4 * 60);
+
+pub(crate) type PooledSession = imap::Session<ImapStream>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct PoolKey {
+    host: String,
+    port: u16,
+    username: String,
+}
+
+struct IdleSession {
+    session: PooledSession,
+    idle_since: Instant,
+}
+
+/// A session borrowed from the pool. Return it with [`ImapPool::checkin`];
+/// dropping it instead simply closes the connection.
+pub(crate) struct Checkout {
+    key: PoolKey,
+    pub session: PooledSession,
+}
+
+#[derive(Default)]
+pub(crate) struct ImapPool {
+    idle: Mutex<HashMap<PoolKey, Vec<IdleSession>>>,
+    keepalive_started: AtomicBool,
+}
+
+impl ImapPool {
+    /// Only implicit-TLS endpoints are pooled; anything else goes through
+    /// the regular per-operation connection.
+    pub fn supports(settings: &ProtocolSettings) -> bool {
+        settings.imap_host.is_some() && settings.imap_port.unwrap_or(993) == 993
+    }
+
+    pub fn checkout(
+        &self,
+        settings: &ProtocolSettings,
+        provider: &Provider,
+    ) -> Result<Checkout, EmailError> {
+        let key = PoolKey {
+            host: settings
+                .imap_host
+                .clone()
+                .ok_or_else(|| EmailError::Data("missing imap_host".to_string()))?,
+            port: settings.imap_port.unwrap_or(993),
+            username: settings.username.clone(),
+        };
+
+        while let Some(idle) = self.take_idle(&key) {
+            let IdleSession {
+                mut session,
+                idle_since,
+            } = idle;
+            if idle_since.elapsed() > MAX_IDLE {
+                let _ = session.logout();
+                continue;
+            }
+            if idle_since.elapsed() > PROBE_AFTER && session.noop().is_err() {
+                continue;
+            }
+            tracing::debug!(host = %key.host, user = %key.username, "reusing pooled imap session");
+            return Ok(Checkout { key, session });
+        }
+
+        let session = connect(&key, settings, provider)?;
+        Ok(Checkout { key, session })
+    }
+
+    /// Hand a session back. Pass `healthy = false` after a protocol or I/O
+    /// error so a connection in an unknown state is not reused.
+    pub fn checkin(self: &Arc<Self>, checkout: Checkout, healthy: bool) {
+        let Checkout { key, mut session } = checkout;
+        if !healthy {
+            return;
+        }
+
+        let mut idle = self.idle.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let sessions = idle.entry(key).or_default();
+        if sessions.len() >= MAX_IDLE_PER_ACCOUNT {
+            drop(idle);
+            let _ = session.logout();
+            return;
+        }
+        sessions.push(IdleSession {
+            session,
+            idle_since: Instant::now(),
+        });
+        drop(idle);
+
+        self.start_keepalive();
+    }
+
+    fn take_idle(&self, key: &PoolKey) -> Option<IdleSession> {
+        let mut idle = self.idle.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        // Most recently returned first: it is the least likely to have timed out.
+        idle.get_mut(key).and_then(Vec::pop)
+    }
+
+    /// NOOP every idle session and drop the ones that are stale or dead.
+    fn keepalive(&self) {
+        let drained = {
+            let mut idle = self.idle.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            std::mem::take(&mut *idle)
+        };
+
+        let mut alive = HashMap::<PoolKey, Vec<IdleSession>>::new();
+        for (key, sessions) in drained {
+            for mut idle in sessions {
+                if idle.idle_since.elapsed() > MAX_IDLE {
+                    let _ = idle.session.logout();
+                } else if idle.session.noop().is_ok() {
+                    alive.entry(key.clone()).or_default().push(idle);
+                }
+            }
+        }
+
+        let mut idle = self.idle.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        for (key, sessions) in alive {
+            let slot = idle.entry(key).or_default();
+            for session in sessions {
+                if slot.len() < MAX_IDLE_PER_ACCOUNT {
+                    slot.push(session);
+                }
+            }
+        }
+    }
+
+    fn start_keepalive(self: &Arc<Self>) {
+        if self.keepalive_started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let pool: Weak<Self> = Arc::downgrade(self);
+        let spawned = std::thread::Builder::new()
+            .name("imap-keepalive".to_string())
+            .spawn(move || loop {
+                std::thread::sleep(KEEPALIVE_INTERVAL);
+                match pool.upgrade() {
+                    Some(pool) => pool.keepalive(),
+                    None => break,
+                }
+            });
+        if spawned.is_err() {
+            self.keepalive_started.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+fn connect(
+    key: &PoolKey,
+    settings: &ProtocolSettings,
+    provider: &Provider,
+) -> Result<PooledSession, EmailError> {
+    let io_error = |err: io::Error| EmailError::Data(format!("imap connect failed: {err}"));
+
+    let tcp = TcpStream::connect((key.host.as_str(), key.port)).map_err(io_error)?;
+    tcp.set_read_timeout(Some(Duration::from_secs(60))).map_err(io_error)?;
+    let tls = RustlsConnector::new_with_native_certs()
+        .map_err(io_error)?
+        .connect(&key.host, tcp)
+        .map_err(|err| EmailError::Data(format!("imap tls handshake failed: {err}")))?;
+
+    let compressed = Arc::new(AtomicBool::new(false));
+    let tap = ImapTap::new(&key.username);
+    let mut client = imap::Client::new(ImapStream::new(tls, compressed.clone(), tap));
+    client.read_greeting().map_err(imap_error_to_email)?;
+    let mut session = login_imap_client(client, settings, provider)?;
+
+    let supports_deflate = session
+        .capabilities()
+        .map(|caps| caps.has_str("COMPRESS=DEFLATE"))
+        .unwrap_or(false);
+    if supports_deflate && session.run_command_and_check_ok("COMPRESS DEFLATE").is_ok() {
+        compressed.store(true, Ordering::SeqCst);
+        tracing::debug!(host = %key.host, "imap COMPRESS=DEFLATE enabled");
+    }
+
+    Ok(session)
+}
+
+// -- Transport ---------------------------------------------------------------
+
+/// TLS stream that switches to raw DEFLATE in both directions once the
+/// server has accepted `COMPRESS DEFLATE`. The switch is shared with the
+/// pool because the stream itself is owned by the `imap::Session`. The
+/// uncompressed traffic feeds the protocol log tap.
+pub(crate) struct ImapStream<S = TlsStream<TcpStream>> {
+    inner: S,
+    compressed: Arc<AtomicBool>,
+    deflate: Option<Box<Deflate>>,
+    tap: ImapTap,
+}
+
+struct Deflate {
+    compress: Compress,
+    decompress: Decompress,
+    input: Vec<u8>,
+    input_pos: usize,
+    input_len: usize,
+    output: Vec<u8>,
+}
+
+impl<S> ImapStream<S> {
+    fn new(inner: S, compressed: Arc<AtomicBool>, tap: ImapTap) -> Self {
+        Self {
+            inner,
+            compressed,
+            deflate: None,
+            tap,
+        }
+    }
+
+    fn deflate(&mut self) -> Option<&mut Deflate> {
+        if self.deflate.is_none() && self.compressed.load(Ordering::SeqCst) {
+            self.deflate = Some(Box::new(Deflate {
+                // RFC 4978 uses raw DEFLATE without a zlib header.
+                compress: Compress::new(Compression::default(), false),
+                decompress: Decompress::new(false),
+                input: vec![0; 16 * 1024],
+                input_pos: 0,
+                input_len: 0,
+                output: Vec::with_capacity(16 * 1024),
+            }));
+        }
+        self.deflate.as_deref_mut()
+    }
+}
+
+fn deflate_error(err: impl std::fmt::Display) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("imap deflate: {err}"))
+}
+
+impl<S: Read + Write> Read for ImapStream<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.read_plain(buf)?;
+        self.tap.received(ProtocolLog::global(), &buf[..read]);
+        Ok(read)
+    }
+}
+
+impl<S: Read + Write> Write for ImapStream<S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.write_plain(buf)?;
+        self.tap.sent(ProtocolLog::global(), &buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<S: Read + Write> ImapStream<S> {
+    fn read_plain(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.deflate().is_none() {
+            return self.inner.read(buf);
+        }
+
+        loop {
+            let Self { inner, deflate, .. } = self;
+            let deflate = deflate.as_deref_mut().expect("deflate state initialised above");
+
+            if deflate.input_pos == deflate.input_len {
+                let read = inner.read(&mut deflate.input)?;
+                if read == 0 {
+                    return Ok(0);
+                }
+                deflate.input_pos = 0;
+                deflate.input_len = read;
+            }
+
+            let before_in = deflate.decompress.total_in();
+            let before_out = deflate.decompress.total_out();
+            let status = deflate
+                .decompress
+                .decompress(
+                    &deflate.input[deflate.input_pos..deflate.input_len],
+                    buf,
+                    FlushDecompress::None,
+                )
+                .map_err(deflate_error)?;
+            let consumed = (deflate.decompress.total_in() - before_in) as usize;
+            deflate.input_pos += consumed;
+
+            let produced = (deflate.decompress.total_out() - before_out) as usize;
+            if produced > 0 {
+                return Ok(produced);
+            }
+            // COMPRESS=DEFLATE never ends the stream, and input that yields
+            // nothing at all would be retried forever on a pooled session.
+            if status == Status::StreamEnd {
+                return Err(deflate_error("server ended the compressed stream"));
+            }
+            if consumed == 0 {
+                return Err(deflate_error("compressed input made no progress"));
+            }
+        }
+    }
+
+    fn write_plain(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.deflate().is_none() {
+            return self.inner.write(buf);
+        }
+
+        let Self { inner, deflate, .. } = self;
+        let deflate = deflate.as_deref_mut().expect("deflate state initialised above");
+
+        // Sync-flush every write so each command reaches the server whole.
+        deflate.output.clear();
+        let mut consumed = 0;
+        loop {
+            deflate.output.reserve(buf.len() / 2 + 64);
+            let before = deflate.compress.total_in();
+            deflate
+                .compress
+                .compress_vec(&buf[consumed..], &mut deflate.output, FlushCompress::Sync)
+                .map_err(deflate_error)?;
+            consumed += (deflate.compress.total_in() - before) as usize;
+            if consumed == buf.len() && deflate.output.len() < deflate.output.capacity() {
+                break;
+            }
+        }
+
+        inner.write_all(&deflate.output)?;
+        Ok(buf.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The server end of a connection: hands out `incoming` at most `chunk`
+    /// bytes per read and keeps everything the client wrote.
+    #[derive(Default)]
+    struct Peer {
+        incoming: Vec<u8>,
+        position: usize,
+        chunk: usize,
+        written: Vec<u8>,
+    }
+
+    impl Read for Peer {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let remaining = &self.incoming[self.position..];
+            let read = remaining.len().min(buf.len()).min(self.chunk);
+            buf[..read].copy_from_slice(&remaining[..read]);
+            self.position += read;
+            Ok(read)
+        }
+    }
+
+    impl Write for Peer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.written.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn compressed(peer: Peer) -> ImapStream<Peer> {
+        let tap = ImapTap::new("me@example.com");
+        ImapStream::new(peer, Arc::new(AtomicBool::new(true)), tap)
+    }
+
+    #[test]
+    fn each_deflate_write_decodes_on_its_own() {
+        let mut stream = compressed(Peer::default());
+        let mut server = Decompress::new(false);
+        let mut boundary = 0;
+        for command in [&b"a1 NOOP\r\n"[..], b"a2 SELECT INBOX\r\n"] {
+            stream.write_all(command).unwrap();
+            let frame = &stream.inner.written[boundary..];
+            // A sync flush ends in an empty stored block, so the server can
+            // decode the command without waiting for more input.
+            assert!(frame.ends_with(&[0x00, 0x00, 0xff, 0xff]));
+            let mut plain = Vec::with_capacity(256);
+            server
+                .decompress_vec(frame, &mut plain, FlushDecompress::Sync)
+                .unwrap();
+            assert_eq!(plain, command);
+            boundary = stream.inner.written.len();
+        }
+    }
+
+    #[test]
+    fn deflate_reads_survive_split_input_and_small_buffers() {
+        let response: Vec<u8> = (0..2000)
+            .flat_map(|n| format!("* {n} FETCH (UID {})\r\n", n * 7).into_bytes())
+            .collect();
+        let (first, second) = response.split_at(response.len() / 2);
+        let mut server = Compress::new(Compression::default(), false);
+        let mut incoming = Vec::with_capacity(response.len() + 1024);
+        for reply in [first, second] {
+            server
+                .compress_vec(reply, &mut incoming, FlushCompress::Sync)
+                .unwrap();
+        }
+        assert_eq!(server.total_in() as usize, response.len());
+
+        let mut stream = compressed(Peer {
+            incoming,
+            chunk: 5,
+            ..Peer::default()
+        });
+        let mut plain = Vec::new();
+        let mut buf = [0; 13];
+        while plain.len() < response.len() {
+            let read = stream.read(&mut buf).unwrap();
+            assert!(read > 0, "stream ended after {} bytes", plain.len());
+            plain.extend_from_slice(&buf[..read]);
+        }
+        assert_eq!(plain, response);
+    }
+
+    #[test]
+    fn deflate_read_fails_instead_of_spinning_after_stream_end() {
+        // A finished stream followed by bytes the decoder will never take.
+        let mut server = Compress::new(Compression::default(), false);
+        let mut incoming = Vec::with_capacity(256);
+        server
+            .compress_vec(b"* BYE\r\n", &mut incoming, FlushCompress::Finish)
+            .unwrap();
+        incoming.extend_from_slice(b"trailing");
+
+        let mut stream = compressed(Peer {
+            incoming,
+            chunk: 64,
+            ..Peer::default()
+        });
+        let mut buf = [0; 64];
+        let read = stream.read(&mut buf).unwrap();
+        assert_eq!(&buf[..read], b"* BYE\r\n");
+        let err = stream.read(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -1,10 +1,51 @@
+mod aliases;
+mod attachment_policy;
 mod backend;
+mod downloads;
+mod dry_run;
 mod error;
+mod groups;
+mod identity;
+mod imap_pool;
+mod merge;
+mod offline;
+mod outbox;
+mod protocol_log;
+mod recipients;
+mod sanitize;
 mod service;
+mod smtp_pool;
+mod snippets;
+mod transcript;
+mod triage;
 
+pub use aliases::{
+    alias_tag_for_sender, is_expected_alias_sender, normalize_alias_tag, plus_address,
+    sender_domain,
+};
+pub use attachment_policy::{
+    inspect_zip, ArchiveSummary, AttachmentPolicy, AttachmentRelease, AttachmentVerdict,
+    DEFAULT_BLOCKED_EXTENSIONS,
+};
 pub use backend::{
-    default_protocol_for_provider, EmailBackend, EwsBackend, FetchResult, ImapSmtpBackend,
-    JmapBackend, OutgoingAttachment, OutgoingMail, ProtocolSettings,
+    default_protocol_for_provider, is_imap_uid, uses_imap_uids, BackendCapabilities, EmailBackend,
+    EwsBackend, FetchPage, FetchResult, ImapSmtpBackend, JmapBackend, OutgoingAttachment,
+    OutgoingMail, ProtocolSettings, SendReceipt,
 };
+pub use downloads::{safe_file_name, unique_download_path, SaveAllReport, SkippedAttachment};
+pub use dry_run::{action_changes, DRY_RUN_SAMPLE_SIZE};
 pub use error::EmailError;
-pub use service::EmailService;
+pub use groups::{expand_group_sends, new_message_id};
+pub use identity::{delivered_to, reply_identity, send_identities, DELIVERED_TO_HEADERS};
+pub use merge::{merge_variables, parse_merge_csv, render_merge_text, MergeTable};
+pub use offline::{OfflineReplay, ReplayError};
+pub use outbox::{next_working_time, OUTBOX_FOLDER};
+pub use protocol_log::{ProtocolLog, PROTOCOL_LOG_CAPACITY};
+pub use recipients::{parse_recipient, split_recipient_input};
+pub use sanitize::sanitize_html;
+pub use service::{EmailService, BACKFILL_PAGE_SIZE};
+pub use snippets::{
+    expand_snippet_at, is_valid_abbreviation, SnippetExpansion, SNIPPET_CURSOR_MARKER,
+};
+pub use transcript::{render_transcript, thread_to_markdown, TranscriptFormat};
+pub use triage::{reply_needed_score, REPLY_CANDIDATE_SCORE, REPLY_NEEDED_SCORE};
@@ -0,0 +1,188 @@
+//! Mail merge helpers: CSV recipient tables and `{{field}}` substitution.
+//!
+//! The CSV reader follows RFC 4180 (quoted fields, doubled quotes, embedded
+//! newlines) which covers what spreadsheet exports produce.
+
+use crate::EmailError;
+use std::collections::BTreeMap;
+
+/// Column names accepted as the recipient address, compared case-insensitively.
+const ADDRESS_COLUMNS: &[&str] = &["email", "email_address", "e-mail", "address"];
+
+/// Parsed recipient CSV. Each row maps header to value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeTable {
+    pub headers: Vec<String>,
+    pub rows: Vec<BTreeMap<String, String>>,
+}
+
+impl MergeTable {
+    /// Header holding recipient addresses, if any.
+    pub fn address_column(&self) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|header| {
+                ADDRESS_COLUMNS
+                    .iter()
+                    .any(|name| header.eq_ignore_ascii_case(name))
+            })
+            .map(String::as_str)
+    }
+}
+
+/// Parse a header row followed by one recipient per line. Blank lines are
+/// skipped; short rows are padded with empty values.
+pub fn parse_merge_csv(input: &str) -> Result<MergeTable, EmailError> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let mut records = parse_csv_records(input)?
+        .into_iter()
+        .filter(|record| record.iter().any(|value| !value.trim().is_empty()));
+
+    let headers: Vec<String> = records
+        .next()
+        .ok_or_else(|| EmailError::Data("CSV has no header row".to_string()))?
+        .into_iter()
+        .map(|header| header.trim().to_string())
+        .collect();
+    if headers.iter().any(String::is_empty) {
+        return Err(EmailError::Data("CSV header has an empty column name".to_string()));
+    }
+
+    let mut rows = Vec::new();
+    for (index, record) in records.enumerate() {
+        if record.len() > headers.len() {
+            return Err(EmailError::Data(format!(
+                "CSV row {} has {} columns, expected {}",
+                index + 2,
+                record.len(),
+                headers.len()
+            )));
+        }
+        let mut row = BTreeMap::new();
+        for (position, header) in headers.iter().enumerate() {
+            let value = record.get(position).map(|value| value.trim()).unwrap_or_default();
+            row.insert(header.clone(), value.to_string());
+        }
+        rows.push(row);
+    }
+
+    Ok(MergeTable { headers, rows })
+}
+
+fn parse_csv_records(input: &str) -> Result<Vec<Vec<String>>, EmailError> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if in_quotes {
+            match ch {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(ch),
+            }
+            continue;
+        }
+        match ch {
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                in_quotes = true;
+            }
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(ch),
+        }
+    }
+    if in_quotes {
+        return Err(EmailError::Data("CSV has an unterminated quoted field".to_string()));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Distinct `{{name}}` variables in `text`, in order of first use.
+pub fn merge_variables(text: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        let name = after[..end].trim();
+        if !name.is_empty() && !names.iter().any(|known| known == name) {
+            names.push(name.to_string());
+        }
+        rest = &after[end + 2..];
+    }
+    names
+}
+
+/// Replace every `{{name}}` with the matching field. Unknown variables are
+/// an error so a typo never reaches a recipient as literal braces.
+pub fn render_merge_text(
+    text: &str,
+    fields: &BTreeMap<String, String>,
+) -> Result<String, EmailError> {
+    let mut rendered = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        let name = after[..end].trim();
+        let value = fields
+            .get(name)
+            .or_else(|| {
+                fields
+                    .iter()
+                    .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                    .map(|(_, value)| value)
+            })
+            .ok_or_else(|| EmailError::Data(format!("no CSV column for {{{{{name}}}}}")))?;
+        rendered.push_str(&rest[..start]);
+        rendered.push_str(value);
+        rest = &after[end + 2..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_quoted_csv_and_renders_fields() {
+        let table = parse_merge_csv(
+            "Email,first_name,note\r\na@example.com,Ann,\"Hi, \"\"there\"\"\"\n\nb@example.com,Bob\n",
+        )
+        .unwrap();
+        assert_eq!(table.headers, vec!["Email", "first_name", "note"]);
+        assert_eq!(table.rows.len(), 2);
+        assert_eq!(table.rows[0]["note"], "Hi, \"there\"");
+        assert_eq!(table.rows[1]["note"], "");
+        assert_eq!(table.address_column(), Some("Email"));
+
+        let body = "Dear {{ first_name }}, {{note}}";
+        assert_eq!(merge_variables(body), vec!["first_name", "note"]);
+        assert_eq!(
+            render_merge_text(body, &table.rows[0]).unwrap(),
+            "Dear Ann, Hi, \"there\""
+        );
+        assert!(render_merge_text("{{company}}", &table.rows[0]).is_err());
+    }
+}
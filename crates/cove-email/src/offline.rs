@@ -0,0 +1,234 @@
+//! Replay of changes queued while a provider was unreachable, shared by both
+//! shells. The shells only load settings and push one change; ordering,
+//! holding back unreachable accounts and the queue bookkeeping live here.
+
+use crate::EmailError;
+use cove_core::{OfflineQueueStatus, PendingOperation, PendingOperationStatus};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// Payload of a queued archive or delete.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct QueuedMessageChange {
+    pub folder_path: String,
+    pub remote_ids: Vec<String>,
+    /// The folder's UIDVALIDITY when the change was made, for IMAP UIDs.
+    #[serde(default)]
+    pub uid_validity: Option<u32>,
+}
+
+/// Why a queued change could not be replayed.
+#[derive(Debug, Clone)]
+pub struct ReplayError {
+    /// The provider was unreachable, so the change stays queued.
+    pub unreachable: bool,
+    pub message: String,
+}
+
+impl ReplayError {
+    pub fn new(unreachable: bool, message: impl Into<String>) -> Self {
+        Self {
+            unreachable,
+            message: message.into(),
+        }
+    }
+
+    /// A failure that retrying will not fix, such as a missing account.
+    pub fn permanent(message: impl Into<String>) -> Self {
+        Self::new(false, message)
+    }
+}
+
+impl From<EmailError> for ReplayError {
+    fn from(err: EmailError) -> Self {
+        Self::new(err.is_network(), err.to_string())
+    }
+}
+
+/// One pass over the offline queue, oldest change first. Start it with
+/// [`crate::EmailService::begin_replay`], push each change returned by
+/// [`OfflineReplay::next_operation`] and hand the result to
+/// [`crate::EmailService::record_replay`].
+#[derive(Debug)]
+pub struct OfflineReplay {
+    operations: std::vec::IntoIter<PendingOperation>,
+    unreachable: HashSet<Uuid>,
+    status: OfflineQueueStatus,
+}
+
+impl OfflineReplay {
+    pub(crate) fn new(operations: Vec<PendingOperation>) -> Self {
+        Self {
+            operations: operations.into_iter(),
+            unreachable: HashSet::new(),
+            status: OfflineQueueStatus::default(),
+        }
+    }
+
+    /// The next change to push. Failed and conflicting changes wait for the
+    /// user, and an account whose provider is still unreachable keeps its
+    /// remaining changes queued, in order.
+    pub fn next_operation(&mut self) -> Option<PendingOperation> {
+        for op in self.operations.by_ref() {
+            if op.status != PendingOperationStatus::Pending {
+                self.status.needs_attention += 1;
+            } else if self.unreachable.contains(&op.account_id) {
+                self.status.pending += 1;
+            } else {
+                return Some(op);
+            }
+        }
+        None
+    }
+
+    /// Counts for the offline banner once the pass is done.
+    pub fn finish(mut self) -> OfflineQueueStatus {
+        while self.next_operation().is_some() {
+            self.status.pending += 1;
+        }
+        self.status.offline = !self.unreachable.is_empty();
+        self.status
+    }
+
+    /// Count the result of one push and return the status the change
+    /// should be stored with, or `None` when it was applied.
+    pub(crate) fn count(
+        &mut self,
+        op: &PendingOperation,
+        result: &Result<cove_core::ReplayOutcome, ReplayError>,
+    ) -> Option<PendingOperationStatus> {
+        match result {
+            Ok(cove_core::ReplayOutcome::Applied) => {
+                self.status.replayed += 1;
+                None
+            }
+            Ok(cove_core::ReplayOutcome::Conflict(_)) => {
+                self.status.needs_attention += 1;
+                Some(PendingOperationStatus::Conflict)
+            }
+            Err(err) if err.unreachable => {
+                self.unreachable.insert(op.account_id);
+                self.status.pending += 1;
+                Some(PendingOperationStatus::Pending)
+            }
+            Err(_) => {
+                self.status.needs_attention += 1;
+                Some(PendingOperationStatus::Failed)
+            }
+        }
+    }
+}
+
+/// Keep only the newest queued change per item, so an item edited twice
+/// offline is pushed once with its final state instead of the second edit
+/// conflicting with the first. Returns the kept changes in queue order and
+/// the ids of the superseded ones.
+pub(crate) fn coalesce(operations: Vec<PendingOperation>) -> (Vec<PendingOperation>, Vec<Uuid>) {
+    let key = |op: &PendingOperation| {
+        op.target_id
+            .map(|target_id| (op.account_id, op.kind, target_id))
+    };
+    let mut newest = HashMap::new();
+    for op in &operations {
+        let Some(key) = key(op) else {
+            continue;
+        };
+        let entry = newest.entry(key).or_insert((op.id, op.updated_at));
+        if op.updated_at >= entry.1 {
+            *entry = (op.id, op.updated_at);
+        }
+    }
+
+    let (kept, superseded): (Vec<_>, Vec<_>) =
+        operations.into_iter().partition(|op| match key(op) {
+            Some(key) => newest.get(&key).map(|(id, _)| *id) == Some(op.id),
+            None => true,
+        });
+    (kept, superseded.into_iter().map(|op| op.id).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cove_core::{PendingOperationKind, ReplayOutcome};
+    use chrono::{Duration, Utc};
+
+    fn op(
+        account_id: Uuid,
+        kind: PendingOperationKind,
+        target_id: Option<Uuid>,
+        minutes: i64,
+    ) -> PendingOperation {
+        let at = Utc::now() + Duration::minutes(minutes);
+        PendingOperation {
+            id: Uuid::new_v4(),
+            account_id,
+            kind,
+            status: PendingOperationStatus::Pending,
+            target_id,
+            summary: String::new(),
+            payload_json: serde_json::Value::Null,
+            attempt_count: 1,
+            last_error: None,
+            created_at: at,
+            updated_at: at,
+        }
+    }
+
+    #[test]
+    fn coalesce_keeps_the_newest_change_per_item() {
+        let account = Uuid::new_v4();
+        let event = Uuid::new_v4();
+        let first = op(account, PendingOperationKind::UpsertEvent, Some(event), 0);
+        let send = op(account, PendingOperationKind::SendMail, None, 1);
+        let second = op(account, PendingOperationKind::UpsertEvent, Some(event), 2);
+        let other = op(account, PendingOperationKind::UpsertEvent, Some(Uuid::new_v4()), 3);
+
+        let (kept, superseded) = coalesce(vec![
+            first.clone(),
+            send.clone(),
+            second.clone(),
+            other.clone(),
+        ]);
+        let kept = kept.iter().map(|op| op.id).collect::<Vec<_>>();
+        assert_eq!(kept, vec![send.id, second.id, other.id]);
+        assert_eq!(superseded, vec![first.id]);
+    }
+
+    #[test]
+    fn unreachable_account_holds_its_later_changes() {
+        let down = Uuid::new_v4();
+        let up = Uuid::new_v4();
+        let mut failed = op(up, PendingOperationKind::SendMail, None, 0);
+        failed.status = PendingOperationStatus::Failed;
+        let queue = vec![
+            failed,
+            op(down, PendingOperationKind::SendMail, None, 1),
+            op(up, PendingOperationKind::SendMail, None, 2),
+            op(down, PendingOperationKind::SendMail, None, 3),
+            op(up, PendingOperationKind::SendMail, None, 4),
+        ];
+
+        let mut replay = OfflineReplay::new(queue);
+        let mut pushed = Vec::new();
+        while let Some(op) = replay.next_operation() {
+            pushed.push(op.account_id);
+            let result = if op.account_id == down {
+                Err(ReplayError::new(true, "connection refused"))
+            } else if pushed.len() == 2 {
+                Ok(ReplayOutcome::Conflict("changed".to_string()))
+            } else {
+                Ok(ReplayOutcome::Applied)
+            };
+            replay.count(&op, &result);
+        }
+
+        assert_eq!(pushed, vec![down, up, up]);
+        let status = replay.finish();
+        assert_eq!(status.replayed, 1);
+        assert_eq!(status.pending, 2);
+        assert_eq!(status.needs_attention, 2);
+        assert!(status.offline);
+    }
+}
@@ -0,0 +1,83 @@
+//! Guardrails applied by the outbox before it sends: mail written outside
+//! working hours waits for the next workday, and sends are throttled per
+//! account (see `EmailService::outbox_budget`).
+
+use cove_core::SendGuardrails;
+use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike, Utc, Weekday};
+
+/// Folder holding messages waiting in the outbox.
+pub const OUTBOX_FOLDER: &str = "Outbox";
+
+/// When mail written at `now` should go out instead, or `None` to send it
+/// right away. `now` carries the sender's local time zone.
+pub fn next_working_time<Tz: TimeZone>(
+    guardrails: &SendGuardrails,
+    now: &DateTime<Tz>,
+) -> Option<DateTime<Utc>> {
+    let start = guardrails.workday_start_hour;
+    let end = guardrails.workday_end_hour.min(24);
+    if !guardrails.defer_outside_working_hours || start >= end {
+        return None;
+    }
+    let workday =
+        |day: Weekday| !guardrails.skip_weekends || !matches!(day, Weekday::Sat | Weekday::Sun);
+    if workday(now.weekday()) && (start..end).contains(&now.hour()) {
+        return None;
+    }
+
+    let today = now.date_naive();
+    let first = if now.hour() < start { 0 } else { 1 };
+    (first..first + 8)
+        .map(|offset| today + Duration::days(offset))
+        .find(|date| workday(date.weekday()))
+        .and_then(|date| date.and_hms_opt(start, 0, 0))
+        .and_then(|morning| now.timezone().from_local_datetime(&morning).earliest())
+        .map(|morning| morning.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    fn at(text: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(text).unwrap()
+    }
+
+    #[test]
+    fn defers_to_the_next_workday_morning() {
+        let guardrails = SendGuardrails {
+            defer_outside_working_hours: true,
+            ..SendGuardrails::default()
+        };
+        // Wednesday afternoon is inside working hours.
+        assert_eq!(
+            next_working_time(&guardrails, &at("2025-03-05T14:00:00+01:00")),
+            None
+        );
+        // Wednesday night waits for Thursday 08:00 local.
+        assert_eq!(
+            next_working_time(&guardrails, &at("2025-03-05T22:30:00+01:00")),
+            Some(at("2025-03-06T08:00:00+01:00").with_timezone(&Utc))
+        );
+        // Early Thursday waits for the same morning.
+        assert_eq!(
+            next_working_time(&guardrails, &at("2025-03-06T05:00:00+01:00")),
+            Some(at("2025-03-06T08:00:00+01:00").with_timezone(&Utc))
+        );
+        // Saturday waits for Monday.
+        assert_eq!(
+            next_working_time(&guardrails, &at("2025-03-08T11:00:00+01:00")),
+            Some(at("2025-03-10T08:00:00+01:00").with_timezone(&Utc))
+        );
+    }
+
+    #[test]
+    fn disabled_guardrails_never_defer() {
+        let guardrails = SendGuardrails::default();
+        assert_eq!(
+            next_working_time(&guardrails, &at("2025-03-08T23:00:00+00:00")),
+            None
+        );
+    }
+}
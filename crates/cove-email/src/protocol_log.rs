@@ -0,0 +1,559 @@
+//! Protocol debug log: the last exchanges with each account's server, kept
+//! in memory for the developer console and support bundles.
+//!
+//! Nothing is recorded until the console turns recording on. Credentials
+//! and mail never reach the log: HTTP authorization headers and request
+//! bodies are dropped, IMAP passwords and continuation data (AUTHENTICATE
+//! responses, APPEND literals) are left out, and password- and token-like
+//! fields are masked. Long requests are cut short.
+
+use cove_core::{ProtocolExchange, ProtocolKind, SupportBundle};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+/// Exchanges kept per account unless the console picks another size.
+pub const PROTOCOL_LOG_CAPACITY: usize = 200;
+
+/// Longest request or response text kept, in bytes.
+const MAX_TEXT: usize = 2048;
+
+/// Longest IMAP line buffered while looking for its end.
+const MAX_IMAP_LINE: usize = 1024;
+
+/// Unanswered IMAP commands tracked per connection.
+const MAX_PENDING: usize = 32;
+
+const REDACTED: &str = "[redacted]";
+
+/// Headers never recorded.
+const SECRET_HEADERS: &[&str] = &["authorization", "proxy-authorization", "cookie"];
+
+/// Ring buffers of recent exchanges, one per account login.
+pub struct ProtocolLog {
+    enabled: AtomicBool,
+    capacity: AtomicUsize,
+    exchanges: Mutex<HashMap<String, VecDeque<ProtocolExchange>>>,
+}
+
+impl ProtocolLog {
+    fn new(capacity: usize) -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            capacity: AtomicUsize::new(capacity.max(1)),
+            exchanges: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The log every backend records into.
+    pub fn global() -> &'static ProtocolLog {
+        static LOG: OnceLock<ProtocolLog> = OnceLock::new();
+        LOG.get_or_init(|| ProtocolLog::new(PROTOCOL_LOG_CAPACITY))
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity.load(Ordering::Relaxed)
+    }
+
+    /// Keep at most `capacity` exchanges per account, dropping the oldest.
+    pub fn set_capacity(&self, capacity: usize) {
+        let capacity = capacity.max(1);
+        self.capacity.store(capacity, Ordering::Relaxed);
+        let mut exchanges = self.lock();
+        for buffer in exchanges.values_mut() {
+            while buffer.len() > capacity {
+                buffer.pop_front();
+            }
+        }
+    }
+
+    /// Add `exchange` to its account's buffer while recording is on.
+    pub fn record(&self, exchange: ProtocolExchange) {
+        if !self.is_enabled() {
+            return;
+        }
+        let capacity = self.capacity();
+        let mut exchanges = self.lock();
+        let buffer = exchanges.entry(exchange.account.clone()).or_default();
+        buffer.push_back(exchange);
+        while buffer.len() > capacity {
+            buffer.pop_front();
+        }
+    }
+
+    /// Accounts with recorded exchanges, sorted.
+    pub fn accounts(&self) -> Vec<String> {
+        let mut accounts: Vec<String> = self.lock().keys().cloned().collect();
+        accounts.sort();
+        accounts
+    }
+
+    /// Recorded exchanges, oldest first. `account` narrows them to one login.
+    pub fn exchanges(&self, account: Option<&str>) -> Vec<ProtocolExchange> {
+        let exchanges = self.lock();
+        let mut out: Vec<ProtocolExchange> = exchanges
+            .iter()
+            .filter(|(login, _)| account.map_or(true, |account| account == login.as_str()))
+            .flat_map(|(_, buffer)| buffer.iter().cloned())
+            .collect();
+        out.sort_by_key(|exchange| exchange.started_at);
+        out
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// The recorded exchanges with the app version and OS, ready to attach
+    /// to a support request.
+    pub fn support_bundle(&self, account: Option<&str>) -> SupportBundle {
+        SupportBundle {
+            generated_at: Utc::now(),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+            exchanges: self.exchanges(account),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, VecDeque<ProtocolExchange>>> {
+        self.exchanges
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Mask passwords, tokens and secrets in `text` and cut it to a loggable
+/// length.
+pub fn redact(text: &str) -> String {
+    static PATTERNS: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+    let patterns = PATTERNS.get_or_init(|| {
+        [
+            (
+                concat!(
+                    r#"(?i)("(?:password|passwd|access_token|refresh_token|id_token|"#,
+                    r#"client_secret|token|secret)"\s*:\s*")[^"]*(")"#,
+                ),
+                "${1}[redacted]${2}",
+            ),
+            (
+                concat!(
+                    r"(?i)\b((?:access_token|refresh_token|id_token|client_secret|token|",
+                    r"password|key)=)[^&\s]*",
+                ),
+                "${1}[redacted]",
+            ),
+            (r"(?i)(<(?:\w+:)?Password>)[^<]*(</)", "${1}[redacted]${2}"),
+            (r"(?i)\b(bearer\s+)[A-Za-z0-9._~+/=-]+", "${1}[redacted]"),
+        ]
+        .into_iter()
+        .map(|(pattern, replacement)| {
+            (
+                Regex::new(pattern).expect("valid redaction pattern"),
+                replacement,
+            )
+        })
+        .collect()
+    });
+
+    let mut text = text.to_string();
+    for (pattern, replacement) in patterns {
+        text = pattern.replace_all(&text, *replacement).into_owned();
+    }
+    truncate(text)
+}
+
+fn truncate(mut text: String) -> String {
+    if text.len() <= MAX_TEXT {
+        return text;
+    }
+    let total = text.len();
+    let mut end = MAX_TEXT;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.truncate(end);
+    text.push_str(&format!("… ({total} bytes)"));
+    text
+}
+
+/// An IMAP command line with its password left out.
+fn redact_imap_command(line: &str) -> String {
+    let mut parts = line.splitn(4, ' ');
+    let tag = parts.next().unwrap_or_default();
+    let command = parts.next().unwrap_or_default();
+    let first = parts.next();
+    let rest = parts.next();
+    if command.eq_ignore_ascii_case("LOGIN") {
+        return format!("{tag} {command} {} {REDACTED}", first.unwrap_or_default());
+    }
+    if command.eq_ignore_ascii_case("AUTHENTICATE") {
+        // SASL-IR sends the initial response on the command line.
+        let initial = if rest.is_some() { " [redacted]" } else { "" };
+        return format!("{tag} {command} {}{initial}", first.unwrap_or_default());
+    }
+    redact(line)
+}
+
+/// Whether `line`, written by the client, starts a tagged command rather
+/// than carrying continuation data.
+fn imap_command_tag(line: &str) -> Option<&str> {
+    let mut parts = line.split(' ');
+    let tag = parts.next()?;
+    let command = parts.next()?;
+    let is_tag = !tag.is_empty()
+        && tag != "+"
+        && tag != "*"
+        && tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '.');
+    let is_command = !command.is_empty() && command.chars().all(|c| c.is_ascii_alphabetic());
+    (is_tag && is_command).then_some(tag)
+}
+
+/// Size of the literal announced at the end of `line` (`{12}` or `{12+}`).
+fn imap_literal_size(line: &str) -> Option<usize> {
+    let open = line.strip_suffix('}')?.rfind('{')?;
+    line[open + 1..line.len() - 1]
+        .trim_end_matches('+')
+        .parse()
+        .ok()
+}
+
+struct PendingCommand {
+    tag: String,
+    request: String,
+    started_at: DateTime<Utc>,
+    started: Instant,
+    received: usize,
+}
+
+/// Watches the plaintext of one IMAP connection and records each command
+/// with its tagged completion. Line and literal boundaries are followed
+/// whether or not the log is recording, so switching recording on halfway
+/// through an APPEND never mistakes message data for a command.
+pub(crate) struct ImapTap {
+    account: String,
+    outgoing: Vec<u8>,
+    incoming: Vec<u8>,
+    /// Outgoing literal bytes still to skip.
+    literal: usize,
+    pending: VecDeque<PendingCommand>,
+}
+
+impl ImapTap {
+    pub(crate) fn new(account: &str) -> Self {
+        Self {
+            account: account.to_string(),
+            outgoing: Vec::new(),
+            incoming: Vec::new(),
+            literal: 0,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Bytes the client wrote; commands are tracked while `log` records.
+    pub(crate) fn sent(&mut self, log: &ProtocolLog, bytes: &[u8]) {
+        let mut bytes = bytes;
+        while !bytes.is_empty() {
+            if self.literal > 0 {
+                let skipped = self.literal.min(bytes.len());
+                self.literal -= skipped;
+                bytes = &bytes[skipped..];
+                continue;
+            }
+            let Some(end) = bytes.iter().position(|&byte| byte == b'\n') else {
+                push_capped(&mut self.outgoing, bytes);
+                return;
+            };
+            push_capped(&mut self.outgoing, &bytes[..end]);
+            bytes = &bytes[end + 1..];
+            let line = String::from_utf8_lossy(&self.outgoing)
+                .trim_end()
+                .to_string();
+            self.outgoing.clear();
+            self.literal = imap_literal_size(&line).unwrap_or(0);
+            if !log.is_enabled() {
+                continue;
+            }
+            if let Some(tag) = imap_command_tag(&line) {
+                if self.pending.len() >= MAX_PENDING {
+                    self.pending.pop_front();
+                }
+                self.pending.push_back(PendingCommand {
+                    tag: tag.to_string(),
+                    request: redact_imap_command(&line),
+                    started_at: Utc::now(),
+                    started: Instant::now(),
+                    received: 0,
+                });
+            }
+        }
+    }
+
+    /// Bytes the server sent; completed commands go to `log`.
+    pub(crate) fn received(&mut self, log: &ProtocolLog, bytes: &[u8]) {
+        if let Some(command) = self.pending.front_mut() {
+            command.received += bytes.len();
+        }
+        let mut bytes = bytes;
+        while let Some(end) = bytes.iter().position(|&byte| byte == b'\n') {
+            push_capped(&mut self.incoming, &bytes[..end]);
+            bytes = &bytes[end + 1..];
+            let line = String::from_utf8_lossy(&self.incoming)
+                .trim_end()
+                .to_string();
+            self.incoming.clear();
+            self.complete(log, &line);
+        }
+        push_capped(&mut self.incoming, bytes);
+    }
+
+    fn complete(&mut self, log: &ProtocolLog, line: &str) {
+        let Some(index) = self.pending.iter().position(|command| {
+            line.strip_prefix(command.tag.as_str())
+                .is_some_and(|rest| rest.starts_with(' '))
+        }) else {
+            return;
+        };
+        let Some(command) = self.pending.remove(index) else {
+            return;
+        };
+        log.record(ProtocolExchange {
+            account: self.account.clone(),
+            protocol: ProtocolKind::Imap,
+            started_at: command.started_at,
+            request: command.request,
+            response: Some(format!("{} ({} bytes)", redact(line), command.received)),
+            duration_ms: Some(command.started.elapsed().as_millis() as u64),
+        });
+    }
+}
+
+fn push_capped(buffer: &mut Vec<u8>, bytes: &[u8]) {
+    let room = MAX_IMAP_LINE.saturating_sub(buffer.len());
+    buffer.extend_from_slice(&bytes[..bytes.len().min(room)]);
+}
+
+/// `request.send()`, recording the exchange while the protocol log is on.
+pub(crate) async fn send_logged(
+    request: reqwest::RequestBuilder,
+    protocol: ProtocolKind,
+    account: &str,
+) -> reqwest::Result<reqwest::Response> {
+    let log = ProtocolLog::global();
+    if !log.is_enabled() {
+        return request.send().await;
+    }
+
+    let (client, request) = request.build_split();
+    let request = request?;
+    let summary = http_request_summary(&request, protocol);
+    let started_at = Utc::now();
+    let started = Instant::now();
+    let response = client.execute(request).await;
+    let outcome = match &response {
+        Ok(response) => match response.content_length() {
+            Some(length) => format!("HTTP {} ({length} bytes)", response.status()),
+            None => format!("HTTP {}", response.status()),
+        },
+        Err(err) => format!("error: {err}"),
+    };
+    log.record(ProtocolExchange {
+        account: account.to_string(),
+        protocol,
+        started_at,
+        request: summary,
+        response: Some(redact(&outcome)),
+        duration_ms: Some(started.elapsed().as_millis() as u64),
+    });
+    response
+}
+
+/// Method, URL, headers other than credentials, body length and, for JMAP,
+/// the method names of `request`. The body itself is never kept: for Graph
+/// and JMAP sends it is the user's mail.
+fn http_request_summary(request: &reqwest::Request, protocol: ProtocolKind) -> String {
+    let mut out = format!("{} {}", request.method(), request.url());
+    for (name, value) in request.headers() {
+        if SECRET_HEADERS.contains(&name.as_str()) {
+            continue;
+        }
+        out.push_str(&format!(
+            "\n{name}: {}",
+            value.to_str().unwrap_or("<binary>")
+        ));
+    }
+    if let Some(body) = request.body().and_then(|body| body.as_bytes()) {
+        if protocol == ProtocolKind::Jmap {
+            let methods = jmap_method_names(&String::from_utf8_lossy(body));
+            if !methods.is_empty() {
+                out.push_str(&format!("\nmethods: {}", methods.join(", ")));
+            }
+        }
+        out.push_str(&format!("\n({} byte body)", body.len()));
+    }
+    redact(&out)
+}
+
+fn jmap_method_names(body: &str) -> Vec<String> {
+    serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|payload| payload.get("methodCalls").cloned())
+        .and_then(|calls| calls.as_array().cloned())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|call| {
+            call.get(0)
+                .and_then(|name| name.as_str())
+                .map(str::to_string)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exchange(account: &str, request: &str) -> ProtocolExchange {
+        ProtocolExchange {
+            account: account.to_string(),
+            protocol: ProtocolKind::Jmap,
+            started_at: Utc::now(),
+            request: request.to_string(),
+            response: None,
+            duration_ms: None,
+        }
+    }
+
+    #[test]
+    fn keeps_the_last_exchanges_per_account_while_enabled() {
+        let log = ProtocolLog::new(2);
+        log.record(exchange("a", "dropped while off"));
+        assert!(log.exchanges(None).is_empty());
+
+        log.set_enabled(true);
+        for request in ["1", "2", "3"] {
+            log.record(exchange("a", request));
+        }
+        log.record(exchange("b", "4"));
+        let requests: Vec<String> = log
+            .exchanges(Some("a"))
+            .into_iter()
+            .map(|exchange| exchange.request)
+            .collect();
+        assert_eq!(requests, ["2", "3"]);
+        assert_eq!(log.accounts(), ["a", "b"]);
+
+        log.set_capacity(1);
+        assert_eq!(log.exchanges(None).len(), 2);
+        log.clear();
+        assert!(log.support_bundle(None).exchanges.is_empty());
+    }
+
+    #[test]
+    fn masks_secrets() {
+        let text = redact(
+            "GET /x?access_token=abc&folder=INBOX\nAuthorization: Bearer eyJ.abc\n\
+             {\"password\": \"hunter2\"}<t:Password>pw</t:Password>",
+        );
+        assert!(!text.contains("abc"));
+        assert!(!text.contains("eyJ"));
+        assert!(!text.contains("hunter2"));
+        assert!(!text.contains(">pw<"));
+        assert!(text.contains("folder=INBOX"));
+        let long = redact(&"x".repeat(MAX_TEXT + 10));
+        assert!(long.ends_with(&format!("({} bytes)", MAX_TEXT + 10)));
+    }
+
+    #[test]
+    fn pairs_imap_commands_with_their_completion() {
+        let log = ProtocolLog::new(10);
+        log.set_enabled(true);
+        let mut tap = ImapTap::new("me@example.com");
+
+        tap.sent(&log, b"a1 LOGIN \"me@example.com\" \"hunter2\"\r\n");
+        tap.received(&log, b"a1 OK LOGIN completed\r\n");
+        tap.sent(&log, b"a2 APPEND Sent {11}\r\n");
+        tap.sent(&log, b"a3 fake\r\n\r\n");
+        tap.received(&log, b"+ go ahead\r\na2 OK APPEND");
+        tap.received(&log, b" completed\r\n");
+        tap.sent(&log, b"a4 AUTHENTICATE XOAUTH2\r\n");
+        tap.sent(&log, b"dXNlcj1tZQ==\r\n");
+        tap.received(&log, b"+ \r\n* CAPABILITY IMAP4rev1\r\na4 NO failed\r\n");
+
+        let exchanges = log.exchanges(None);
+        let requests: Vec<&str> = exchanges.iter().map(|e| e.request.as_str()).collect();
+        assert_eq!(
+            requests,
+            [
+                "a1 LOGIN \"me@example.com\" [redacted]",
+                "a2 APPEND Sent {11}",
+                "a4 AUTHENTICATE XOAUTH2",
+            ]
+        );
+        assert_eq!(
+            exchanges[1].response.as_deref(),
+            Some("a2 OK APPEND completed (36 bytes)")
+        );
+        assert!(exchanges[2]
+            .response
+            .as_deref()
+            .unwrap()
+            .starts_with("a4 NO failed"));
+    }
+
+    #[test]
+    fn follows_literals_sent_before_recording_started() {
+        let log = ProtocolLog::new(10);
+        let mut tap = ImapTap::new("me@example.com");
+
+        tap.sent(&log, b"a1 APPEND Sent {17}\r\n");
+        tap.sent(&log, b"a9 DELETE ");
+        log.set_enabled(true);
+        tap.sent(&log, b"INBOX\r\n\r\n");
+        tap.received(&log, b"a1 OK APPEND completed\r\n");
+        tap.sent(&log, b"a2 NOOP\r\n");
+        tap.received(&log, b"a2 OK NOOP completed\r\n");
+
+        let requests: Vec<String> = log
+            .exchanges(None)
+            .into_iter()
+            .map(|exchange| exchange.request)
+            .collect();
+        assert_eq!(requests, ["a2 NOOP"]);
+    }
+
+    #[test]
+    fn http_summary_keeps_methods_but_not_the_body() {
+        let body = r#"{"using":[],"methodCalls":[["Email/set",{"create":{"d":{"subject":"Salary review"}}},"a"]]}"#;
+        let request = reqwest::Client::new()
+            .post("https://jmap.example.com/api")
+            .bearer_auth("secret-token")
+            .body(body)
+            .build()
+            .unwrap();
+
+        let summary = http_request_summary(&request, ProtocolKind::Jmap);
+        assert!(summary.starts_with("POST https://jmap.example.com/api"));
+        assert!(summary.contains("methods: Email/set"));
+        assert!(summary.contains(&format!("({} byte body)", body.len())));
+        assert!(!summary.contains("Salary review"));
+        assert!(!summary.contains("secret-token"));
+    }
+
+    #[test]
+    fn lists_jmap_methods() {
+        let body = r#"{"using":[],"methodCalls":[["Email/query",{},"a"],["Email/get",{},"b"]]}"#;
+        assert_eq!(jmap_method_names(body), ["Email/query", "Email/get"]);
+        assert!(jmap_method_names("not json").is_empty());
+    }
+}
@@ -0,0 +1,136 @@
+//! Recipient parsing for the compose To/Cc/Bcc fields.
+//!
+//! Each typed or pasted entry becomes one chip. Entries are parsed as an
+//! RFC 5322 `mailbox` (`addr-spec` or `display-name <addr-spec>`); groups
+//! and lists must be split first with [`split_recipient_input`].
+
+use crate::EmailError;
+use cove_core::MailAddress;
+use mailparse::MailAddr;
+
+/// Split pasted text into recipient entries on `,`, `;` or newlines,
+/// ignoring separators inside quoted display names or angle brackets.
+pub fn split_recipient_input(raw: &str) -> Vec<String> {
+    let mut entries = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut in_angle = false;
+    let mut escaped = false;
+
+    for ch in raw.chars() {
+        if escaped {
+            current.push(ch);
+            escaped = false;
+            continue;
+        }
+        match ch {
+            '\\' if in_quotes => {
+                current.push(ch);
+                escaped = true;
+            }
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(ch);
+            }
+            '<' if !in_quotes => {
+                in_angle = true;
+                current.push(ch);
+            }
+            '>' if !in_quotes => {
+                in_angle = false;
+                current.push(ch);
+            }
+            ',' | ';' | '\n' | '\r' if !in_quotes && !in_angle => {
+                entries.push(std::mem::take(&mut current));
+            }
+            _ => current.push(ch),
+        }
+    }
+    entries.push(current);
+
+    entries
+        .into_iter()
+        .map(|entry| entry.trim().to_string())
+        .filter(|entry| !entry.is_empty())
+        .collect()
+}
+
+/// Parse one recipient entry into an address, rejecting anything a
+/// server would bounce at submission.
+pub fn parse_recipient(raw: &str) -> Result<MailAddress, EmailError> {
+    let raw = raw.trim();
+    let invalid = |reason: &str| EmailError::Data(format!("\"{raw}\": {reason}"));
+
+    let list = mailparse::addrparse(raw).map_err(|_| invalid("not a valid address"))?;
+    let [MailAddr::Single(single)] = list.as_slice() else {
+        return Err(invalid("expected a single address"));
+    };
+    validate_addr_spec(&single.addr).map_err(invalid)?;
+
+    Ok(MailAddress {
+        name: single
+            .display_name
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string),
+        address: single.addr.clone(),
+    })
+}
+
+fn validate_addr_spec(addr: &str) -> Result<(), &'static str> {
+    let (local, domain) = addr.rsplit_once('@').ok_or("missing @")?;
+    if local.is_empty() || local.len() > 64 {
+        return Err("invalid mailbox name");
+    }
+    if local.starts_with('.') || local.ends_with('.') || local.contains("..") {
+        return Err("invalid mailbox name");
+    }
+    if local.chars().any(|ch| ch.is_whitespace() || ch.is_control()) {
+        return Err("invalid mailbox name");
+    }
+    if domain.starts_with('[') && domain.ends_with(']') {
+        return Ok(());
+    }
+    let labels: Vec<&str> = domain.split('.').collect();
+    if labels.len() < 2 || domain.len() > 253 {
+        return Err("invalid domain");
+    }
+    let label_ok = |label: &&str| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label
+                .chars()
+                .all(|ch| ch.is_alphanumeric() || ch == '-')
+    };
+    if !labels.iter().all(label_ok) {
+        return Err("invalid domain");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_and_validates_recipients() {
+        let entries =
+            split_recipient_input("\"Doe, Jane\" <jane@example.com>; bob@example.org,\n\n bad@");
+        assert_eq!(
+            entries,
+            vec!["\"Doe, Jane\" <jane@example.com>", "bob@example.org", "bad@"]
+        );
+
+        let jane = parse_recipient(&entries[0]).unwrap();
+        assert_eq!(jane.name.as_deref(), Some("Doe, Jane"));
+        assert_eq!(jane.address, "jane@example.com");
+        assert!(parse_recipient("bob@example.org").unwrap().name.is_none());
+
+        for invalid in ["bad@", "no-at-sign", "a@localhost", "a..b@example.com", "x@-bad.com"] {
+            assert!(parse_recipient(invalid).is_err(), "{invalid} should be rejected");
+        }
+    }
+}
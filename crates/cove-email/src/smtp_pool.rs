@@ -0,0 +1,549 @@
+//! Cached SMTP connections for bulk sends.
+//!
+//! A send on a fresh connection pays for a TCP connect, a TLS handshake,
+//! EHLO and AUTH before the first MAIL FROM. Authenticated connections are
+//! kept per account so the scheduled queue and digests skip all of that.
+//! When the server advertises PIPELINING (RFC 2920), MAIL FROM, every
+//! RCPT TO and DATA go out in one write and their replies are read back in
+//! order, so a message costs two round trips instead of three plus one per
+//! recipient.
+
+use crate::backend::smtp_error_to_email;
+use crate::EmailError;
+use lettre::address::Envelope;
+use lettre::transport::smtp::authentication::{Credentials, Mechanism};
+use lettre::transport::smtp::client::{AsyncSmtpConnection, TlsParameters};
+use lettre::transport::smtp::commands::{Data, Ehlo, Mail, Rcpt, Rset};
+use lettre::transport::smtp::extension::{ClientId, Extension, MailBodyParameter, MailParameter};
+use lettre::transport::smtp::response::Response;
+use lettre::transport::smtp::SUBMISSIONS_PORT;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Idle connections kept per account.
+const MAX_IDLE_PER_ACCOUNT: usize = 2;
+/// Submission servers drop quiet clients after a few minutes; connections
+/// idle longer than this are closed rather than probed.
+const MAX_IDLE: Duration = Duration::from_secs(60);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Credentials are part of the key so a changed password or refreshed token
+/// never reuses a connection authenticated with the old secret.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub(crate) struct SmtpKey {
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub secret: Option<String>,
+    pub xoauth2: bool,
+}
+
+/// An authenticated connection borrowed from the pool. Return it with
+/// [`SmtpPool::checkin`]; dropping it instead simply closes the connection.
+pub(crate) struct SmtpConnection {
+    key: SmtpKey,
+    conn: AsyncSmtpConnection,
+    pipelining: bool,
+    /// The connection came from the pool rather than a fresh connect.
+    pub reused: bool,
+    /// A failed transaction left the connection out of step with the server.
+    broken: bool,
+}
+
+struct IdleConnection {
+    connection: SmtpConnection,
+    idle_since: Instant,
+}
+
+#[derive(Default)]
+pub(crate) struct SmtpPool {
+    idle: Mutex<HashMap<SmtpKey, Vec<IdleConnection>>>,
+}
+
+impl SmtpPool {
+    /// Take an idle connection for `key` that still answers NOOP, or open a
+    /// new one.
+    pub async fn checkout(&self, key: SmtpKey) -> Result<SmtpConnection, EmailError> {
+        while let Some(mut connection) = self.take_idle(&key) {
+            if connection.conn.test_connected().await {
+                connection.reused = true;
+                return Ok(connection);
+            }
+        }
+        SmtpConnection::open(key).await
+    }
+
+    pub fn checkin(&self, connection: SmtpConnection) {
+        if connection.broken || connection.conn.has_broken() {
+            return;
+        }
+        let mut idle = self
+            .idle
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let entry = idle.entry(connection.key.clone()).or_default();
+        if entry.len() < MAX_IDLE_PER_ACCOUNT {
+            entry.push(IdleConnection {
+                connection,
+                idle_since: Instant::now(),
+            });
+        }
+    }
+
+    fn take_idle(&self, key: &SmtpKey) -> Option<SmtpConnection> {
+        let mut idle = self
+            .idle
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        idle.retain(|_, entry| {
+            entry.retain(|cached| cached.idle_since.elapsed() < MAX_IDLE);
+            !entry.is_empty()
+        });
+        idle.get_mut(key)?.pop().map(|cached| cached.connection)
+    }
+}
+
+impl SmtpConnection {
+    /// Port 465 speaks TLS from the first byte; any other port has to
+    /// upgrade with STARTTLS before credentials are sent.
+    async fn open(key: SmtpKey) -> Result<Self, EmailError> {
+        let hello = ClientId::default();
+        let tls = TlsParameters::new(key.host.clone())
+            .map_err(|err| EmailError::Smtp(err.to_string()))?;
+        let implicit_tls = key.port == SUBMISSIONS_PORT;
+        let mut conn = AsyncSmtpConnection::connect_tokio1(
+            (key.host.as_str(), key.port),
+            Some(CONNECT_TIMEOUT),
+            &hello,
+            implicit_tls.then(|| tls.clone()),
+            None,
+        )
+        .await
+        .map_err(|err| smtp_error_to_email(err, false))?;
+        if !implicit_tls {
+            conn.starttls(tls, &hello)
+                .await
+                .map_err(|err| smtp_error_to_email(err, false))?;
+        }
+        Self::ready(key, conn, &hello).await
+    }
+
+    /// Look for PIPELINING and authenticate. lettre keeps only the EHLO
+    /// keywords it acts on itself, so the advertisement is read from a
+    /// second EHLO.
+    async fn ready(
+        key: SmtpKey,
+        mut conn: AsyncSmtpConnection,
+        hello: &ClientId,
+    ) -> Result<Self, EmailError> {
+        let ehlo = conn
+            .command(Ehlo::new(hello.clone()))
+            .await
+            .map_err(|err| smtp_error_to_email(err, false))?;
+        let pipelining = advertises_pipelining(&ehlo);
+
+        if let Some(secret) = &key.secret {
+            let mechanisms: &[Mechanism] = if key.xoauth2 {
+                &[Mechanism::Xoauth2]
+            } else {
+                &[Mechanism::Plain, Mechanism::Login]
+            };
+            conn.auth(
+                mechanisms,
+                &Credentials::new(key.username.clone(), secret.clone()),
+            )
+            .await
+            .map_err(|err| smtp_error_to_email(err, false))?;
+        }
+
+        Ok(Self {
+            key,
+            conn,
+            pipelining,
+            reused: false,
+            broken: false,
+        })
+    }
+
+    pub fn pipelining(&self) -> bool {
+        self.pipelining
+    }
+
+    /// Run one mail transaction and return the server's reply to the
+    /// message body.
+    pub async fn send(
+        &mut self,
+        envelope: &Envelope,
+        email: &[u8],
+    ) -> Result<Response, EmailError> {
+        let mail = Mail::new(
+            envelope.from().cloned(),
+            self.mail_parameters(envelope, email)?,
+        );
+        let mut commands = vec![mail.to_string()];
+        commands.extend(
+            envelope
+                .to()
+                .iter()
+                .map(|to| Rcpt::new(to.clone(), Vec::new()).to_string()),
+        );
+        commands.push(Data.to_string());
+
+        if self.pipelining {
+            self.pipelined_envelope(&commands).await?;
+        } else {
+            self.sequential_envelope(&commands).await?;
+        }
+
+        // DATA was answered with 354: from here on a dropped connection may
+        // already have delivered the message.
+        self.conn.message(email).await.map_err(|err| {
+            if err.status().is_none() {
+                self.broken = true;
+            }
+            smtp_error_to_email(err, true)
+        })
+    }
+
+    /// SMTPUTF8 (RFC 6531) and 8BITMIME (RFC 6152) parameters, refusing
+    /// what the server cannot carry.
+    fn mail_parameters(
+        &self,
+        envelope: &Envelope,
+        email: &[u8],
+    ) -> Result<Vec<MailParameter>, EmailError> {
+        let server = self.conn.server_info();
+        let mut parameters = Vec::new();
+        let non_ascii_address = envelope
+            .from()
+            .into_iter()
+            .chain(envelope.to())
+            .any(|address| !AsRef::<str>::as_ref(address).is_ascii());
+        if non_ascii_address {
+            if !server.supports_feature(Extension::SmtpUtfEight) {
+                return Err(EmailError::Smtp(
+                    "recipient addresses need SMTPUTF8, which the server does not support"
+                        .to_string(),
+                ));
+            }
+            parameters.push(MailParameter::SmtpUtfEight);
+        }
+        if !email.is_ascii() {
+            if !server.supports_feature(Extension::EightBitMime) {
+                return Err(EmailError::Smtp(
+                    "message needs 8BITMIME, which the server does not support".to_string(),
+                ));
+            }
+            parameters.push(MailParameter::Body(MailBodyParameter::EightBitMime));
+        }
+        Ok(parameters)
+    }
+
+    /// All commands in one write, then one reply per command in order.
+    /// Every reply is read even after a rejection so the connection stays in
+    /// step with the server.
+    async fn pipelined_envelope(&mut self, commands: &[String]) -> Result<(), EmailError> {
+        let mut rejected = None;
+        let mut data_accepted = false;
+        for index in 0..commands.len() {
+            let reply = if index == 0 {
+                self.conn.command(commands.concat()).await
+            } else {
+                self.conn.read_response().await
+            };
+            match reply {
+                Ok(_) => data_accepted = index + 1 == commands.len(),
+                Err(err) if err.status().is_none() => {
+                    self.broken = true;
+                    return Err(smtp_error_to_email(err, false));
+                }
+                Err(err) => {
+                    rejected.get_or_insert(err);
+                }
+            }
+        }
+        let Some(err) = rejected else {
+            return Ok(());
+        };
+        if data_accepted {
+            // A recipient was refused but DATA still got 354. Ending the
+            // data phase would deliver an empty message to the rest, so the
+            // connection is dropped instead and the server discards the
+            // unfinished transaction.
+            self.broken = true;
+        } else {
+            self.reset().await;
+        }
+        Err(smtp_error_to_email(err, false))
+    }
+
+    async fn sequential_envelope(&mut self, commands: &[String]) -> Result<(), EmailError> {
+        for command in commands {
+            if let Err(err) = self.conn.command(command).await {
+                if err.status().is_none() {
+                    self.broken = true;
+                } else {
+                    self.reset().await;
+                }
+                return Err(smtp_error_to_email(err, false));
+            }
+        }
+        Ok(())
+    }
+
+    async fn reset(&mut self) {
+        if self.conn.command(Rset).await.is_err() {
+            self.broken = true;
+        }
+    }
+}
+
+fn advertises_pipelining(ehlo: &Response) -> bool {
+    // The first line is the server's greeting, the rest one keyword each.
+    ehlo.message().skip(1).any(|line| {
+        line.split_whitespace()
+            .next()
+            .is_some_and(|keyword| keyword.eq_ignore_ascii_case("PIPELINING"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lettre::transport::smtp::client::AsyncTokioStream;
+    use std::io;
+    use std::net::SocketAddr;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use tokio::io::{
+        AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, DuplexStream, ReadBuf,
+    };
+
+    /// The client half of an in-memory connection.
+    #[derive(Debug)]
+    struct Peer(DuplexStream);
+
+    impl AsyncRead for Peer {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.0).poll_read(cx, buf)
+        }
+    }
+
+    impl AsyncWrite for Peer {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            Pin::new(&mut self.0).poll_write(cx, buf)
+        }
+
+        fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.0).poll_flush(cx)
+        }
+
+        fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.0).poll_shutdown(cx)
+        }
+    }
+
+    impl AsyncTokioStream for Peer {
+        fn peer_addr(&self) -> io::Result<SocketAddr> {
+            Err(io::ErrorKind::NotConnected.into())
+        }
+    }
+
+    enum Step {
+        /// Read this many command lines before answering anything.
+        Read(usize),
+        /// Read a message body up to the terminating dot.
+        ReadData,
+        Reply(&'static str),
+    }
+
+    /// A scripted server; returns every line it read. A client that waits
+    /// for a reply the script has not sent yet deadlocks, which the test
+    /// timeout turns into a failure.
+    async fn serve(stream: DuplexStream, script: Vec<Step>) -> Vec<String> {
+        let mut stream = BufReader::new(stream);
+        let mut lines = Vec::new();
+        for step in script {
+            match step {
+                Step::Read(count) => {
+                    for _ in 0..count {
+                        let mut line = String::new();
+                        stream.read_line(&mut line).await.unwrap();
+                        lines.push(line);
+                    }
+                }
+                Step::ReadData => loop {
+                    let mut line = String::new();
+                    stream.read_line(&mut line).await.unwrap();
+                    let done = line == ".\r\n";
+                    lines.push(line);
+                    if done {
+                        break;
+                    }
+                },
+                Step::Reply(reply) => stream.get_mut().write_all(reply.as_bytes()).await.unwrap(),
+            }
+        }
+        lines
+    }
+
+    const EHLO: &str = "250-mail.example.com\r\n250-PIPELINING\r\n250 8BITMIME\r\n";
+
+    fn key() -> SmtpKey {
+        SmtpKey {
+            host: "mail.example.com".to_string(),
+            port: 465,
+            username: "alice@example.com".to_string(),
+            secret: None,
+            xoauth2: false,
+        }
+    }
+
+    async fn connect(script: Vec<Step>) -> (SmtpConnection, tokio::task::JoinHandle<Vec<String>>) {
+        let (client, server) = tokio::io::duplex(16 * 1024);
+        let server = tokio::spawn(serve(server, script));
+        let hello = ClientId::Domain("client.example.com".to_string());
+        let conn = AsyncSmtpConnection::connect_with_transport(Box::new(Peer(client)), &hello)
+            .await
+            .unwrap();
+        (
+            SmtpConnection::ready(key(), conn, &hello).await.unwrap(),
+            server,
+        )
+    }
+
+    fn envelope(to: &[&str]) -> Envelope {
+        Envelope::new(
+            Some("alice@example.com".parse().unwrap()),
+            to.iter().map(|address| address.parse().unwrap()).collect(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn pipelines_the_envelope_and_reuses_the_connection() {
+        let script = vec![
+            Step::Reply("220 mail.example.com ESMTP\r\n"),
+            Step::Read(1),
+            Step::Reply(EHLO),
+            Step::Read(1),
+            Step::Reply(EHLO),
+            Step::Read(4),
+            Step::Reply("250 sender ok\r\n250 rcpt ok\r\n250 rcpt ok\r\n354 go ahead\r\n"),
+            Step::ReadData,
+            Step::Reply("250 queued as A1\r\n"),
+            Step::Read(1),
+            Step::Reply("250 ok\r\n"),
+            Step::Read(3),
+            Step::Reply("250 sender ok\r\n250 rcpt ok\r\n354 go ahead\r\n"),
+            Step::ReadData,
+            Step::Reply("250 queued as A2\r\n"),
+        ];
+        tokio::time::timeout(Duration::from_secs(5), async {
+            let (mut connection, server) = connect(script).await;
+            assert!(connection.pipelining());
+
+            let first = connection
+                .send(
+                    &envelope(&["bob@example.com", "carol@example.com"]),
+                    b"Subject: 1\r\n\r\nhi",
+                )
+                .await
+                .unwrap();
+            assert_eq!(first.first_line(), Some("queued as A1"));
+
+            let pool = SmtpPool::default();
+            pool.checkin(connection);
+            let mut connection = pool.checkout(key()).await.unwrap();
+            assert!(connection.reused);
+            let second = connection
+                .send(&envelope(&["bob@example.com"]), b"Subject: 2\r\n\r\nhi")
+                .await
+                .unwrap();
+            assert_eq!(second.first_line(), Some("queued as A2"));
+
+            let lines = server.await.unwrap();
+            assert_eq!(lines[2], "MAIL FROM:<alice@example.com>\r\n");
+            assert_eq!(lines[3], "RCPT TO:<bob@example.com>\r\n");
+            assert_eq!(lines[4], "RCPT TO:<carol@example.com>\r\n");
+            assert_eq!(lines[5], "DATA\r\n");
+            assert!(lines.iter().any(|line| line == "NOOP\r\n"));
+        })
+        .await
+        .expect("client waited for a reply between pipelined commands");
+    }
+
+    #[tokio::test]
+    async fn refused_envelope_is_reset_and_the_connection_kept() {
+        let script = vec![
+            Step::Reply("220 mail.example.com ESMTP\r\n"),
+            Step::Read(1),
+            Step::Reply(EHLO),
+            Step::Read(1),
+            Step::Reply(EHLO),
+            Step::Read(3),
+            Step::Reply("250 sender ok\r\n550 no such user\r\n554 no valid recipients\r\n"),
+            Step::Read(1),
+            Step::Reply("250 flushed\r\n"),
+            Step::Read(3),
+            Step::Reply("250 sender ok\r\n250 rcpt ok\r\n354 go ahead\r\n"),
+            Step::ReadData,
+            Step::Reply("250 queued as B1\r\n"),
+        ];
+        tokio::time::timeout(Duration::from_secs(5), async {
+            let (mut connection, server) = connect(script).await;
+
+            let refused = connection
+                .send(&envelope(&["nobody@example.com"]), b"Subject: 1\r\n\r\nhi")
+                .await
+                .unwrap_err();
+            assert!(refused.to_string().contains("no such user"), "{refused}");
+            assert!(!connection.broken);
+
+            let sent = connection
+                .send(&envelope(&["bob@example.com"]), b"Subject: 2\r\n\r\nhi")
+                .await
+                .unwrap();
+            assert_eq!(sent.first_line(), Some("queued as B1"));
+            assert!(server.await.unwrap().iter().any(|line| line == "RSET\r\n"));
+        })
+        .await
+        .expect("pipelined replies were not drained");
+    }
+
+    #[tokio::test]
+    async fn refused_recipient_after_data_drops_the_connection() {
+        let script = vec![
+            Step::Reply("220 mail.example.com ESMTP\r\n"),
+            Step::Read(1),
+            Step::Reply(EHLO),
+            Step::Read(1),
+            Step::Reply(EHLO),
+            Step::Read(4),
+            Step::Reply("250 sender ok\r\n550 no such user\r\n250 rcpt ok\r\n354 go ahead\r\n"),
+        ];
+        tokio::time::timeout(Duration::from_secs(5), async {
+            let (mut connection, _server) = connect(script).await;
+            assert!(connection
+                .send(&envelope(&["nobody@example.com", "bob@example.com"]), b"hi")
+                .await
+                .is_err());
+            assert!(connection.broken);
+
+            let pool = SmtpPool::default();
+            pool.checkin(connection);
+            assert!(pool.take_idle(&key()).is_none());
+        })
+        .await
+        .unwrap();
+    }
+}
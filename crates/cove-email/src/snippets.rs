@@ -0,0 +1,113 @@
+//! Inline snippet expansion for the compose editor.
+//!
+//! Typing an abbreviation such as `;sig1` and then a space, tab or newline
+//! replaces it with the snippet body. A `{cursor}` marker in the body sets
+//! where the caret lands; without one the caret goes after the delimiter.
+
+use cove_core::TextSnippet;
+
+/// Marks the caret position inside a snippet body.
+pub const SNIPPET_CURSOR_MARKER: &str = "{cursor}";
+
+/// Result of a successful expansion. `cursor` is a char index into `text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnippetExpansion {
+    pub text: String,
+    pub cursor: usize,
+}
+
+/// Expand the abbreviation that ends just before the delimiter the user
+/// typed at char index `cursor - 1`. Returns `None` when nothing matches.
+pub fn expand_snippet_at(
+    text: &str,
+    cursor: usize,
+    snippets: &[TextSnippet],
+) -> Option<SnippetExpansion> {
+    let chars: Vec<char> = text.chars().collect();
+    if cursor == 0 || cursor > chars.len() {
+        return None;
+    }
+    let delimiter = chars[cursor - 1];
+    if !matches!(delimiter, ' ' | '\t' | '\n') {
+        return None;
+    }
+
+    let token_end = cursor - 1;
+    let token_start = chars[..token_end]
+        .iter()
+        .rposition(|ch| ch.is_whitespace())
+        .map_or(0, |index| index + 1);
+    if token_start == token_end {
+        return None;
+    }
+    let token: String = chars[token_start..token_end].iter().collect();
+    let snippet = snippets
+        .iter()
+        .find(|snippet| snippet.abbreviation == token)?;
+
+    let (body, caret) = match snippet.body.find(SNIPPET_CURSOR_MARKER) {
+        Some(byte_index) => {
+            let caret = snippet.body[..byte_index].chars().count();
+            (snippet.body.replace(SNIPPET_CURSOR_MARKER, ""), Some(caret))
+        }
+        None => (snippet.body.clone(), None),
+    };
+
+    let mut expanded: String = chars[..token_start].iter().collect();
+    expanded.push_str(&body);
+    let cursor = match caret {
+        // The caret sits inside the body, so the delimiter is dropped.
+        Some(caret) => token_start + caret,
+        None => {
+            expanded.push(delimiter);
+            token_start + body.chars().count() + 1
+        }
+    };
+    expanded.extend(&chars[token_end + 1..]);
+
+    Some(SnippetExpansion {
+        text: expanded,
+        cursor,
+    })
+}
+
+/// Abbreviations are single tokens so they can be matched as you type.
+pub fn is_valid_abbreviation(abbreviation: &str) -> bool {
+    !abbreviation.is_empty() && !abbreviation.chars().any(char::is_whitespace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn snippet(abbreviation: &str, body: &str) -> TextSnippet {
+        TextSnippet {
+            id: Uuid::new_v4(),
+            abbreviation: abbreviation.to_string(),
+            body: body.to_string(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn expands_abbreviation_before_delimiter() {
+        let snippets = vec![snippet(";addr", "1 Main St")];
+        let text = "Ship to ;addr please";
+        let expansion = expand_snippet_at(text, 14, &snippets).unwrap();
+        assert_eq!(expansion.text, "Ship to 1 Main St please");
+        assert_eq!(expansion.cursor, 18);
+
+        assert!(expand_snippet_at("x;addr ", 7, &snippets).is_none());
+        assert!(expand_snippet_at(";add ", 5, &snippets).is_none());
+    }
+
+    #[test]
+    fn cursor_marker_sets_caret() {
+        let snippets = vec![snippet(";hi", "Hi {cursor},\n\nThanks")];
+        let expansion = expand_snippet_at(";hi\n", 4, &snippets).unwrap();
+        assert_eq!(expansion.text, "Hi ,\n\nThanks");
+        assert_eq!(expansion.cursor, 3);
+    }
+}
@@ -0,0 +1,265 @@
+//! Thread transcripts: a whole conversation rendered to Markdown or PDF for
+//! hand-offs and record keeping.
+
+use cove_core::{MailAddress, MailMessage};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptFormat {
+    Markdown,
+    Pdf,
+}
+
+impl TranscriptFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Pdf => "pdf",
+        }
+    }
+}
+
+/// Render a thread (oldest message first) to the requested format.
+pub fn render_transcript(messages: &[MailMessage], format: TranscriptFormat) -> Vec<u8> {
+    match format {
+        TranscriptFormat::Markdown => thread_to_markdown(messages).into_bytes(),
+        TranscriptFormat::Pdf => thread_to_pdf(messages),
+    }
+}
+
+pub fn thread_to_markdown(messages: &[MailMessage]) -> String {
+    let subject = messages
+        .first()
+        .map(|message| message.subject.trim())
+        .filter(|subject| !subject.is_empty())
+        .unwrap_or("(no subject)");
+
+    let mut out = format!("# {subject}\n\n");
+    out.push_str(&format!("- **Messages:** {}\n", messages.len()));
+    out.push_str(&format!("- **Participants:** {}\n", participants(messages).join(", ")));
+    out.push_str("\n---\n");
+
+    for message in messages {
+        let sender = message
+            .from
+            .first()
+            .map(display_address)
+            .unwrap_or_else(|| "Unknown sender".to_string());
+        out.push_str(&format!(
+            "\n## {sender} — {}\n\n",
+            message.received_at.format("%Y-%m-%d %H:%M UTC")
+        ));
+        if !message.to.is_empty() {
+            out.push_str(&format!("- **To:** {}\n", join_addresses(&message.to)));
+        }
+        if !message.cc.is_empty() {
+            out.push_str(&format!("- **Cc:** {}\n", join_addresses(&message.cc)));
+        }
+        if !message.attachments.is_empty() {
+            out.push_str("- **Attachments:**\n");
+            for attachment in &message.attachments {
+                out.push_str(&format!(
+                    "  - {} ({}, {} B)\n",
+                    attachment.file_name, attachment.mime_type, attachment.size
+                ));
+            }
+        }
+        out.push('\n');
+        out.push_str(&fold_quotes(message_body(message)));
+        out.push_str("\n\n---\n");
+    }
+
+    out
+}
+
+/// Lay the transcript out as plain text on A4 pages using the built-in
+/// Helvetica font, so no font files or PDF crate are needed.
+pub fn thread_to_pdf(messages: &[MailMessage]) -> Vec<u8> {
+    const LINES_PER_PAGE: usize = 60;
+    const WRAP_COLUMNS: usize = 95;
+
+    let mut lines = Vec::new();
+    for line in thread_to_markdown(messages).lines() {
+        let line = line.trim_start_matches("# ").trim_start_matches("## ").replace("**", "");
+        lines.extend(wrap_line(&line, WRAP_COLUMNS));
+    }
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+
+    let pages = lines.chunks(LINES_PER_PAGE).collect::<Vec<_>>();
+    let page_count = pages.len();
+
+    // Object layout: 1 catalog, 2 page tree, 3 font, then a (page, content)
+    // pair per page.
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {page_count} >>",
+            (0..page_count)
+                .map(|index| format!("{} 0 R", 4 + index * 2))
+                .collect::<Vec<_>>()
+                .join(" ")
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+    ];
+
+    for (index, page) in pages.into_iter().enumerate() {
+        let mut stream = String::from("BT /F1 10 Tf 12 TL 50 800 Td\n");
+        for line in page {
+            stream.push_str(&format!("({}) Tj T*\n", pdf_escape(line)));
+        }
+        stream.push_str("ET");
+
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 595 842] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            5 + index * 2
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{stream}\nendstream",
+            encode_win_ansi(&stream).len()
+        ));
+    }
+
+    let mut out = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        out.extend(format!("{} 0 obj\n", index + 1).bytes());
+        out.extend(encode_win_ansi(object));
+        out.extend(b"\nendobj\n");
+    }
+
+    let xref_at = out.len();
+    out.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).bytes());
+    for offset in offsets {
+        out.extend(format!("{offset:010} 00000 n \n").bytes());
+    }
+    out.extend(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref_at}\n%%EOF\n",
+            objects.len() + 1
+        )
+        .bytes(),
+    );
+    out
+}
+
+/// Collapse quoted history (`>` lines and the "On ... wrote:" line that
+/// introduces it) into a single placeholder per block.
+pub fn fold_quotes(body: &str) -> String {
+    let mut out = Vec::new();
+    let mut folded = 0usize;
+
+    let flush = |out: &mut Vec<String>, folded: &mut usize| {
+        if *folded > 0 {
+            out.push(format!("_[{folded} quoted line(s) folded]_"));
+            *folded = 0;
+        }
+    };
+
+    let lines = body.lines().collect::<Vec<_>>();
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        let introduces_quote = trimmed.starts_with("On ")
+            && trimmed.trim_end().ends_with("wrote:")
+            && lines
+                .get(index + 1)
+                .is_some_and(|next| next.trim_start().starts_with('>'));
+
+        if trimmed.starts_with('>') || introduces_quote {
+            folded += 1;
+            continue;
+        }
+        flush(&mut out, &mut folded);
+        out.push(line.trim_end().to_string());
+    }
+    flush(&mut out, &mut folded);
+
+    out.join("\n").trim().to_string()
+}
+
+fn message_body(message: &MailMessage) -> &str {
+    message.body_text.as_deref().unwrap_or(&message.preview)
+}
+
+fn participants(messages: &[MailMessage]) -> Vec<String> {
+    let mut seen = Vec::<String>::new();
+    let mut out = Vec::new();
+    for address in messages
+        .iter()
+        .flat_map(|message| message.from.iter().chain(&message.to).chain(&message.cc))
+    {
+        let key = address.address.to_lowercase();
+        if !seen.contains(&key) {
+            seen.push(key);
+            out.push(display_address(address));
+        }
+    }
+    out
+}
+
+fn display_address(address: &MailAddress) -> String {
+    match &address.name {
+        Some(name) if !name.trim().is_empty() => format!("{} <{}>", name.trim(), address.address),
+        _ => address.address.clone(),
+    }
+}
+
+fn join_addresses(list: &[MailAddress]) -> String {
+    list.iter().map(display_address).collect::<Vec<_>>().join(", ")
+}
+
+fn wrap_line(line: &str, columns: usize) -> Vec<String> {
+    if line.chars().count() <= columns {
+        return vec![line.to_string()];
+    }
+
+    let mut out = Vec::new();
+    let mut current = String::new();
+    for word in line.split(' ') {
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > columns {
+            out.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+        while current.chars().count() > columns {
+            let split_at = current
+                .char_indices()
+                .nth(columns)
+                .map(|(at, _)| at)
+                .unwrap_or(current.len());
+            let rest = current.split_off(split_at);
+            out.push(std::mem::replace(&mut current, rest));
+        }
+    }
+    out.push(current);
+    out
+}
+
+fn pdf_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('(', "\\(").replace(')', "\\)")
+}
+
+/// Map text to single-byte WinAnsi, substituting `?` for anything the
+/// standard fonts cannot show.
+fn encode_win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c {
+            '\u{2014}' => 0x97,
+            '\u{2013}' => 0x96,
+            '\u{2018}' => 0x91,
+            '\u{2019}' => 0x92,
+            '\u{201C}' => 0x93,
+            '\u{201D}' => 0x94,
+            '\u{2022}' => 0x95,
+            '\u{20AC}' => 0x80,
+            c if (c as u32) < 0x80 || (0xA0..=0xFF).contains(&(c as u32)) => c as u32 as u8,
+            _ => b'?',
+        })
+        .collect()
+}
@@ -0,0 +1,292 @@
+//! Local heuristic for spotting inbound mail that is waiting on a reply.
+
+use cove_core::MailMessage;
+
+/// Score at or above which a message goes straight into the reply queue.
+pub const REPLY_NEEDED_SCORE: u8 = 60;
+/// Lower bound for borderline candidates that an AI check may confirm.
+pub const REPLY_CANDIDATE_SCORE: u8 = 35;
+
+const AUTOMATED_SENDER_MARKERS: &[&str] = &[
+    "noreply",
+    "no-reply",
+    "donotreply",
+    "do-not-reply",
+    "notifications@",
+    "mailer-daemon",
+    "bounce",
+];
+
+const REQUEST_PHRASES: &[&str] = &[
+    "can you",
+    "could you",
+    "would you",
+    "please let me know",
+    "let me know",
+    "what do you think",
+    "are you available",
+    "please confirm",
+    "get back to me",
+    "your thoughts",
+];
+
+/// Score how likely `message` needs a reply from `own_address`, with the
+/// human-readable reasons that contributed. Returns a zero score for mail
+/// that should never be queued (own mail, lists, automated senders).
+pub fn reply_needed_score(message: &MailMessage, own_address: &str) -> (u8, Vec<String>) {
+    let own = own_address.trim().to_lowercase();
+    let Some(sender) = message.from.first() else {
+        return (0, Vec::new());
+    };
+    let sender = sender.address.to_lowercase();
+
+    if sender == own || message.flags.answered || message.flags.draft {
+        return (0, Vec::new());
+    }
+    if has_header(message, "List-Id")
+        || has_header(message, "List-Unsubscribe")
+        || header_is(message, "Precedence", &["bulk", "list", "junk"])
+        || (has_header(message, "Auto-Submitted")
+            && !header_is(message, "Auto-Submitted", &["no"]))
+        || AUTOMATED_SENDER_MARKERS
+            .iter()
+            .any(|marker| sender.contains(marker))
+    {
+        return (0, Vec::new());
+    }
+
+    let mut score = 0u32;
+    let mut reasons = Vec::new();
+
+    let direct = message
+        .to
+        .iter()
+        .any(|address| address.address.eq_ignore_ascii_case(&own));
+    if direct {
+        score += 20;
+        reasons.push("Addressed to you directly".to_string());
+        if message.to.len() == 1 && message.cc.is_empty() {
+            score += 15;
+            reasons.push("You are the only recipient".to_string());
+        }
+    }
+
+    let body = message
+        .body_text
+        .as_deref()
+        .unwrap_or(&message.preview);
+    let fresh_text = body
+        .lines()
+        .take_while(|line| {
+            let trimmed = line.trim_start();
+            !(trimmed.starts_with('>') || (trimmed.starts_with("On ") && trimmed.ends_with("wrote:")))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .to_lowercase();
+
+    if fresh_text.contains('?') || message.subject.contains('?') {
+        score += 30;
+        reasons.push("Contains a direct question".to_string());
+    }
+    if REQUEST_PHRASES.iter().any(|phrase| fresh_text.contains(phrase)) {
+        score += 20;
+        reasons.push("Asks you for something".to_string());
+    }
+    if message.subject.trim_start().to_lowercase().starts_with("re:") {
+        score += 5;
+        reasons.push("Continues a conversation".to_string());
+    }
+
+    (score.min(100) as u8, reasons)
+}
+
+fn has_header(message: &MailMessage, name: &str) -> bool {
+    message
+        .headers
+        .keys()
+        .any(|key| key.eq_ignore_ascii_case(name))
+}
+
+fn header_is(message: &MailMessage, name: &str, values: &[&str]) -> bool {
+    message
+        .headers
+        .iter()
+        .filter(|(key, _)| key.eq_ignore_ascii_case(name))
+        .any(|(_, value)| {
+            values
+                .iter()
+                .any(|candidate| value.trim().eq_ignore_ascii_case(candidate))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cove_core::{MailAddress, MailFlags};
+
+    const OWN: &str = "me@example.com";
+
+    struct Case {
+        name: &'static str,
+        from: &'static str,
+        to: &'static [&'static str],
+        cc: &'static [&'static str],
+        subject: &'static str,
+        body: &'static str,
+        headers: &'static [(&'static str, &'static str)],
+        answered: bool,
+        score: u8,
+    }
+
+    const BASE: Case = Case {
+        name: "",
+        from: "alice@example.org",
+        to: &[OWN],
+        cc: &[],
+        subject: "Budget",
+        body: "Here are the numbers.",
+        headers: &[],
+        answered: false,
+        score: 35,
+    };
+
+    fn addresses(list: &[&str]) -> Vec<MailAddress> {
+        list.iter()
+            .map(|address| MailAddress {
+                name: None,
+                address: address.to_string(),
+            })
+            .collect()
+    }
+
+    fn message(case: &Case) -> MailMessage {
+        MailMessage {
+            from: addresses(&[case.from]),
+            to: addresses(case.to),
+            cc: addresses(case.cc),
+            subject: case.subject.to_string(),
+            body_text: Some(case.body.to_string()),
+            flags: MailFlags {
+                answered: case.answered,
+                ..MailFlags::default()
+            },
+            headers: case
+                .headers
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            ..MailMessage::fixture()
+        }
+    }
+
+    #[test]
+    fn reply_needed_scores() {
+        let cases = [
+            Case {
+                name: "sole direct recipient",
+                ..BASE
+            },
+            Case {
+                name: "own address matches case-insensitively",
+                to: &["Me@Example.COM"],
+                ..BASE
+            },
+            Case {
+                name: "direct question with a request on a reply",
+                subject: "Re: Budget",
+                body: "Can you send the numbers? Thanks",
+                score: 90,
+                ..BASE
+            },
+            Case {
+                name: "question in the subject",
+                subject: "Lunch on Friday?",
+                score: 65,
+                ..BASE
+            },
+            Case {
+                name: "shared with others",
+                to: &[OWN, "bob@example.org"],
+                body: "Could you review this?",
+                score: 70,
+                ..BASE
+            },
+            Case {
+                name: "only copied",
+                to: &["bob@example.org"],
+                cc: &[OWN],
+                body: "FYI, could you?",
+                score: 50,
+                ..BASE
+            },
+            Case {
+                name: "question only in quoted text",
+                body: "Done.\n> Can you check this?",
+                ..BASE
+            },
+            Case {
+                name: "question only below the attribution line",
+                body: "Done.\nOn Mon, Bob wrote:\nCan you check this?",
+                ..BASE
+            },
+            Case {
+                name: "sent by me",
+                from: OWN,
+                body: "Can you check this?",
+                score: 0,
+                ..BASE
+            },
+            Case {
+                name: "already answered",
+                body: "Can you check this?",
+                answered: true,
+                score: 0,
+                ..BASE
+            },
+            Case {
+                name: "mailing list",
+                body: "Can you check this?",
+                headers: &[("List-Id", "<dev.lists.example.org>")],
+                score: 0,
+                ..BASE
+            },
+            Case {
+                name: "bulk precedence",
+                headers: &[("Precedence", " Bulk ")],
+                score: 0,
+                ..BASE
+            },
+            Case {
+                name: "auto-submitted",
+                headers: &[("Auto-Submitted", "auto-replied")],
+                score: 0,
+                ..BASE
+            },
+            Case {
+                name: "auto-submitted no is a person",
+                headers: &[("auto-submitted", "no")],
+                ..BASE
+            },
+            Case {
+                name: "automated sender",
+                from: "No-Reply@shop.example",
+                body: "Can you rate your order?",
+                score: 0,
+                ..BASE
+            },
+        ];
+        for case in &cases {
+            let (score, reasons) = reply_needed_score(&message(case), OWN);
+            assert_eq!(score, case.score, "{}", case.name);
+            assert_eq!(score == 0, reasons.is_empty(), "{}", case.name);
+        }
+    }
+
+    #[test]
+    fn no_sender_scores_zero() {
+        let mut message = message(&BASE);
+        message.from.clear();
+        assert_eq!(reply_needed_score(&message, OWN), (0, Vec::new()));
+    }
+}
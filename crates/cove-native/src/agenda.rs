@@ -0,0 +1,385 @@
+//! Daily agenda: today's events, due tasks and unread VIP mail, delivered
+//! once per day at the configured time.
+
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use cove_config::AgendaConfig;
+use cove_core::{CalendarEvent, MailMessage, ReminderTask};
+use uuid::Uuid;
+
+/// Audit log kind recorded for each delivery, so the last delivery date
+/// survives restarts.
+pub const AGENDA_AUDIT_KIND: &str = "agenda.delivered";
+
+/// How long after `delivery_time` a missed agenda is still delivered, so a
+/// restart later in the day does not produce a second agenda.
+const DELIVERY_WINDOW_MINUTES: i64 = 60;
+
+pub struct DailyAgenda {
+    pub date: NaiveDate,
+    pub events: Vec<CalendarEvent>,
+    pub tasks: Vec<ReminderTask>,
+    pub vip_mail: Vec<MailMessage>,
+    pub narrative: Option<String>,
+}
+
+impl DailyAgenda {
+    /// Filter raw storage rows down to what belongs on today's agenda.
+    pub fn build(
+        date: NaiveDate,
+        mut events: Vec<CalendarEvent>,
+        tasks: Vec<ReminderTask>,
+        messages: Vec<MailMessage>,
+        vip_senders: &[String],
+    ) -> Self {
+        let (_, day_end) = local_day_bounds(date);
+
+        events.sort_by_key(|event| event.starts_at);
+
+        let mut tasks = tasks
+            .into_iter()
+            .filter(|task| task.completed_at.is_none())
+            .filter(|task| task.due_at.is_some_and(|due| due < day_end))
+            .collect::<Vec<_>>();
+        tasks.sort_by_key(|task| task.due_at);
+
+        let vip_mail = messages
+            .into_iter()
+            .filter(|message| !message.flags.seen)
+            .filter(|message| {
+                message.from.iter().any(|sender| {
+                    vip_senders
+                        .iter()
+                        .any(|vip| vip.trim().eq_ignore_ascii_case(&sender.address))
+                })
+            })
+            .collect();
+
+        Self {
+            date,
+            events,
+            tasks,
+            vip_mail,
+            narrative: None,
+        }
+    }
+
+    /// The agenda limited to items from accounts for which `allowed`
+    /// holds, without the narrative. Used to keep content from accounts
+    /// whose AI policy forbids it out of the narrative prompt.
+    pub fn for_accounts(&self, allowed: impl Fn(Uuid) -> bool) -> Self {
+        Self {
+            date: self.date,
+            events: self
+                .events
+                .iter()
+                .filter(|event| allowed(event.account_id))
+                .cloned()
+                .collect(),
+            tasks: self
+                .tasks
+                .iter()
+                .filter(|task| allowed(task.account_id))
+                .cloned()
+                .collect(),
+            vip_mail: self
+                .vip_mail
+                .iter()
+                .filter(|message| allowed(message.account_id))
+                .cloned()
+                .collect(),
+            narrative: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty() && self.tasks.is_empty() && self.vip_mail.is_empty()
+    }
+
+    /// One-line summary used as the notification title and email subject.
+    pub fn headline(&self) -> String {
+        format!(
+            "Agenda for {}: {} event(s), {} task(s) due, {} VIP message(s)",
+            self.date.format("%a %b %d"),
+            self.events.len(),
+            self.tasks.len(),
+            self.vip_mail.len()
+        )
+    }
+
+    pub fn to_plain_text(&self) -> String {
+        let mut out = String::new();
+
+        if let Some(narrative) = &self.narrative {
+            out.push_str(narrative.trim());
+            out.push_str("\n\n");
+        }
+
+        out.push_str("Events\n");
+        if self.events.is_empty() {
+            out.push_str("  (none)\n");
+        }
+        for event in &self.events {
+            let when = if event.all_day {
+                "all day".to_string()
+            } else {
+                format!(
+                    "{}-{}",
+                    event.starts_at.with_timezone(&Local).format("%H:%M"),
+                    event.ends_at.with_timezone(&Local).format("%H:%M")
+                )
+            };
+            match &event.location {
+                Some(location) => {
+                    out.push_str(&format!("  {when}  {} @ {location}\n", event.title))
+                }
+                None => out.push_str(&format!("  {when}  {}\n", event.title)),
+            }
+        }
+
+        out.push_str("\nTasks due\n");
+        if self.tasks.is_empty() {
+            out.push_str("  (none)\n");
+        }
+        for task in &self.tasks {
+            let overdue = task
+                .due_at
+                .is_some_and(|due| due < local_day_bounds(self.date).0);
+            let marker = if overdue { " (overdue)" } else { "" };
+            out.push_str(&format!("  [{:?}] {}{marker}\n", task.priority, task.title));
+        }
+
+        out.push_str("\nUnread VIP mail\n");
+        if self.vip_mail.is_empty() {
+            out.push_str("  (none)\n");
+        }
+        for message in &self.vip_mail {
+            let sender = message
+                .from
+                .first()
+                .map(|a| a.name.clone().unwrap_or_else(|| a.address.clone()))
+                .unwrap_or_else(|| "Unknown sender".to_string());
+            out.push_str(&format!("  {sender}: {}\n", message.subject));
+        }
+
+        out
+    }
+}
+
+/// Tracks the last delivery so the agenda fires at most once per day.
+pub struct AgendaState {
+    last_delivered: Option<NaiveDate>,
+}
+
+impl AgendaState {
+    /// `last_delivered` comes from the audit log, so restarting inside the
+    /// delivery window does not send the agenda again.
+    pub fn new(last_delivered: Option<NaiveDate>) -> Self {
+        Self { last_delivered }
+    }
+
+    pub fn is_due(&self, config: &AgendaConfig, now: DateTime<Local>) -> bool {
+        if !config.enabled || self.last_delivered == Some(now.date_naive()) {
+            return false;
+        }
+        let Ok(at) = NaiveTime::parse_from_str(&config.delivery_time, "%H:%M") else {
+            return false;
+        };
+
+        let minutes_past = (now.time() - at).num_minutes();
+        (0..DELIVERY_WINDOW_MINUTES).contains(&minutes_past)
+    }
+
+    pub fn mark_delivered(&mut self, date: NaiveDate) {
+        self.last_delivered = Some(date);
+    }
+}
+
+/// UTC bounds of the given local calendar day.
+pub fn local_day_bounds(date: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+    let to_utc = |date: NaiveDate| {
+        Local
+            .from_local_datetime(&date.and_time(NaiveTime::MIN))
+            .earliest()
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|| Utc.from_utc_datetime(&date.and_time(NaiveTime::MIN)))
+    };
+    let next = date.succ_opt().unwrap_or(date);
+    (to_utc(date), to_utc(next))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use cove_core::{MailAddress, MailFlags, RsvpStatus, TaskPriority, TaskStatus};
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 6, 15).unwrap()
+    }
+
+    fn at(date: NaiveDate, hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .from_local_datetime(&date.and_hms_opt(hour, minute, 0).unwrap())
+            .earliest()
+            .unwrap()
+    }
+
+    fn event(title: &str, starts_at: DateTime<Utc>) -> CalendarEvent {
+        CalendarEvent {
+            id: Uuid::new_v4(),
+            account_id: Uuid::new_v4(),
+            calendar_id: "primary".to_string(),
+            remote_id: Uuid::new_v4().to_string(),
+            title: title.to_string(),
+            description: None,
+            location: None,
+            timezone: None,
+            starts_at,
+            ends_at: starts_at + Duration::hours(1),
+            all_day: false,
+            recurrence_rule: None,
+            attendees: Vec::new(),
+            organizer: None,
+            alarms: Vec::new(),
+            rsvp_status: RsvpStatus::Accepted,
+            online_meeting_url: None,
+            updated_at: starts_at,
+        }
+    }
+
+    fn task(title: &str, due_at: Option<DateTime<Utc>>, done: bool) -> ReminderTask {
+        let now = Utc::now();
+        ReminderTask {
+            id: Uuid::new_v4(),
+            account_id: Uuid::new_v4(),
+            list_id: "default".to_string(),
+            list_name: None,
+            remote_id: None,
+            title: title.to_string(),
+            notes: None,
+            due_at,
+            completed_at: done.then_some(now),
+            priority: TaskPriority::Normal,
+            status: if done {
+                TaskStatus::Completed
+            } else {
+                TaskStatus::NotStarted
+            },
+            repeat_rule: None,
+            parent_id: None,
+            snoozed_until: None,
+            created_at: now,
+            updated_at: now,
+            estimate_minutes: None,
+        }
+    }
+
+    fn message(from: &str, seen: bool) -> MailMessage {
+        MailMessage {
+            from: vec![MailAddress {
+                name: None,
+                address: from.to_string(),
+            }],
+            subject: format!("From {from}"),
+            flags: MailFlags {
+                seen,
+                ..MailFlags::default()
+            },
+            ..MailMessage::fixture()
+        }
+    }
+
+    fn titles<T>(items: &[T], title: impl Fn(&T) -> &str) -> Vec<&str> {
+        items.iter().map(title).collect()
+    }
+
+    #[test]
+    fn build_keeps_open_tasks_due_by_tonight_and_unread_vip_mail() {
+        let (day_start, day_end) = local_day_bounds(date());
+        let agenda = DailyAgenda::build(
+            date(),
+            vec![
+                event("Lunch", day_start + Duration::hours(12)),
+                event("Standup", day_start + Duration::hours(9)),
+            ],
+            vec![
+                task("Later today", Some(day_end - Duration::hours(1)), false),
+                task("Overdue", Some(day_start - Duration::days(2)), false),
+                task("Tomorrow", Some(day_end + Duration::hours(1)), false),
+                task("No due date", None, false),
+                task("Done", Some(day_start), true),
+            ],
+            vec![
+                message("Boss@Example.com", false),
+                message("boss@example.com", true),
+                message("someone@example.com", false),
+            ],
+            &[" boss@example.com ".to_string()],
+        );
+
+        assert_eq!(titles(&agenda.events, |e| &e.title), ["Standup", "Lunch"]);
+        assert_eq!(titles(&agenda.tasks, |t| &t.title), ["Overdue", "Later today"]);
+        assert_eq!(
+            titles(&agenda.vip_mail, |m| &m.subject),
+            ["From Boss@Example.com"]
+        );
+        assert!(agenda.narrative.is_none());
+        assert!(!agenda.is_empty());
+
+        let text = agenda.to_plain_text();
+        assert!(text.contains("Overdue (overdue)"));
+        assert!(!text.contains("Later today (overdue)"));
+    }
+
+    #[test]
+    fn build_with_nothing_is_empty() {
+        let agenda = DailyAgenda::build(date(), Vec::new(), Vec::new(), Vec::new(), &[]);
+        assert!(agenda.is_empty());
+        assert!(agenda.to_plain_text().contains("(none)"));
+    }
+
+    #[test]
+    fn is_due_only_inside_the_delivery_window_once_a_day() {
+        let config = AgendaConfig {
+            enabled: true,
+            delivery_time: "07:30".to_string(),
+            ..AgendaConfig::default()
+        };
+        let state = AgendaState::new(None);
+
+        assert!(!state.is_due(&config, at(date(), 7, 29)));
+        assert!(state.is_due(&config, at(date(), 7, 30)));
+        assert!(state.is_due(&config, at(date(), 8, 29)));
+        assert!(!state.is_due(&config, at(date(), 8, 30)));
+
+        let disabled = AgendaConfig {
+            enabled: false,
+            ..config.clone()
+        };
+        assert!(!state.is_due(&disabled, at(date(), 7, 45)));
+
+        let invalid = AgendaConfig {
+            delivery_time: "7.30am".to_string(),
+            ..config.clone()
+        };
+        assert!(!state.is_due(&invalid, at(date(), 7, 45)));
+    }
+
+    #[test]
+    fn is_due_skips_a_day_already_delivered() {
+        let config = AgendaConfig {
+            enabled: true,
+            delivery_time: "07:30".to_string(),
+            ..AgendaConfig::default()
+        };
+        let mut state = AgendaState::new(date().pred_opt());
+        assert!(state.is_due(&config, at(date(), 7, 45)));
+
+        state.mark_delivered(date());
+        assert!(!state.is_due(&config, at(date(), 7, 45)));
+
+        // Restored from the audit log after a restart.
+        let restarted = AgendaState::new(Some(date()));
+        assert!(!restarted.is_due(&config, at(date(), 7, 50)));
+    }
+}
@@ -1,6 +1,8 @@
 use cove_config::AppConfig;
-use cove_core::Account;
+use cove_core::{Account, AccountAiPolicy, EmailSignature, MailMessage};
+use chrono::{DateTime, Utc};
 use age::Encryptor;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::io::{Read, Write};
@@ -20,11 +22,45 @@ pub struct ExportPayload {
     pub accounts: Vec<AccountExport>,
 }
 
+/// One account on its own: settings, optionally secrets, and optionally
+/// its mail from a date on. Moves a mailbox to another machine, or shares
+/// a configured account profile when secrets and mail are left out.
+#[derive(Serialize, Deserialize)]
+pub struct AccountBundle {
+    pub account: AccountExport,
+    #[serde(default)]
+    pub ai_policy: AccountAiPolicy,
+    /// Signatures of this account; shared signatures stay behind.
+    #[serde(default)]
+    pub signatures: Vec<EmailSignature>,
+    /// Start of the exported mail; `None` with mail means all of it.
+    #[serde(default)]
+    pub mail_since: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub mail: Vec<MailMessage>,
+}
+
 pub fn export_settings(
     payload: &ExportPayload,
     password: &str,
     path: &Path,
 ) -> anyhow::Result<()> {
+    write_encrypted(payload, password, path)
+}
+
+pub fn import_settings(password: &str, path: &Path) -> anyhow::Result<ExportPayload> {
+    read_encrypted(password, path)
+}
+
+pub fn export_account(bundle: &AccountBundle, password: &str, path: &Path) -> anyhow::Result<()> {
+    write_encrypted(bundle, password, path)
+}
+
+pub fn import_account(password: &str, path: &Path) -> anyhow::Result<AccountBundle> {
+    read_encrypted(password, path)
+}
+
+fn write_encrypted<T: Serialize>(payload: &T, password: &str, path: &Path) -> anyhow::Result<()> {
     // Serialize to JSON bytes
     let json_bytes = serde_json::to_vec(payload)?;
 
@@ -38,7 +74,7 @@ pub fn export_settings(
     Ok(())
 }
 
-pub fn import_settings(password: &str, path: &Path) -> anyhow::Result<ExportPayload> {
+fn read_encrypted<T: DeserializeOwned>(password: &str, path: &Path) -> anyhow::Result<T> {
     // the password doesn't matter for the file existence, but Decryptor needs to read it
     let encrypted_file = std::fs::File::open(path)?;
     let buffered_file = std::io::BufReader::new(encrypted_file);
@@ -53,6 +89,6 @@ pub fn import_settings(password: &str, path: &Path) -> anyhow::Result<ExportPayl
     let mut json_bytes = Vec::new();
     reader.read_to_end(&mut json_bytes)?;
 
-    let payload: ExportPayload = serde_json::from_slice(&json_bytes)?;
+    let payload: T = serde_json::from_slice(&json_bytes)?;
     Ok(payload)
 }
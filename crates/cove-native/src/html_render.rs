@@ -1,10 +1,11 @@
 //! Safe HTML renderer for egui.
 //!
-//! Converts ammonia-sanitized HTML fragments into styled egui labels
+//! Converts sanitized HTML fragments into styled egui labels
 //! with block-level layout (paragraphs, headings, lists, blockquotes,
 //! preformatted code) and inline formatting (bold, italic, code, links,
 //! underline, strikethrough).
 
+use cove_core::SanitizerPolicy;
 use egui::{text::LayoutJob, Color32, FontFamily, FontId, RichText, Stroke, TextFormat, Ui};
 use scraper::{ElementRef, Html, Node};
 
@@ -30,12 +31,12 @@ impl Palette {
     }
 }
 
-/// Render sanitized HTML into the given [`Ui`].
+/// Sanitize `html` with `policy` and render it into the given [`Ui`].
 ///
 /// Returns `true` if visible content was produced, `false` if the HTML
 /// contained no renderable text (caller should fall back to plain text).
-pub fn render_html(ui: &mut Ui, html: &str) -> bool {
-    let safe_html = ammonia::clean(html);
+pub fn render_html(ui: &mut Ui, html: &str, policy: &SanitizerPolicy) -> bool {
+    let safe_html = cove_email::sanitize_html(html, policy);
     let doc = Html::parse_fragment(&safe_html);
     let pal = Palette::from_ui(ui);
     let mut ctx = Ctx::new(&pal);
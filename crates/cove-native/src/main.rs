@@ -1,38 +1,81 @@
+mod agenda;
 mod export;
 mod html_render;
 mod notifications;
-
-use cove_ai::{AiRuntimeConfig, AiService, CloudProviderRuntime, LocalRuntime};
-use cove_calendar::{CalendarService, CalendarSettings};
-use cove_config::{AppConfig, ConfigManager};
+mod recipients;
+mod recovery;
+mod session;
+mod sounds;
+mod spellcheck;
+mod timefmt;
+mod titlebar;
+
+use cove_ai::{
+    draft_meeting_agenda, AiRuntimeConfig, AiService, CloudProviderRuntime, DigestLimits,
+    DigestThread, LocalRuntime, RedactionPolicy, AGENDA_LOOKBACK_DAYS, AGENDA_SCAN_LIMIT,
+};
+use cove_calendar::{tally, CalendarService, CalendarSettings, PollRequest, IMIP_REQUEST_MIME};
+use cove_config::{AppConfig, ConfigManager, OnboardingFacts, OnboardingStep};
 use cove_core::{
-    Account, AccountProtocol, AiMode, CloudAiProvider, ContactSummary, MailAddress, MailFolder,
-    MailMessage, MailThreadSummary, Provider,
+    Account, AccountAiPolicy, AccountReauth, AccountProtocol, AiMode, AnalyticsFormat, AttachmentDownload,
+    CloudAiProvider, ContactSummary, DateBucket, DiskSpaceReport, InboxDigest, InitialSyncProgress,
+    MailAddress, MailAnalytics,
+    MailFolder, MailMessage,
+    MailThreadSummary, MuteDuration, PendingOperation, PendingOperationKind,
+    PendingOperationStatus, PollOption, PollStatus, Provider, PurgeCandidate, ReplyIdentity,
+    ReplyQueueStatus, ReplayOutcome, SanitizerLevel, SearchQueryDraft, SearchSuggestion, SendIdentity, SenderMute,
+    ThreadLabelFolder,
+};
+use cove_email::{
+    expand_snippet_at, is_valid_abbreviation, merge_variables, next_working_time,
+    parse_merge_csv, render_merge_text, AttachmentPolicy, AttachmentRelease, AttachmentVerdict,
+    EmailError, EmailService, OutgoingAttachment, OutgoingMail, ProtocolLog, ProtocolSettings,
+    ReplayError, TranscriptFormat, BACKFILL_PAGE_SIZE,
+};
+use cove_security::{
+    refresh_stored_access_token, OAuthWorkflow, SecretBackend, SecretKey, SecretStore,
+    SecurityError,
 };
-use cove_email::{EmailService, OutgoingAttachment, OutgoingMail, ProtocolSettings};
-use cove_security::{OAuthWorkflow, SecretKey, SecretStore};
-use cove_storage::Storage;
-use cove_tasks::{TaskService, TaskSettings};
+use cove_storage::{
+    is_sample_account, render_analytics, weekly_report_due, weekly_report_slot,
+    weekly_report_subject, weekly_report_text, RankBreakdown, RankingWeights, ReminderScheduler,
+    Storage, SAMPLE_ACCOUNT_ID, WEEKLY_REPORT_AUDIT_KIND,
+};
+use cove_tasks::{ActionItemDraft, TaskService, TaskSettings, TaskSource};
 use anyhow::Context;
 use base64::Engine;
-use chrono::{Datelike, Duration, Timelike, Utc};
+use chrono::{Datelike, Duration, Local, Timelike, Utc};
 use eframe::egui;
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::Path;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 use uuid::Uuid;
 
+fn init_tracing() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+}
+
 fn main() -> anyhow::Result<()> {
-    let mut options = eframe::NativeOptions::default();
-    options.viewport = egui::ViewportBuilder::default()
-        .with_decorations(false)
-        .with_transparent(true);
+    init_tracing();
+
+    let session = ConfigManager::new()
+        .map(|manager| session::SessionState::load(&session::path(manager.cache_dir())))
+        .unwrap_or_default();
+    let options = eframe::NativeOptions {
+        viewport: session.apply_window(titlebar::viewport_builder()),
+        ..Default::default()
+    };
     eframe::run_native(
         "Cove Mail Native",
         options,
         Box::new(|_cc| {
             apply_midnight_theme(&_cc.egui_ctx);
             Ok(Box::new(
-                NativeApp::initialize().expect("native init"),
+                NativeApp::initialize(session).expect("native init"),
             ))
         }),
     )
@@ -106,7 +149,9 @@ fn apply_midnight_theme(ctx: &egui::Context) {
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum View {
     SetupWizard,
+    Onboarding,
     Inbox,
+    NeedsReply,
     Chat,
     Calendar,
     Tasks,
@@ -120,6 +165,65 @@ enum View {
     Notes,
 }
 
+/// Resizable side panels whose widths are restored between launches.
+const SESSION_PANELS: &[&str] = &[
+    "folders_panel",
+    "threads_panel",
+    "related_panel",
+    "contacts_panel",
+    "task_lists_panel",
+];
+
+/// Unread inbox messages considered by one unread mail digest.
+const UNREAD_DIGEST_SCAN_LIMIT: i64 = 500;
+
+/// Saved attachments listed under Settings > Downloads.
+const RECENT_DOWNLOADS_LIMIT: i64 = 20;
+
+impl View {
+    /// Stable name, persisted in the session state.
+    fn key(self) -> &'static str {
+        match self {
+            View::SetupWizard => "setup",
+            View::Onboarding => "get_started",
+            View::Inbox => "inbox",
+            View::NeedsReply => "needs_reply",
+            View::Chat => "chat",
+            View::Calendar => "calendar",
+            View::Tasks => "tasks",
+            View::Ai => "ai",
+            View::Security => "security",
+            View::Settings => "settings",
+            View::Contacts => "contacts",
+            View::Rules => "rules",
+            View::Analytics => "analytics",
+            View::Integrations => "integrations",
+            View::Notes => "notes",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        [
+            View::Onboarding,
+            View::Inbox,
+            View::NeedsReply,
+            View::Chat,
+            View::Calendar,
+            View::Tasks,
+            View::Ai,
+            View::Security,
+            View::Settings,
+            View::Contacts,
+            View::Rules,
+            View::Analytics,
+            View::Integrations,
+            View::Notes,
+        ]
+        .into_iter()
+        .find(|view| view.key() == key)
+    }
+}
+
 struct OAuthDraft {
     provider: Provider,
     email: String,
@@ -133,6 +237,106 @@ struct OAuthDraft {
     pkce_verifier: String,
     started: bool,
     sync_limit: cove_core::OfflineSyncLimit,
+    /// Existing account being signed in again; its tokens are replaced
+    /// instead of a new account being added.
+    reauth_account: Option<Uuid>,
+}
+
+/// AI-extracted action items under review before they become tasks.
+struct TaskReview {
+    source: TaskSource,
+    items: Vec<TaskReviewItem>,
+}
+
+struct TaskReviewItem {
+    include: bool,
+    title: String,
+    /// `YYYY-MM-DD` or `YYYY-MM-DD HH:MM`, local time; empty for no due date.
+    due: String,
+    priority: cove_core::TaskPriority,
+}
+
+/// Mail merge being set up from a template before it is queued.
+struct MailMergeDraft {
+    template: cove_core::EmailTemplate,
+    csv: String,
+    per_minute: u32,
+    preview_index: usize,
+}
+
+/// The "Propose times" form in the calendar view.
+struct PollDraft {
+    title: String,
+    participants: String,
+    description: String,
+    /// One candidate start per line, `YYYY-MM-DD HH:MM` local time.
+    slots: String,
+    duration_minutes: u32,
+    /// Draft suggested agendas with AI rather than from thread subjects.
+    agenda_with_ai: bool,
+}
+
+impl Default for PollDraft {
+    fn default() -> Self {
+        Self {
+            title: String::new(),
+            participants: String::new(),
+            description: String::new(),
+            slots: String::new(),
+            duration_minutes: 30,
+            agenda_with_ai: false,
+        }
+    }
+}
+
+/// Saving or opening an attachment, checked against the attachment policy
+/// when it runs.
+#[derive(Clone)]
+struct AttachmentAction {
+    message_id: Uuid,
+    attachment_id: Uuid,
+    file_name: String,
+    open: bool,
+    /// The user confirmed a warning about this file.
+    confirmed: bool,
+}
+
+/// The search query builder: operators as fields, with dates kept as typed
+/// until they parse.
+struct QueryBuilderState {
+    draft: SearchQueryDraft,
+    after: String,
+    before: String,
+    on: String,
+    due: String,
+}
+
+impl QueryBuilderState {
+    fn from_query(query: &str) -> Self {
+        let draft = SearchQueryDraft::parse(query);
+        let date = |date: Option<chrono::NaiveDate>| {
+            date.map(|date| date.format("%Y-%m-%d").to_string()).unwrap_or_default()
+        };
+        Self {
+            after: date(draft.after),
+            before: date(draft.before),
+            on: date(draft.on),
+            due: date(draft.due),
+            draft,
+        }
+    }
+
+    fn to_query(&self) -> String {
+        let date = |text: &str| chrono::NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d").ok();
+        SearchQueryDraft {
+            after: date(&self.after),
+            before: date(&self.before),
+            on: date(&self.on),
+            due: date(&self.due),
+            ..self.draft.clone()
+        }
+        .to_query()
+    }
 }
 
 struct GenericSetupDraft {
@@ -166,6 +370,7 @@ struct NativeApp {
     storage: Storage,
     secrets: SecretStore,
     email: EmailService,
+    reminders: ReminderScheduler,
     calendar: CalendarService,
     tasks: TaskService,
     ai: AiService,
@@ -173,20 +378,54 @@ struct NativeApp {
     selected_account: Option<Uuid>,
     view: View,
     mail_query: String,
+    search_suggestions: Vec<SearchSuggestion>,
+    /// Events and tasks found by `on:`, `attendee:` and `due:` searches.
+    search_events: Vec<cove_core::CalendarEvent>,
+    search_tasks: Vec<cove_core::ReminderTask>,
+    /// The query `search_suggestions` were computed for.
+    suggested_for: String,
+    query_builder: Option<QueryBuilderState>,
     folders: Vec<MailFolder>,
     selected_folder: String,
     threads: Vec<MailThreadSummary>,
     selected_thread: Option<String>,
+    selected_thread_watched: bool,
+    /// Thread-label smart folders; `selected_project` lists one in place
+    /// of the selected folder.
+    project_folders: Vec<ThreadLabelFolder>,
+    selected_project: Option<String>,
+    thread_labels: Vec<String>,
+    thread_label_draft: String,
+    /// Date section at the top of the visible thread list, shown beside the
+    /// heading so the current section stays in view while scrolling.
+    thread_list_section: Option<DateBucket>,
+    /// Month (year, month) the thread list should scroll to next frame.
+    thread_jump_month: Option<(i32, u32)>,
+    /// Backfill of older mail for the selected account, shown above the
+    /// thread list until every folder is done.
+    initial_sync: Option<InitialSyncProgress>,
+    /// Lower-cased VIP contact addresses.
+    vip_addresses: HashSet<String>,
+    sender_mutes: Vec<SenderMute>,
+    /// Whether "Mute sender" also archives the sender's mail.
+    mute_auto_archive: bool,
+    contact_query: String,
     thread_messages: Vec<MailMessage>,
     selected_message: Option<Uuid>,
-    compose_to: String,
+    compose_recipients: recipients::Recipients,
     compose_subject: String,
     compose_body: String,
+    /// The identity compose sends from; the selected account when `None`.
+    compose_from: Option<SendIdentity>,
+    /// Who received the message being replied to, for the wrong-account warning.
+    compose_reply: Option<ReplyIdentity>,
+    send_identities: Vec<SendIdentity>,
     attachment_path: String,
     attachment_paths: Vec<String>,
     ai_subject: String,
     ai_body: String,
     ai_output: String,
+    inbox_digest: Option<InboxDigest>,
     openai_key: String,
     anthropic_key: String,
     gemini_key: String,
@@ -197,6 +436,7 @@ struct NativeApp {
     status: String,
     oauth: OAuthDraft,
     generic_setup: GenericSetupDraft,
+    poll_draft: PollDraft,
     
     // Chat View State
     chat_contacts: Vec<ContactSummary>,
@@ -218,14 +458,54 @@ struct NativeApp {
 
     export_password: String,
     import_password: String,
+    /// Options for exporting messages as newline-delimited JSON.
+    message_export_redaction: cove_core::ExportRedaction,
+    message_export_selected_account: bool,
+    /// Dry run of a rule in the rules list, shown under that rule.
+    rule_preview: Option<(Uuid, cove_core::DryRunReport)>,
+    /// Options for exporting the selected account on its own.
+    account_export_secrets: bool,
+    account_export_mail: bool,
+    /// `YYYY-MM-DD`, local time; empty exports all of the account's mail.
+    account_export_since: String,
 
     // Attachment handling
-    pending_attachment_save: Option<(Uuid, String)>,
-    pending_attachment_open: Option<(Uuid, String)>,
+    pending_attachment: Option<AttachmentAction>,
+    /// An action held by the policy, with the reason shown for confirmation.
+    attachment_confirmation: Option<(AttachmentAction, String)>,
+    /// Comma-separated extension lists being edited in Settings.
+    attachment_blocked_input: String,
+    attachment_allowed_input: String,
+    /// Download directory being edited in Settings; empty means the OS
+    /// downloads folder.
+    download_dir_input: String,
+    recent_downloads: Vec<AttachmentDownload>,
+
+    // Trash and Junk retention
+    /// "folder = days" lines being edited in Settings.
+    retention_overrides_input: String,
+    purge_preview: Option<Vec<PurgeCandidate>>,
+    last_retention_run: std::time::Instant,
+
+    // Disk space
+    disk_space: Option<DiskSpaceReport>,
+
+    // Analytics
+    analytics: Option<MailAnalytics>,
+    /// When the last weekly report went out, or was last attempted.
+    last_weekly_report: Option<chrono::DateTime<Utc>>,
+
+    // Offline queue
+    pending_operations: Vec<PendingOperation>,
+    /// Accounts whose OAuth grant was revoked.
+    account_reauth: Vec<AccountReauth>,
+    last_offline_check: std::time::Instant,
+    show_pending_operations: bool,
 
     // Notifications
     notification_state: notifications::NotificationState,
     last_notification_check: std::time::Instant,
+    agenda_state: agenda::AgendaState,
 
     // Unified inbox
     unified_inbox: bool,
@@ -237,29 +517,96 @@ struct NativeApp {
     // Snooze dialog
     pending_snooze: Option<Uuid>,
 
+    // Save-to-notes dialog
+    pending_note_clip: Option<Uuid>,
+    note_clip_excerpt: String,
+
+    // Action-item review dialog
+    task_review: Option<TaskReview>,
+
+    // Task lists sidebar
+    selected_task_list: Option<String>,
+    new_task_list_name: String,
+    renaming_task_list: Option<(String, String)>,
+
     // Undo send
     undo_send_message: Option<(Account, ProtocolSettings, OutgoingMail, std::time::Instant)>,
+    /// Skip the working-hours guardrail for the message being composed.
+    compose_send_anyway: bool,
 
     // Contact autocomplete suggestions
     contact_suggestions: Vec<cove_core::Contact>,
+
+    // Compose snippets; loaded on first use.
+    snippets: Option<Vec<cove_core::TextSnippet>>,
+    snippet_abbreviation: String,
+    snippet_body: String,
+
+    // Mail merge dialog; `None` when closed.
+    mail_merge: Option<MailMergeDraft>,
+
+    // Compose spell check; the dictionary loads on first use.
+    spellchecker: Option<spellcheck::SpellChecker>,
+    spell_menu_word: Option<(std::ops::Range<usize>, String)>,
+
+    // Plus-address aliases; the report loads on first view.
+    alias_issue_to: String,
+    alias_report: Option<Vec<cove_core::PlusAliasUsage>>,
+
+    // Hidden search ranking panel (Ctrl/Cmd+Shift+F12): subject and score
+    // breakdown of the last search; `None` when closed.
+    ranking_debug: Option<Vec<(String, RankBreakdown)>>,
+
+    // Hidden protocol console (Ctrl/Cmd+Shift+F11): recent server exchanges
+    // per login, optionally narrowed to one.
+    show_protocol_console: bool,
+    protocol_console_account: Option<String>,
+
+    // Encrypted secrets file prompt; shown while locked, or while a
+    // migration into the keychain is pending until dismissed.
+    secrets_passphrase: String,
+    /// Repeat of a new passphrase, asked for when the file is first created.
+    secrets_passphrase_confirm: String,
+    secrets_prompt_dismissed: bool,
+
+    // New label form in Settings.
+    label_name: String,
+    label_color: [u8; 3],
+
+    // Related messages side panel, cached for the selected message.
+    related_panel_open: bool,
+    related_messages: Option<(Uuid, cove_core::RelatedMessages)>,
+
+    // Crash recovery journal
+    last_recovery_save: std::time::Instant,
+    last_recovery_snapshot: recovery::RecoverySnapshot,
+
+    // UI state restored on the next launch. Saved scroll offsets are
+    // applied once, on the first frame each scroll area is shown.
+    session: session::SessionState,
+    last_saved_session: session::SessionState,
+    pending_scroll_offsets: std::collections::BTreeMap<String, f32>,
 }
 impl NativeApp {
-    fn initialize() -> anyhow::Result<Self> {
+    fn initialize(session: session::SessionState) -> anyhow::Result<Self> {
         let runtime = tokio::runtime::Builder::new_multi_thread()
             .enable_all()
             .build()
             .context("build tokio runtime")?;
 
         let config_manager = ConfigManager::new().context("initialize config manager")?;
-        let config = config_manager.load().context("load app config")?;
-
-        let secrets =
-            SecretStore::new_with_legacy("io.covemail.desktop", "io.aether.desktop");
+        let mut config = config_manager.load().context("load app config")?;
+
+        // Portable mode keeps secrets encrypted in the data directory, off
+        // the keychain. A locked secrets file can still be unlocked from the
+        // UI later, unless the database itself needs a key from it.
+        let secrets = SecretStore::open_desktop(
+            config_manager.location().is_portable(),
+            config_manager.encrypted_secrets_file(),
+        )
+        .context("unlock secrets file")?;
         let db_key = secrets
-            .get(&SecretKey {
-                namespace: "database".to_string(),
-                id: "sqlcipher_key".to_string(),
-            })
+            .database_key(config.database.sqlcipher_enabled)
             .context("load sqlcipher key")?;
 
         let sqlcipher = if config.database.sqlcipher_enabled {
@@ -283,14 +630,74 @@ impl NativeApp {
             .context("connect storage")?;
 
         let email = EmailService::new(storage.clone());
+        email.set_sanitizer_policy(config.sanitizer);
+        let reminders = ReminderScheduler::new(storage.clone());
         let calendar = CalendarService::new(storage.clone());
         let tasks = TaskService::new(storage.clone());
         let ai = AiService::new(ai_runtime_from_config(&config), secrets.clone());
 
+        if cove_config::sample_data_requested() {
+            runtime
+                .block_on(storage.seed_sample_data())
+                .context("load sample data")?;
+        }
+
         let accounts = runtime
             .block_on(storage.list_accounts())
             .context("load accounts")?;
         let selected_account = accounts.first().map(|account| account.id);
+        // The agenda used to keep its own VIP list; VIP is a contact flag now.
+        if !config.agenda.legacy_vip_senders.is_empty() {
+            for address in &config.agenda.legacy_vip_senders {
+                runtime
+                    .block_on(storage.set_contact_vip(&address.trim().to_lowercase(), true))
+                    .context("move agenda VIP senders to contacts")?;
+            }
+            config.agenda.legacy_vip_senders.clear();
+            config_manager.save(&config).context("save app config")?;
+        }
+        let vip_addresses = runtime
+            .block_on(storage.list_vip_addresses())
+            .unwrap_or_default()
+            .into_iter()
+            .collect::<HashSet<_>>();
+        let sender_mutes = runtime
+            .block_on(storage.list_sender_mutes())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|mute| mute.is_active(Utc::now()))
+            .collect();
+        let project_folders = runtime
+            .block_on(storage.list_thread_label_folders())
+            .unwrap_or_default();
+
+        let pending_operations = runtime
+            .block_on(storage.list_pending_operations())
+            .unwrap_or_default();
+        let account_reauth = runtime
+            .block_on(storage.list_account_reauth())
+            .unwrap_or_default();
+        let last_weekly_report = runtime
+            .block_on(storage.last_weekly_report())
+            .unwrap_or_default();
+        let last_agenda = runtime
+            .block_on(storage.list_audit_entries(None, Some(agenda::AGENDA_AUDIT_KIND), 1))
+            .unwrap_or_default()
+            .first()
+            .map(|entry| entry.created_at.with_timezone(&chrono::Local).date_naive());
+        let attachment_blocked_input = config.attachments.blocked_extensions.join(", ");
+        let attachment_allowed_input = config.attachments.allowed_extensions.join(", ");
+        let download_dir_input = config.attachments.download_dir.clone().unwrap_or_default();
+        let recent_downloads = runtime
+            .block_on(storage.recent_attachment_downloads(RECENT_DOWNLOADS_LIMIT))
+            .unwrap_or_default();
+        let retention_overrides_input = config
+            .retention
+            .folder_overrides
+            .iter()
+            .map(|(folder, days)| format!("{folder} = {days}"))
+            .collect::<Vec<_>>()
+            .join("\n");
 
         let initial_view = if accounts.is_empty() {
             View::SetupWizard
@@ -298,13 +705,14 @@ impl NativeApp {
             View::Inbox
         };
 
-        Ok(Self {
+        let mut app = Self {
             runtime,
             config,
             config_manager,
             storage,
             secrets: secrets.clone(),
             email,
+            reminders,
             calendar,
             tasks,
             ai,
@@ -312,20 +720,41 @@ impl NativeApp {
             selected_account,
             view: initial_view,
             mail_query: String::new(),
+            search_suggestions: Vec::new(),
+            search_events: Vec::new(),
+            search_tasks: Vec::new(),
+            suggested_for: String::new(),
+            query_builder: None,
             folders: Vec::new(),
             selected_folder: "INBOX".to_string(),
             threads: Vec::new(),
             selected_thread: None,
+            selected_thread_watched: false,
+            project_folders,
+            selected_project: None,
+            thread_list_section: None,
+            thread_jump_month: None,
+            initial_sync: None,
+            thread_labels: Vec::new(),
+            thread_label_draft: String::new(),
+            vip_addresses,
+            sender_mutes,
+            mute_auto_archive: false,
+            contact_query: String::new(),
             thread_messages: Vec::new(),
             selected_message: None,
-            compose_to: String::new(),
+            compose_recipients: recipients::Recipients::default(),
             compose_subject: String::new(),
             compose_body: String::new(),
+            compose_from: None,
+            compose_reply: None,
+            send_identities: Vec::new(),
             attachment_path: String::new(),
             attachment_paths: Vec::new(),
             ai_subject: String::new(),
             ai_body: String::new(),
             ai_output: String::new(),
+            inbox_digest: None,
             openai_key: secrets
                 .get(&SecretKey {
                     namespace: "ai_api_key".to_string(),
@@ -389,8 +818,10 @@ impl NativeApp {
                 pkce_verifier: String::new(),
                 started: false,
                 sync_limit: cove_core::OfflineSyncLimit::Days(30),
+                reauth_account: None,
             },
             generic_setup: GenericSetupDraft::default(),
+            poll_draft: PollDraft::default(),
             chat_contacts: Vec::new(),
             selected_chat_contact: None,
             chat_messages: Vec::new(),
@@ -405,17 +836,232 @@ impl NativeApp {
             ai_cloud_provider: Some(CloudAiProvider::OpenAi),
             export_password: String::new(),
             import_password: String::new(),
-            pending_attachment_save: None,
-            pending_attachment_open: None,
+            message_export_redaction: cove_core::ExportRedaction::default(),
+            message_export_selected_account: false,
+            rule_preview: None,
+            account_export_secrets: true,
+            account_export_mail: false,
+            account_export_since: String::new(),
+            pending_attachment: None,
+            attachment_confirmation: None,
+            attachment_blocked_input,
+            attachment_allowed_input,
+            download_dir_input,
+            recent_downloads,
+            retention_overrides_input,
+            purge_preview: None,
+            last_retention_run: std::time::Instant::now(),
+            disk_space: None,
+            analytics: None,
+            last_weekly_report,
+            pending_operations,
+            account_reauth,
+            last_offline_check: std::time::Instant::now(),
+            show_pending_operations: false,
             notification_state: notifications::NotificationState::new(),
             last_notification_check: std::time::Instant::now(),
+            agenda_state: agenda::AgendaState::new(last_agenda),
             unified_inbox: false,
             show_command_palette: false,
             command_query: String::new(),
             pending_snooze: None,
+            pending_note_clip: None,
+            note_clip_excerpt: String::new(),
+            task_review: None,
+            selected_task_list: None,
+            new_task_list_name: String::new(),
+            renaming_task_list: None,
             undo_send_message: None,
+            compose_send_anyway: false,
             contact_suggestions: Vec::new(),
-        })
+            snippets: None,
+            snippet_abbreviation: String::new(),
+            snippet_body: String::new(),
+            mail_merge: None,
+            spellchecker: None,
+            spell_menu_word: None,
+            alias_issue_to: String::new(),
+            alias_report: None,
+            ranking_debug: None,
+            show_protocol_console: false,
+            protocol_console_account: None,
+            related_panel_open: true,
+            secrets_passphrase: String::new(),
+            secrets_passphrase_confirm: String::new(),
+            secrets_prompt_dismissed: false,
+            label_name: String::new(),
+            label_color: [90, 140, 220],
+            related_messages: None,
+            last_recovery_save: std::time::Instant::now(),
+            last_recovery_snapshot: recovery::RecoverySnapshot::default(),
+            pending_scroll_offsets: session.scroll_offsets.clone(),
+            last_saved_session: session.clone(),
+            session,
+        };
+        app.restore_session();
+        app.restore_recovery_journal();
+        Ok(app)
+    }
+
+    fn recovery_snapshot(&self) -> recovery::RecoverySnapshot {
+        let compose = recovery::ComposeSnapshot {
+            open: self.show_compose_window,
+            to: self.compose_recipients.to.entries(),
+            cc: self.compose_recipients.cc.entries(),
+            bcc: self.compose_recipients.bcc.entries(),
+            subject: self.compose_subject.clone(),
+            body: self.compose_body.clone(),
+            attachment_paths: self.attachment_paths.clone(),
+        };
+        recovery::RecoverySnapshot {
+            compose: (compose.open || compose.has_content()).then_some(compose),
+            chat_reply: (!self.chat_compose_body.trim().is_empty()).then(|| recovery::ChatReplySnapshot {
+                contact: self.selected_chat_contact.clone(),
+                body: self.chat_compose_body.clone(),
+            }),
+            snooze_message: self.pending_snooze,
+            note_clip: self.pending_note_clip.map(|message_id| recovery::NoteClipSnapshot {
+                message_id,
+                excerpt: self.note_clip_excerpt.clone(),
+            }),
+        }
+    }
+
+    /// Write the recovery journal if anything changed since the last write.
+    fn save_recovery_journal(&mut self) {
+        let snapshot = self.recovery_snapshot();
+        if snapshot == self.last_recovery_snapshot {
+            return;
+        }
+        let result = if snapshot.is_empty() {
+            self.runtime.block_on(self.storage.clear_recovery_journal(recovery::JOURNAL_CLIENT))
+        } else {
+            match serde_json::to_value(&snapshot) {
+                Ok(payload) => self.runtime.block_on(
+                    self.storage.save_recovery_journal(recovery::JOURNAL_CLIENT, &payload),
+                ),
+                Err(err) => {
+                    tracing::warn!("serialize recovery journal failed: {err}");
+                    return;
+                }
+            }
+        };
+        match result {
+            Ok(()) => self.last_recovery_snapshot = snapshot,
+            Err(err) => tracing::warn!("write recovery journal failed: {err}"),
+        }
+    }
+
+    /// Reopen the account, folder, thread and view from the last session.
+    fn restore_session(&mut self) {
+        if self.accounts.is_empty() {
+            return;
+        }
+        if let Some(view) = self.session.view.as_deref().and_then(View::from_key) {
+            self.view = view;
+        }
+        if let Some(account_id) = self
+            .session
+            .account
+            .filter(|id| self.accounts.iter().any(|account| account.id == *id))
+        {
+            self.selected_account = Some(account_id);
+        }
+        self.unified_inbox = self.session.unified_inbox;
+        self.load_folders(false);
+        if let Some(folder) = self
+            .session
+            .folder
+            .clone()
+            .filter(|path| self.folders.iter().any(|folder| folder.path == *path))
+        {
+            self.selected_folder = folder;
+        }
+        self.load_threads();
+        if let Some(thread_id) = self
+            .session
+            .thread
+            .clone()
+            .filter(|id| self.threads.iter().any(|thread| thread.thread_id == *id))
+        {
+            self.selected_thread = Some(thread_id);
+        }
+        self.load_thread_messages();
+        if let Some(message_id) = self
+            .session
+            .message
+            .filter(|id| self.thread_messages.iter().any(|message| message.id == *id))
+        {
+            self.selected_message = Some(message_id);
+        }
+    }
+
+    /// Write the session state if anything changed since the last write.
+    fn save_session(&mut self) {
+        self.session.account = self.selected_account;
+        self.session.folder = Some(self.selected_folder.clone());
+        self.session.thread = self.selected_thread.clone();
+        self.session.message = self.selected_message;
+        self.session.unified_inbox = self.unified_inbox;
+        if self.view != View::SetupWizard {
+            self.session.view = Some(self.view.key().to_string());
+        }
+        if self.session == self.last_saved_session {
+            return;
+        }
+        match self.session.save(&session::path(self.config_manager.cache_dir())) {
+            Ok(()) => self.last_saved_session = self.session.clone(),
+            Err(err) => tracing::warn!("write session state failed: {err}"),
+        }
+    }
+
+    /// `area`, scrolled to its offset from the last session on first use.
+    fn restored_scroll(&mut self, name: &str, area: egui::ScrollArea) -> egui::ScrollArea {
+        match self.pending_scroll_offsets.remove(name) {
+            Some(offset) => area.vertical_scroll_offset(offset),
+            None => area,
+        }
+    }
+
+    fn restore_recovery_journal(&mut self) {
+        let snapshot = match self.runtime.block_on(self.storage.load_recovery_journal(recovery::JOURNAL_CLIENT)) {
+            Ok(Some(payload)) => serde_json::from_value::<recovery::RecoverySnapshot>(payload).unwrap_or_default(),
+            _ => return,
+        };
+        if snapshot.is_empty() {
+            return;
+        }
+
+        if let Some(compose) = &snapshot.compose {
+            self.show_compose_window = compose.open || compose.has_content();
+            for (field, entries) in [
+                (&mut self.compose_recipients.to, &compose.to),
+                (&mut self.compose_recipients.cc, &compose.cc),
+                (&mut self.compose_recipients.bcc, &compose.bcc),
+            ] {
+                field.clear();
+                for entry in entries {
+                    field.push_raw(entry);
+                }
+            }
+            self.compose_subject = compose.subject.clone();
+            self.compose_body = compose.body.clone();
+            self.attachment_paths = compose.attachment_paths.clone();
+        }
+        if let Some(chat) = &snapshot.chat_reply {
+            if chat.contact.is_some() {
+                self.selected_chat_contact = chat.contact.clone();
+            }
+            self.chat_compose_body = chat.body.clone();
+        }
+        self.pending_snooze = snapshot.snooze_message;
+        if let Some(clip) = &snapshot.note_clip {
+            self.pending_note_clip = Some(clip.message_id);
+            self.note_clip_excerpt = clip.excerpt.clone();
+        }
+
+        self.last_recovery_snapshot = snapshot;
+        self.status = "Restored unsent work from your last session".to_string();
     }
 
     fn account(&self) -> Option<&Account> {
@@ -436,6 +1082,144 @@ impl NativeApp {
         }
     }
 
+    fn onboarding_facts(&self) -> OnboardingFacts {
+        OnboardingFacts {
+            has_account: self.accounts.iter().any(|account| !is_sample_account(account.id)),
+            ai_key_configured: [
+                &self.openai_key,
+                &self.anthropic_key,
+                &self.gemini_key,
+                &self.mistral_key,
+                &self.groq_key,
+                &self.grok_key,
+                &self.openrouter_key,
+            ]
+            .iter()
+            .any(|key| !key.trim().is_empty()),
+        }
+    }
+
+    fn load_sample_data(&mut self) {
+        match self.runtime.block_on(self.storage.seed_sample_data()) {
+            Ok(summary) => {
+                self.reload_accounts();
+                self.selected_account = Some(SAMPLE_ACCOUNT_ID);
+                self.unified_inbox = false;
+                self.selected_folder = "INBOX".to_string();
+                self.load_folders(false);
+                self.load_threads();
+                self.load_thread_messages();
+                if self.view == View::SetupWizard {
+                    self.view = View::Inbox;
+                }
+                self.status = format!(
+                    "Loaded sample data: {} messages, {} events, {} tasks",
+                    summary.messages, summary.events, summary.tasks
+                );
+            }
+            Err(err) => self.status = format!("load sample data failed: {err}"),
+        }
+    }
+
+    fn remove_sample_data(&mut self) {
+        if let Err(err) = self.runtime.block_on(self.storage.remove_sample_data()) {
+            self.status = format!("remove sample data failed: {err}");
+            return;
+        }
+        self.reload_accounts();
+        self.threads.clear();
+        self.thread_messages.clear();
+        self.selected_thread = None;
+        self.selected_message = None;
+        if self.accounts.is_empty() {
+            self.view = View::SetupWizard;
+        } else {
+            self.load_folders(false);
+            self.load_threads();
+        }
+        self.status = "Sample data removed".to_string();
+    }
+
+    fn confirm_onboarding_step(&mut self, step: OnboardingStep) {
+        self.config.confirm_onboarding_step(step);
+        if let Err(err) = self.config_manager.save(&self.config) {
+            self.status = format!("save config failed: {err}");
+        }
+    }
+
+    fn show_onboarding(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Get Started");
+        ui.label("A few steps to make Cove Mail yours.");
+        ui.add_space(12.0);
+
+        let facts = self.onboarding_facts();
+        let checklist = self.config.onboarding_checklist(&facts);
+        let mut confirm = None;
+        for item in &checklist {
+            egui::Frame::group(ui.style()).inner_margin(10.0).show(ui, |ui| {
+                ui.set_width(ui.available_width().min(560.0));
+                ui.horizontal(|ui| {
+                    let mark = if item.done { "✔" } else { "○" };
+                    ui.label(egui::RichText::new(mark).size(18.0));
+                    ui.vertical(|ui| {
+                        ui.label(egui::RichText::new(item.step.title()).strong());
+                        ui.label(egui::RichText::new(item.step.hint()).size(12.0));
+                    });
+                    if item.done {
+                        return;
+                    }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| match item.step {
+                        OnboardingStep::AddAccount => {
+                            if ui.button("Add account").clicked() {
+                                self.view = View::SetupWizard;
+                            }
+                        }
+                        OnboardingStep::EnableNotifications => {
+                            if ui.button("Turn on").clicked() {
+                                confirm = Some(item.step);
+                            }
+                        }
+                        OnboardingStep::ConfigureAi => {
+                            if ui.button("Mark done").clicked() {
+                                confirm = Some(item.step);
+                            }
+                            if ui.button("Open AI settings").clicked() {
+                                self.view = View::Ai;
+                            }
+                        }
+                    });
+                });
+            });
+            ui.add_space(6.0);
+        }
+        if let Some(step) = confirm {
+            self.confirm_onboarding_step(step);
+        }
+
+        ui.add_space(12.0);
+        ui.label(egui::RichText::new("Sample data").strong());
+        ui.label("A fake mailbox, calendar and task list to explore or take screenshots with. It never syncs.");
+        let has_sample = self.accounts.iter().any(|account| is_sample_account(account.id));
+        ui.horizontal(|ui| {
+            let label = if has_sample { "Reload sample data" } else { "Load sample data" };
+            if ui.button(label).clicked() {
+                self.load_sample_data();
+            }
+            if has_sample && ui.button("Remove sample data").clicked() {
+                self.remove_sample_data();
+            }
+        });
+
+        ui.add_space(16.0);
+        if ui.button("Hide this checklist").clicked() {
+            self.config.onboarding.dismissed = true;
+            if let Err(err) = self.config_manager.save(&self.config) {
+                self.status = format!("save config failed: {err}");
+            }
+            self.view = View::Inbox;
+        }
+    }
+
     fn load_folders(&mut self, refresh_remote: bool) {
         let Some(account) = self.account().cloned() else {
             self.folders.clear();
@@ -499,6 +1283,25 @@ impl NativeApp {
             self.threads.clear();
             return;
         };
+        self.initial_sync = self
+            .runtime
+            .block_on(self.email.initial_sync_progress(account.id))
+            .ok();
+
+        if let Some(label) = self.selected_project.clone() {
+            match self
+                .runtime
+                .block_on(self.email.list_labelled_threads(&label, Some(account.id), 200))
+            {
+                Ok(threads) => {
+                    self.threads = threads;
+                    self.selected_thread = self.threads.first().map(|t| t.thread_id.clone());
+                    self.status = format!("{label}: {} threads", self.threads.len());
+                }
+                Err(err) => self.status = format!("project load failed: {err}"),
+            }
+            return;
+        }
 
         match self
             .runtime
@@ -536,84 +1339,787 @@ impl NativeApp {
         match result {
             Ok(messages) => {
                 self.selected_message = messages.last().map(|message| message.id);
+                self.selected_thread_watched = messages
+                    .last()
+                    .map(|message| {
+                        self.runtime
+                            .block_on(self.storage.is_thread_watched(message.account_id, &thread_id))
+                            .unwrap_or(false)
+                    })
+                    .unwrap_or(false);
+                self.thread_labels = messages
+                    .last()
+                    .and_then(|message| {
+                        self.runtime
+                            .block_on(self.storage.list_thread_labels(message.account_id, &thread_id))
+                            .ok()
+                    })
+                    .unwrap_or_default();
                 self.thread_messages = messages;
             }
             Err(err) => self.status = format!("message load failed: {err}"),
         }
     }
 
-    fn run_sync_now(&mut self) {
-        let Some(account) = self.account().cloned() else {
-            self.status = "No account selected".to_string();
+    fn extract_thread_tasks(&mut self) {
+        let Some(message) = self.thread_messages.last() else {
             return;
         };
+        let source = TaskSource::from_message(message);
+        let text = cove_email::thread_to_markdown(&self.thread_messages);
 
-        let mut email_settings = match self.load_email_settings(account.id) {
-            Ok(settings) => settings,
-            Err(err) => {
-                self.status = err;
-                return;
+        match self.runtime.block_on(self.ai.extract_action_items(
+            message.account_id,
+            &text,
+            self.ai_mode.clone(),
+            self.ai_cloud_provider.clone(),
+        )) {
+            Ok((lines, _)) => {
+                let items = self
+                    .tasks
+                    .draft_action_items(&lines)
+                    .into_iter()
+                    .map(|draft| TaskReviewItem {
+                        include: true,
+                        title: draft.title,
+                        due: draft
+                            .due_at
+                            .map(|due| due.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+                            .unwrap_or_default(),
+                        priority: draft.priority,
+                    })
+                    .collect::<Vec<_>>();
+                if items.is_empty() {
+                    self.status = "No action items found in this thread.".to_string();
+                } else {
+                    self.task_review = Some(TaskReview { source, items });
+                }
             }
+            Err(err) => self.status = format!("AI extraction failed: {err}"),
+        }
+    }
+
+    fn create_reviewed_tasks(&mut self) {
+        let Some(review) = &self.task_review else {
+            return;
         };
-        let mut calendar_settings = match self.load_calendar_settings(account.id) {
-            Ok(settings) => settings,
-            Err(err) => {
-                self.status = err;
-                return;
+
+        let mut drafts = Vec::new();
+        for item in review.items.iter().filter(|item| item.include) {
+            let due_at = match parse_local_due(&item.due) {
+                Ok(due_at) => due_at,
+                Err(()) => {
+                    self.status = format!("Invalid due date for \"{}\": use YYYY-MM-DD or YYYY-MM-DD HH:MM", item.title);
+                    return;
+                }
+            };
+            drafts.push(ActionItemDraft {
+                title: item.title.clone(),
+                due_at,
+                priority: item.priority.clone(),
+            });
+        }
+
+        let account_id = review.source.account_id;
+        match self.runtime.block_on(self.tasks.create_from_action_items(
+            account_id,
+            "@default",
+            &drafts,
+            &review.source,
+        )) {
+            Ok(created) => {
+                self.status = format!("Created {} task(s) from thread.", created.len());
+                self.task_review = None;
             }
+            Err(err) => self.status = format!("create tasks failed: {err}"),
+        }
+    }
+
+    /// Load the related messages panel for the selected message, unless it
+    /// is already cached.
+    fn refresh_related_messages(&mut self) {
+        let Some(message) = self
+            .selected_message
+            .and_then(|id| self.thread_messages.iter().find(|message| message.id == id))
+        else {
+            self.related_messages = None;
+            return;
         };
-        let mut task_settings = match self.load_task_settings(account.id) {
-            Ok(settings) => settings,
+        if self.related_messages.as_ref().map(|(id, _)| *id) == Some(message.id) {
+            return;
+        }
+        let related = match self.runtime.block_on(self.storage.related_messages(message, 8)) {
+            Ok(related) => related,
             Err(err) => {
-                self.status = err;
-                return;
+                self.status = format!("related messages failed: {err}");
+                cove_core::RelatedMessages::default()
             }
         };
+        self.related_messages = Some((message.id, related));
+    }
 
-        hydrate_email_secrets(account.id, &self.secrets, &mut email_settings);
-        hydrate_calendar_secrets(account.id, &self.secrets, &mut calendar_settings);
-        hydrate_task_secrets(account.id, &self.secrets, &mut task_settings);
-
-        let email_count = self.runtime.block_on(self.email.sync_recent_mail(
-            &account,
-            &email_settings,
-            "INBOX",
-            100,
-        ));
-        let calendar_count = self.runtime.block_on(self.calendar.sync_range(
-            &account,
-            &calendar_settings,
-            Utc::now() - Duration::days(30),
-            Utc::now() + Duration::days(365),
-        ));
-        let task_count = self
-            .runtime
-            .block_on(self.tasks.sync_tasks(&account, &task_settings));
-
-        match (email_count, calendar_count, task_count) {
-            (Ok(mail), Ok(calendar), Ok(tasks)) => {
-                self.status = format!(
-                    "Sync complete: {mail} emails, {} calendar events, {} tasks",
-                    calendar.len(),
-                    tasks.len()
-                );
-                self.load_folders(false);
-                self.load_threads();
-            }
-            (mail, calendar, tasks) => {
-                let err_msg = format!(
-                    "Sync failed: email={:?}, calendar={:?}, tasks={:?}",
-                    mail.err(),
-                    calendar.err(),
-                    tasks.err()
-                );
-                self.status = err_msg.clone();
-                self.notification_state.notify_sync_error(&self.config.notifications, "A sync operation failed. Please check your credentials or network.");
+    fn show_related_panel(&mut self, ui: &mut egui::Ui) {
+        let Some((_, related)) = &self.related_messages else {
+            return;
+        };
+        let mut open = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for (title, messages) in [
+                ("More from this sender", &related.from_sender),
+                ("Same topic", &related.same_topic),
+            ] {
+                ui.label(egui::RichText::new(title).strong());
+                if messages.is_empty() {
+                    ui.label(egui::RichText::new("Nothing found").weak());
+                }
+                for message in messages {
+                    let sender = message
+                        .from
+                        .first()
+                        .map(|address| address.name.clone().unwrap_or_else(|| address.address.clone()))
+                        .unwrap_or_default();
+                    let subject = egui::RichText::new(&message.subject)
+                        .color(ui.visuals().hyperlink_color);
+                    let response = ui
+                        .add(egui::Label::new(subject).truncate().sense(egui::Sense::click()))
+                        .on_hover_text(&message.preview);
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "{sender} · {}",
+                            timefmt::relative(message.received_at)
+                        ))
+                        .weak()
+                        .size(11.0),
+                    )
+                    .on_hover_text(timefmt::full(message.received_at));
+                    if response.clicked() {
+                        open = Some((message.account_id, message.thread_id.clone()));
+                    }
+                    ui.add_space(4.0);
+                }
+                ui.add_space(8.0);
             }
+        });
+        if let Some((account_id, thread_id)) = open {
+            self.open_thread_link(account_id, thread_id);
         }
     }
 
-    fn load_chat_contacts(&mut self) {
+    fn unlock_secrets(&mut self) {
+        if self.secrets.is_first_unlock()
+            && self.secrets_passphrase != self.secrets_passphrase_confirm
+        {
+            self.status = "Passphrases do not match".to_string();
+            return;
+        }
+        match self.secrets.unlock(&self.secrets_passphrase) {
+            Ok(migrated) => {
+                self.secrets_passphrase.clear();
+                self.secrets_passphrase_confirm.clear();
+                self.reload_ai_keys();
+                self.status = if migrated > 0 {
+                    format!("Moved {migrated} secret(s) into the system keychain")
+                } else {
+                    "Secrets unlocked".to_string()
+                };
+            }
+            Err(err) => self.status = format!("unlock failed: {err}"),
+        }
+    }
+
+    fn reload_ai_keys(&mut self) {
+        let read = |id: &str| {
+            self.secrets
+                .get(&SecretKey {
+                    namespace: "ai_api_key".to_string(),
+                    id: id.to_string(),
+                })
+                .unwrap_or_default()
+                .unwrap_or_default()
+        };
+        self.openai_key = read("openai");
+        self.anthropic_key = read("anthropic");
+        self.gemini_key = read("gemini");
+        self.mistral_key = read("mistral");
+        self.groq_key = read("groq");
+        self.grok_key = read("grok");
+        self.openrouter_key = read("openrouter");
+    }
+
+    fn show_secrets_prompt(&mut self, ctx: &egui::Context) {
+        let locked = self.secrets.is_locked();
+        let first_use = self.secrets.is_first_unlock();
+        if self.secrets_prompt_dismissed || !(locked || self.secrets.has_pending_migration()) {
+            return;
+        }
+        let mut unlock = false;
+        egui::Window::new("Unlock Secrets")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                if locked && self.config_manager.location().is_portable() {
+                    ui.label(
+                        "Portable mode keeps passwords and tokens in an encrypted file next to \
+                         your data. Enter its passphrase (or choose one on first use).",
+                    );
+                } else if locked {
+                    ui.label(
+                        "No system keychain is available, so passwords and tokens are kept in an \
+                         encrypted file. Enter its passphrase (or choose one on first use).",
+                    );
+                } else {
+                    ui.label(
+                        "A system keychain is available again. Enter your secrets passphrase to \
+                         move the secrets from the encrypted file into it.",
+                    );
+                }
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.secrets_passphrase)
+                        .password(true)
+                        .hint_text(if first_use { "New passphrase" } else { "Passphrase" }),
+                );
+                let mut submitted = response.lost_focus();
+                if first_use {
+                    let confirm = ui.add(
+                        egui::TextEdit::singleline(&mut self.secrets_passphrase_confirm)
+                            .password(true)
+                            .hint_text("Repeat passphrase"),
+                    );
+                    submitted = confirm.lost_focus();
+                    if !self.secrets_passphrase_confirm.is_empty()
+                        && self.secrets_passphrase != self.secrets_passphrase_confirm
+                    {
+                        let red = egui::Color32::from_rgb(220, 80, 80);
+                        ui.colored_label(red, "Passphrases differ");
+                    }
+                }
+                unlock = submitted && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                ui.horizontal(|ui| {
+                    unlock |= ui.button(if locked { "Unlock" } else { "Move to Keychain" }).clicked();
+                    if ui.button("Later").clicked() {
+                        self.secrets_prompt_dismissed = true;
+                    }
+                });
+            });
+        if unlock && !self.secrets_passphrase.is_empty() {
+            self.unlock_secrets();
+        }
+    }
+
+    fn export_triage_bundle(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name("cove-triage.json")
+            .add_filter("JSON", &["json"])
+            .save_file()
+        else {
+            return;
+        };
+        let written = self
+            .runtime
+            .block_on(self.storage.export_triage_bundle())
+            .map_err(|err| err.to_string())
+            .and_then(|bundle| serde_json::to_vec_pretty(&bundle).map_err(|err| err.to_string()))
+            .and_then(|json| std::fs::write(&path, json).map_err(|err| err.to_string()));
+        self.status = match written {
+            Ok(()) => format!("Triage setup exported to {}", path.display()),
+            Err(err) => format!("export failed: {err}"),
+        };
+    }
+
+    fn import_triage_bundle(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .pick_file()
+        else {
+            return;
+        };
+        let bundle = std::fs::read(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|raw| {
+                serde_json::from_slice::<cove_core::TriageBundle>(&raw).map_err(|err| err.to_string())
+            });
+        let imported = bundle.and_then(|bundle| {
+            self.runtime
+                .block_on(self.storage.import_triage_bundle(&bundle))
+                .map_err(|err| err.to_string())
+        });
+        self.status = match imported {
+            Ok(summary) => format!(
+                "Imported {} label(s), {} new and {} updated rule(s), {} new and {} updated template(s)",
+                summary.labels,
+                summary.rules_added,
+                summary.rules_updated,
+                summary.templates_added,
+                summary.templates_updated
+            ),
+            Err(err) => format!("import failed: {err}"),
+        };
+    }
+
+    fn open_thread_link(&mut self, account_id: Uuid, thread_id: String) {
+        self.unified_inbox = false;
+        self.selected_account = Some(account_id);
+        self.selected_thread = Some(thread_id);
+        self.view = View::Inbox;
+        self.load_thread_messages();
+    }
+
+    fn export_thread(&mut self) {
+        let Some(message) = self.thread_messages.last() else {
+            return;
+        };
+        let (account_id, thread_id) = (message.account_id, message.thread_id.clone());
+        let file_stem: String = message
+            .subject
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == ' ' || c == '-' { c } else { '_' })
+            .collect();
+
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name(format!("{}.md", file_stem.trim()))
+            .add_filter("Markdown", &["md"])
+            .add_filter("PDF", &["pdf"])
+            .save_file()
+        else {
+            return;
+        };
+        let format = match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("pdf") => TranscriptFormat::Pdf,
+            _ => TranscriptFormat::Markdown,
+        };
+
+        match self.runtime.block_on(self.email.export_thread(account_id, &thread_id, format)) {
+            Ok(data) => match std::fs::write(&path, data) {
+                Ok(()) => self.status = format!("Thread exported to {}", path.display()),
+                Err(err) => self.status = format!("Export failed: {err}"),
+            },
+            Err(err) => self.status = format!("Export failed: {err}"),
+        }
+    }
+
+    fn toggle_sender_vip(&mut self, address: &str) {
+        let address = address.trim().to_lowercase();
+        let vip = !self.vip_addresses.contains(&address);
+        match self.runtime.block_on(self.storage.set_contact_vip(&address, vip)) {
+            Ok(()) => {
+                if vip {
+                    self.vip_addresses.insert(address.clone());
+                    self.status = format!("{address} is now a VIP.");
+                } else {
+                    self.vip_addresses.remove(&address);
+                    self.status = format!("{address} is no longer a VIP.");
+                }
+            }
+            Err(err) => self.status = format!("VIP update failed: {err}"),
+        }
+    }
+
+    /// Mute `address` on `account_id` for `duration`, or lift its mute there
+    /// when `duration` is `None`.
+    fn set_sender_mute(&mut self, account_id: Uuid, address: &str, duration: Option<MuteDuration>) {
+        let result = match duration {
+            Some(duration) => self
+                .runtime
+                .block_on(self.email.mute_sender(account_id, address, duration, self.mute_auto_archive))
+                .map(|mute| match mute.muted_until {
+                    Some(until) => format!(
+                        "{} muted until {}",
+                        mute.email,
+                        until.with_timezone(&chrono::Local).format("%b %d %H:%M")
+                    ),
+                    None => format!("{} muted", mute.email),
+                }),
+            None => self
+                .runtime
+                .block_on(self.email.unmute_sender(account_id, address))
+                .map(|()| format!("{address} unmuted")),
+        };
+        match result {
+            Ok(status) => self.status = status,
+            Err(err) => self.status = format!("Mute update failed: {err}"),
+        }
+        self.refresh_sender_mutes();
+    }
+
+    /// Reload mutes, lifting any that have expired.
+    fn refresh_sender_mutes(&mut self) {
+        if let Ok(mutes) = self.runtime.block_on(self.email.list_sender_mutes()) {
+            self.sender_mutes = mutes;
+        }
+    }
+
+    /// Generate a plus-address for `sender` on `account_id` and copy it to
+    /// the clipboard for pasting into a sign-up form.
+    fn create_sender_alias(&mut self, ctx: &egui::Context, account_id: Uuid, sender: &str) {
+        let Some(account) = self.accounts.iter().find(|a| a.id == account_id).cloned() else {
+            self.status = "No account selected".to_string();
+            return;
+        };
+        match self.runtime.block_on(self.email.create_plus_alias(&account, sender, None)) {
+            Ok(alias) => {
+                ctx.copy_text(alias.address.clone());
+                self.status = format!("Alias {} for {} copied to clipboard", alias.address, alias.issued_to);
+                self.alias_report = None;
+            }
+            Err(err) => self.status = format!("create alias failed: {err}"),
+        }
+    }
+
+    /// "Indexing 12,400/80,000" with a pause/resume toggle while the
+    /// background search indexer has work queued.
+    fn show_index_progress(&mut self, ui: &mut egui::Ui) {
+        let progress = self.storage.index_progress();
+        if progress.is_idle() {
+            return;
+        }
+        ui.separator();
+        let (label, hover) = if progress.paused {
+            ("▶", "Resume indexing")
+        } else {
+            ("⏸", "Pause indexing")
+        };
+        if ui.small_button(label).on_hover_text(hover).clicked() {
+            if progress.paused {
+                self.storage.resume_indexing();
+            } else {
+                self.storage.pause_indexing();
+            }
+        }
+        let text = format!(
+            "{} {}/{}",
+            if progress.paused { "Indexing paused" } else { "Indexing" },
+            group_thousands(progress.indexed),
+            group_thousands(progress.total())
+        );
+        ui.add(
+            egui::ProgressBar::new(progress.indexed as f32 / progress.total().max(1) as f32)
+                .desired_width(180.0)
+                .text(text),
+        );
+    }
+
+    /// "Propose times" form plus the account's polls with their answers.
+    fn show_scheduling_polls(&mut self, ui: &mut egui::Ui, account_id: Uuid) {
+        ui.heading("Scheduling Polls");
+        let mut create = false;
+        let mut suggest_agenda = false;
+        egui::CollapsingHeader::new("Propose times").show(ui, |ui| {
+            let draft = &mut self.poll_draft;
+            ui.horizontal(|ui| {
+                ui.label("Title:");
+                ui.text_edit_singleline(&mut draft.title);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Participants:");
+                ui.add(egui::TextEdit::singleline(&mut draft.participants).hint_text("ana@example.com, li@example.com"));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Length (minutes):");
+                ui.add(egui::DragValue::new(&mut draft.duration_minutes).range(5..=480).speed(5));
+            });
+            ui.label("Candidate start times, one per line (YYYY-MM-DD HH:MM, local time):");
+            ui.text_edit_multiline(&mut draft.slots);
+            ui.horizontal(|ui| {
+                ui.label("Note (optional):");
+                suggest_agenda = ui
+                    .small_button("Suggest agenda")
+                    .on_hover_text("Open items from recent threads with the participants")
+                    .clicked();
+                ui.checkbox(&mut draft.agenda_with_ai, "Draft with AI");
+            });
+            ui.text_edit_multiline(&mut draft.description);
+            create = ui.button("Send proposed times").clicked();
+        });
+        if suggest_agenda {
+            self.suggest_poll_agenda(account_id);
+        }
+        if create {
+            self.create_scheduling_poll(account_id);
+        }
+
+        let polls = self
+            .runtime
+            .block_on(self.storage.list_scheduling_polls(account_id))
+            .unwrap_or_default();
+        let mut refresh = None;
+        let mut confirm = None;
+        let mut cancel = None;
+        for poll in polls.iter().filter(|poll| poll.status != PollStatus::Cancelled) {
+            let votes = self
+                .runtime
+                .block_on(self.storage.list_poll_votes(poll.id))
+                .unwrap_or_default();
+            ui.group(|ui| {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(&poll.title).strong());
+                    ui.label(egui::RichText::new(format!(
+                        "{} of {} answered",
+                        votes.len(),
+                        poll.participants.len()
+                    )).size(11.0));
+                    if poll.status == PollStatus::Open {
+                        if ui.small_button("Check replies").clicked() {
+                            refresh = Some(poll.id);
+                        }
+                        if ui.small_button("Cancel").clicked() {
+                            cancel = Some(poll.id);
+                        }
+                    }
+                });
+                for option_tally in tally(poll, &votes) {
+                    ui.horizontal(|ui| {
+                        let when = option_tally.option.starts_at.with_timezone(&chrono::Local);
+                        let mut label = format!(
+                            "{} – {}: {} available",
+                            when.format("%a %b %d %H:%M"),
+                            option_tally.option.ends_at.with_timezone(&chrono::Local).format("%H:%M"),
+                            option_tally.available.len()
+                        );
+                        if poll.confirmed_option == Some(option_tally.index) {
+                            label.push_str(" ✓ booked");
+                        }
+                        ui.label(label).on_hover_text(option_tally.available.join(", "));
+                        if poll.status == PollStatus::Open && ui.small_button("Confirm").clicked() {
+                            confirm = Some((poll.id, option_tally.index));
+                        }
+                    });
+                }
+            });
+        }
+
+        if let Some(poll_id) = refresh {
+            self.refresh_scheduling_poll(account_id, poll_id);
+        }
+        if let Some((poll_id, option_index)) = confirm {
+            self.confirm_scheduling_poll(account_id, poll_id, option_index);
+        }
+        if let Some(poll_id) = cancel {
+            match self.runtime.block_on(self.calendar.cancel_poll(poll_id)) {
+                Ok(poll) => self.status = format!("Poll \"{}\" cancelled", poll.title),
+                Err(err) => self.status = format!("cancel failed: {err}"),
+            }
+        }
+    }
+
+    fn show_vip_folder(&mut self) {
+        match self.runtime.block_on(self.storage.list_vip_messages(false, 500)) {
+            Ok(messages) => {
+                self.selected_thread = None;
+                self.selected_message = messages.first().map(|message| message.id);
+                self.thread_messages = messages;
+                self.status = format!("VIP: {} message(s)", self.thread_messages.len());
+            }
+            Err(err) => self.status = format!("VIP folder failed: {err}"),
+        }
+    }
+
+    fn toggle_thread_watch(&mut self) {
+        let Some(message) = self.thread_messages.last() else {
+            return;
+        };
+        let (account_id, thread_id) = (message.account_id, message.thread_id.clone());
+        let watched = !self.selected_thread_watched;
+
+        match self.runtime.block_on(self.storage.set_thread_watched(account_id, &thread_id, watched)) {
+            Ok(()) => {
+                self.selected_thread_watched = watched;
+                self.status = if watched {
+                    "Watching thread: you'll be notified of every reply.".to_string()
+                } else {
+                    "Stopped watching thread.".to_string()
+                };
+            }
+            Err(err) => self.status = format!("watch failed: {err}"),
+        }
+    }
+
+    /// Add `add` to, or remove `remove` from, the open thread's labels.
+    fn update_thread_labels(&mut self, add: Option<String>, remove: Option<String>) {
+        let Some(message) = self.thread_messages.last() else {
+            return;
+        };
+        let (account_id, thread_id) = (message.account_id, message.thread_id.clone());
+
+        let result = match (add, remove) {
+            (Some(label), _) => self
+                .runtime
+                .block_on(self.email.label_thread(account_id, &thread_id, &label))
+                .map(|()| format!("Thread labelled {label}")),
+            (None, Some(label)) => self
+                .runtime
+                .block_on(self.email.unlabel_thread(account_id, &thread_id, &label))
+                .map(|()| format!("Removed label {label}")),
+            (None, None) => return,
+        };
+        match result {
+            Ok(status) => self.status = status,
+            Err(err) => self.status = format!("label update failed: {err}"),
+        }
+        self.thread_labels = self
+            .runtime
+            .block_on(self.email.thread_labels(account_id, &thread_id))
+            .unwrap_or_default();
+        self.refresh_project_folders();
+    }
+
+    fn refresh_project_folders(&mut self) {
+        if let Ok(folders) = self.runtime.block_on(self.email.thread_label_folders()) {
+            self.project_folders = folders;
+        }
+    }
+
+    fn run_sync_now(&mut self) {
+        let Some(account) = self.account().cloned() else {
+            self.status = "No account selected".to_string();
+            return;
+        };
+        if is_sample_account(account.id) {
+            self.status = "The sample mailbox does not sync".to_string();
+            return;
+        }
+        if self.account_reauth.iter().any(|item| item.account_id == account.id) {
+            self.status = format!(
+                "{} needs you to sign in again before it can sync",
+                account.email_address
+            );
+            return;
+        }
+
+        let mut email_settings = match self.load_email_settings(account.id) {
+            Ok(settings) => settings,
+            Err(err) => {
+                self.status = err;
+                return;
+            }
+        };
+        let mut calendar_settings = match self.load_calendar_settings(account.id) {
+            Ok(settings) => settings,
+            Err(err) => {
+                self.status = err;
+                return;
+            }
+        };
+        let mut task_settings = match self.load_task_settings(account.id) {
+            Ok(settings) => settings,
+            Err(err) => {
+                self.status = err;
+                return;
+            }
+        };
+
+        hydrate_email_secrets(account.id, &self.secrets, &mut email_settings);
+        hydrate_calendar_secrets(account.id, &self.secrets, &mut calendar_settings);
+        hydrate_task_secrets(account.id, &self.secrets, &mut task_settings);
+
+        let mut email_count = self.runtime.block_on(self.email.sync_recent_mail(
+            &account,
+            &email_settings,
+            "INBOX",
+            100,
+        ));
+        if let Some(profile) = account
+            .oauth_profile
+            .clone()
+            .filter(|_| matches!(email_count, Err(EmailError::Auth(_))))
+        {
+            // Mail, calendar and tasks share one access token; refresh it
+            // once for all three.
+            email_count = match self.refresh_access_token(&account, profile) {
+                Ok(access_token) => {
+                    calendar_settings.access_token = Some(access_token.clone());
+                    task_settings.access_token = Some(access_token.clone());
+                    email_settings.access_token = Some(access_token);
+                    self.runtime.block_on(self.email.sync_recent_mail(
+                        &account,
+                        &email_settings,
+                        "INBOX",
+                        100,
+                    ))
+                }
+                Err(err) => Err(err),
+            };
+            self.refresh_account_reauth();
+            if self.account_reauth.iter().any(|item| item.account_id == account.id) {
+                self.status = format!(
+                    "{} needs you to sign in again before it can sync",
+                    account.email_address
+                );
+                return;
+            }
+        }
+        if email_count.is_ok() {
+            // Attendee replies to invitations arrive as ordinary mail.
+            let _ = self.runtime.block_on(
+                self.calendar
+                    .apply_imip_replies(&account, Utc::now() - Duration::days(7)),
+            );
+            if let Err(err) = self
+                .runtime
+                .block_on(self.email.auto_archive_muted(&account, &email_settings))
+            {
+                tracing::warn!("auto-archiving muted senders failed: {err}");
+            }
+            // One page of older mail per sync; the stored cursor carries on
+            // from there next time.
+            if let Err(err) = self.runtime.block_on(self.email.backfill_next(
+                &account,
+                &email_settings,
+                BACKFILL_PAGE_SIZE,
+            )) {
+                tracing::warn!("mail backfill failed: {err}");
+            }
+        }
+        let calendar_count = self.runtime.block_on(self.calendar.sync_range(
+            &account,
+            &calendar_settings,
+            Utc::now() - Duration::days(30),
+            Utc::now() + Duration::days(365),
+        ));
+        let task_count = self
+            .runtime
+            .block_on(self.tasks.sync_tasks(&account, &task_settings));
+        if let Ok(tasks) = &task_count {
+            // Tasks completed elsewhere still mark their time blocks here.
+            let blocked: HashSet<Uuid> = self
+                .runtime
+                .block_on(self.storage.list_task_time_blocks(account.id))
+                .unwrap_or_default()
+                .into_iter()
+                .map(|block| block.task_id)
+                .collect();
+            let tasks: Vec<_> = tasks
+                .iter()
+                .filter(|task| blocked.contains(&task.id))
+                .cloned()
+                .collect();
+            self.sync_time_block_completion(&account, &tasks);
+        }
+
+        match (email_count, calendar_count, task_count) {
+            (Ok(mail), Ok(calendar), Ok(tasks)) => {
+                self.status = format!(
+                    "Sync complete: {mail} emails, {} calendar events, {} tasks",
+                    calendar.len(),
+                    tasks.len()
+                );
+                self.load_folders(false);
+                self.load_threads();
+            }
+            (mail, calendar, tasks) => {
+                let err_msg = format!(
+                    "Sync failed: email={:?}, calendar={:?}, tasks={:?}",
+                    mail.err(),
+                    calendar.err(),
+                    tasks.err()
+                );
+                self.status = err_msg.clone();
+                self.notification_state.notify_sync_error(&self.config.notifications, "A sync operation failed. Please check your credentials or network.");
+            }
+        }
+    }
+
+    fn load_chat_contacts(&mut self) {
         let Some(account) = self.account().cloned() else {
             self.chat_contacts.clear();
             return;
@@ -713,12 +2219,10 @@ impl NativeApp {
             body_text: self.chat_compose_body.clone(),
             body_html: None,
             attachments: Vec::new(),
+            message_id: None,
         };
 
-        match self
-            .runtime
-            .block_on(self.email.send(&account, &settings, &outgoing))
-        {
+        match self.send_outgoing(&account, &settings, &outgoing) {
             Ok(()) => {
                 self.status = "Chat reply sent".to_string();
                 self.chat_compose_body.clear();
@@ -729,6 +2233,13 @@ impl NativeApp {
 
     fn search_mail(&mut self) {
         let query = self.mail_query.trim().to_string();
+        self.search_events.clear();
+        self.search_tasks.clear();
+        let draft = SearchQueryDraft::parse(&query);
+        if draft.targets_events() || draft.targets_tasks() {
+            self.search_everything(&query);
+            return;
+        }
         // Check for search operators.
         let has_operators = query.contains(':')
             && ["from:", "to:", "subject:", "has:", "is:", "before:", "after:", "label:"]
@@ -738,6 +2249,9 @@ impl NativeApp {
         if has_operators {
             self.search_with_operators(&query);
         } else {
+            if self.ranking_debug.is_some() {
+                self.refresh_ranking_debug(&query);
+            }
             match self.runtime.block_on(self.storage.search_mail(&query, 100)) {
                 Ok(result) => {
                     self.selected_thread = None;
@@ -750,11 +2264,262 @@ impl NativeApp {
         }
     }
 
-    fn search_with_operators(&mut self, query: &str) {
-        let Some(account_id) = self.selected_account else {
-            self.status = "No account selected for operator search".to_string();
-            return;
-        };
+    /// Search with calendar or task operators; mail hits fill the message
+    /// pane, events and tasks are listed under the search bar.
+    fn search_everything(&mut self, query: &str) {
+        match self.runtime.block_on(self.storage.search_all(query, 100)) {
+            Ok(results) => {
+                self.selected_thread = None;
+                self.selected_message = results.messages.last().map(|message| message.id);
+                self.status = format!(
+                    "Search returned {} message(s), {} event(s), {} task(s)",
+                    results.messages.len(),
+                    results.events.len(),
+                    results.tasks.len()
+                );
+                self.thread_messages = results.messages;
+                self.search_events = results.events;
+                self.search_tasks = results.tasks;
+            }
+            Err(err) => self.status = format!("search failed: {err}"),
+        }
+    }
+
+    fn refresh_ranking_debug(&mut self, query: &str) {
+        match self.runtime.block_on(self.storage.search_mail_ranked(query, 100)) {
+            Ok(ranked) => {
+                self.ranking_debug = Some(
+                    ranked
+                        .into_iter()
+                        .map(|(message, breakdown)| (message.subject, breakdown))
+                        .collect(),
+                );
+            }
+            Err(err) => self.status = format!("ranking debug failed: {err}"),
+        }
+    }
+
+    fn show_ranking_debug(&mut self, ctx: &egui::Context) {
+        let Some(hits) = &self.ranking_debug else {
+            return;
+        };
+        let mut weights = self.storage.ranking_weights();
+        let mut changed = false;
+        let mut open = true;
+        egui::Window::new("Search Ranking")
+            .open(&mut open)
+            .default_width(560.0)
+            .show(ctx, |ui| {
+                egui::Grid::new("ranking_weights").num_columns(2).show(ui, |ui| {
+                    ui.label("Relevance (BM25)");
+                    changed |= ui.add(egui::Slider::new(&mut weights.relevance, 0.0..=2.0)).changed();
+                    ui.end_row();
+                    ui.label("Recency");
+                    changed |= ui.add(egui::Slider::new(&mut weights.recency, 0.0..=2.0)).changed();
+                    ui.end_row();
+                    ui.label("Recency half-life (days)");
+                    changed |= ui
+                        .add(egui::Slider::new(&mut weights.half_life_days, 1.0..=365.0).logarithmic(true))
+                        .changed();
+                    ui.end_row();
+                    ui.label("Sender affinity");
+                    changed |= ui.add(egui::Slider::new(&mut weights.affinity, 0.0..=2.0)).changed();
+                    ui.end_row();
+                });
+                if ui.button("Reset to defaults").clicked() {
+                    weights = RankingWeights::default();
+                    changed = true;
+                }
+                ui.separator();
+
+                if hits.is_empty() {
+                    ui.label("Run a plain-text search to see score breakdowns.");
+                }
+                egui::ScrollArea::vertical().max_height(360.0).show(ui, |ui| {
+                    egui::Grid::new("ranking_hits").striped(true).num_columns(6).show(ui, |ui| {
+                        for header in ["Subject", "Score", "BM25", "Recency", "Affinity", "Replies"] {
+                            ui.strong(header);
+                        }
+                        ui.end_row();
+                        for (subject, rank) in hits {
+                            ui.add(egui::Label::new(subject.as_str()).truncate());
+                            ui.label(format!("{:.3}", rank.score));
+                            ui.label(format!("{:.2} ({:.2})", rank.relevance, rank.bm25));
+                            ui.label(format!("{:.3}", rank.recency));
+                            ui.label(format!("{:.3}", rank.affinity));
+                            ui.label(rank.replies_to_sender.to_string());
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+
+        if changed {
+            self.storage.set_ranking_weights(weights);
+            if !self.mail_query.trim().is_empty() {
+                self.search_mail();
+            }
+        }
+        if !open {
+            self.ranking_debug = None;
+        }
+    }
+
+    fn show_protocol_console(&mut self, ctx: &egui::Context) {
+        if !self.show_protocol_console {
+            return;
+        }
+        let log = ProtocolLog::global();
+        let mut open = true;
+        let mut export = false;
+        egui::Window::new("Protocol Console")
+            .open(&mut open)
+            .default_width(640.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let mut enabled = log.is_enabled();
+                    if ui.checkbox(&mut enabled, "Record").on_hover_text(
+                        "Keep recent IMAP commands and HTTP requests, without credentials, until restart",
+                    ).changed() {
+                        log.set_enabled(enabled);
+                    }
+                    let mut capacity = log.capacity();
+                    if ui.add(egui::DragValue::new(&mut capacity).range(10..=5000).suffix(" per account")).changed() {
+                        log.set_capacity(capacity);
+                    }
+                    egui::ComboBox::from_id_salt("protocol_console_account")
+                        .selected_text(self.protocol_console_account.as_deref().unwrap_or("All accounts"))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.protocol_console_account, None, "All accounts");
+                            for account in log.accounts() {
+                                let label = account.clone();
+                                ui.selectable_value(&mut self.protocol_console_account, Some(account), label);
+                            }
+                        });
+                    if ui.button("Clear").clicked() {
+                        log.clear();
+                    }
+                    export = ui.button("Export Support Bundle").clicked();
+                });
+                ui.separator();
+
+                let exchanges = log.exchanges(self.protocol_console_account.as_deref());
+                if exchanges.is_empty() {
+                    ui.label("Nothing recorded yet. Turn on Record and sync an account.");
+                }
+                egui::ScrollArea::vertical().max_height(420.0).stick_to_bottom(true).show(ui, |ui| {
+                    for exchange in &exchanges {
+                        let heading = format!(
+                            "{} · {} · {} · {}",
+                            exchange.started_at.with_timezone(&Local).format("%H:%M:%S%.3f"),
+                            exchange.protocol.label(),
+                            exchange.account,
+                            exchange.response.as_deref().unwrap_or("no response"),
+                        );
+                        egui::CollapsingHeader::new(egui::RichText::new(heading).monospace().size(11.0))
+                            .id_salt((exchange.account.as_str(), exchange.started_at, exchange.request.as_str()))
+                            .show(ui, |ui| {
+                                if let Some(duration) = exchange.duration_ms {
+                                    ui.label(format!("{duration} ms"));
+                                }
+                                ui.label(egui::RichText::new(&exchange.request).monospace().size(11.0));
+                            });
+                    }
+                });
+            });
+
+        if export {
+            self.export_support_bundle();
+        }
+        if !open {
+            self.show_protocol_console = false;
+        }
+    }
+
+    /// Save the protocol console's exchanges, with app version and OS, as
+    /// JSON to attach to a support request.
+    fn export_support_bundle(&mut self) {
+        let bundle = ProtocolLog::global().support_bundle(self.protocol_console_account.as_deref());
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name(format!("covemail-support-{}.json", Local::now().format("%Y%m%d-%H%M")))
+            .add_filter("JSON", &["json"])
+            .save_file()
+        else {
+            return;
+        };
+        let written = serde_json::to_vec_pretty(&bundle)
+            .map_err(|err| err.to_string())
+            .and_then(|json| std::fs::write(&path, json).map_err(|err| err.to_string()));
+        self.status = match written {
+            Ok(()) => format!("Support bundle with {} exchange(s) saved to {}", bundle.exchanges.len(), path.display()),
+            Err(err) => format!("Support bundle export failed: {err}"),
+        };
+    }
+
+    fn show_query_builder(&mut self, ctx: &egui::Context) {
+        let Some(builder) = self.query_builder.as_mut() else {
+            return;
+        };
+        let mut open = true;
+        let mut apply = false;
+        egui::Window::new("Query Builder")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                egui::Grid::new("query_builder_grid").num_columns(2).show(ui, |ui| {
+                    for (label, value) in [
+                        ("From", &mut builder.draft.from),
+                        ("To", &mut builder.draft.to),
+                        ("Subject", &mut builder.draft.subject),
+                        ("Label", &mut builder.draft.label),
+                        ("Attendee", &mut builder.draft.attendee),
+                        ("Words", &mut builder.draft.text),
+                    ] {
+                        ui.label(label);
+                        ui.text_edit_singleline(value);
+                        ui.end_row();
+                    }
+                    ui.label("After");
+                    ui.add(egui::TextEdit::singleline(&mut builder.after).hint_text("YYYY-MM-DD"));
+                    ui.end_row();
+                    ui.label("Before");
+                    ui.add(egui::TextEdit::singleline(&mut builder.before).hint_text("YYYY-MM-DD"));
+                    ui.end_row();
+                    ui.label("Event on");
+                    ui.add(egui::TextEdit::singleline(&mut builder.on).hint_text("YYYY-MM-DD"));
+                    ui.end_row();
+                    ui.label("Task due");
+                    ui.add(egui::TextEdit::singleline(&mut builder.due).hint_text("YYYY-MM-DD"));
+                    ui.end_row();
+                });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut builder.draft.has_attachment, "Has attachment");
+                    ui.checkbox(&mut builder.draft.unread, "Unread");
+                    ui.checkbox(&mut builder.draft.pinned, "Pinned");
+                });
+                ui.add_space(6.0);
+                ui.label(egui::RichText::new(builder.to_query()).monospace().weak());
+                if ui.button("Apply").clicked() {
+                    apply = true;
+                }
+            });
+
+        if apply {
+            self.mail_query = builder.to_query();
+            self.query_builder = None;
+            self.search_mail();
+        } else if !open {
+            self.query_builder = None;
+        }
+    }
+
+    fn search_with_operators(&mut self, query: &str) {
+        let Some(account_id) = self.selected_account else {
+            self.status = "No account selected for operator search".to_string();
+            return;
+        };
 
         // Load a wide set of messages and filter client-side.
         let all_messages = match self.runtime.block_on(
@@ -789,6 +2554,12 @@ impl NativeApp {
                 results.retain(|m| !m.flags.seen);
             } else if lower == "is:pinned" {
                 results.retain(|m| m.pinned);
+            } else if lower == "is:vip" {
+                results.retain(|m| {
+                    m.from
+                        .iter()
+                        .any(|a| self.vip_addresses.contains(&a.address.to_lowercase()))
+                });
             } else if let Some(val) = lower.strip_prefix("label:") {
                 results.retain(|m| m.labels.iter().any(|l| l.to_lowercase().contains(val)));
             } else if let Some(val) = lower.strip_prefix("before:") {
@@ -808,139 +2579,1344 @@ impl NativeApp {
                     }
                 }
             }
-            // Plain text tokens: match against subject/preview.
-            else {
-                let val = token.to_lowercase();
-                results.retain(|m| {
-                    m.subject.to_lowercase().contains(&val)
-                        || m.preview.to_lowercase().contains(&val)
-                });
+            // Plain text tokens: match against subject/preview.
+            else {
+                let val = token.to_lowercase();
+                results.retain(|m| {
+                    m.subject.to_lowercase().contains(&val)
+                        || m.preview.to_lowercase().contains(&val)
+                });
+            }
+        }
+
+        self.selected_thread = None;
+        self.selected_message = results.last().map(|m| m.id);
+        self.thread_messages = results;
+        self.status = format!("Operator search: {} result(s)", self.thread_messages.len());
+    }
+
+    fn send_compose(&mut self) {
+        let account = match &self.compose_from {
+            Some(from) => self.accounts.iter().find(|account| account.id == from.account_id),
+            None => self.account(),
+        };
+        let Some(account) = account.cloned() else {
+            self.status = "No account selected".to_string();
+            return;
+        };
+        let from_address = self
+            .compose_from
+            .as_ref()
+            .map(|from| from.address.clone())
+            .unwrap_or_else(|| account.email_address.clone());
+
+        let mut settings = match self.load_email_settings(account.id) {
+            Ok(settings) => settings,
+            Err(err) => {
+                self.status = err;
+                return;
+            }
+        };
+        hydrate_email_secrets(account.id, &self.secrets, &mut settings);
+
+        let mut attachments = Vec::new();
+        for path in &self.attachment_paths {
+            let file_path = Path::new(path);
+            let Ok(bytes) = std::fs::read(file_path) else {
+                continue;
+            };
+            let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
+            let file_name = file_path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("attachment.bin")
+                .to_string();
+            attachments.push(OutgoingAttachment {
+                file_name,
+                mime_type: "application/octet-stream".to_string(),
+                content_base64: encoded,
+                inline: false,
+            });
+        }
+
+        let (to, cc, bcc) = match self.compose_recipients.addresses() {
+            Ok(addresses) => addresses,
+            Err(err) => {
+                self.status = format!("Fix recipients before sending: {err}");
+                return;
+            }
+        };
+
+        if to.is_empty() && cc.is_empty() && bcc.is_empty() {
+            self.status = "Compose requires at least one recipient".to_string();
+            return;
+        }
+
+        let outgoing = OutgoingMail {
+            from: MailAddress {
+                name: Some(account.display_name.clone()),
+                address: from_address,
+            },
+            to,
+            cc,
+            bcc,
+            reply_to: Vec::new(),
+            subject: self.compose_subject.clone(),
+            body_text: self.compose_body.clone(),
+            body_html: None,
+            attachments,
+            message_id: None,
+        };
+
+        let deferred_until = next_working_time(&self.config.send_guardrails, &chrono::Local::now())
+            .filter(|_| !self.compose_send_anyway);
+        if let Some(send_at) = deferred_until {
+            match self
+                .runtime
+                .block_on(self.email.schedule_outgoing(account.id, &outgoing, send_at))
+            {
+                Ok(_) => {
+                    self.status = format!(
+                        "Outside working hours: scheduled for {}",
+                        send_at.with_timezone(&chrono::Local).format("%a %b %d %H:%M")
+                    );
+                    self.compose_recipients.clear();
+                    self.compose_subject.clear();
+                    self.compose_body.clear();
+                    self.attachment_paths.clear();
+                    self.compose_send_anyway = false;
+                    self.compose_from = None;
+                    self.compose_reply = None;
+                }
+                Err(err) => self.status = format!("schedule failed: {err}"),
+            }
+            return;
+        }
+
+        self.undo_send_message = Some((
+            account.clone(),
+            settings.clone(),
+            outgoing,
+            std::time::Instant::now()
+        ));
+        self.status = "Draft ready to send (Undo available for 5s)".to_string();
+        self.compose_recipients.clear();
+        self.compose_subject.clear();
+        self.compose_body.clear();
+        self.attachment_paths.clear();
+        self.compose_send_anyway = false;
+        self.compose_from = None;
+        self.compose_reply = None;
+    }
+
+    /// Open compose as a reply to `message`, sending from the identity the
+    /// message was delivered to.
+    fn start_reply(&mut self, message: &MailMessage) {
+        let sender = message
+            .from
+            .first()
+            .map(|address| address.address.clone())
+            .unwrap_or_default();
+        self.compose_subject = if message.subject.to_lowercase().starts_with("re:") {
+            message.subject.clone()
+        } else {
+            format!("Re: {}", message.subject)
+        };
+        self.compose_recipients.to.set(&sender);
+        self.send_identities = self
+            .runtime
+            .block_on(self.email.send_identities())
+            .unwrap_or_default();
+        match self.runtime.block_on(self.email.reply_identity(message.id)) {
+            Ok(reply) => {
+                self.compose_from = Some(reply.suggested.clone());
+                self.compose_reply = Some(reply);
+            }
+            Err(err) => {
+                self.compose_from = None;
+                self.compose_reply = None;
+                self.status = format!("Reply identity unavailable: {err}");
+            }
+        }
+        self.show_compose_window = true;
+    }
+
+    /// Send scheduled and deferred mail that is due, staying within each
+    /// account's send throttle.
+    fn process_scheduled_messages(&mut self) {
+        let Ok(due_messages) = self.runtime.block_on(self.storage.due_scheduled_messages()) else {
+            return;
+        };
+
+        let mut budgets: HashMap<Uuid, usize> = HashMap::new();
+        for msg in due_messages {
+            let Some(account) = self.accounts.iter().find(|a| a.id == msg.account_id).cloned() else {
+                continue;
+            };
+            let budget = match budgets.get(&account.id) {
+                Some(budget) => *budget,
+                None => self
+                    .runtime
+                    .block_on(self.email.outbox_budget(account.id, &self.config.send_guardrails))
+                    .unwrap_or(0),
+            };
+            budgets.insert(account.id, budget);
+            if budget == 0 {
+                continue;
+            }
+
+            let mut settings = match self.load_email_settings(account.id) {
+                Ok(settings) => settings,
+                Err(_) => continue,
+            };
+            hydrate_email_secrets(account.id, &self.secrets, &mut settings);
+
+            let fallback_from = MailAddress {
+                name: Some(account.display_name.clone()),
+                address: account.email_address.clone(),
+            };
+            let outgoing = match self
+                .runtime
+                .block_on(self.email.scheduled_outgoing(&msg, fallback_from))
+            {
+                Ok(outgoing) => outgoing,
+                Err(err) => {
+                    self.status = format!("scheduled message \"{}\" failed: {err}", msg.subject);
+                    continue;
+                }
+            };
+
+            if self.send_outgoing(&account, &settings, &outgoing).is_ok() {
+                let _ = self.runtime.block_on(self.email.finish_scheduled(&msg));
+                budgets.insert(account.id, budget - 1);
+                self.status = "Scheduled message sent".to_string();
+            }
+        }
+    }
+
+    fn queue_mail_merge(&mut self) {
+        let Some(draft) = self.mail_merge.as_ref() else {
+            return;
+        };
+        let Some(account_id) = self.selected_account else {
+            self.status = "Select the account to send the mail merge from".to_string();
+            return;
+        };
+        match self.runtime.block_on(self.email.create_mail_merge(
+            account_id,
+            &draft.template,
+            &draft.csv,
+            draft.per_minute,
+        )) {
+            Ok(campaign) => {
+                self.status = format!(
+                    "Mail merge \"{}\" queued at {} per minute",
+                    campaign.name, campaign.per_minute
+                );
+                self.mail_merge = None;
+            }
+            Err(err) => self.status = format!("mail merge failed: {err}"),
+        }
+    }
+
+    /// Send the next throttled batch of every mail merge with recipients
+    /// still pending.
+    fn process_mail_merges(&mut self) {
+        let Ok(campaigns) = self.runtime.block_on(self.storage.list_mail_merges(None)) else {
+            return;
+        };
+
+        for campaign in campaigns {
+            let Ok(mut batch) = self.runtime.block_on(self.email.next_mail_merge_batch(&campaign))
+            else {
+                continue;
+            };
+            let budget = self
+                .runtime
+                .block_on(
+                    self.email
+                        .outbox_budget(campaign.account_id, &self.config.send_guardrails),
+                )
+                .unwrap_or(0);
+            batch.truncate(budget);
+            if batch.is_empty() {
+                continue;
+            }
+            let Some(account) = self.accounts.iter().find(|a| a.id == campaign.account_id).cloned() else {
+                continue;
+            };
+            let mut settings = match self.load_email_settings(account.id) {
+                Ok(settings) => settings,
+                Err(_) => continue,
+            };
+            hydrate_email_secrets(account.id, &self.secrets, &mut settings);
+            let from = MailAddress {
+                name: Some(account.display_name.clone()),
+                address: account.email_address.clone(),
+            };
+
+            let mut sent = 0;
+            for recipient in batch {
+                let result = EmailService::render_mail_merge(&campaign, &recipient, from.clone())
+                    .map_err(|err| err.to_string())
+                    .and_then(|outgoing| self.send_outgoing(&account, &settings, &outgoing));
+                if result.is_ok() {
+                    sent += 1;
+                }
+                let _ = self
+                    .runtime
+                    .block_on(self.email.record_mail_merge_result(recipient.id, result));
+            }
+            if sent > 0 {
+                self.status = format!("Mail merge \"{}\": sent {sent} message(s)", campaign.name);
+            }
+        }
+    }
+
+    fn set_task_completed(&mut self, mut task: cove_core::ReminderTask, done: bool) {
+        let Some(account) = self.accounts.iter().find(|a| a.id == task.account_id).cloned() else {
+            return;
+        };
+        let mut settings = match self.load_task_settings(account.id) {
+            Ok(settings) => settings,
+            Err(err) => {
+                self.status = err;
+                return;
+            }
+        };
+        hydrate_task_secrets(account.id, &self.secrets, &mut settings);
+
+        let now = Utc::now();
+        task.completed_at = done.then_some(now);
+        task.status = if done {
+            cove_core::TaskStatus::Completed
+        } else {
+            cove_core::TaskStatus::NotStarted
+        };
+        task.updated_at = now;
+
+        match self.runtime.block_on(self.tasks.upsert_task(&account, &settings, &task)) {
+            Ok(()) => self.status = format!("Task updated: {}", task.title),
+            Err(err) => {
+                self.status = format!("task update failed: {err}");
+                return;
+            }
+        }
+        self.sync_time_block_completion(&account, &[task]);
+    }
+
+    /// Mark the time blocks of completed tasks in the calendar, and unmark
+    /// the blocks of tasks that were reopened.
+    fn sync_time_block_completion(&self, account: &Account, tasks: &[cove_core::ReminderTask]) {
+        let mut settings = match self.load_calendar_settings(account.id) {
+            Ok(settings) => settings,
+            Err(err) => {
+                tracing::warn!("time block update skipped: {err}");
+                return;
+            }
+        };
+        hydrate_calendar_secrets(account.id, &self.secrets, &mut settings);
+
+        for task in tasks {
+            if let Err(err) = self.runtime.block_on(
+                self.calendar.sync_time_block_completion(account, &settings, task),
+            ) {
+                tracing::warn!("time block update for \"{}\" failed: {err}", task.title);
+            }
+        }
+    }
+
+    /// Expand a snippet abbreviation the user just finished typing in the
+    /// compose body and move the caret to the snippet's cursor marker.
+    fn expand_compose_snippet(&mut self, ctx: &egui::Context, mut output: egui::text_edit::TextEditOutput) {
+        let Some(cursor) = output.cursor_range.map(|range| range.primary.ccursor.index) else {
+            return;
+        };
+        if self.snippets.is_none() {
+            self.snippets = Some(self.runtime.block_on(self.storage.list_snippets()).unwrap_or_default());
+        }
+        let snippets = self.snippets.as_deref().unwrap_or_default();
+        let Some(expansion) = expand_snippet_at(&self.compose_body, cursor, snippets) else {
+            return;
+        };
+
+        self.compose_body = expansion.text;
+        output.state.cursor.set_char_range(Some(egui::text::CCursorRange::one(
+            egui::text::CCursor::new(expansion.cursor),
+        )));
+        output.state.store(ctx, output.response.id);
+    }
+
+    /// Right-click on a misspelled word in the compose body offers
+    /// suggestions and "Add to dictionary".
+    fn compose_spelling_menu(&mut self, output: &egui::text_edit::TextEditOutput) {
+        let Some(checker) = self.spellchecker.as_ref().filter(|checker| checker.is_available()) else {
+            return;
+        };
+        if output.response.secondary_clicked() {
+            self.spell_menu_word = output.response.interact_pointer_pos().and_then(|pos| {
+                let char_index = output.galley.cursor_from_pos(pos - output.galley_pos).ccursor.index;
+                let byte_index = self
+                    .compose_body
+                    .char_indices()
+                    .nth(char_index)
+                    .map_or(self.compose_body.len(), |(index, _)| index);
+                let range = spellcheck::word_at(&self.compose_body, byte_index)?;
+                let word = self.compose_body[range.clone()].to_string();
+                (!checker.is_correct(&word)).then_some((range, word))
+            });
+        }
+
+        output.response.context_menu(|ui| {
+            let Some((range, word)) = self.spell_menu_word.clone() else {
+                ui.close_menu();
+                return;
+            };
+            let Some(checker) = self.spellchecker.as_mut() else {
+                return;
+            };
+            let suggestions = checker.suggestions(&word);
+            if suggestions.is_empty() {
+                ui.label(egui::RichText::new("No suggestions").italics());
+            }
+            let mut replacement = None;
+            for suggestion in suggestions {
+                if ui.button(&suggestion).clicked() {
+                    replacement = Some(suggestion);
+                }
+            }
+            ui.separator();
+            if ui.button("Add to dictionary").clicked() {
+                if let Err(err) = checker.add_word(&word) {
+                    self.status = format!("save custom dictionary failed: {err}");
+                }
+                self.spell_menu_word = None;
+                ui.close_menu();
+            }
+            if let Some(replacement) = replacement {
+                // The body may have changed since the menu opened.
+                if self.compose_body.get(range.clone()) == Some(word.as_str()) {
+                    self.compose_body.replace_range(range, &replacement);
+                }
+                self.spell_menu_word = None;
+                ui.close_menu();
+            }
+        });
+    }
+
+    fn save_snippet(&mut self) {
+        let abbreviation = self.snippet_abbreviation.trim().to_string();
+        if !is_valid_abbreviation(&abbreviation) || self.snippet_body.is_empty() {
+            self.status = "A snippet needs a one-word abbreviation and a body".to_string();
+            return;
+        }
+
+        let snippet = cove_core::TextSnippet {
+            id: Uuid::new_v4(),
+            abbreviation,
+            body: self.snippet_body.clone(),
+            updated_at: Utc::now(),
+        };
+        match self.runtime.block_on(self.storage.upsert_snippet(&snippet)) {
+            Ok(()) => {
+                self.status = format!("Saved snippet {}", snippet.abbreviation);
+                self.snippet_abbreviation.clear();
+                self.snippet_body.clear();
+                self.snippets = None;
+            }
+            Err(err) => self.status = format!("save snippet failed: {err}"),
+        }
+    }
+
+    fn task_account_settings(&mut self, account_id: Uuid) -> Option<(Account, TaskSettings)> {
+        let account = self.accounts.iter().find(|a| a.id == account_id).cloned()?;
+        let mut settings = match self.load_task_settings(account.id) {
+            Ok(settings) => settings,
+            Err(err) => {
+                self.status = err;
+                return None;
+            }
+        };
+        hydrate_task_secrets(account.id, &self.secrets, &mut settings);
+        Some((account, settings))
+    }
+
+    fn refresh_task_lists(&mut self, account_id: Uuid) {
+        let Some((account, settings)) = self.task_account_settings(account_id) else {
+            return;
+        };
+        match self.runtime.block_on(self.tasks.discover_task_lists(&account, &settings)) {
+            Ok(lists) => self.status = format!("Found {} task lists", lists.len()),
+            Err(err) => self.status = format!("task list refresh failed: {err}"),
+        }
+    }
+
+    fn create_task_list(&mut self, account_id: Uuid) {
+        let name = std::mem::take(&mut self.new_task_list_name);
+        let Some((account, settings)) = self.task_account_settings(account_id) else {
+            return;
+        };
+        match self.runtime.block_on(self.tasks.create_task_list(&account, &settings, &name)) {
+            Ok(list) => {
+                self.status = format!("Created list {}", list.name);
+                self.selected_task_list = Some(list.list_id);
+            }
+            Err(err) => {
+                self.new_task_list_name = name;
+                self.status = format!("create list failed: {err}");
+            }
+        }
+    }
+
+    fn rename_task_list(&mut self, account_id: Uuid, list_id: &str, name: &str) {
+        let Some((account, settings)) = self.task_account_settings(account_id) else {
+            return;
+        };
+        match self
+            .runtime
+            .block_on(self.tasks.rename_task_list(&account, &settings, list_id, name))
+        {
+            Ok(list) => self.status = format!("Renamed list to {}", list.name),
+            Err(err) => self.status = format!("rename list failed: {err}"),
+        }
+    }
+
+    fn delete_task_list(&mut self, account_id: Uuid, list_id: &str) {
+        let Some((account, settings)) = self.task_account_settings(account_id) else {
+            return;
+        };
+        match self
+            .runtime
+            .block_on(self.tasks.delete_task_list(&account, &settings, list_id))
+        {
+            Ok(()) => {
+                self.status = "Task list deleted".to_string();
+                if self.selected_task_list.as_deref() == Some(list_id) {
+                    self.selected_task_list = None;
+                }
+            }
+            Err(err) => self.status = format!("delete list failed: {err}"),
+        }
+    }
+
+    fn move_task_to_list(&mut self, task: &cove_core::ReminderTask, list_id: &str) {
+        let Some((account, settings)) = self.task_account_settings(task.account_id) else {
+            return;
+        };
+        match self
+            .runtime
+            .block_on(self.tasks.move_task(&account, &settings, task, list_id))
+        {
+            Ok(moved) => {
+                self.status = format!(
+                    "Moved \"{}\" to {}",
+                    moved.title,
+                    moved.list_name.as_deref().unwrap_or(&moved.list_id)
+                )
+            }
+            Err(err) => self.status = format!("move task failed: {err}"),
+        }
+    }
+
+    fn time_block_task(&mut self, task: &cove_core::ReminderTask) {
+        let Some(account) = self.accounts.iter().find(|a| a.id == task.account_id).cloned() else {
+            return;
+        };
+        let mut settings = match self.load_calendar_settings(account.id) {
+            Ok(settings) => settings,
+            Err(err) => {
+                self.status = err;
+                return;
+            }
+        };
+        hydrate_calendar_secrets(account.id, &self.secrets, &mut settings);
+
+        // Start at the next full hour.
+        let now = Utc::now();
+        let starts_at = now
+            .with_minute(0)
+            .and_then(|dt| dt.with_second(0))
+            .and_then(|dt| dt.with_nanosecond(0))
+            .unwrap_or(now)
+            + Duration::hours(1);
+
+        match self.runtime.block_on(
+            self.calendar.time_block_task(&account, &settings, task, starts_at),
+        ) {
+            Ok(event) => {
+                self.status = format!(
+                    "Blocked {} for \"{}\"",
+                    event.starts_at.with_timezone(&chrono::Local).format("%b %d %H:%M"),
+                    task.title
+                )
+            }
+            Err(err) => self.status = format!("time block failed: {err}"),
+        }
+    }
+
+    /// Add an agenda drawn from recent threads with the poll participants
+    /// to the poll note, which becomes the event description.
+    fn suggest_poll_agenda(&mut self, account_id: Uuid) {
+        let attendees: Vec<String> = self
+            .poll_draft
+            .participants
+            .split([',', ';', '\n'])
+            .map(|address| address.trim().to_string())
+            .filter(|address| !address.is_empty())
+            .collect();
+        if attendees.is_empty() {
+            self.status = "Add participants to suggest an agenda.".to_string();
+            return;
+        }
+        let since = Utc::now() - Duration::days(AGENDA_LOOKBACK_DAYS);
+        let messages = match self.runtime.block_on(self.storage.messages_with_participants(
+            account_id,
+            &attendees,
+            since,
+            AGENDA_SCAN_LIMIT,
+        )) {
+            Ok(messages) => messages,
+            Err(err) => {
+                self.status = format!("agenda failed: {err}");
+                return;
+            }
+        };
+        let own_addresses: Vec<String> = self.accounts.iter().map(|account| account.email_address.clone()).collect();
+        let threads = DigestThread::group(messages);
+        let agenda = if self.poll_draft.agenda_with_ai {
+            match self.runtime.block_on(self.ai.suggest_meeting_agenda(
+                account_id,
+                &self.poll_draft.title,
+                &threads,
+                &own_addresses,
+                self.ai_mode.clone(),
+                self.ai_cloud_provider.clone(),
+            )) {
+                Ok(agenda) => agenda,
+                Err(err) => {
+                    self.status = format!("AI agenda failed: {err}");
+                    return;
+                }
+            }
+        } else {
+            draft_meeting_agenda(&threads, &own_addresses)
+        };
+        if agenda.items.is_empty() {
+            self.status = format!("No open threads with these participants in the last {AGENDA_LOOKBACK_DAYS} days.");
+            return;
+        }
+        let description = &mut self.poll_draft.description;
+        if !description.trim().is_empty() {
+            description.push_str("\n\n");
+        }
+        description.push_str(&agenda.markdown);
+        self.status = format!("Suggested {} agenda item(s) from {} thread(s)", agenda.items.len(), agenda.sources.len());
+    }
+
+    fn create_scheduling_poll(&mut self, account_id: Uuid) {
+        let Some(account) = self.accounts.iter().find(|a| a.id == account_id).cloned() else {
+            return;
+        };
+        let options = match parse_poll_slots(&self.poll_draft.slots, self.poll_draft.duration_minutes) {
+            Ok(options) => options,
+            Err(err) => {
+                self.status = err;
+                return;
+            }
+        };
+        let request = PollRequest {
+            title: self.poll_draft.title.clone(),
+            description: Some(self.poll_draft.description.clone()),
+            options,
+            participants: self
+                .poll_draft
+                .participants
+                .split([',', ';', '\n'])
+                .map(str::to_string)
+                .collect(),
+        };
+        let (poll, email) = match self.runtime.block_on(self.calendar.create_poll(&account, request)) {
+            Ok(created) => created,
+            Err(err) => {
+                self.status = format!("poll failed: {err}");
+                return;
+            }
+        };
+        match self.send_account_mail(&account, &email.recipients, &email.subject, &email.body_text, Vec::new()) {
+            Ok(()) => {
+                self.status = format!("Sent {} proposed times to {} people", poll.options.len(), poll.participants.len());
+                self.poll_draft = PollDraft::default();
+            }
+            Err(err) => self.status = format!("poll saved but the email was not sent: {err}"),
+        }
+    }
+
+    fn refresh_scheduling_poll(&mut self, account_id: Uuid, poll_id: Uuid) {
+        let Some(account) = self.accounts.iter().find(|a| a.id == account_id).cloned() else {
+            return;
+        };
+        match self.runtime.block_on(self.calendar.collect_poll_votes(&account, poll_id)) {
+            Ok(votes) => self.status = format!("{} answer(s) so far", votes.len()),
+            Err(err) => self.status = format!("checking replies failed: {err}"),
+        }
+    }
+
+    fn confirm_scheduling_poll(&mut self, account_id: Uuid, poll_id: Uuid, option_index: usize) {
+        let Some(account) = self.accounts.iter().find(|a| a.id == account_id).cloned() else {
+            return;
+        };
+        let mut settings = match self.load_calendar_settings(account.id) {
+            Ok(settings) => settings,
+            Err(err) => {
+                self.status = format!("confirm failed: {err}");
+                return;
+            }
+        };
+        hydrate_calendar_secrets(account.id, &self.secrets, &mut settings);
+
+        let (_, event, email) = match self.runtime.block_on(
+            self.calendar.confirm_poll(&account, &settings, poll_id, option_index),
+        ) {
+            Ok(confirmed) => confirmed,
+            Err(err) => {
+                self.status = format!("confirm failed: {err}");
+                return;
+            }
+        };
+        let mut sent = self.send_account_mail(&account, &email.recipients, &email.subject, &email.body_text, Vec::new());
+        if let (Ok(()), Some(invitation)) = (&sent, self.calendar.imip_invitation(&account, &event)) {
+            let attachment = OutgoingAttachment::from_bytes("invite.ics", IMIP_REQUEST_MIME, invitation.ics.as_bytes());
+            sent = self.send_account_mail(&account, &invitation.recipients, &invitation.subject, &invitation.body_text, vec![attachment]);
+        }
+        self.status = match sent {
+            Ok(()) => format!(
+                "\"{}\" booked for {}",
+                event.title,
+                event.starts_at.with_timezone(&chrono::Local).format("%b %d %H:%M")
+            ),
+            Err(err) => format!("meeting created but participants were not told: {err}"),
+        };
+    }
+
+    /// Send a plain-text message from `account`.
+    fn send_account_mail(
+        &self,
+        account: &Account,
+        recipients: &[String],
+        subject: &str,
+        body_text: &str,
+        attachments: Vec<OutgoingAttachment>,
+    ) -> Result<(), String> {
+        let mut settings = self.load_email_settings(account.id)?;
+        hydrate_email_secrets(account.id, &self.secrets, &mut settings);
+        let outgoing = OutgoingMail {
+            from: MailAddress {
+                name: Some(account.display_name.clone()),
+                address: account.email_address.clone(),
+            },
+            to: recipients
+                .iter()
+                .map(|address| MailAddress {
+                    name: None,
+                    address: address.clone(),
+                })
+                .collect(),
+            cc: Vec::new(),
+            bcc: Vec::new(),
+            reply_to: Vec::new(),
+            subject: subject.to_string(),
+            body_text: body_text.to_string(),
+            body_html: None,
+            attachments,
+            message_id: None,
+        };
+        self.send_outgoing(account, &settings, &outgoing)
+    }
+
+    fn export_messages_json(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name("messages.ndjson")
+            .add_filter("NDJSON", &["ndjson", "jsonl"])
+            .save_file()
+        else {
+            return;
+        };
+        let filter = cove_core::MessageExportFilter {
+            account_id: self.selected_account.filter(|_| self.message_export_selected_account),
+            redaction: self.message_export_redaction,
+            ..Default::default()
+        };
+        match self.runtime.block_on(self.storage.export_messages_json(&filter, &path)) {
+            Ok(count) => {
+                self.status = format!("Exported {} messages to {}", group_thousands(count), path.display());
+            }
+            Err(err) => self.status = format!("Message export failed: {err}"),
+        }
+    }
+
+    /// The account with its protocol settings and, when `with_secrets`,
+    /// the passwords and tokens stored for it.
+    fn gather_account_export(&self, account: &Account, with_secrets: bool) -> Result<export::AccountExport, cove_storage::StorageError> {
+        let protocol_settings_json = self.runtime.block_on(self.storage.account_protocol_settings(account.id))?;
+        let mut secrets = BTreeMap::new();
+        // Best effort extraction of associated secrets.
+        for ns in ACCOUNT_SECRET_NAMESPACES.iter().filter(|_| with_secrets) {
+            let key = SecretKey {
+                namespace: ns.to_string(),
+                id: account.id.to_string(),
+            };
+            if let Ok(Some(secret_val)) = self.secrets.get(&key) {
+                secrets.insert(ns.to_string(), secret_val);
+            }
+        }
+        Ok(export::AccountExport {
+            account: account.clone(),
+            protocol_settings_json,
+            secrets,
+        })
+    }
+
+    /// Store an imported account with its protocol settings and secrets.
+    fn restore_account_export(&self, acc_export: export::AccountExport) -> Result<(), String> {
+        let account_id = acc_export.account.id;
+        self.runtime
+            .block_on(self.storage.upsert_account(&acc_export.account))
+            .map_err(|err| format!("Failed to import account {account_id}: {err}"))?;
+        if let Some(settings_json) = acc_export.protocol_settings_json {
+            self.runtime
+                .block_on(self.storage.upsert_account_protocol_settings(account_id, &settings_json))
+                .map_err(|err| format!("Failed to import settings for account {account_id}: {err}"))?;
+        }
+        for (ns, val) in acc_export.secrets {
+            let key = SecretKey {
+                namespace: ns,
+                id: account_id.to_string(),
+            };
+            set_secret_guarded(&self.secrets, key, &val)
+                .map_err(|err| format!("Failed to import secret for account {account_id}: {err}"))?;
+        }
+        Ok(())
+    }
+
+    fn export_selected_account(&mut self) {
+        let Some(account) = self.selected_account.and_then(|id| self.accounts.iter().find(|a| a.id == id)).cloned() else {
+            return;
+        };
+        if self.export_password.is_empty() {
+            self.status = "Export password cannot be empty.".to_string();
+            return;
+        }
+        let mail_since = match self.account_export_since.trim() {
+            _ if !self.account_export_mail => None,
+            "" => None,
+            text => match chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .and_then(|naive| chrono::TimeZone::from_local_datetime(&Local, &naive).earliest())
+            {
+                Some(local) => Some(local.with_timezone(&Utc)),
+                None => {
+                    self.status = format!("Not a date: {text}");
+                    return;
+                }
+            },
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name(format!("{}.age", account.email_address))
+            .add_filter("Age Encrypted Backup", &["age"])
+            .save_file()
+        else {
+            return;
+        };
+
+        let gathered = self.gather_account_export(&account, self.account_export_secrets).and_then(|exported| {
+            let signatures = self
+                .runtime
+                .block_on(self.storage.list_signatures(Some(account.id)))?
+                .into_iter()
+                .filter(|signature| signature.account_id == Some(account.id))
+                .collect();
+            let mail = if self.account_export_mail {
+                self.runtime.block_on(self.storage.list_account_messages(account.id, mail_since))?
+            } else {
+                Vec::new()
+            };
+            Ok(export::AccountBundle {
+                account: exported,
+                ai_policy: self.config.ai.account_policy(&account.id.to_string()),
+                signatures,
+                mail_since,
+                mail,
+            })
+        });
+        let bundle = match gathered {
+            Ok(bundle) => bundle,
+            Err(err) => {
+                self.status = format!("Export error: {err}");
+                return;
+            }
+        };
+        match export::export_account(&bundle, &self.export_password, &path) {
+            Ok(()) => {
+                self.status = format!(
+                    "Exported {} with {} message(s) to {}",
+                    account.email_address,
+                    group_thousands(bundle.mail.len() as u64),
+                    path.display()
+                );
+                self.export_password.clear();
+            }
+            Err(err) => self.status = format!("Export failed: {err}"),
+        }
+    }
+
+    /// Add one exported account, leaving every other account and the
+    /// app settings as they are.
+    fn import_account_bundle(&mut self) {
+        if self.import_password.is_empty() {
+            self.status = "Import password cannot be empty.".to_string();
+            return;
+        }
+        let Some(path) = rfd::FileDialog::new().add_filter("Age Encrypted Backup", &["age"]).pick_file() else {
+            return;
+        };
+        let bundle = match export::import_account(&self.import_password, &path) {
+            Ok(bundle) => bundle,
+            Err(err) => {
+                self.status = format!("Import failed: {err}");
+                return;
+            }
+        };
+        let account_id = bundle.account.account.id;
+        let email_address = bundle.account.account.email_address.clone();
+        if let Err(err) = self.restore_account_export(bundle.account) {
+            self.status = err;
+            return;
+        }
+        for signature in &bundle.signatures {
+            if let Err(err) = self.runtime.block_on(self.storage.upsert_signature(signature)) {
+                self.status = format!("Failed to import signature {}: {err}", signature.name);
+                return;
+            }
+        }
+        if let Err(err) = self.runtime.block_on(self.storage.upsert_mail_messages(&bundle.mail)) {
+            self.status = format!("Failed to import mail for {email_address}: {err}");
+            return;
+        }
+        self.config.ai.set_account_policy(&account_id.to_string(), bundle.ai_policy);
+        self.ai.update_config(ai_runtime_from_config(&self.config));
+        if let Err(err) = self.config_manager.save(&self.config) {
+            self.status = format!("save config failed: {err}");
+            return;
+        }
+        self.status = format!(
+            "Imported {email_address} with {} message(s) from {}",
+            group_thousands(bundle.mail.len() as u64),
+            path.display()
+        );
+        self.import_password.clear();
+        self.reload_accounts();
+    }
+
+    /// Last week's analytics, shown in the Analytics view.
+    fn refresh_analytics(&mut self) {
+        let until = Utc::now();
+        let top = self.config.weekly_report.top_correspondents as usize;
+        match self
+            .runtime
+            .block_on(self.storage.mail_analytics(until - Duration::days(7), until, top))
+        {
+            Ok(analytics) => self.analytics = Some(analytics),
+            Err(err) => self.status = format!("load analytics failed: {err}"),
+        }
+    }
+
+    fn export_analytics(&mut self, format: AnalyticsFormat) {
+        let Some(analytics) = &self.analytics else {
+            return;
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name(format!(
+                "cove-mail-analytics-{}.{}",
+                chrono::Local::now().format("%Y-%m-%d"),
+                format.extension()
+            ))
+            .add_filter(format.extension().to_uppercase(), &[format.extension()])
+            .save_file()
+        else {
+            return;
+        };
+        let result = render_analytics(analytics, format)
+            .map_err(|err| err.to_string())
+            .and_then(|rendered| std::fs::write(&path, rendered).map_err(|err| err.to_string()));
+        self.status = match result {
+            Ok(()) => format!("Analytics exported to {}", path.display()),
+            Err(err) => format!("Analytics export failed: {err}"),
+        };
+    }
+
+    /// Mail last week's analytics to the first real account. A failed send
+    /// is retried the next time the app starts rather than every check.
+    fn deliver_weekly_report(&mut self) {
+        self.last_weekly_report = Some(Utc::now());
+        let policy = self.config.weekly_report.clone();
+        let Some(until) = weekly_report_slot(&policy, &chrono::Local::now()) else {
+            return;
+        };
+        let Some(account) = self
+            .accounts
+            .iter()
+            .find(|account| !is_sample_account(account.id))
+            .cloned()
+        else {
+            return;
+        };
+
+        let mut settings = match self.load_email_settings(account.id) {
+            Ok(settings) => settings,
+            Err(err) => {
+                self.status = format!("weekly report failed: {err}");
+                return;
+            }
+        };
+        hydrate_email_secrets(account.id, &self.secrets, &mut settings);
+        let analytics = match self.runtime.block_on(self.storage.mail_analytics(
+            until - Duration::days(7),
+            until,
+            policy.top_correspondents as usize,
+        )) {
+            Ok(analytics) => analytics,
+            Err(err) => {
+                self.status = format!("weekly report failed: {err}");
+                return;
+            }
+        };
+
+        let me = MailAddress {
+            name: Some(account.display_name.clone()),
+            address: account.email_address.clone(),
+        };
+        let subject = weekly_report_subject(&analytics);
+        let outgoing = OutgoingMail {
+            from: me.clone(),
+            to: vec![me],
+            cc: Vec::new(),
+            bcc: Vec::new(),
+            reply_to: Vec::new(),
+            subject: subject.clone(),
+            body_text: weekly_report_text(&analytics),
+            body_html: None,
+            attachments: Vec::new(),
+            message_id: None,
+        };
+        if let Err(err) = self.send_outgoing(&account, &settings, &outgoing) {
+            self.status = format!("weekly report failed: {err}");
+            return;
+        }
+
+        let entry = cove_core::AuditEntry {
+            id: Uuid::new_v4(),
+            account_id: Some(account.id),
+            kind: WEEKLY_REPORT_AUDIT_KIND.to_string(),
+            success: true,
+            summary: subject.clone(),
+            detail: serde_json::json!({
+                "since": analytics.since,
+                "until": analytics.until,
+                "received": analytics.received,
+                "sent": analytics.sent,
+            }),
+            created_at: Utc::now(),
+        };
+        if let Err(err) = self.runtime.block_on(self.storage.append_audit_entry(&entry)) {
+            tracing::warn!("failed to record weekly report: {err}");
+        }
+        self.status = subject;
+    }
+
+    /// What the retention policy would purge now, across all accounts.
+    fn preview_retention(&mut self) {
+        let mut candidates = Vec::new();
+        for account in &self.accounts {
+            match self.runtime.block_on(self.email.retention_preview(account.id, &self.config.retention)) {
+                Ok(found) => candidates.extend(found),
+                Err(err) => {
+                    self.status = format!("Cleanup preview failed: {err}");
+                    return;
+                }
+            }
+        }
+        self.purge_preview = Some(candidates);
+    }
+
+    /// Purge old Trash and Junk mail on every account, server first.
+    fn purge_expired_mail(&mut self) {
+        self.last_retention_run = std::time::Instant::now();
+        let mut purged = 0;
+        let mut failures = Vec::new();
+        for account in self.accounts.clone() {
+            let mut settings = match self.load_email_settings(account.id) {
+                Ok(settings) => settings,
+                Err(err) => {
+                    failures.push(format!("{}: {err}", account.email_address));
+                    continue;
+                }
+            };
+            hydrate_email_secrets(account.id, &self.secrets, &mut settings);
+            match self.runtime.block_on(self.email.purge_expired(&account, &settings, &self.config.retention)) {
+                Ok(count) => purged += count,
+                Err(err) => failures.push(format!("{}: {err}", account.email_address)),
+            }
+        }
+        self.purge_preview = None;
+        self.status = match failures.first() {
+            Some(failure) => format!("Cleanup removed {purged} message(s); failed for {failure}"),
+            None => format!("Cleanup removed {purged} old message(s) from Trash and Junk"),
+        };
+        if purged > 0 {
+            self.load_folders(false);
+            self.load_threads();
+        }
+    }
+
+    /// Save or open an attachment once the attachment policy releases it;
+    /// a warning the user may override becomes a confirmation dialog.
+    fn run_attachment_action(&mut self, action: AttachmentAction) {
+        let policy = attachment_policy_from_config(&self.config);
+        let release = self.runtime.block_on(self.email.release_attachment(
+            action.attachment_id,
+            &action.file_name,
+            &policy,
+            action.confirmed,
+        ));
+        let content = match release {
+            Ok(AttachmentRelease::Released { content }) => content,
+            Ok(AttachmentRelease::Held { verdict }) => {
+                let reason = verdict.reason().unwrap_or_default().to_string();
+                if verdict.permits(true) && !action.confirmed {
+                    self.attachment_confirmation = Some((action, reason));
+                } else {
+                    self.status = format!("{} is blocked: {reason}", action.file_name);
+                }
+                return;
+            }
+            Ok(AttachmentRelease::Unavailable) => {
+                self.status = "Attachment content not available offline.".to_string();
+                return;
+            }
+            Err(e) => {
+                self.status = format!("Error loading attachment: {e}");
+                return;
+            }
+        };
+
+        if action.open {
+            let tmp_dir = std::env::temp_dir().join("cove-attachments");
+            let _ = std::fs::create_dir_all(&tmp_dir);
+            let tmp_path = tmp_dir.join(&action.file_name);
+            match std::fs::write(&tmp_path, &content) {
+                Ok(_) => {
+                    let _ = open::that(&tmp_path);
+                }
+                Err(e) => {
+                    self.status = format!("Failed to write temp file: {e}");
+                }
+            }
+        } else {
+            let Some(dir) = self.config.attachments.download_dir() else {
+                self.status = "Choose a download folder in Settings first.".to_string();
+                return;
+            };
+            let Some(message) = self.thread_messages.iter().find(|m| m.id == action.message_id) else {
+                return;
+            };
+            let Some(attachment) = message.attachments.iter().find(|a| a.id == action.attachment_id) else {
+                return;
+            };
+            match self
+                .runtime
+                .block_on(self.email.write_download(message, attachment, &content, &dir))
+            {
+                Ok(download) => {
+                    self.status = format!("Saved to {}", download.path);
+                    self.refresh_recent_downloads();
+                }
+                Err(e) => self.status = format!("Save failed: {e}"),
             }
         }
+    }
 
-        self.selected_thread = None;
-        self.selected_message = results.last().map(|m| m.id);
-        self.thread_messages = results;
-        self.status = format!("Operator search: {} result(s)", self.thread_messages.len());
+    /// Save every attachment of `messages` to the download folder; files the
+    /// policy holds back are skipped and named in the status line.
+    fn save_all_attachments(&mut self, messages: &[MailMessage]) {
+        let Some(dir) = self.config.attachments.download_dir() else {
+            self.status = "Choose a download folder in Settings first.".to_string();
+            return;
+        };
+        let policy = attachment_policy_from_config(&self.config);
+        match self
+            .runtime
+            .block_on(self.email.save_all_attachments(messages, &dir, &policy))
+        {
+            Ok(report) => {
+                self.status = match report.skipped.as_slice() {
+                    [] => format!("Saved {} attachment(s) to {}", report.saved.len(), dir.display()),
+                    skipped => format!(
+                        "Saved {} attachment(s) to {}; skipped {}",
+                        report.saved.len(),
+                        dir.display(),
+                        skipped
+                            .iter()
+                            .map(|item| format!("{} ({})", item.file_name, item.reason))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                };
+                self.refresh_recent_downloads();
+            }
+            Err(err) => self.status = format!("Save failed: {err}"),
+        }
     }
 
-    fn send_compose(&mut self) {
-        let Some(account) = self.account().cloned() else {
-            self.status = "No account selected".to_string();
+    fn refresh_recent_downloads(&mut self) {
+        if let Ok(downloads) = self
+            .runtime
+            .block_on(self.email.recent_downloads(RECENT_DOWNLOADS_LIMIT))
+        {
+            self.recent_downloads = downloads;
+        }
+    }
+
+    fn deliver_agenda(&mut self) {
+        let today = chrono::Local::now().date_naive();
+        let (day_start, day_end) = agenda::local_day_bounds(today);
+
+        let mut events = Vec::new();
+        let mut tasks = Vec::new();
+        let mut messages = Vec::new();
+        for account in &self.accounts {
+            if let Ok(found) = self.runtime.block_on(
+                self.storage.list_calendar_events(account.id, day_start, day_end),
+            ) {
+                events.extend(found);
+            }
+            if let Ok(found) = self.runtime.block_on(self.storage.list_tasks(account.id)) {
+                tasks.extend(found);
+            }
+            if let Ok(found) = self.runtime.block_on(
+                self.storage.list_mail_messages(account.id, Some("INBOX"), 500, 0),
+            ) {
+                messages.extend(found.items);
+            }
+        }
+
+        let vip_senders = self
+            .runtime
+            .block_on(self.storage.list_vip_addresses())
+            .unwrap_or_default();
+        let mut daily = agenda::DailyAgenda::build(
+            today,
+            events,
+            tasks,
+            messages,
+            &vip_senders,
+        );
+
+        // The narrative is best-effort; the plain agenda is still delivered
+        // when no model is configured or cloud AI is not opted in.
+        // Accounts whose AI policy forbids the current mode stay out of the
+        // prompt; their items are still listed below the narrative.
+        let shared = daily.for_accounts(|account_id| {
+            self.ai
+                .ensure_account_allows(Some(account_id), &self.ai_mode)
+                .is_ok()
+        });
+        if self.config.agenda.ai_narrative && !shared.is_empty() {
+            let plain = shared.to_plain_text();
+            if let Ok((response, _)) = self.runtime.block_on(self.ai.agenda_narrative(
+                &plain,
+                self.ai_mode.clone(),
+                self.ai_cloud_provider.clone(),
+            )) {
+                daily.narrative = Some(response.output);
+            }
+        }
+
+        let headline = daily.headline();
+        let body = daily.to_plain_text();
+        self.notification_state
+            .notify_agenda(&self.config.notifications, &headline, &body);
+
+        if self.config.agenda.send_email {
+            self.send_agenda_email(&headline, &body);
+        }
+
+        self.agenda_state.mark_delivered(today);
+        let entry = cove_core::AuditEntry {
+            id: Uuid::new_v4(),
+            account_id: None,
+            kind: agenda::AGENDA_AUDIT_KIND.to_string(),
+            success: true,
+            summary: headline.clone(),
+            detail: serde_json::json!({ "date": today }),
+            created_at: Utc::now(),
+        };
+        if let Err(err) = self.runtime.block_on(self.storage.append_audit_entry(&entry)) {
+            tracing::warn!("failed to record agenda delivery: {err}");
+        }
+        self.status = headline;
+    }
+
+    /// Mail the agenda to the primary (first real) account, whichever
+    /// account happens to be selected.
+    fn send_agenda_email(&mut self, subject: &str, body: &str) {
+        let Some(account) = self
+            .accounts
+            .iter()
+            .find(|account| !is_sample_account(account.id))
+            .cloned()
+        else {
             return;
         };
 
         let mut settings = match self.load_email_settings(account.id) {
             Ok(settings) => settings,
             Err(err) => {
-                self.status = err;
+                self.status = format!("agenda email failed: {err}");
                 return;
             }
         };
         hydrate_email_secrets(account.id, &self.secrets, &mut settings);
 
-        let mut attachments = Vec::new();
-        for path in &self.attachment_paths {
-            let file_path = Path::new(path);
-            let Ok(bytes) = std::fs::read(file_path) else {
-                continue;
-            };
-            let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
-            let file_name = file_path
-                .file_name()
-                .and_then(|name| name.to_str())
-                .unwrap_or("attachment.bin")
-                .to_string();
-            attachments.push(OutgoingAttachment {
-                file_name,
-                mime_type: "application/octet-stream".to_string(),
-                content_base64: encoded,
-                inline: false,
-            });
-        }
-
-        let to = self
-            .compose_to
-            .split(',')
-            .map(str::trim)
-            .filter(|email| !email.is_empty())
-            .map(|email| MailAddress {
-                name: None,
-                address: email.to_string(),
-            })
-            .collect::<Vec<_>>();
-
-        if to.is_empty() {
-            self.status = "Compose requires at least one recipient".to_string();
-            return;
-        }
-
+        let me = MailAddress {
+            name: Some(account.display_name.clone()),
+            address: account.email_address.clone(),
+        };
         let outgoing = OutgoingMail {
-            from: MailAddress {
-                name: Some(account.display_name.clone()),
-                address: account.email_address.clone(),
-            },
-            to,
+            from: me.clone(),
+            to: vec![me],
             cc: Vec::new(),
             bcc: Vec::new(),
             reply_to: Vec::new(),
-            subject: self.compose_subject.clone(),
-            body_text: self.compose_body.clone(),
+            subject: subject.to_string(),
+            body_text: body.to_string(),
             body_html: None,
-            attachments,
+            attachments: Vec::new(),
+            message_id: None,
         };
 
-        self.undo_send_message = Some((
-            account.clone(),
-            settings.clone(),
-            outgoing,
-            std::time::Instant::now()
-        ));
-        self.status = "Draft ready to send (Undo available for 5s)".to_string();
-        self.compose_subject.clear();
-        self.compose_body.clear();
-        self.attachment_paths.clear();
-    }
-
-    fn process_scheduled_messages(&mut self) {
-        let Ok(due_messages) = self.runtime.block_on(self.storage.due_scheduled_messages()) else { return; };
-        
-        for msg in due_messages {
-            let mut settings = match self.load_email_settings(msg.account_id) {
-                Ok(settings) => settings,
-                Err(_) => continue,
-            };
-            hydrate_email_secrets(msg.account_id, &self.secrets, &mut settings);
-            
-            let Some(account) = self.accounts.iter().find(|a| a.id == msg.account_id).cloned() else { continue; };
-            
-            let mut attachments = Vec::new(); // Simplifying here, we aren't pulling DB attachment blobs for scheduled yet
-            
-            // To be accurate, we need to extract raw recipients
-            let to = msg.to.clone();
-            
-            let outgoing = OutgoingMail {
-                from: msg.from.first().cloned().unwrap_or(MailAddress { name: None, address: account.email_address.clone() }),
-                to,
-                cc: msg.cc.clone(),
-                bcc: msg.bcc.clone(),
-                reply_to: msg.reply_to.clone(),
-                subject: msg.subject.clone(),
-                body_text: msg.body_text.clone().unwrap_or_default(),
-                body_html: msg.body_html.clone(),
-                attachments,
-            };
-            
-            if self.runtime.block_on(self.email.send(&account, &settings, &outgoing)).is_ok() {
-                // Remove scheduled flag
-                let _ = self.runtime.block_on(self.storage.schedule_send(msg.id, None));
-                self.status = "Scheduled message sent".to_string();
-            }
+        if let Err(err) = self.send_outgoing(&account, &settings, &outgoing) {
+            self.status = format!("agenda email failed: {err}");
         }
     }
 
     fn summarize_ai(&mut self) {
+        let Some(account_id) = self.selected_account else {
+            self.status = "Select an account before summarizing".to_string();
+            return;
+        };
         let response = self.runtime.block_on(self.ai.summarize_email(
+            account_id,
             &self.ai_subject,
             &self.ai_body,
             self.ai_mode.clone(),
@@ -956,6 +3932,43 @@ impl NativeApp {
         }
     }
 
+    /// Summarize unread inbox mail into one digest, reusing stored thread
+    /// summaries that are still current.
+    fn summarize_unread_mail(&mut self) {
+        let limits = digest_limits_from_config(&self.config);
+        let result = self.runtime.block_on(async {
+            let unread = self.storage.list_unread_inbox_messages(UNREAD_DIGEST_SCAN_LIMIT).await?;
+            let cached = self.storage.list_thread_digests().await?;
+            let digest = self
+                .ai
+                .build_inbox_digest(
+                    DigestThread::group(unread),
+                    &cached,
+                    &limits,
+                    self.ai_mode.clone(),
+                    self.ai_cloud_provider.clone(),
+                )
+                .await;
+            for entry in &digest.entries {
+                self.storage.upsert_thread_digest(entry).await?;
+            }
+            Ok::<_, cove_storage::StorageError>(digest)
+        });
+        match result {
+            Ok(digest) => {
+                self.status = format!(
+                    "Digest ready: {} summarized, {} reused, {} skipped, {} failed",
+                    digest.summarized,
+                    digest.reused,
+                    digest.skipped.len(),
+                    digest.failed.len()
+                );
+                self.inbox_digest = Some(digest);
+            }
+            Err(err) => self.status = format!("Unread mail digest failed: {err}"),
+        }
+    }
+
     fn generate_magic_message(&mut self) {
         if self.magic_compose_prompt.trim().is_empty() {
             self.status = "Please enter a prompt for magic compose".to_string();
@@ -1018,6 +4031,89 @@ impl NativeApp {
         }
     }
 
+    /// Restart the PKCE consent flow for an account whose grant was
+    /// revoked, pre-filled from its stored OAuth profile.
+    fn begin_reauth(&mut self, account_id: Uuid) {
+        let Some(account) = self.accounts.iter().find(|a| a.id == account_id).cloned() else {
+            self.status = "Account not found".to_string();
+            return;
+        };
+        let Some(profile) = account.oauth_profile.clone() else {
+            self.status = format!("{} does not sign in with OAuth", account.email_address);
+            return;
+        };
+        let session = match OAuthWorkflow::new(profile.clone())
+            .and_then(|workflow| workflow.begin_pkce_session())
+        {
+            Ok(session) => session,
+            Err(err) => {
+                self.status = format!("OAuth start failed: {err}");
+                return;
+            }
+        };
+
+        self.oauth.provider = account.provider.clone();
+        self.oauth.email = account.email_address.clone();
+        self.oauth.display_name = account.display_name.clone();
+        self.oauth.client_id = profile.client_id;
+        self.oauth.redirect_url = profile.redirect_url.to_string();
+        self.oauth.auth_url = session.authorization_url;
+        self.oauth.expected_state = session.csrf_state;
+        self.oauth.csrf_state.clear();
+        self.oauth.code.clear();
+        self.oauth.pkce_verifier = session.pkce_verifier;
+        self.oauth.started = true;
+        self.oauth.reauth_account = Some(account_id);
+        self.view = View::Security;
+        let _ = open::that(&self.oauth.auth_url);
+        self.status = format!(
+            "Approve access for {} in the browser, then paste the code and state",
+            account.email_address
+        );
+    }
+
+    /// Store fresh tokens for the account being signed in again.
+    fn finish_reauth(&mut self, account_id: Uuid, token: cove_security::OAuthTokenResult) {
+        let stored = set_secret_guarded(
+            &self.secrets,
+            SecretKey {
+                namespace: "oauth_access_token".to_string(),
+                id: account_id.to_string(),
+            },
+            &token.access_token,
+        )
+        .and_then(|()| match token.refresh_token.as_deref() {
+            Some(refresh_token) => set_secret_guarded(
+                &self.secrets,
+                SecretKey {
+                    namespace: "oauth_refresh_token".to_string(),
+                    id: account_id.to_string(),
+                },
+                refresh_token,
+            ),
+            None => Ok(()),
+        });
+        if let Err(err) = stored {
+            self.status = format!("saving tokens failed: {err}");
+            return;
+        }
+
+        if let Err(err) = self.runtime.block_on(self.storage.clear_account_reauth(account_id)) {
+            tracing::warn!("clearing re-auth flag failed: {err}");
+        }
+        self.refresh_account_reauth();
+        self.oauth.code.clear();
+        self.oauth.csrf_state.clear();
+        self.oauth.expected_state.clear();
+        self.status = "Signed in again; sync resumed".to_string();
+    }
+
+    fn refresh_account_reauth(&mut self) {
+        if let Ok(items) = self.runtime.block_on(self.storage.list_account_reauth()) {
+            self.account_reauth = items;
+        }
+    }
+
     fn complete_oauth(&mut self) {
         let Some(profile) = oauth_profile_for_provider(
             self.oauth.provider.clone(),
@@ -1061,6 +4157,11 @@ impl NativeApp {
             }
         };
 
+        if let Some(account_id) = self.oauth.reauth_account.take() {
+            self.finish_reauth(account_id, token);
+            return;
+        }
+
         let now = Utc::now();
         let account = Account {
             id: Uuid::new_v4(),
@@ -1155,6 +4256,278 @@ impl NativeApp {
         parse_domain_settings(&raw, "email").map_err(|err| err.to_string())
     }
 
+    /// Send through the email service, or queue the message for replay when
+    /// the server is unreachable.
+    fn send_outgoing(
+        &self,
+        account: &Account,
+        settings: &ProtocolSettings,
+        outgoing: &OutgoingMail,
+    ) -> Result<(), String> {
+        match self.send_outgoing_now(account, settings, outgoing) {
+            Err(err) if err.is_network() => self
+                .runtime
+                .block_on(self.email.queue_send(account.id, outgoing, &err))
+                .map_err(|err| err.to_string()),
+            result => result.map_err(|err| err.to_string()),
+        }
+    }
+
+    /// Send through the email service, refreshing an expired OAuth access
+    /// token once when the server rejects it.
+    fn send_outgoing_now(
+        &self,
+        account: &Account,
+        settings: &ProtocolSettings,
+        outgoing: &OutgoingMail,
+    ) -> Result<(), EmailError> {
+        let result = self.runtime.block_on(self.email.send(account, settings, outgoing));
+        let (Err(EmailError::Auth(_)), Some(profile)) = (&result, account.oauth_profile.clone())
+        else {
+            return result;
+        };
+
+        let access_token = self.refresh_access_token(account, profile)?;
+        let mut settings = settings.clone();
+        settings.access_token = Some(access_token);
+        self.runtime.block_on(self.email.send(account, &settings, outgoing))
+    }
+
+    /// Refresh `account`'s OAuth access token. A grant the provider revoked
+    /// flags the account for re-authentication.
+    fn refresh_access_token(
+        &self,
+        account: &Account,
+        profile: cove_core::OAuthProfile,
+    ) -> Result<String, EmailError> {
+        match self.runtime.block_on(refresh_stored_access_token(
+            &self.secrets,
+            &account.id.to_string(),
+            profile,
+        )) {
+            Ok(access_token) => Ok(access_token),
+            Err(SecurityError::ReauthRequired(reason)) => {
+                self.runtime
+                    .block_on(self.storage.mark_account_reauth(account.id, &reason))?;
+                Err(EmailError::Auth(format!(
+                    "{} needs to sign in again",
+                    account.email_address
+                )))
+            }
+            Err(err) => Err(EmailError::Auth(format!("token refresh failed: {err}"))),
+        }
+    }
+
+    /// Replay changes queued while offline, oldest first. An account whose
+    /// provider is still unreachable keeps its remaining changes queued.
+    fn replay_offline_queue(&mut self) {
+        let mut replay = match self.runtime.block_on(self.email.begin_replay()) {
+            Ok(replay) => replay,
+            Err(err) => {
+                self.status = format!("Offline queue unavailable: {err}");
+                return;
+            }
+        };
+        while let Some(op) = replay.next_operation() {
+            let result = self.replay_operation(&op);
+            if let Err(err) = self.runtime.block_on(self.email.record_replay(&mut replay, &op, result)) {
+                tracing::warn!("failed to update offline queue: {err}");
+            }
+        }
+        let status = replay.finish();
+        if status.replayed > 0 {
+            self.status = format!("Back online: {} queued change(s) sent", status.replayed);
+        }
+        self.pending_operations = self
+            .runtime
+            .block_on(self.storage.list_pending_operations())
+            .unwrap_or_default();
+    }
+
+    /// Push one queued change to its provider.
+    fn replay_operation(&self, op: &PendingOperation) -> Result<ReplayOutcome, ReplayError> {
+        let account = self
+            .accounts
+            .iter()
+            .find(|account| account.id == op.account_id)
+            .cloned()
+            .ok_or_else(|| ReplayError::permanent("account not found"))?;
+        match op.kind {
+            PendingOperationKind::SendMail => {
+                let outgoing = EmailService::queued_mail(op)?;
+                let mut settings = self.load_email_settings(account.id).map_err(ReplayError::permanent)?;
+                hydrate_email_secrets(account.id, &self.secrets, &mut settings);
+                self.send_outgoing_now(&account, &settings, &outgoing)?;
+                Ok(ReplayOutcome::Applied)
+            }
+            PendingOperationKind::ArchiveMessages | PendingOperationKind::DeleteMessages => {
+                let mut settings = self.load_email_settings(account.id).map_err(ReplayError::permanent)?;
+                hydrate_email_secrets(account.id, &self.secrets, &mut settings);
+                Ok(self
+                    .runtime
+                    .block_on(self.email.replay_message_change(&account, &settings, op))?)
+            }
+            PendingOperationKind::UpsertEvent => {
+                let mut settings = self.load_calendar_settings(account.id).map_err(ReplayError::permanent)?;
+                hydrate_calendar_secrets(account.id, &self.secrets, &mut settings);
+                self.runtime
+                    .block_on(self.calendar.replay_event(&account, &settings, op))
+                    .map_err(|err| ReplayError::new(err.is_network(), err.to_string()))
+            }
+        }
+    }
+
+    /// Measure free disk space; sync drops to headers only while it is low.
+    fn check_disk_space(&mut self) {
+        let before = self.email.sync_fidelity();
+        match self.runtime.block_on(self.email.check_disk_space(&self.config.disk_space)) {
+            Ok(report) => {
+                if report.fidelity != before {
+                    self.status = if report.is_degraded() {
+                        format!(
+                            "Disk space low ({} free): syncing headers only",
+                            format_bytes(report.available_bytes)
+                        )
+                    } else {
+                        "Disk space recovered: full sync resumed".to_string()
+                    };
+                }
+                self.disk_space = Some(report);
+            }
+            Err(err) => tracing::warn!("disk space check failed: {err}"),
+        }
+    }
+
+    fn show_disk_space_banner(&mut self, ctx: &egui::Context) {
+        let Some(report) = self.disk_space.as_ref().filter(|report| report.is_degraded()) else {
+            return;
+        };
+        let text = format!(
+            "Low disk space — {} free. Syncing headers only and not caching attachments; \
+             full sync needs about {} over the next {} days.",
+            format_bytes(report.available_bytes),
+            format_bytes(report.projected_need_bytes),
+            cove_storage::PROJECTION_DAYS,
+        );
+        egui::TopBottomPanel::top("disk_space_banner").show(ctx, |ui| {
+            ui.label(egui::RichText::new(text).strong().color(egui::Color32::from_rgb(220, 140, 40)));
+        });
+    }
+
+    fn show_reauth_banner(&mut self, ctx: &egui::Context) {
+        if self.account_reauth.is_empty() {
+            return;
+        }
+        let mut reconnect = None;
+        egui::TopBottomPanel::top("reauth_banner").show(ctx, |ui| {
+            for item in &self.account_reauth {
+                let email = self
+                    .accounts
+                    .iter()
+                    .find(|account| account.id == item.account_id)
+                    .map(|account| account.email_address.as_str())
+                    .unwrap_or("An account");
+                ui.horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "{email} needs you to sign in again. Sync is paused until you reconnect."
+                        ))
+                        .strong()
+                        .color(egui::Color32::from_rgb(220, 90, 70)),
+                    )
+                    .on_hover_text(&item.reason);
+                    if ui.button("Sign in again").clicked() {
+                        reconnect = Some(item.account_id);
+                    }
+                });
+            }
+        });
+        if let Some(account_id) = reconnect {
+            self.begin_reauth(account_id);
+        }
+    }
+
+    /// A strip under the titlebar while changes are waiting to reach a
+    /// provider, with the queue itself behind "Details".
+    fn show_offline_banner(&mut self, ctx: &egui::Context) {
+        if self.pending_operations.is_empty() {
+            return;
+        }
+        let pending = self
+            .pending_operations
+            .iter()
+            .filter(|op| op.status == PendingOperationStatus::Pending)
+            .count();
+        let attention = self.pending_operations.len() - pending;
+        let mut replay = false;
+        let mut retry = None;
+        let mut discard = None;
+        egui::TopBottomPanel::top("offline_banner").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let mut text = format!("Offline — {pending} pending change(s)");
+                if attention > 0 {
+                    text.push_str(&format!(", {attention} need attention"));
+                }
+                ui.label(egui::RichText::new(text).strong());
+                if pending > 0 && ui.button("Retry now").clicked() {
+                    replay = true;
+                }
+                let label = if self.show_pending_operations { "Hide details" } else { "Details" };
+                if ui.button(label).clicked() {
+                    self.show_pending_operations = !self.show_pending_operations;
+                }
+            });
+            if !self.show_pending_operations {
+                return;
+            }
+            for op in &self.pending_operations {
+                ui.horizontal(|ui| {
+                    let state = match op.status {
+                        PendingOperationStatus::Pending => "waiting",
+                        PendingOperationStatus::Failed => "failed",
+                        PendingOperationStatus::Conflict => "conflict",
+                    };
+                    ui.label(format!("[{state}] {}", op.summary));
+                    if let Some(error) = &op.last_error {
+                        ui.weak(error);
+                    }
+                    if op.status != PendingOperationStatus::Pending {
+                        if ui.small_button("Retry").clicked() {
+                            retry = Some(op.id);
+                        }
+                        if ui.small_button("Discard").clicked() {
+                            discard = Some(op.id);
+                        }
+                    }
+                });
+            }
+        });
+
+        if let Some(id) = retry {
+            let _ = self.runtime.block_on(self.storage.update_pending_operation_status(
+                id,
+                PendingOperationStatus::Pending,
+                None,
+                None,
+            ));
+            replay = true;
+        }
+        if let Some(id) = discard {
+            match self.runtime.block_on(self.storage.delete_pending_operation(id)) {
+                Ok(()) => self.status = "Queued change discarded".to_string(),
+                Err(err) => self.status = format!("Discard failed: {err}"),
+            }
+        }
+        if replay {
+            self.replay_offline_queue();
+        } else if discard.is_some() {
+            self.pending_operations = self
+                .runtime
+                .block_on(self.storage.list_pending_operations())
+                .unwrap_or_default();
+        }
+    }
+
     fn load_calendar_settings(&self, account_id: Uuid) -> Result<CalendarSettings, String> {
         let raw = self
             .runtime
@@ -1409,6 +4782,19 @@ impl NativeApp {
                         ui.add_space(20.0);
                         ui.label(egui::RichText::new(&self.status).color(ui.visuals().warn_fg_color));
                     }
+
+                    ui.add_space(20.0);
+                    if self.accounts.is_empty() {
+                        if ui
+                            .button("Explore with sample data")
+                            .on_hover_text("Look around with a fake mailbox, calendar and tasks first")
+                            .clicked()
+                        {
+                            self.load_sample_data();
+                        }
+                    } else if ui.button("Back").clicked() {
+                        self.view = View::Onboarding;
+                    }
                 });
             });
         });
@@ -1417,28 +4803,73 @@ impl NativeApp {
 
 impl eframe::App for NativeApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        titlebar::handle_resize(ctx);
         if self.view == View::SetupWizard {
+            egui::TopBottomPanel::top("wizard_titlebar")
+                .frame(egui::Frame::default().fill(ctx.style().visuals.panel_fill).inner_margin(egui::Margin { left: titlebar::leading_inset(), right: 8, top: 8, bottom: 4 }))
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        let drag = ui.interact(ui.max_rect(), ui.id().with("drag"), egui::Sense::click_and_drag());
+                        titlebar::drag_region(ctx, &drag);
+                        ui.label("Cove Mail");
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), titlebar::window_controls);
+                    });
+                });
             self.show_setup_wizard(ctx);
             return;
         }
 
+        self.session.track_window(ctx);
+        self.session.track_panels(ctx, SESSION_PANELS);
+        if self.last_recovery_save.elapsed() >= recovery::SAVE_INTERVAL {
+            self.last_recovery_save = std::time::Instant::now();
+            self.save_recovery_journal();
+            self.save_session();
+        }
+        // Keep ticking while idle so the journal and timers stay current.
+        ctx.request_repaint_after(recovery::SAVE_INTERVAL);
+
         // Global keyboard shortcuts.
         let modifiers = ctx.input(|i| i.modifiers);
         if ctx.input(|i| i.key_pressed(egui::Key::K) && modifiers.command) {
             self.show_command_palette = !self.show_command_palette;
             self.command_query.clear();
         }
-        if ctx.input(|i| i.key_pressed(egui::Key::N) && modifiers.command) {
-            self.show_compose_window = true;
+        if ctx.input(|i| i.key_pressed(egui::Key::N) && modifiers.command) {
+            self.show_compose_window = true;
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::V) && modifiers.command && modifiers.shift) {
+            let sender = self
+                .selected_message
+                .and_then(|id| self.thread_messages.iter().find(|m| m.id == id))
+                .and_then(|m| m.from.first())
+                .map(|a| a.address.clone());
+            if let Some(sender) = sender {
+                self.toggle_sender_vip(&sender);
+            }
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::F12) && modifiers.command && modifiers.shift)
+            && self.ranking_debug.take().is_none()
+        {
+            self.ranking_debug = Some(Vec::new());
+            let query = self.mail_query.trim().to_string();
+            if !query.is_empty() {
+                self.refresh_ranking_debug(&query);
+            }
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::F11) && modifiers.command && modifiers.shift) {
+            self.show_protocol_console = !self.show_protocol_console;
         }
         if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
             self.show_command_palette = false;
         }
 
+        self.show_secrets_prompt(ctx);
+
         // Undo send countdown (5 seconds).
         if let Some((account, settings, outgoing, sent_at)) = self.undo_send_message.clone() {
             if sent_at.elapsed() >= std::time::Duration::from_secs(5) {
-                match self.runtime.block_on(self.email.send(&account, &settings, &outgoing)) {
+                match self.send_outgoing(&account, &settings, &outgoing) {
                     Ok(()) => self.status = "Message sent successfully".to_string(),
                     Err(err) => self.status = format!("Send failed: {err}")
                 }
@@ -1446,47 +4877,94 @@ impl eframe::App for NativeApp {
             }
         }
 
+        // Keep the offline banner current; sends queue themselves on failure.
+        if self.last_offline_check.elapsed() >= std::time::Duration::from_secs(5) {
+            self.last_offline_check = std::time::Instant::now();
+            self.pending_operations = self
+                .runtime
+                .block_on(self.storage.list_pending_operations())
+                .unwrap_or_default();
+        }
+
+        // Hourly maintenance: purge Trash and Junk past their retention.
+        if self.config.retention.enabled
+            && self.last_retention_run.elapsed() >= std::time::Duration::from_secs(3600)
+        {
+            self.purge_expired_mail();
+        }
+
         // Periodic notification check (every 30 seconds).
         if self.last_notification_check.elapsed() >= std::time::Duration::from_secs(30) {
             self.last_notification_check = std::time::Instant::now();
+            self.check_disk_space();
+            if self.pending_operations.iter().any(|op| op.status == PendingOperationStatus::Pending) {
+                self.replay_offline_queue();
+            }
             
             self.process_scheduled_messages();
+            self.process_mail_merges();
+            if self.agenda_state.is_due(&self.config.agenda, chrono::Local::now()) {
+                self.deliver_agenda();
+            }
+            if weekly_report_due(
+                &self.config.weekly_report,
+                self.last_weekly_report,
+                &chrono::Local::now(),
+            ) {
+                self.deliver_weekly_report();
+            }
+            self.refresh_sender_mutes();
+            self.notification_state.set_muted_senders(
+                self.sender_mutes.iter().map(|mute| (mute.account_id, mute.email.clone())).collect(),
+            );
+            self.refresh_project_folders();
             let notif_config = &self.config.notifications;
 
+            // Watched threads first: they override the new-mail toggle and quiet hours.
+            if let Ok(watched) = self.runtime.block_on(self.storage.list_watched_thread_messages(200)) {
+                self.notification_state.check_watched_threads(&watched);
+            }
+
+            // VIP senders also override muting, with their own sound.
+            if let Ok(vip_mail) = self.runtime.block_on(self.storage.list_vip_messages(true, 100)) {
+                self.notification_state.check_vip_mail(notif_config, &vip_mail);
+            }
+
             // New-mail notifications for the current thread list.
             self.notification_state.check_new_mail(notif_config, &self.thread_messages);
 
-            // Calendar reminder notifications.
-            if let Some(account_id) = self.selected_account {
-                let now = Utc::now();
-                let window_end = now + chrono::Duration::minutes(
-                    *notif_config.reminder_minutes_before.iter().max().unwrap_or(&15) + 1
-                );
-                if let Ok(events) = self.runtime.block_on(
-                    self.storage.list_calendar_events(account_id, now, window_end)
-                ) {
-                    self.notification_state.check_calendar_reminders(notif_config, &events);
-                }
-
-                if let Ok(tasks) = self.runtime.block_on(
-                    self.storage.list_tasks(account_id)
-                ) {
-                    self.notification_state.check_task_reminders(notif_config, &tasks);
+            // Snoozes, follow-ups, event alerts and task due times, for every account.
+            let mut alerts = notifications::ReminderAlerts::new(notif_config);
+            match self.runtime.block_on(self.reminders.poll(
+                Utc::now(),
+                &notif_config.reminder_minutes_before,
+                &mut alerts,
+            )) {
+                Ok(_) if alerts.mail_returned > 0 => {
+                    self.status = format!("{} snoozed message(s) back in the inbox", alerts.mail_returned);
                 }
+                Ok(_) => {}
+                Err(err) => self.status = format!("reminders failed: {err}"),
             }
         }
 
-        // Custom draggable titlebar with space for macOS traffic lights
+        // Custom draggable titlebar: room for the traffic lights on macOS,
+        // window controls elsewhere.
         egui::TopBottomPanel::top("top")
-            .frame(egui::Frame::default().fill(ctx.style().visuals.panel_fill).inner_margin(egui::Margin { left: 80, right: 8, top: 12, bottom: 8 })) // 80px left padding for macOS traffic lights
+            .frame(egui::Frame::default().fill(ctx.style().visuals.panel_fill).inner_margin(egui::Margin { left: titlebar::leading_inset(), right: 8, top: 12, bottom: 8 }))
             .show(ctx, |ui| {
                 ui.horizontal(|ui| {
-                    if ui.interact(ui.max_rect(), ui.id().with("drag"), egui::Sense::drag()).dragged() {
-                        ctx.send_viewport_cmd(egui::ViewportCommand::StartDrag);
-                    }
+                    let drag = ui.interact(ui.max_rect(), ui.id().with("drag"), egui::Sense::click_and_drag());
+                    titlebar::drag_region(ctx, &drag);
 
+                    if self.config.onboarding_pending(&self.onboarding_facts())
+                        && ui.selectable_label(self.view == View::Onboarding, "Get Started").clicked()
+                    {
+                        self.view = View::Onboarding;
+                    }
                     for (view, label) in [
                         (View::Inbox, "Inbox"),
+                        (View::NeedsReply, "Needs Reply"),
                         (View::Chat, "Chat"),
                         (View::Calendar, "Calendar"),
                         (View::Tasks, "Tasks"),
@@ -1516,11 +4994,17 @@ impl eframe::App for NativeApp {
                         self.reload_accounts();
                     }
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        titlebar::window_controls(ui);
                         ui.label(&self.status);
+                        self.show_index_progress(ui);
                     });
                 });
             });
 
+        self.show_reauth_banner(ctx);
+        self.show_offline_banner(ctx);
+        self.show_disk_space_banner(ctx);
+
         egui::SidePanel::left("accounts").frame(egui::Frame::default().fill(ctx.style().visuals.panel_fill).inner_margin(12.0)).show(ctx, |ui| {
             ui.heading("Accounts");
             ui.add_space(8.0);
@@ -1547,10 +5031,33 @@ impl eframe::App for NativeApp {
         egui::CentralPanel::default().show(ctx, |ui| match self.view {
             View::Inbox => {
                 ui.horizontal(|ui| {
-                    ui.text_edit_singleline(&mut self.mail_query);
+                    let query_edit = ui.add(
+                        egui::TextEdit::singleline(&mut self.mail_query)
+                            .hint_text("Search (try from: or label:)"),
+                    );
+                    if query_edit.has_focus() && self.suggested_for != self.mail_query {
+                        self.suggested_for = self.mail_query.clone();
+                        self.search_suggestions = self
+                            .runtime
+                            .block_on(self.storage.search_suggestions(&self.mail_query, 8))
+                            .unwrap_or_default();
+                    }
+                    if query_edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        self.search_mail();
+                    }
                     if ui.button("Search").clicked() {
                         self.search_mail();
                     }
+                    if ui
+                        .selectable_label(self.query_builder.is_some(), "Builder")
+                        .on_hover_text("Build a query from search operators")
+                        .clicked()
+                    {
+                        self.query_builder = match self.query_builder {
+                            Some(_) => None,
+                            None => Some(QueryBuilderState::from_query(&self.mail_query)),
+                        };
+                    }
                     if ui.button("Refresh Folders").clicked() {
                         self.load_folders(true);
                     }
@@ -1563,24 +5070,62 @@ impl eframe::App for NativeApp {
                         }
                     });
                 });
+                if !self.search_suggestions.is_empty() && self.suggested_for == self.mail_query {
+                    let mut chosen = None;
+                    ui.horizontal_wrapped(|ui| {
+                        for suggestion in &self.search_suggestions {
+                            if ui.small_button(&suggestion.label).clicked() {
+                                chosen = Some(suggestion.completion.clone());
+                            }
+                        }
+                    });
+                    if let Some(completion) = chosen {
+                        self.mail_query = completion;
+                        self.search_suggestions.clear();
+                    }
+                }
+                if !self.search_events.is_empty() || !self.search_tasks.is_empty() {
+                    let mut open_view = None;
+                    ui.horizontal_wrapped(|ui| {
+                        for event in &self.search_events {
+                            let start = event.starts_at.with_timezone(&Local).format("%b %e %H:%M");
+                            if ui.small_button(format!("📅 {} · {start}", event.title)).clicked() {
+                                open_view = Some(View::Calendar);
+                            }
+                        }
+                        for task in &self.search_tasks {
+                            if ui.small_button(format!("☑ {}", task.title)).clicked() {
+                                open_view = Some(View::Tasks);
+                            }
+                        }
+                    });
+                    if let Some(view) = open_view {
+                        self.view = view;
+                    }
+                }
                 ui.add_space(8.0);
 
                 let available_height = ui.available_height();
                 
                 egui::SidePanel::left("folders_panel")
                     .resizable(true)
-                    .default_width(200.0)
+                    .default_width(self.session.panel_width("folders_panel", 200.0))
                     .width_range(150.0..=400.0)
                     .frame(egui::Frame::default().inner_margin(8.0))
                     .show_inside(ui, |ui| {
                         ui.heading(egui::RichText::new("Folders").strong());
                         ui.add_space(4.0);
-                        egui::ScrollArea::vertical()
-                            .max_height(available_height - 20.0)
+                        let area = egui::ScrollArea::vertical().max_height(available_height - 20.0);
+                        let scroll = self
+                            .restored_scroll("folders", area)
                             .show(ui, |ui| {
                                 let mut next_folder = None;
+                                if ui.add(egui::SelectableLabel::new(false, egui::RichText::new("★ VIP").strong())).clicked() {
+                                    self.show_vip_folder();
+                                }
                                 for folder in &self.folders {
-                                    let is_selected = self.selected_folder == folder.path;
+                                    let is_selected = self.selected_project.is_none()
+                                        && self.selected_folder == folder.path;
                                     let label = format!(
                                         "{} ({}/{})",
                                         folder.path, folder.unread_count, folder.total_count
@@ -1605,26 +5150,109 @@ impl eframe::App for NativeApp {
                                         }
                                     });
                                 }
+                                if !self.project_folders.is_empty() {
+                                    ui.add_space(8.0);
+                                    ui.label(egui::RichText::new("Projects").strong());
+                                }
+                                let mut next_project = None;
+                                for project in &self.project_folders {
+                                    let is_selected = self.selected_project.as_deref() == Some(project.label.as_str());
+                                    let label = format!(
+                                        "{} ({}/{})",
+                                        project.label, project.unread_count, project.thread_count
+                                    );
+                                    if ui.add(egui::SelectableLabel::new(is_selected, label)).clicked() {
+                                        next_project = Some(project.label.clone());
+                                    }
+                                }
                                 if let Some(folder) = next_folder {
+                                    self.selected_project = None;
                                     self.selected_folder = folder;
                                     self.load_threads();
                                 }
+                                if let Some(project) = next_project {
+                                    self.selected_project = Some(project);
+                                    self.load_threads();
+                                }
                             });
+                        self.session.set_scroll_offset("folders", scroll.state.offset.y);
                     });
 
                 egui::SidePanel::left("threads_panel")
                     .resizable(true)
-                    .default_width(350.0)
+                    .default_width(self.session.panel_width("threads_panel", 350.0))
                     .width_range(250.0..=600.0)
                     .frame(egui::Frame::default().inner_margin(8.0))
                     .show_inside(ui, |ui| {
-                        ui.heading(egui::RichText::new("Threads").strong());
+                        let months = thread_months(&self.threads);
+                        ui.horizontal(|ui| {
+                            ui.heading(egui::RichText::new("Threads").strong());
+                            if let Some(section) = self.thread_list_section {
+                                ui.label(egui::RichText::new(format!("· {}", section.label())).weak());
+                            }
+                            if months.len() > 1 {
+                                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                    ui.menu_button("Jump to", |ui| {
+                                        for &(year, month) in &months {
+                                            if ui.button(month_label(year, month)).clicked() {
+                                                self.thread_jump_month = Some((year, month));
+                                                ui.close_menu();
+                                            }
+                                        }
+                                    });
+                                });
+                            }
+                        });
+                        let mut toggle_backfill = None;
+                        if let Some(progress) = self.initial_sync.as_ref().filter(|progress| !progress.completed) {
+                            ui.horizontal(|ui| {
+                                let fraction = progress.messages_total.map_or(0.0, |total| {
+                                    progress.messages_fetched as f32 / total.max(1) as f32
+                                });
+                                let mut text = match progress.messages_total {
+                                    Some(total) => format!("{} of {total} messages", progress.messages_fetched),
+                                    None => format!("{} messages", progress.messages_fetched),
+                                };
+                                if progress.folders_total > 0 {
+                                    text.push_str(&format!(" · {}/{} folders", progress.folders_done, progress.folders_total));
+                                }
+                                if progress.paused {
+                                    text.push_str(" · paused");
+                                } else if let Some(eta) = progress.eta_seconds {
+                                    text.push_str(&format!(" · ~{} left", eta_label(eta)));
+                                }
+                                ui.add(egui::ProgressBar::new(fraction).text(text).desired_width(ui.available_width() - 70.0));
+                                let label = if progress.paused { "Resume" } else { "Pause" };
+                                if ui.small_button(label).on_hover_text("Backfill of older mail; new mail keeps syncing").clicked() {
+                                    toggle_backfill = Some((progress.account_id, !progress.paused));
+                                }
+                            });
+                        }
+                        if let Some((account_id, paused)) = toggle_backfill {
+                            match self.runtime.block_on(self.email.set_backfill_paused(account_id, paused)) {
+                                Ok(progress) => {
+                                    self.initial_sync = Some(progress);
+                                    self.status = if paused { "Backfill paused".to_string() } else { "Backfill resumed".to_string() };
+                                }
+                                Err(err) => self.status = format!("backfill update failed: {err}"),
+                            }
+                        }
                         ui.add_space(4.0);
                         let mut next_thread = None;
-                        egui::ScrollArea::vertical()
-                            .max_height(available_height - 20.0)
+                        let mut top_section = None;
+                        let jump_month = self.thread_jump_month.take();
+                        let area = egui::ScrollArea::vertical().max_height(available_height - 20.0);
+                        let scroll = self
+                            .restored_scroll("threads", area)
                             .show(ui, |ui| {
+                                let mut current_bucket = None;
+                                let mut jumped = false;
                                 for thread in &self.threads {
+                                    if current_bucket != Some(thread.date_bucket) {
+                                        current_bucket = Some(thread.date_bucket);
+                                        ui.add_space(6.0);
+                                        ui.label(egui::RichText::new(thread.date_bucket.label()).small().strong().weak());
+                                    }
                                     let is_selected = self.selected_thread.as_deref() == Some(&thread.thread_id);
                                     
                                     let participants = if thread.participants.is_empty() {
@@ -1647,28 +5275,88 @@ impl eframe::App for NativeApp {
                                         let text_color = if is_selected { ui.visuals().selection.stroke.color } else { ui.visuals().text_color() };
                                         let title_color = if is_selected { text_color } else { ui.visuals().strong_text_color() };
                                         
-                                        ui.label(egui::RichText::new(&thread.subject).strong().color(title_color).size(15.0));
+                                        ui.horizontal(|ui| {
+                                            ui.label(egui::RichText::new(&thread.subject).strong().color(title_color).size(15.0));
+                                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                                timefmt::label(ui, thread.most_recent_at, 12.0);
+                                            });
+                                        });
                                         ui.add_space(2.0);
                                         ui.label(egui::RichText::new(format!("{} ({} unread / {})", participants, thread.unread_count, thread.message_count)).color(text_color).size(13.0));
                                     }).response;
                                     
+                                    if top_section.is_none() && response.rect.max.y > ui.clip_rect().min.y {
+                                        top_section = Some(thread.date_bucket);
+                                    }
+                                    if !jumped && jump_month == Some(local_month(thread.most_recent_at)) {
+                                        jumped = true;
+                                        response.scroll_to_me(Some(egui::Align::TOP));
+                                    }
                                     if response.interact(egui::Sense::click()).clicked() {
                                         next_thread = Some(thread.thread_id.clone());
                                     }
                                 }
                             });
+                        self.thread_list_section = top_section;
+                        self.session.set_scroll_offset("threads", scroll.state.offset.y);
                         if let Some(thread_id) = next_thread {
                             self.selected_thread = Some(thread_id);
                             self.load_thread_messages();
                         }
                     });
 
+                if self.related_panel_open && self.selected_message.is_some() {
+                    self.refresh_related_messages();
+                    egui::SidePanel::right("related_panel")
+                        .resizable(true)
+                        .default_width(self.session.panel_width("related_panel", 240.0))
+                        .width_range(180.0..=400.0)
+                        .frame(egui::Frame::default().inner_margin(8.0))
+                        .show_inside(ui, |ui| {
+                            ui.heading(egui::RichText::new("Related").strong());
+                            ui.add_space(4.0);
+                            self.show_related_panel(ui);
+                        });
+                }
+
                 egui::CentralPanel::default()
                     .frame(egui::Frame::default().inner_margin(16.0))
                     .show_inside(ui, |ui| {
                         ui.horizontal(|ui| {
                             ui.heading(egui::RichText::new("Message").strong());
                             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                ui.toggle_value(&mut self.related_panel_open, "Related")
+                                    .on_hover_text("Other threads from this sender and on the same topic");
+                                if ui.small_button("Extract Tasks").on_hover_text("Find action items in this thread and review them as tasks").clicked() {
+                                    self.extract_thread_tasks();
+                                }
+                                if ui.small_button("Export Thread").clicked() {
+                                    self.export_thread();
+                                }
+                                if self.thread_messages.iter().any(|m| !m.attachments.is_empty())
+                                    && ui.small_button("Save Attachments")
+                                        .on_hover_text("Save every attachment in this thread to the download folder")
+                                        .clicked()
+                                {
+                                    let messages = self.thread_messages.clone();
+                                    self.save_all_attachments(&messages);
+                                }
+                                let watch_label = if self.selected_thread_watched { "Unwatch Thread" } else { "Watch Thread" };
+                                if ui.small_button(watch_label)
+                                    .on_hover_text("Always notify on replies, even when mail notifications are muted")
+                                    .clicked()
+                                {
+                                    self.toggle_thread_watch();
+                                }
+                                if ui.small_button("Reply")
+                                    .on_hover_text("Reply from the address this message was delivered to")
+                                    .clicked()
+                                {
+                                    if let Some(msg) = self.thread_messages.last().cloned() {
+                                        self.compose_body.clear();
+                                        self.start_reply(&msg);
+                                    }
+                                }
                                 if ui.small_button("AI Draft Reply").clicked() {
                                     if let Some(msg) = self.thread_messages.last() {
                                         let sender = msg.from.first()
@@ -1676,18 +5364,13 @@ impl eframe::App for NativeApp {
                                             .unwrap_or_default();
                                         let body = msg.body_text.as_deref().unwrap_or(&msg.preview);
                                         match self.runtime.block_on(self.ai.draft_reply_suggestion(
-                                            &sender, &msg.subject, body,
+                                            msg.account_id, &sender, &msg.subject, body,
                                             self.ai_mode.clone(), self.ai_cloud_provider.clone(),
                                         )) {
                                             Ok((reply, _)) => {
+                                                let msg = msg.clone();
+                                                self.start_reply(&msg);
                                                 self.compose_body = reply.output;
-                                                self.compose_subject = if msg.subject.to_lowercase().starts_with("re:") {
-                                                    msg.subject.clone()
-                                                } else {
-                                                    format!("Re: {}", msg.subject)
-                                                };
-                                                self.compose_to = sender;
-                                                self.show_compose_window = true;
                                                 self.status = "AI draft reply generated.".to_string();
                                             }
                                             Err(err) => self.status = format!("AI draft failed: {err}"),
@@ -1702,9 +5385,10 @@ impl eframe::App for NativeApp {
                                         let body = m.body_text.as_deref().unwrap_or(&m.preview).to_string();
                                         (sender, m.subject.clone(), body)
                                     }).collect();
-                                    if !msgs.is_empty() {
+                                    let account_id = self.thread_messages.last().map(|m| m.account_id);
+                                    if let Some(account_id) = account_id.filter(|_| !msgs.is_empty()) {
                                         match self.runtime.block_on(self.ai.summarize_thread(
-                                            &msgs, self.ai_mode.clone(), self.ai_cloud_provider.clone(),
+                                            account_id, &msgs, self.ai_mode.clone(), self.ai_cloud_provider.clone(),
                                         )) {
                                             Ok((summary, _)) => {
                                                 self.ai_output = summary.output;
@@ -1717,6 +5401,36 @@ impl eframe::App for NativeApp {
                             });
                         });
 
+                        if !self.thread_messages.is_empty() {
+                            let mut add_label = None;
+                            let mut remove_label = None;
+                            ui.horizontal_wrapped(|ui| {
+                                ui.label(egui::RichText::new("Labels:").size(12.0));
+                                for label in &self.thread_labels {
+                                    if ui.small_button(format!("{label} ×"))
+                                        .on_hover_text("Remove this label from the thread")
+                                        .clicked()
+                                    {
+                                        remove_label = Some(label.clone());
+                                    }
+                                }
+                                let input = ui.add(
+                                    egui::TextEdit::singleline(&mut self.thread_label_draft)
+                                        .hint_text("Label thread…")
+                                        .desired_width(120.0),
+                                );
+                                if input.lost_focus()
+                                    && ui.input(|i| i.key_pressed(egui::Key::Enter))
+                                    && !self.thread_label_draft.trim().is_empty()
+                                {
+                                    add_label = Some(std::mem::take(&mut self.thread_label_draft));
+                                }
+                            });
+                            if add_label.is_some() || remove_label.is_some() {
+                                self.update_thread_labels(add_label, remove_label);
+                            }
+                        }
+
                         // Show AI summary if available.
                         if !self.ai_output.is_empty() {
                             ui.group(|ui| {
@@ -1743,13 +5457,25 @@ impl eframe::App for NativeApp {
 
                         let mut deferred_pin: Option<(Uuid, bool)> = None;
                         let mut deferred_snooze: Option<Uuid> = None;
-                        let mut deferred_save: Option<(Uuid, String)> = None;
-                        let mut deferred_open: Option<(Uuid, String)> = None;
+                        let mut deferred_clip: Option<Uuid> = None;
+                        let mut deferred_vip: Option<String> = None;
+                        let mut deferred_alias: Option<(Uuid, String)> = None;
+                        let mut deferred_attachment: Option<AttachmentAction> = None;
+                        let mut deferred_save_all: Option<Uuid> = None;
+                        let mut deferred_mute: Option<(Uuid, String, Option<MuteDuration>)> = None;
+                        let capabilities = self
+                            .selected_account
+                            .and_then(|id| self.accounts.iter().find(|account| account.id == id))
+                            .map(|account| self.email.capabilities(account));
+                        let can_move = capabilities.map_or(true, |caps| caps.move_messages);
+                        let mut mute_auto_archive = self.mute_auto_archive && can_move;
+                        let attachment_policy = attachment_policy_from_config(&self.config);
                         let mut deferred_read: Option<(Uuid, bool)> = None;
                         let mut next_message = None;
 
-                        egui::ScrollArea::vertical()
-                            .max_height(available_height - 20.0)
+                        let area = egui::ScrollArea::vertical().max_height(available_height - 20.0);
+                        let scroll = self
+                            .restored_scroll("messages", area)
                             .show(ui, |ui| {
                                 for (msg_id, pinned, subject, preview, received_at,
                                      from, _to, _cc, headers, attachments,
@@ -1772,7 +5498,7 @@ impl eframe::App for NativeApp {
                                         ui.horizontal(|ui| {
                                             ui.label(egui::RichText::new(&sender).strong().size(15.0));
                                             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                                                ui.label(egui::RichText::new(received_at.to_string()).size(12.0));
+                                                timefmt::label(ui, *received_at, 12.0);
                                             });
                                         });
                                         ui.add_space(4.0);
@@ -1790,9 +5516,60 @@ impl eframe::App for NativeApp {
                                                 if ui.small_button(read_label).clicked() {
                                                     deferred_read = Some((*msg_id, !_flags.seen));
                                                 }
-                                                if ui.small_button("Snooze").clicked() {
+                                                let snooze = ui.small_button("Snooze");
+                                                let snooze = if capabilities.is_some_and(|caps| !caps.server_snooze) {
+                                                    snooze.on_hover_text("Snooze is kept on this device only")
+                                                } else {
+                                                    snooze
+                                                };
+                                                if snooze.clicked() {
                                                     deferred_snooze = Some(*msg_id);
                                                 }
+                                                if ui.small_button("Save to Notes").clicked() {
+                                                    deferred_clip = Some(*msg_id);
+                                                }
+                                                if let Some(sender) = from.first() {
+                                                    let is_vip = self.vip_addresses.contains(&sender.address.to_lowercase());
+                                                    let vip_label = if is_vip { "★ VIP" } else { "☆ VIP" };
+                                                    if ui.small_button(vip_label).on_hover_text("Toggle VIP sender (Ctrl/Cmd+Shift+V)").clicked() {
+                                                        deferred_vip = Some(sender.address.clone());
+                                                    }
+                                                    if ui.small_button("Alias").on_hover_text("Generate a plus-address for this sender").clicked() {
+                                                        deferred_alias = Some((*msg_id, sender.address.clone()));
+                                                    }
+                                                    let address = sender.address.to_lowercase();
+                                                    let account_id = self
+                                                        .thread_messages
+                                                        .iter()
+                                                        .find(|m| m.id == *msg_id)
+                                                        .map(|m| m.account_id)
+                                                        .or(self.selected_account);
+                                                    if let Some(account_id) = account_id {
+                                                        if self.sender_mutes.iter().any(|mute| mute.account_id == account_id && mute.email == address) {
+                                                            if ui.small_button("Unmute").clicked() {
+                                                                deferred_mute = Some((account_id, address, None));
+                                                            }
+                                                        } else {
+                                                            ui.menu_button("Mute", |ui| {
+                                                                for (label, duration) in [
+                                                                    ("For 7 days", MuteDuration::Week),
+                                                                    ("For 30 days", MuteDuration::Month),
+                                                                    ("Forever", MuteDuration::Forever),
+                                                                ] {
+                                                                    if ui.button(label).clicked() {
+                                                                        deferred_mute = Some((account_id, address.clone(), Some(duration)));
+                                                                        ui.close_menu();
+                                                                    }
+                                                                }
+                                                                ui.separator();
+                                                                ui.add_enabled(can_move, egui::Checkbox::new(&mut mute_auto_archive, "Archive their mail"))
+                                                                    .on_disabled_hover_text("This account cannot move mail on the server");
+                                                            })
+                                                            .response
+                                                            .on_hover_text("Silence notifications from this sender on this account");
+                                                        }
+                                                    }
+                                                }
                                                 // 1-click unsubscribe: check List-Unsubscribe header
                                                 if let Some(unsub) = headers.get("List-Unsubscribe") {
                                                     if ui.small_button("Unsubscribe").on_hover_text(unsub).clicked() {
@@ -1816,7 +5593,12 @@ impl eframe::App for NativeApp {
                                             ui.add_space(4.0);
 
                                             if !attachments.is_empty() {
-                                                ui.label(egui::RichText::new("Attachments:").strong());
+                                                ui.horizontal(|ui| {
+                                                    ui.label(egui::RichText::new("Attachments:").strong());
+                                                    if attachments.len() > 1 && ui.small_button("Save all").clicked() {
+                                                        deferred_save_all = Some(*msg_id);
+                                                    }
+                                                });
                                                 for attachment in attachments {
                                                     ui.horizontal(|ui| {
                                                         let size_str = if attachment.size >= 1_048_576 {
@@ -1828,30 +5610,37 @@ impl eframe::App for NativeApp {
                                                         };
                                                         ui.label(format!("{} ({})", attachment.file_name, size_str));
 
-                                                        let ext = std::path::Path::new(&attachment.file_name)
-                                                            .extension()
-                                                            .and_then(|s| s.to_str())
-                                                            .unwrap_or("")
-                                                            .to_lowercase();
-                                                        let is_dangerous = matches!(ext.as_str(), "exe" | "sh" | "bat" | "cmd" | "vbs" | "scr" | "js" | "jar" | "app" | "scpt");
-                                                        
-                                                        if is_dangerous {
-                                                            ui.label(egui::RichText::new("Blocked").color(egui::Color32::RED).strong())
-                                                                .on_hover_text("This file type is blocked for security reasons.");
-                                                        } else {
-                                                            if ui.small_button("Save").clicked() {
-                                                                deferred_save = Some((attachment.id, attachment.file_name.clone()));
+                                                        let action = |open| AttachmentAction {
+                                                            message_id: *msg_id,
+                                                            attachment_id: attachment.id,
+                                                            file_name: attachment.file_name.clone(),
+                                                            open,
+                                                            confirmed: false,
+                                                        };
+                                                        match attachment_policy.check_name(&attachment.file_name) {
+                                                            AttachmentVerdict::Blocked { reason, can_override } => {
+                                                                ui.label(egui::RichText::new("Blocked").color(egui::Color32::RED).strong())
+                                                                    .on_hover_text(reason);
+                                                                if can_override && ui.small_button("Open anyway…").clicked() {
+                                                                    deferred_attachment = Some(action(true));
+                                                                }
                                                             }
-                                                            if ui.small_button("Open").clicked() {
-                                                                deferred_open = Some((attachment.id, attachment.file_name.clone()));
+                                                            _ => {
+                                                                if ui.small_button("Save").clicked() {
+                                                                    deferred_attachment = Some(action(false));
+                                                                }
+                                                                if ui.small_button("Open").clicked() {
+                                                                    deferred_attachment = Some(action(true));
+                                                                }
                                                             }
                                                         }
                                                     });
                                                 }
                                                 ui.add_space(8.0);
                                             }
+                                            let sanitizer = self.config.sanitizer;
                                             let rendered = body_html.as_deref()
-                                                .map(|html| html_render::render_html(ui, html))
+                                                .map(|html| html_render::render_html(ui, html, &sanitizer))
                                                 .unwrap_or(false);
                                             if !rendered {
                                                 let body = body_text.as_deref().unwrap_or(preview);
@@ -1867,6 +5656,7 @@ impl eframe::App for NativeApp {
                                     }
                                 }
                             });
+                        self.session.set_scroll_offset("messages", scroll.state.offset.y);
 
                         // Apply deferred actions after the borrow of thread_messages is released.
                         if let Some(message_id) = next_message {
@@ -1884,11 +5674,44 @@ impl eframe::App for NativeApp {
                         if let Some(msg_id) = deferred_snooze {
                             self.pending_snooze = Some(msg_id);
                         }
-                        if let Some(save) = deferred_save {
-                            self.pending_attachment_save = Some(save);
+                        if let Some(address) = deferred_vip {
+                            self.toggle_sender_vip(&address);
+                        }
+                        self.mute_auto_archive = mute_auto_archive;
+                        if let Some((account_id, address, duration)) = deferred_mute {
+                            self.set_sender_mute(account_id, &address, duration);
+                        }
+                        if let Some((msg_id, sender)) = deferred_alias {
+                            let account_id = self
+                                .thread_messages
+                                .iter()
+                                .find(|m| m.id == msg_id)
+                                .map(|m| m.account_id)
+                                .or(self.selected_account);
+                            if let Some(account_id) = account_id {
+                                self.create_sender_alias(ctx, account_id, &sender);
+                            }
+                        }
+                        if let Some(msg_id) = deferred_clip {
+                            self.note_clip_excerpt = self
+                                .thread_messages
+                                .iter()
+                                .find(|m| m.id == msg_id)
+                                .map(|m| m.body_text.clone().unwrap_or_else(|| m.preview.clone()))
+                                .unwrap_or_default();
+                            self.pending_note_clip = Some(msg_id);
+                        }
+                        if let Some(action) = deferred_attachment {
+                            self.pending_attachment = Some(action);
                         }
-                        if let Some(open_att) = deferred_open {
-                            self.pending_attachment_open = Some(open_att);
+                        if let Some(msg_id) = deferred_save_all {
+                            let messages: Vec<_> = self
+                                .thread_messages
+                                .iter()
+                                .filter(|m| m.id == msg_id)
+                                .cloned()
+                                .collect();
+                            self.save_all_attachments(&messages);
                         }
                     });
 
@@ -1946,11 +5769,31 @@ impl eframe::App for NativeApp {
                                 ui.separator();
                             }
 
-                            ui.label("To:");
-                            let to_response = ui.text_edit_singleline(&mut self.compose_to);
-                            // Contact autocomplete dropdown.
-                            if to_response.changed() {
-                                let query = self.compose_to.split(',').last().unwrap_or("").trim().to_string();
+                            if let Some(from) = self.compose_from.clone() {
+                                ui.horizontal(|ui| {
+                                    ui.label("From:");
+                                    egui::ComboBox::from_id_salt("compose_from")
+                                        .selected_text(format!("{} <{}>", from.name, from.address))
+                                        .show_ui(ui, |ui| {
+                                            for identity in &self.send_identities {
+                                                let label = format!("{} <{}>", identity.name, identity.address);
+                                                if ui.selectable_label(*identity == from, label).clicked() {
+                                                    self.compose_from = Some(identity.clone());
+                                                }
+                                            }
+                                        });
+                                });
+                                let warning = self.compose_reply.as_ref()
+                                    .zip(self.compose_from.as_ref())
+                                    .and_then(|(reply, from)| reply.account_warning(from));
+                                if let Some(warning) = warning {
+                                    ui.colored_label(egui::Color32::from_rgb(230, 160, 40), format!("⚠ {warning}"));
+                                }
+                            }
+
+                            // Recipient chips with contact autocomplete for the field being typed in.
+                            if self.compose_recipients.show(ui).is_some() {
+                                let query = self.compose_recipients.active_field_mut().input.trim().to_string();
                                 if query.len() >= 2 {
                                     self.contact_suggestions = self.runtime
                                         .block_on(self.email.autocomplete_contacts(&query, 8))
@@ -1969,26 +5812,52 @@ impl eframe::App for NativeApp {
                                             contact.email.clone()
                                         };
                                         if ui.selectable_label(false, &label).clicked() {
-                                            picked = Some(contact.email.clone());
+                                            picked = Some(match &contact.display_name {
+                                                Some(name) => format!("\"{}\" <{}>", name.replace('"', ""), contact.email),
+                                                None => contact.email.clone(),
+                                            });
                                         }
                                     }
-                                    if let Some(email) = picked {
-                                        // Append to compose_to, replacing the last partial token.
-                                        let parts: Vec<&str> = self.compose_to.split(',').collect();
-                                        if parts.len() > 1 {
-                                            let prefix = parts[..parts.len() - 1].join(",");
-                                            self.compose_to = format!("{prefix}, {email}, ");
-                                        } else {
-                                            self.compose_to = format!("{email}, ");
-                                        }
+                                    if let Some(recipient) = picked {
+                                        // Replace the partial text with a chip for the picked contact.
+                                        let field = self.compose_recipients.active_field_mut();
+                                        field.input.clear();
+                                        field.push_raw(&recipient);
                                         self.contact_suggestions.clear();
                                     }
                                 });
                             }
+                            let recipient_errors = self.compose_recipients.errors();
+                            for error in &recipient_errors {
+                                ui.colored_label(egui::Color32::from_rgb(220, 80, 80), error);
+                            }
                             ui.label("Subject:");
                             ui.text_edit_singleline(&mut self.compose_subject);
                             ui.label("Message:");
-                            ui.text_edit_multiline(&mut self.compose_body);
+                            if self.config.spellcheck.enabled && self.spellchecker.is_none() {
+                                let config_dir = self.config_manager.config_path().parent().unwrap_or(Path::new("."));
+                                self.spellchecker = Some(spellcheck::SpellChecker::load(&self.config.spellcheck.language, config_dir));
+                            }
+                            let spellchecker = self
+                                .spellchecker
+                                .as_ref()
+                                .filter(|checker| self.config.spellcheck.enabled && checker.is_available());
+                            let mut layouter = |ui: &egui::Ui, text: &str, wrap_width: f32| {
+                                let font_id = egui::TextStyle::Body.resolve(ui.style());
+                                let color = ui.visuals().widgets.inactive.text_color();
+                                let job = match spellchecker {
+                                    Some(checker) => checker.layout_job(text, font_id, color, wrap_width),
+                                    None => egui::text::LayoutJob::simple(text.to_string(), font_id, color, wrap_width),
+                                };
+                                ui.fonts(|fonts| fonts.layout_job(job))
+                            };
+                            let body_output = egui::TextEdit::multiline(&mut self.compose_body)
+                                .layouter(&mut layouter)
+                                .show(ui);
+                            self.compose_spelling_menu(&body_output);
+                            if body_output.response.changed() {
+                                self.expand_compose_snippet(ui.ctx(), body_output);
+                            }
                             
                             ui.horizontal(|ui| {
                                 ui.text_edit_singleline(&mut self.attachment_path);
@@ -2018,11 +5887,18 @@ impl eframe::App for NativeApp {
                             
                             ui.add_space(16.0);
                             ui.horizontal(|ui| {
-                                let send_btn = ui.button(egui::RichText::new("Send Now").strong().size(16.0).color(egui::Color32::WHITE));
+                                let send_btn = ui.add_enabled(
+                                    recipient_errors.is_empty(),
+                                    egui::Button::new(egui::RichText::new("Send Now").strong().size(16.0).color(egui::Color32::WHITE)),
+                                );
                                 if send_btn.clicked() {
                                     self.send_compose();
                                     close_window = true;
                                 }
+                                if next_working_time(&self.config.send_guardrails, &chrono::Local::now()).is_some() {
+                                    ui.checkbox(&mut self.compose_send_anyway, "Send now anyway")
+                                        .on_hover_text("Outside working hours this message would wait in the Outbox");
+                                }
                                 // Send Later: schedule for a future time.
                                 egui::ComboBox::from_id_salt("send_later")
                                     .selected_text("Send Later")
@@ -2054,6 +5930,10 @@ impl eframe::App for NativeApp {
                 if close_window {
                     show_compose = false;
                 }
+                if !show_compose {
+                    self.compose_from = None;
+                    self.compose_reply = None;
+                }
                 self.show_compose_window = show_compose;
 
                 // Undo send banner.
@@ -2110,6 +5990,272 @@ impl eframe::App for NativeApp {
                     }
                 }
 
+                // Action-item review dialog.
+                if self.task_review.is_some() {
+                    let mut create = false;
+                    let mut cancel = false;
+                    egui::Window::new("Review Action Items")
+                        .collapsible(false)
+                        .default_width(560.0)
+                        .show(ctx, |ui| {
+                            let Some(review) = self.task_review.as_mut() else {
+                                return;
+                            };
+                            ui.label(format!("From: {}", review.source.subject));
+                            ui.add_space(4.0);
+                            egui::Grid::new("task_review_grid").num_columns(4).striped(true).show(ui, |ui| {
+                                ui.label("");
+                                ui.label(egui::RichText::new("Title").strong());
+                                ui.label(egui::RichText::new("Due (YYYY-MM-DD HH:MM)").strong());
+                                ui.label(egui::RichText::new("Priority").strong());
+                                ui.end_row();
+                                for (index, item) in review.items.iter_mut().enumerate() {
+                                    ui.checkbox(&mut item.include, "");
+                                    ui.add(egui::TextEdit::singleline(&mut item.title).desired_width(260.0));
+                                    ui.add(egui::TextEdit::singleline(&mut item.due).desired_width(130.0));
+                                    egui::ComboBox::from_id_salt(("task_review_priority", index))
+                                        .selected_text(format!("{:?}", item.priority))
+                                        .show_ui(ui, |ui| {
+                                            for priority in [
+                                                cove_core::TaskPriority::Low,
+                                                cove_core::TaskPriority::Normal,
+                                                cove_core::TaskPriority::High,
+                                                cove_core::TaskPriority::Critical,
+                                            ] {
+                                                let label = format!("{priority:?}");
+                                                ui.selectable_value(&mut item.priority, priority, label);
+                                            }
+                                        });
+                                    ui.end_row();
+                                }
+                            });
+                            ui.add_space(8.0);
+                            let selected = review.items.iter().filter(|item| item.include).count();
+                            ui.horizontal(|ui| {
+                                if ui.add_enabled(selected > 0, egui::Button::new(format!("Create {selected} task(s)"))).clicked() {
+                                    create = true;
+                                }
+                                if ui.button("Cancel").clicked() {
+                                    cancel = true;
+                                }
+                            });
+                        });
+                    if create {
+                        self.create_reviewed_tasks();
+                    }
+                    if cancel {
+                        self.task_review = None;
+                    }
+                }
+
+                // Mail merge dialog.
+                if self.mail_merge.is_some() {
+                    let mut open = true;
+                    let mut queue = false;
+                    egui::Window::new("Mail Merge")
+                        .open(&mut open)
+                        .default_width(560.0)
+                        .vscroll(true)
+                        .show(ctx, |ui| {
+                            let Some(draft) = self.mail_merge.as_mut() else {
+                                return;
+                            };
+                            ui.label(egui::RichText::new(&draft.template.name).strong());
+                            let variables: Vec<String> = merge_variables(&draft.template.subject)
+                                .into_iter()
+                                .chain(merge_variables(&draft.template.body_text))
+                                .collect();
+                            if variables.is_empty() {
+                                ui.label("This template has no {{variables}}.");
+                            } else {
+                                ui.label(format!("Variables: {}", variables.join(", ")));
+                            }
+
+                            ui.horizontal(|ui| {
+                                ui.label("Recipients CSV (needs an \"email\" column):");
+                                if ui.button("Load CSV…").clicked() {
+                                    if let Some(path) = rfd::FileDialog::new().add_filter("CSV", &["csv"]).pick_file() {
+                                        match std::fs::read_to_string(&path) {
+                                            Ok(csv) => {
+                                                draft.csv = csv;
+                                                draft.preview_index = 0;
+                                            }
+                                            Err(err) => self.status = format!("read CSV failed: {err}"),
+                                        }
+                                    }
+                                }
+                            });
+                            ui.add(
+                                egui::TextEdit::multiline(&mut draft.csv)
+                                    .desired_rows(6)
+                                    .desired_width(f32::INFINITY)
+                                    .code_editor(),
+                            );
+                            ui.horizontal(|ui| {
+                                ui.label("Send at most");
+                                ui.add(egui::DragValue::new(&mut draft.per_minute).range(1..=120));
+                                ui.label("per minute");
+                            });
+
+                            ui.separator();
+                            match parse_merge_csv(&draft.csv) {
+                                Ok(table) if !table.rows.is_empty() => {
+                                    let count = table.rows.len();
+                                    draft.preview_index = draft.preview_index.min(count - 1);
+                                    ui.horizontal(|ui| {
+                                        if ui.small_button("◀").clicked() {
+                                            draft.preview_index = draft.preview_index.saturating_sub(1);
+                                        }
+                                        ui.label(format!("Preview {} of {count}", draft.preview_index + 1));
+                                        if ui.small_button("▶").clicked() && draft.preview_index + 1 < count {
+                                            draft.preview_index += 1;
+                                        }
+                                    });
+                                    let fields = &table.rows[draft.preview_index];
+                                    let to = table
+                                        .address_column()
+                                        .and_then(|column| fields.get(column))
+                                        .cloned()
+                                        .unwrap_or_else(|| "(no email column)".to_string());
+                                    let rendered = render_merge_text(&draft.template.subject, fields)
+                                        .and_then(|subject| {
+                                            render_merge_text(&draft.template.body_text, fields)
+                                                .map(|body| (subject, body))
+                                        });
+                                    ui.label(format!("To: {to}"));
+                                    match rendered {
+                                        Ok((subject, body)) => {
+                                            ui.label(format!("Subject: {subject}"));
+                                            ui.group(|ui| {
+                                                ui.label(body);
+                                            });
+                                            queue = ui.button("Queue send").clicked();
+                                        }
+                                        Err(err) => {
+                                            ui.colored_label(egui::Color32::from_rgb(220, 80, 80), err.to_string());
+                                        }
+                                    }
+                                }
+                                Ok(_) => {
+                                    ui.label("Paste or load a CSV with a header row to preview messages.");
+                                }
+                                Err(err) => {
+                                    ui.colored_label(egui::Color32::from_rgb(220, 80, 80), err.to_string());
+                                }
+                            }
+
+                            ui.separator();
+                            ui.label(egui::RichText::new("Campaigns").strong());
+                            let campaigns = self
+                                .runtime
+                                .block_on(self.storage.list_mail_merges(None))
+                                .unwrap_or_default();
+                            if campaigns.is_empty() {
+                                ui.label("No mail merges yet.");
+                            }
+                            let mut retry = None;
+                            let mut delete = None;
+                            for campaign in &campaigns {
+                                let recipients = self
+                                    .runtime
+                                    .block_on(self.storage.list_mail_merge_recipients(campaign.id, None, i64::MAX))
+                                    .unwrap_or_default();
+                                let count = |status: cove_core::MergeRecipientStatus| {
+                                    recipients.iter().filter(|r| r.status == status).count()
+                                };
+                                let (sent, failed, pending) = (
+                                    count(cove_core::MergeRecipientStatus::Sent),
+                                    count(cove_core::MergeRecipientStatus::Failed),
+                                    count(cove_core::MergeRecipientStatus::Pending),
+                                );
+                                egui::CollapsingHeader::new(format!(
+                                    "{} — {sent} sent, {pending} pending, {failed} failed",
+                                    campaign.name
+                                ))
+                                .id_salt(campaign.id)
+                                .show(ui, |ui| {
+                                    ui.horizontal(|ui| {
+                                        if failed > 0 && ui.small_button("Retry failed").clicked() {
+                                            retry = Some(campaign.id);
+                                        }
+                                        if ui.small_button("Delete").clicked() {
+                                            delete = Some(campaign.id);
+                                        }
+                                    });
+                                    for recipient in &recipients {
+                                        let status = match recipient.status {
+                                            cove_core::MergeRecipientStatus::Pending => "pending".to_string(),
+                                            cove_core::MergeRecipientStatus::Sent => recipient
+                                                .sent_at
+                                                .map(|at| format!("sent {}", at.with_timezone(&chrono::Local).format("%H:%M")))
+                                                .unwrap_or_else(|| "sent".to_string()),
+                                            cove_core::MergeRecipientStatus::Failed => format!(
+                                                "failed: {}",
+                                                recipient.error.as_deref().unwrap_or("unknown error")
+                                            ),
+                                        };
+                                        ui.label(format!("{} — {status}", recipient.email));
+                                    }
+                                });
+                            }
+                            if let Some(campaign_id) = retry {
+                                match self.runtime.block_on(self.email.retry_failed_mail_merge(campaign_id)) {
+                                    Ok(count) => self.status = format!("Requeued {count} recipient(s)"),
+                                    Err(err) => self.status = format!("retry failed: {err}"),
+                                }
+                            }
+                            if let Some(campaign_id) = delete {
+                                let _ = self.runtime.block_on(self.storage.delete_mail_merge(campaign_id));
+                            }
+                        });
+                    if queue {
+                        self.queue_mail_merge();
+                    }
+                    if !open {
+                        self.mail_merge = None;
+                    }
+                }
+
+                self.show_ranking_debug(ctx);
+                self.show_protocol_console(ctx);
+
+                // Save-to-notes dialog.
+                if let Some(msg_id) = self.pending_note_clip {
+                    let mut close_clip = false;
+                    egui::Window::new("Save to Notes")
+                        .collapsible(false)
+                        .default_width(480.0)
+                        .show(ctx, |ui| {
+                            ui.label("Trim the text below to clip a selection, or save the whole message.");
+                            egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                                ui.add(egui::TextEdit::multiline(&mut self.note_clip_excerpt).desired_width(f32::INFINITY));
+                            });
+                            ui.horizontal(|ui| {
+                                let mut clip: Option<Option<String>> = None;
+                                if ui.button("Save whole message").clicked() {
+                                    clip = Some(None);
+                                }
+                                if ui.button("Save excerpt").clicked() {
+                                    clip = Some(Some(self.note_clip_excerpt.clone()));
+                                }
+                                if ui.button("Cancel").clicked() {
+                                    close_clip = true;
+                                }
+                                if let Some(selection) = clip {
+                                    match self.runtime.block_on(self.email.clip_to_note(msg_id, selection.as_deref())) {
+                                        Ok(note) => self.status = format!("Saved to Notes: {}", note.title),
+                                        Err(err) => self.status = format!("save to notes failed: {err}"),
+                                    }
+                                    close_clip = true;
+                                }
+                            });
+                        });
+                    if close_clip {
+                        self.pending_note_clip = None;
+                        self.note_clip_excerpt.clear();
+                    }
+                }
+
                 // Command palette.
                 if self.show_command_palette {
                     egui::Window::new("Command Palette")
@@ -2183,7 +6329,7 @@ impl eframe::App for NativeApp {
                 
                 egui::SidePanel::left("contacts_panel")
                     .resizable(true)
-                    .default_width(300.0)
+                    .default_width(self.session.panel_width("contacts_panel", 300.0))
                     .width_range(200.0..=500.0)
                     .frame(egui::Frame::default().inner_margin(8.0))
                     .show_inside(ui, |ui| {
@@ -2261,6 +6407,7 @@ impl eframe::App for NativeApp {
                                                     .corner_radius(12.0);
                                                 frame.show(ui, |ui| {
                                                     ui.label(egui::RichText::new(ag_chat_bubble_text(message)).color(ui.visuals().selection.stroke.color).size(14.0));
+                                                    timefmt::label(ui, message.received_at, 11.0);
                                                 });
                                             });
                                         } else {
@@ -2270,6 +6417,7 @@ impl eframe::App for NativeApp {
                                                     .corner_radius(12.0);
                                                 frame.show(ui, |ui| {
                                                     ui.label(egui::RichText::new(ag_chat_bubble_text(message)).size(14.0));
+                                                    timefmt::label(ui, message.received_at, 11.0);
                                                 });
                                             });
                                         }
@@ -2304,6 +6452,9 @@ impl eframe::App for NativeApp {
                                 ui.label("No calendar events found. Try syncing first.");
                             }
                             let mut rsvp_change: Option<(Uuid, cove_core::RsvpStatus)> = None;
+                            let time_blocks = self.runtime
+                                .block_on(self.storage.list_task_time_blocks(account_id))
+                                .unwrap_or_default();
 
                             egui::ScrollArea::vertical().show(ui, |ui| {
                                 for event in &events {
@@ -2313,25 +6464,49 @@ impl eframe::App for NativeApp {
                                             if event.all_day {
                                                 ui.label(egui::RichText::new("All Day").size(11.0).italics());
                                             }
+                                            if let Some(block) = time_blocks.iter().find(|block| {
+                                                block.calendar_id == event.calendar_id
+                                                    && block.event_remote_id == event.remote_id
+                                            }) {
+                                                let label = if block.completed { "Time block ✓ done" } else { "Time block" };
+                                                ui.label(egui::RichText::new(label).size(11.0).italics());
+                                            }
                                         });
                                         ui.horizontal(|ui| {
                                             ui.label(egui::RichText::new(
                                                 format!("{} → {}",
-                                                    event.starts_at.format("%b %d %H:%M"),
-                                                    event.ends_at.format("%H:%M"))
+                                                    event.starts_at.with_timezone(&chrono::Local).format("%b %d %H:%M"),
+                                                    event.ends_at.with_timezone(&chrono::Local).format("%H:%M"))
                                             ).size(13.0));
                                             if let Some(loc) = &event.location {
                                                 ui.label(egui::RichText::new(format!("@ {loc}")).size(13.0));
                                             }
                                         });
+                                        if let Some(url) = &event.online_meeting_url {
+                                            ui.hyperlink_to("Join online meeting", url);
+                                        }
                                         // Recurrence display
                                         if let Some(rrule) = &event.recurrence_rule {
                                             ui.label(egui::RichText::new(format!("Repeats: {rrule}")).size(11.0).italics());
                                         }
-                                        // Attendees
+                                        // Attendees, with any replies received by email
                                         if !event.attendees.is_empty() {
+                                            let responses = self
+                                                .runtime
+                                                .block_on(self.storage.list_attendee_responses(event.id))
+                                                .unwrap_or_default();
+                                            let attendees = event
+                                                .attendees
+                                                .iter()
+                                                .map(|attendee| {
+                                                    match responses.iter().find(|r| r.email.eq_ignore_ascii_case(attendee)) {
+                                                        Some(response) => format!("{attendee} ({:?})", response.status),
+                                                        None => attendee.clone(),
+                                                    }
+                                                })
+                                                .collect::<Vec<_>>();
                                             ui.label(egui::RichText::new(
-                                                format!("Attendees: {}", event.attendees.join(", "))
+                                                format!("Attendees: {}", attendees.join(", "))
                                             ).size(11.0));
                                         }
                                         // RSVP buttons
@@ -2365,6 +6540,9 @@ impl eframe::App for NativeApp {
                             ui.label(format!("Calendar load failed: {err}"));
                         }
                     }
+
+                    ui.separator();
+                    self.show_scheduling_polls(ui, account_id);
                 } else {
                     ui.label("Select an account to view calendar events.");
                 }
@@ -2372,18 +6550,164 @@ impl eframe::App for NativeApp {
             View::Notes => {
                 ui.heading("Notes Workspace");
                 ui.add_space(8.0);
-                ui.label("A dedicated space for rich-text notes synced across accounts.");
+                let notes = self.runtime.block_on(self.storage.list_notes()).unwrap_or_default();
+                if notes.is_empty() {
+                    ui.label("No notes yet. Use \"Save to Notes\" on a message to clip it here.");
+                }
+                let mut delete_note = None;
+                let mut save_attachment = None;
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for note in &notes {
+                        ui.group(|ui| {
+                            ui.set_width(ui.available_width());
+                            ui.horizontal(|ui| {
+                                ui.label(egui::RichText::new(&note.title).strong().size(15.0));
+                                timefmt::label(ui, note.updated_at, 11.0);
+                                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                    if ui.small_button("Delete").clicked() {
+                                        delete_note = Some(note.id);
+                                    }
+                                });
+                            });
+                            egui::CollapsingHeader::new("Show note")
+                                .id_salt(note.id)
+                                .show(ui, |ui| {
+                                    ui.label(egui::RichText::new(&note.body_markdown).monospace().size(12.0));
+                                });
+                            let attachments = self.runtime
+                                .block_on(self.storage.list_note_attachments(note.id))
+                                .unwrap_or_default();
+                            for attachment in &attachments {
+                                ui.horizontal(|ui| {
+                                    ui.label(format!("📎 {} ({} B)", attachment.file_name, attachment.size));
+                                    if ui.small_button("Save").clicked() {
+                                        save_attachment = Some((attachment.id, attachment.file_name.clone()));
+                                    }
+                                });
+                            }
+                        });
+                        ui.add_space(4.0);
+                    }
+                });
+                if let Some(note_id) = delete_note {
+                    let _ = self.runtime.block_on(self.storage.delete_note(note_id));
+                }
+                if let Some((attachment_id, file_name)) = save_attachment {
+                    if let Some(path) = rfd::FileDialog::new().set_file_name(&file_name).save_file() {
+                        match self.runtime.block_on(self.storage.get_note_attachment_content(attachment_id)) {
+                            Ok(Some(data)) => match std::fs::write(&path, &data) {
+                                Ok(()) => self.status = format!("Saved to {}", path.display()),
+                                Err(err) => self.status = format!("Save failed: {err}"),
+                            },
+                            Ok(None) => self.status = "Note attachment not found.".to_string(),
+                            Err(err) => self.status = format!("Error loading attachment: {err}"),
+                        }
+                    }
+                }
             }
             View::Tasks => {
                 ui.heading("Tasks");
                 if let Some(account) = self.account() {
                     let account_id = account.id;
+                    let lists = self
+                        .runtime
+                        .block_on(self.storage.list_task_lists(account_id))
+                        .unwrap_or_default();
+                    let mut deferred_move: Option<(Uuid, String)> = None;
+                    let mut deferred_rename: Option<(String, String)> = None;
+                    let mut deferred_delete: Option<String> = None;
+                    let mut create_list = false;
+                    let mut refresh_lists = false;
+
+                    egui::SidePanel::left("task_lists_panel")
+                        .resizable(true)
+                        .default_width(self.session.panel_width("task_lists_panel", 180.0))
+                        .width_range(140.0..=320.0)
+                        .frame(egui::Frame::default().inner_margin(8.0))
+                        .show_inside(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.heading(egui::RichText::new("Lists").strong());
+                                if ui.small_button("⟳").on_hover_text("Refresh lists from the provider").clicked() {
+                                    refresh_lists = true;
+                                }
+                            });
+                            ui.add_space(4.0);
+                            if ui.selectable_label(self.selected_task_list.is_none(), "All lists").clicked() {
+                                self.selected_task_list = None;
+                            }
+                            for list in &lists {
+                                let is_selected = self.selected_task_list.as_deref() == Some(list.list_id.as_str());
+                                // Each list is a drop target for tasks dragged from the list on the right.
+                                let (_, dropped) = ui.dnd_drop_zone::<Uuid, ()>(
+                                    egui::Frame::default().inner_margin(egui::Margin::symmetric(4, 2)),
+                                    |ui| {
+                                        if let Some((editing_id, name)) = self.renaming_task_list.as_mut() {
+                                            if *editing_id == list.list_id {
+                                                let response = ui.text_edit_singleline(name);
+                                                if response.lost_focus() {
+                                                    if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                                                        deferred_rename = Some((editing_id.clone(), name.clone()));
+                                                    }
+                                                    self.renaming_task_list = None;
+                                                }
+                                                return;
+                                            }
+                                        }
+                                        let label = if list.is_default {
+                                            format!("{} (default)", list.name)
+                                        } else {
+                                            list.name.clone()
+                                        };
+                                        let response = ui.selectable_label(is_selected, label);
+                                        if response.clicked() {
+                                            self.selected_task_list = Some(list.list_id.clone());
+                                        }
+                                        response.context_menu(|ui| {
+                                            if ui.add_enabled(!list.is_default, egui::Button::new("Rename")).clicked() {
+                                                self.renaming_task_list = Some((list.list_id.clone(), list.name.clone()));
+                                                ui.close_menu();
+                                            }
+                                            if ui.add_enabled(!list.is_default, egui::Button::new("Delete list and its tasks")).clicked() {
+                                                deferred_delete = Some(list.list_id.clone());
+                                                ui.close_menu();
+                                            }
+                                        });
+                                    },
+                                );
+                                if let Some(task_id) = dropped {
+                                    deferred_move = Some((*task_id, list.list_id.clone()));
+                                }
+                            }
+                            ui.separator();
+                            ui.horizontal(|ui| {
+                                let response = ui.add(
+                                    egui::TextEdit::singleline(&mut self.new_task_list_name)
+                                        .hint_text("New list")
+                                        .desired_width(110.0),
+                                );
+                                let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                                if (ui.small_button("Add").clicked() || submitted)
+                                    && !self.new_task_list_name.trim().is_empty()
+                                {
+                                    create_list = true;
+                                }
+                            });
+                        });
+
                     // Priority view toggle (using priority-sorted query).
                     match self.runtime.block_on(self.storage.list_tasks_by_priority(account_id)) {
-                        Ok(tasks) => {
+                        Ok(mut tasks) => {
+                            if let Some(list_id) = &self.selected_task_list {
+                                tasks.retain(|task| &task.list_id == list_id);
+                            }
                             if tasks.is_empty() {
                                 ui.label("No tasks. Try syncing first.");
                             }
+                            let show_list_names = self.selected_task_list.is_none() && lists.len() > 1;
+                            let mut deferred_complete: Option<(cove_core::ReminderTask, bool)> = None;
+                            let mut deferred_block: Option<cove_core::ReminderTask> = None;
+                            let mut deferred_estimate: Option<(Uuid, u32)> = None;
+                            let mut deferred_open_thread: Option<(Uuid, String)> = None;
                             egui::ScrollArea::vertical().show(ui, |ui| {
                                 for task in &tasks {
                                     let priority_color = match task.priority {
@@ -2395,6 +6719,11 @@ impl eframe::App for NativeApp {
                                     let completed = task.completed_at.is_some();
                                     ui.group(|ui| {
                                         ui.horizontal(|ui| {
+                                            ui.dnd_drag_source(egui::Id::new(("task_drag", task.id)), task.id, |ui| {
+                                                ui.label(egui::RichText::new("⠿").weak());
+                                            })
+                                            .response
+                                            .on_hover_text("Drag onto a list to move this task");
                                             ui.label(egui::RichText::new(format!("[{:?}]", task.priority))
                                                 .size(11.0).color(priority_color));
                                             let title_text = egui::RichText::new(&task.title).size(14.0);
@@ -2408,6 +6737,32 @@ impl eframe::App for NativeApp {
                                                     format!("Due: {}", due.format("%b %d %H:%M"))
                                                 ).size(11.0));
                                             }
+                                            if show_list_names {
+                                                if let Some(list_name) = &task.list_name {
+                                                    ui.label(egui::RichText::new(list_name).size(11.0).weak());
+                                                }
+                                            }
+                                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                                let done_label = if completed { "Reopen" } else { "Done" };
+                                                if ui.small_button(done_label).clicked() {
+                                                    deferred_complete = Some((task.clone(), !completed));
+                                                }
+                                                if !completed && ui.small_button("Time block").on_hover_text("Reserve calendar time for this task").clicked() {
+                                                    deferred_block = Some(task.clone());
+                                                }
+                                                let mut minutes = task.estimate_minutes.unwrap_or(0);
+                                                if !completed && ui.add(egui::DragValue::new(&mut minutes).range(0..=480).speed(5).suffix(" min"))
+                                                    .on_hover_text("Estimate; sizes the time block (0 clears it)")
+                                                    .changed()
+                                                {
+                                                    deferred_estimate = Some((task.id, minutes));
+                                                }
+                                                if let Some(link) = task.notes.as_deref().and_then(parse_thread_link) {
+                                                    if ui.small_button("Open email").on_hover_text("Go to the thread this task came from").clicked() {
+                                                        deferred_open_thread = Some(link);
+                                                    }
+                                                }
+                                            });
                                         });
 
                                         // Subtasks
@@ -2431,11 +6786,43 @@ impl eframe::App for NativeApp {
                                     });
                                 }
                             });
+                            if let Some((task, done)) = deferred_complete {
+                                self.set_task_completed(task, done);
+                            }
+                            if let Some(task) = deferred_block {
+                                self.time_block_task(&task);
+                            }
+                            if let Some((task_id, minutes)) = deferred_estimate {
+                                if let Err(err) = self.runtime.block_on(self.tasks.set_estimate(task_id, Some(minutes))) {
+                                    self.status = format!("estimate update failed: {err}");
+                                }
+                            }
+                            if let Some((account_id, thread_id)) = deferred_open_thread {
+                                self.open_thread_link(account_id, thread_id);
+                            }
+                            if let Some((task_id, list_id)) = deferred_move {
+                                if let Some(task) = tasks.iter().find(|task| task.id == task_id) {
+                                    self.move_task_to_list(task, &list_id);
+                                }
+                            }
                         }
                         Err(err) => {
                             ui.label(format!("load tasks failed: {err}"));
                         }
                     }
+
+                    if let Some((list_id, name)) = deferred_rename {
+                        self.rename_task_list(account_id, &list_id, &name);
+                    }
+                    if let Some(list_id) = deferred_delete {
+                        self.delete_task_list(account_id, &list_id);
+                    }
+                    if create_list {
+                        self.create_task_list(account_id);
+                    }
+                    if refresh_lists {
+                        self.refresh_task_lists(account_id);
+                    }
                 }
             }
             View::Ai => {
@@ -2470,9 +6857,104 @@ impl eframe::App for NativeApp {
                     ui.checkbox(&mut b2, "Enable AI Draft Suggestions");
                     ui.checkbox(&mut b3, "Enable AI Inbox Categorization (Experimental)");
                 });
+
+                ui.add_space(8.0);
+                ui.group(|ui| {
+                    ui.label(egui::RichText::new("Cloud Privacy Redaction").strong());
+                    ui.label(egui::RichText::new("Replace identifiers with placeholders before text leaves this device; originals are restored in the AI response.").size(11.0));
+                    let redaction = &mut self.config.ai.cloud.redaction;
+                    let mut changed = false;
+                    changed |= ui.checkbox(&mut redaction.enabled, "Redact cloud prompts").changed();
+                    ui.add_enabled_ui(redaction.enabled, |ui| {
+                        changed |= ui.checkbox(&mut redaction.redact_emails, "Email addresses").changed();
+                        changed |= ui.checkbox(&mut redaction.redact_phone_numbers, "Phone numbers").changed();
+                        changed |= ui.checkbox(&mut redaction.redact_numeric_ids, "Numeric identifiers (5+ digits)").changed();
+                        ui.label("Custom patterns (one regular expression per line):");
+                        let mut patterns = redaction.custom_patterns.join("\n");
+                        if ui.text_edit_multiline(&mut patterns).lost_focus() {
+                            redaction.custom_patterns = patterns
+                                .lines()
+                                .map(str::trim)
+                                .filter(|line| !line.is_empty())
+                                .map(str::to_string)
+                                .collect();
+                            changed = true;
+                        }
+                    });
+                    if changed {
+                        self.ai.update_config(ai_runtime_from_config(&self.config));
+                        if let Err(err) = self.config_manager.save(&self.config) {
+                            self.status = format!("save config failed: {err}");
+                        }
+                    }
+                });
+
+                ui.add_space(8.0);
+                ui.group(|ui| {
+                    ui.label(egui::RichText::new("Account AI Policy").strong());
+                    ui.label(egui::RichText::new("Choose per account whether message content may go to cloud providers, stay on this device, or skip AI entirely.").size(11.0));
+                    let mut changed = false;
+                    for account in &self.accounts {
+                        let key = account.id.to_string();
+                        let mut policy = self.config.ai.account_policy(&key);
+                        ui.horizontal(|ui| {
+                            ui.label(&account.email_address);
+                            egui::ComboBox::from_id_salt(("account_ai_policy", account.id))
+                                .selected_text(policy.label())
+                                .show_ui(ui, |ui| {
+                                    for option in AccountAiPolicy::ALL {
+                                        changed |= ui.selectable_value(&mut policy, option, option.label()).changed();
+                                    }
+                                });
+                        });
+                        self.config.ai.set_account_policy(&key, policy);
+                    }
+                    if self.accounts.is_empty() {
+                        ui.label("No accounts yet.");
+                    }
+                    if changed {
+                        self.ai.update_config(ai_runtime_from_config(&self.config));
+                        if let Err(err) = self.config_manager.save(&self.config) {
+                            self.status = format!("save config failed: {err}");
+                        }
+                    }
+                });
                 
                 ui.separator();
 
+                ui.heading("Unread Mail Digest");
+                ui.label(egui::RichText::new(format!(
+                    "Summarizes up to {} unread threads, {} at a time and {} per minute. Threads already summarized are only re-run when new mail arrives.",
+                    self.config.ai.digest.max_threads,
+                    self.config.ai.digest.max_concurrency,
+                    self.config.ai.digest.requests_per_minute,
+                )).size(11.0));
+                ui.horizontal(|ui| {
+                    if ui.button("Summarize my unread mail").clicked() {
+                        self.summarize_unread_mail();
+                    }
+                    if self.inbox_digest.is_some() {
+                        if ui.button("Copy digest").clicked() {
+                            if let Some(digest) = &self.inbox_digest {
+                                ui.ctx().copy_text(digest.markdown.clone());
+                            }
+                        }
+                        if ui.button("Clear").clicked() {
+                            self.inbox_digest = None;
+                        }
+                    }
+                });
+                if let Some(digest) = &self.inbox_digest {
+                    egui::ScrollArea::vertical()
+                        .id_salt("inbox_digest")
+                        .max_height(320.0)
+                        .show(ui, |ui| {
+                            ui.label(egui::RichText::new(&digest.markdown).size(13.0));
+                        });
+                }
+
+                ui.separator();
+
                 ui.heading("Test AI");
                 ui.text_edit_singleline(&mut self.ai_subject);
                 ui.text_edit_multiline(&mut self.ai_body);
@@ -2520,6 +7002,35 @@ impl eframe::App for NativeApp {
                 ui.label("Secrets are stored in OS keychain namespaces.");
                 ui.separator();
                 
+                ui.heading("Search Index");
+                let progress = self.storage.index_progress();
+                if progress.is_idle() {
+                    ui.label("All messages are indexed.");
+                } else {
+                    ui.label(format!(
+                        "{} of {} messages indexed{}",
+                        group_thousands(progress.indexed),
+                        group_thousands(progress.total()),
+                        if progress.paused { " (paused)" } else { "" }
+                    ));
+                }
+                ui.horizontal(|ui| {
+                    if progress.paused {
+                        if ui.button("Resume indexing").clicked() {
+                            self.storage.resume_indexing();
+                        }
+                    } else if ui.button("Pause indexing").clicked() {
+                        self.storage.pause_indexing();
+                    }
+                    if ui.button("Rebuild search index").on_hover_text("Clear the index and re-index every stored message in the background").clicked() {
+                        match self.runtime.block_on(self.storage.rebuild_search_index()) {
+                            Ok(count) => self.status = format!("Re-indexing {} messages", group_thousands(count)),
+                            Err(err) => self.status = format!("Rebuild index failed: {err}"),
+                        }
+                    }
+                });
+                ui.separator();
+
                 ui.heading("Settings Export / Import");
                 ui.label("Encrypt and backup your configuration, accounts, and secrets.");
 
@@ -2551,6 +7062,16 @@ impl eframe::App for NativeApp {
                     ui.checkbox(&mut b1, "Block remote trackers/pixels automatically");
                 });
                 
+                ui.add_space(8.0);
+                ui.heading("Message Export (JSON)");
+                ui.label("One JSON object per line, for your own analytics or scripts.");
+                ui.checkbox(&mut self.message_export_selected_account, "Selected account only");
+                ui.checkbox(&mut self.message_export_redaction.addresses, "Replace addresses with pseudonyms");
+                ui.checkbox(&mut self.message_export_redaction.bodies, "Leave out message bodies");
+                if ui.button("Export Messages...").clicked() {
+                    self.export_messages_json();
+                }
+
                 ui.add_space(8.0);
                 ui.heading("Account Purge");
                 if ui.button(egui::RichText::new("Purge Local Cache & Secrets Data for Selected Account").color(egui::Color32::RED)).clicked() {
@@ -2573,31 +7094,13 @@ impl eframe::App for NativeApp {
                             self.status = "Gathering settings...".to_string();
                             let mut accounts_export = Vec::new();
                             for account in &self.accounts {
-                                let protocol_json = match self.runtime.block_on(self.storage.account_protocol_settings(account.id)) {
-                                    Ok(json) => json,
+                                match self.gather_account_export(account, true) {
+                                    Ok(exported) => accounts_export.push(exported),
                                     Err(err) => {
                                         self.status = format!("Export error: {}", err);
                                         break; // In real app, might just skip or fail gracefully
                                     }
-                                };
-                                
-                                let mut secrets = BTreeMap::new();
-                                // Best effort extraction of associated secrets.
-                                for ns in ["account_password", "oauth_refresh_token", "oauth_access_token"] {
-                                    let key = SecretKey {
-                                        namespace: ns.to_string(),
-                                        id: account.id.to_string()
-                                    };
-                                    if let Ok(Some(secret_val)) = self.secrets.get(&key) {
-                                        secrets.insert(ns.to_string(), secret_val);
-                                    }
                                 }
-                                
-                                accounts_export.push(export::AccountExport {
-                                    account: account.clone(),
-                                    protocol_settings_json: protocol_json,
-                                    secrets,
-                                });
                             }
                             
                             let payload = export::ExportPayload {
@@ -2634,6 +7137,7 @@ impl eframe::App for NativeApp {
                             match export::import_settings(&password_clone, &path) {
                                 Ok(payload) => {
                                     self.config = payload.config;
+                                    self.email.set_sanitizer_policy(self.config.sanitizer);
                                     // Save config to disk
                                     if let Err(err) = self.config_manager.save(&self.config) {
                                         self.status = format!("Failed to save imported config: {}", err);
@@ -2641,33 +7145,11 @@ impl eframe::App for NativeApp {
                                         // Restore accounts and secrets
                                         let mut import_success = true;
                                         for acc_export in payload.accounts {
-                                            if let Err(err) = self.runtime.block_on(self.storage.upsert_account(&acc_export.account)) {
-                                                self.status = format!("Failed to import account {}: {}", acc_export.account.id, err);
+                                            if let Err(err) = self.restore_account_export(acc_export) {
+                                                self.status = err;
                                                 import_success = false;
                                                 break;
                                             }
-                                            
-                                            // Restore protocol settings
-                                            if let Some(settings_json) = acc_export.protocol_settings_json {
-                                                if let Err(err) = self.runtime.block_on(self.storage.upsert_account_protocol_settings(acc_export.account.id, &settings_json)) {
-                                                    self.status = format!("Failed to import settings for account {}: {}", acc_export.account.id, err);
-                                                    import_success = false;
-                                                    break;
-                                                }
-                                            }
-                                            
-                                            // Restore secrets
-                                            for (ns, val) in acc_export.secrets {
-                                                let key = SecretKey {
-                                                    namespace: ns,
-                                                    id: acc_export.account.id.to_string(),
-                                                };
-                                                if let Err(err) = set_secret_guarded(&self.secrets, key, &val) {
-                                                     self.status = format!("Failed to import secret for account {}: {}", acc_export.account.id, err);
-                                                     import_success = false;
-                                                     break;
-                                                }
-                                            }
                                         }
                                         
                                         if import_success {
@@ -2685,6 +7167,26 @@ impl eframe::App for NativeApp {
                     }
                 });
                 
+                ui.add_space(8.0);
+                ui.heading("Single Account Export");
+                ui.label("Moves the selected account to another machine, or shares its setup without passwords or mail. Uses the passwords above.");
+                ui.checkbox(&mut self.account_export_secrets, "Include passwords and tokens");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.account_export_mail, "Include mail received since");
+                    ui.add_enabled(
+                        self.account_export_mail,
+                        egui::TextEdit::singleline(&mut self.account_export_since).hint_text("YYYY-MM-DD, empty for all").desired_width(140.0),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(self.selected_account.is_some(), egui::Button::new("Export Selected Account")).clicked() {
+                        self.export_selected_account();
+                    }
+                    if ui.button("Import Account").clicked() {
+                        self.import_account_bundle();
+                    }
+                });
+
                 ui.separator();
                 egui::ComboBox::from_label("Provider")
                     .selected_text(format!("{:?}", self.oauth.provider))
@@ -2740,6 +7242,35 @@ impl eframe::App for NativeApp {
             }
             View::Settings => {
                 ui.heading("Settings");
+                if self.secrets.is_locked() || self.secrets.has_pending_migration() {
+                    ui.horizontal(|ui| {
+                        let note = if self.secrets.is_locked() {
+                            "Secrets are locked; accounts cannot sync until they are unlocked."
+                        } else {
+                            "Secrets in the encrypted file can be moved into the system keychain."
+                        };
+                        ui.label(egui::RichText::new(note).color(egui::Color32::from_rgb(230, 160, 60)));
+                        if ui.button("Unlock...").clicked() {
+                            self.secrets_prompt_dismissed = false;
+                        }
+                    });
+                } else if self.secrets.backend() == SecretBackend::EncryptedFile
+                    && !self.config_manager.location().is_portable()
+                {
+                    ui.label(
+                        egui::RichText::new("No system keychain found: secrets are kept in an encrypted file.")
+                            .size(11.0),
+                    );
+                }
+                if let cove_config::DataLocation::Portable(root) = self.config_manager.location() {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "Portable mode: all data and encrypted secrets are stored in {}",
+                            root.display()
+                        ))
+                        .size(11.0),
+                    );
+                }
                 ui.add_space(8.0);
 
                 // -- Signatures --
@@ -2792,6 +7323,14 @@ impl eframe::App for NativeApp {
                                         self.compose_body = tmpl.body_text.clone();
                                         self.show_compose_window = true;
                                     }
+                                    if ui.small_button("Mail Merge").clicked() {
+                                        self.mail_merge = Some(MailMergeDraft {
+                                            template: tmpl.clone(),
+                                            csv: String::new(),
+                                            per_minute: 20,
+                                            preview_index: 0,
+                                        });
+                                    }
                                     if ui.small_button("Delete").clicked() {
                                         delete_tmpl = Some(tmpl.id);
                                     }
@@ -2799,8 +7338,434 @@ impl eframe::App for NativeApp {
                                 ui.label(format!("Subject: {}", tmpl.subject));
                             });
                         }
-                        if let Some(tmpl_id) = delete_tmpl {
-                            let _ = self.runtime.block_on(self.storage.delete_template(tmpl_id));
+                        if let Some(tmpl_id) = delete_tmpl {
+                            let _ = self.runtime.block_on(self.storage.delete_template(tmpl_id));
+                        }
+                    });
+
+                ui.add_space(8.0);
+
+                // -- Snippets --
+                egui::CollapsingHeader::new(egui::RichText::new("Text Snippets").heading())
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        ui.label(egui::RichText::new(
+                            "Type an abbreviation followed by a space in the message body to expand it. \
+                             Put {cursor} in the text to choose where the caret lands."
+                        ).size(11.0));
+                        let snippets = self.runtime.block_on(self.storage.list_snippets())
+                            .unwrap_or_default();
+                        let mut delete_snippet = None;
+                        for snippet in &snippets {
+                            ui.group(|ui| {
+                                ui.horizontal(|ui| {
+                                    ui.label(egui::RichText::new(&snippet.abbreviation).strong().monospace());
+                                    if ui.small_button("Edit").clicked() {
+                                        self.snippet_abbreviation = snippet.abbreviation.clone();
+                                        self.snippet_body = snippet.body.clone();
+                                    }
+                                    if ui.small_button("Delete").clicked() {
+                                        delete_snippet = Some(snippet.id);
+                                    }
+                                });
+                                ui.label(&snippet.body);
+                            });
+                        }
+                        if let Some(snippet_id) = delete_snippet {
+                            let _ = self.runtime.block_on(self.storage.delete_snippet(snippet_id));
+                            self.snippets = None;
+                        }
+                        ui.separator();
+                        ui.horizontal(|ui| {
+                            ui.label("Abbreviation:");
+                            ui.add(egui::TextEdit::singleline(&mut self.snippet_abbreviation)
+                                .hint_text(";sig1")
+                                .desired_width(120.0));
+                        });
+                        ui.add(egui::TextEdit::multiline(&mut self.snippet_body)
+                            .hint_text("Snippet text")
+                            .desired_rows(3));
+                        if ui.button("Save snippet").clicked() {
+                            self.save_snippet();
+                        }
+                    });
+
+                ui.add_space(8.0);
+
+                // -- Spell check --
+                egui::CollapsingHeader::new(egui::RichText::new("Spell Check").heading())
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        let mut changed = ui.checkbox(&mut self.config.spellcheck.enabled, "Check spelling while composing").changed();
+                        ui.horizontal(|ui| {
+                            ui.label("Dictionary language:");
+                            changed |= ui.add(egui::TextEdit::singleline(&mut self.config.spellcheck.language)
+                                .hint_text("en_US")
+                                .desired_width(80.0))
+                                .lost_focus();
+                        });
+                        if changed {
+                            self.spellchecker = None;
+                            if let Err(err) = self.config_manager.save(&self.config) {
+                                self.status = format!("save config failed: {err}");
+                            }
+                        }
+                        if let Some(checker) = &self.spellchecker {
+                            if !checker.is_available() {
+                                let dir = self.config_manager.config_path().parent().unwrap_or(Path::new(".")).join("dictionaries");
+                                ui.label(egui::RichText::new(format!(
+                                    "No {0} dictionary found. Add {0}.aff and {0}.dic to {1}.",
+                                    self.config.spellcheck.language,
+                                    dir.display()
+                                )).size(11.0));
+                            }
+                        }
+                    });
+
+                ui.add_space(8.0);
+
+                // -- Attachments --
+                egui::CollapsingHeader::new(egui::RichText::new("Attachments").heading())
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        ui.label(egui::RichText::new(format!(
+                            "Always blocked: {}",
+                            cove_email::DEFAULT_BLOCKED_EXTENSIONS.join(", ")
+                        )).size(11.0).weak());
+                        let mut changed = false;
+                        ui.horizontal(|ui| {
+                            ui.label("Also block:");
+                            changed |= ui.add(egui::TextEdit::singleline(&mut self.attachment_blocked_input)
+                                .hint_text("iso, docm")
+                                .desired_width(200.0))
+                                .lost_focus();
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Never block:");
+                            changed |= ui.add(egui::TextEdit::singleline(&mut self.attachment_allowed_input)
+                                .hint_text("js")
+                                .desired_width(200.0))
+                                .lost_focus();
+                        });
+                        changed |= ui.checkbox(
+                            &mut self.config.attachments.block_encrypted_archives,
+                            "Block password-protected archives instead of asking",
+                        ).changed();
+                        changed |= ui.checkbox(
+                            &mut self.config.attachments.allow_open_anyway,
+                            "Offer \"Open anyway\" for blocked files",
+                        ).changed();
+                        if changed {
+                            let parse = |input: &str| -> Vec<String> {
+                                input
+                                    .split([',', ' '])
+                                    .map(|ext| ext.trim().trim_start_matches('.').to_ascii_lowercase())
+                                    .filter(|ext| !ext.is_empty())
+                                    .collect()
+                            };
+                            self.config.attachments.blocked_extensions = parse(&self.attachment_blocked_input);
+                            self.config.attachments.allowed_extensions = parse(&self.attachment_allowed_input);
+                            if let Err(err) = self.config_manager.save(&self.config) {
+                                self.status = format!("save config failed: {err}");
+                            }
+                        }
+                    });
+
+                ui.add_space(8.0);
+
+                // -- Disk space --
+                egui::CollapsingHeader::new(egui::RichText::new("Disk Space").heading())
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        if let Some(report) = &self.disk_space {
+                            ui.label(format!(
+                                "{} free of {}. Mail uses {} and grows about {} a day.",
+                                format_bytes(report.available_bytes),
+                                format_bytes(report.total_bytes),
+                                format_bytes(report.mail_bytes),
+                                format_bytes(report.daily_growth_bytes),
+                            ));
+                        }
+                        let policy = &mut self.config.disk_space;
+                        let mut changed = ui.checkbox(
+                            &mut policy.degrade_when_low,
+                            "Sync headers only when free space is low",
+                        ).changed();
+                        ui.horizontal(|ui| {
+                            ui.label("Minimum free space:");
+                            changed |= ui.add(egui::DragValue::new(&mut policy.min_free_mb).range(0..=1_048_576).suffix(" MB")).changed();
+                        });
+                        if changed {
+                            if let Err(err) = self.config_manager.save(&self.config) {
+                                self.status = format!("save config failed: {err}");
+                            }
+                            self.check_disk_space();
+                        }
+                    });
+
+                ui.add_space(8.0);
+
+                // -- Downloads --
+                egui::CollapsingHeader::new(egui::RichText::new("Downloads").heading())
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        ui.label(egui::RichText::new(
+                            "Saved attachments go here; existing files are never overwritten.",
+                        ).size(11.0).weak());
+                        let mut changed = false;
+                        ui.horizontal(|ui| {
+                            ui.label("Folder:");
+                            changed |= ui.add(egui::TextEdit::singleline(&mut self.download_dir_input)
+                                .hint_text("System downloads folder")
+                                .desired_width(260.0))
+                                .lost_focus();
+                            if ui.button("Browse…").clicked() {
+                                if let Some(dir) = rfd::FileDialog::new().pick_folder() {
+                                    self.download_dir_input = dir.display().to_string();
+                                    changed = true;
+                                }
+                            }
+                        });
+                        if changed {
+                            let dir = self.download_dir_input.trim();
+                            self.config.attachments.download_dir = (!dir.is_empty()).then(|| dir.to_string());
+                            if let Err(err) = self.config_manager.save(&self.config) {
+                                self.status = format!("save config failed: {err}");
+                            }
+                        }
+
+                        ui.add_space(4.0);
+                        ui.label(egui::RichText::new("Recent downloads").strong());
+                        if self.recent_downloads.is_empty() {
+                            ui.label(egui::RichText::new("No attachments saved yet.").weak());
+                        }
+                        for download in &self.recent_downloads {
+                            ui.horizontal(|ui| {
+                                ui.label(&download.file_name).on_hover_text(&download.path);
+                                ui.label(
+                                    egui::RichText::new(
+                                        download.saved_at.with_timezone(&chrono::Local).format("%b %d %H:%M").to_string(),
+                                    )
+                                    .weak(),
+                                );
+                                if ui.small_button("Open").clicked() {
+                                    let _ = open::that(&download.path);
+                                }
+                                if ui.small_button("Show folder").clicked() {
+                                    if let Some(folder) = Path::new(&download.path).parent() {
+                                        let _ = open::that(folder);
+                                    }
+                                }
+                            });
+                        }
+                    });
+
+                ui.add_space(8.0);
+
+                // -- Weekly report --
+                egui::CollapsingHeader::new(egui::RichText::new("Weekly Report").heading())
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        ui.label(egui::RichText::new(
+                            "A weekly email to yourself with mail volume, response times and top correspondents.",
+                        ).size(11.0).weak());
+                        let policy = &mut self.config.weekly_report;
+                        let mut changed = ui.checkbox(&mut policy.enabled, "Email me a weekly report").changed();
+                        ui.horizontal(|ui| {
+                            ui.label("Send on");
+                            egui::ComboBox::from_id_salt("weekly_report_day")
+                                .selected_text(policy.weekday.to_string())
+                                .show_ui(ui, |ui| {
+                                    for day in [
+                                        chrono::Weekday::Mon,
+                                        chrono::Weekday::Tue,
+                                        chrono::Weekday::Wed,
+                                        chrono::Weekday::Thu,
+                                        chrono::Weekday::Fri,
+                                        chrono::Weekday::Sat,
+                                        chrono::Weekday::Sun,
+                                    ] {
+                                        changed |= ui.selectable_value(&mut policy.weekday, day, day.to_string()).changed();
+                                    }
+                                });
+                            ui.label("at");
+                            changed |= ui.add(egui::DragValue::new(&mut policy.hour).range(0..=23).suffix(":00")).changed();
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Top correspondents:");
+                            changed |= ui.add(egui::DragValue::new(&mut policy.top_correspondents).range(1..=50)).changed();
+                        });
+                        if changed {
+                            if let Err(err) = self.config_manager.save(&self.config) {
+                                self.status = format!("save config failed: {err}");
+                            }
+                        }
+                    });
+
+                ui.add_space(8.0);
+
+                // -- Muted senders --
+                egui::CollapsingHeader::new(egui::RichText::new("Muted Senders").heading())
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        ui.label(egui::RichText::new(
+                            "Muted senders raise no notifications on the account they were muted on; mutes lift on their own when they expire.",
+                        ).size(11.0).weak());
+                        if self.sender_mutes.is_empty() {
+                            ui.label(egui::RichText::new("No muted senders.").weak());
+                        }
+                        let mut unmute = None;
+                        for mute in &self.sender_mutes {
+                            ui.horizontal(|ui| {
+                                ui.label(&mute.email);
+                                let account = self
+                                    .accounts
+                                    .iter()
+                                    .find(|account| account.id == mute.account_id)
+                                    .map_or("a removed account", |account| account.email_address.as_str());
+                                ui.label(egui::RichText::new(format!("on {account}")).weak());
+                                let until = match mute.muted_until {
+                                    Some(until) => format!(
+                                        "until {}",
+                                        until.with_timezone(&chrono::Local).format("%b %d %H:%M")
+                                    ),
+                                    None => "forever".to_string(),
+                                };
+                                ui.label(egui::RichText::new(until).weak());
+                                if mute.auto_archive {
+                                    ui.label(egui::RichText::new("archiving").weak());
+                                }
+                                if ui.small_button("Unmute").clicked() {
+                                    unmute = Some((mute.account_id, mute.email.clone()));
+                                }
+                            });
+                        }
+                        if let Some((account_id, email)) = unmute {
+                            self.set_sender_mute(account_id, &email, None);
+                        }
+                    });
+
+                ui.add_space(8.0);
+
+                // -- Send guardrails --
+                egui::CollapsingHeader::new(egui::RichText::new("Send Guardrails").heading())
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        ui.label(egui::RichText::new(
+                            "Mail written outside working hours waits in the Outbox until the next workday.",
+                        ).size(11.0).weak());
+                        let guardrails = &mut self.config.send_guardrails;
+                        let mut changed = ui.checkbox(
+                            &mut guardrails.defer_outside_working_hours,
+                            "Hold mail until working hours",
+                        ).changed();
+                        ui.horizontal(|ui| {
+                            ui.label("Workday:");
+                            changed |= ui.add(egui::DragValue::new(&mut guardrails.workday_start_hour).range(0..=23).suffix(":00")).changed();
+                            ui.label("to");
+                            changed |= ui.add(egui::DragValue::new(&mut guardrails.workday_end_hour).range(1..=24).suffix(":00")).changed();
+                        });
+                        changed |= ui.checkbox(&mut guardrails.skip_weekends, "Skip weekends").changed();
+                        changed |= ui.checkbox(&mut guardrails.throttle_sends, "Throttle bulk sends").changed();
+                        ui.add_enabled_ui(guardrails.throttle_sends, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("At most");
+                                changed |= ui.add(egui::DragValue::new(&mut guardrails.max_sends_per_minute).range(1..=600)).changed();
+                                ui.label("messages per minute, per account");
+                            });
+                        });
+                        if changed {
+                            if let Err(err) = self.config_manager.save(&self.config) {
+                                self.status = format!("save config failed: {err}");
+                            }
+                        }
+                    });
+
+                ui.add_space(8.0);
+
+                // -- Message HTML --
+                egui::CollapsingHeader::new(egui::RichText::new("Message HTML").heading())
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        ui.label(egui::RichText::new(
+                            "Scripts, forms and event handlers are always removed; the level decides how much layout survives.",
+                        ).size(11.0).weak());
+                        let policy = &mut self.config.sanitizer;
+                        let mut changed = false;
+                        ui.horizontal(|ui| {
+                            changed |= ui.radio_value(&mut policy.level, SanitizerLevel::Strict, "Strict").changed();
+                            changed |= ui.radio_value(&mut policy.level, SanitizerLevel::Standard, "Standard").changed();
+                            changed |= ui.radio_value(&mut policy.level, SanitizerLevel::Permissive, "Permissive").changed();
+                        });
+                        changed |= ui.checkbox(&mut policy.strip_css, "Strip all CSS").changed();
+                        if changed {
+                            self.email.set_sanitizer_policy(self.config.sanitizer);
+                            if let Err(err) = self.config_manager.save(&self.config) {
+                                self.status = format!("save config failed: {err}");
+                            }
+                        }
+                    });
+
+                ui.add_space(8.0);
+
+                // -- Trash and Junk cleanup --
+                egui::CollapsingHeader::new(egui::RichText::new("Trash & Junk Cleanup").heading())
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        ui.label(egui::RichText::new(
+                            "Old messages are deleted on the server and locally, checked hourly. 0 days keeps mail forever.",
+                        ).size(11.0).weak());
+                        let retention = &mut self.config.retention;
+                        let mut changed = ui.checkbox(&mut retention.enabled, "Clean up automatically").changed();
+                        ui.horizontal(|ui| {
+                            ui.label("Trash:");
+                            changed |= ui.add(egui::DragValue::new(&mut retention.trash_days).range(0..=3650).suffix(" days")).changed();
+                            ui.label("Junk:");
+                            changed |= ui.add(egui::DragValue::new(&mut retention.junk_days).range(0..=3650).suffix(" days")).changed();
+                        });
+                        ui.label("Folder overrides, one \"folder = days\" per line:");
+                        changed |= ui.add(egui::TextEdit::multiline(&mut self.retention_overrides_input)
+                            .hint_text("[Gmail]/Spam = 7")
+                            .desired_rows(2)
+                            .desired_width(260.0))
+                            .lost_focus();
+                        if changed {
+                            self.config.retention.folder_overrides = self
+                                .retention_overrides_input
+                                .lines()
+                                .filter_map(|line| {
+                                    let (folder, days) = line.split_once('=')?;
+                                    let folder = folder.trim();
+                                    let days = days.trim().parse().ok()?;
+                                    (!folder.is_empty()).then(|| (folder.to_string(), days))
+                                })
+                                .collect();
+                            self.purge_preview = None;
+                            if let Err(err) = self.config_manager.save(&self.config) {
+                                self.status = format!("save config failed: {err}");
+                            }
+                        }
+                        // Manual cleanup follows the same switch as the hourly run.
+                        let enabled = self.config.retention.enabled;
+                        ui.horizontal(|ui| {
+                            if ui.add_enabled(enabled, egui::Button::new("Preview")).clicked() {
+                                self.preview_retention();
+                            }
+                            if ui.add_enabled(enabled, egui::Button::new("Clean up now")).clicked() {
+                                self.purge_expired_mail();
+                            }
+                        });
+                        if let Some(candidates) = &self.purge_preview {
+                            if candidates.is_empty() {
+                                ui.label("Nothing is old enough to remove.");
+                            }
+                            let mut by_folder: BTreeMap<&str, usize> = BTreeMap::new();
+                            for candidate in candidates {
+                                *by_folder.entry(candidate.folder_path.as_str()).or_default() += 1;
+                            }
+                            for (folder, count) in by_folder {
+                                ui.label(format!("{folder}: {} message(s) will be removed", group_thousands(count as u64)));
+                            }
                         }
                     });
 
@@ -2816,6 +7781,7 @@ impl eframe::App for NativeApp {
                             ui.label("No rules configured.");
                         }
                         let mut delete_rule = None;
+                        let mut preview_rule = None;
                         for rule in &rules {
                             ui.group(|ui| {
                                 ui.horizontal(|ui| {
@@ -2825,6 +7791,9 @@ impl eframe::App for NativeApp {
                                     if ui.small_button("Delete").clicked() {
                                         delete_rule = Some(rule.id);
                                     }
+                                    if ui.small_button("Preview").on_hover_text("Show which stored messages this rule would change, without changing them").clicked() {
+                                        preview_rule = Some(rule.clone());
+                                    }
                                 });
                                 let cond_text: Vec<String> = rule.conditions.iter().map(|c| {
                                     format!("{:?} {:?} '{}'", c.field, c.operator, c.value)
@@ -2832,11 +7801,159 @@ impl eframe::App for NativeApp {
                                 ui.label(format!("If {}: {}", if rule.match_all { "ALL" } else { "ANY" }, cond_text.join(", ")));
                                 let action_text: Vec<String> = rule.actions.iter().map(|a| format!("{a:?}")).collect();
                                 ui.label(format!("Then: {}", action_text.join(", ")));
+                                if let Some((_, report)) = self.rule_preview.as_ref().filter(|(id, _)| *id == rule.id) {
+                                    show_dry_run_report(ui, report);
+                                }
                             });
                         }
                         if let Some(rule_id) = delete_rule {
                             let _ = self.runtime.block_on(self.storage.delete_rule(rule_id));
                         }
+                        if let Some(rule) = preview_rule {
+                            match self.runtime.block_on(self.email.dry_run_rule(&rule, None)) {
+                                Ok(report) => self.rule_preview = Some((rule.id, report)),
+                                Err(err) => self.status = format!("rule preview failed: {err}"),
+                            }
+                        }
+                    });
+
+                ui.add_space(8.0);
+
+                // -- Labels and shared triage setup --
+                egui::CollapsingHeader::new(egui::RichText::new("Labels & Shared Setup").heading())
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        let labels = self.runtime.block_on(self.storage.list_labels())
+                            .unwrap_or_default();
+                        if labels.is_empty() {
+                            ui.label("No labels yet.");
+                        }
+                        let mut delete_label = None;
+                        for label in &labels {
+                            ui.horizontal(|ui| {
+                                let color = label
+                                    .color
+                                    .as_deref()
+                                    .and_then(|hex| egui::Color32::from_hex(hex).ok())
+                                    .unwrap_or(ui.visuals().weak_text_color());
+                                ui.label(egui::RichText::new("●").color(color));
+                                ui.label(&label.name);
+                                if let Some(description) = &label.description {
+                                    ui.label(egui::RichText::new(description).weak().size(11.0));
+                                }
+                                if label.color.is_some() && ui.small_button("Remove").clicked() {
+                                    delete_label = Some(label.name.clone());
+                                }
+                            });
+                        }
+                        if let Some(name) = delete_label {
+                            let _ = self.runtime.block_on(self.storage.delete_label(&name));
+                        }
+
+                        ui.horizontal(|ui| {
+                            ui.add(egui::TextEdit::singleline(&mut self.label_name).hint_text("Label name").desired_width(160.0));
+                            ui.color_edit_button_srgb(&mut self.label_color);
+                            if ui.button("Add Label").clicked() && !self.label_name.trim().is_empty() {
+                                let [r, g, b] = self.label_color;
+                                let label = cove_core::MailLabel {
+                                    name: self.label_name.trim().to_string(),
+                                    color: Some(format!("#{r:02x}{g:02x}{b:02x}")),
+                                    description: None,
+                                };
+                                match self.runtime.block_on(self.storage.upsert_label(&label)) {
+                                    Ok(()) => self.label_name.clear(),
+                                    Err(err) => self.status = format!("save label failed: {err}"),
+                                }
+                            }
+                        });
+
+                        ui.add_space(6.0);
+                        ui.label(egui::RichText::new(
+                            "Share labels, rules and templates as a JSON bundle. Bundles contain no accounts or secrets; \
+                             imported rules and templates replace local ones with the same name."
+                        ).size(11.0));
+                        ui.horizontal(|ui| {
+                            if ui.button("Export Bundle...").clicked() {
+                                self.export_triage_bundle();
+                            }
+                            if ui.button("Import Bundle...").clicked() {
+                                self.import_triage_bundle();
+                            }
+                        });
+                    });
+
+                ui.add_space(8.0);
+
+                // -- Plus-address aliases --
+                egui::CollapsingHeader::new(egui::RichText::new("Plus-Address Aliases").heading())
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        ui.label(egui::RichText::new(
+                            "Give each site its own you+site@ address. Mail from anyone else on that alias \
+                             means the address was shared or leaked."
+                        ).size(11.0));
+                        ui.horizontal(|ui| {
+                            ui.add(egui::TextEdit::singleline(&mut self.alias_issue_to)
+                                .hint_text("shop.example.com")
+                                .desired_width(200.0));
+                            if ui.button("Generate alias").clicked() && !self.alias_issue_to.trim().is_empty() {
+                                if let Some(account_id) = self.selected_account {
+                                    let issue_to = self.alias_issue_to.trim().to_string();
+                                    self.create_sender_alias(ui.ctx(), account_id, &issue_to);
+                                    self.alias_issue_to.clear();
+                                }
+                            }
+                            if ui.button("Refresh report").clicked() {
+                                self.alias_report = None;
+                            }
+                        });
+
+                        if self.alias_report.is_none() {
+                            self.alias_report = Some(
+                                self.runtime
+                                    .block_on(self.email.plus_alias_report(None))
+                                    .unwrap_or_default(),
+                            );
+                        }
+                        let mut delete_alias = None;
+                        for usage in self.alias_report.as_deref().unwrap_or_default() {
+                            ui.group(|ui| {
+                                ui.horizontal(|ui| {
+                                    ui.label(egui::RichText::new(&usage.alias.address).strong().monospace());
+                                    ui.label(format!("for {}", usage.alias.issued_to));
+                                    if usage.is_leaked() {
+                                        ui.label(egui::RichText::new("⚠ possibly leaked").color(egui::Color32::from_rgb(230, 140, 40)));
+                                    }
+                                    if ui.small_button("Copy").clicked() {
+                                        ui.ctx().copy_text(usage.alias.address.clone());
+                                    }
+                                    if ui.small_button("Delete").clicked() {
+                                        delete_alias = Some(usage.alias.id);
+                                    }
+                                });
+                                if usage.senders.is_empty() {
+                                    ui.label(egui::RichText::new("No mail received yet").size(11.0).italics());
+                                }
+                                for sender in &usage.senders {
+                                    let text = format!(
+                                        "{} — {} message(s), last {}",
+                                        sender.address,
+                                        sender.message_count,
+                                        sender.last_received_at.with_timezone(&chrono::Local).format("%Y-%m-%d")
+                                    );
+                                    let color = if sender.expected {
+                                        ui.visuals().weak_text_color()
+                                    } else {
+                                        egui::Color32::from_rgb(230, 140, 40)
+                                    };
+                                    ui.label(egui::RichText::new(text).size(11.0).color(color));
+                                }
+                            });
+                        }
+                        if let Some(alias_id) = delete_alias {
+                            let _ = self.runtime.block_on(self.storage.delete_plus_alias(alias_id));
+                            self.alias_report = None;
+                        }
                     });
 
                 ui.add_space(8.0);
@@ -2860,6 +7977,90 @@ impl eframe::App for NativeApp {
 
                 ui.add_space(8.0);
 
+                // -- Daily Agenda --
+                egui::CollapsingHeader::new(egui::RichText::new("Daily Agenda").heading())
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        ui.label("A morning summary of today's events, due tasks, and unread VIP mail.");
+                        let mut changed = false;
+                        changed |= ui.checkbox(&mut self.config.agenda.enabled, "Enable daily agenda").changed();
+                        ui.horizontal(|ui| {
+                            ui.label("Delivery time (HH:MM):");
+                            changed |= ui.text_edit_singleline(&mut self.config.agenda.delivery_time).lost_focus();
+                        });
+                        changed |= ui.checkbox(&mut self.config.agenda.send_email, "Also email the agenda to myself").changed();
+                        changed |= ui.checkbox(&mut self.config.agenda.ai_narrative, "Add AI-written narrative").changed();
+                        ui.label(egui::RichText::new(
+                            "Unread mail from VIP contacts is listed; mark a sender as VIP from their message.",
+                        ).size(11.0).weak());
+                        if changed {
+                            if let Err(err) = self.config_manager.save(&self.config) {
+                                self.status = format!("save config failed: {err}");
+                            }
+                        }
+                        if ui.button("Deliver agenda now").clicked() {
+                            self.deliver_agenda();
+                        }
+                    });
+
+                ui.add_space(8.0);
+
+                // -- Notifications & sounds --
+                egui::CollapsingHeader::new(egui::RichText::new("Notifications & Sounds").heading())
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        let notif = &mut self.config.notifications;
+                        let mut changed = false;
+                        changed |= ui.checkbox(&mut notif.new_mail_enabled, "New mail notifications").changed();
+                        changed |= ui.checkbox(&mut notif.new_mail_sound, "Play a sound for new mail").changed();
+                        changed |= ui.checkbox(&mut notif.reminder_enabled, "Calendar and task reminders").changed();
+                        ui.horizontal(|ui| {
+                            changed |= ui.checkbox(&mut notif.quiet_hours_enabled, "Quiet hours").changed();
+                            changed |= ui.add(egui::TextEdit::singleline(&mut notif.quiet_hours_start).desired_width(50.0)).lost_focus();
+                            ui.label("to");
+                            changed |= ui.add(egui::TextEdit::singleline(&mut notif.quiet_hours_end).desired_width(50.0)).lost_focus();
+                        });
+                        changed |= ui.checkbox(&mut notif.sounds_muted, "Mute all sounds").changed();
+                        ui.add_space(4.0);
+
+                        ui.add_enabled_ui(!notif.sounds_muted, |ui| {
+                            egui::Grid::new("notification_sounds_grid").num_columns(4).show(ui, |ui| {
+                                for event in sounds::SoundEvent::ALL {
+                                    ui.label(event.label());
+                                    let sound = event.sound_mut(notif);
+                                    egui::ComboBox::from_id_salt(("notification_sound", event.label()))
+                                        .selected_text(sounds::sound_label(sound))
+                                        .show_ui(ui, |ui| {
+                                            for (bundled, label) in sounds::BUNDLED {
+                                                changed |= ui.selectable_value(sound, bundled, label).changed();
+                                            }
+                                        });
+                                    if ui.small_button("Custom…").on_hover_text("Use your own audio file").clicked() {
+                                        if let Some(path) = rfd::FileDialog::new()
+                                            .add_filter("Audio", &["wav", "ogg", "flac", "mp3"])
+                                            .pick_file()
+                                        {
+                                            *sound = cove_config::NotificationSound::File(path.display().to_string());
+                                            changed = true;
+                                        }
+                                    }
+                                    if ui.small_button("▶ Preview").clicked() {
+                                        sounds::play(sound.clone());
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                        });
+
+                        if changed {
+                            if let Err(err) = self.config_manager.save(&self.config) {
+                                self.status = format!("save config failed: {err}");
+                            }
+                        }
+                    });
+
+                ui.add_space(8.0);
+
                 // -- Search operators help --
                 egui::CollapsingHeader::new(egui::RichText::new("Search Operators").heading())
                     .default_open(false)
@@ -2873,9 +8074,13 @@ impl eframe::App for NativeApp {
                             ("has:attachment", "Messages with attachments"),
                             ("is:unread", "Unread messages"),
                             ("is:pinned", "Pinned messages"),
+                            ("is:vip", "Messages from VIP contacts (Ctrl/Cmd+Shift+V toggles the sender)"),
                             ("before:2025-01-01", "Messages before a date"),
                             ("after:2025-06-01", "Messages after a date"),
                             ("label:important", "Messages with a label"),
+                            ("on:2025-06-01", "Calendar events on a day"),
+                            ("attendee:carol@example.com", "Calendar events with an attendee"),
+                            ("due:2025-06-01", "Tasks due on a day"),
                         ] {
                             ui.horizontal(|ui| {
                                 ui.label(egui::RichText::new(op).monospace().strong());
@@ -2888,20 +8093,237 @@ impl eframe::App for NativeApp {
                 ui.heading("Setup Wizard");
                 ui.label("Setup your accounts here.");
             }
+            View::Onboarding => self.show_onboarding(ui),
             View::Contacts => {
                 ui.heading("Contacts Management");
                 ui.add_space(8.0);
-                ui.label("CRM Lite: Aggregate communication history and manage contact templates.");
+                ui.horizontal(|ui| {
+                    ui.label("Search:");
+                    ui.text_edit_singleline(&mut self.contact_query);
+                });
+                ui.add_space(4.0);
+                let contacts = self
+                    .runtime
+                    .block_on(self.storage.search_contacts(self.contact_query.trim(), 200))
+                    .unwrap_or_default();
+                let mut deferred_vip: Option<String> = None;
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for contact in &contacts {
+                        ui.horizontal(|ui| {
+                            let star = if contact.vip { "★" } else { "☆" };
+                            if ui.small_button(star).on_hover_text("Toggle VIP").clicked() {
+                                deferred_vip = Some(contact.email.clone());
+                            }
+                            match &contact.display_name {
+                                Some(name) => ui.label(format!("{name} <{}>", contact.email)),
+                                None => ui.label(&contact.email),
+                            };
+                            ui.label(egui::RichText::new(format!("{} message(s)", contact.contact_count)).size(11.0));
+                        });
+                    }
+                });
+                if let Some(email) = deferred_vip {
+                    self.toggle_sender_vip(&email);
+                }
             }
             View::Rules => {
                 ui.heading("Local Rules Engine");
                 ui.add_space(8.0);
                 ui.label("Robust local filtering and actions. Configure your Thunderbird-class rules here.");
             }
+            View::NeedsReply => {
+                ui.heading("Needs Reply");
+                ui.label("Inbound messages that look like they are waiting on you, oldest first.");
+                ui.add_space(8.0);
+                let queue = self.runtime.block_on(self.storage.list_reply_queue()).unwrap_or_else(|err| {
+                    self.status = format!("load reply queue failed: {err}");
+                    Vec::new()
+                });
+                let (confirmed, borderline): (Vec<_>, Vec<_>) = queue
+                    .iter()
+                    .filter(|(item, _)| item.ai_confirmed != Some(false))
+                    .partition(|(item, _)| {
+                        item.score >= cove_email::REPLY_NEEDED_SCORE || item.ai_confirmed == Some(true)
+                    });
+
+                let mut deferred_status: Option<(Uuid, ReplyQueueStatus, Option<chrono::DateTime<Utc>>)> = None;
+                let mut deferred_open: Option<(Uuid, String)> = None;
+                let mut check_borderline = false;
+                let now = Utc::now();
+
+                let mut queue_row = |ui: &mut egui::Ui, item: &cove_core::ReplyQueueItem, message: &cove_core::MailMessage| {
+                    ui.group(|ui| {
+                        ui.set_width(ui.available_width());
+                        ui.horizontal(|ui| {
+                            let sender = message.from.first()
+                                .map(|a| a.name.clone().unwrap_or_else(|| a.address.clone()))
+                                .unwrap_or_else(|| "Unknown sender".to_string());
+                            let age = now - message.received_at;
+                            let age_text = if age.num_days() > 0 {
+                                format!("{}d", age.num_days())
+                            } else {
+                                format!("{}h", age.num_hours())
+                            };
+                            ui.label(egui::RichText::new(age_text).size(11.0).color(egui::Color32::from_rgb(220, 150, 50)));
+                            ui.label(egui::RichText::new(sender).strong());
+                            ui.label(&message.subject);
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                if ui.small_button("Dismiss").clicked() {
+                                    deferred_status = Some((item.message_id, ReplyQueueStatus::Dismissed, None));
+                                }
+                                if ui.small_button("Snooze 1d").clicked() {
+                                    deferred_status = Some((item.message_id, ReplyQueueStatus::Snoozed, Some(now + Duration::days(1))));
+                                }
+                                if ui.small_button("Open").clicked() {
+                                    deferred_open = Some((item.account_id, item.thread_id.clone()));
+                                }
+                            });
+                        });
+                        ui.label(egui::RichText::new(format!("{}% · {}", item.score, item.reasons.join(", "))).size(11.0));
+                    });
+                };
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    if confirmed.is_empty() {
+                        ui.label("Nothing waiting on a reply.");
+                    }
+                    for (item, message) in &confirmed {
+                        queue_row(ui, item, message);
+                    }
+
+                    if !borderline.is_empty() {
+                        ui.add_space(12.0);
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new(format!("Possibly needs reply ({})", borderline.len())).strong());
+                            if ui.small_button("Check with AI").on_hover_text("Ask the AI model to confirm these borderline messages").clicked() {
+                                check_borderline = true;
+                            }
+                        });
+                        for (item, message) in &borderline {
+                            queue_row(ui, item, message);
+                        }
+                    }
+                });
+
+                if check_borderline {
+                    let mut confirmed_count = 0;
+                    let mut failed = false;
+                    for (item, message) in borderline.iter().filter(|(item, message)| {
+                        item.ai_confirmed.is_none()
+                            && self.ai.ensure_account_allows(Some(message.account_id), &self.ai_mode).is_ok()
+                    }) {
+                        let sender = message.from.first().map(|a| a.address.clone()).unwrap_or_default();
+                        let body = message.body_text.as_deref().unwrap_or(&message.preview);
+                        match self.runtime.block_on(self.ai.needs_reply(
+                            message.account_id, &sender, &message.subject, body,
+                            self.ai_mode.clone(), self.ai_cloud_provider.clone(),
+                        )) {
+                            Ok((verdict, _)) => {
+                                confirmed_count += verdict as usize;
+                                let _ = self.runtime.block_on(self.storage.set_reply_ai_verdict(item.message_id, verdict));
+                            }
+                            Err(err) => {
+                                self.status = format!("AI reply check failed: {err}");
+                                failed = true;
+                                break;
+                            }
+                        }
+                    }
+                    if !failed {
+                        self.status = format!("AI confirmed {confirmed_count} message(s) need a reply.");
+                    }
+                }
+                if let Some((message_id, status, until)) = deferred_status {
+                    if let Err(err) = self.runtime.block_on(self.storage.set_reply_status(message_id, status, until)) {
+                        self.status = format!("update reply queue failed: {err}");
+                    }
+                }
+                if let Some((account_id, thread_id)) = deferred_open {
+                    self.open_thread_link(account_id, thread_id);
+                }
+            }
             View::Analytics => {
                 ui.heading("Analytics & Read Status");
                 ui.add_space(8.0);
-                ui.label("Superhuman-class Response-time and Workflow dashbaords.");
+                ui.label(egui::RichText::new("Reply queue").strong());
+                match self.runtime.block_on(self.storage.reply_queue_stats()) {
+                    Ok(stats) => {
+                        egui::Grid::new("reply_stats_grid").num_columns(2).show(ui, |ui| {
+                            ui.label("Waiting on you");
+                            ui.label(stats.open.to_string());
+                            ui.end_row();
+                            ui.label("Snoozed");
+                            ui.label(stats.snoozed.to_string());
+                            ui.end_row();
+                            ui.label("Replied");
+                            ui.label(stats.replied.to_string());
+                            ui.end_row();
+                            ui.label("Dismissed");
+                            ui.label(stats.dismissed.to_string());
+                            ui.end_row();
+                            ui.label("Average time to reply");
+                            ui.label(stats.avg_reply_hours.map(|h| format!("{h:.1} h")).unwrap_or_else(|| "—".to_string()));
+                            ui.end_row();
+                            ui.label("Oldest waiting");
+                            ui.label(stats.oldest_open_hours.map(|h| format!("{h:.1} h")).unwrap_or_else(|| "—".to_string()));
+                            ui.end_row();
+                        });
+                    }
+                    Err(err) => {
+                        ui.label(format!("load reply stats failed: {err}"));
+                    }
+                }
+
+                ui.add_space(12.0);
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new("Last 7 days").strong());
+                    if ui.small_button("Refresh").clicked() || self.analytics.is_none() {
+                        self.refresh_analytics();
+                    }
+                    if ui.small_button("Export CSV").clicked() {
+                        self.export_analytics(AnalyticsFormat::Csv);
+                    }
+                    if ui.small_button("Export JSON").clicked() {
+                        self.export_analytics(AnalyticsFormat::Json);
+                    }
+                });
+                if let Some(analytics) = &self.analytics {
+                    let hours = |hours: Option<f64>| {
+                        hours.map(|h| format!("{h:.1} h")).unwrap_or_else(|| "—".to_string())
+                    };
+                    egui::Grid::new("mail_volume_grid").num_columns(2).show(ui, |ui| {
+                        ui.label("Received");
+                        ui.label(analytics.received.to_string());
+                        ui.end_row();
+                        ui.label("Sent");
+                        ui.label(analytics.sent.to_string());
+                        ui.end_row();
+                        ui.label("Replies");
+                        ui.label(analytics.replies.to_string());
+                        ui.end_row();
+                        ui.label("Average response");
+                        ui.label(hours(analytics.avg_response_hours));
+                        ui.end_row();
+                        ui.label("Median response");
+                        ui.label(hours(analytics.median_response_hours));
+                        ui.end_row();
+                    });
+
+                    ui.add_space(8.0);
+                    ui.label(egui::RichText::new("Top correspondents").strong());
+                    if analytics.top_correspondents.is_empty() {
+                        ui.label(egui::RichText::new("No mail in this period.").weak());
+                    }
+                    egui::Grid::new("top_correspondents_grid").num_columns(3).show(ui, |ui| {
+                        for person in &analytics.top_correspondents {
+                            ui.label(person.name.as_deref().unwrap_or(&person.address))
+                                .on_hover_text(&person.address);
+                            ui.label(format!("{} received", person.received));
+                            ui.label(format!("{} sent", person.sent));
+                            ui.end_row();
+                        }
+                    });
+                }
             }
             View::Integrations => {
                 ui.heading("App Integrations");
@@ -2910,52 +8332,57 @@ impl eframe::App for NativeApp {
             }
         });
 
-        // Process pending attachment save/open after UI draw.
-        if let Some((att_id, file_name)) = self.pending_attachment_save.take() {
-            if let Some(path) = rfd::FileDialog::new()
-                .set_file_name(&file_name)
-                .save_file()
-            {
-                match self.runtime.block_on(self.email.get_attachment_content(att_id)) {
-                    Ok(Some(data)) => {
-                        if let Err(e) = std::fs::write(&path, &data) {
-                            self.status = format!("Save failed: {e}");
-                        } else {
-                            self.status = format!("Saved to {}", path.display());
-                        }
-                    }
-                    Ok(None) => {
-                        self.status = "Attachment content not available offline.".to_string();
-                    }
-                    Err(e) => {
-                        self.status = format!("Error loading attachment: {e}");
-                    }
-                }
-            }
-        }
-        if let Some((att_id, file_name)) = self.pending_attachment_open.take() {
-            match self.runtime.block_on(self.email.get_attachment_content(att_id)) {
-                Ok(Some(data)) => {
-                    let tmp_dir = std::env::temp_dir().join("cove-attachments");
-                    let _ = std::fs::create_dir_all(&tmp_dir);
-                    let tmp_path = tmp_dir.join(&file_name);
-                    match std::fs::write(&tmp_path, &data) {
-                        Ok(_) => {
-                            let _ = open::that(&tmp_path);
+        // Attachments the policy held back until the user confirms.
+        if let Some((action, reason)) = self.attachment_confirmation.clone() {
+            let mut close = false;
+            egui::Window::new("Attachment Warning")
+                .collapsible(false)
+                .resizable(false)
+                .default_width(340.0)
+                .show(ctx, |ui| {
+                    ui.label(egui::RichText::new(&action.file_name).strong());
+                    ui.label(&reason);
+                    ui.add_space(6.0);
+                    ui.label(
+                        egui::RichText::new("Only continue if you trust the sender and expected this file.")
+                            .weak(),
+                    );
+                    ui.horizontal(|ui| {
+                        let confirm = if action.open { "Open anyway" } else { "Save anyway" };
+                        if ui
+                            .button(egui::RichText::new(confirm).color(egui::Color32::RED))
+                            .clicked()
+                        {
+                            self.pending_attachment = Some(AttachmentAction {
+                                confirmed: true,
+                                ..action.clone()
+                            });
+                            close = true;
                         }
-                        Err(e) => {
-                            self.status = format!("Failed to write temp file: {e}");
+                        if ui.button("Cancel").clicked() {
+                            close = true;
                         }
-                    }
-                }
-                Ok(None) => {
-                    self.status = "Attachment content not available offline.".to_string();
-                }
-                Err(e) => {
-                    self.status = format!("Error loading attachment: {e}");
-                }
+                    });
+                });
+            if close {
+                self.attachment_confirmation = None;
+                ctx.request_repaint();
             }
         }
+
+        if self.query_builder.is_some() {
+            self.show_query_builder(ctx);
+        }
+
+        // Process pending attachment save/open after UI draw.
+        if let Some(action) = self.pending_attachment.take() {
+            self.run_attachment_action(action);
+        }
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.save_recovery_journal();
+        self.save_session();
     }
 }
 
@@ -2995,6 +8422,34 @@ fn ag_chat_bubble_text(message: &MailMessage) -> String {
     }
 }
 
+/// Counts and sample messages of a rule or bulk action dry run.
+fn show_dry_run_report(ui: &mut egui::Ui, report: &cove_core::DryRunReport) {
+    ui.label(egui::RichText::new(format!(
+        "Would change {} of {} matching messages ({} scanned). Nothing was changed.",
+        group_thousands(report.affected as u64),
+        group_thousands(report.matched as u64),
+        group_thousands(report.scanned as u64)
+    )).strong());
+    for count in &report.per_action {
+        ui.label(format!("{:?}: {} message(s)", count.action, group_thousands(count.messages as u64)));
+    }
+    for sample in &report.samples {
+        ui.label(egui::RichText::new(format!(
+            "{} · {} · {} · {}",
+            sample.received_at.with_timezone(&Local).format("%Y-%m-%d"),
+            sample.sender,
+            sample.subject,
+            sample.folder_path
+        )).size(11.0));
+    }
+    if report.affected > report.samples.len() {
+        ui.label(egui::RichText::new(format!("…and {} more", report.affected - report.samples.len())).weak().size(11.0));
+    }
+}
+
+/// Secrets stored per account, by namespace.
+const ACCOUNT_SECRET_NAMESPACES: [&str; 3] = ["account_password", "oauth_refresh_token", "oauth_access_token"];
+
 fn set_secret_guarded(secrets: &SecretStore, key: SecretKey, value: &str) -> Result<(), String> {
     validate_secret_key(&key, value)?;
     secrets.set(&key, value).map_err(|err| err.to_string())
@@ -3065,6 +8520,44 @@ fn ai_runtime_from_config(config: &AppConfig) -> AiRuntimeConfig {
         cloud_enabled: config.ai.cloud.enabled,
         cloud_feature_opt_in,
         cloud,
+        cloud_redaction: config.ai.cloud.redaction.enabled.then(|| RedactionPolicy {
+            emails: config.ai.cloud.redaction.redact_emails,
+            phone_numbers: config.ai.cloud.redaction.redact_phone_numbers,
+            numeric_ids: config.ai.cloud.redaction.redact_numeric_ids,
+            custom_patterns: config.ai.cloud.redaction.custom_patterns.clone(),
+        }),
+        account_policies: account_ai_policies(config),
+    }
+}
+
+fn account_ai_policies(config: &AppConfig) -> BTreeMap<Uuid, AccountAiPolicy> {
+    config
+        .ai
+        .account_policies
+        .iter()
+        .filter_map(|(id, policy)| Some((Uuid::parse_str(id).ok()?, *policy)))
+        .collect()
+}
+
+fn attachment_policy_from_config(config: &AppConfig) -> AttachmentPolicy {
+    let attachments = &config.attachments;
+    AttachmentPolicy::new(
+        cove_email::DEFAULT_BLOCKED_EXTENSIONS
+            .iter()
+            .copied()
+            .chain(attachments.blocked_extensions.iter().map(String::as_str)),
+        &attachments.allowed_extensions,
+        attachments.block_encrypted_archives,
+        attachments.allow_open_anyway,
+    )
+}
+
+fn digest_limits_from_config(config: &AppConfig) -> DigestLimits {
+    DigestLimits {
+        max_concurrency: config.ai.digest.max_concurrency,
+        requests_per_minute: config.ai.digest.requests_per_minute,
+        max_threads: config.ai.digest.max_threads,
+        max_prompt_chars: config.ai.digest.max_prompt_chars,
     }
 }
 
@@ -3104,6 +8597,27 @@ fn hydrate_email_secrets(account_id: Uuid, secrets: &SecretStore, settings: &mut
     }
 }
 
+/// Parse one `YYYY-MM-DD HH:MM` local start per line into poll options.
+fn parse_poll_slots(text: &str, duration_minutes: u32) -> Result<Vec<PollOption>, String> {
+    let duration = Duration::minutes(i64::from(duration_minutes.max(5)));
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let naive = chrono::NaiveDateTime::parse_from_str(line, "%Y-%m-%d %H:%M")
+                .map_err(|_| format!("\"{line}\" is not a YYYY-MM-DD HH:MM time"))?;
+            let starts_at = chrono::TimeZone::from_local_datetime(&chrono::Local, &naive)
+                .earliest()
+                .ok_or_else(|| format!("\"{line}\" does not exist in the local time zone"))?
+                .with_timezone(&Utc);
+            Ok(PollOption {
+                starts_at,
+                ends_at: starts_at + duration,
+            })
+        })
+        .collect()
+}
+
 fn hydrate_calendar_secrets(
     account_id: Uuid,
     secrets: &SecretStore,
@@ -3168,6 +8682,98 @@ fn oauth_profile_for_provider(
     })
 }
 
+/// Find a `cove://thread/{account_id}/{thread_id}` back-link in free text.
+fn parse_thread_link(text: &str) -> Option<(Uuid, String)> {
+    let rest = &text[text.find("cove://thread/")? + "cove://thread/".len()..];
+    let (account, thread) = rest.split_once('/')?;
+    let thread = thread.split(|c: char| c.is_whitespace() || c == ')').next()?;
+    if thread.is_empty() {
+        return None;
+    }
+    Some((Uuid::parse_str(account).ok()?, thread.to_string()))
+}
+
+/// Parse a review-dialog due date in local time. Date-only values default
+/// to 17:00.
+fn parse_local_due(text: &str) -> Result<Option<chrono::DateTime<Utc>>, ()> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    let naive = chrono::NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M")
+        .or_else(|_| {
+            chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .map(|date| date.and_hms_opt(17, 0, 0).unwrap_or_default())
+        })
+        .map_err(|_| ())?;
+    chrono::TimeZone::from_local_datetime(&chrono::Local, &naive)
+        .earliest()
+        .map(|local| Some(local.with_timezone(&Utc)))
+        .ok_or(())
+}
+
+/// `12400` -> `"12,400"`.
+/// Local (year, month) a thread's latest message falls in.
+fn local_month(at: chrono::DateTime<Utc>) -> (i32, u32) {
+    let local = at.with_timezone(&Local);
+    (local.year(), local.month())
+}
+
+/// Distinct months in the thread list, in list order (newest first).
+fn thread_months(threads: &[MailThreadSummary]) -> Vec<(i32, u32)> {
+    let mut months = Vec::new();
+    for thread in threads {
+        let month = local_month(thread.most_recent_at);
+        if !months.contains(&month) {
+            months.push(month);
+        }
+    }
+    months
+}
+
+/// Rough time left for the backfill progress bar.
+fn eta_label(seconds: u64) -> String {
+    match seconds {
+        0..=89 => "1 min".to_string(),
+        90..=5399 => format!("{} min", (seconds + 30) / 60),
+        5400..=129_599 => format!("{} h", (seconds + 1800) / 3600),
+        _ => format!("{} days", (seconds + 43_200) / 86_400),
+    }
+}
+
+fn month_label(year: i32, month: u32) -> String {
+    chrono::NaiveDate::from_ymd_opt(year, month, 1)
+        .map(|date| date.format("%B %Y").to_string())
+        .unwrap_or_else(|| format!("{year}-{month:02}"))
+}
+
+fn group_thousands(value: u64) -> String {
+    let digits = value.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, ch) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(ch);
+    }
+    grouped
+}
+
+/// `1_572_864` -> `"1.5 MB"`.
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
 /// Extract an HTTP(S) unsubscribe URL from a `List-Unsubscribe` header value.
 /// The header typically contains one or more URIs in angle brackets, e.g.
 /// `<https://example.com/unsub>, <mailto:unsub@example.com>`.
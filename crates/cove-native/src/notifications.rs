@@ -1,8 +1,9 @@
 //! Desktop notification support for new mail and calendar/task reminders.
 
-use chrono::Utc;
 use cove_config::NotificationConfig;
-use cove_core::{CalendarEvent, ReminderTask};
+use cove_core::Reminder;
+use cove_storage::ReminderHandler;
+use crate::sounds::{self, SoundEvent};
 use notify_rust::Notification;
 use std::collections::HashSet;
 use uuid::Uuid;
@@ -11,18 +12,68 @@ use uuid::Uuid;
 pub struct NotificationState {
     /// Message IDs for which we've already sent a new-mail notification.
     notified_messages: HashSet<Uuid>,
-    /// (event/task id, minutes_before) pairs we've already fired.
-    notified_reminders: HashSet<(Uuid, i64)>,
+    /// `(account, lowercased address)` pairs the user muted; that sender's
+    /// mail to that account never notifies.
+    muted_senders: HashSet<(Uuid, String)>,
 }
 
 impl NotificationState {
     pub fn new() -> Self {
         Self {
             notified_messages: HashSet::new(),
-            notified_reminders: HashSet::new(),
+            muted_senders: HashSet::new(),
         }
     }
 
+    pub fn set_muted_senders(&mut self, muted: HashSet<(Uuid, String)>) {
+        self.muted_senders = muted;
+    }
+
+    fn is_muted(&self, msg: &cove_core::MailMessage) -> bool {
+        msg.from
+            .first()
+            .is_some_and(|a| {
+                self.muted_senders
+                    .contains(&(msg.account_id, a.address.to_lowercase()))
+            })
+    }
+
+    /// Notify for unseen mail from VIP contacts. VIPs override the new-mail
+    /// toggle and quiet hours and use a distinct sound. Run before
+    /// `check_new_mail` so each message fires once.
+    pub fn check_vip_mail(
+        &mut self,
+        config: &NotificationConfig,
+        messages: &[cove_core::MailMessage],
+    ) -> usize {
+        let mut count = 0;
+        for msg in messages {
+            if msg.flags.seen || !self.notified_messages.insert(msg.id) || self.is_muted(msg) {
+                continue;
+            }
+
+            let sender = msg
+                .from
+                .first()
+                .map(|a| a.name.as_deref().unwrap_or(&a.address).to_string())
+                .unwrap_or_else(|| "Unknown sender".to_string());
+
+            let _ = Notification::new()
+                .summary(&format!("★ VIP mail from {sender}"))
+                .body(&msg.subject)
+                .appname("Cove Mail")
+                .timeout(12000)
+                .show();
+
+            count += 1;
+        }
+
+        if count > 0 {
+            sounds::play_event(config, SoundEvent::VipMail);
+        }
+        count
+    }
+
     /// Check for new unseen messages and send desktop notifications.
     /// Returns the number of notifications sent.
     pub fn check_new_mail(
@@ -43,6 +94,9 @@ impl NotificationState {
                 continue;
             }
             self.notified_messages.insert(msg.id);
+            if self.is_muted(msg) {
+                continue;
+            }
 
             let sender = msg
                 .from
@@ -65,6 +119,10 @@ impl NotificationState {
             count += 1;
         }
 
+        if count > 0 && config.new_mail_sound {
+            sounds::play_event(config, SoundEvent::NewMail);
+        }
+
         // Prune old entries to prevent unbounded growth.
         if self.notified_messages.len() > 5000 {
             self.notified_messages.clear();
@@ -73,124 +131,50 @@ impl NotificationState {
         count
     }
 
-    /// Check calendar events for upcoming reminders and send notifications.
-    pub fn check_calendar_reminders(
-        &mut self,
-        config: &NotificationConfig,
-        events: &[CalendarEvent],
-    ) -> usize {
-        if !config.reminder_enabled || is_quiet_hours(config) {
-            return 0;
-        }
-
-        let now = Utc::now();
+    /// Send high-priority notifications for new messages in watched threads.
+    /// A watch is an explicit request, so it ignores the new-mail toggle and
+    /// quiet hours. Run before `check_new_mail` so each message fires once.
+    pub fn check_watched_threads(&mut self, messages: &[cove_core::MailMessage]) -> usize {
         let mut count = 0;
-
-        for event in events {
-            let start = event.starts_at;
-            if start < now {
+        for msg in messages {
+            if msg.flags.seen || !self.notified_messages.insert(msg.id) {
                 continue;
             }
 
-            let minutes_until = (start - now).num_minutes();
-
-            for &mins in &config.reminder_minutes_before {
-                let key = (event.id, mins);
-                if self.notified_reminders.contains(&key) {
-                    continue;
-                }
-                if minutes_until <= mins && minutes_until >= 0 {
-                    self.notified_reminders.insert(key);
-
-                    let label = if mins == 0 {
-                        "now".to_string()
-                    } else {
-                        format!("in {mins} min")
-                    };
-
-                    let _ = Notification::new()
-                        .summary(&format!("{} - {label}", event.title))
-                        .body(&event.location.clone().unwrap_or_default())
-                        .appname("Cove Mail")
-                        .timeout(10000)
-                        .show();
-
-                    count += 1;
-                }
-            }
-        }
+            let sender = msg
+                .from
+                .first()
+                .map(|a| a.name.as_deref().unwrap_or(&a.address).to_string())
+                .unwrap_or_else(|| "Unknown sender".to_string());
+
+            let mut notification = Notification::new();
+            notification
+                .summary(&format!("Reply in watched thread from {sender}"))
+                .body(&msg.subject)
+                .appname("Cove Mail")
+                .timeout(15000);
+            #[cfg(all(unix, not(target_os = "macos")))]
+            notification.urgency(notify_rust::Urgency::Critical);
+            let _ = notification.show();
 
-        // Prune old entries.
-        if self.notified_reminders.len() > 2000 {
-            self.notified_reminders.clear();
+            count += 1;
         }
 
         count
     }
 
-    /// Check tasks with due dates for reminders.
-    pub fn check_task_reminders(
-        &mut self,
-        config: &NotificationConfig,
-        tasks: &[ReminderTask],
-    ) -> usize {
-        if !config.reminder_enabled || is_quiet_hours(config) {
-            return 0;
-        }
-
-        let now = Utc::now();
-        let mut count = 0;
-
-        for task in tasks {
-            // Skip completed/canceled tasks.
-            if task.completed_at.is_some() {
-                continue;
-            }
-
-            // Respect snooze.
-            if let Some(snoozed) = task.snoozed_until {
-                if now < snoozed {
-                    continue;
-                }
-            }
-
-            let due = match task.due_at {
-                Some(dt) => dt,
-                None => continue,
-            };
-            if due < now {
-                continue;
-            }
-
-            let minutes_until = (due - now).num_minutes();
-
-            for &mins in &config.reminder_minutes_before {
-                let key = (task.id, mins);
-                if self.notified_reminders.contains(&key) {
-                    continue;
-                }
-                if minutes_until <= mins && minutes_until >= 0 {
-                    self.notified_reminders.insert(key);
-
-                    let label = if mins == 0 {
-                        "now".to_string()
-                    } else {
-                        format!("in {mins} min")
-                    };
-
-                    let _ = Notification::new()
-                        .summary(&format!("Task due {label}"))
-                        .body(&task.title)
-                        .appname("Cove Mail")
-                        .timeout(10000)
-                        .show();
-
-                    count += 1;
-                }
-            }
+    /// Show the daily agenda as a single multi-line notification.
+    pub fn notify_agenda(&self, config: &NotificationConfig, headline: &str, body: &str) {
+        if is_quiet_hours(config) {
+            return;
         }
 
-        count
+        let _ = Notification::new()
+            .summary(headline)
+            .body(body)
+            .appname("Cove Mail")
+            .timeout(20000)
+            .show();
     }
 
     /// Notify about an actionable sync error.
@@ -205,6 +189,88 @@ impl NotificationState {
             .appname("Cove Mail")
             .timeout(10000)
             .show();
+        sounds::play_event(config, SoundEvent::SyncError);
+    }
+}
+
+/// Shows the reminders the scheduler hands over. Alerts wait out quiet
+/// hours; a snoozed message comes back regardless, only silently.
+pub struct ReminderAlerts<'a> {
+    config: &'a NotificationConfig,
+    /// Snoozed messages that came back during this poll.
+    pub mail_returned: usize,
+}
+
+impl<'a> ReminderAlerts<'a> {
+    pub fn new(config: &'a NotificationConfig) -> Self {
+        Self {
+            config,
+            mail_returned: 0,
+        }
+    }
+
+    fn show(&self, summary: &str, body: &str, sound: Option<SoundEvent>) {
+        let _ = Notification::new()
+            .summary(summary)
+            .body(body)
+            .appname("Cove Mail")
+            .timeout(10000)
+            .show();
+        if let Some(sound) = sound {
+            sounds::play_event(self.config, sound);
+        }
+    }
+}
+
+impl ReminderHandler for ReminderAlerts<'_> {
+    fn mail_snooze_ended(&mut self, reminder: &Reminder) -> bool {
+        self.mail_returned += 1;
+        if self.config.new_mail_enabled && !is_quiet_hours(self.config) {
+            self.show("Snoozed mail is back", &reminder.title, None);
+        }
+        true
+    }
+
+    fn follow_up_due(&mut self, reminder: &Reminder) -> bool {
+        if is_quiet_hours(self.config) {
+            return false;
+        }
+        let body = match &reminder.detail {
+            Some(sender) => format!("{} ({sender})", reminder.title),
+            None => reminder.title.clone(),
+        };
+        self.show("Time to reply", &body, None);
+        true
+    }
+
+    fn event_alert(&mut self, reminder: &Reminder) -> bool {
+        if !self.config.reminder_enabled {
+            return true;
+        }
+        if is_quiet_hours(self.config) {
+            return false;
+        }
+        self.show(
+            &format!("{} - {}", reminder.title, reminder.lead_label()),
+            reminder.detail.as_deref().unwrap_or_default(),
+            Some(SoundEvent::CalendarReminder),
+        );
+        true
+    }
+
+    fn task_due(&mut self, reminder: &Reminder) -> bool {
+        if !self.config.reminder_enabled {
+            return true;
+        }
+        if is_quiet_hours(self.config) {
+            return false;
+        }
+        self.show(
+            &format!("Task due {}", reminder.lead_label()),
+            &reminder.title,
+            Some(SoundEvent::TaskDue),
+        );
+        true
     }
 }
 
@@ -0,0 +1,304 @@
+//! To/Cc/Bcc recipient chips for the compose window.
+//!
+//! Typed text becomes a chip on `,`, `;`, Enter or focus loss. Every chip is
+//! parsed on creation; invalid and duplicate chips are shown in red/orange
+//! and listed by [`Recipients::errors`], which blocks sending.
+
+use cove_core::MailAddress;
+use cove_email::{parse_recipient, split_recipient_input};
+use egui::{Color32, RichText, Ui};
+use std::collections::HashSet;
+
+const INVALID: Color32 = Color32::from_rgb(220, 80, 80);
+const DUPLICATE: Color32 = Color32::from_rgb(230, 160, 60);
+
+/// Parsed (to, cc, bcc) addresses.
+pub type AddressLists = (Vec<MailAddress>, Vec<MailAddress>, Vec<MailAddress>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RecipientKind {
+    To,
+    Cc,
+    Bcc,
+}
+
+impl RecipientKind {
+    const ALL: [RecipientKind; 3] = [RecipientKind::To, RecipientKind::Cc, RecipientKind::Bcc];
+
+    fn label(self) -> &'static str {
+        match self {
+            RecipientKind::To => "To",
+            RecipientKind::Cc => "Cc",
+            RecipientKind::Bcc => "Bcc",
+        }
+    }
+}
+
+pub struct RecipientChip {
+    pub raw: String,
+    pub parsed: Result<MailAddress, String>,
+}
+
+impl RecipientChip {
+    fn new(raw: String) -> Self {
+        let parsed = parse_recipient(&raw).map_err(|err| err.to_string());
+        Self { raw, parsed }
+    }
+
+    fn dedup_key(&self) -> Option<String> {
+        self.parsed
+            .as_ref()
+            .ok()
+            .map(|address| address.address.to_lowercase())
+    }
+
+    fn text(&self) -> String {
+        match &self.parsed {
+            Ok(MailAddress {
+                name: Some(name),
+                address,
+            }) => format!("{name} <{address}>"),
+            Ok(MailAddress { address, .. }) => address.clone(),
+            Err(_) => self.raw.clone(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct RecipientField {
+    pub chips: Vec<RecipientChip>,
+    /// Text typed after the last chip; also the autocomplete query.
+    pub input: String,
+}
+
+impl RecipientField {
+    pub fn push_raw(&mut self, raw: &str) {
+        self.chips
+            .extend(split_recipient_input(raw).into_iter().map(RecipientChip::new));
+    }
+
+    /// Replace the field's contents with the entries in `raw`.
+    pub fn set(&mut self, raw: &str) {
+        self.clear();
+        self.push_raw(raw);
+    }
+
+    pub fn clear(&mut self) {
+        self.chips.clear();
+        self.input.clear();
+    }
+
+    /// Chips plus any uncommitted text, as typed; used for crash recovery.
+    pub fn entries(&self) -> Vec<String> {
+        let mut entries: Vec<String> = self.chips.iter().map(|chip| chip.raw.clone()).collect();
+        if !self.input.trim().is_empty() {
+            entries.push(self.input.trim().to_string());
+        }
+        entries
+    }
+
+    fn commit_input(&mut self) {
+        let input = std::mem::take(&mut self.input);
+        self.push_raw(&input);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chips.is_empty() && self.input.trim().is_empty()
+    }
+}
+
+#[derive(Default)]
+pub struct Recipients {
+    pub to: RecipientField,
+    pub cc: RecipientField,
+    pub bcc: RecipientField,
+    show_cc: bool,
+    show_bcc: bool,
+    /// Field whose input was edited last; autocomplete picks land here.
+    active: Option<RecipientKind>,
+}
+
+impl Recipients {
+    pub fn field(&self, kind: RecipientKind) -> &RecipientField {
+        match kind {
+            RecipientKind::To => &self.to,
+            RecipientKind::Cc => &self.cc,
+            RecipientKind::Bcc => &self.bcc,
+        }
+    }
+
+    pub fn field_mut(&mut self, kind: RecipientKind) -> &mut RecipientField {
+        match kind {
+            RecipientKind::To => &mut self.to,
+            RecipientKind::Cc => &mut self.cc,
+            RecipientKind::Bcc => &mut self.bcc,
+        }
+    }
+
+    /// The field being typed into, for contact autocomplete.
+    pub fn active_field_mut(&mut self) -> &mut RecipientField {
+        self.field_mut(self.active.unwrap_or(RecipientKind::To))
+    }
+
+    pub fn clear(&mut self) {
+        for kind in RecipientKind::ALL {
+            self.field_mut(kind).clear();
+        }
+        self.show_cc = false;
+        self.show_bcc = false;
+        self.active = None;
+    }
+
+    /// For each field, whether each chip repeats an address from an earlier
+    /// chip in To, Cc or Bcc.
+    fn duplicate_flags(&self) -> [Vec<bool>; 3] {
+        let mut seen = HashSet::new();
+        RecipientKind::ALL.map(|kind| {
+            self.field(kind)
+                .chips
+                .iter()
+                .map(|chip| chip.dedup_key().is_some_and(|key| !seen.insert(key)))
+                .collect()
+        })
+    }
+
+    /// Problems that block sending, one line per offending chip.
+    pub fn errors(&self) -> Vec<String> {
+        let duplicates = self.duplicate_flags();
+        let mut errors = Vec::new();
+        for (kind, flags) in RecipientKind::ALL.into_iter().zip(duplicates) {
+            for (chip, duplicate) in self.field(kind).chips.iter().zip(flags) {
+                match &chip.parsed {
+                    Err(err) => errors.push(format!("{}: {err}", kind.label())),
+                    Ok(address) if duplicate => errors.push(format!(
+                        "{}: {} is already a recipient",
+                        kind.label(),
+                        address.address
+                    )),
+                    Ok(_) => {}
+                }
+            }
+        }
+        errors
+    }
+
+    /// Commit any typed text and return (to, cc, bcc), or the errors that
+    /// block sending.
+    pub fn addresses(&mut self) -> Result<AddressLists, String> {
+        for kind in RecipientKind::ALL {
+            self.field_mut(kind).commit_input();
+        }
+        let errors = self.errors();
+        if !errors.is_empty() {
+            return Err(errors.join("; "));
+        }
+        let collect = |field: &RecipientField| {
+            field
+                .chips
+                .iter()
+                .filter_map(|chip| chip.parsed.clone().ok())
+                .collect::<Vec<_>>()
+        };
+        Ok((collect(&self.to), collect(&self.cc), collect(&self.bcc)))
+    }
+
+    /// Draw the recipient rows. Returns the field whose typed text changed,
+    /// so the caller can refresh autocomplete.
+    pub fn show(&mut self, ui: &mut Ui) -> Option<RecipientKind> {
+        let duplicates = self.duplicate_flags();
+        let mut changed = None;
+        let mut moved: Option<((RecipientKind, usize), RecipientKind)> = None;
+        let mut removed: Option<(RecipientKind, usize)> = None;
+
+        for (kind, flags) in RecipientKind::ALL.into_iter().zip(duplicates) {
+            let visible = match kind {
+                RecipientKind::To => true,
+                RecipientKind::Cc => self.show_cc || !self.cc.is_empty(),
+                RecipientKind::Bcc => self.show_bcc || !self.bcc.is_empty(),
+            };
+            if !visible {
+                continue;
+            }
+
+            let field = self.field_mut(kind);
+            let (_, dropped) = ui.dnd_drop_zone::<(RecipientKind, usize), ()>(
+                egui::Frame::default().inner_margin(egui::Margin::symmetric(4, 2)),
+                |ui| {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label(format!("{}:", kind.label()));
+                        for (index, (chip, duplicate)) in field.chips.iter().zip(&flags).enumerate() {
+                            let (color, hover) = match &chip.parsed {
+                                Err(err) => (INVALID, Some(err.clone())),
+                                Ok(_) if *duplicate => {
+                                    (DUPLICATE, Some("Already a recipient".to_string()))
+                                }
+                                Ok(_) => (ui.visuals().text_color(), None),
+                            };
+                            let response = ui
+                                .dnd_drag_source(
+                                    egui::Id::new(("recipient_chip", kind, index)),
+                                    (kind, index),
+                                    |ui| {
+                                        egui::Frame::group(ui.style())
+                                            .inner_margin(egui::Margin::symmetric(6, 1))
+                                            .show(ui, |ui| {
+                                                ui.label(RichText::new(chip.text()).color(color));
+                                            });
+                                    },
+                                )
+                                .response;
+                            if let Some(hover) = hover {
+                                response.on_hover_text(hover);
+                            }
+                            if ui.small_button("×").clicked() {
+                                removed = Some((kind, index));
+                            }
+                        }
+
+                        let response = ui.add(
+                            egui::TextEdit::singleline(&mut field.input)
+                                .desired_width(180.0)
+                                .hint_text("name@example.com"),
+                        );
+                        if response.changed() {
+                            if field.input.contains([',', ';']) {
+                                field.commit_input();
+                            }
+                            changed = Some(kind);
+                        }
+                        if response.lost_focus() && !field.input.trim().is_empty() {
+                            field.commit_input();
+                        }
+                    });
+                },
+            );
+            if let Some(payload) = dropped {
+                if payload.0 != kind {
+                    moved = Some((*payload, kind));
+                }
+            }
+        }
+
+        ui.horizontal(|ui| {
+            if self.cc.is_empty() {
+                ui.toggle_value(&mut self.show_cc, "Cc");
+            }
+            if self.bcc.is_empty() {
+                ui.toggle_value(&mut self.show_bcc, "Bcc");
+            }
+        });
+
+        if let Some((kind, index)) = removed {
+            self.field_mut(kind).chips.remove(index);
+        } else if let Some(((from, index), to)) = moved {
+            if index < self.field(from).chips.len() {
+                let chip = self.field_mut(from).chips.remove(index);
+                self.field_mut(to).chips.push(chip);
+            }
+        }
+        if changed.is_some() {
+            self.active = changed;
+        }
+        changed
+    }
+}
@@ -0,0 +1,63 @@
+//! Crash recovery journal for work that is not yet a draft: the compose
+//! window, the chat reply box and open message dialogs.
+//!
+//! A snapshot is written to storage every few seconds while it changes and
+//! restored on the next launch, so a crash or forced quit loses at most the
+//! last few seconds of typing.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Storage key for this client's snapshot.
+pub const JOURNAL_CLIENT: &str = "native";
+/// Minimum time between journal writes.
+pub const SAVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3);
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecoverySnapshot {
+    pub compose: Option<ComposeSnapshot>,
+    pub chat_reply: Option<ChatReplySnapshot>,
+    /// Message whose snooze dialog was open.
+    pub snooze_message: Option<Uuid>,
+    pub note_clip: Option<NoteClipSnapshot>,
+}
+
+impl RecoverySnapshot {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ComposeSnapshot {
+    pub open: bool,
+    pub to: Vec<String>,
+    pub cc: Vec<String>,
+    pub bcc: Vec<String>,
+    pub subject: String,
+    pub body: String,
+    pub attachment_paths: Vec<String>,
+}
+
+impl ComposeSnapshot {
+    pub fn has_content(&self) -> bool {
+        !(self.to.is_empty()
+            && self.cc.is_empty()
+            && self.bcc.is_empty()
+            && self.subject.is_empty()
+            && self.body.is_empty()
+            && self.attachment_paths.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatReplySnapshot {
+    pub contact: Option<String>,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NoteClipSnapshot {
+    pub message_id: Uuid,
+    pub excerpt: String,
+}
@@ -0,0 +1,132 @@
+//! UI state restored between launches: the selected account, folder and
+//! thread, the active view, scroll positions, panel widths and window
+//! geometry.
+//!
+//! Stored as JSON in the cache directory. A missing or unreadable file just
+//! means a fresh start, so nothing here is allowed to fail the launch.
+
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+const FILE_NAME: &str = "session.json";
+/// Windows smaller than this are not worth restoring.
+const MIN_WINDOW_SIZE: f32 = 200.0;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionState {
+    pub account: Option<Uuid>,
+    pub folder: Option<String>,
+    pub thread: Option<String>,
+    pub message: Option<Uuid>,
+    pub unified_inbox: bool,
+    /// Key of the active view, e.g. `inbox` or `calendar`.
+    pub view: Option<String>,
+    pub window: Option<WindowGeometry>,
+    /// Widths of resizable side panels, by panel id.
+    pub panel_widths: BTreeMap<String, f32>,
+    /// Vertical offsets of scroll areas, by a stable name.
+    pub scroll_offsets: BTreeMap<String, f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub maximized: bool,
+}
+
+pub fn path(cache_dir: &Path) -> PathBuf {
+    cache_dir.join(FILE_NAME)
+}
+
+impl SessionState {
+    pub fn load(path: &Path) -> Self {
+        match std::fs::read(path) {
+            Ok(raw) => serde_json::from_slice(&raw).unwrap_or_else(|err| {
+                tracing::warn!("ignoring unreadable session state {}: {err}", path.display());
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_vec_pretty(self).map_err(std::io::Error::other)?;
+        let temp = path.with_extension("tmp");
+        std::fs::write(&temp, json)?;
+        std::fs::rename(&temp, path)
+    }
+
+    /// Apply the saved window geometry to the startup viewport.
+    pub fn apply_window(&self, builder: egui::ViewportBuilder) -> egui::ViewportBuilder {
+        let Some(window) = self.window else {
+            return builder;
+        };
+        if window.width < MIN_WINDOW_SIZE || window.height < MIN_WINDOW_SIZE {
+            return builder;
+        }
+        builder
+            .with_inner_size([window.width, window.height])
+            .with_position([window.x, window.y])
+            .with_maximized(window.maximized)
+    }
+
+    /// Record the current window geometry. While maximized the restored
+    /// size is kept, so un-maximizing after a restart lands somewhere sane.
+    pub fn track_window(&mut self, ctx: &egui::Context) {
+        let (outer, inner, maximized, minimized) = ctx.input(|i| {
+            let viewport = i.viewport();
+            (
+                viewport.outer_rect,
+                viewport.inner_rect,
+                viewport.maximized.unwrap_or(false),
+                viewport.minimized.unwrap_or(false),
+            )
+        });
+        if minimized {
+            return;
+        }
+        if maximized {
+            if let Some(window) = &mut self.window {
+                window.maximized = true;
+            }
+            return;
+        }
+        let (Some(outer), Some(inner)) = (outer, inner) else {
+            return;
+        };
+        self.window = Some(WindowGeometry {
+            x: outer.min.x,
+            y: outer.min.y,
+            width: inner.width(),
+            height: inner.height(),
+            maximized: false,
+        });
+    }
+
+    pub fn panel_width(&self, id: &str, default: f32) -> f32 {
+        self.panel_widths.get(id).copied().unwrap_or(default)
+    }
+
+    /// Record the widths of the side panels in `ids` that have been shown.
+    pub fn track_panels(&mut self, ctx: &egui::Context, ids: &[&str]) {
+        for id in ids {
+            if let Some(state) = egui::containers::panel::PanelState::load(ctx, egui::Id::new(id)) {
+                self.panel_widths.insert(id.to_string(), state.rect.width());
+            }
+        }
+    }
+
+    pub fn set_scroll_offset(&mut self, name: &str, offset: f32) {
+        self.scroll_offsets.insert(name.to_string(), offset);
+    }
+}
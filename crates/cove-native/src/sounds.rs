@@ -0,0 +1,158 @@
+//! Notification sounds: short synthesized tones bundled in the binary plus
+//! user-provided audio files, played on a background thread.
+
+use cove_config::{NotificationConfig, NotificationSound};
+#[cfg(feature = "sounds")]
+use rodio::source::{SineWave, Source};
+#[cfg(feature = "sounds")]
+use rodio::{Decoder, OutputStream, Sink};
+#[cfg(feature = "sounds")]
+use std::fs::File;
+#[cfg(feature = "sounds")]
+use std::io::BufReader;
+#[cfg(feature = "sounds")]
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundEvent {
+    NewMail,
+    VipMail,
+    CalendarReminder,
+    TaskDue,
+    SyncError,
+}
+
+impl SoundEvent {
+    pub const ALL: [SoundEvent; 5] = [
+        SoundEvent::NewMail,
+        SoundEvent::VipMail,
+        SoundEvent::CalendarReminder,
+        SoundEvent::TaskDue,
+        SoundEvent::SyncError,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SoundEvent::NewMail => "New mail",
+            SoundEvent::VipMail => "New VIP mail",
+            SoundEvent::CalendarReminder => "Calendar reminder",
+            SoundEvent::TaskDue => "Task due",
+            SoundEvent::SyncError => "Sync error",
+        }
+    }
+
+    pub fn sound(self, config: &NotificationConfig) -> &NotificationSound {
+        let sounds = &config.sounds;
+        match self {
+            SoundEvent::NewMail => &sounds.new_mail,
+            SoundEvent::VipMail => &sounds.vip_mail,
+            SoundEvent::CalendarReminder => &sounds.calendar_reminder,
+            SoundEvent::TaskDue => &sounds.task_due,
+            SoundEvent::SyncError => &sounds.sync_error,
+        }
+    }
+
+    pub fn sound_mut(self, config: &mut NotificationConfig) -> &mut NotificationSound {
+        let sounds = &mut config.sounds;
+        match self {
+            SoundEvent::NewMail => &mut sounds.new_mail,
+            SoundEvent::VipMail => &mut sounds.vip_mail,
+            SoundEvent::CalendarReminder => &mut sounds.calendar_reminder,
+            SoundEvent::TaskDue => &mut sounds.task_due,
+            SoundEvent::SyncError => &mut sounds.sync_error,
+        }
+    }
+}
+
+/// Bundled sounds offered in Settings, in display order.
+pub const BUNDLED: [(NotificationSound, &str); 5] = [
+    (NotificationSound::Silent, "Silent"),
+    (NotificationSound::Chime, "Chime"),
+    (NotificationSound::Bell, "Bell"),
+    (NotificationSound::Pop, "Pop"),
+    (NotificationSound::Alert, "Alert"),
+];
+
+pub fn sound_label(sound: &NotificationSound) -> String {
+    match sound {
+        NotificationSound::File(path) => std::path::Path::new(path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(path)
+            .to_string(),
+        other => BUNDLED
+            .iter()
+            .find(|(bundled, _)| bundled == other)
+            .map(|(_, label)| label.to_string())
+            .unwrap_or_default(),
+    }
+}
+
+/// Play the sound mapped to `event`, unless sounds are globally muted.
+pub fn play_event(config: &NotificationConfig, event: SoundEvent) {
+    if config.sounds_muted {
+        return;
+    }
+    play(event.sound(config).clone());
+}
+
+/// Play a sound without blocking the UI. Failures (no audio device,
+/// unreadable file) are logged and otherwise ignored. Builds without the
+/// `sounds` feature stay silent.
+pub fn play(sound: NotificationSound) {
+    if sound == NotificationSound::Silent || !cfg!(feature = "sounds") {
+        return;
+    }
+
+    std::thread::spawn(move || {
+        if let Err(err) = play_blocking(&sound) {
+            tracing::warn!("notification sound failed: {err}");
+        }
+    });
+}
+
+#[cfg(not(feature = "sounds"))]
+fn play_blocking(_sound: &NotificationSound) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(feature = "sounds")]
+fn play_blocking(sound: &NotificationSound) -> Result<(), String> {
+    let (_stream, handle) = OutputStream::try_default().map_err(|err| err.to_string())?;
+    let sink = Sink::try_new(&handle).map_err(|err| err.to_string())?;
+
+    match sound {
+        NotificationSound::Silent => return Ok(()),
+        NotificationSound::File(path) => {
+            let file = File::open(path).map_err(|err| format!("{path}: {err}"))?;
+            let source = Decoder::new(BufReader::new(file)).map_err(|err| format!("{path}: {err}"))?;
+            // Cap custom sounds so a long file cannot play indefinitely.
+            sink.append(source.take_duration(Duration::from_secs(5)));
+        }
+        bundled => {
+            for &(frequency, millis) in tones(bundled) {
+                sink.append(
+                    SineWave::new(frequency)
+                        .take_duration(Duration::from_millis(millis))
+                        .fade_in(Duration::from_millis(8))
+                        .amplify(0.20),
+                );
+            }
+        }
+    }
+
+    sink.sleep_until_end();
+    Ok(())
+}
+
+/// (frequency Hz, duration ms) steps for each bundled sound.
+#[cfg(feature = "sounds")]
+fn tones(sound: &NotificationSound) -> &'static [(f32, u64)] {
+    match sound {
+        NotificationSound::Chime => &[(880.0, 120), (1318.5, 220)],
+        NotificationSound::Bell => &[(1046.5, 160), (784.0, 160), (1046.5, 260)],
+        NotificationSound::Pop => &[(660.0, 70)],
+        NotificationSound::Alert => &[(440.0, 140), (0.0, 60), (440.0, 140), (0.0, 60), (440.0, 200)],
+        NotificationSound::Silent | NotificationSound::File(_) => &[],
+    }
+}
@@ -0,0 +1,181 @@
+//! Compose spell checking with Hunspell `.aff`/`.dic` dictionaries.
+//!
+//! Dictionaries ship in a `dictionaries/` directory next to the executable;
+//! the user's config dir and the system Hunspell paths are searched too, so
+//! extra languages can be dropped in without a rebuild. Words the user adds
+//! go to `custom_dictionary.txt` in the config dir, one per line.
+
+use egui::text::{LayoutJob, TextFormat};
+use egui::{Color32, FontId, Stroke};
+use spellbook::Dictionary;
+use std::collections::BTreeSet;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+const CUSTOM_DICTIONARY_FILE: &str = "custom_dictionary.txt";
+const MAX_SUGGESTIONS: usize = 6;
+
+pub struct SpellChecker {
+    dictionary: Option<Dictionary>,
+    custom_words: BTreeSet<String>,
+    custom_path: PathBuf,
+}
+
+impl SpellChecker {
+    /// Load `language` (e.g. `en_US`). Without a dictionary on disk every
+    /// word is accepted and [`Self::is_available`] reports false.
+    pub fn load(language: &str, config_dir: &Path) -> Self {
+        let dictionary = dictionary_dirs(config_dir)
+            .into_iter()
+            .find_map(|dir| load_dictionary(&dir, language));
+
+        let custom_path = config_dir.join(CUSTOM_DICTIONARY_FILE);
+        let custom_words = std::fs::read_to_string(&custom_path)
+            .map(|raw| {
+                raw.lines()
+                    .map(str::trim)
+                    .filter(|word| !word.is_empty())
+                    .map(str::to_lowercase)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            dictionary,
+            custom_words,
+            custom_path,
+        }
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.dictionary.is_some()
+    }
+
+    pub fn is_correct(&self, word: &str) -> bool {
+        let Some(dictionary) = &self.dictionary else {
+            return true;
+        };
+        self.custom_words.contains(&word.to_lowercase()) || dictionary.check(word)
+    }
+
+    /// Byte ranges of misspelled words in `text`.
+    pub fn misspelled(&self, text: &str) -> Vec<Range<usize>> {
+        if self.dictionary.is_none() {
+            return Vec::new();
+        }
+        words(text)
+            .filter(|range| !self.is_correct(&text[range.clone()]))
+            .collect()
+    }
+
+    pub fn suggestions(&self, word: &str) -> Vec<String> {
+        let Some(dictionary) = &self.dictionary else {
+            return Vec::new();
+        };
+        let mut suggestions = Vec::new();
+        dictionary.suggest(word, &mut suggestions);
+        suggestions.truncate(MAX_SUGGESTIONS);
+        suggestions
+    }
+
+    /// Accept `word` from now on and persist it to the custom dictionary.
+    pub fn add_word(&mut self, word: &str) -> std::io::Result<()> {
+        if !self.custom_words.insert(word.to_lowercase()) {
+            return Ok(());
+        }
+        let contents = self
+            .custom_words
+            .iter()
+            .map(|word| format!("{word}\n"))
+            .collect::<String>();
+        std::fs::write(&self.custom_path, contents)
+    }
+
+    /// Plain-text layout with a red underline under each misspelled word,
+    /// for use as a `TextEdit` layouter.
+    pub fn layout_job(&self, text: &str, font_id: FontId, color: Color32, wrap_width: f32) -> LayoutJob {
+        let plain = TextFormat::simple(font_id, color);
+        let misspelled = TextFormat {
+            underline: Stroke::new(1.5, Color32::from_rgb(220, 60, 60)),
+            ..plain.clone()
+        };
+
+        let mut job = LayoutJob::default();
+        let mut last = 0;
+        for range in self.misspelled(text) {
+            job.append(&text[last..range.start], 0.0, plain.clone());
+            job.append(&text[range.clone()], 0.0, misspelled.clone());
+            last = range.end;
+        }
+        job.append(&text[last..], 0.0, plain);
+        job.wrap.max_width = wrap_width;
+        job
+    }
+}
+
+/// The word (as a byte range) touching byte offset `at`, if any.
+pub fn word_at(text: &str, at: usize) -> Option<Range<usize>> {
+    words(text).find(|range| range.start <= at && at <= range.end)
+}
+
+/// Byte ranges of checkable words. Apostrophes inside a word are kept
+/// ("don't"); tokens with digits, `@` or `://` are skipped as they are
+/// numbers, addresses or links rather than prose.
+fn words(text: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (index, ch) in text.char_indices() {
+        let in_token = !ch.is_whitespace() && !matches!(ch, '<' | '>' | '(' | ')' | '"' | ',' | ';');
+        match (in_token, start) {
+            (true, None) => start = Some(index),
+            (false, Some(begin)) => {
+                tokens.push(begin..index);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(begin) = start {
+        tokens.push(begin..text.len());
+    }
+
+    tokens.into_iter().filter_map(move |token| {
+        let raw = &text[token.clone()];
+        if raw.contains('@') || raw.contains("://") || raw.chars().any(|ch| ch.is_ascii_digit()) {
+            return None;
+        }
+        let leading = raw.len() - raw.trim_start_matches(|ch: char| !ch.is_alphabetic()).len();
+        let trailing = raw.len() - raw.trim_end_matches(|ch: char| !ch.is_alphabetic()).len();
+        let word = token.start + leading..token.end - trailing;
+        (word.start < word.end && text[word.clone()].chars().count() > 1).then_some(word)
+    })
+}
+
+fn dictionary_dirs(config_dir: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(exe_dir) = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+    {
+        dirs.push(exe_dir.join("dictionaries"));
+    }
+    dirs.push(config_dir.join("dictionaries"));
+    dirs.extend(
+        ["/usr/share/hunspell", "/usr/share/myspell/dicts", "/Library/Spelling"]
+            .into_iter()
+            .map(PathBuf::from),
+    );
+    dirs
+}
+
+fn load_dictionary(dir: &Path, language: &str) -> Option<Dictionary> {
+    let aff = std::fs::read_to_string(dir.join(format!("{language}.aff"))).ok()?;
+    let dic = std::fs::read_to_string(dir.join(format!("{language}.dic"))).ok()?;
+    match Dictionary::new(&aff, &dic) {
+        Ok(dictionary) => Some(dictionary),
+        Err(err) => {
+            tracing::warn!("invalid spell check dictionary in {}: {err}", dir.display());
+            None
+        }
+    }
+}
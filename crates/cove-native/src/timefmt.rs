@@ -0,0 +1,44 @@
+//! Timestamps for display: local time, relative to today, with the full
+//! date and zone on hover.
+
+use chrono::{DateTime, Datelike, Local, TimeZone, Utc};
+use eframe::egui;
+
+/// Days back for which a weekday name is clearer than a date.
+const WEEKDAY_RANGE_DAYS: i64 = 6;
+
+/// "14:03", "Yesterday 14:03", "Mon", "Mar 3" or "Mar 3, 2023", in local
+/// time.
+pub fn relative(at: DateTime<Utc>) -> String {
+    relative_to(at, Local::now())
+}
+
+/// [`relative`] against an explicit "now", whose zone is used for display.
+pub fn relative_to<Tz: TimeZone>(at: DateTime<Utc>, now: DateTime<Tz>) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let local = at.with_timezone(&now.timezone());
+    let days = (now.date_naive() - local.date_naive()).num_days();
+    match days {
+        0 => local.format("%H:%M").to_string(),
+        1 => format!("Yesterday {}", local.format("%H:%M")),
+        -1 => format!("Tomorrow {}", local.format("%H:%M")),
+        2..=WEEKDAY_RANGE_DAYS => local.format("%a").to_string(),
+        _ if local.year() == now.year() => local.format("%b %-d").to_string(),
+        _ => local.format("%b %-d, %Y").to_string(),
+    }
+}
+
+/// "Monday, March 3, 2025 14:03:09 (UTC+01:00)" in local time.
+pub fn full(at: DateTime<Utc>) -> String {
+    at.with_timezone(&Local)
+        .format("%A, %B %-d, %Y %H:%M:%S (UTC%:z)")
+        .to_string()
+}
+
+/// A weak [`relative`] timestamp that shows [`full`] on hover.
+pub fn label(ui: &mut egui::Ui, at: DateTime<Utc>, size: f32) -> egui::Response {
+    ui.label(egui::RichText::new(relative(at)).size(size).weak())
+        .on_hover_text(full(at))
+}
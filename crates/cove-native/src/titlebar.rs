@@ -0,0 +1,135 @@
+//! Platform-aware window chrome for the custom titlebar.
+//!
+//! macOS keeps its native traffic lights over a full-size content view, so
+//! the titlebar only leaves room for them. Windows and Linux run without
+//! OS decorations and get minimize/maximize/close buttons, double-click to
+//! maximize and resize borders drawn here. Dragging goes through the
+//! system move loop, so Aero Snap, Win+arrow and tiling window managers
+//! keep working.
+
+use eframe::egui;
+use egui::{Color32, CursorIcon, ResizeDirection, ViewportCommand};
+
+const MACOS: bool = cfg!(target_os = "macos");
+/// Width of the invisible resize border on undecorated windows.
+const RESIZE_MARGIN: f32 = 5.0;
+const CLOSE_HOVER: Color32 = Color32::from_rgb(196, 43, 28);
+
+pub fn viewport_builder() -> egui::ViewportBuilder {
+    let builder = egui::ViewportBuilder::default().with_transparent(true);
+    if MACOS {
+        builder
+            .with_decorations(true)
+            .with_fullsize_content_view(true)
+            .with_titlebar_shown(false)
+            .with_title_shown(false)
+    } else {
+        builder.with_decorations(false).with_resizable(true)
+    }
+}
+
+/// Left padding of the titlebar: room for the traffic lights on macOS.
+pub fn leading_inset() -> i8 {
+    if MACOS {
+        80
+    } else {
+        8
+    }
+}
+
+fn is_maximized(ctx: &egui::Context) -> bool {
+    ctx.input(|i| i.viewport().maximized.unwrap_or(false))
+}
+
+/// Make `response` (the titlebar background) move the window, and
+/// double-click toggle maximize.
+pub fn drag_region(ctx: &egui::Context, response: &egui::Response) {
+    if response.double_clicked() {
+        ctx.send_viewport_cmd(ViewportCommand::Maximized(!is_maximized(ctx)));
+    } else if response.drag_started() {
+        ctx.send_viewport_cmd(ViewportCommand::StartDrag);
+    }
+}
+
+/// Minimize, maximize/restore and close, right to left. Draws nothing on
+/// macOS. Call inside a right-to-left layout.
+pub fn window_controls(ui: &mut egui::Ui) {
+    if MACOS {
+        return;
+    }
+    let ctx = ui.ctx().clone();
+    let button = |text: &str| {
+        egui::Button::new(egui::RichText::new(text).size(14.0))
+            .frame(false)
+            .min_size(egui::vec2(36.0, 24.0))
+    };
+
+    let close = ui.add(button("✕")).on_hover_text("Close");
+    if close.hovered() {
+        ui.painter().rect_filled(close.rect, 2.0, CLOSE_HOVER);
+        ui.painter().text(
+            close.rect.center(),
+            egui::Align2::CENTER_CENTER,
+            "✕",
+            egui::FontId::proportional(14.0),
+            Color32::WHITE,
+        );
+    }
+    if close.clicked() {
+        ctx.send_viewport_cmd(ViewportCommand::Close);
+    }
+
+    let maximized = is_maximized(&ctx);
+    let (icon, hover) = if maximized { ("❐", "Restore") } else { ("☐", "Maximize") };
+    if ui.add(button(icon)).on_hover_text(hover).clicked() {
+        ctx.send_viewport_cmd(ViewportCommand::Maximized(!maximized));
+    }
+    if ui.add(button("—")).on_hover_text("Minimize").clicked() {
+        ctx.send_viewport_cmd(ViewportCommand::Minimized(true));
+    }
+}
+
+/// Resize borders for undecorated windows. Call once per frame before
+/// drawing panels so the border wins over widgets underneath.
+pub fn handle_resize(ctx: &egui::Context) {
+    if MACOS || is_maximized(ctx) {
+        return;
+    }
+    let Some(pointer) = ctx.input(|i| i.pointer.hover_pos()) else {
+        return;
+    };
+    let Some(direction) = resize_direction(ctx.screen_rect(), pointer) else {
+        return;
+    };
+    ctx.set_cursor_icon(match direction {
+        ResizeDirection::North => CursorIcon::ResizeNorth,
+        ResizeDirection::South => CursorIcon::ResizeSouth,
+        ResizeDirection::East => CursorIcon::ResizeEast,
+        ResizeDirection::West => CursorIcon::ResizeWest,
+        ResizeDirection::NorthEast => CursorIcon::ResizeNorthEast,
+        ResizeDirection::NorthWest => CursorIcon::ResizeNorthWest,
+        ResizeDirection::SouthEast => CursorIcon::ResizeSouthEast,
+        ResizeDirection::SouthWest => CursorIcon::ResizeSouthWest,
+    });
+    if ctx.input(|i| i.pointer.primary_pressed()) {
+        ctx.send_viewport_cmd(ViewportCommand::BeginResize(direction));
+    }
+}
+
+fn resize_direction(window: egui::Rect, pointer: egui::Pos2) -> Option<ResizeDirection> {
+    let north = pointer.y - window.top() <= RESIZE_MARGIN;
+    let south = window.bottom() - pointer.y <= RESIZE_MARGIN;
+    let west = pointer.x - window.left() <= RESIZE_MARGIN;
+    let east = window.right() - pointer.x <= RESIZE_MARGIN;
+    match (north, south, west, east) {
+        (true, _, true, _) => Some(ResizeDirection::NorthWest),
+        (true, _, _, true) => Some(ResizeDirection::NorthEast),
+        (_, true, true, _) => Some(ResizeDirection::SouthWest),
+        (_, true, _, true) => Some(ResizeDirection::SouthEast),
+        (true, ..) => Some(ResizeDirection::North),
+        (_, true, ..) => Some(ResizeDirection::South),
+        (.., true, _) => Some(ResizeDirection::West),
+        (.., true) => Some(ResizeDirection::East),
+        _ => None,
+    }
+}
@@ -4,8 +4,20 @@ use thiserror::Error;
 pub enum SecurityError {
     #[error("keychain error: {0}")]
     Keychain(#[from] keyring::Error),
+    #[error("secret file error: {0}")]
+    SecretFile(String),
+    #[error("secrets are locked; enter the secrets passphrase to unlock them")]
+    Locked,
+    #[error("wrong secrets passphrase")]
+    WrongPassphrase,
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
     #[error("oauth error: {0}")]
     OAuth(String),
+    /// The provider revoked the grant (or none is stored); refreshing cannot
+    /// help and the user has to sign in again.
+    #[error("re-authentication required: {0}")]
+    ReauthRequired(String),
     #[error("request error: {0}")]
     Request(#[from] reqwest::Error),
     #[error("url parse error: {0}")]
@@ -0,0 +1,331 @@
+use crate::SecurityError;
+use age::secrecy::SecretString;
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Secrets kept in an age-encrypted JSON file instead of the OS keychain.
+///
+/// Used by portable installs and as the keychain fallback; the store starts
+/// locked until [`FileSecretStore::unlock`] is given the passphrase.
+#[derive(Debug)]
+pub(crate) struct FileSecretStore {
+    path: PathBuf,
+    state: Mutex<FileState>,
+}
+
+#[derive(Debug)]
+enum FileState {
+    Locked,
+    Unlocked {
+        entries: BTreeMap<String, String>,
+        passphrase: String,
+    },
+    /// Contents moved to the OS keychain; the file is gone.
+    Retired,
+}
+
+impl FileSecretStore {
+    /// An age-encrypted file, locked until unlocked with its passphrase.
+    pub(crate) fn open_encrypted(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            state: Mutex::new(FileState::Locked),
+        }
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(crate) fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    pub(crate) fn is_locked(&self) -> bool {
+        self.lock()
+            .map(|state| matches!(*state, FileState::Locked))
+            .unwrap_or(true)
+    }
+
+    /// Decrypt the file, or start a new one encrypted with `passphrase` if
+    /// none exists yet.
+    pub(crate) fn unlock(&self, passphrase: &str) -> Result<(), SecurityError> {
+        let mut state = self.lock()?;
+        if !matches!(*state, FileState::Locked) {
+            return Ok(());
+        }
+        let entries = match std::fs::read(&self.path) {
+            Ok(raw) => parse_entries(&self.path, &decrypt(&raw, passphrase)?)?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                if passphrase.is_empty() {
+                    return Err(SecurityError::SecretFile("passphrase is empty".to_string()));
+                }
+                BTreeMap::new()
+            }
+            Err(err) => return Err(err.into()),
+        };
+        *state = FileState::Unlocked {
+            entries,
+            passphrase: passphrase.to_string(),
+        };
+        Ok(())
+    }
+
+    /// All entries, for migration into the keychain.
+    pub(crate) fn entries(&self) -> Result<BTreeMap<String, String>, SecurityError> {
+        match &*self.lock()? {
+            FileState::Unlocked { entries, .. } => Ok(entries.clone()),
+            FileState::Locked => Err(SecurityError::Locked),
+            FileState::Retired => Ok(BTreeMap::new()),
+        }
+    }
+
+    /// Delete the file once its contents live elsewhere.
+    pub(crate) fn retire(&self) -> Result<(), SecurityError> {
+        let mut state = self.lock()?;
+        match std::fs::remove_file(&self.path) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+        *state = FileState::Retired;
+        Ok(())
+    }
+
+    pub(crate) fn get(&self, username: &str) -> Result<Option<String>, SecurityError> {
+        match &*self.lock()? {
+            FileState::Unlocked { entries, .. } => Ok(entries.get(username).cloned()),
+            FileState::Locked => Err(SecurityError::Locked),
+            FileState::Retired => Ok(None),
+        }
+    }
+
+    pub(crate) fn set(&self, username: &str, value: &str) -> Result<(), SecurityError> {
+        let mut state = self.lock()?;
+        let FileState::Unlocked {
+            entries,
+            passphrase,
+        } = &mut *state
+        else {
+            return Err(SecurityError::Locked);
+        };
+        entries.insert(username.to_string(), value.to_string());
+        self.persist(entries, passphrase)
+    }
+
+    pub(crate) fn delete(&self, username: &str) -> Result<(), SecurityError> {
+        let mut state = self.lock()?;
+        let FileState::Unlocked {
+            entries,
+            passphrase,
+        } = &mut *state
+        else {
+            return Err(SecurityError::Locked);
+        };
+        if entries.remove(username).is_some() {
+            self.persist(entries, passphrase)?;
+        }
+        Ok(())
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, FileState>, SecurityError> {
+        self.state
+            .lock()
+            .map_err(|_| SecurityError::SecretFile("secret store lock poisoned".to_string()))
+    }
+
+    /// Write to a fresh sibling temp file and rename, so a crash mid-write
+    /// never leaves a truncated store. The temp file is created owner-only
+    /// and must not exist yet, so nobody can read it or plant a symlink.
+    fn persist(
+        &self,
+        entries: &BTreeMap<String, String>,
+        passphrase: &str,
+    ) -> Result<(), SecurityError> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_vec_pretty(entries)
+            .map_err(|err| SecurityError::SecretFile(err.to_string()))?;
+        let contents = encrypt(&json, passphrase)?;
+        let temp = self
+            .path
+            .with_extension(format!("{}.tmp", uuid::Uuid::new_v4().simple()));
+        let written = create_private(&temp).and_then(|mut file| {
+            file.write_all(&contents)?;
+            file.sync_all()
+        });
+        if let Err(err) = written.and_then(|()| std::fs::rename(&temp, &self.path)) {
+            let _ = std::fs::remove_file(&temp);
+            return Err(err.into());
+        }
+        Ok(())
+    }
+}
+
+fn parse_entries(path: &Path, raw: &[u8]) -> Result<BTreeMap<String, String>, SecurityError> {
+    serde_json::from_slice(raw)
+        .map_err(|err| SecurityError::SecretFile(format!("{}: {err}", path.display())))
+}
+
+fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<Vec<u8>, SecurityError> {
+    let encryptor = age::Encryptor::with_user_passphrase(SecretString::new(passphrase.to_string()));
+    let mut encrypted = Vec::new();
+    let mut writer = encryptor
+        .wrap_output(&mut encrypted)
+        .map_err(|err| SecurityError::SecretFile(err.to_string()))?;
+    writer.write_all(plaintext)?;
+    writer.finish()?;
+    Ok(encrypted)
+}
+
+fn decrypt(ciphertext: &[u8], passphrase: &str) -> Result<Vec<u8>, SecurityError> {
+    let decryptor = match age::Decryptor::new(ciphertext)
+        .map_err(|err| SecurityError::SecretFile(err.to_string()))?
+    {
+        age::Decryptor::Passphrase(decryptor) => decryptor,
+        _ => {
+            return Err(SecurityError::SecretFile(
+                "secrets file is not passphrase-encrypted".to_string(),
+            ))
+        }
+    };
+    let mut reader = decryptor
+        .decrypt(&SecretString::new(passphrase.to_string()), None)
+        .map_err(|err| match err {
+            age::DecryptError::DecryptionFailed | age::DecryptError::NoMatchingKeys => {
+                SecurityError::WrongPassphrase
+            }
+            other => SecurityError::SecretFile(other.to_string()),
+        })?;
+    let mut plaintext = Vec::new();
+    reader.read_to_end(&mut plaintext)?;
+    Ok(plaintext)
+}
+
+/// Create a new file readable only by its owner, failing if anything
+/// (including a symlink) already exists at `path`.
+fn create_private(path: &Path) -> std::io::Result<std::fs::File> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(test: &str) -> PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("cove-file-store-{test}-{nanos}"));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("secrets.age")
+    }
+
+    #[test]
+    fn encrypted_file_round_trips() {
+        let path = temp_path("encrypted");
+        let store = FileSecretStore::open_encrypted(&path);
+        store.unlock("passphrase").unwrap();
+        store.set("mail:work", "hunter2").unwrap();
+        store.set("ai_api_key:openai", "sk-test").unwrap();
+        store.delete("ai_api_key:openai").unwrap();
+
+        let raw = std::fs::read(&path).unwrap();
+        assert!(!String::from_utf8_lossy(&raw).contains("hunter2"));
+
+        let reopened = FileSecretStore::open_encrypted(&path);
+        reopened.unlock("passphrase").unwrap();
+        assert_eq!(reopened.get("mail:work").unwrap().as_deref(), Some("hunter2"));
+        assert_eq!(reopened.get("ai_api_key:openai").unwrap(), None);
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn file_is_owner_only_and_leaves_no_temp_files() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = temp_path("private");
+        let store = FileSecretStore::open_encrypted(&path);
+        store.unlock("passphrase").unwrap();
+        store.set("mail:work", "hunter2").unwrap();
+        store.set("mail:home", "hunter3").unwrap();
+
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        let names: Vec<_> = std::fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, vec![std::ffi::OsString::from("secrets.age")]);
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn wrong_passphrase_is_rejected_and_stays_locked() {
+        let path = temp_path("wrong");
+        let store = FileSecretStore::open_encrypted(&path);
+        store.unlock("right").unwrap();
+        store.set("mail:work", "hunter2").unwrap();
+
+        let reopened = FileSecretStore::open_encrypted(&path);
+        assert!(matches!(
+            reopened.unlock("wrong"),
+            Err(SecurityError::WrongPassphrase)
+        ));
+        assert!(reopened.is_locked());
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn locked_store_refuses_reads_and_writes() {
+        let path = temp_path("locked");
+        let store = FileSecretStore::open_encrypted(&path);
+        assert!(store.is_locked());
+        assert!(matches!(store.get("mail:work"), Err(SecurityError::Locked)));
+        assert!(matches!(
+            store.set("mail:work", "hunter2"),
+            Err(SecurityError::Locked)
+        ));
+        assert!(matches!(store.entries(), Err(SecurityError::Locked)));
+        assert!(!path.exists());
+        assert!(store.unlock("").is_err());
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn retire_deletes_the_file_after_migration() {
+        let path = temp_path("retire");
+        let store = FileSecretStore::open_encrypted(&path);
+        store.unlock("passphrase").unwrap();
+        store.set("mail:work", "hunter2").unwrap();
+
+        let pending = FileSecretStore::open_encrypted(&path);
+        pending.unlock("passphrase").unwrap();
+        let entries = pending.entries().unwrap();
+        assert_eq!(entries.get("mail:work").map(String::as_str), Some("hunter2"));
+        pending.retire().unwrap();
+
+        assert!(!path.exists());
+        assert!(!pending.is_locked());
+        assert_eq!(pending.get("mail:work").unwrap(), None);
+        assert!(pending.entries().unwrap().is_empty());
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
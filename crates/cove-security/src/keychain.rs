@@ -1,9 +1,34 @@
+use crate::file_store::FileSecretStore;
 use crate::SecurityError;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Keychain entry read to check that the OS keychain works.
+const PROBE_USERNAME: &str = "keychain-probe";
+/// Keychain service the desktop apps keep secrets under, and the one
+/// earlier releases used.
+const DESKTOP_SERVICE_NAME: &str = "io.covemail.desktop";
+const LEGACY_DESKTOP_SERVICE_NAME: &str = "io.aether.desktop";
+/// Environment variable holding the passphrase for the encrypted secrets
+/// file, for unattended starts.
+pub const SECRETS_PASSPHRASE_ENV: &str = "COVE_MAIL_SECRETS_PASSPHRASE";
 
 #[derive(Debug, Clone)]
 pub struct SecretStore {
     service_name: String,
     legacy_service_name: Option<String>,
+    /// When set, secrets go to this encrypted file and never the keychain:
+    /// portable mode, or the fallback when no keychain is available.
+    file: Option<Arc<FileSecretStore>>,
+    /// Encrypted fallback file from a session without a keychain. Its
+    /// secrets move into the keychain when it is unlocked.
+    pending_migration: Option<Arc<FileSecretStore>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretBackend {
+    Keychain,
+    EncryptedFile,
 }
 
 #[derive(Debug, Clone)]
@@ -23,6 +48,8 @@ impl SecretStore {
         Self {
             service_name: service_name.into(),
             legacy_service_name: None,
+            file: None,
+            pending_migration: None,
         }
     }
 
@@ -33,16 +60,159 @@ impl SecretStore {
         Self {
             service_name: service_name.into(),
             legacy_service_name: Some(legacy_service_name.into()),
+            file: None,
+            pending_migration: None,
+        }
+    }
+
+    /// A store for portable installs: an age-encrypted file at `path` that
+    /// never touches the OS keychain and stays locked until [`Self::unlock`].
+    pub fn portable(service_name: impl Into<String>, path: impl AsRef<Path>) -> Self {
+        Self {
+            service_name: service_name.into(),
+            legacy_service_name: None,
+            file: Some(Arc::new(FileSecretStore::open_encrypted(path.as_ref()))),
+            pending_migration: None,
+        }
+    }
+
+    /// The OS keychain when one is reachable, otherwise an age-encrypted
+    /// file at `fallback_path` that stays locked until [`Self::unlock`].
+    /// If the keychain works again and the fallback file still exists, the
+    /// file's secrets are moved into the keychain on unlock.
+    pub fn keychain_or_encrypted_file(
+        service_name: impl Into<String>,
+        legacy_service_name: impl Into<String>,
+        fallback_path: impl AsRef<Path>,
+    ) -> Self {
+        let mut store = Self::new_with_legacy(service_name, legacy_service_name);
+        let fallback = Arc::new(FileSecretStore::open_encrypted(fallback_path.as_ref()));
+        if !store.keychain_available() {
+            tracing::warn!(
+                path = %fallback.path().display(),
+                "OS keychain unavailable; using encrypted secrets file"
+            );
+            store.file = Some(fallback);
+        } else if fallback.exists() {
+            store.pending_migration = Some(fallback);
+        }
+        store
+    }
+
+    /// The desktop apps' store: the encrypted file at `encrypted_file` in
+    /// portable mode (never the keychain), otherwise
+    /// [`Self::keychain_or_encrypted_file`]. Unlocked right away when
+    /// [`SECRETS_PASSPHRASE_ENV`] is set; otherwise an encrypted file stays
+    /// locked until the user enters the passphrase.
+    pub fn open_desktop(
+        portable: bool,
+        encrypted_file: impl AsRef<Path>,
+    ) -> Result<Self, SecurityError> {
+        let store = if portable {
+            Self::portable(DESKTOP_SERVICE_NAME, encrypted_file)
+        } else {
+            Self::keychain_or_encrypted_file(
+                DESKTOP_SERVICE_NAME,
+                LEGACY_DESKTOP_SERVICE_NAME,
+                encrypted_file,
+            )
+        };
+        if let Some(passphrase) = std::env::var(SECRETS_PASSPHRASE_ENV)
+            .ok()
+            .filter(|passphrase| !passphrase.is_empty())
+        {
+            store.unlock(&passphrase)?;
+        }
+        Ok(store)
+    }
+
+    /// The SQLCipher key for the database. A locked store has none to give,
+    /// which is only an error when the database is encrypted (`required`):
+    /// it has to open before any UI could ask for the passphrase.
+    pub fn database_key(&self, required: bool) -> Result<Option<String>, SecurityError> {
+        if self.is_locked() {
+            if required {
+                return Err(SecurityError::SecretFile(format!(
+                    "secrets file is locked; set {SECRETS_PASSPHRASE_ENV} to open the encrypted database"
+                )));
+            }
+            return Ok(None);
+        }
+        self.get(&SecretKey {
+            namespace: "database".to_string(),
+            id: "sqlcipher_key".to_string(),
+        })
+    }
+
+    /// Whether the OS keychain answers at all; a missing entry still counts.
+    pub fn keychain_available(&self) -> bool {
+        let probe = keyring::Entry::new(&self.service_name, PROBE_USERNAME)
+            .and_then(|entry| entry.get_password());
+        matches!(probe, Ok(_) | Err(keyring::Error::NoEntry))
+    }
+
+    pub fn backend(&self) -> SecretBackend {
+        match &self.file {
+            None => SecretBackend::Keychain,
+            Some(_) => SecretBackend::EncryptedFile,
+        }
+    }
+
+    /// True until the encrypted file is unlocked.
+    pub fn is_locked(&self) -> bool {
+        self.file.as_ref().is_some_and(|file| file.is_locked())
+    }
+
+    /// True while locked with no encrypted file yet, so [`Self::unlock`]
+    /// will set a new passphrase rather than check one.
+    pub fn is_first_unlock(&self) -> bool {
+        self.file
+            .as_ref()
+            .is_some_and(|file| file.is_locked() && !file.exists())
+    }
+
+    /// Whether an old encrypted file is waiting to be moved into the
+    /// keychain.
+    pub fn has_pending_migration(&self) -> bool {
+        self.pending_migration
+            .as_ref()
+            .is_some_and(|file| file.is_locked())
+    }
+
+    /// Unlock the encrypted file (creating it with `passphrase` on first
+    /// use), or decrypt a pending fallback file and move its secrets into
+    /// the keychain. Returns the number of secrets migrated.
+    pub fn unlock(&self, passphrase: &str) -> Result<usize, SecurityError> {
+        if let Some(file) = &self.file {
+            file.unlock(passphrase)?;
+            return Ok(0);
+        }
+        let Some(pending) = &self.pending_migration else {
+            return Ok(0);
+        };
+        pending.unlock(passphrase)?;
+        let entries = pending.entries()?;
+        for (username, value) in &entries {
+            keyring::Entry::new(&self.service_name, username)?.set_password(value)?;
         }
+        pending.retire()?;
+        tracing::info!(count = entries.len(), "moved secrets from encrypted file into the OS keychain");
+        Ok(entries.len())
     }
 
     pub fn set(&self, key: &SecretKey, value: &str) -> Result<(), SecurityError> {
+        if let Some(file) = &self.file {
+            return file.set(&key.as_username(), value);
+        }
         let entry = keyring::Entry::new(&self.service_name, &key.as_username())?;
         entry.set_password(value)?;
         Ok(())
     }
 
     pub fn get(&self, key: &SecretKey) -> Result<Option<String>, SecurityError> {
+        if let Some(file) = &self.file {
+            return file.get(&key.as_username());
+        }
         let entry = keyring::Entry::new(&self.service_name, &key.as_username())?;
         match entry.get_password() {
             Ok(secret) => Ok(Some(secret)),
@@ -65,6 +235,9 @@ impl SecretStore {
     }
 
     pub fn delete(&self, key: &SecretKey) -> Result<(), SecurityError> {
+        if let Some(file) = &self.file {
+            return file.delete(&key.as_username());
+        }
         let entry = keyring::Entry::new(&self.service_name, &key.as_username())?;
         match entry.delete_credential() {
             Ok(()) | Err(keyring::Error::NoEntry) => {}
@@ -89,4 +262,75 @@ impl SecretStore {
     pub fn legacy_service_name(&self) -> Option<&str> {
         self.legacy_service_name.as_deref()
     }
+
+    /// The secrets file, when not using the OS keychain.
+    pub fn file_path(&self) -> Option<&Path> {
+        self.file.as_deref().map(FileSecretStore::path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_dir() -> PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("cove-keychain-test-{nanos}"));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn portable_store_is_encrypted_and_starts_locked() {
+        let dir = temp_dir();
+        let encrypted = dir.join("secrets.age");
+        let key = SecretKey {
+            namespace: "mail".to_string(),
+            id: "work".to_string(),
+        };
+
+        let store = SecretStore::portable("cove-test", &encrypted);
+        assert_eq!(store.backend(), SecretBackend::EncryptedFile);
+        assert!(store.is_locked());
+        assert!(store.is_first_unlock());
+        assert!(matches!(store.get(&key), Err(SecurityError::Locked)));
+
+        assert_eq!(store.unlock("correct horse").unwrap(), 0);
+        store.set(&key, "hunter2").unwrap();
+        assert_eq!(store.get(&key).unwrap().as_deref(), Some("hunter2"));
+        let raw = std::fs::read(&encrypted).unwrap();
+        assert!(!String::from_utf8_lossy(&raw).contains("hunter2"));
+
+        let reopened = SecretStore::portable("cove-test", &encrypted);
+        assert!(!reopened.is_first_unlock());
+        assert_eq!(reopened.unlock("correct horse").unwrap(), 0);
+        assert_eq!(reopened.get(&key).unwrap().as_deref(), Some("hunter2"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn locked_store_only_fails_database_key_when_required() {
+        let dir = temp_dir();
+        let store = SecretStore::portable("cove-test", dir.join("secrets.age"));
+        assert_eq!(store.database_key(false).unwrap(), None);
+        assert!(store.database_key(true).is_err());
+
+        store.unlock("correct horse").unwrap();
+        let key = SecretKey {
+            namespace: "database".to_string(),
+            id: "sqlcipher_key".to_string(),
+        };
+        store.set(&key, "0123456789abcdef").unwrap();
+        assert_eq!(
+            store.database_key(true).unwrap().as_deref(),
+            Some("0123456789abcdef")
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
 }
@@ -1,7 +1,8 @@
 mod error;
+mod file_store;
 mod keychain;
 mod oauth;
 
 pub use error::SecurityError;
-pub use keychain::{SecretKey, SecretStore};
-pub use oauth::{OAuthPkceSession, OAuthTokenResult, OAuthWorkflow};
+pub use keychain::{SecretBackend, SecretKey, SecretStore, SECRETS_PASSPHRASE_ENV};
+pub use oauth::{refresh_stored_access_token, OAuthPkceSession, OAuthTokenResult, OAuthWorkflow};
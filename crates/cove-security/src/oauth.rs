@@ -1,8 +1,9 @@
-use crate::SecurityError;
+use crate::{SecretKey, SecretStore, SecurityError};
 use cove_core::OAuthProfile;
 use oauth2::{
-    basic::BasicClient, AuthUrl, AuthorizationCode, ClientId, CsrfToken, PkceCodeChallenge,
-    PkceCodeVerifier, RedirectUrl, Scope, TokenResponse, TokenUrl,
+    basic::{BasicClient, BasicErrorResponseType},
+    AuthUrl, AuthorizationCode, ClientId, CsrfToken, PkceCodeChallenge, PkceCodeVerifier,
+    RedirectUrl, RefreshToken, RequestTokenError, Scope, TokenResponse, TokenUrl,
 };
 use serde::{Deserialize, Serialize};
 
@@ -174,4 +175,70 @@ impl OAuthWorkflow {
             expires_in_secs: token.expires_in().map(|duration| duration.as_secs()),
         })
     }
+
+    /// Exchange a refresh token for a fresh access token. Providers may rotate
+    /// the refresh token; `refresh_token` is `None` when they did not.
+    pub async fn refresh(&self, refresh_token: &str) -> Result<OAuthTokenResult, SecurityError> {
+        let client = BasicClient::new(ClientId::new(self.profile.client_id.clone()))
+            .set_auth_uri(AuthUrl::new(self.profile.auth_url.as_str().to_string())?)
+            .set_token_uri(TokenUrl::new(self.profile.token_url.as_str().to_string())?);
+
+        let http_client = reqwest::ClientBuilder::new()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()?;
+
+        let token = client
+            .exchange_refresh_token(&RefreshToken::new(refresh_token.to_string()))
+            .request_async(&http_client)
+            .await
+            .map_err(|err| match err {
+                // Revoked consent, a changed password or an expired refresh
+                // token all come back as invalid_grant.
+                RequestTokenError::ServerResponse(response)
+                    if matches!(response.error(), BasicErrorResponseType::InvalidGrant) =>
+                {
+                    SecurityError::ReauthRequired(response.to_string())
+                }
+                other => SecurityError::OAuth(other.to_string()),
+            })?;
+
+        Ok(OAuthTokenResult {
+            access_token: token.access_token().secret().to_string(),
+            refresh_token: token
+                .refresh_token()
+                .map(|token| token.secret().to_string()),
+            expires_in_secs: token.expires_in().map(|duration| duration.as_secs()),
+        })
+    }
+}
+
+/// Refresh the access token stored for `account_id` using its stored refresh
+/// token, persist the new token(s) and return the new access token.
+pub async fn refresh_stored_access_token(
+    secrets: &SecretStore,
+    account_id: &str,
+    profile: OAuthProfile,
+) -> Result<String, SecurityError> {
+    let refresh_key = SecretKey {
+        namespace: "oauth_refresh_token".to_string(),
+        id: account_id.to_string(),
+    };
+    let refresh_token = secrets
+        .get(&refresh_key)?
+        .ok_or_else(|| SecurityError::ReauthRequired("no refresh token stored".to_string()))?;
+
+    let token = OAuthWorkflow::new(profile)?.refresh(&refresh_token).await?;
+
+    secrets.set(
+        &SecretKey {
+            namespace: "oauth_access_token".to_string(),
+            id: account_id.to_string(),
+        },
+        &token.access_token,
+    )?;
+    if let Some(rotated) = token.refresh_token.as_deref() {
+        secrets.set(&refresh_key, rotated)?;
+    }
+
+    Ok(token.access_token)
 }
@@ -0,0 +1,16 @@
+-- Tasks: effort estimate used as the default time-block length
+ALTER TABLE reminder_tasks ADD COLUMN estimate_minutes INTEGER;
+
+-- Task <-> calendar time blocks (one block per task)
+CREATE TABLE IF NOT EXISTS task_time_blocks (
+  task_id TEXT PRIMARY KEY,
+  account_id TEXT NOT NULL,
+  calendar_id TEXT NOT NULL,
+  event_remote_id TEXT NOT NULL,
+  completed INTEGER NOT NULL DEFAULT 0,
+  created_at TEXT NOT NULL,
+  FOREIGN KEY(task_id) REFERENCES reminder_tasks(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_task_time_blocks_event
+  ON task_time_blocks(account_id, calendar_id, event_remote_id);
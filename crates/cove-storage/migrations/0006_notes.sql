@@ -0,0 +1,26 @@
+-- Notes workspace (including notes clipped from email)
+CREATE TABLE IF NOT EXISTS notes (
+  id TEXT PRIMARY KEY,
+  account_id TEXT,
+  title TEXT NOT NULL,
+  body_markdown TEXT NOT NULL,
+  source_message_id TEXT,
+  source_thread_id TEXT,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  FOREIGN KEY(account_id) REFERENCES accounts(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_notes_updated ON notes(updated_at DESC);
+
+CREATE TABLE IF NOT EXISTS note_attachments (
+  id TEXT PRIMARY KEY,
+  note_id TEXT NOT NULL,
+  file_name TEXT NOT NULL,
+  mime_type TEXT NOT NULL,
+  size INTEGER NOT NULL,
+  content BLOB NOT NULL,
+  FOREIGN KEY(note_id) REFERENCES notes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_note_attachments_note ON note_attachments(note_id);
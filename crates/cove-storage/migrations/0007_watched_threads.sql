@@ -0,0 +1,8 @@
+-- Watched threads: replies always raise a high-priority notification
+CREATE TABLE IF NOT EXISTS watched_threads (
+  account_id TEXT NOT NULL,
+  thread_id TEXT NOT NULL,
+  created_at TEXT NOT NULL,
+  PRIMARY KEY(account_id, thread_id),
+  FOREIGN KEY(account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
@@ -0,0 +1,17 @@
+-- Needs-reply triage queue (one row per inbound message flagged by the heuristic)
+CREATE TABLE IF NOT EXISTS reply_queue (
+  message_id TEXT PRIMARY KEY,
+  account_id TEXT NOT NULL,
+  thread_id TEXT NOT NULL,
+  score INTEGER NOT NULL,
+  reasons_json TEXT NOT NULL,
+  ai_confirmed INTEGER,
+  status TEXT NOT NULL,
+  snoozed_until TEXT,
+  detected_at TEXT NOT NULL,
+  resolved_at TEXT,
+  FOREIGN KEY(message_id) REFERENCES mail_messages(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_reply_queue_thread ON reply_queue(account_id, thread_id);
+CREATE INDEX IF NOT EXISTS idx_reply_queue_status ON reply_queue(status);
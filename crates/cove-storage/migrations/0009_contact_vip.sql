@@ -0,0 +1,4 @@
+-- VIP contacts: notifications bypass muting, `is:vip` search, VIP smart folder
+ALTER TABLE contacts ADD COLUMN vip INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS idx_contacts_vip ON contacts(vip) WHERE vip = 1;
@@ -0,0 +1,12 @@
+-- Online meeting join links and per-calendar incremental sync tokens
+ALTER TABLE calendar_events ADD COLUMN online_meeting_url TEXT;
+
+CREATE TABLE IF NOT EXISTS calendar_sync_state (
+  account_id TEXT NOT NULL,
+  calendar_id TEXT NOT NULL,
+  delta_token TEXT NOT NULL,
+  window_start TEXT NOT NULL,
+  window_end TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  PRIMARY KEY(account_id, calendar_id)
+);
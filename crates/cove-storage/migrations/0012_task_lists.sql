@@ -0,0 +1,13 @@
+-- Multi-list task sync: discovered provider lists and list names on tasks
+ALTER TABLE reminder_tasks ADD COLUMN list_name TEXT;
+
+CREATE TABLE IF NOT EXISTS task_lists (
+  account_id TEXT NOT NULL,
+  list_id TEXT NOT NULL,
+  name TEXT NOT NULL,
+  is_default INTEGER NOT NULL DEFAULT 0,
+  updated_at TEXT NOT NULL,
+  PRIMARY KEY(account_id, list_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_reminder_tasks_list ON reminder_tasks(account_id, list_id);
@@ -0,0 +1,8 @@
+-- Per-attendee responses to invitations, from incoming iTIP REPLY messages
+CREATE TABLE IF NOT EXISTS calendar_attendee_status (
+  event_id TEXT NOT NULL,
+  email TEXT NOT NULL,
+  status TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  PRIMARY KEY(event_id, email)
+);
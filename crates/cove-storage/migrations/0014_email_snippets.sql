@@ -0,0 +1,7 @@
+-- Compose snippets: abbreviations expanded inline while typing
+CREATE TABLE IF NOT EXISTS email_snippets (
+  id TEXT PRIMARY KEY,
+  abbreviation TEXT NOT NULL UNIQUE,
+  body TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
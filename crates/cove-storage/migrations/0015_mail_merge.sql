@@ -0,0 +1,26 @@
+-- Mail merge campaigns and their per-recipient send status
+CREATE TABLE IF NOT EXISTS mail_merge_campaigns (
+  id TEXT PRIMARY KEY,
+  account_id TEXT NOT NULL,
+  name TEXT NOT NULL,
+  subject TEXT NOT NULL,
+  body_text TEXT NOT NULL,
+  per_minute INTEGER NOT NULL,
+  created_at TEXT NOT NULL,
+  FOREIGN KEY(account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS mail_merge_recipients (
+  id TEXT PRIMARY KEY,
+  campaign_id TEXT NOT NULL,
+  position INTEGER NOT NULL,
+  email TEXT NOT NULL,
+  fields_json TEXT NOT NULL,
+  status TEXT NOT NULL,
+  error TEXT,
+  sent_at TEXT,
+  FOREIGN KEY(campaign_id) REFERENCES mail_merge_campaigns(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_mail_merge_recipients_campaign
+  ON mail_merge_recipients(campaign_id, status, position);
@@ -0,0 +1,6 @@
+-- Crash recovery: the latest snapshot of unsaved UI state per client
+CREATE TABLE IF NOT EXISTS ui_recovery_journal (
+  client TEXT PRIMARY KEY,
+  payload_json TEXT NOT NULL,
+  saved_at TEXT NOT NULL
+);
@@ -0,0 +1,10 @@
+-- Plus-address aliases (user+tag@domain) handed out to one sender each
+CREATE TABLE IF NOT EXISTS plus_aliases (
+  id TEXT PRIMARY KEY,
+  account_id TEXT NOT NULL,
+  address TEXT NOT NULL,
+  tag TEXT NOT NULL,
+  issued_to TEXT NOT NULL,
+  created_at TEXT NOT NULL,
+  UNIQUE(account_id, address)
+);
@@ -0,0 +1,7 @@
+-- Messages waiting for the background search indexer
+CREATE TABLE IF NOT EXISTS search_index_queue (
+  message_id TEXT PRIMARY KEY,
+  enqueued_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_search_index_queue_enqueued ON search_index_queue(enqueued_at);
@@ -0,0 +1,7 @@
+-- Shared label taxonomy; messages reference labels by name
+CREATE TABLE IF NOT EXISTS mail_labels (
+  name TEXT PRIMARY KEY COLLATE NOCASE,
+  color TEXT,
+  description TEXT,
+  created_at TEXT NOT NULL
+);
@@ -0,0 +1,13 @@
+-- Per-thread AI summaries reused by the unread mail digest
+CREATE TABLE IF NOT EXISTS ai_thread_digests (
+  account_id TEXT NOT NULL,
+  thread_id TEXT NOT NULL,
+  subject TEXT NOT NULL,
+  senders_json TEXT NOT NULL,
+  unread_count INTEGER NOT NULL,
+  summary TEXT NOT NULL,
+  latest_received_at TEXT NOT NULL,
+  provenance_json TEXT NOT NULL,
+  created_at TEXT NOT NULL,
+  PRIMARY KEY (account_id, thread_id)
+);
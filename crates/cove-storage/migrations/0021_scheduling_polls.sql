@@ -0,0 +1,25 @@
+-- "Propose times" polls and the answers parsed from participants' replies
+CREATE TABLE IF NOT EXISTS scheduling_polls (
+  id TEXT PRIMARY KEY,
+  account_id TEXT NOT NULL,
+  title TEXT NOT NULL,
+  description TEXT,
+  options_json TEXT NOT NULL,
+  participants_json TEXT NOT NULL,
+  token TEXT NOT NULL UNIQUE,
+  status TEXT NOT NULL,
+  confirmed_option INTEGER,
+  event_id TEXT,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS scheduling_poll_votes (
+  poll_id TEXT NOT NULL REFERENCES scheduling_polls(id) ON DELETE CASCADE,
+  participant TEXT NOT NULL,
+  choices_json TEXT NOT NULL,
+  voted_at TEXT NOT NULL,
+  PRIMARY KEY (poll_id, participant)
+);
+
+CREATE INDEX IF NOT EXISTS idx_scheduling_polls_account ON scheduling_polls(account_id, status);
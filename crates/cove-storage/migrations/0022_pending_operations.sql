@@ -0,0 +1,16 @@
+-- Changes made while offline, replayed when the provider is reachable again
+CREATE TABLE IF NOT EXISTS pending_operations (
+  id TEXT PRIMARY KEY,
+  account_id TEXT NOT NULL,
+  kind TEXT NOT NULL,
+  status TEXT NOT NULL,
+  target_id TEXT,
+  summary TEXT NOT NULL,
+  payload_json TEXT NOT NULL,
+  attempt_count INTEGER NOT NULL DEFAULT 0,
+  last_error TEXT,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_pending_operations_status ON pending_operations(status, created_at);
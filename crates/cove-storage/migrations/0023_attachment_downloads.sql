@@ -0,0 +1,12 @@
+-- Attachments saved to disk, for the recent-downloads list
+CREATE TABLE IF NOT EXISTS attachment_downloads (
+  id TEXT PRIMARY KEY,
+  account_id TEXT NOT NULL,
+  message_id TEXT NOT NULL,
+  attachment_id TEXT NOT NULL,
+  file_name TEXT NOT NULL,
+  path TEXT NOT NULL,
+  saved_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_attachment_downloads_saved ON attachment_downloads(saved_at);
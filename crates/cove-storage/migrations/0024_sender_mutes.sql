@@ -0,0 +1,7 @@
+-- Senders muted by the user; muted_until NULL means forever
+CREATE TABLE IF NOT EXISTS sender_mutes (
+  email TEXT PRIMARY KEY,
+  muted_until TEXT,
+  auto_archive INTEGER NOT NULL DEFAULT 0,
+  created_at TEXT NOT NULL
+);
@@ -0,0 +1,10 @@
+-- Labels applied to a whole conversation rather than a single message
+CREATE TABLE IF NOT EXISTS thread_labels (
+  account_id TEXT NOT NULL,
+  thread_id TEXT NOT NULL,
+  label TEXT NOT NULL COLLATE NOCASE,
+  created_at TEXT NOT NULL,
+  PRIMARY KEY (account_id, thread_id, label)
+);
+
+CREATE INDEX IF NOT EXISTS idx_thread_labels_label ON thread_labels(label);
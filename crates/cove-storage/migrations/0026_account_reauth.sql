@@ -0,0 +1,6 @@
+-- Accounts whose OAuth grant was revoked and need the user to sign in again
+CREATE TABLE IF NOT EXISTS account_reauth (
+  account_id TEXT PRIMARY KEY,
+  reason TEXT NOT NULL,
+  detected_at TEXT NOT NULL
+);
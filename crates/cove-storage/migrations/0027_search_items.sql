@@ -0,0 +1,3 @@
+-- Calendar events searched by day from the search bar (`on:`)
+CREATE INDEX IF NOT EXISTS idx_calendar_events_starts ON calendar_events(starts_at);
+CREATE INDEX IF NOT EXISTS idx_calendar_events_ends ON calendar_events(ends_at);
@@ -0,0 +1,19 @@
+-- Per-folder cursors for backfilling older mail after the first sync, and
+-- accounts whose backfill the user paused
+CREATE TABLE IF NOT EXISTS mail_backfill (
+  account_id TEXT NOT NULL,
+  folder_path TEXT NOT NULL,
+  position INTEGER NOT NULL,
+  cursor TEXT,
+  fetched INTEGER NOT NULL DEFAULT 0,
+  total INTEGER,
+  completed INTEGER NOT NULL DEFAULT 0,
+  started_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL,
+  PRIMARY KEY(account_id, folder_path)
+);
+
+CREATE TABLE IF NOT EXISTS mail_backfill_paused (
+  account_id TEXT PRIMARY KEY,
+  paused_at TEXT NOT NULL
+);
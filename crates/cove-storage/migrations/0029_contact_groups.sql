@@ -0,0 +1,24 @@
+-- Contact groups used as send lists, and who each group send expanded to,
+-- keyed by the sent message's Message-ID
+CREATE TABLE IF NOT EXISTS contact_groups (
+  id TEXT PRIMARY KEY,
+  name TEXT NOT NULL,
+  members_json TEXT NOT NULL,
+  created_at TEXT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS sent_group_expansions (
+  id TEXT PRIMARY KEY,
+  account_id TEXT NOT NULL,
+  message_id TEXT NOT NULL,
+  group_id TEXT NOT NULL,
+  group_name TEXT NOT NULL,
+  field TEXT NOT NULL,
+  recipients_json TEXT NOT NULL,
+  excluded_json TEXT NOT NULL,
+  sent_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_sent_group_expansions_message
+  ON sent_group_expansions(message_id);
@@ -0,0 +1,18 @@
+-- Future triggers for mail snoozes, follow-ups, event alerts and task due
+-- times, polled in one place; fired_at is set once a shell handled one
+CREATE TABLE IF NOT EXISTS reminders (
+  id TEXT PRIMARY KEY,
+  kind TEXT NOT NULL,
+  target_id TEXT NOT NULL,
+  account_id TEXT NOT NULL,
+  trigger_at TEXT NOT NULL,
+  expires_at TEXT,
+  lead_minutes INTEGER NOT NULL DEFAULT 0,
+  title TEXT NOT NULL,
+  detail TEXT,
+  fired_at TEXT,
+  created_at TEXT NOT NULL,
+  UNIQUE(kind, target_id, trigger_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_reminders_due ON reminders(fired_at, trigger_at);
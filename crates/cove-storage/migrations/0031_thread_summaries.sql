@@ -0,0 +1,108 @@
+-- One row per thread and folder, kept current by triggers on mail_messages
+-- so the message list reads threads without grouping messages. Subject,
+-- snippet and latest_at come from the newest message (SQLite fills bare
+-- columns from the row that produced MAX(received_at), the only MAX).
+CREATE TABLE IF NOT EXISTS thread_summaries (
+  account_id TEXT NOT NULL,
+  folder_path TEXT NOT NULL,
+  thread_id TEXT NOT NULL,
+  subject TEXT NOT NULL,
+  snippet TEXT NOT NULL,
+  participants_json TEXT NOT NULL,
+  message_count INTEGER NOT NULL,
+  unread_count INTEGER NOT NULL,
+  has_attachments INTEGER NOT NULL,
+  latest_at TEXT NOT NULL,
+  PRIMARY KEY (account_id, folder_path, thread_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_thread_summaries_folder
+  ON thread_summaries(account_id, folder_path, latest_at DESC);
+CREATE INDEX IF NOT EXISTS idx_thread_summaries_latest
+  ON thread_summaries(folder_path, latest_at DESC);
+CREATE INDEX IF NOT EXISTS idx_mail_messages_account_folder_thread
+  ON mail_messages(account_id, folder_path, thread_id);
+
+INSERT OR REPLACE INTO thread_summaries
+SELECT m.account_id, m.folder_path, m.thread_id, m.subject, m.preview,
+  (SELECT json_group_array(DISTINCT json_extract(f.value, '$.address'))
+     FROM mail_messages p, json_each(p.from_json) f
+    WHERE p.account_id = m.account_id AND p.folder_path = m.folder_path
+      AND p.thread_id = m.thread_id),
+  COUNT(*),
+  SUM(NOT COALESCE(json_extract(m.flags_json, '$.seen'), 0)),
+  SUM(m.attachments_json <> '[]') > 0,
+  MAX(m.received_at)
+FROM mail_messages m
+GROUP BY m.account_id, m.folder_path, m.thread_id;
+
+CREATE TRIGGER IF NOT EXISTS thread_summaries_insert
+AFTER INSERT ON mail_messages
+BEGIN
+  DELETE FROM thread_summaries
+   WHERE account_id = NEW.account_id AND folder_path = NEW.folder_path
+     AND thread_id = NEW.thread_id;
+  INSERT INTO thread_summaries
+  SELECT m.account_id, m.folder_path, m.thread_id, m.subject, m.preview,
+    (SELECT json_group_array(DISTINCT json_extract(f.value, '$.address'))
+       FROM mail_messages p, json_each(p.from_json) f
+      WHERE p.account_id = m.account_id AND p.folder_path = m.folder_path
+        AND p.thread_id = m.thread_id),
+    COUNT(*),
+    SUM(NOT COALESCE(json_extract(m.flags_json, '$.seen'), 0)),
+    SUM(m.attachments_json <> '[]') > 0,
+    MAX(m.received_at)
+  FROM mail_messages m
+  WHERE m.account_id = NEW.account_id AND m.folder_path = NEW.folder_path
+    AND m.thread_id = NEW.thread_id
+  GROUP BY m.account_id, m.folder_path, m.thread_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS thread_summaries_update
+AFTER UPDATE OF account_id, folder_path, thread_id, from_json, subject, preview,
+  flags_json, attachments_json, received_at ON mail_messages
+BEGIN
+  DELETE FROM thread_summaries
+   WHERE (account_id = OLD.account_id AND folder_path = OLD.folder_path
+          AND thread_id = OLD.thread_id)
+      OR (account_id = NEW.account_id AND folder_path = NEW.folder_path
+          AND thread_id = NEW.thread_id);
+  INSERT INTO thread_summaries
+  SELECT m.account_id, m.folder_path, m.thread_id, m.subject, m.preview,
+    (SELECT json_group_array(DISTINCT json_extract(f.value, '$.address'))
+       FROM mail_messages p, json_each(p.from_json) f
+      WHERE p.account_id = m.account_id AND p.folder_path = m.folder_path
+        AND p.thread_id = m.thread_id),
+    COUNT(*),
+    SUM(NOT COALESCE(json_extract(m.flags_json, '$.seen'), 0)),
+    SUM(m.attachments_json <> '[]') > 0,
+    MAX(m.received_at)
+  FROM mail_messages m
+  WHERE (m.account_id = OLD.account_id AND m.folder_path = OLD.folder_path
+         AND m.thread_id = OLD.thread_id)
+     OR (m.account_id = NEW.account_id AND m.folder_path = NEW.folder_path
+         AND m.thread_id = NEW.thread_id)
+  GROUP BY m.account_id, m.folder_path, m.thread_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS thread_summaries_delete
+AFTER DELETE ON mail_messages
+BEGIN
+  DELETE FROM thread_summaries
+   WHERE account_id = OLD.account_id AND folder_path = OLD.folder_path
+     AND thread_id = OLD.thread_id;
+  INSERT INTO thread_summaries
+  SELECT m.account_id, m.folder_path, m.thread_id, m.subject, m.preview,
+    (SELECT json_group_array(DISTINCT json_extract(f.value, '$.address'))
+       FROM mail_messages p, json_each(p.from_json) f
+      WHERE p.account_id = m.account_id AND p.folder_path = m.folder_path
+        AND p.thread_id = m.thread_id),
+    COUNT(*),
+    SUM(NOT COALESCE(json_extract(m.flags_json, '$.seen'), 0)),
+    SUM(m.attachments_json <> '[]') > 0,
+    MAX(m.received_at)
+  FROM mail_messages m
+  WHERE m.account_id = OLD.account_id AND m.folder_path = OLD.folder_path
+    AND m.thread_id = OLD.thread_id
+  GROUP BY m.account_id, m.folder_path, m.thread_id;
+END;
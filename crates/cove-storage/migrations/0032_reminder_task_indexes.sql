@@ -0,0 +1,8 @@
+-- Migration 0004 shipped indexing a `tasks` table that no release created.
+-- Storage::connect gives it an empty placeholder so it can apply; drop that
+-- placeholder here and put the subtask and priority indexes on the real
+-- table (reminder_tasks(due_at) is already indexed by 0001).
+DROP TABLE IF EXISTS tasks;
+
+CREATE INDEX IF NOT EXISTS idx_reminder_tasks_parent ON reminder_tasks(parent_id);
+CREATE INDEX IF NOT EXISTS idx_reminder_tasks_priority ON reminder_tasks(priority);
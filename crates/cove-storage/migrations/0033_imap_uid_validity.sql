@@ -0,0 +1,9 @@
+-- UIDVALIDITY last seen per IMAP folder. Stored UIDs name the same messages
+-- only while it is unchanged, so purges check it before expunging.
+CREATE TABLE IF NOT EXISTS imap_folder_state (
+  account_id TEXT NOT NULL,
+  folder_path TEXT NOT NULL,
+  uid_validity INTEGER NOT NULL,
+  updated_at TEXT NOT NULL,
+  PRIMARY KEY(account_id, folder_path)
+);
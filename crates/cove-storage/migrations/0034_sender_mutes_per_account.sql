@@ -0,0 +1,17 @@
+-- Mutes belong to one account. Mutes made before this applied to every
+-- account, so each is copied to all of them.
+CREATE TABLE IF NOT EXISTS sender_mutes_scoped (
+  account_id TEXT NOT NULL,
+  email TEXT NOT NULL,
+  muted_until TEXT,
+  auto_archive INTEGER NOT NULL DEFAULT 0,
+  created_at TEXT NOT NULL,
+  PRIMARY KEY(account_id, email)
+);
+
+INSERT OR IGNORE INTO sender_mutes_scoped (account_id, email, muted_until, auto_archive, created_at)
+SELECT a.id, s.email, s.muted_until, s.auto_archive, s.created_at
+FROM sender_mutes s CROSS JOIN accounts a;
+
+DROP TABLE sender_mutes;
+ALTER TABLE sender_mutes_scoped RENAME TO sender_mutes;
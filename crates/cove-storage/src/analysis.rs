@@ -0,0 +1,183 @@
+//! Text analysis for the search index.
+//!
+//! Word fields are lowercased and ASCII-folded, so `resume` finds `résumé`
+//! and `Strasse` finds `STRASSE`. Scripts written without spaces (Chinese,
+//! Japanese, Thai, ...) cannot be split into words that way; their runs are
+//! copied into a separate field indexed as character uni- and bigrams.
+
+use crate::StorageError;
+use tantivy::tokenizer::{
+    AsciiFoldingFilter, LowerCaser, NgramTokenizer, RemoveLongFilter, SimpleTokenizer,
+    TextAnalyzer,
+};
+use tantivy::Index;
+
+pub(crate) const FOLDED_TOKENIZER: &str = "cove_folded";
+pub(crate) const NGRAM_TOKENIZER: &str = "cove_ngram";
+/// Stored for documents whose language could not be detected reliably.
+pub(crate) const UNDETERMINED_LANGUAGE: &str = "und";
+
+/// Characters sampled for language detection; plenty for a reliable guess.
+const DETECTION_SAMPLE_CHARS: usize = 2_000;
+
+/// Register the analyzers named in the schema. Tokenizers are not persisted
+/// with the index, so this runs on every open.
+pub(crate) fn register_analyzers(index: &Index) -> Result<(), StorageError> {
+    let folded = TextAnalyzer::builder(SimpleTokenizer::default())
+        .filter(RemoveLongFilter::limit(40))
+        .filter(LowerCaser)
+        .filter(AsciiFoldingFilter)
+        .build();
+    let ngram = TextAnalyzer::builder(NgramTokenizer::new(1, 2, false)?)
+        .filter(LowerCaser)
+        .build();
+
+    index.tokenizers().register(FOLDED_TOKENIZER, folded);
+    index.tokenizers().register(NGRAM_TOKENIZER, ngram);
+    Ok(())
+}
+
+/// ISO 639-3 code of the dominant language, or [`UNDETERMINED_LANGUAGE`].
+pub(crate) fn detect_language(text: &str) -> String {
+    let sample: String = text.chars().take(DETECTION_SAMPLE_CHARS).collect();
+    whatlang::detect(&sample)
+        .filter(|info| info.is_reliable())
+        .map(|info| info.lang().code().to_string())
+        .unwrap_or_else(|| UNDETERMINED_LANGUAGE.to_string())
+}
+
+/// The runs of `text` in scripts written without word separators.
+pub(crate) fn unsegmented_runs(text: &str) -> Vec<String> {
+    let mut runs = Vec::new();
+    let mut current = String::new();
+    for ch in text.chars() {
+        if is_unsegmented(ch) {
+            current.push(ch);
+        } else if !current.is_empty() {
+            runs.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        runs.push(current);
+    }
+    runs
+}
+
+/// Reply/forward prefixes stripped from subjects, in several languages.
+const SUBJECT_PREFIXES: &[&str] = &["re", "fw", "fwd", "aw", "wg", "sv", "vs", "tr", "rif"];
+/// Words too common to say anything about a topic.
+const STOPWORDS: &[&str] = &[
+    "about", "after", "again", "also", "from", "have", "hello", "here", "just", "meeting",
+    "more", "please", "regarding", "that", "thanks", "there", "this", "update", "what", "when",
+    "with", "your",
+];
+const MAX_TOPIC_WORDS: usize = 8;
+const MAX_ENTITIES: usize = 6;
+
+/// A search query for mail on the same topic: significant subject words
+/// plus identifier-like entities (`INV-20931`, `#4521`) from subject and
+/// body, each quoted so query syntax in the text cannot break parsing.
+/// `None` when nothing useful is left.
+pub(crate) fn related_query(subject: &str, body: &str) -> Option<String> {
+    let mut subject = subject.trim();
+    while let Some((prefix, rest)) = subject.split_once(':') {
+        if !SUBJECT_PREFIXES.contains(&prefix.trim().to_lowercase().as_str()) {
+            break;
+        }
+        subject = rest.trim_start();
+    }
+
+    fn push(term: String, terms: &mut Vec<String>) {
+        if !terms.iter().any(|existing| existing.eq_ignore_ascii_case(&term)) {
+            terms.push(term);
+        }
+    }
+    let mut terms: Vec<String> = Vec::new();
+
+    let topic_words = subject
+        .split(|ch: char| !ch.is_alphanumeric())
+        .filter(|word| word.chars().count() >= 4 && !word.chars().all(|ch| ch.is_ascii_digit()))
+        .filter(|word| !STOPWORDS.contains(&word.to_lowercase().as_str()))
+        .take(MAX_TOPIC_WORDS);
+    for word in topic_words {
+        push(word.to_string(), &mut terms);
+    }
+    for run in unsegmented_runs(subject).into_iter().filter(|run| run.chars().count() >= 2) {
+        push(run, &mut terms);
+    }
+
+    let entities = subject
+        .split_whitespace()
+        .chain(body.split_whitespace())
+        .map(|token| token.trim_matches(|ch: char| !ch.is_alphanumeric() && ch != '#'))
+        .filter(|token| is_entity(token))
+        .take(MAX_ENTITIES)
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    for entity in entities {
+        push(entity, &mut terms);
+    }
+
+    if terms.is_empty() {
+        return None;
+    }
+    Some(
+        terms
+            .iter()
+            .map(|term| format!("\"{}\"", term.replace('"', "")))
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+/// Ticket, order and invoice references: `#` plus 3+ digits, or a token
+/// of 5+ characters that mixes letters and digits (`INV-20931`, `AB12CD`).
+fn is_entity(token: &str) -> bool {
+    if let Some(number) = token.strip_prefix('#') {
+        return number.len() >= 3 && number.chars().all(|ch| ch.is_ascii_digit());
+    }
+    token.len() >= 5
+        && token.len() <= 32
+        && token.chars().any(|ch| ch.is_ascii_digit())
+        && token.chars().any(|ch| ch.is_alphabetic())
+        && token.chars().all(|ch| ch.is_alphanumeric() || ch == '-' || ch == '_')
+}
+
+fn is_unsegmented(ch: char) -> bool {
+    matches!(ch,
+        '\u{0E00}'..='\u{0EFF}'      // Thai, Lao
+        | '\u{1000}'..='\u{109F}'    // Myanmar
+        | '\u{1780}'..='\u{17FF}'    // Khmer
+        | '\u{3040}'..='\u{30FF}'    // Hiragana, Katakana
+        | '\u{3400}'..='\u{4DBF}'    // CJK extension A
+        | '\u{4E00}'..='\u{9FFF}'    // CJK unified ideographs
+        | '\u{AC00}'..='\u{D7AF}'    // Hangul syllables
+        | '\u{F900}'..='\u{FAFF}'    // CJK compatibility ideographs
+        | '\u{FF66}'..='\u{FF9F}'    // Half-width Katakana
+        | '\u{20000}'..='\u{2FA1F}'  // CJK extensions B-F
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_out_unsegmented_runs() {
+        assert_eq!(
+            unsegmented_runs("Re: 東京タワー meeting 明日 ok"),
+            vec!["東京タワー".to_string(), "明日".to_string()]
+        );
+        assert!(unsegmented_runs("Café résumé").is_empty());
+    }
+
+    #[test]
+    fn builds_related_query_from_subject_and_entities() {
+        assert_eq!(
+            related_query("Re: Fwd: Quarterly budget review", "See invoice INV-20931 and #4521.")
+                .as_deref(),
+            Some("\"Quarterly\" \"budget\" \"review\" \"INV-20931\" \"#4521\"")
+        );
+        assert_eq!(related_query("Re: hi", "ok"), None);
+    }
+}
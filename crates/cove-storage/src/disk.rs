@@ -0,0 +1,66 @@
+//! Free-space monitoring for the data directory. When the disk runs low,
+//! sync falls back to headers only and attachments are no longer cached,
+//! so a full disk cannot corrupt the database mid-write.
+
+use cove_core::{DiskSpacePolicy, DiskSpaceReport, SyncFidelity};
+use std::path::Path;
+
+/// Days of growth covered by `DiskSpaceReport::projected_need_bytes`.
+pub const PROJECTION_DAYS: u64 = 30;
+
+/// Available and total bytes on the disk holding `dir`.
+pub fn disk_capacity(dir: &Path) -> std::io::Result<(u64, u64)> {
+    Ok((fs4::available_space(dir)?, fs4::total_space(dir)?))
+}
+
+/// Decide the sync fidelity for the measured space.
+pub(crate) fn assess(
+    policy: &DiskSpacePolicy,
+    available_bytes: u64,
+    total_bytes: u64,
+    mail_bytes: u64,
+    daily_growth_bytes: u64,
+) -> DiskSpaceReport {
+    let min_free_bytes = policy.min_free_mb.saturating_mul(1024 * 1024);
+    let fidelity = if policy.degrade_when_low && available_bytes < min_free_bytes {
+        SyncFidelity::HeadersOnly
+    } else {
+        SyncFidelity::Full
+    };
+    DiskSpaceReport {
+        available_bytes,
+        total_bytes,
+        mail_bytes,
+        daily_growth_bytes,
+        projected_need_bytes: daily_growth_bytes.saturating_mul(PROJECTION_DAYS),
+        fidelity,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MB: u64 = 1024 * 1024;
+
+    #[test]
+    fn degrades_below_the_free_space_floor() {
+        let policy = DiskSpacePolicy {
+            degrade_when_low: true,
+            min_free_mb: 500,
+        };
+        let report = assess(&policy, 400 * MB, 100_000 * MB, 900 * MB, 2 * MB);
+        assert_eq!(report.fidelity, SyncFidelity::HeadersOnly);
+        assert_eq!(report.projected_need_bytes, 60 * MB);
+
+        let report = assess(&policy, 600 * MB, 100_000 * MB, 900 * MB, 2 * MB);
+        assert_eq!(report.fidelity, SyncFidelity::Full);
+
+        let policy = DiskSpacePolicy {
+            degrade_when_low: false,
+            ..policy
+        };
+        let report = assess(&policy, 10 * MB, 100_000 * MB, 900 * MB, 2 * MB);
+        assert_eq!(report.fidelity, SyncFidelity::Full);
+    }
+}
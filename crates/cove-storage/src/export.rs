@@ -0,0 +1,139 @@
+//! Newline-delimited JSON records for exporting messages to outside tools.
+//!
+//! Each line is one message with its annotations (flags, labels, pin and
+//! snooze state) but without raw headers or HTML, which are bulky and the
+//! most likely place for tracking data to hide.
+
+use cove_core::{ExportRedaction, MailAddress, MailFlags, MailMessage};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize)]
+struct ExportedMessage<'a> {
+    id: Uuid,
+    account_id: Uuid,
+    thread_id: &'a str,
+    folder: &'a str,
+    from: Vec<ExportedAddress>,
+    to: Vec<ExportedAddress>,
+    cc: Vec<ExportedAddress>,
+    subject: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    preview: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body_text: Option<&'a str>,
+    flags: &'a MailFlags,
+    labels: &'a [String],
+    pinned: bool,
+    snoozed_until: Option<DateTime<Utc>>,
+    attachments: Vec<&'a str>,
+    sent_at: Option<DateTime<Utc>>,
+    received_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExportedAddress {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    address: String,
+}
+
+/// Hands out `contact-N` in order of first appearance, so the same address
+/// always gets the same pseudonym within one export.
+#[derive(Debug, Default)]
+pub(crate) struct Pseudonyms {
+    assigned: HashMap<String, String>,
+}
+
+impl Pseudonyms {
+    fn get(&mut self, address: &str) -> String {
+        let next = self.assigned.len() + 1;
+        self.assigned
+            .entry(address.trim().to_ascii_lowercase())
+            .or_insert_with(|| format!("contact-{next}"))
+            .clone()
+    }
+}
+
+/// One NDJSON line for `message`, newline included.
+pub(crate) fn export_line(
+    message: &MailMessage,
+    redaction: ExportRedaction,
+    pseudonyms: &mut Pseudonyms,
+) -> Result<String, serde_json::Error> {
+    let mut addresses = |list: &[MailAddress]| -> Vec<ExportedAddress> {
+        list.iter()
+            .map(|entry| {
+                if redaction.addresses {
+                    ExportedAddress {
+                        name: None,
+                        address: pseudonyms.get(&entry.address),
+                    }
+                } else {
+                    ExportedAddress {
+                        name: entry.name.clone(),
+                        address: entry.address.clone(),
+                    }
+                }
+            })
+            .collect()
+    };
+    let record = ExportedMessage {
+        id: message.id,
+        account_id: message.account_id,
+        thread_id: &message.thread_id,
+        folder: &message.folder_path,
+        from: addresses(&message.from),
+        to: addresses(&message.to),
+        cc: addresses(&message.cc),
+        subject: &message.subject,
+        preview: (!redaction.bodies).then_some(message.preview.as_str()),
+        body_text: message.body_text.as_deref().filter(|_| !redaction.bodies),
+        flags: &message.flags,
+        labels: &message.labels,
+        pinned: message.pinned,
+        snoozed_until: message.snoozed_until,
+        attachments: message
+            .attachments
+            .iter()
+            .map(|attachment| attachment.file_name.as_str())
+            .collect(),
+        sent_at: message.sent_at,
+        received_at: message.received_at,
+    };
+    let mut line = serde_json::to_string(&record)?;
+    line.push('\n');
+    Ok(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sample::sample_mailbox;
+
+    #[test]
+    fn redaction_pseudonymizes_addresses_and_drops_bodies() {
+        let messages = sample_mailbox(Utc::now()).messages;
+        let redaction = ExportRedaction {
+            addresses: true,
+            bodies: true,
+        };
+        let mut pseudonyms = Pseudonyms::default();
+        let lines: Vec<serde_json::Value> = messages
+            .iter()
+            .map(|message| {
+                let line = export_line(message, redaction, &mut pseudonyms).unwrap();
+                serde_json::from_str(&line).unwrap()
+            })
+            .collect();
+
+        let first = &messages[0].from[0].address;
+        let text = serde_json::to_string(&lines).unwrap();
+        assert!(!text.contains(first.as_str()));
+        assert!(lines.iter().all(|line| line.get("body_text").is_none()));
+        assert_eq!(lines[0]["from"][0]["address"], "contact-1");
+        assert_eq!(pseudonyms.get(first), "contact-1");
+    }
+}
@@ -0,0 +1,79 @@
+//! Shared state for the background search indexer.
+//!
+//! Message writes only enqueue ids in `search_index_queue`; a worker task
+//! spawned by [`crate::Storage::connect`] drains the queue in batches with
+//! one Tantivy commit per batch.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use tokio::sync::Notify;
+
+/// Messages indexed per Tantivy commit.
+pub(crate) const INDEX_BATCH_SIZE: i64 = 500;
+
+/// Snapshot for progress displays such as "Indexing 12,400/80,000".
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexProgress {
+    /// Messages indexed since the queue was last empty.
+    pub indexed: u64,
+    /// Messages still waiting in the queue.
+    pub pending: u64,
+    pub paused: bool,
+}
+
+impl IndexProgress {
+    pub fn total(&self) -> u64 {
+        self.indexed + self.pending
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.pending == 0
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct IndexerState {
+    paused: AtomicBool,
+    indexed: AtomicU64,
+    pending: AtomicU64,
+    wake: Notify,
+}
+
+impl IndexerState {
+    pub(crate) fn progress(&self) -> IndexProgress {
+        IndexProgress {
+            indexed: self.indexed.load(Ordering::Relaxed),
+            pending: self.pending.load(Ordering::Relaxed),
+            paused: self.paused.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(crate) fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+        self.wake.notify_one();
+    }
+
+    pub(crate) fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// New work was queued; wake the worker.
+    pub(crate) fn notify(&self) {
+        self.wake.notify_one();
+    }
+
+    pub(crate) async fn wait(&self, timeout: std::time::Duration) {
+        let _ = tokio::time::timeout(timeout, self.wake.notified()).await;
+    }
+
+    pub(crate) fn record_batch(&self, indexed: u64, pending: u64) {
+        self.indexed.fetch_add(indexed, Ordering::Relaxed);
+        self.pending.store(pending, Ordering::Relaxed);
+    }
+
+    /// The queue drained; the next burst of work starts counting from zero.
+    pub(crate) fn reset(&self) {
+        self.indexed.store(0, Ordering::Relaxed);
+        self.pending.store(0, Ordering::Relaxed);
+    }
+}
@@ -1,7 +1,24 @@
+mod analysis;
+mod disk;
 mod error;
+mod export;
+mod indexer;
+mod metrics;
+mod ranking;
+mod reminders;
+mod sample;
 mod search;
 mod storage;
 
+pub use disk::{disk_capacity, PROJECTION_DAYS};
 pub use error::StorageError;
+pub use indexer::IndexProgress;
+pub use metrics::{
+    render_analytics, weekly_report_due, weekly_report_slot, weekly_report_subject,
+    weekly_report_text, WEEKLY_REPORT_AUDIT_KIND,
+};
+pub use ranking::{RankBreakdown, RankingWeights};
+pub use reminders::{plan_reminders, ReminderHandler, ReminderScheduler, ReminderSources};
+pub use sample::{is_sample_account, SAMPLE_ACCOUNT_ID};
 pub use search::MailSearchIndex;
 pub use storage::Storage;
@@ -0,0 +1,378 @@
+//! Mail analytics: tallying volume, response times and correspondents,
+//! exporting the result, and the weekly self-addressed report.
+
+use cove_core::{
+    AnalyticsFormat, CorrespondentStats, DailyVolume, MailAddress, MailAnalytics,
+    WeeklyReportPolicy,
+};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use std::collections::{BTreeMap, HashMap};
+
+/// Audit-log kind recorded for each weekly report sent; the newest entry
+/// tells when the last one went out.
+pub const WEEKLY_REPORT_AUDIT_KIND: &str = "analytics.weekly_report";
+
+/// Running counts while scanning the period's messages.
+#[derive(Debug, Default)]
+pub(crate) struct Tally {
+    received: u32,
+    sent: u32,
+    daily: BTreeMap<NaiveDate, (u32, u32)>,
+    correspondents: HashMap<String, CorrespondentStats>,
+}
+
+impl Tally {
+    /// Count one message. It is sent mail when it comes from `me`, in which
+    /// case every recipient other than `me` is a correspondent.
+    pub(crate) fn add<'a>(
+        &mut self,
+        day: NaiveDate,
+        from: Option<&MailAddress>,
+        recipients: impl Iterator<Item = &'a MailAddress>,
+        me: &str,
+    ) {
+        let is_me = |address: &MailAddress| address.address.trim().eq_ignore_ascii_case(me);
+        match from {
+            Some(sender) if is_me(sender) => {
+                self.sent += 1;
+                self.daily.entry(day).or_default().1 += 1;
+                for recipient in recipients.filter(|recipient| !is_me(recipient)) {
+                    self.correspondent(recipient).sent += 1;
+                }
+            }
+            sender => {
+                self.received += 1;
+                self.daily.entry(day).or_default().0 += 1;
+                if let Some(sender) = sender {
+                    self.correspondent(sender).received += 1;
+                }
+            }
+        }
+    }
+
+    fn correspondent(&mut self, address: &MailAddress) -> &mut CorrespondentStats {
+        let stats = self
+            .correspondents
+            .entry(address.address.trim().to_ascii_lowercase())
+            .or_insert_with_key(|key| CorrespondentStats {
+                address: key.clone(),
+                name: None,
+                received: 0,
+                sent: 0,
+            });
+        if stats.name.is_none() {
+            stats.name = address.name.clone().filter(|name| !name.trim().is_empty());
+        }
+        stats
+    }
+
+    pub(crate) fn finish(
+        self,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        mut response_hours: Vec<f64>,
+        top_correspondents: usize,
+    ) -> MailAnalytics {
+        let mut correspondents: Vec<CorrespondentStats> =
+            self.correspondents.into_values().collect();
+        correspondents.sort_by(|a, b| {
+            (b.received + b.sent)
+                .cmp(&(a.received + a.sent))
+                .then_with(|| a.address.cmp(&b.address))
+        });
+        correspondents.truncate(top_correspondents);
+
+        let avg_response_hours = (!response_hours.is_empty())
+            .then(|| response_hours.iter().sum::<f64>() / response_hours.len() as f64);
+        MailAnalytics {
+            since,
+            until,
+            received: self.received,
+            sent: self.sent,
+            daily: self
+                .daily
+                .into_iter()
+                .map(|(date, (received, sent))| DailyVolume {
+                    date,
+                    received,
+                    sent,
+                })
+                .collect(),
+            replies: response_hours.len() as u32,
+            avg_response_hours,
+            median_response_hours: median(&mut response_hours),
+            top_correspondents: correspondents,
+        }
+    }
+}
+
+fn median(values: &mut [f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    Some(if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    })
+}
+
+/// `analytics` as a file for spreadsheets (CSV) or scripts (JSON).
+pub fn render_analytics(
+    analytics: &MailAnalytics,
+    format: AnalyticsFormat,
+) -> Result<String, serde_json::Error> {
+    match format {
+        AnalyticsFormat::Json => serde_json::to_string_pretty(analytics),
+        AnalyticsFormat::Csv => Ok(analytics_csv(analytics)),
+    }
+}
+
+/// Long-format CSV: one `metric,key,value` row per number, so every metric
+/// fits one sheet and pivots cleanly.
+fn analytics_csv(analytics: &MailAnalytics) -> String {
+    let mut out = String::from("metric,key,value\n");
+    let mut row = |metric: &str, key: &str, value: String| {
+        out.push_str(&format!(
+            "{},{},{}\n",
+            csv_field(metric),
+            csv_field(key),
+            csv_field(&value)
+        ));
+    };
+
+    row("period", "since", analytics.since.to_rfc3339());
+    row("period", "until", analytics.until.to_rfc3339());
+    row("received", "total", analytics.received.to_string());
+    row("sent", "total", analytics.sent.to_string());
+    row("replies", "total", analytics.replies.to_string());
+    if let Some(hours) = analytics.avg_response_hours {
+        row("response_hours", "average", format!("{hours:.2}"));
+    }
+    if let Some(hours) = analytics.median_response_hours {
+        row("response_hours", "median", format!("{hours:.2}"));
+    }
+    for day in &analytics.daily {
+        let date = day.date.to_string();
+        row("received", &date, day.received.to_string());
+        row("sent", &date, day.sent.to_string());
+    }
+    for person in &analytics.top_correspondents {
+        row("correspondent_received", &person.address, person.received.to_string());
+        row("correspondent_sent", &person.address, person.sent.to_string());
+    }
+    out
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Subject line of the weekly report email.
+pub fn weekly_report_subject(analytics: &MailAnalytics) -> String {
+    format!(
+        "Your week in mail: {} received, {} sent",
+        analytics.received, analytics.sent
+    )
+}
+
+/// Plain-text body of the weekly report email.
+pub fn weekly_report_text(analytics: &MailAnalytics) -> String {
+    let local = |at: DateTime<Utc>| at.with_timezone(&Local).format("%a %b %d").to_string();
+    let hours = |hours: Option<f64>| {
+        hours
+            .map(|hours| format!("{hours:.1} h"))
+            .unwrap_or_else(|| "—".to_string())
+    };
+
+    let mut out = format!(
+        "Mail from {} to {}\n\nVolume\n  Received: {}\n  Sent: {}\n",
+        local(analytics.since),
+        local(analytics.until - Duration::seconds(1)),
+        analytics.received,
+        analytics.sent
+    );
+    if let Some(busiest) = analytics
+        .daily
+        .iter()
+        .max_by_key(|day| (day.received + day.sent, std::cmp::Reverse(day.date)))
+    {
+        out.push_str(&format!(
+            "  Busiest day: {} ({} messages)\n",
+            busiest.date.format("%a %b %d"),
+            busiest.received + busiest.sent
+        ));
+    }
+
+    out.push_str(&format!(
+        "\nResponse times\n  Replies: {}\n  Average: {}\n  Median: {}\n",
+        analytics.replies,
+        hours(analytics.avg_response_hours),
+        hours(analytics.median_response_hours)
+    ));
+
+    out.push_str("\nTop correspondents\n");
+    if analytics.top_correspondents.is_empty() {
+        out.push_str("  (none)\n");
+    }
+    for person in &analytics.top_correspondents {
+        let who = match &person.name {
+            Some(name) => format!("{name} <{}>", person.address),
+            None => person.address.clone(),
+        };
+        out.push_str(&format!(
+            "  {who}: {} received, {} sent\n",
+            person.received, person.sent
+        ));
+    }
+    out
+}
+
+/// The latest report time at or before `now`: this week's configured
+/// weekday and hour, or last week's if that is still ahead.
+pub fn weekly_report_slot<Tz: TimeZone>(
+    policy: &WeeklyReportPolicy,
+    now: &DateTime<Tz>,
+) -> Option<DateTime<Utc>> {
+    let time = NaiveTime::from_hms_opt(policy.hour.min(23), 0, 0)?;
+    let today = now.date_naive();
+    let days_back =
+        (today.weekday().num_days_from_monday() + 7 - policy.weekday.num_days_from_monday()) % 7;
+    let at = |date: NaiveDate| {
+        now.timezone()
+            .from_local_datetime(&date.and_time(time))
+            .earliest()
+            .map(|slot| slot.with_timezone(&Utc))
+    };
+
+    let date = today - Duration::days(i64::from(days_back));
+    let slot = at(date)?;
+    if slot > now.with_timezone(&Utc) {
+        at(date - Duration::days(7))
+    } else {
+        Some(slot)
+    }
+}
+
+/// Whether a report is owed: enabled, and none sent since the latest slot.
+/// A slot missed while the app was closed is made up on the next check.
+pub fn weekly_report_due<Tz: TimeZone>(
+    policy: &WeeklyReportPolicy,
+    last_sent: Option<DateTime<Utc>>,
+    now: &DateTime<Tz>,
+) -> bool {
+    policy.enabled
+        && weekly_report_slot(policy, now)
+            .is_some_and(|slot| last_sent.map_or(true, |last| last < slot))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, Weekday};
+
+    fn address(address: &str, name: Option<&str>) -> MailAddress {
+        MailAddress {
+            name: name.map(str::to_string),
+            address: address.to_string(),
+        }
+    }
+
+    #[test]
+    fn tallies_sent_and_received_mail() {
+        let day = NaiveDate::from_ymd_opt(2026, 10, 12).unwrap();
+        let me = "me@example.com";
+        let alice = address("Alice@Example.com", Some("Alice"));
+        let bob = address("bob@example.com", None);
+
+        let mut tally = Tally::default();
+        tally.add(day, Some(&alice), [address(me, None)].iter(), me);
+        tally.add(day, Some(&alice), std::iter::empty(), me);
+        tally.add(
+            day,
+            Some(&address("ME@example.com", None)),
+            [bob.clone(), alice.clone()].iter(),
+            me,
+        );
+
+        let since = Utc.with_ymd_and_hms(2026, 10, 12, 0, 0, 0).unwrap();
+        let analytics = tally.finish(
+            since,
+            since + Duration::days(7),
+            vec![5.0, 1.0, 3.0, 2.0],
+            1,
+        );
+
+        assert_eq!((analytics.received, analytics.sent), (2, 1));
+        assert_eq!(
+            analytics.daily,
+            vec![DailyVolume {
+                date: day,
+                received: 2,
+                sent: 1
+            }]
+        );
+        assert_eq!(analytics.replies, 4);
+        assert_eq!(analytics.avg_response_hours, Some(2.75));
+        assert_eq!(analytics.median_response_hours, Some(2.5));
+        assert_eq!(
+            analytics.top_correspondents,
+            vec![CorrespondentStats {
+                address: "alice@example.com".to_string(),
+                name: Some("Alice".to_string()),
+                received: 2,
+                sent: 1,
+            }]
+        );
+
+        let csv = analytics_csv(&analytics);
+        assert!(csv.starts_with("metric,key,value\n"));
+        assert!(csv.contains("received,2026-10-12,2\n"));
+        assert!(csv.contains("response_hours,median,2.50\n"));
+        assert!(csv.contains("correspondent_sent,alice@example.com,1\n"));
+        assert_eq!(csv_field("a,\"b\""), "\"a,\"\"b\"\"\"");
+    }
+
+    #[test]
+    fn report_is_due_once_per_weekly_slot() {
+        let policy = WeeklyReportPolicy {
+            enabled: true,
+            weekday: Weekday::Mon,
+            hour: 8,
+            ..WeeklyReportPolicy::default()
+        };
+        let tz = FixedOffset::east_opt(2 * 3600).unwrap();
+        // Wednesday; the slot is Monday 08:00 local.
+        let now = tz.with_ymd_and_hms(2026, 10, 14, 10, 0, 0).unwrap();
+        let slot = Utc.with_ymd_and_hms(2026, 10, 12, 6, 0, 0).unwrap();
+
+        assert_eq!(weekly_report_slot(&policy, &now), Some(slot));
+        assert!(weekly_report_due(&policy, None, &now));
+        assert!(weekly_report_due(
+            &policy,
+            Some(slot - Duration::hours(1)),
+            &now
+        ));
+        assert!(!weekly_report_due(
+            &policy,
+            Some(slot + Duration::hours(1)),
+            &now
+        ));
+
+        // Monday before 08:00 still belongs to the previous week's slot.
+        let early = tz.with_ymd_and_hms(2026, 10, 12, 7, 0, 0).unwrap();
+        assert_eq!(
+            weekly_report_slot(&policy, &early),
+            Some(slot - Duration::days(7))
+        );
+
+        let disabled = WeeklyReportPolicy::default();
+        assert!(!weekly_report_due(&disabled, None, &now));
+    }
+}
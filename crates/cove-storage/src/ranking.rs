@@ -0,0 +1,145 @@
+//! Re-ranking of full-text hits: BM25 relevance blended with recency and
+//! sender affinity, so a fresh message from someone the user writes to
+//! beats a stale newsletter with the same terms.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Candidate hits fetched from Tantivy per requested result.
+pub(crate) const CANDIDATE_FACTOR: usize = 3;
+/// Upper bound on candidates re-ranked per query.
+pub(crate) const MAX_CANDIDATES: usize = 300;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RankingWeights {
+    /// Weight of the BM25 score, normalised to the best hit.
+    pub relevance: f32,
+    /// Weight of the recency term, which halves every `half_life_days`.
+    pub recency: f32,
+    pub half_life_days: f32,
+    /// Weight of how often the user has written to the sender.
+    pub affinity: f32,
+}
+
+impl Default for RankingWeights {
+    fn default() -> Self {
+        Self {
+            relevance: 1.0,
+            recency: 0.5,
+            half_life_days: 30.0,
+            affinity: 0.35,
+        }
+    }
+}
+
+/// Per-hit score components, for the search debug panel.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RankBreakdown {
+    pub bm25: f32,
+    pub relevance: f32,
+    pub recency: f32,
+    pub affinity: f32,
+    /// Messages the user has sent to this hit's sender.
+    pub replies_to_sender: u32,
+    pub score: f32,
+}
+
+impl RankingWeights {
+    pub fn score(
+        &self,
+        bm25: f32,
+        best_bm25: f32,
+        received_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+        replies_to_sender: u32,
+    ) -> RankBreakdown {
+        let relevance = if best_bm25 > 0.0 { bm25 / best_bm25 } else { 0.0 };
+        let age_days = (now - received_at).num_seconds().max(0) as f32 / 86_400.0;
+        let recency = 0.5_f32.powf(age_days / self.half_life_days.max(0.1));
+        // Saturating: the first few replies matter most.
+        let replies = (replies_to_sender as f32).ln_1p();
+        let affinity = replies / (1.0 + replies);
+
+        RankBreakdown {
+            bm25,
+            relevance,
+            recency,
+            affinity,
+            replies_to_sender,
+            score: self.relevance * relevance + self.recency * recency + self.affinity * affinity,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2025-06-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn default_weights_favour_relevance_over_recency_and_affinity() {
+        let weights = RankingWeights::default();
+        assert_eq!(weights.relevance, 1.0);
+        assert_eq!(weights.recency, 0.5);
+        assert_eq!(weights.half_life_days, 30.0);
+        assert_eq!(weights.affinity, 0.35);
+
+        // The best hit, received just now from a stranger, scores the
+        // relevance and recency weights and nothing for affinity.
+        let top = weights.score(4.0, 4.0, now(), now(), 0);
+        assert_eq!(top.relevance, 1.0);
+        assert_eq!(top.recency, 1.0);
+        assert_eq!(top.affinity, 0.0);
+        assert!((top.score - 1.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn recency_halves_every_half_life_and_orders_equal_hits() {
+        let weights = RankingWeights::default();
+        let fresh = weights.score(2.0, 2.0, now() - Duration::days(1), now(), 0);
+        let month = weights.score(2.0, 2.0, now() - Duration::days(30), now(), 0);
+        let year = weights.score(2.0, 2.0, now() - Duration::days(365), now(), 0);
+        assert!((month.recency - 0.5).abs() < 1e-4);
+        assert!(fresh.score > month.score && month.score > year.score);
+
+        // Mail dated in the future counts as received now.
+        let future = weights.score(2.0, 2.0, now() + Duration::days(3), now(), 0);
+        assert_eq!(future.recency, 1.0);
+    }
+
+    #[test]
+    fn affinity_boosts_known_senders_with_diminishing_returns() {
+        let weights = RankingWeights::default();
+        let received = now() - Duration::days(10);
+        let stranger = weights.score(3.0, 4.0, received, now(), 0);
+        let one = weights.score(3.0, 4.0, received, now(), 1);
+        let many = weights.score(3.0, 4.0, received, now(), 50);
+        assert!(stranger.score < one.score && one.score < many.score);
+        assert!(many.affinity < 1.0);
+        assert!(many.affinity - one.affinity < one.affinity);
+        assert_eq!(many.replies_to_sender, 50);
+    }
+
+    #[test]
+    fn zero_weights_leave_plain_bm25_order() {
+        let weights = RankingWeights {
+            recency: 0.0,
+            affinity: 0.0,
+            ..RankingWeights::default()
+        };
+        let old_match = weights.score(8.0, 8.0, now() - Duration::days(900), now(), 0);
+        let fresh_partial = weights.score(2.0, 8.0, now(), now(), 40);
+        assert_eq!(old_match.score, 1.0);
+        assert_eq!(fresh_partial.score, 0.25);
+        assert!(old_match.score > fresh_partial.score);
+
+        // No positive best score means nothing to normalise against.
+        assert_eq!(weights.score(0.0, 0.0, now(), now(), 0).score, 0.0);
+    }
+}
@@ -0,0 +1,312 @@
+//! One scheduler for every time-based trigger: snoozed mail coming back,
+//! needs-reply follow-ups, event alerts and task due times.
+//!
+//! Triggers are planned from their sources into the `reminders` table and
+//! polled from there, so a reminder that fell due while the app was closed
+//! still fires on the next poll, exactly once.
+
+use crate::{Storage, StorageError};
+use cove_core::{CalendarEvent, MailMessage, Reminder, ReminderKind, ReminderTask};
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+/// How far ahead event alerts are planned; later events are picked up by
+/// a later poll.
+const EVENT_HORIZON_DAYS: i64 = 7;
+
+/// Receives due reminders, one method per kind. Returning `false` leaves
+/// the reminder pending for the next poll, e.g. during quiet hours.
+pub trait ReminderHandler {
+    fn mail_snooze_ended(&mut self, reminder: &Reminder) -> bool;
+    fn follow_up_due(&mut self, reminder: &Reminder) -> bool;
+    fn event_alert(&mut self, reminder: &Reminder) -> bool;
+    fn task_due(&mut self, reminder: &Reminder) -> bool;
+}
+
+/// Everything reminders are planned from.
+#[derive(Debug, Default)]
+pub struct ReminderSources {
+    pub snoozed_mail: Vec<MailMessage>,
+    /// Needs-reply items with the time their snooze ends.
+    pub follow_ups: Vec<(DateTime<Utc>, MailMessage)>,
+    pub events: Vec<CalendarEvent>,
+    pub tasks: Vec<ReminderTask>,
+}
+
+#[derive(Clone)]
+pub struct ReminderScheduler {
+    storage: Storage,
+}
+
+impl ReminderScheduler {
+    pub fn new(storage: Storage) -> Self {
+        Self { storage }
+    }
+
+    /// Hand every due reminder to `handler`, then plan the next ones.
+    /// Returns how many reminders fired.
+    pub async fn poll<H: ReminderHandler + ?Sized>(
+        &self,
+        now: DateTime<Utc>,
+        lead_minutes: &[i64],
+        handler: &mut H,
+    ) -> Result<usize, StorageError> {
+        let mut fired = 0;
+        for reminder in self.storage.due_reminders(now).await? {
+            let handled = match reminder.kind {
+                ReminderKind::MailSnooze => handler.mail_snooze_ended(&reminder),
+                ReminderKind::FollowUp => handler.follow_up_due(&reminder),
+                ReminderKind::EventAlert => handler.event_alert(&reminder),
+                ReminderKind::TaskDue => handler.task_due(&reminder),
+            };
+            if handled {
+                self.storage.mark_reminder_fired(reminder.id, now).await?;
+                fired += 1;
+            }
+        }
+        self.refresh(now, lead_minutes).await?;
+        Ok(fired)
+    }
+
+    /// Re-plan future reminders from the current snoozes, follow-ups,
+    /// events and tasks, dropping those whose source went away.
+    pub async fn refresh(
+        &self,
+        now: DateTime<Utc>,
+        lead_minutes: &[i64],
+    ) -> Result<(), StorageError> {
+        let sources = ReminderSources {
+            snoozed_mail: self.storage.snoozed_mail_messages(now).await?,
+            follow_ups: self.storage.snoozed_follow_ups(now).await?,
+            events: self
+                .storage
+                .upcoming_calendar_events(now, now + Duration::days(EVENT_HORIZON_DAYS))
+                .await?,
+            tasks: self.storage.open_tasks_due_after(now).await?,
+        };
+        let planned = plan_reminders(now, &sources, lead_minutes);
+        self.storage.replace_pending_reminders(now, &planned).await
+    }
+}
+
+/// The reminders `sources` call for. Event and task alerts get one reminder
+/// per lead time; of the lead times already past only the latest is kept,
+/// so a restart inside the window alerts once rather than once per lead.
+pub fn plan_reminders(
+    now: DateTime<Utc>,
+    sources: &ReminderSources,
+    lead_minutes: &[i64],
+) -> Vec<Reminder> {
+    let mut leads: Vec<i64> = lead_minutes
+        .iter()
+        .copied()
+        .filter(|lead| *lead >= 0)
+        .collect();
+    leads.sort_unstable_by(|a, b| b.cmp(a));
+    leads.dedup();
+
+    let mut planned = Vec::new();
+    for message in &sources.snoozed_mail {
+        let Some(until) = message.snoozed_until else {
+            continue;
+        };
+        planned.push(mail_reminder(ReminderKind::MailSnooze, until, message));
+    }
+    for (until, message) in &sources.follow_ups {
+        planned.push(mail_reminder(ReminderKind::FollowUp, *until, message));
+    }
+
+    for event in &sources.events {
+        for (lead, trigger_at) in lead_triggers(now, event.starts_at, &leads) {
+            planned.push(Reminder {
+                id: Uuid::new_v4(),
+                kind: ReminderKind::EventAlert,
+                target_id: event.id,
+                account_id: event.account_id,
+                trigger_at,
+                // A "starts now" alert is still useful while the event runs.
+                expires_at: Some(if lead == 0 {
+                    event.ends_at
+                } else {
+                    event.starts_at
+                }),
+                lead_minutes: lead,
+                title: event.title.clone(),
+                detail: event.location.clone(),
+            });
+        }
+    }
+
+    for task in &sources.tasks {
+        let Some(due_at) = task.due_at.filter(|_| task.completed_at.is_none()) else {
+            continue;
+        };
+        for (lead, trigger_at) in lead_triggers(now, due_at, &leads) {
+            if task.snoozed_until.is_some_and(|until| until > trigger_at) {
+                continue;
+            }
+            planned.push(Reminder {
+                id: Uuid::new_v4(),
+                kind: ReminderKind::TaskDue,
+                target_id: task.id,
+                account_id: task.account_id,
+                trigger_at,
+                expires_at: (lead > 0).then_some(due_at),
+                lead_minutes: lead,
+                title: task.title.clone(),
+                detail: task.notes.clone(),
+            });
+        }
+    }
+    planned
+}
+
+fn mail_reminder(kind: ReminderKind, trigger_at: DateTime<Utc>, message: &MailMessage) -> Reminder {
+    Reminder {
+        id: Uuid::new_v4(),
+        kind,
+        target_id: message.id,
+        account_id: message.account_id,
+        trigger_at,
+        expires_at: None,
+        lead_minutes: 0,
+        title: message.subject.clone(),
+        detail: message.from.first().map(|sender| {
+            sender
+                .name
+                .clone()
+                .unwrap_or_else(|| sender.address.clone())
+        }),
+    }
+}
+
+/// `(lead, trigger)` pairs for something happening at `at`, with `leads`
+/// sorted longest first. Past triggers collapse into the latest of them.
+fn lead_triggers(
+    now: DateTime<Utc>,
+    at: DateTime<Utc>,
+    leads: &[i64],
+) -> Vec<(i64, DateTime<Utc>)> {
+    if at <= now {
+        return Vec::new();
+    }
+    let triggers: Vec<(i64, DateTime<Utc>)> = leads
+        .iter()
+        .map(|lead| (*lead, at - Duration::minutes(*lead)))
+        .collect();
+    let latest_past = triggers.iter().rposition(|(_, trigger)| *trigger <= now);
+    triggers
+        .into_iter()
+        .enumerate()
+        .filter(|(index, (_, trigger))| *trigger > now || Some(*index) == latest_past)
+        .map(|(_, pair)| pair)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cove_core::{RsvpStatus, TaskPriority, TaskStatus};
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap()
+    }
+
+    fn event(starts_in_minutes: i64) -> CalendarEvent {
+        let starts_at = now() + Duration::minutes(starts_in_minutes);
+        CalendarEvent {
+            id: Uuid::new_v4(),
+            account_id: Uuid::new_v4(),
+            calendar_id: "primary".to_string(),
+            remote_id: "evt".to_string(),
+            title: "Standup".to_string(),
+            description: None,
+            location: Some("Room 4".to_string()),
+            timezone: None,
+            starts_at,
+            ends_at: starts_at + Duration::minutes(15),
+            all_day: false,
+            recurrence_rule: None,
+            attendees: Vec::new(),
+            organizer: None,
+            alarms: Vec::new(),
+            rsvp_status: RsvpStatus::Accepted,
+            online_meeting_url: None,
+            updated_at: now(),
+        }
+    }
+
+    fn task(due_in_minutes: i64) -> ReminderTask {
+        ReminderTask {
+            id: Uuid::new_v4(),
+            account_id: Uuid::new_v4(),
+            list_id: "default".to_string(),
+            remote_id: None,
+            title: "File expenses".to_string(),
+            notes: None,
+            due_at: Some(now() + Duration::minutes(due_in_minutes)),
+            completed_at: None,
+            priority: TaskPriority::Normal,
+            status: TaskStatus::NotStarted,
+            repeat_rule: None,
+            parent_id: None,
+            snoozed_until: None,
+            estimate_minutes: None,
+            list_name: None,
+            created_at: now(),
+            updated_at: now(),
+        }
+    }
+
+    #[test]
+    fn plans_one_alert_per_future_lead() {
+        let sources = ReminderSources {
+            events: vec![event(60)],
+            ..Default::default()
+        };
+        let planned = plan_reminders(now(), &sources, &[5, 15, 15, -1]);
+        let leads: Vec<i64> = planned
+            .iter()
+            .map(|reminder| reminder.lead_minutes)
+            .collect();
+        assert_eq!(leads, [15, 5]);
+        assert_eq!(planned[0].trigger_at, now() + Duration::minutes(45));
+        assert_eq!(planned[0].expires_at, Some(now() + Duration::minutes(60)));
+    }
+
+    #[test]
+    fn past_leads_collapse_into_the_latest() {
+        let sources = ReminderSources {
+            events: vec![event(3)],
+            tasks: vec![task(3)],
+            ..Default::default()
+        };
+        let planned = plan_reminders(now(), &sources, &[30, 15, 5, 0]);
+        let leads = |kind| {
+            planned
+                .iter()
+                .filter(|reminder| reminder.kind == kind)
+                .map(|reminder| reminder.lead_minutes)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(leads(ReminderKind::EventAlert), [5, 0]);
+        assert_eq!(leads(ReminderKind::TaskDue), [5, 0]);
+    }
+
+    #[test]
+    fn skips_started_events_and_task_leads_inside_a_snooze() {
+        let mut snoozed = task(60);
+        snoozed.snoozed_until = Some(now() + Duration::minutes(50));
+        let sources = ReminderSources {
+            events: vec![event(-5)],
+            tasks: vec![snoozed],
+            ..Default::default()
+        };
+        let planned = plan_reminders(now(), &sources, &[15, 0]);
+        assert_eq!(planned.len(), 1);
+        assert_eq!(planned[0].kind, ReminderKind::TaskDue);
+        assert_eq!(planned[0].lead_minutes, 0);
+        assert_eq!(planned[0].expires_at, None);
+    }
+}
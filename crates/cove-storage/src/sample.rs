@@ -0,0 +1,383 @@
+//! A fake mailbox, calendar and task list for screenshots, tests and
+//! first-run exploration.
+//!
+//! Everything belongs to one account with a fixed id, and every other id is
+//! derived from it, so seeding twice updates in place and removal finds
+//! exactly what was added. Dates are relative to the time of seeding so the
+//! data always looks current.
+
+use cove_core::{
+    Account, AccountProtocol, CalendarAlarm, CalendarEvent, Contact, MailAddress, MailFlags,
+    MailMessage, Provider, ReminderTask, RsvpStatus, TaskList, TaskPriority, TaskStatus,
+};
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Id of the sample account. Sync skips it.
+pub const SAMPLE_ACCOUNT_ID: Uuid = Uuid::from_u128(0x5a3b_1e00_c0de_4000_8000_0000_0000_0000);
+
+const USER_NAME: &str = "Sam Rivera";
+const USER_ADDRESS: &str = "sam@example.com";
+const CALENDAR_ID: &str = "sample-calendar";
+const TASK_LIST_ID: &str = "sample-tasks";
+
+pub fn is_sample_account(account_id: Uuid) -> bool {
+    account_id == SAMPLE_ACCOUNT_ID
+}
+
+pub(crate) struct SampleMailbox {
+    pub account: Account,
+    pub contacts: Vec<Contact>,
+    pub messages: Vec<MailMessage>,
+    pub events: Vec<CalendarEvent>,
+    pub task_list: TaskList,
+    pub tasks: Vec<ReminderTask>,
+}
+
+/// Ids are the account id with a per-kind tag and sequence number in the
+/// low bits.
+fn sample_id(kind: u128, n: u128) -> Uuid {
+    Uuid::from_u128(SAMPLE_ACCOUNT_ID.as_u128() | (kind << 16) | n)
+}
+
+fn address(name: &str, address: &str) -> MailAddress {
+    MailAddress {
+        name: Some(name.to_string()),
+        address: address.to_string(),
+    }
+}
+
+fn user() -> MailAddress {
+    address(USER_NAME, USER_ADDRESS)
+}
+
+struct Mail<'a> {
+    thread: &'a str,
+    folder: &'a str,
+    from: MailAddress,
+    to: Vec<MailAddress>,
+    subject: &'a str,
+    body: &'a str,
+    hours_ago: i64,
+    seen: bool,
+    flagged: bool,
+    labels: &'a [&'a str],
+}
+
+impl Mail<'_> {
+    fn build(self, n: u128, now: DateTime<Utc>) -> MailMessage {
+        let at = now - Duration::hours(self.hours_ago);
+        let preview: String = self.body.split_whitespace().collect::<Vec<_>>().join(" ");
+        let mut headers = BTreeMap::new();
+        headers.insert("Message-ID".to_string(), format!("<sample-{n}@example.com>"));
+        MailMessage {
+            id: sample_id(1, n),
+            account_id: SAMPLE_ACCOUNT_ID,
+            remote_id: format!("sample-{n}"),
+            thread_id: format!("sample-thread-{}", self.thread),
+            folder_path: self.folder.to_string(),
+            from: vec![self.from],
+            to: self.to,
+            cc: Vec::new(),
+            bcc: Vec::new(),
+            reply_to: Vec::new(),
+            subject: self.subject.to_string(),
+            preview: preview.chars().take(160).collect(),
+            body_text: Some(self.body.to_string()),
+            body_html: None,
+            flags: MailFlags {
+                seen: self.seen,
+                flagged: self.flagged,
+                ..MailFlags::default()
+            },
+            labels: self.labels.iter().map(|label| label.to_string()).collect(),
+            headers,
+            attachments: Vec::new(),
+            sent_at: Some(at),
+            received_at: at,
+            created_at: at,
+            updated_at: at,
+            snoozed_until: None,
+            pinned: false,
+            send_at: None,
+        }
+    }
+}
+
+pub(crate) fn sample_mailbox(now: DateTime<Utc>) -> SampleMailbox {
+    let priya = address("Priya Shah", "priya@northwind.example");
+    let marco = address("Marco Rossi", "marco@northwind.example");
+    let jordan = address("Jordan Lee", "jordan@example.org");
+    let billing = address("Contoso Cloud Billing", "billing@contoso.example");
+    let legal = address("Fabrikam Legal", "legal@fabrikam.example");
+    let skyways = address("Skyways", "trips@skyways.example");
+    let digest = address("This Week in Rust", "digest@this-week.example");
+
+    let mails = vec![
+        Mail {
+            thread: "roadmap",
+            folder: "INBOX",
+            from: priya.clone(),
+            to: vec![user(), marco.clone()],
+            subject: "Q3 roadmap review",
+            body: "Hi both,\n\nI've put the Q3 roadmap draft in the shared folder. Could you look at the \
+                   offline sync and search milestones before Thursday's review?\n\nThanks,\nPriya",
+            hours_ago: 50,
+            seen: true,
+            flagged: false,
+            labels: &["Work"],
+        },
+        Mail {
+            thread: "roadmap",
+            folder: "Sent",
+            from: user(),
+            to: vec![priya.clone(), marco.clone()],
+            subject: "Re: Q3 roadmap review",
+            body: "Looks good overall. I'd move search ranking ahead of the calendar work; \
+                   happy to talk it through on Thursday.\n\nSam",
+            hours_ago: 46,
+            seen: true,
+            flagged: false,
+            labels: &["Work"],
+        },
+        Mail {
+            thread: "roadmap",
+            folder: "INBOX",
+            from: marco.clone(),
+            to: vec![user(), priya.clone()],
+            subject: "Re: Q3 roadmap review",
+            body: "Agree with Sam on search. I can have the estimates ready by Wednesday evening.\n\nMarco",
+            hours_ago: 20,
+            seen: false,
+            flagged: false,
+            labels: &["Work"],
+        },
+        Mail {
+            thread: "design",
+            folder: "INBOX",
+            from: marco.clone(),
+            to: vec![user()],
+            subject: "Design critique: onboarding flow",
+            body: "The new onboarding screens are up in the design file. The account step still \
+                   feels long; can we drop the server fields behind an \"Advanced\" toggle?",
+            hours_ago: 5,
+            seen: false,
+            flagged: true,
+            labels: &["Work", "Design"],
+        },
+        Mail {
+            thread: "invoice",
+            folder: "INBOX",
+            from: billing,
+            to: vec![user()],
+            subject: "Invoice INV-20931 is ready",
+            body: "Your invoice INV-20931 for $142.80 is ready. Payment is due within 14 days.\n\n\
+                   Contoso Cloud",
+            hours_ago: 30,
+            seen: true,
+            flagged: false,
+            labels: &["Finance"],
+        },
+        Mail {
+            thread: "contract",
+            folder: "INBOX",
+            from: legal,
+            to: vec![user()],
+            subject: "Contract renewal - action needed by Friday",
+            body: "The Fabrikam services agreement expires at the end of the month. Please review \
+                   the renewal terms in the customer portal and confirm by Friday.",
+            hours_ago: 9,
+            seen: false,
+            flagged: true,
+            labels: &["Work"],
+        },
+        Mail {
+            thread: "lunch",
+            folder: "INBOX",
+            from: jordan.clone(),
+            to: vec![user()],
+            subject: "Lunch on Friday?",
+            body: "Want to try the new ramen place on Friday? 12:30 works for me.\n\nJ",
+            hours_ago: 3,
+            seen: false,
+            flagged: false,
+            labels: &[],
+        },
+        Mail {
+            thread: "lunch",
+            folder: "Sent",
+            from: user(),
+            to: vec![jordan],
+            subject: "Re: Lunch on Friday?",
+            body: "Yes! 12:30 it is. I'll put it in the calendar.",
+            hours_ago: 2,
+            seen: true,
+            flagged: false,
+            labels: &[],
+        },
+        Mail {
+            thread: "flight",
+            folder: "INBOX",
+            from: skyways,
+            to: vec![user()],
+            subject: "Your flight to Lisbon is confirmed",
+            body: "Booking reference KX7Q2M. Flight SW 482 departs next week at 07:45. \
+                   Online check-in opens 24 hours before departure.",
+            hours_ago: 70,
+            seen: true,
+            flagged: false,
+            labels: &["Travel"],
+        },
+        Mail {
+            thread: "digest",
+            folder: "INBOX",
+            from: digest,
+            to: vec![user()],
+            subject: "This week: async traits, faster builds and a new egui release",
+            body: "Highlights from the community this week, plus jobs and upcoming events.",
+            hours_ago: 28,
+            seen: false,
+            flagged: false,
+            labels: &["Newsletters"],
+        },
+        Mail {
+            thread: "welcome",
+            folder: "Archive",
+            from: address("Cove Mail", "hello@covemail.example"),
+            to: vec![user()],
+            subject: "Welcome to Cove Mail",
+            body: "This is sample data. Remove it from the Get Started checklist whenever you like.",
+            hours_ago: 24 * 14,
+            seen: true,
+            flagged: false,
+            labels: &[],
+        },
+    ];
+    let messages = mails
+        .into_iter()
+        .zip(1..)
+        .map(|(mail, n)| mail.build(n, now))
+        .collect();
+
+    let contacts = [(&priya, 14), (&marco, 9)]
+        .into_iter()
+        .zip(1..)
+        .map(|((person, count), n)| Contact {
+            id: sample_id(2, n),
+            account_id: Some(SAMPLE_ACCOUNT_ID),
+            email: person.address.clone(),
+            display_name: person.name.clone(),
+            phone: None,
+            organization: Some("Northwind".to_string()),
+            notes: None,
+            last_contacted: Some(now - Duration::days(2)),
+            contact_count: count,
+            vip: false,
+        })
+        .collect();
+
+    let at = |days: i64, hour: u32, minute: u32| {
+        (now + Duration::days(days))
+            .date_naive()
+            .and_time(NaiveTime::from_hms_opt(hour, minute, 0).unwrap_or_default())
+            .and_utc()
+    };
+    let event = |n: u128,
+                 title: &str,
+                 starts_at: DateTime<Utc>,
+                 minutes: i64,
+                 location: Option<&str>,
+                 attendees: &[&MailAddress]| {
+        CalendarEvent {
+            id: sample_id(3, n),
+            account_id: SAMPLE_ACCOUNT_ID,
+            calendar_id: CALENDAR_ID.to_string(),
+            remote_id: format!("sample-event-{n}"),
+            title: title.to_string(),
+            description: None,
+            location: location.map(str::to_string),
+            timezone: None,
+            starts_at,
+            ends_at: starts_at + Duration::minutes(minutes),
+            all_day: false,
+            recurrence_rule: None,
+            attendees: attendees.iter().map(|person| person.address.clone()).collect(),
+            organizer: Some(USER_ADDRESS.to_string()),
+            alarms: vec![CalendarAlarm {
+                minutes_before: 10,
+                message: None,
+            }],
+            rsvp_status: RsvpStatus::Accepted,
+            online_meeting_url: None,
+            updated_at: now,
+        }
+    };
+    let events = vec![
+        event(1, "Q3 roadmap review", at(1, 10, 0), 60, Some("Room 4B"), &[&priya, &marco]),
+        event(2, "1:1 with Priya", at(2, 15, 30), 30, None, &[&priya]),
+        event(3, "Lunch with Jordan", at(3, 12, 30), 60, Some("Ramen Ichi"), &[]),
+        event(4, "Dentist", at(4, 9, 0), 45, Some("Harbor Dental"), &[]),
+        event(5, "Flight SW 482 to Lisbon", at(7, 7, 45), 165, Some("Terminal 2"), &[]),
+    ];
+
+    let task_list = TaskList {
+        account_id: SAMPLE_ACCOUNT_ID,
+        list_id: TASK_LIST_ID.to_string(),
+        name: "Sample tasks".to_string(),
+        is_default: true,
+        updated_at: now,
+    };
+    let task = |n: u128,
+                title: &str,
+                due_days: Option<i64>,
+                priority: TaskPriority,
+                done: bool| ReminderTask {
+        id: sample_id(4, n),
+        account_id: SAMPLE_ACCOUNT_ID,
+        list_id: TASK_LIST_ID.to_string(),
+        list_name: Some(task_list.name.clone()),
+        remote_id: None,
+        title: title.to_string(),
+        notes: None,
+        due_at: due_days.map(|days| at(days, 17, 0)),
+        completed_at: done.then_some(now - Duration::days(1)),
+        priority,
+        status: if done {
+            TaskStatus::Completed
+        } else {
+            TaskStatus::NotStarted
+        },
+        repeat_rule: None,
+        parent_id: None,
+        snoozed_until: None,
+        created_at: now - Duration::days(3),
+        updated_at: now,
+        estimate_minutes: None,
+    };
+    let tasks = vec![
+        task(1, "Send roadmap feedback to Priya", Some(1), TaskPriority::High, false),
+        task(2, "Review Fabrikam renewal terms", Some(2), TaskPriority::Critical, false),
+        task(3, "Pay Contoso invoice INV-20931", Some(5), TaskPriority::Normal, false),
+        task(4, "Book a hotel in Lisbon", None, TaskPriority::Low, false),
+        task(5, "Renew passport", Some(-2), TaskPriority::Normal, true),
+    ];
+
+    SampleMailbox {
+        account: Account {
+            id: SAMPLE_ACCOUNT_ID,
+            provider: Provider::Generic,
+            protocols: vec![AccountProtocol::ImapSmtp],
+            display_name: format!("{USER_NAME} (sample)"),
+            email_address: USER_ADDRESS.to_string(),
+            oauth_profile: None,
+            created_at: now,
+            updated_at: now,
+        },
+        contacts,
+        messages,
+        events,
+        task_list,
+        tasks,
+    }
+}
@@ -1,11 +1,17 @@
+use crate::analysis::{
+    detect_language, register_analyzers, unsegmented_runs, FOLDED_TOKENIZER, NGRAM_TOKENIZER,
+};
 use crate::StorageError;
 use cove_core::MailMessage;
 use std::path::Path;
 use std::sync::Arc;
 use tantivy::collector::TopDocs;
-use tantivy::query::QueryParser;
-use tantivy::schema::{Schema, Value, STORED, STRING, TEXT};
-use tantivy::{doc, Index, IndexReader, IndexWriter, ReloadPolicy, Term};
+use tantivy::query::{BooleanQuery, Occur, Query, QueryParser, TermQuery};
+use tantivy::schema::{
+    IndexRecordOption, Schema, TextFieldIndexing, TextOptions, Value, STORED, STRING,
+};
+use tantivy::tokenizer::TokenStream;
+use tantivy::{doc, Index, IndexReader, IndexWriter, ReloadPolicy, TantivyDocument, Term};
 use tokio::sync::Mutex;
 
 #[derive(Clone)]
@@ -18,6 +24,10 @@ pub struct MailSearchIndex {
     preview_field: tantivy::schema::Field,
     body_field: tantivy::schema::Field,
     labels_field: tantivy::schema::Field,
+    unsegmented_field: tantivy::schema::Field,
+    lang_field: tantivy::schema::Field,
+    /// The on-disk index had an older schema and was recreated empty.
+    recreated: bool,
 }
 
 impl MailSearchIndex {
@@ -25,10 +35,16 @@ impl MailSearchIndex {
         std::fs::create_dir_all(path)?;
 
         let schema = Self::schema();
-        let index = match Index::open_in_dir(path) {
-            Ok(index) => index,
-            Err(_) => Index::create_in_dir(path, schema.clone())?,
+        let (index, recreated) = match Index::open_in_dir(path) {
+            Ok(index) if index.schema() == schema => (index, false),
+            Ok(_) => {
+                std::fs::remove_dir_all(path)?;
+                std::fs::create_dir_all(path)?;
+                (Index::create_in_dir(path, schema.clone())?, true)
+            }
+            Err(_) => (Index::create_in_dir(path, schema.clone())?, false),
         };
+        register_analyzers(&index)?;
 
         let id_field = schema
             .get_field("id")
@@ -45,6 +61,12 @@ impl MailSearchIndex {
         let labels_field = schema
             .get_field("labels")
             .map_err(|err| StorageError::Data(err.to_string()))?;
+        let unsegmented_field = schema
+            .get_field("unsegmented")
+            .map_err(|err| StorageError::Data(err.to_string()))?;
+        let lang_field = schema
+            .get_field("lang")
+            .map_err(|err| StorageError::Data(err.to_string()))?;
 
         let writer = index.writer(30_000_000)?;
         let reader = index
@@ -61,21 +83,24 @@ impl MailSearchIndex {
             preview_field,
             body_field,
             labels_field,
+            unsegmented_field,
+            lang_field,
+            recreated,
         })
     }
 
+    /// Whether the index was recreated on open, so every message needs
+    /// indexing again.
+    pub fn was_recreated(&self) -> bool {
+        self.recreated
+    }
+
     pub async fn index_message(&self, message: &MailMessage) -> Result<(), StorageError> {
         let id = message.id.to_string();
         let mut writer = self.writer.lock().await;
 
         writer.delete_term(Term::from_field_text(self.id_field, &id));
-        writer.add_document(doc!(
-            self.id_field => id,
-            self.subject_field => message.subject.clone(),
-            self.preview_field => message.preview.clone(),
-            self.body_field => message.body_text.clone().unwrap_or_default(),
-            self.labels_field => message.labels.join(" "),
-        ))?;
+        writer.add_document(self.document(message))?;
 
         writer.commit()?;
         self.reader.reload()?;
@@ -92,21 +117,52 @@ impl MailSearchIndex {
         for message in messages {
             let id = message.id.to_string();
             writer.delete_term(Term::from_field_text(self.id_field, &id));
-            writer.add_document(doc!(
-                self.id_field => id,
-                self.subject_field => message.subject.clone(),
-                self.preview_field => message.preview.clone(),
-                self.body_field => message.body_text.clone().unwrap_or_default(),
-                self.labels_field => message.labels.join(" "),
-            ))?;
+            writer.add_document(self.document(message))?;
+        }
+
+        writer.commit()?;
+        self.reader.reload()?;
+        Ok(())
+    }
+
+    pub async fn remove_messages(&self, ids: &[String]) -> Result<(), StorageError> {
+        if ids.is_empty() {
+            return Ok(());
         }
 
+        let mut writer = self.writer.lock().await;
+        for id in ids {
+            writer.delete_term(Term::from_field_text(self.id_field, id));
+        }
+
+        writer.commit()?;
+        self.reader.reload()?;
+        Ok(())
+    }
+
+    /// Drop every document, ahead of a full re-index.
+    pub async fn clear(&self) -> Result<(), StorageError> {
+        let mut writer = self.writer.lock().await;
+        writer.delete_all_documents()?;
         writer.commit()?;
         self.reader.reload()?;
         Ok(())
     }
 
     pub fn search(&self, query_text: &str, limit: usize) -> Result<Vec<String>, StorageError> {
+        Ok(self
+            .search_scored(query_text, limit)?
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect())
+    }
+
+    /// Message ids with their BM25 scores, best first.
+    pub fn search_scored(
+        &self,
+        query_text: &str,
+        limit: usize,
+    ) -> Result<Vec<(String, f32)>, StorageError> {
         if query_text.trim().is_empty() {
             return Ok(Vec::new());
         }
@@ -122,22 +178,29 @@ impl MailSearchIndex {
             ],
         );
 
-        let query = parser
+        let parsed = parser
             .parse_query(query_text)
             .map_err(|err| StorageError::Data(err.to_string()))?;
+        let query: Box<dyn Query> = match self.unsegmented_query(query_text)? {
+            Some(ngrams) => Box::new(BooleanQuery::new(vec![
+                (Occur::Should, parsed),
+                (Occur::Should, ngrams),
+            ])),
+            None => parsed,
+        };
 
         let top_docs = searcher
             .search(&query, &TopDocs::with_limit(limit))
             .map_err(|err| StorageError::Data(err.to_string()))?;
 
         let mut ids = Vec::with_capacity(top_docs.len());
-        for (_score, addr) in top_docs {
+        for (score, addr) in top_docs {
             let doc = searcher
                 .doc::<tantivy::schema::TantivyDocument>(addr)
                 .map_err(|err| StorageError::Data(err.to_string()))?;
             if let Some(value) = doc.get_first(self.id_field) {
                 if let Some(text) = value.as_str() {
-                    ids.push(text.to_string());
+                    ids.push((text.to_string(), score));
                 }
             }
         }
@@ -145,13 +208,81 @@ impl MailSearchIndex {
         Ok(ids)
     }
 
+    fn document(&self, message: &MailMessage) -> TantivyDocument {
+        let body = message.body_text.clone().unwrap_or_default();
+        let unsegmented = unsegmented_runs(&message.subject)
+            .into_iter()
+            .chain(unsegmented_runs(&body))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let language = detect_language(if body.trim().is_empty() {
+            &message.subject
+        } else {
+            &body
+        });
+
+        doc!(
+            self.id_field => message.id.to_string(),
+            self.subject_field => message.subject.clone(),
+            self.preview_field => message.preview.clone(),
+            self.body_field => body,
+            self.labels_field => message.labels.join(" "),
+            self.unsegmented_field => unsegmented,
+            self.lang_field => language,
+        )
+    }
+
+    /// Every n-gram of each unsegmented run in the query must match; `None`
+    /// when the query has no such runs.
+    fn unsegmented_query(&self, query_text: &str) -> Result<Option<Box<dyn Query>>, StorageError> {
+        let runs = unsegmented_runs(query_text);
+        if runs.is_empty() {
+            return Ok(None);
+        }
+
+        let mut analyzer = self.index.tokenizer_for_field(self.unsegmented_field)?;
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        for run in runs {
+            let mut stream = analyzer.token_stream(&run);
+            while stream.advance() {
+                let term = Term::from_field_text(self.unsegmented_field, &stream.token().text);
+                clauses.push((
+                    Occur::Must,
+                    Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs)),
+                ));
+            }
+        }
+        Ok(Some(Box::new(BooleanQuery::new(clauses))))
+    }
+
     fn schema() -> Schema {
+        let words = |stored: bool| {
+            let options = TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer(FOLDED_TOKENIZER)
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+            );
+            if stored {
+                options.set_stored()
+            } else {
+                options
+            }
+        };
+        let ngrams = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer(NGRAM_TOKENIZER)
+                .set_index_option(IndexRecordOption::WithFreqs),
+        );
+
         let mut builder = Schema::builder();
         builder.add_text_field("id", STRING | STORED);
-        builder.add_text_field("subject", TEXT | STORED);
-        builder.add_text_field("preview", TEXT | STORED);
-        builder.add_text_field("body", TEXT);
-        builder.add_text_field("labels", TEXT);
+        builder.add_text_field("subject", words(true));
+        builder.add_text_field("preview", words(true));
+        builder.add_text_field("body", words(false));
+        builder.add_text_field("labels", words(false));
+        builder.add_text_field("unsegmented", ngrams);
+        // Detected language (ISO 639-3), searchable as `lang:deu`.
+        builder.add_text_field("lang", STRING | STORED);
         builder.build()
     }
 }
@@ -1,19 +1,31 @@
-use crate::{MailSearchIndex, StorageError};
+use crate::disk::{assess, disk_capacity};
+use crate::export::{export_line, Pseudonyms};
+use crate::indexer::{IndexerState, INDEX_BATCH_SIZE};
+use crate::metrics::Tally;
+use crate::ranking::{CANDIDATE_FACTOR, MAX_CANDIDATES};
+use crate::{IndexProgress, MailSearchIndex, RankBreakdown, RankingWeights, StorageError};
 use cove_core::{
     Account, CalendarEvent, MailFolder, ReminderTask, SearchResult, SyncJob, SyncStatus,
+    TaskTimeBlock,
 };
 use chrono::{DateTime, Utc};
 use serde::de::DeserializeOwned;
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
 use sqlx::{Row, SqlitePool};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
 use uuid::Uuid;
 
 #[derive(Clone)]
 pub struct Storage {
     pool: SqlitePool,
     search: MailSearchIndex,
+    indexer: Arc<IndexerState>,
+    ranking: Arc<std::sync::RwLock<RankingWeights>>,
+    /// Directory holding the database, watched for free space.
+    data_dir: PathBuf,
 }
 
 impl Storage {
@@ -49,11 +61,26 @@ impl Storage {
                 .await?;
         }
 
+        prepare_migration_0004(&pool).await?;
         sqlx::migrate!("./migrations").run(&pool).await?;
 
         let search = MailSearchIndex::open_or_create(search_index_dir)?;
 
-        Ok(Self { pool, search })
+        let storage = Self {
+            pool,
+            search,
+            indexer: Arc::new(IndexerState::default()),
+            ranking: Arc::new(std::sync::RwLock::new(RankingWeights::default())),
+            data_dir: db_path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from(".")),
+        };
+        if storage.search.was_recreated() {
+            storage.enqueue_all_for_indexing().await?;
+        }
+        storage.spawn_indexer();
+        Ok(storage)
     }
 
     pub fn pool(&self) -> &SqlitePool {
@@ -123,9 +150,81 @@ impl Storage {
             .execute(&self.pool)
             .await?;
 
+        // Queued changes cannot be replayed without the account.
+        sqlx::query("DELETE FROM pending_operations WHERE account_id = ?1")
+            .bind(account_id.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        for table in [
+            "mail_backfill",
+            "mail_backfill_paused",
+            "imap_folder_state",
+            "sender_mutes",
+            "sent_group_expansions",
+            "reminders",
+        ] {
+            sqlx::query(&format!("DELETE FROM {table} WHERE account_id = ?1"))
+                .bind(account_id.to_string())
+                .execute(&self.pool)
+                .await?;
+        }
+
+        self.clear_account_reauth(account_id).await?;
+
+        Ok(())
+    }
+
+    /// Flag `account_id` as needing the user to sign in again. The first
+    /// detection time is kept when the flag is already set.
+    pub async fn mark_account_reauth(
+        &self,
+        account_id: Uuid,
+        reason: &str,
+    ) -> Result<(), StorageError> {
+        sqlx::query(
+            r#"
+            INSERT INTO account_reauth (account_id, reason, detected_at)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT(account_id) DO UPDATE SET reason = excluded.reason
+            "#,
+        )
+        .bind(account_id.to_string())
+        .bind(reason)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn clear_account_reauth(&self, account_id: Uuid) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM account_reauth WHERE account_id = ?1")
+            .bind(account_id.to_string())
+            .execute(&self.pool)
+            .await?;
         Ok(())
     }
 
+    pub async fn list_account_reauth(
+        &self,
+    ) -> Result<Vec<cove_core::AccountReauth>, StorageError> {
+        let rows = sqlx::query("SELECT * FROM account_reauth ORDER BY detected_at ASC")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let account_id: String = row.try_get("account_id")?;
+                let detected_at: String = row.try_get("detected_at")?;
+                Ok(cove_core::AccountReauth {
+                    account_id: parse_uuid(&account_id, "account_reauth.account_id")?,
+                    reason: row.try_get("reason")?,
+                    detected_at: parse_datetime(&detected_at, "account_reauth.detected_at")?,
+                })
+            })
+            .collect()
+    }
+
     pub async fn upsert_account_protocol_settings(
         &self,
         account_id: Uuid,
@@ -243,7 +342,8 @@ impl Storage {
         .execute(&self.pool)
         .await?;
 
-        self.search.index_message(message).await?;
+        Self::enqueue_for_indexing(&self.pool, message).await?;
+        self.indexer.notify();
         Ok(())
     }
 
@@ -284,13 +384,20 @@ impl Storage {
                   bcc_json = excluded.bcc_json,
                   reply_to_json = excluded.reply_to_json,
                   subject = excluded.subject,
-                  preview = excluded.preview,
-                  body_text = excluded.body_text,
-                  body_html = excluded.body_html,
+                  -- a headers-only sync (no body) keeps what a full sync stored
+                  preview = CASE
+                    WHEN excluded.body_text IS NULL AND excluded.body_html IS NULL
+                    THEN mail_messages.preview ELSE excluded.preview
+                  END,
+                  body_text = COALESCE(excluded.body_text, mail_messages.body_text),
+                  body_html = COALESCE(excluded.body_html, mail_messages.body_html),
                   flags_json = excluded.flags_json,
                   labels_json = excluded.labels_json,
                   headers_json = excluded.headers_json,
-                  attachments_json = excluded.attachments_json,
+                  attachments_json = CASE
+                    WHEN excluded.body_text IS NULL AND excluded.body_html IS NULL
+                    THEN mail_messages.attachments_json ELSE excluded.attachments_json
+                  END,
                   sent_at = excluded.sent_at,
                   received_at = excluded.received_at,
                   updated_at = excluded.updated_at
@@ -320,14 +427,142 @@ impl Storage {
             .bind(message.updated_at.to_rfc3339())
             .execute(&mut *tx)
             .await?;
+            Self::enqueue_for_indexing(&mut *tx, message).await?;
         }
 
         tx.commit().await?;
+        self.indexer.notify();
+        Ok(())
+    }
 
-        self.search.index_messages(messages).await?;
+    // -- background indexing -------------------------------------------------
+
+    /// Queue a message for the search indexer. Looked up by remote id because
+    /// an upsert that hits an existing row keeps that row's id.
+    async fn enqueue_for_indexing<'e, E>(
+        executor: E,
+        message: &cove_core::MailMessage,
+    ) -> Result<(), StorageError>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+    {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO search_index_queue (message_id, enqueued_at)
+            SELECT id, ?3 FROM mail_messages WHERE account_id = ?1 AND remote_id = ?2
+            "#,
+        )
+        .bind(message.account_id.to_string())
+        .bind(&message.remote_id)
+        .bind(Utc::now().to_rfc3339())
+        .execute(executor)
+        .await?;
         Ok(())
     }
 
+    pub fn index_progress(&self) -> IndexProgress {
+        self.indexer.progress()
+    }
+
+    pub fn pause_indexing(&self) {
+        self.indexer.set_paused(true);
+    }
+
+    pub fn resume_indexing(&self) {
+        self.indexer.set_paused(false);
+    }
+
+    /// Clear the search index and queue every stored message for
+    /// re-indexing. Returns the number of messages queued.
+    pub async fn rebuild_search_index(&self) -> Result<u64, StorageError> {
+        self.search.clear().await?;
+        self.enqueue_all_for_indexing().await
+    }
+
+    async fn enqueue_all_for_indexing(&self) -> Result<u64, StorageError> {
+        let queued = sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO search_index_queue (message_id, enqueued_at)
+            SELECT id, ?1 FROM mail_messages
+            "#,
+        )
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        self.indexer.notify();
+        Ok(queued)
+    }
+
+    fn spawn_indexer(&self) {
+        let storage = self.clone();
+        tokio::spawn(async move {
+            loop {
+                if storage.indexer.is_paused() {
+                    storage.indexer.wait(std::time::Duration::from_secs(5)).await;
+                    continue;
+                }
+                match storage.index_next_batch().await {
+                    Ok(0) => {
+                        storage.indexer.reset();
+                        storage.indexer.wait(std::time::Duration::from_secs(30)).await;
+                    }
+                    Ok(_) => tokio::task::yield_now().await,
+                    Err(err) => {
+                        tracing::warn!(error = %err, "background indexing failed");
+                        storage.indexer.wait(std::time::Duration::from_secs(30)).await;
+                    }
+                }
+            }
+        });
+    }
+
+    /// Index one batch from the queue with a single commit. Returns how many
+    /// queue entries were consumed.
+    async fn index_next_batch(&self) -> Result<usize, StorageError> {
+        let queued: Vec<(String, String)> = sqlx::query_as(
+            "SELECT message_id, enqueued_at FROM search_index_queue ORDER BY enqueued_at ASC LIMIT ?1",
+        )
+        .bind(INDEX_BATCH_SIZE)
+        .fetch_all(&self.pool)
+        .await?;
+        if queued.is_empty() {
+            return Ok(0);
+        }
+
+        let mut messages = Vec::with_capacity(queued.len());
+        for (id, _) in &queued {
+            let row = sqlx::query("SELECT * FROM mail_messages WHERE id = ?1")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?;
+            // Messages deleted since they were queued are simply dropped.
+            if let Some(row) = row {
+                messages.push(Self::row_to_mail_message(row)?);
+            }
+        }
+        self.search.index_messages(&messages).await?;
+
+        // Entries re-queued by a write during this batch keep their newer
+        // timestamp and are picked up again.
+        let mut tx = self.pool.begin().await?;
+        for (id, enqueued_at) in &queued {
+            sqlx::query("DELETE FROM search_index_queue WHERE message_id = ?1 AND enqueued_at = ?2")
+                .bind(id)
+                .bind(enqueued_at)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        let pending: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM search_index_queue")
+            .fetch_one(&self.pool)
+            .await?;
+        self.indexer
+            .record_batch(messages.len() as u64, pending.max(0) as u64);
+        Ok(queued.len())
+    }
+
     pub async fn list_mail_messages(
         &self,
         account_id: Uuid,
@@ -377,6 +612,85 @@ impl Storage {
         })
     }
 
+    // -- thread summaries ----------------------------------------------------
+
+    /// Threads of one account, newest first, read from the `thread_summaries`
+    /// table the mail_messages triggers maintain. Without a folder, a thread's
+    /// rows in every folder are combined. `date_bucket` is left for the
+    /// caller, which knows the local day.
+    pub async fn list_thread_summaries(
+        &self,
+        account_id: Uuid,
+        folder: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<cove_core::MailThreadSummary>, StorageError> {
+        let rows = if let Some(folder) = folder {
+            sqlx::query(
+                r#"
+                SELECT * FROM thread_summaries
+                WHERE account_id = ?1 AND folder_path = ?2
+                ORDER BY latest_at DESC
+                LIMIT ?3 OFFSET ?4
+                "#,
+            )
+            .bind(account_id.to_string())
+            .bind(folder)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?
+        } else {
+            // Subject and snippet are bare columns taken from the row with
+            // MAX(latest_at).
+            sqlx::query(
+                r#"
+                SELECT t.thread_id, t.subject, t.snippet,
+                       (SELECT json_group_array(DISTINCT p.value)
+                        FROM thread_summaries s, json_each(s.participants_json) p
+                        WHERE s.account_id = t.account_id
+                          AND s.thread_id = t.thread_id) AS participants_json,
+                       SUM(t.message_count) AS message_count,
+                       SUM(t.unread_count) AS unread_count,
+                       SUM(t.has_attachments) > 0 AS has_attachments,
+                       MAX(t.latest_at) AS latest_at
+                FROM thread_summaries t
+                WHERE t.account_id = ?1
+                GROUP BY t.thread_id
+                ORDER BY latest_at DESC
+                LIMIT ?2 OFFSET ?3
+                "#,
+            )
+            .bind(account_id.to_string())
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?
+        };
+        rows.into_iter().map(Self::row_to_thread_summary).collect()
+    }
+
+    /// INBOX threads of every account, newest first.
+    pub async fn list_inbox_thread_summaries(
+        &self,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<cove_core::MailThreadSummary>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM thread_summaries
+            WHERE folder_path = 'INBOX'
+            ORDER BY latest_at DESC
+            LIMIT ?1 OFFSET ?2
+            "#,
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(Self::row_to_thread_summary).collect()
+    }
+
     pub async fn list_mail_folders(
         &self,
         account_id: Uuid,
@@ -415,6 +729,158 @@ impl Storage {
         Ok(folders)
     }
 
+    /// Backfill cursors for `account_id` in the order folders are backfilled.
+    pub async fn list_backfill_cursors(
+        &self,
+        account_id: Uuid,
+    ) -> Result<Vec<cove_core::BackfillCursor>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT * FROM mail_backfill WHERE account_id = ?1 ORDER BY position ASC, folder_path ASC",
+        )
+        .bind(account_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let total: Option<i64> = row.try_get("total")?;
+                Ok(cove_core::BackfillCursor {
+                    account_id,
+                    folder_path: row.try_get("folder_path")?,
+                    position: row.try_get::<i64, _>("position")?.max(0) as u32,
+                    cursor: row.try_get("cursor")?,
+                    fetched: row.try_get::<i64, _>("fetched")?.max(0) as u64,
+                    total: total.map(|total| total.max(0) as u64),
+                    completed: row.try_get::<i64, _>("completed")? != 0,
+                    started_at: parse_datetime(
+                        &row.try_get::<String, _>("started_at")?,
+                        "mail_backfill.started_at",
+                    )?,
+                    updated_at: parse_datetime(
+                        &row.try_get::<String, _>("updated_at")?,
+                        "mail_backfill.updated_at",
+                    )?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn save_backfill_cursor(
+        &self,
+        cursor: &cove_core::BackfillCursor,
+    ) -> Result<(), StorageError> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO mail_backfill
+              (account_id, folder_path, position, cursor, fetched, total, completed,
+               started_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            "#,
+        )
+        .bind(cursor.account_id.to_string())
+        .bind(&cursor.folder_path)
+        .bind(i64::from(cursor.position))
+        .bind(&cursor.cursor)
+        .bind(cursor.fetched as i64)
+        .bind(cursor.total.map(|total| total as i64))
+        .bind(cursor.completed)
+        .bind(cursor.started_at.to_rfc3339())
+        .bind(cursor.updated_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn backfill_paused(&self, account_id: Uuid) -> Result<bool, StorageError> {
+        let row = sqlx::query("SELECT 1 FROM mail_backfill_paused WHERE account_id = ?1")
+            .bind(account_id.to_string())
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.is_some())
+    }
+
+    pub async fn set_backfill_paused(
+        &self,
+        account_id: Uuid,
+        paused: bool,
+    ) -> Result<(), StorageError> {
+        if paused {
+            sqlx::query(
+                "INSERT OR IGNORE INTO mail_backfill_paused (account_id, paused_at) VALUES (?1, ?2)",
+            )
+            .bind(account_id.to_string())
+            .bind(Utc::now().to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+        } else {
+            sqlx::query("DELETE FROM mail_backfill_paused WHERE account_id = ?1")
+                .bind(account_id.to_string())
+                .execute(&self.pool)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// UIDVALIDITY of an IMAP folder as of its last sync.
+    pub async fn imap_uid_validity(
+        &self,
+        account_id: Uuid,
+        folder_path: &str,
+    ) -> Result<Option<u32>, StorageError> {
+        let value: Option<i64> = sqlx::query_scalar(
+            "SELECT uid_validity FROM imap_folder_state WHERE account_id = ?1 AND folder_path = ?2",
+        )
+        .bind(account_id.to_string())
+        .bind(folder_path)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(value.and_then(|value| u32::try_from(value).ok()))
+    }
+
+    /// Record the UIDVALIDITY a sync of `folder_path` saw. When it differs from
+    /// the recorded one the cached messages carry UIDs of a mailbox that no
+    /// longer exists, so they are deleted and the folder is fetched afresh
+    /// (RFC 4549). Returns whether the cache was dropped.
+    pub async fn record_imap_uid_validity(
+        &self,
+        account_id: Uuid,
+        folder_path: &str,
+        uid_validity: u32,
+    ) -> Result<bool, StorageError> {
+        let previous = self.imap_uid_validity(account_id, folder_path).await?;
+        let reset = previous.is_some_and(|previous| previous != uid_validity);
+        if reset {
+            let ids: Vec<String> = sqlx::query_scalar(
+                "SELECT id FROM mail_messages WHERE account_id = ?1 AND folder_path = ?2",
+            )
+            .bind(account_id.to_string())
+            .bind(folder_path)
+            .fetch_all(&self.pool)
+            .await?;
+            let ids = ids
+                .iter()
+                .map(|id| parse_uuid(id, "mail_messages.id"))
+                .collect::<Result<Vec<_>, _>>()?;
+            self.delete_mail_messages(&ids).await?;
+        }
+        if previous != Some(uid_validity) {
+            sqlx::query(
+                r#"
+                INSERT OR REPLACE INTO imap_folder_state
+                  (account_id, folder_path, uid_validity, updated_at)
+                VALUES (?1, ?2, ?3, ?4)
+                "#,
+            )
+            .bind(account_id.to_string())
+            .bind(folder_path)
+            .bind(i64::from(uid_validity))
+            .bind(Utc::now().to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+        }
+        Ok(reset)
+    }
+
     pub async fn list_thread_messages(
         &self,
         account_id: Uuid,
@@ -434,53 +900,244 @@ impl Storage {
         rows.into_iter().map(Self::row_to_mail_message).collect()
     }
 
-    pub async fn list_unified_thread_messages(
+    /// One page of stored messages, newest first, optionally limited to
+    /// an account and a folder.
+    pub async fn list_messages_page(
         &self,
-        thread_id: &str,
+        account_id: Option<Uuid>,
+        folder_path: Option<&str>,
+        offset: i64,
+        limit: i64,
     ) -> Result<Vec<cove_core::MailMessage>, StorageError> {
         let rows = sqlx::query(
             r#"
             SELECT * FROM mail_messages
-            WHERE thread_id = ?1
-            ORDER BY received_at ASC
+            WHERE (?1 IS NULL OR account_id = ?1)
+              AND (?2 IS NULL OR folder_path = ?2)
+            ORDER BY received_at DESC, id ASC
+            LIMIT ?3 OFFSET ?4
             "#,
         )
-        .bind(thread_id)
+        .bind(account_id.map(|id| id.to_string()))
+        .bind(folder_path)
+        .bind(limit)
+        .bind(offset)
         .fetch_all(&self.pool)
         .await?;
         rows.into_iter().map(Self::row_to_mail_message).collect()
     }
 
-    pub async fn get_mail_message(
+    /// The stored messages among `ids`, newest first; unknown ids are
+    /// skipped.
+    pub async fn get_mail_messages(
         &self,
-        message_id: Uuid,
-    ) -> Result<Option<cove_core::MailMessage>, StorageError> {
-        let row = sqlx::query("SELECT * FROM mail_messages WHERE id = ?1")
-            .bind(message_id.to_string())
-            .fetch_optional(&self.pool)
-            .await?;
+        ids: &[Uuid],
+    ) -> Result<Vec<cove_core::MailMessage>, StorageError> {
+        let ids: Vec<String> = ids.iter().map(Uuid::to_string).collect();
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM mail_messages
+            WHERE id IN (SELECT value FROM json_each(?1))
+            ORDER BY received_at DESC
+            "#,
+        )
+        .bind(serde_json::to_string(&ids)?)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(Self::row_to_mail_message).collect()
+    }
 
-        row.map(Self::row_to_mail_message).transpose()
+    /// Every message of `account_id`, or those received since `since`,
+    /// oldest first.
+    pub async fn list_account_messages(
+        &self,
+        account_id: Uuid,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<cove_core::MailMessage>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM mail_messages
+            WHERE account_id = ?1 AND (?2 IS NULL OR received_at >= ?2)
+            ORDER BY received_at ASC
+            "#,
+        )
+        .bind(account_id.to_string())
+        .bind(since.map(|since| since.to_rfc3339()))
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(Self::row_to_mail_message).collect()
     }
 
-    pub async fn search_mail(
+    /// Messages of `account_id` received since `since` that any of
+    /// `addresses` sent or received, newest first.
+    pub async fn messages_with_participants(
         &self,
-        query_text: &str,
+        account_id: Uuid,
+        addresses: &[String],
+        since: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<cove_core::MailMessage>, StorageError> {
+        let addresses: Vec<String> = addresses
+            .iter()
+            .map(|address| address.trim().to_lowercase())
+            .filter(|address| !address.is_empty())
+            .collect();
+        let rows = sqlx::query(
+            r#"
+            SELECT m.* FROM mail_messages m
+            WHERE m.account_id = ?1
+              AND m.received_at >= ?2
+              AND EXISTS (
+                SELECT 1 FROM json_each(?3) p
+                WHERE EXISTS (
+                  SELECT 1 FROM json_each(m.from_json) a
+                  WHERE lower(json_extract(a.value, '$.address')) = p.value)
+                OR EXISTS (
+                  SELECT 1 FROM json_each(m.to_json) a
+                  WHERE lower(json_extract(a.value, '$.address')) = p.value)
+                OR EXISTS (
+                  SELECT 1 FROM json_each(m.cc_json) a
+                  WHERE lower(json_extract(a.value, '$.address')) = p.value))
+            ORDER BY m.received_at DESC
+            LIMIT ?4
+            "#,
+        )
+        .bind(account_id.to_string())
+        .bind(since.to_rfc3339())
+        .bind(serde_json::to_string(&addresses)?)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(Self::row_to_mail_message).collect()
+    }
+
+    pub async fn list_unified_thread_messages(
+        &self,
+        thread_id: &str,
+    ) -> Result<Vec<cove_core::MailMessage>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM mail_messages
+            WHERE thread_id = ?1
+            ORDER BY received_at ASC
+            "#,
+        )
+        .bind(thread_id)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(Self::row_to_mail_message).collect()
+    }
+
+    pub async fn get_mail_message(
+        &self,
+        message_id: Uuid,
+    ) -> Result<Option<cove_core::MailMessage>, StorageError> {
+        let row = sqlx::query("SELECT * FROM mail_messages WHERE id = ?1")
+            .bind(message_id.to_string())
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(Self::row_to_mail_message).transpose()
+    }
+
+    /// Other threads from the sender of `message`, one message per thread,
+    /// newest first.
+    pub async fn recent_threads_from_sender(
+        &self,
+        message: &cove_core::MailMessage,
         limit: usize,
-    ) -> Result<SearchResult<cove_core::MailMessage>, StorageError> {
-        let mut hits = Vec::new();
-        let ids = self.search.search(query_text, limit)?;
+    ) -> Result<Vec<cove_core::MailMessage>, StorageError> {
+        let Some(sender) = message.from.first() else {
+            return Ok(Vec::new());
+        };
+        let rows = sqlx::query(
+            r#"
+            SELECT m.* FROM mail_messages m
+            JOIN (
+              SELECT thread_id, MAX(received_at) AS latest
+              FROM mail_messages
+              WHERE account_id = ?1
+                AND lower(json_extract(from_json, '$[0].address')) = lower(?2)
+                AND thread_id != ?3
+              GROUP BY thread_id
+            ) t ON t.thread_id = m.thread_id AND t.latest = m.received_at
+            WHERE m.account_id = ?1
+            ORDER BY m.received_at DESC
+            LIMIT ?4
+            "#,
+        )
+        .bind(message.account_id.to_string())
+        .bind(&sender.address)
+        .bind(&message.thread_id)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
 
-        for id in &ids {
-            let row = sqlx::query("SELECT * FROM mail_messages WHERE id = ?1")
-                .bind(id)
-                .fetch_optional(&self.pool)
-                .await?;
-            if let Some(row) = row {
-                hits.push(Self::row_to_mail_message(row)?);
+        let mut seen = std::collections::HashSet::new();
+        let mut messages = Vec::with_capacity(rows.len());
+        for row in rows {
+            let message = Self::row_to_mail_message(row)?;
+            if seen.insert(message.thread_id.clone()) {
+                messages.push(message);
+            }
+        }
+        Ok(messages)
+    }
+
+    /// Threads from the same sender plus threads on the same subject or
+    /// mentioning the same entities, found through the search index.
+    pub async fn related_messages(
+        &self,
+        message: &cove_core::MailMessage,
+        limit: usize,
+    ) -> Result<cove_core::RelatedMessages, StorageError> {
+        let from_sender = self.recent_threads_from_sender(message, limit).await?;
+
+        let mut seen: std::collections::HashSet<(Uuid, String)> = from_sender
+            .iter()
+            .map(|other| (other.account_id, other.thread_id.clone()))
+            .collect();
+        seen.insert((message.account_id, message.thread_id.clone()));
+
+        let mut same_topic = Vec::new();
+        let body = message.body_text.as_deref().unwrap_or(&message.preview);
+        if let Some(query) = crate::analysis::related_query(&message.subject, body) {
+            for (id, _) in self.search.search_scored(&query, limit * 4)? {
+                if same_topic.len() >= limit {
+                    break;
+                }
+                let Some(other) = self.get_mail_message(parse_uuid(&id, "id")?).await? else {
+                    continue;
+                };
+                if seen.insert((other.account_id, other.thread_id.clone())) {
+                    same_topic.push(other);
+                }
             }
         }
 
+        Ok(cove_core::RelatedMessages {
+            from_sender,
+            same_topic,
+        })
+    }
+
+    pub async fn search_mail(
+        &self,
+        query_text: &str,
+        limit: usize,
+    ) -> Result<SearchResult<cove_core::MailMessage>, StorageError> {
+        let draft = cove_core::SearchQueryDraft::parse(query_text);
+        if draft.has_operators() {
+            return self.search_mail_with_operators(None, &draft, limit).await;
+        }
+
+        let mut hits: Vec<cove_core::MailMessage> = self
+            .search_mail_ranked(query_text, limit)
+            .await?
+            .into_iter()
+            .map(|(message, _)| message)
+            .collect();
+
         if hits.is_empty() {
             let like = format!("%{}%", query_text);
             let fallback = sqlx::query(
@@ -508,6 +1165,413 @@ impl Storage {
         })
     }
 
+    /// Messages matching every operator in `draft`, newest first. Each word
+    /// of a text field must match on its own.
+    pub async fn search_mail_with_operators(
+        &self,
+        account_id: Option<Uuid>,
+        draft: &cove_core::SearchQueryDraft,
+        limit: usize,
+    ) -> Result<SearchResult<cove_core::MailMessage>, StorageError> {
+        let words = |value: &str| {
+            serde_json::to_string(&value.split_whitespace().collect::<Vec<_>>())
+        };
+        let day_bound = |date: Option<chrono::NaiveDate>, hour, minute, second| {
+            date.and_then(|date| date.and_hms_opt(hour, minute, second))
+                .map(|moment| moment.and_utc().to_rfc3339())
+        };
+        let rows = sqlx::query(
+            r#"
+            SELECT m.* FROM mail_messages m
+            WHERE (?1 IS NULL OR m.account_id = ?1)
+              AND NOT EXISTS (
+                SELECT 1 FROM json_each(?2) w WHERE NOT EXISTS (
+                  SELECT 1 FROM json_each(m.from_json) a
+                  WHERE json_extract(a.value, '$.address') LIKE '%' || w.value || '%'
+                     OR coalesce(json_extract(a.value, '$.name'), '') LIKE '%' || w.value || '%'))
+              AND NOT EXISTS (
+                SELECT 1 FROM json_each(?3) w WHERE NOT EXISTS (
+                  SELECT 1 FROM json_each(m.to_json) a
+                  WHERE json_extract(a.value, '$.address') LIKE '%' || w.value || '%')
+                AND NOT EXISTS (
+                  -- members a group send expanded to, which may have been Bcc
+                  SELECT 1 FROM sent_group_expansions g, json_each(g.recipients_json) a
+                  WHERE g.message_id = coalesce(
+                      json_extract(m.headers_json, '$."Message-ID"'),
+                      json_extract(m.headers_json, '$."Message-Id"'))
+                    AND (json_extract(a.value, '$.address') LIKE '%' || w.value || '%'
+                      OR coalesce(json_extract(a.value, '$.name'), '') LIKE '%' || w.value || '%')))
+              AND NOT EXISTS (
+                SELECT 1 FROM json_each(?4) w WHERE m.subject NOT LIKE '%' || w.value || '%')
+              AND NOT EXISTS (
+                SELECT 1 FROM json_each(?5) w WHERE NOT EXISTS (
+                  SELECT 1 FROM json_each(m.labels_json) l
+                  WHERE l.value LIKE '%' || w.value || '%'))
+              AND NOT EXISTS (
+                SELECT 1 FROM json_each(?6) w
+                WHERE m.subject NOT LIKE '%' || w.value || '%'
+                  AND m.preview NOT LIKE '%' || w.value || '%')
+              AND (?7 = 0 OR json_array_length(m.attachments_json) > 0)
+              AND (?8 = 0 OR json_extract(m.flags_json, '$.seen') = 0)
+              AND (?9 = 0 OR m.pinned = 1)
+              AND (?10 IS NULL OR m.received_at >= ?10)
+              AND (?11 IS NULL OR m.received_at <= ?11)
+            ORDER BY m.received_at DESC
+            LIMIT ?12
+            "#,
+        )
+        .bind(account_id.map(|id| id.to_string()))
+        .bind(words(&draft.from)?)
+        .bind(words(&draft.to)?)
+        .bind(words(&draft.subject)?)
+        .bind(words(&draft.label)?)
+        .bind(words(&draft.text)?)
+        .bind(draft.has_attachment)
+        .bind(draft.unread)
+        .bind(draft.pinned)
+        .bind(day_bound(draft.after, 0, 0, 0))
+        .bind(day_bound(draft.before, 23, 59, 59))
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let items: Vec<cove_core::MailMessage> = rows
+            .into_iter()
+            .map(Self::row_to_mail_message)
+            .collect::<Result<_, _>>()?;
+        Ok(SearchResult {
+            total: items.len(),
+            items,
+        })
+    }
+
+    /// Everything the search bar can find for `query_text`: mail, plus
+    /// calendar events for `on:`/`attendee:` and tasks for `due:`.
+    pub async fn search_all(
+        &self,
+        query_text: &str,
+        limit: usize,
+    ) -> Result<cove_core::UnifiedSearchResults, StorageError> {
+        let draft = cove_core::SearchQueryDraft::parse(query_text);
+        let mut results = cove_core::UnifiedSearchResults::default();
+        if draft.targets_events() {
+            results.events = self.search_calendar_events(&draft, limit).await?;
+        }
+        if draft.targets_tasks() {
+            results.tasks = self.search_tasks(&draft, limit).await?;
+        }
+        let mail_only = cove_core::SearchQueryDraft {
+            on: None,
+            attendee: String::new(),
+            due: None,
+            ..draft.clone()
+        };
+        if !(draft.targets_events() || draft.targets_tasks()) {
+            results.messages = self.search_mail(query_text, limit).await?.items;
+        } else if mail_only.has_operators() {
+            results.messages = self
+                .search_mail_with_operators(None, &mail_only, limit)
+                .await?
+                .items;
+        }
+        Ok(results)
+    }
+
+    /// Events overlapping the `on:` day whose attendees match every
+    /// `attendee:` word; free text matches title, location or description.
+    pub async fn search_calendar_events(
+        &self,
+        draft: &cove_core::SearchQueryDraft,
+        limit: usize,
+    ) -> Result<Vec<CalendarEvent>, StorageError> {
+        let words = |value: &str| {
+            serde_json::to_string(&value.split_whitespace().collect::<Vec<_>>())
+        };
+        let (day_start, day_end) = day_bounds(draft.on);
+        let rows = sqlx::query(
+            r#"
+            SELECT e.* FROM calendar_events e
+            WHERE (?1 IS NULL OR e.ends_at >= ?1)
+              AND (?2 IS NULL OR e.starts_at <= ?2)
+              AND NOT EXISTS (
+                SELECT 1 FROM json_each(?3) w WHERE NOT EXISTS (
+                  SELECT 1 FROM json_each(e.attendees_json) a
+                  WHERE a.value LIKE '%' || w.value || '%'))
+              AND NOT EXISTS (
+                SELECT 1 FROM json_each(?4) w
+                WHERE e.title NOT LIKE '%' || w.value || '%'
+                  AND coalesce(e.location, '') NOT LIKE '%' || w.value || '%'
+                  AND coalesce(e.description, '') NOT LIKE '%' || w.value || '%')
+            ORDER BY e.starts_at ASC
+            LIMIT ?5
+            "#,
+        )
+        .bind(day_start)
+        .bind(day_end)
+        .bind(words(&draft.attendee)?)
+        .bind(words(&draft.text)?)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(Self::row_to_calendar_event).collect()
+    }
+
+    /// Tasks due on the `due:` day; free text matches title or notes.
+    pub async fn search_tasks(
+        &self,
+        draft: &cove_core::SearchQueryDraft,
+        limit: usize,
+    ) -> Result<Vec<ReminderTask>, StorageError> {
+        let words = |value: &str| {
+            serde_json::to_string(&value.split_whitespace().collect::<Vec<_>>())
+        };
+        let (day_start, day_end) = day_bounds(draft.due);
+        let rows = sqlx::query(
+            r#"
+            SELECT t.* FROM reminder_tasks t
+            WHERE (?1 IS NULL OR t.due_at >= ?1)
+              AND (?2 IS NULL OR t.due_at <= ?2)
+              AND NOT EXISTS (
+                SELECT 1 FROM json_each(?3) w
+                WHERE t.title NOT LIKE '%' || w.value || '%'
+                  AND coalesce(t.notes, '') NOT LIKE '%' || w.value || '%')
+            ORDER BY t.due_at ASC
+            LIMIT ?4
+            "#,
+        )
+        .bind(day_start)
+        .bind(day_end)
+        .bind(words(&draft.text)?)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(Self::row_to_task).collect()
+    }
+
+    /// Autocomplete for the word being typed at the end of `query`: operator
+    /// names, recent senders after `from:`, existing labels after `label:`.
+    pub async fn search_suggestions(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<cove_core::SearchSuggestion>, StorageError> {
+        let start = query
+            .rfind(char::is_whitespace)
+            .map(|at| at + 1)
+            .unwrap_or(0);
+        let (head, word) = query.split_at(start);
+        if word.is_empty() {
+            return Ok(Vec::new());
+        }
+        let suggest = |label: String, value: &str| cove_core::SearchSuggestion {
+            label,
+            completion: format!("{head}{value}"),
+        };
+
+        let Some((operator, partial)) = word.split_once(':') else {
+            let lower = word.to_lowercase();
+            return Ok(cove_core::SEARCH_OPERATORS
+                .iter()
+                .filter(|operator| operator.starts_with(&lower))
+                .map(|operator| suggest(operator.to_string(), operator))
+                .take(limit)
+                .collect());
+        };
+        let operator = operator.to_lowercase();
+        let partial = partial.to_lowercase();
+        let suggestions = match operator.as_str() {
+            "from" => self
+                .recent_senders(&partial, limit)
+                .await?
+                .into_iter()
+                .map(|sender| {
+                    let label = match &sender.name {
+                        Some(name) => format!("{name} <{}>", sender.address),
+                        None => sender.address.clone(),
+                    };
+                    suggest(label, &format!("from:{} ", sender.address))
+                })
+                .collect(),
+            "label" => self
+                .list_labels()
+                .await?
+                .into_iter()
+                .filter(|label| {
+                    !label.name.contains(char::is_whitespace)
+                        && label.name.to_lowercase().contains(&partial)
+                })
+                .take(limit)
+                .map(|label| suggest(label.name.clone(), &format!("label:{} ", label.name)))
+                .collect(),
+            "is" | "has" => {
+                let values: &[&str] = if operator == "is" {
+                    &["unread", "pinned"]
+                } else {
+                    &["attachment"]
+                };
+                values
+                    .iter()
+                    .filter(|value| value.starts_with(&partial))
+                    .map(|value| {
+                        let token = format!("{operator}:{value}");
+                        suggest(token.clone(), &format!("{token} "))
+                    })
+                    .collect()
+            }
+            "on" | "due" => {
+                let today = Utc::now().date_naive();
+                [("today", today), ("tomorrow", today + chrono::Days::new(1))]
+                    .into_iter()
+                    .map(|(name, day)| {
+                        let token = format!("{operator}:{}", day.format("%Y-%m-%d"));
+                        suggest(format!("{token} ({name})"), &format!("{token} "))
+                    })
+                    .filter(|suggestion| suggestion.label.contains(partial.as_str()))
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+        Ok(suggestions)
+    }
+
+    /// Senders whose address or name contains `partial`, most recently
+    /// heard from first.
+    pub async fn recent_senders(
+        &self,
+        partial: &str,
+        limit: usize,
+    ) -> Result<Vec<cove_core::MailAddress>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT json_extract(f.value, '$.address') AS address,
+                   max(json_extract(f.value, '$.name')) AS name,
+                   max(m.received_at) AS last_seen
+            FROM mail_messages m, json_each(m.from_json) f
+            WHERE json_extract(f.value, '$.address') LIKE ?1
+               OR json_extract(f.value, '$.name') LIKE ?1
+            GROUP BY lower(json_extract(f.value, '$.address'))
+            ORDER BY last_seen DESC
+            LIMIT ?2
+            "#,
+        )
+        .bind(format!("%{partial}%"))
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(cove_core::MailAddress {
+                    name: row.try_get("name")?,
+                    address: row.try_get("address")?,
+                })
+            })
+            .collect()
+    }
+
+    pub fn ranking_weights(&self) -> RankingWeights {
+        self.ranking
+            .read()
+            .map(|weights| *weights)
+            .unwrap_or_default()
+    }
+
+    /// Tune ranking at runtime; used by the search debug panel.
+    pub fn set_ranking_weights(&self, weights: RankingWeights) {
+        if let Ok(mut current) = self.ranking.write() {
+            *current = weights;
+        }
+    }
+
+    /// Full-text hits re-ranked by relevance, recency and sender affinity,
+    /// with each hit's score breakdown.
+    pub async fn search_mail_ranked(
+        &self,
+        query_text: &str,
+        limit: usize,
+    ) -> Result<Vec<(cove_core::MailMessage, RankBreakdown)>, StorageError> {
+        let candidates = self
+            .search
+            .search_scored(query_text, (limit * CANDIDATE_FACTOR).clamp(limit, MAX_CANDIDATES))?;
+        if candidates.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut hits = Vec::with_capacity(candidates.len());
+        for (id, bm25) in candidates {
+            let row = sqlx::query("SELECT * FROM mail_messages WHERE id = ?1")
+                .bind(&id)
+                .fetch_optional(&self.pool)
+                .await?;
+            if let Some(row) = row {
+                hits.push((Self::row_to_mail_message(row)?, bm25));
+            }
+        }
+
+        let senders: Vec<String> = hits
+            .iter()
+            .filter_map(|(message, _)| message.from.first())
+            .map(|sender| sender.address.to_lowercase())
+            .collect();
+        let replies = self.reply_counts_to(&senders).await?;
+
+        let weights = self.ranking_weights();
+        let best = hits.iter().map(|(_, bm25)| *bm25).fold(0.0_f32, f32::max);
+        let now = Utc::now();
+        let mut ranked: Vec<(cove_core::MailMessage, RankBreakdown)> = hits
+            .into_iter()
+            .map(|(message, bm25)| {
+                let sender_replies = message
+                    .from
+                    .first()
+                    .and_then(|sender| replies.get(&sender.address.to_lowercase()))
+                    .copied()
+                    .unwrap_or(0);
+                let breakdown =
+                    weights.score(bm25, best, message.received_at, now, sender_replies);
+                (message, breakdown)
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.1.score.total_cmp(&a.1.score));
+        ranked.truncate(limit);
+        Ok(ranked)
+    }
+
+    /// How many messages the user (any configured account) has sent to each
+    /// of `addresses`, keyed by lowercase address.
+    async fn reply_counts_to(
+        &self,
+        addresses: &[String],
+    ) -> Result<std::collections::HashMap<String, u32>, StorageError> {
+        if addresses.is_empty() {
+            return Ok(std::collections::HashMap::new());
+        }
+        let rows = sqlx::query(
+            r#"
+            SELECT lower(json_extract(r.value, '$.address')) AS address, COUNT(*) AS replies
+            FROM mail_messages m, json_each(m.to_json) r
+            WHERE lower(json_extract(m.from_json, '$[0].address'))
+                    IN (SELECT lower(email_address) FROM accounts)
+              AND lower(json_extract(r.value, '$.address'))
+                    IN (SELECT value FROM json_each(?1))
+            GROUP BY address
+            "#,
+        )
+        .bind(serde_json::to_string(addresses)?)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let address: String = row.try_get("address")?;
+                let replies: i64 = row.try_get("replies")?;
+                Ok((address, replies.max(0) as u32))
+            })
+            .collect()
+    }
+
     // -- attachment content ------------------------------------------------
 
     pub async fn save_attachment_content(
@@ -547,55 +1611,170 @@ impl Storage {
         Ok(row.map(|r| r.get::<Vec<u8>, _>("content")))
     }
 
-    // -- snooze / pin / send-later ------------------------------------------
+    // -- disk space ----------------------------------------------------------
 
-    pub async fn snooze_message(
+    /// Free space on the database's disk, local mail usage and its recent
+    /// growth, and the sync fidelity `policy` calls for.
+    pub async fn disk_space_report(
         &self,
-        message_id: Uuid,
-        until: DateTime<Utc>,
-    ) -> Result<(), StorageError> {
-        sqlx::query("UPDATE mail_messages SET snoozed_until = ?1 WHERE id = ?2")
-            .bind(until.to_rfc3339())
-            .bind(message_id.to_string())
-            .execute(&self.pool)
-            .await?;
-        Ok(())
-    }
+        policy: &cove_core::DiskSpacePolicy,
+    ) -> Result<cove_core::DiskSpaceReport, StorageError> {
+        let (available, total) = disk_capacity(&self.data_dir)?;
 
-    pub async fn unsnooze_message(&self, message_id: Uuid) -> Result<(), StorageError> {
-        sqlx::query("UPDATE mail_messages SET snoozed_until = NULL WHERE id = ?1")
-            .bind(message_id.to_string())
-            .execute(&self.pool)
-            .await?;
-        Ok(())
+        let body_bytes: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COALESCE(SUM(COALESCE(LENGTH(body_text), 0) + COALESCE(LENGTH(body_html), 0)), 0)
+            FROM mail_messages
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        let attachment_bytes: i64 = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(LENGTH(content)), 0) FROM mail_attachment_content",
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let week_ago = (Utc::now() - chrono::Duration::days(7)).to_rfc3339();
+        let recent_bytes: i64 = sqlx::query_scalar(
+            r#"
+            SELECT
+              COALESCE((SELECT SUM(COALESCE(LENGTH(body_text), 0) + COALESCE(LENGTH(body_html), 0))
+                        FROM mail_messages WHERE received_at >= ?1), 0)
+              + COALESCE((SELECT SUM(LENGTH(c.content))
+                          FROM mail_attachment_content c
+                          JOIN mail_messages m ON m.id = c.message_id
+                          WHERE m.received_at >= ?1), 0)
+            "#,
+        )
+        .bind(&week_ago)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(assess(
+            policy,
+            available,
+            total,
+            (body_bytes.max(0) + attachment_bytes.max(0)) as u64,
+            recent_bytes.max(0) as u64 / 7,
+        ))
     }
 
-    pub async fn set_pinned(
+    // -- attachment downloads ------------------------------------------------
+
+    pub async fn record_attachment_download(
         &self,
-        message_id: Uuid,
-        pinned: bool,
+        download: &cove_core::AttachmentDownload,
     ) -> Result<(), StorageError> {
-        sqlx::query("UPDATE mail_messages SET pinned = ?1 WHERE id = ?2")
-            .bind(pinned as i32)
+        sqlx::query(
+            r#"
+            INSERT INTO attachment_downloads
+              (id, account_id, message_id, attachment_id, file_name, path, saved_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            "#,
+        )
+        .bind(download.id.to_string())
+        .bind(download.account_id.to_string())
+        .bind(download.message_id.to_string())
+        .bind(download.attachment_id.to_string())
+        .bind(&download.file_name)
+        .bind(&download.path)
+        .bind(download.saved_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Newest first.
+    pub async fn recent_attachment_downloads(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<cove_core::AttachmentDownload>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT * FROM attachment_downloads ORDER BY saved_at DESC LIMIT ?1",
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let id: String = row.try_get("id")?;
+                let account_id: String = row.try_get("account_id")?;
+                let message_id: String = row.try_get("message_id")?;
+                let attachment_id: String = row.try_get("attachment_id")?;
+                let saved_at: String = row.try_get("saved_at")?;
+                Ok(cove_core::AttachmentDownload {
+                    id: parse_uuid(&id, "attachment_downloads.id")?,
+                    account_id: parse_uuid(&account_id, "attachment_downloads.account_id")?,
+                    message_id: parse_uuid(&message_id, "attachment_downloads.message_id")?,
+                    attachment_id: parse_uuid(
+                        &attachment_id,
+                        "attachment_downloads.attachment_id",
+                    )?,
+                    file_name: row.try_get("file_name")?,
+                    path: row.try_get("path")?,
+                    saved_at: parse_datetime(&saved_at, "attachment_downloads.saved_at")?,
+                })
+            })
+            .collect()
+    }
+
+    // -- snooze / pin / send-later ------------------------------------------
+
+    pub async fn snooze_message(
+        &self,
+        message_id: Uuid,
+        until: DateTime<Utc>,
+    ) -> Result<(), StorageError> {
+        sqlx::query("UPDATE mail_messages SET snoozed_until = ?1 WHERE id = ?2")
+            .bind(until.to_rfc3339())
             .bind(message_id.to_string())
             .execute(&self.pool)
             .await?;
         Ok(())
     }
 
-    pub async fn set_message_seen(
+    pub async fn unsnooze_message(&self, message_id: Uuid) -> Result<(), StorageError> {
+        sqlx::query("UPDATE mail_messages SET snoozed_until = NULL WHERE id = ?1")
+            .bind(message_id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_pinned(
         &self,
         message_id: Uuid,
-        seen: bool,
+        pinned: bool,
     ) -> Result<(), StorageError> {
-        sqlx::query("UPDATE mail_messages SET flags_seen = ?1 WHERE id = ?2")
-            .bind(seen as i32)
+        sqlx::query("UPDATE mail_messages SET pinned = ?1 WHERE id = ?2")
+            .bind(pinned as i32)
             .bind(message_id.to_string())
             .execute(&self.pool)
             .await?;
         Ok(())
     }
 
+    pub async fn set_message_seen(
+        &self,
+        message_id: Uuid,
+        seen: bool,
+    ) -> Result<(), StorageError> {
+        // Flags live in flags_json; there is no separate seen column.
+        sqlx::query(
+            r#"
+            UPDATE mail_messages SET flags_json = json_set(flags_json, '$.seen', json(?1))
+            WHERE id = ?2
+            "#,
+        )
+        .bind(if seen { "true" } else { "false" })
+        .bind(message_id.to_string())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
     pub async fn schedule_send(
         &self,
         message_id: Uuid,
@@ -620,254 +1799,2270 @@ impl Storage {
         rows.into_iter().map(Self::row_to_mail_message).collect()
     }
 
-    // -- unified inbox -------------------------------------------------------
+    // -- reminders -----------------------------------------------------------
 
-    pub async fn list_all_mail_messages(
+    /// Messages snoozed until after `after`, across all accounts.
+    pub async fn snoozed_mail_messages(
         &self,
-        limit: i64,
-        offset: i64,
-    ) -> Result<SearchResult<cove_core::MailMessage>, StorageError> {
-        let count_row = sqlx::query(
-            "SELECT COUNT(*) as cnt FROM mail_messages WHERE folder_path = 'INBOX'",
+        after: DateTime<Utc>,
+    ) -> Result<Vec<cove_core::MailMessage>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT * FROM mail_messages WHERE snoozed_until > ?1 ORDER BY snoozed_until ASC",
         )
-        .fetch_one(&self.pool)
+        .bind(after.to_rfc3339())
+        .fetch_all(&self.pool)
         .await?;
-        let total: i64 = count_row.try_get("cnt")?;
+        rows.into_iter().map(Self::row_to_mail_message).collect()
+    }
 
+    /// Needs-reply items snoozed until after `after`, with their messages.
+    pub async fn snoozed_follow_ups(
+        &self,
+        after: DateTime<Utc>,
+    ) -> Result<Vec<(DateTime<Utc>, cove_core::MailMessage)>, StorageError> {
         let rows = sqlx::query(
             r#"
-            SELECT * FROM mail_messages
-            WHERE folder_path = 'INBOX'
-            ORDER BY pinned DESC, received_at DESC
-            LIMIT ?1 OFFSET ?2
+            SELECT m.*, q.snoozed_until AS q_snoozed_until
+            FROM reply_queue q
+            JOIN mail_messages m ON m.id = q.message_id
+            WHERE q.status = ?1 AND q.snoozed_until > ?2
+            ORDER BY q.snoozed_until ASC
             "#,
         )
-        .bind(limit)
-        .bind(offset)
+        .bind(serde_json::to_string(&cove_core::ReplyQueueStatus::Snoozed)?)
+        .bind(after.to_rfc3339())
         .fetch_all(&self.pool)
         .await?;
 
-        let items: Vec<_> = rows.into_iter().map(Self::row_to_mail_message).collect::<Result<_, _>>()?;
-        Ok(SearchResult {
-            total: total as usize,
-            items,
-        })
+        rows.into_iter()
+            .map(|row| {
+                let until = parse_datetime(
+                    &row.try_get::<String, _>("q_snoozed_until")?,
+                    "reply_queue.snoozed_until",
+                )?;
+                Ok((until, Self::row_to_mail_message(row)?))
+            })
+            .collect()
     }
 
-    // -- signatures ----------------------------------------------------------
+    /// Events starting in `[from, to]`, across all accounts.
+    pub async fn upcoming_calendar_events(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<CalendarEvent>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM calendar_events
+            WHERE starts_at >= ?1 AND starts_at <= ?2
+            ORDER BY starts_at ASC
+            "#,
+        )
+        .bind(from.to_rfc3339())
+        .bind(to.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(Self::row_to_calendar_event).collect()
+    }
 
-    pub async fn upsert_signature(
+    /// Open tasks due after `after`, across all accounts.
+    pub async fn open_tasks_due_after(
         &self,
-        sig: &cove_core::EmailSignature,
+        after: DateTime<Utc>,
+    ) -> Result<Vec<ReminderTask>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM reminder_tasks
+            WHERE completed_at IS NULL AND due_at > ?1
+            ORDER BY due_at ASC
+            "#,
+        )
+        .bind(after.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(Self::row_to_task).collect()
+    }
+
+    /// Swap the unfired reminders still in the future for `planned`. Due
+    /// ones stay until a shell handles them or they expire, and a planned
+    /// reminder that already fired is kept as fired, so none fires twice.
+    pub async fn replace_pending_reminders(
+        &self,
+        now: DateTime<Utc>,
+        planned: &[cove_core::Reminder],
     ) -> Result<(), StorageError> {
+        let now = now.to_rfc3339();
+        let mut tx = self.pool.begin().await?;
         sqlx::query(
             r#"
-            INSERT OR REPLACE INTO email_signatures (id, account_id, name, body_html, body_text, is_default)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            DELETE FROM reminders
+            WHERE fired_at IS NULL
+              AND (trigger_at > ?1 OR (expires_at IS NOT NULL AND expires_at <= ?1))
             "#,
         )
-        .bind(sig.id.to_string())
-        .bind(sig.account_id.map(|id| id.to_string()))
-        .bind(&sig.name)
-        .bind(&sig.body_html)
-        .bind(&sig.body_text)
-        .bind(sig.is_default as i32)
-        .execute(&self.pool)
+        .bind(&now)
+        .execute(&mut *tx)
         .await?;
+        for reminder in planned {
+            sqlx::query(
+                r#"
+                INSERT INTO reminders
+                  (id, kind, target_id, account_id, trigger_at, expires_at, lead_minutes,
+                   title, detail, fired_at, created_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, NULL, ?10)
+                ON CONFLICT(kind, target_id, trigger_at) DO NOTHING
+                "#,
+            )
+            .bind(reminder.id.to_string())
+            .bind(serde_json::to_string(&reminder.kind)?)
+            .bind(reminder.target_id.to_string())
+            .bind(reminder.account_id.to_string())
+            .bind(reminder.trigger_at.to_rfc3339())
+            .bind(reminder.expires_at.map(|at| at.to_rfc3339()))
+            .bind(reminder.lead_minutes)
+            .bind(&reminder.title)
+            .bind(&reminder.detail)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
         Ok(())
     }
 
-    pub async fn list_signatures(
+    /// Unfired reminders due by `now` that have not expired, oldest first.
+    pub async fn due_reminders(
         &self,
-        account_id: Option<Uuid>,
-    ) -> Result<Vec<cove_core::EmailSignature>, StorageError> {
+        now: DateTime<Utc>,
+    ) -> Result<Vec<cove_core::Reminder>, StorageError> {
+        let now = now.to_rfc3339();
         let rows = sqlx::query(
-            "SELECT * FROM email_signatures WHERE account_id IS ?1 OR account_id IS NULL ORDER BY is_default DESC, name",
+            r#"
+            SELECT * FROM reminders
+            WHERE fired_at IS NULL AND trigger_at <= ?1
+              AND (expires_at IS NULL OR expires_at > ?1)
+            ORDER BY trigger_at ASC
+            "#,
         )
-        .bind(account_id.map(|id| id.to_string()))
+        .bind(&now)
         .fetch_all(&self.pool)
         .await?;
 
         rows.into_iter()
             .map(|row| {
-                let id: String = row.try_get("id")?;
-                let acct: Option<String> = row.try_get("account_id")?;
-                Ok(cove_core::EmailSignature {
-                    id: parse_uuid(&id, "email_signatures.id")?,
-                    account_id: acct.as_deref().map(|v| parse_uuid(v, "email_signatures.account_id")).transpose()?,
-                    name: row.try_get("name")?,
-                    body_html: row.try_get("body_html")?,
-                    body_text: row.try_get("body_text")?,
-                    is_default: row.try_get::<i32, _>("is_default")? != 0,
+                let expires_at: Option<String> = row.try_get("expires_at")?;
+                Ok(cove_core::Reminder {
+                    id: parse_uuid(&row.try_get::<String, _>("id")?, "reminders.id")?,
+                    kind: parse_json(&row.try_get::<String, _>("kind")?, "reminders.kind")?,
+                    target_id: parse_uuid(
+                        &row.try_get::<String, _>("target_id")?,
+                        "reminders.target_id",
+                    )?,
+                    account_id: parse_uuid(
+                        &row.try_get::<String, _>("account_id")?,
+                        "reminders.account_id",
+                    )?,
+                    trigger_at: parse_datetime(
+                        &row.try_get::<String, _>("trigger_at")?,
+                        "reminders.trigger_at",
+                    )?,
+                    expires_at: expires_at
+                        .as_deref()
+                        .map(|raw| parse_datetime(raw, "reminders.expires_at"))
+                        .transpose()?,
+                    lead_minutes: row.try_get("lead_minutes")?,
+                    title: row.try_get("title")?,
+                    detail: row.try_get("detail")?,
                 })
             })
             .collect()
     }
 
-    pub async fn delete_signature(&self, id: Uuid) -> Result<(), StorageError> {
-        sqlx::query("DELETE FROM email_signatures WHERE id = ?1")
+    pub async fn mark_reminder_fired(
+        &self,
+        id: Uuid,
+        fired_at: DateTime<Utc>,
+    ) -> Result<(), StorageError> {
+        sqlx::query("UPDATE reminders SET fired_at = ?1 WHERE id = ?2")
+            .bind(fired_at.to_rfc3339())
             .bind(id.to_string())
             .execute(&self.pool)
             .await?;
         Ok(())
     }
 
-    // -- templates -----------------------------------------------------------
+    // -- watched threads -----------------------------------------------------
 
-    pub async fn upsert_template(
+    pub async fn set_thread_watched(
         &self,
-        tpl: &cove_core::EmailTemplate,
+        account_id: Uuid,
+        thread_id: &str,
+        watched: bool,
     ) -> Result<(), StorageError> {
+        if watched {
+            sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO watched_threads (account_id, thread_id, created_at)
+                VALUES (?1, ?2, ?3)
+                "#,
+            )
+            .bind(account_id.to_string())
+            .bind(thread_id)
+            .bind(Utc::now().to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+        } else {
+            sqlx::query("DELETE FROM watched_threads WHERE account_id = ?1 AND thread_id = ?2")
+                .bind(account_id.to_string())
+                .bind(thread_id)
+                .execute(&self.pool)
+                .await?;
+        }
+        Ok(())
+    }
+
+    pub async fn is_thread_watched(
+        &self,
+        account_id: Uuid,
+        thread_id: &str,
+    ) -> Result<bool, StorageError> {
+        let row = sqlx::query(
+            "SELECT 1 FROM watched_threads WHERE account_id = ?1 AND thread_id = ?2",
+        )
+        .bind(account_id.to_string())
+        .bind(thread_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.is_some())
+    }
+
+    /// Unread messages in watched threads that arrived after the watch began.
+    pub async fn list_watched_thread_messages(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<cove_core::MailMessage>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT m.* FROM mail_messages m
+            JOIN watched_threads w
+              ON w.account_id = m.account_id AND w.thread_id = m.thread_id
+            WHERE json_extract(m.flags_json, '$.seen') = 0 AND m.received_at >= w.created_at
+            ORDER BY m.received_at DESC
+            LIMIT ?1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(Self::row_to_mail_message).collect()
+    }
+
+    // -- sender mutes --------------------------------------------------------
+
+    /// Mute `mute.email` on `mute.account_id`, replacing any earlier mute of
+    /// the same sender there.
+    pub async fn mute_sender(&self, mute: &cove_core::SenderMute) -> Result<(), StorageError> {
         sqlx::query(
             r#"
-            INSERT OR REPLACE INTO email_templates (id, name, subject, body_html, body_text)
+            INSERT INTO sender_mutes (account_id, email, muted_until, auto_archive, created_at)
             VALUES (?1, ?2, ?3, ?4, ?5)
+            ON CONFLICT(account_id, email) DO UPDATE SET
+              muted_until = excluded.muted_until,
+              auto_archive = excluded.auto_archive,
+              created_at = excluded.created_at
+            "#,
+        )
+        .bind(mute.account_id.to_string())
+        .bind(mute.email.to_lowercase())
+        .bind(mute.muted_until.map(|until| until.to_rfc3339()))
+        .bind(mute.auto_archive)
+        .bind(mute.created_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn unmute_sender(&self, account_id: Uuid, email: &str) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM sender_mutes WHERE account_id = ?1 AND email = ?2")
+            .bind(account_id.to_string())
+            .bind(email.to_lowercase())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Muted senders on every account, forever mutes first and the rest by
+    /// soonest expiry.
+    pub async fn list_sender_mutes(&self) -> Result<Vec<cove_core::SenderMute>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM sender_mutes
+            ORDER BY muted_until IS NOT NULL, julianday(muted_until) ASC, email ASC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let account_id: String = row.try_get("account_id")?;
+                let muted_until: Option<String> = row.try_get("muted_until")?;
+                let created_at: String = row.try_get("created_at")?;
+                Ok(cove_core::SenderMute {
+                    account_id: parse_uuid(&account_id, "sender_mutes.account_id")?,
+                    email: row.try_get("email")?,
+                    muted_until: muted_until
+                        .map(|until| parse_datetime(&until, "sender_mutes.muted_until"))
+                        .transpose()?,
+                    auto_archive: row.try_get("auto_archive")?,
+                    created_at: parse_datetime(&created_at, "sender_mutes.created_at")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Drop mutes that ended before `now`. Returns how many were lifted.
+    pub async fn lift_expired_mutes(&self, now: DateTime<Utc>) -> Result<u64, StorageError> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM sender_mutes
+            WHERE muted_until IS NOT NULL AND julianday(muted_until) <= julianday(?1)
+            "#,
+        )
+        .bind(now.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Messages in `folder_path` from senders muted on this account with
+    /// auto-archive that arrived while the mute was in force. Timestamps are
+    /// compared through julianday() since stored offsets and fractional
+    /// seconds differ.
+    pub async fn list_auto_archive_candidates(
+        &self,
+        account_id: Uuid,
+        folder_path: &str,
+        limit: i64,
+    ) -> Result<Vec<cove_core::MailMessage>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT m.* FROM mail_messages m
+            JOIN sender_mutes s
+              ON s.account_id = m.account_id
+              AND s.email = lower(json_extract(m.from_json, '$[0].address'))
+            WHERE m.account_id = ?1 AND m.folder_path = ?2
+              AND s.auto_archive = 1 AND julianday(m.received_at) >= julianday(s.created_at)
+            ORDER BY m.received_at ASC
+            LIMIT ?3
+            "#,
+        )
+        .bind(account_id.to_string())
+        .bind(folder_path)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(Self::row_to_mail_message).collect()
+    }
+
+    // -- reply triage --------------------------------------------------------
+
+    /// Record a reply candidate. Existing rows keep their status so a
+    /// re-sync never resurrects a dismissed item.
+    pub async fn upsert_reply_candidate(
+        &self,
+        message: &cove_core::MailMessage,
+        score: u8,
+        reasons: &[String],
+    ) -> Result<(), StorageError> {
+        sqlx::query(
+            r#"
+            INSERT INTO reply_queue
+              (message_id, account_id, thread_id, score, reasons_json, status, detected_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            ON CONFLICT(message_id) DO UPDATE SET
+              score = excluded.score,
+              reasons_json = excluded.reasons_json
+            "#,
+        )
+        .bind(message.id.to_string())
+        .bind(message.account_id.to_string())
+        .bind(&message.thread_id)
+        .bind(score as i64)
+        .bind(serde_json::to_string(reasons)?)
+        .bind(serde_json::to_string(&cove_core::ReplyQueueStatus::Open)?)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Open (or snooze-expired) items with their messages, oldest first.
+    pub async fn list_reply_queue(
+        &self,
+    ) -> Result<Vec<(cove_core::ReplyQueueItem, cove_core::MailMessage)>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT m.*,
+                   q.message_id AS q_message_id, q.account_id AS q_account_id,
+                   q.thread_id AS q_thread_id, q.score AS q_score,
+                   q.reasons_json AS q_reasons_json, q.ai_confirmed AS q_ai_confirmed,
+                   q.status AS q_status, q.snoozed_until AS q_snoozed_until,
+                   q.detected_at AS q_detected_at, q.resolved_at AS q_resolved_at
+            FROM reply_queue q
+            JOIN mail_messages m ON m.id = q.message_id
+            WHERE q.status = ?1 OR (q.status = ?2 AND q.snoozed_until <= ?3)
+            ORDER BY m.received_at ASC
+            "#,
+        )
+        .bind(serde_json::to_string(&cove_core::ReplyQueueStatus::Open)?)
+        .bind(serde_json::to_string(&cove_core::ReplyQueueStatus::Snoozed)?)
+        .bind(Utc::now().to_rfc3339())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let message_id: String = row.try_get("q_message_id")?;
+                let account_id: String = row.try_get("q_account_id")?;
+                let ai_confirmed: Option<i64> = row.try_get("q_ai_confirmed")?;
+                let snoozed: Option<String> = row.try_get("q_snoozed_until")?;
+                let resolved: Option<String> = row.try_get("q_resolved_at")?;
+                let item = cove_core::ReplyQueueItem {
+                    message_id: parse_uuid(&message_id, "reply_queue.message_id")?,
+                    account_id: parse_uuid(&account_id, "reply_queue.account_id")?,
+                    thread_id: row.try_get("q_thread_id")?,
+                    score: row.try_get::<i64, _>("q_score")?.clamp(0, 100) as u8,
+                    reasons: parse_json(&row.try_get::<String, _>("q_reasons_json")?, "reply_queue.reasons_json")?,
+                    ai_confirmed: ai_confirmed.map(|value| value != 0),
+                    status: parse_json(&row.try_get::<String, _>("q_status")?, "reply_queue.status")?,
+                    snoozed_until: snoozed.as_deref().map(|v| parse_datetime(v, "reply_queue.snoozed_until")).transpose()?,
+                    detected_at: parse_datetime(&row.try_get::<String, _>("q_detected_at")?, "reply_queue.detected_at")?,
+                    resolved_at: resolved.as_deref().map(|v| parse_datetime(v, "reply_queue.resolved_at")).transpose()?,
+                };
+                Ok((item, Self::row_to_mail_message(row)?))
+            })
+            .collect()
+    }
+
+    pub async fn set_reply_status(
+        &self,
+        message_id: Uuid,
+        status: cove_core::ReplyQueueStatus,
+        snoozed_until: Option<DateTime<Utc>>,
+    ) -> Result<(), StorageError> {
+        let resolved_at = matches!(
+            status,
+            cove_core::ReplyQueueStatus::Dismissed | cove_core::ReplyQueueStatus::Replied
+        )
+        .then(|| Utc::now().to_rfc3339());
+
+        sqlx::query(
+            "UPDATE reply_queue SET status = ?1, snoozed_until = ?2, resolved_at = ?3 WHERE message_id = ?4",
+        )
+        .bind(serde_json::to_string(&status)?)
+        .bind(snoozed_until.map(|t| t.to_rfc3339()))
+        .bind(resolved_at)
+        .bind(message_id.to_string())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn set_reply_ai_verdict(
+        &self,
+        message_id: Uuid,
+        needs_reply: bool,
+    ) -> Result<(), StorageError> {
+        sqlx::query("UPDATE reply_queue SET ai_confirmed = ?1 WHERE message_id = ?2")
+            .bind(needs_reply as i32)
+            .bind(message_id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Close queue items whose thread has a later message sent from
+    /// `own_address`. Returns the number of items resolved.
+    pub async fn resolve_replied_threads(
+        &self,
+        account_id: Uuid,
+        own_address: &str,
+    ) -> Result<u64, StorageError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE reply_queue
+            SET status = ?1, resolved_at = (
+              SELECT MIN(r.received_at) FROM mail_messages r
+              WHERE r.account_id = reply_queue.account_id
+                AND r.thread_id = reply_queue.thread_id
+                AND r.received_at > (SELECT received_at FROM mail_messages WHERE id = reply_queue.message_id)
+                AND instr(lower(r.from_json), ?4) > 0
+            )
+            WHERE account_id = ?2
+              AND status IN (?3, ?5)
+              AND EXISTS (
+                SELECT 1 FROM mail_messages r
+                WHERE r.account_id = reply_queue.account_id
+                  AND r.thread_id = reply_queue.thread_id
+                  AND r.received_at > (SELECT received_at FROM mail_messages WHERE id = reply_queue.message_id)
+                  AND instr(lower(r.from_json), ?4) > 0
+              )
+            "#,
+        )
+        .bind(serde_json::to_string(&cove_core::ReplyQueueStatus::Replied)?)
+        .bind(account_id.to_string())
+        .bind(serde_json::to_string(&cove_core::ReplyQueueStatus::Open)?)
+        .bind(format!("\"{}\"", own_address.to_lowercase()))
+        .bind(serde_json::to_string(&cove_core::ReplyQueueStatus::Snoozed)?)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn reply_queue_stats(&self) -> Result<cove_core::ReplyQueueStats, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT q.status, q.resolved_at, m.received_at
+            FROM reply_queue q
+            JOIN mail_messages m ON m.id = q.message_id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let now = Utc::now();
+        let mut stats = cove_core::ReplyQueueStats::default();
+        let mut reply_hours = Vec::new();
+        for row in rows {
+            let status: cove_core::ReplyQueueStatus =
+                parse_json(&row.try_get::<String, _>("status")?, "reply_queue.status")?;
+            let received = parse_datetime(&row.try_get::<String, _>("received_at")?, "mail_messages.received_at")?;
+            let resolved: Option<String> = row.try_get("resolved_at")?;
+            let hours_since = |at: DateTime<Utc>| (at - received).num_minutes() as f64 / 60.0;
+
+            match status {
+                cove_core::ReplyQueueStatus::Open => {
+                    stats.open += 1;
+                    let age = hours_since(now);
+                    stats.oldest_open_hours = Some(stats.oldest_open_hours.map_or(age, |h| h.max(age)));
+                }
+                cove_core::ReplyQueueStatus::Snoozed => stats.snoozed += 1,
+                cove_core::ReplyQueueStatus::Dismissed => stats.dismissed += 1,
+                cove_core::ReplyQueueStatus::Replied => {
+                    stats.replied += 1;
+                    if let Some(resolved) = resolved {
+                        reply_hours.push(hours_since(parse_datetime(&resolved, "reply_queue.resolved_at")?));
+                    }
+                }
+            }
+        }
+        if !reply_hours.is_empty() {
+            stats.avg_reply_hours = Some(reply_hours.iter().sum::<f64>() / reply_hours.len() as f64);
+        }
+
+        Ok(stats)
+    }
+
+    // -- analytics -----------------------------------------------------------
+
+    /// Volume, response times and top correspondents for `[since, until)`.
+    /// Mail from an account's own address counts as sent; response times
+    /// come from reply-queue items answered in the period.
+    pub async fn mail_analytics(
+        &self,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        top_correspondents: usize,
+    ) -> Result<cove_core::MailAnalytics, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT m.from_json, m.to_json, m.cc_json, m.received_at, a.email_address
+            FROM mail_messages m
+            JOIN accounts a ON a.id = m.account_id
+            WHERE m.received_at >= ?1 AND m.received_at < ?2
+            "#,
+        )
+        .bind(since.to_rfc3339())
+        .bind(until.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut tally = Tally::default();
+        for row in rows {
+            let from: Vec<cove_core::MailAddress> =
+                parse_json(&row.try_get::<String, _>("from_json")?, "mail_messages.from_json")?;
+            let to: Vec<cove_core::MailAddress> =
+                parse_json(&row.try_get::<String, _>("to_json")?, "mail_messages.to_json")?;
+            let cc: Vec<cove_core::MailAddress> =
+                parse_json(&row.try_get::<String, _>("cc_json")?, "mail_messages.cc_json")?;
+            let received_at = parse_datetime(
+                &row.try_get::<String, _>("received_at")?,
+                "mail_messages.received_at",
+            )?;
+            let me: String = row.try_get("email_address")?;
+            tally.add(
+                received_at.with_timezone(&chrono::Local).date_naive(),
+                from.first(),
+                to.iter().chain(&cc),
+                &me,
+            );
+        }
+
+        let rows = sqlx::query(
+            r#"
+            SELECT q.status, q.resolved_at, m.received_at
+            FROM reply_queue q
+            JOIN mail_messages m ON m.id = q.message_id
+            WHERE q.resolved_at >= ?1 AND q.resolved_at < ?2
+            "#,
+        )
+        .bind(since.to_rfc3339())
+        .bind(until.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut response_hours = Vec::new();
+        for row in rows {
+            let status: cove_core::ReplyQueueStatus =
+                parse_json(&row.try_get::<String, _>("status")?, "reply_queue.status")?;
+            if status != cove_core::ReplyQueueStatus::Replied {
+                continue;
+            }
+            let received = parse_datetime(
+                &row.try_get::<String, _>("received_at")?,
+                "mail_messages.received_at",
+            )?;
+            let resolved = parse_datetime(
+                &row.try_get::<String, _>("resolved_at")?,
+                "reply_queue.resolved_at",
+            )?;
+            response_hours.push((resolved - received).num_minutes() as f64 / 60.0);
+        }
+
+        Ok(tally.finish(since, until, response_hours, top_correspondents))
+    }
+
+    /// When the last weekly report went out, if ever.
+    pub async fn last_weekly_report(&self) -> Result<Option<DateTime<Utc>>, StorageError> {
+        Ok(self
+            .list_audit_entries(None, Some(crate::WEEKLY_REPORT_AUDIT_KIND), 1)
+            .await?
+            .first()
+            .map(|entry| entry.created_at))
+    }
+
+    // -- unified inbox -------------------------------------------------------
+
+    pub async fn list_all_mail_messages(
+        &self,
+        limit: i64,
+        offset: i64,
+    ) -> Result<SearchResult<cove_core::MailMessage>, StorageError> {
+        let count_row = sqlx::query(
+            "SELECT COUNT(*) as cnt FROM mail_messages WHERE folder_path = 'INBOX'",
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        let total: i64 = count_row.try_get("cnt")?;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM mail_messages
+            WHERE folder_path = 'INBOX'
+            ORDER BY pinned DESC, received_at DESC
+            LIMIT ?1 OFFSET ?2
+            "#,
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let items: Vec<_> = rows.into_iter().map(Self::row_to_mail_message).collect::<Result<_, _>>()?;
+        Ok(SearchResult {
+            total: total as usize,
+            items,
+        })
+    }
+
+    // -- signatures ----------------------------------------------------------
+
+    pub async fn upsert_signature(
+        &self,
+        sig: &cove_core::EmailSignature,
+    ) -> Result<(), StorageError> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO email_signatures (id, account_id, name, body_html, body_text, is_default)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+        )
+        .bind(sig.id.to_string())
+        .bind(sig.account_id.map(|id| id.to_string()))
+        .bind(&sig.name)
+        .bind(&sig.body_html)
+        .bind(&sig.body_text)
+        .bind(sig.is_default as i32)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list_signatures(
+        &self,
+        account_id: Option<Uuid>,
+    ) -> Result<Vec<cove_core::EmailSignature>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT * FROM email_signatures WHERE account_id IS ?1 OR account_id IS NULL ORDER BY is_default DESC, name",
+        )
+        .bind(account_id.map(|id| id.to_string()))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let id: String = row.try_get("id")?;
+                let acct: Option<String> = row.try_get("account_id")?;
+                Ok(cove_core::EmailSignature {
+                    id: parse_uuid(&id, "email_signatures.id")?,
+                    account_id: acct.as_deref().map(|v| parse_uuid(v, "email_signatures.account_id")).transpose()?,
+                    name: row.try_get("name")?,
+                    body_html: row.try_get("body_html")?,
+                    body_text: row.try_get("body_text")?,
+                    is_default: row.try_get::<i32, _>("is_default")? != 0,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn delete_signature(&self, id: Uuid) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM email_signatures WHERE id = ?1")
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // -- templates -----------------------------------------------------------
+
+    pub async fn upsert_template(
+        &self,
+        tpl: &cove_core::EmailTemplate,
+    ) -> Result<(), StorageError> {
+        Self::write_template(&self.pool, tpl).await
+    }
+
+    async fn write_template<'e, E>(
+        executor: E,
+        tpl: &cove_core::EmailTemplate,
+    ) -> Result<(), StorageError>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+    {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO email_templates (id, name, subject, body_html, body_text)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            "#,
+        )
+        .bind(tpl.id.to_string())
+        .bind(&tpl.name)
+        .bind(&tpl.subject)
+        .bind(&tpl.body_html)
+        .bind(&tpl.body_text)
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list_templates(&self) -> Result<Vec<cove_core::EmailTemplate>, StorageError> {
+        let rows = sqlx::query("SELECT * FROM email_templates ORDER BY name")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let id: String = row.try_get("id")?;
+                Ok(cove_core::EmailTemplate {
+                    id: parse_uuid(&id, "email_templates.id")?,
+                    name: row.try_get("name")?,
+                    subject: row.try_get("subject")?,
+                    body_html: row.try_get("body_html")?,
+                    body_text: row.try_get("body_text")?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn delete_template(&self, id: Uuid) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM email_templates WHERE id = ?1")
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // -- snippets ------------------------------------------------------------
+
+    /// Insert or update a snippet. Abbreviations are unique, so saving one
+    /// that is already taken replaces the older snippet.
+    pub async fn upsert_snippet(
+        &self,
+        snippet: &cove_core::TextSnippet,
+    ) -> Result<(), StorageError> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM email_snippets WHERE abbreviation = ?1 AND id != ?2")
+            .bind(&snippet.abbreviation)
+            .bind(snippet.id.to_string())
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO email_snippets (id, abbreviation, body, updated_at)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+        )
+        .bind(snippet.id.to_string())
+        .bind(&snippet.abbreviation)
+        .bind(&snippet.body)
+        .bind(snippet.updated_at.to_rfc3339())
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn list_snippets(&self) -> Result<Vec<cove_core::TextSnippet>, StorageError> {
+        let rows = sqlx::query("SELECT * FROM email_snippets ORDER BY abbreviation")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let id: String = row.try_get("id")?;
+                let updated_at: String = row.try_get("updated_at")?;
+                Ok(cove_core::TextSnippet {
+                    id: parse_uuid(&id, "email_snippets.id")?,
+                    abbreviation: row.try_get("abbreviation")?,
+                    body: row.try_get("body")?,
+                    updated_at: parse_datetime(&updated_at, "email_snippets.updated_at")?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn delete_snippet(&self, id: Uuid) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM email_snippets WHERE id = ?1")
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // -- recovery journal ----------------------------------------------------
+
+    /// Replace `client`'s recovery snapshot. Kept in the database so it is
+    /// covered by SQLCipher like the drafts it protects.
+    pub async fn save_recovery_journal(
+        &self,
+        client: &str,
+        payload: &serde_json::Value,
+    ) -> Result<(), StorageError> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO ui_recovery_journal (client, payload_json, saved_at)
+            VALUES (?1, ?2, ?3)
+            "#,
+        )
+        .bind(client)
+        .bind(serde_json::to_string(payload)?)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn load_recovery_journal(
+        &self,
+        client: &str,
+    ) -> Result<Option<serde_json::Value>, StorageError> {
+        let raw: Option<String> =
+            sqlx::query_scalar("SELECT payload_json FROM ui_recovery_journal WHERE client = ?1")
+                .bind(client)
+                .fetch_optional(&self.pool)
+                .await?;
+        raw.map(|raw| parse_json(&raw, "ui_recovery_journal.payload_json"))
+            .transpose()
+    }
+
+    pub async fn clear_recovery_journal(&self, client: &str) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM ui_recovery_journal WHERE client = ?1")
+            .bind(client)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // -- plus-address aliases ------------------------------------------------
+
+    pub async fn insert_plus_alias(&self, alias: &cove_core::PlusAlias) -> Result<(), StorageError> {
+        sqlx::query(
+            r#"
+            INSERT INTO plus_aliases (id, account_id, address, tag, issued_to, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            ON CONFLICT(account_id, address) DO UPDATE SET issued_to = excluded.issued_to
+            "#,
+        )
+        .bind(alias.id.to_string())
+        .bind(alias.account_id.to_string())
+        .bind(&alias.address)
+        .bind(&alias.tag)
+        .bind(&alias.issued_to)
+        .bind(alias.created_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list_plus_aliases(
+        &self,
+        account_id: Option<Uuid>,
+    ) -> Result<Vec<cove_core::PlusAlias>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM plus_aliases
+            WHERE ?1 IS NULL OR account_id = ?1
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(account_id.map(|id| id.to_string()))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let id_raw: String = row.try_get("id")?;
+                let account_raw: String = row.try_get("account_id")?;
+                let created_raw: String = row.try_get("created_at")?;
+                Ok(cove_core::PlusAlias {
+                    id: parse_uuid(&id_raw, "plus_aliases.id")?,
+                    account_id: parse_uuid(&account_raw, "plus_aliases.account_id")?,
+                    address: row.try_get("address")?,
+                    tag: row.try_get("tag")?,
+                    issued_to: row.try_get("issued_to")?,
+                    created_at: parse_datetime(&created_raw, "plus_aliases.created_at")?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn delete_plus_alias(&self, alias_id: Uuid) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM plus_aliases WHERE id = ?1")
+            .bind(alias_id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Senders of mail addressed (To or Cc) to `address` in `account_id`,
+    /// as (sender, message count, latest received_at).
+    pub async fn plus_alias_senders(
+        &self,
+        account_id: Uuid,
+        address: &str,
+    ) -> Result<Vec<(String, u32, DateTime<Utc>)>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT lower(json_extract(m.from_json, '$[0].address')) AS sender,
+                   COUNT(DISTINCT m.id) AS message_count,
+                   MAX(m.received_at) AS last_received_at
+            FROM mail_messages m
+            WHERE m.account_id = ?1
+              AND (
+                EXISTS (SELECT 1 FROM json_each(m.to_json) r
+                        WHERE lower(json_extract(r.value, '$.address')) = lower(?2))
+                OR EXISTS (SELECT 1 FROM json_each(m.cc_json) r
+                           WHERE lower(json_extract(r.value, '$.address')) = lower(?2))
+              )
+            GROUP BY sender
+            ORDER BY last_received_at DESC
+            "#,
+        )
+        .bind(account_id.to_string())
+        .bind(address)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let sender: Option<String> = row.try_get("sender")?;
+                let count: i64 = row.try_get("message_count")?;
+                let last_raw: String = row.try_get("last_received_at")?;
+                Ok((
+                    sender.unwrap_or_default(),
+                    count.max(0) as u32,
+                    parse_datetime(&last_raw, "mail_messages.received_at")?,
+                ))
+            })
+            .collect()
+    }
+
+    // -- mail merge ----------------------------------------------------------
+
+    pub async fn insert_mail_merge(
+        &self,
+        campaign: &cove_core::MailMergeCampaign,
+        recipients: &[cove_core::MailMergeRecipient],
+    ) -> Result<(), StorageError> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO mail_merge_campaigns (id, account_id, name, subject, body_text, per_minute, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            "#,
+        )
+        .bind(campaign.id.to_string())
+        .bind(campaign.account_id.to_string())
+        .bind(&campaign.name)
+        .bind(&campaign.subject)
+        .bind(&campaign.body_text)
+        .bind(i64::from(campaign.per_minute))
+        .bind(campaign.created_at.to_rfc3339())
+        .execute(&mut *tx)
+        .await?;
+
+        for (position, recipient) in recipients.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO mail_merge_recipients
+                  (id, campaign_id, position, email, fields_json, status, error, sent_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                "#,
+            )
+            .bind(recipient.id.to_string())
+            .bind(campaign.id.to_string())
+            .bind(position as i64)
+            .bind(&recipient.email)
+            .bind(serde_json::to_string(&recipient.fields)?)
+            .bind(merge_status_str(&recipient.status)?)
+            .bind(&recipient.error)
+            .bind(recipient.sent_at.map(|value| value.to_rfc3339()))
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn get_mail_merge(
+        &self,
+        campaign_id: Uuid,
+    ) -> Result<Option<cove_core::MailMergeCampaign>, StorageError> {
+        let row = sqlx::query("SELECT * FROM mail_merge_campaigns WHERE id = ?1")
+            .bind(campaign_id.to_string())
+            .fetch_optional(&self.pool)
+            .await?;
+        row.map(Self::row_to_mail_merge).transpose()
+    }
+
+    pub async fn list_mail_merges(
+        &self,
+        account_id: Option<Uuid>,
+    ) -> Result<Vec<cove_core::MailMergeCampaign>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM mail_merge_campaigns
+            WHERE ?1 IS NULL OR account_id = ?1
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(account_id.map(|id| id.to_string()))
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(Self::row_to_mail_merge).collect()
+    }
+
+    /// Recipients in CSV order, optionally only those with `status`.
+    pub async fn list_mail_merge_recipients(
+        &self,
+        campaign_id: Uuid,
+        status: Option<&cove_core::MergeRecipientStatus>,
+        limit: i64,
+    ) -> Result<Vec<cove_core::MailMergeRecipient>, StorageError> {
+        let status = status.map(merge_status_str).transpose()?;
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM mail_merge_recipients
+            WHERE campaign_id = ?1 AND (?2 IS NULL OR status = ?2)
+            ORDER BY position ASC
+            LIMIT ?3
+            "#,
+        )
+        .bind(campaign_id.to_string())
+        .bind(status)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let status: String = row.try_get("status")?;
+                let sent_raw: Option<String> = row.try_get("sent_at")?;
+                Ok(cove_core::MailMergeRecipient {
+                    id: parse_uuid(&row.try_get::<String, _>("id")?, "mail_merge_recipients.id")?,
+                    campaign_id: parse_uuid(
+                        &row.try_get::<String, _>("campaign_id")?,
+                        "mail_merge_recipients.campaign_id",
+                    )?,
+                    email: row.try_get("email")?,
+                    fields: parse_json(
+                        &row.try_get::<String, _>("fields_json")?,
+                        "mail_merge_recipients.fields_json",
+                    )?,
+                    status: parse_json(&format!("\"{status}\""), "mail_merge_recipients.status")?,
+                    error: row.try_get("error")?,
+                    sent_at: sent_raw
+                        .as_deref()
+                        .map(|raw| parse_datetime(raw, "mail_merge_recipients.sent_at"))
+                        .transpose()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Messages of a campaign sent at or after `since`; drives throttling.
+    pub async fn count_mail_merge_sent_since(
+        &self,
+        campaign_id: Uuid,
+        since: DateTime<Utc>,
+    ) -> Result<u32, StorageError> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM mail_merge_recipients WHERE campaign_id = ?1 AND sent_at >= ?2",
+        )
+        .bind(campaign_id.to_string())
+        .bind(since.to_rfc3339())
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(count.max(0) as u32)
+    }
+
+    pub async fn set_mail_merge_recipient_status(
+        &self,
+        recipient_id: Uuid,
+        status: &cove_core::MergeRecipientStatus,
+        error: Option<&str>,
+        sent_at: Option<DateTime<Utc>>,
+    ) -> Result<(), StorageError> {
+        sqlx::query("UPDATE mail_merge_recipients SET status = ?1, error = ?2, sent_at = ?3 WHERE id = ?4")
+            .bind(merge_status_str(status)?)
+            .bind(error)
+            .bind(sent_at.map(|value| value.to_rfc3339()))
+            .bind(recipient_id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete_mail_merge(&self, campaign_id: Uuid) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM mail_merge_recipients WHERE campaign_id = ?1")
+            .bind(campaign_id.to_string())
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("DELETE FROM mail_merge_campaigns WHERE id = ?1")
+            .bind(campaign_id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // -- mail rules ----------------------------------------------------------
+
+    pub async fn upsert_rule(
+        &self,
+        rule: &cove_core::MailRule,
+    ) -> Result<(), StorageError> {
+        Self::write_rule(&self.pool, rule).await
+    }
+
+    async fn write_rule<'e, E>(executor: E, rule: &cove_core::MailRule) -> Result<(), StorageError>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+    {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO mail_rules
+              (id, account_id, name, enabled, conditions_json, match_all, actions_json, stop_processing, sort_order)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            "#,
+        )
+        .bind(rule.id.to_string())
+        .bind(rule.account_id.map(|id| id.to_string()))
+        .bind(&rule.name)
+        .bind(rule.enabled as i32)
+        .bind(serde_json::to_string(&rule.conditions)?)
+        .bind(rule.match_all as i32)
+        .bind(serde_json::to_string(&rule.actions)?)
+        .bind(rule.stop_processing as i32)
+        .bind(rule.order)
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list_rules(&self) -> Result<Vec<cove_core::MailRule>, StorageError> {
+        let rows = sqlx::query("SELECT * FROM mail_rules ORDER BY sort_order")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let id: String = row.try_get("id")?;
+                let acct: Option<String> = row.try_get("account_id")?;
+                Ok(cove_core::MailRule {
+                    id: parse_uuid(&id, "mail_rules.id")?,
+                    account_id: acct.as_deref().map(|v| parse_uuid(v, "mail_rules.account_id")).transpose()?,
+                    name: row.try_get("name")?,
+                    enabled: row.try_get::<i32, _>("enabled")? != 0,
+                    conditions: parse_json(&row.try_get::<String, _>("conditions_json")?, "mail_rules.conditions_json")?,
+                    match_all: row.try_get::<i32, _>("match_all")? != 0,
+                    actions: parse_json(&row.try_get::<String, _>("actions_json")?, "mail_rules.actions_json")?,
+                    stop_processing: row.try_get::<i32, _>("stop_processing")? != 0,
+                    order: row.try_get("sort_order")?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn delete_rule(&self, id: Uuid) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM mail_rules WHERE id = ?1")
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // -- labels and triage bundles -------------------------------------------
+
+    pub async fn upsert_label(&self, label: &cove_core::MailLabel) -> Result<(), StorageError> {
+        Self::write_label(&self.pool, label).await
+    }
+
+    async fn write_label<'e, E>(executor: E, label: &cove_core::MailLabel) -> Result<(), StorageError>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+    {
+        sqlx::query(
+            r#"
+            INSERT INTO mail_labels (name, color, description, created_at)
+            VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT(name) DO UPDATE SET
+              color = COALESCE(excluded.color, mail_labels.color),
+              description = COALESCE(excluded.description, mail_labels.description)
+            "#,
+        )
+        .bind(label.name.trim())
+        .bind(&label.color)
+        .bind(&label.description)
+        .bind(Utc::now().to_rfc3339())
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    /// Defined labels plus any label in use on a message, by name.
+    pub async fn list_labels(&self) -> Result<Vec<cove_core::MailLabel>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT name, color, description FROM mail_labels
+            UNION
+            SELECT DISTINCT l.value, NULL, NULL
+            FROM mail_messages m, json_each(m.labels_json) l
+            WHERE l.value NOT IN (SELECT name FROM mail_labels)
+            ORDER BY 1 COLLATE NOCASE
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(cove_core::MailLabel {
+                    name: row.try_get(0)?,
+                    color: row.try_get(1)?,
+                    description: row.try_get(2)?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn delete_label(&self, name: &str) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM mail_labels WHERE name = ?1")
+            .bind(name)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // -- thread labels -------------------------------------------------------
+
+    /// Tag a whole conversation. Replies synced later share the thread id
+    /// and so carry the label without being tagged themselves.
+    pub async fn add_thread_label(
+        &self,
+        account_id: Uuid,
+        thread_id: &str,
+        label: &str,
+    ) -> Result<(), StorageError> {
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO thread_labels (account_id, thread_id, label, created_at)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+        )
+        .bind(account_id.to_string())
+        .bind(thread_id)
+        .bind(label.trim())
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn remove_thread_label(
+        &self,
+        account_id: Uuid,
+        thread_id: &str,
+        label: &str,
+    ) -> Result<(), StorageError> {
+        sqlx::query(
+            "DELETE FROM thread_labels WHERE account_id = ?1 AND thread_id = ?2 AND label = ?3",
+        )
+        .bind(account_id.to_string())
+        .bind(thread_id)
+        .bind(label.trim())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list_thread_labels(
+        &self,
+        account_id: Uuid,
+        thread_id: &str,
+    ) -> Result<Vec<String>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT label FROM thread_labels
+            WHERE account_id = ?1 AND thread_id = ?2
+            ORDER BY label COLLATE NOCASE
+            "#,
+        )
+        .bind(account_id.to_string())
+        .bind(thread_id)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter()
+            .map(|row| Ok(row.try_get("label")?))
+            .collect()
+    }
+
+    /// One smart folder per thread label in use, with thread and unread
+    /// counts across all accounts.
+    pub async fn list_thread_label_folders(
+        &self,
+    ) -> Result<Vec<cove_core::ThreadLabelFolder>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT t.label AS label,
+                   (SELECT color FROM mail_labels l WHERE l.name = t.label) AS color,
+                   COUNT(DISTINCT t.account_id || ':' || t.thread_id) AS thread_count,
+                   (SELECT COUNT(DISTINCT m.account_id || ':' || m.thread_id)
+                    FROM mail_messages m
+                    JOIN thread_labels u
+                      ON u.account_id = m.account_id AND u.thread_id = m.thread_id
+                    WHERE u.label = t.label
+                      AND m.flags_json LIKE '%"seen":false%') AS unread_count
+            FROM thread_labels t
+            GROUP BY t.label COLLATE NOCASE
+            ORDER BY t.label COLLATE NOCASE
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let thread_count: i64 = row.try_get("thread_count")?;
+                let unread_count: i64 = row.try_get("unread_count")?;
+                Ok(cove_core::ThreadLabelFolder {
+                    label: row.try_get("label")?,
+                    color: row.try_get("color")?,
+                    thread_count: thread_count as usize,
+                    unread_count: unread_count as usize,
+                })
+            })
+            .collect()
+    }
+
+    /// Messages from every thread tagged `label`, in any folder, newest
+    /// first. `account_id` narrows the result to one account.
+    pub async fn list_thread_label_messages(
+        &self,
+        label: &str,
+        account_id: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<cove_core::MailMessage>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT m.* FROM mail_messages m
+            JOIN thread_labels t
+              ON t.account_id = m.account_id AND t.thread_id = m.thread_id
+            WHERE t.label = ?1 AND (?2 IS NULL OR m.account_id = ?2)
+            ORDER BY m.received_at DESC
+            LIMIT ?3
+            "#,
+        )
+        .bind(label.trim())
+        .bind(account_id.map(|id| id.to_string()))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(Self::row_to_mail_message).collect()
+    }
+
+    pub async fn export_triage_bundle(&self) -> Result<cove_core::TriageBundle, StorageError> {
+        let rules = self
+            .list_rules()
+            .await?
+            .into_iter()
+            .map(|rule| cove_core::MailRule {
+                account_id: None,
+                ..rule
+            })
+            .collect();
+
+        Ok(cove_core::TriageBundle {
+            version: cove_core::TRIAGE_BUNDLE_VERSION,
+            exported_at: Utc::now(),
+            labels: self.list_labels().await?,
+            rules,
+            templates: self.list_templates().await?,
+        })
+    }
+
+    /// Merge a bundle in one transaction. Rules and templates replace
+    /// local ones with the same name (keeping the local id and, for
+    /// rules, account binding); the rest are added.
+    pub async fn import_triage_bundle(
+        &self,
+        bundle: &cove_core::TriageBundle,
+    ) -> Result<cove_core::TriageImportSummary, StorageError> {
+        if bundle.version > cove_core::TRIAGE_BUNDLE_VERSION {
+            return Err(StorageError::Data(format!(
+                "bundle version {} is newer than this app supports ({})",
+                bundle.version,
+                cove_core::TRIAGE_BUNDLE_VERSION
+            )));
+        }
+
+        let existing_rules = self.list_rules().await?;
+        let existing_templates = self.list_templates().await?;
+        let mut next_order = existing_rules.iter().map(|rule| rule.order).max().unwrap_or(0);
+        let mut summary = cove_core::TriageImportSummary::default();
+        let mut tx = self.pool.begin().await?;
+
+        for label in bundle.labels.iter().filter(|label| !label.name.trim().is_empty()) {
+            Self::write_label(&mut *tx, label).await?;
+            summary.labels += 1;
+        }
+
+        for rule in &bundle.rules {
+            let local = existing_rules
+                .iter()
+                .find(|existing| existing.name.eq_ignore_ascii_case(&rule.name));
+            let merged = match local {
+                Some(local) => {
+                    summary.rules_updated += 1;
+                    cove_core::MailRule {
+                        id: local.id,
+                        account_id: local.account_id,
+                        order: local.order,
+                        ..rule.clone()
+                    }
+                }
+                None => {
+                    summary.rules_added += 1;
+                    next_order += 1;
+                    cove_core::MailRule {
+                        id: Uuid::new_v4(),
+                        account_id: None,
+                        order: next_order,
+                        ..rule.clone()
+                    }
+                }
+            };
+            Self::write_rule(&mut *tx, &merged).await?;
+        }
+
+        for template in &bundle.templates {
+            let local = existing_templates
+                .iter()
+                .find(|existing| existing.name.eq_ignore_ascii_case(&template.name));
+            let id = match local {
+                Some(local) => {
+                    summary.templates_updated += 1;
+                    local.id
+                }
+                None => {
+                    summary.templates_added += 1;
+                    Uuid::new_v4()
+                }
+            };
+            let merged = cove_core::EmailTemplate {
+                id,
+                ..template.clone()
+            };
+            Self::write_template(&mut *tx, &merged).await?;
+        }
+
+        tx.commit().await?;
+        Ok(summary)
+    }
+
+    // -- message export ------------------------------------------------------
+
+    /// Write messages matching `filter` to `path` as newline-delimited JSON,
+    /// oldest first, and return how many were written.
+    pub async fn export_messages_json(
+        &self,
+        filter: &cove_core::MessageExportFilter,
+        path: &Path,
+    ) -> Result<u64, StorageError> {
+        const PAGE_SIZE: i64 = 500;
+
+        let file = tokio::fs::File::create(path).await?;
+        let mut out = tokio::io::BufWriter::new(file);
+        let mut pseudonyms = Pseudonyms::default();
+        let mut written = 0_u64;
+        loop {
+            let rows = sqlx::query(
+                r#"
+                SELECT * FROM mail_messages
+                WHERE (?1 IS NULL OR account_id = ?1)
+                  AND (?2 IS NULL OR folder_path = ?2)
+                  AND (?3 IS NULL OR received_at >= ?3)
+                  AND (?4 IS NULL OR received_at < ?4)
+                ORDER BY received_at ASC, id ASC
+                LIMIT ?5 OFFSET ?6
+                "#,
+            )
+            .bind(filter.account_id.map(|id| id.to_string()))
+            .bind(filter.folder.as_deref())
+            .bind(filter.since.map(|at| at.to_rfc3339()))
+            .bind(filter.until.map(|at| at.to_rfc3339()))
+            .bind(PAGE_SIZE)
+            .bind(written as i64)
+            .fetch_all(&self.pool)
+            .await?;
+            let fetched = rows.len();
+            for row in rows {
+                let message = Self::row_to_mail_message(row)?;
+                let line = export_line(&message, filter.redaction, &mut pseudonyms)?;
+                out.write_all(line.as_bytes()).await?;
+                written += 1;
+            }
+            if (fetched as i64) < PAGE_SIZE {
+                break;
+            }
+        }
+        out.flush().await?;
+        Ok(written)
+    }
+
+    // -- retention -----------------------------------------------------------
+
+    /// Messages in `folder_path` received before `cutoff`, oldest first.
+    pub async fn list_purge_candidates(
+        &self,
+        account_id: Uuid,
+        folder_path: &str,
+        cutoff: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<cove_core::PurgeCandidate>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, remote_id, subject, received_at FROM mail_messages
+            WHERE account_id = ?1 AND folder_path = ?2 AND received_at < ?3
+            ORDER BY received_at ASC
+            LIMIT ?4
+            "#,
+        )
+        .bind(account_id.to_string())
+        .bind(folder_path)
+        .bind(cutoff.to_rfc3339())
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let id: String = row.try_get("id")?;
+                let received_at: String = row.try_get("received_at")?;
+                Ok(cove_core::PurgeCandidate {
+                    message_id: parse_uuid(&id, "mail_messages.id")?,
+                    account_id,
+                    remote_id: row.try_get("remote_id")?,
+                    folder_path: folder_path.to_string(),
+                    subject: row.try_get("subject")?,
+                    received_at: parse_datetime(&received_at, "mail_messages.received_at")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Delete messages locally, along with their search documents.
+    pub async fn delete_mail_messages(&self, ids: &[Uuid]) -> Result<(), StorageError> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let ids: Vec<String> = ids.iter().map(Uuid::to_string).collect();
+        let json = serde_json::to_string(&ids)?;
+
+        let mut tx = self.pool.begin().await?;
+        for table in ["search_index_queue", "mail_messages"] {
+            let column = if table == "mail_messages" { "id" } else { "message_id" };
+            sqlx::query(&format!(
+                "DELETE FROM {table} WHERE {column} IN (SELECT value FROM json_each(?1))"
+            ))
+            .bind(&json)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        self.search.remove_messages(&ids).await
+    }
+
+    /// Delete the messages in `folder_path` with the given remote ids.
+    pub async fn delete_mail_messages_by_remote_id(
+        &self,
+        account_id: Uuid,
+        folder_path: &str,
+        remote_ids: &[String],
+    ) -> Result<(), StorageError> {
+        let ids: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT id FROM mail_messages
+            WHERE account_id = ?1 AND folder_path = ?2
+              AND remote_id IN (SELECT value FROM json_each(?3))
+            "#,
+        )
+        .bind(account_id.to_string())
+        .bind(folder_path)
+        .bind(serde_json::to_string(remote_ids)?)
+        .fetch_all(&self.pool)
+        .await?;
+        let ids = ids
+            .iter()
+            .map(|id| parse_uuid(id, "mail_messages.id"))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.delete_mail_messages(&ids).await
+    }
+
+    // -- inbox digest --------------------------------------------------------
+
+    /// Unread inbox messages, newest first, for the unread mail digest.
+    pub async fn list_unread_inbox_messages(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<cove_core::MailMessage>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM mail_messages
+            WHERE folder_path = 'INBOX' AND json_extract(flags_json, '$.seen') = 0
+            ORDER BY received_at DESC
+            LIMIT ?1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(Self::row_to_mail_message).collect()
+    }
+
+    pub async fn list_thread_digests(
+        &self,
+    ) -> Result<Vec<cove_core::ThreadDigestEntry>, StorageError> {
+        let rows = sqlx::query("SELECT * FROM ai_thread_digests")
+            .fetch_all(&self.pool)
+            .await?;
+        rows.into_iter()
+            .map(|row| {
+                let account_id: String = row.try_get("account_id")?;
+                let senders_json: String = row.try_get("senders_json")?;
+                let unread_count: i64 = row.try_get("unread_count")?;
+                let latest_received_at: String = row.try_get("latest_received_at")?;
+                let provenance_json: String = row.try_get("provenance_json")?;
+                let created_at: String = row.try_get("created_at")?;
+                Ok(cove_core::ThreadDigestEntry {
+                    account_id: parse_uuid(&account_id, "ai_thread_digests.account_id")?,
+                    thread_id: row.try_get("thread_id")?,
+                    subject: row.try_get("subject")?,
+                    senders: parse_json(&senders_json, "ai_thread_digests.senders_json")?,
+                    unread_count: unread_count.max(0) as u32,
+                    summary: row.try_get("summary")?,
+                    latest_received_at: parse_datetime(
+                        &latest_received_at,
+                        "ai_thread_digests.latest_received_at",
+                    )?,
+                    provenance: parse_json(&provenance_json, "ai_thread_digests.provenance_json")?,
+                    created_at: parse_datetime(&created_at, "ai_thread_digests.created_at")?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn upsert_thread_digest(
+        &self,
+        entry: &cove_core::ThreadDigestEntry,
+    ) -> Result<(), StorageError> {
+        sqlx::query(
+            r#"
+            INSERT INTO ai_thread_digests (
+              account_id, thread_id, subject, senders_json, unread_count, summary,
+              latest_received_at, provenance_json, created_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            ON CONFLICT(account_id, thread_id) DO UPDATE SET
+              subject = excluded.subject,
+              senders_json = excluded.senders_json,
+              unread_count = excluded.unread_count,
+              summary = excluded.summary,
+              latest_received_at = excluded.latest_received_at,
+              provenance_json = excluded.provenance_json,
+              created_at = excluded.created_at
+            "#,
+        )
+        .bind(entry.account_id.to_string())
+        .bind(&entry.thread_id)
+        .bind(&entry.subject)
+        .bind(serde_json::to_string(&entry.senders)?)
+        .bind(entry.unread_count as i64)
+        .bind(&entry.summary)
+        .bind(entry.latest_received_at.to_rfc3339())
+        .bind(serde_json::to_string(&entry.provenance)?)
+        .bind(entry.created_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // -- sample data ---------------------------------------------------------
+
+    /// Add the sample mailbox, calendar and tasks, or refresh them if they
+    /// are already there.
+    pub async fn seed_sample_data(&self) -> Result<cove_core::SampleDataSummary, StorageError> {
+        let sample = crate::sample::sample_mailbox(Utc::now());
+        self.upsert_account(&sample.account).await?;
+        for contact in &sample.contacts {
+            self.upsert_contact(contact).await?;
+        }
+        self.upsert_mail_messages(&sample.messages).await?;
+        for event in &sample.events {
+            self.upsert_calendar_event(event).await?;
+        }
+        self.upsert_task_list(&sample.task_list).await?;
+        for task in &sample.tasks {
+            self.upsert_task(task).await?;
+        }
+
+        Ok(cove_core::SampleDataSummary {
+            messages: sample.messages.len(),
+            events: sample.events.len(),
+            tasks: sample.tasks.len(),
+        })
+    }
+
+    pub async fn has_sample_data(&self) -> Result<bool, StorageError> {
+        let row = sqlx::query("SELECT 1 FROM accounts WHERE id = ?1")
+            .bind(crate::SAMPLE_ACCOUNT_ID.to_string())
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.is_some())
+    }
+
+    /// Remove the sample account and everything stored under it.
+    pub async fn remove_sample_data(&self) -> Result<(), StorageError> {
+        let account_id = crate::SAMPLE_ACCOUNT_ID.to_string();
+        let message_ids: Vec<String> =
+            sqlx::query_scalar("SELECT id FROM mail_messages WHERE account_id = ?1")
+                .bind(&account_id)
+                .fetch_all(&self.pool)
+                .await?;
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+            DELETE FROM search_index_queue
+            WHERE message_id IN (SELECT id FROM mail_messages WHERE account_id = ?1)
+            "#,
+        )
+        .bind(&account_id)
+        .execute(&mut *tx)
+        .await?;
+        for table in [
+            "mail_messages",
+            "ai_thread_digests",
+            "calendar_events",
+            "task_time_blocks",
+            "reminder_tasks",
+            "task_lists",
+            "contacts",
+            "sync_queue",
+            "account_protocol_settings",
+            "accounts",
+        ] {
+            let column = if table == "accounts" { "id" } else { "account_id" };
+            sqlx::query(&format!("DELETE FROM {table} WHERE {column} = ?1"))
+                .bind(&account_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        self.search.remove_messages(&message_ids).await
+    }
+
+    // -- contacts ------------------------------------------------------------
+
+    pub async fn upsert_contact(
+        &self,
+        contact: &cove_core::Contact,
+    ) -> Result<(), StorageError> {
+        sqlx::query(
+            r#"
+            INSERT INTO contacts (id, account_id, email, display_name, phone, organization, notes, last_contacted, contact_count)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            ON CONFLICT(email) DO UPDATE SET
+              display_name = COALESCE(excluded.display_name, contacts.display_name),
+              phone = COALESCE(excluded.phone, contacts.phone),
+              organization = COALESCE(excluded.organization, contacts.organization),
+              notes = COALESCE(excluded.notes, contacts.notes),
+              last_contacted = excluded.last_contacted,
+              contact_count = excluded.contact_count
+            "#,
+        )
+        .bind(contact.id.to_string())
+        .bind(contact.account_id.map(|id| id.to_string()))
+        .bind(&contact.email)
+        .bind(&contact.display_name)
+        .bind(&contact.phone)
+        .bind(&contact.organization)
+        .bind(&contact.notes)
+        .bind(contact.last_contacted.map(|dt| dt.to_rfc3339()))
+        .bind(contact.contact_count)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn search_contacts(
+        &self,
+        query: &str,
+        limit: i64,
+    ) -> Result<Vec<cove_core::Contact>, StorageError> {
+        let pattern = format!("%{query}%");
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM contacts
+            WHERE email LIKE ?1 OR display_name LIKE ?1
+            ORDER BY contact_count DESC, display_name
+            LIMIT ?2
+            "#,
+        )
+        .bind(&pattern)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let id: String = row.try_get("id")?;
+                let acct: Option<String> = row.try_get("account_id")?;
+                let last: Option<String> = row.try_get("last_contacted")?;
+                Ok(cove_core::Contact {
+                    id: parse_uuid(&id, "contacts.id")?,
+                    account_id: acct.as_deref().map(|v| parse_uuid(v, "contacts.account_id")).transpose()?,
+                    email: row.try_get("email")?,
+                    display_name: row.try_get("display_name")?,
+                    phone: row.try_get("phone")?,
+                    organization: row.try_get("organization")?,
+                    notes: row.try_get("notes")?,
+                    last_contacted: last.as_deref().map(|v| parse_datetime(v, "contacts.last_contacted")).transpose()?,
+                    contact_count: row.try_get::<u32, _>("contact_count").unwrap_or(0),
+                    vip: row.try_get::<i32, _>("vip").unwrap_or(0) != 0,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn increment_contact_count(&self, email: &str) -> Result<(), StorageError> {
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            INSERT INTO contacts (id, email, contact_count, last_contacted)
+            VALUES (?1, ?2, 1, ?3)
+            ON CONFLICT(email) DO UPDATE SET
+              contact_count = contacts.contact_count + 1,
+              last_contacted = excluded.last_contacted
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(email)
+        .bind(&now)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Flag or unflag a sender as VIP, harvesting a contact row if the
+    /// address has not been seen before.
+    pub async fn set_contact_vip(&self, email: &str, vip: bool) -> Result<(), StorageError> {
+        sqlx::query(
+            r#"
+            INSERT INTO contacts (id, email, contact_count, vip)
+            VALUES (?1, ?2, 0, ?3)
+            ON CONFLICT(email) DO UPDATE SET vip = excluded.vip
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(email.trim().to_lowercase())
+        .bind(vip as i32)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Lower-cased addresses of all VIP contacts.
+    pub async fn list_vip_addresses(&self) -> Result<Vec<String>, StorageError> {
+        let rows = sqlx::query("SELECT email FROM contacts WHERE vip = 1 ORDER BY email")
+            .fetch_all(&self.pool)
+            .await?;
+        rows.into_iter()
+            .map(|row| Ok(row.try_get::<String, _>("email")?.to_lowercase()))
+            .collect()
+    }
+
+    pub async fn list_contact_groups(
+        &self,
+    ) -> Result<Vec<cove_core::ContactGroup>, StorageError> {
+        let rows = sqlx::query("SELECT * FROM contact_groups ORDER BY name COLLATE NOCASE ASC")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(cove_core::ContactGroup {
+                    id: parse_uuid(&row.try_get::<String, _>("id")?, "contact_groups.id")?,
+                    name: row.try_get("name")?,
+                    members: parse_json(
+                        &row.try_get::<String, _>("members_json")?,
+                        "contact_groups.members_json",
+                    )?,
+                    created_at: parse_datetime(
+                        &row.try_get::<String, _>("created_at")?,
+                        "contact_groups.created_at",
+                    )?,
+                    updated_at: parse_datetime(
+                        &row.try_get::<String, _>("updated_at")?,
+                        "contact_groups.updated_at",
+                    )?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn upsert_contact_group(
+        &self,
+        group: &cove_core::ContactGroup,
+    ) -> Result<(), StorageError> {
+        sqlx::query(
+            r#"
+            INSERT INTO contact_groups (id, name, members_json, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            ON CONFLICT(id) DO UPDATE SET
+              name = excluded.name,
+              members_json = excluded.members_json,
+              updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(group.id.to_string())
+        .bind(&group.name)
+        .bind(serde_json::to_string(&group.members)?)
+        .bind(group.created_at.to_rfc3339())
+        .bind(group.updated_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_contact_group(&self, id: Uuid) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM contact_groups WHERE id = ?1")
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Record who a message's group sends went to, under its Message-ID.
+    pub async fn record_group_expansions(
+        &self,
+        account_id: Uuid,
+        message_id: &str,
+        expansions: &[cove_core::GroupExpansion],
+    ) -> Result<(), StorageError> {
+        let mut tx = self.pool.begin().await?;
+        for expansion in expansions {
+            sqlx::query(
+                r#"
+                INSERT INTO sent_group_expansions
+                  (id, account_id, message_id, group_id, group_name, field,
+                   recipients_json, excluded_json, sent_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                "#,
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(account_id.to_string())
+            .bind(message_id)
+            .bind(expansion.group_id.to_string())
+            .bind(&expansion.group_name)
+            .bind(serde_json::to_string(&expansion.field)?)
+            .bind(serde_json::to_string(&expansion.recipients)?)
+            .bind(serde_json::to_string(&expansion.excluded)?)
+            .bind(Utc::now().to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Group expansions recorded for the message with this Message-ID.
+    pub async fn group_expansions_for(
+        &self,
+        message_id: &str,
+    ) -> Result<Vec<cove_core::GroupExpansion>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT * FROM sent_group_expansions WHERE message_id = ?1 ORDER BY group_name ASC",
+        )
+        .bind(message_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(cove_core::GroupExpansion {
+                    group_id: parse_uuid(
+                        &row.try_get::<String, _>("group_id")?,
+                        "sent_group_expansions.group_id",
+                    )?,
+                    group_name: row.try_get("group_name")?,
+                    field: parse_json(
+                        &row.try_get::<String, _>("field")?,
+                        "sent_group_expansions.field",
+                    )?,
+                    recipients: parse_json(
+                        &row.try_get::<String, _>("recipients_json")?,
+                        "sent_group_expansions.recipients_json",
+                    )?,
+                    excluded: parse_json(
+                        &row.try_get::<String, _>("excluded_json")?,
+                        "sent_group_expansions.excluded_json",
+                    )?,
+                })
+            })
+            .collect()
+    }
+
+    /// Messages from VIP contacts across all accounts, newest first.
+    pub async fn list_vip_messages(
+        &self,
+        unread_only: bool,
+        limit: i64,
+    ) -> Result<Vec<cove_core::MailMessage>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT m.* FROM mail_messages m
+            WHERE EXISTS (
+              SELECT 1 FROM contacts c
+              WHERE c.vip = 1 AND instr(lower(m.from_json), '"' || lower(c.email) || '"') > 0
+            )
+            AND (?1 = 0 OR json_extract(m.flags_json, '$.seen') = 0)
+            ORDER BY m.received_at DESC
+            LIMIT ?2
+            "#,
+        )
+        .bind(unread_only as i32)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(Self::row_to_mail_message).collect()
+    }
+
+    // -- notes ---------------------------------------------------------------
+
+    pub async fn upsert_note(&self, note: &cove_core::Note) -> Result<(), StorageError> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO notes
+              (id, account_id, title, body_markdown, source_message_id, source_thread_id, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
             "#,
         )
-        .bind(tpl.id.to_string())
-        .bind(&tpl.name)
-        .bind(&tpl.subject)
-        .bind(&tpl.body_html)
-        .bind(&tpl.body_text)
+        .bind(note.id.to_string())
+        .bind(note.account_id.map(|id| id.to_string()))
+        .bind(&note.title)
+        .bind(&note.body_markdown)
+        .bind(note.source_message_id.map(|id| id.to_string()))
+        .bind(&note.source_thread_id)
+        .bind(note.created_at.to_rfc3339())
+        .bind(note.updated_at.to_rfc3339())
         .execute(&self.pool)
         .await?;
         Ok(())
     }
 
-    pub async fn list_templates(&self) -> Result<Vec<cove_core::EmailTemplate>, StorageError> {
-        let rows = sqlx::query("SELECT * FROM email_templates ORDER BY name")
+    pub async fn list_notes(&self) -> Result<Vec<cove_core::Note>, StorageError> {
+        let rows = sqlx::query("SELECT * FROM notes ORDER BY updated_at DESC")
             .fetch_all(&self.pool)
             .await?;
 
         rows.into_iter()
             .map(|row| {
                 let id: String = row.try_get("id")?;
-                Ok(cove_core::EmailTemplate {
-                    id: parse_uuid(&id, "email_templates.id")?,
-                    name: row.try_get("name")?,
-                    subject: row.try_get("subject")?,
-                    body_html: row.try_get("body_html")?,
-                    body_text: row.try_get("body_text")?,
+                let acct: Option<String> = row.try_get("account_id")?;
+                let source: Option<String> = row.try_get("source_message_id")?;
+                Ok(cove_core::Note {
+                    id: parse_uuid(&id, "notes.id")?,
+                    account_id: acct.as_deref().map(|v| parse_uuid(v, "notes.account_id")).transpose()?,
+                    title: row.try_get("title")?,
+                    body_markdown: row.try_get("body_markdown")?,
+                    source_message_id: source.as_deref().map(|v| parse_uuid(v, "notes.source_message_id")).transpose()?,
+                    source_thread_id: row.try_get("source_thread_id")?,
+                    created_at: parse_datetime(&row.try_get::<String, _>("created_at")?, "notes.created_at")?,
+                    updated_at: parse_datetime(&row.try_get::<String, _>("updated_at")?, "notes.updated_at")?,
                 })
             })
             .collect()
     }
 
-    pub async fn delete_template(&self, id: Uuid) -> Result<(), StorageError> {
-        sqlx::query("DELETE FROM email_templates WHERE id = ?1")
+    pub async fn delete_note(&self, id: Uuid) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM notes WHERE id = ?1")
             .bind(id.to_string())
             .execute(&self.pool)
             .await?;
         Ok(())
     }
 
-    // -- mail rules ----------------------------------------------------------
-
-    pub async fn upsert_rule(
+    pub async fn save_note_attachment(
         &self,
-        rule: &cove_core::MailRule,
+        attachment: &cove_core::NoteAttachment,
+        content: &[u8],
     ) -> Result<(), StorageError> {
         sqlx::query(
             r#"
-            INSERT OR REPLACE INTO mail_rules
-              (id, account_id, name, enabled, conditions_json, match_all, actions_json, stop_processing, sort_order)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            INSERT OR REPLACE INTO note_attachments (id, note_id, file_name, mime_type, size, content)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
             "#,
         )
-        .bind(rule.id.to_string())
-        .bind(rule.account_id.map(|id| id.to_string()))
-        .bind(&rule.name)
-        .bind(rule.enabled as i32)
-        .bind(serde_json::to_string(&rule.conditions)?)
-        .bind(rule.match_all as i32)
-        .bind(serde_json::to_string(&rule.actions)?)
-        .bind(rule.stop_processing as i32)
-        .bind(rule.order)
+        .bind(attachment.id.to_string())
+        .bind(attachment.note_id.to_string())
+        .bind(&attachment.file_name)
+        .bind(&attachment.mime_type)
+        .bind(attachment.size as i64)
+        .bind(content)
         .execute(&self.pool)
         .await?;
         Ok(())
     }
 
-    pub async fn list_rules(&self) -> Result<Vec<cove_core::MailRule>, StorageError> {
-        let rows = sqlx::query("SELECT * FROM mail_rules ORDER BY sort_order")
-            .fetch_all(&self.pool)
-            .await?;
+    pub async fn list_note_attachments(
+        &self,
+        note_id: Uuid,
+    ) -> Result<Vec<cove_core::NoteAttachment>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT id, note_id, file_name, mime_type, size FROM note_attachments WHERE note_id = ?1 ORDER BY file_name",
+        )
+        .bind(note_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
 
         rows.into_iter()
             .map(|row| {
                 let id: String = row.try_get("id")?;
-                let acct: Option<String> = row.try_get("account_id")?;
-                Ok(cove_core::MailRule {
-                    id: parse_uuid(&id, "mail_rules.id")?,
-                    account_id: acct.as_deref().map(|v| parse_uuid(v, "mail_rules.account_id")).transpose()?,
-                    name: row.try_get("name")?,
-                    enabled: row.try_get::<i32, _>("enabled")? != 0,
-                    conditions: parse_json(&row.try_get::<String, _>("conditions_json")?, "mail_rules.conditions_json")?,
-                    match_all: row.try_get::<i32, _>("match_all")? != 0,
-                    actions: parse_json(&row.try_get::<String, _>("actions_json")?, "mail_rules.actions_json")?,
-                    stop_processing: row.try_get::<i32, _>("stop_processing")? != 0,
-                    order: row.try_get("sort_order")?,
+                let note: String = row.try_get("note_id")?;
+                Ok(cove_core::NoteAttachment {
+                    id: parse_uuid(&id, "note_attachments.id")?,
+                    note_id: parse_uuid(&note, "note_attachments.note_id")?,
+                    file_name: row.try_get("file_name")?,
+                    mime_type: row.try_get("mime_type")?,
+                    size: row.try_get::<i64, _>("size")?.max(0) as u64,
                 })
             })
             .collect()
     }
 
-    pub async fn delete_rule(&self, id: Uuid) -> Result<(), StorageError> {
-        sqlx::query("DELETE FROM mail_rules WHERE id = ?1")
-            .bind(id.to_string())
-            .execute(&self.pool)
+    pub async fn get_note_attachment_content(
+        &self,
+        attachment_id: Uuid,
+    ) -> Result<Option<Vec<u8>>, StorageError> {
+        let row = sqlx::query("SELECT content FROM note_attachments WHERE id = ?1")
+            .bind(attachment_id.to_string())
+            .fetch_optional(&self.pool)
             .await?;
-        Ok(())
+
+        Ok(row.map(|r| r.get::<Vec<u8>, _>("content")))
     }
 
-    // -- contacts ------------------------------------------------------------
+    // -- audit log -----------------------------------------------------------
 
-    pub async fn upsert_contact(
-        &self,
-        contact: &cove_core::Contact,
-    ) -> Result<(), StorageError> {
+    pub async fn append_audit_entry(&self, entry: &cove_core::AuditEntry) -> Result<(), StorageError> {
         sqlx::query(
             r#"
-            INSERT INTO contacts (id, account_id, email, display_name, phone, organization, notes, last_contacted, contact_count)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
-            ON CONFLICT(email) DO UPDATE SET
-              display_name = COALESCE(excluded.display_name, contacts.display_name),
-              phone = COALESCE(excluded.phone, contacts.phone),
-              organization = COALESCE(excluded.organization, contacts.organization),
-              notes = COALESCE(excluded.notes, contacts.notes),
-              last_contacted = excluded.last_contacted,
-              contact_count = excluded.contact_count
+            INSERT INTO audit_log (id, account_id, kind, success, summary, detail_json, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
             "#,
         )
-        .bind(contact.id.to_string())
-        .bind(contact.account_id.map(|id| id.to_string()))
-        .bind(&contact.email)
-        .bind(&contact.display_name)
-        .bind(&contact.phone)
-        .bind(&contact.organization)
-        .bind(&contact.notes)
-        .bind(contact.last_contacted.map(|dt| dt.to_rfc3339()))
-        .bind(contact.contact_count)
+        .bind(entry.id.to_string())
+        .bind(entry.account_id.map(|id| id.to_string()))
+        .bind(&entry.kind)
+        .bind(entry.success)
+        .bind(&entry.summary)
+        .bind(serde_json::to_string(&entry.detail)?)
+        .bind(entry.created_at.to_rfc3339())
         .execute(&self.pool)
         .await?;
         Ok(())
     }
 
-    pub async fn search_contacts(
+    /// Newest first; `kind_prefix` filters on the dotted event name (`mail.`).
+    pub async fn list_audit_entries(
         &self,
-        query: &str,
+        account_id: Option<Uuid>,
+        kind_prefix: Option<&str>,
         limit: i64,
-    ) -> Result<Vec<cove_core::Contact>, StorageError> {
-        let pattern = format!("%{query}%");
+    ) -> Result<Vec<cove_core::AuditEntry>, StorageError> {
         let rows = sqlx::query(
             r#"
-            SELECT * FROM contacts
-            WHERE email LIKE ?1 OR display_name LIKE ?1
-            ORDER BY contact_count DESC, display_name
-            LIMIT ?2
+            SELECT * FROM audit_log
+            WHERE (?1 IS NULL OR account_id = ?1)
+              AND (?2 IS NULL OR kind LIKE ?2 || '%')
+            ORDER BY created_at DESC
+            LIMIT ?3
             "#,
         )
-        .bind(&pattern)
+        .bind(account_id.map(|id| id.to_string()))
+        .bind(kind_prefix)
         .bind(limit)
         .fetch_all(&self.pool)
         .await?;
@@ -876,39 +4071,44 @@ impl Storage {
             .map(|row| {
                 let id: String = row.try_get("id")?;
                 let acct: Option<String> = row.try_get("account_id")?;
-                let last: Option<String> = row.try_get("last_contacted")?;
-                Ok(cove_core::Contact {
-                    id: parse_uuid(&id, "contacts.id")?,
-                    account_id: acct.as_deref().map(|v| parse_uuid(v, "contacts.account_id")).transpose()?,
-                    email: row.try_get("email")?,
-                    display_name: row.try_get("display_name")?,
-                    phone: row.try_get("phone")?,
-                    organization: row.try_get("organization")?,
-                    notes: row.try_get("notes")?,
-                    last_contacted: last.as_deref().map(|v| parse_datetime(v, "contacts.last_contacted")).transpose()?,
-                    contact_count: row.try_get::<u32, _>("contact_count").unwrap_or(0),
+                Ok(cove_core::AuditEntry {
+                    id: parse_uuid(&id, "audit_log.id")?,
+                    account_id: acct.as_deref().map(|v| parse_uuid(v, "audit_log.account_id")).transpose()?,
+                    kind: row.try_get("kind")?,
+                    success: row.try_get("success")?,
+                    summary: row.try_get("summary")?,
+                    detail: parse_json(&row.try_get::<String, _>("detail_json")?, "audit_log.detail_json")?,
+                    created_at: parse_datetime(&row.try_get::<String, _>("created_at")?, "audit_log.created_at")?,
                 })
             })
             .collect()
     }
 
-    pub async fn increment_contact_count(&self, email: &str) -> Result<(), StorageError> {
-        let now = Utc::now().to_rfc3339();
-        sqlx::query(
-            r#"
-            INSERT INTO contacts (id, email, contact_count, last_contacted)
-            VALUES (?1, ?2, 1, ?3)
-            ON CONFLICT(email) DO UPDATE SET
-              contact_count = contacts.contact_count + 1,
-              last_contacted = excluded.last_contacted
-            "#,
+    /// Entries of exactly `kind` recorded for an account since `since`.
+    pub async fn count_audit_entries_since(
+        &self,
+        account_id: Uuid,
+        kind: &str,
+        since: DateTime<Utc>,
+    ) -> Result<u32, StorageError> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM audit_log WHERE account_id = ?1 AND kind = ?2 AND created_at >= ?3",
         )
-        .bind(Uuid::new_v4().to_string())
-        .bind(email)
-        .bind(&now)
-        .execute(&self.pool)
+        .bind(account_id.to_string())
+        .bind(kind)
+        .bind(since.to_rfc3339())
+        .fetch_one(&self.pool)
         .await?;
-        Ok(())
+        Ok(count.max(0) as u32)
+    }
+
+    /// Drop entries older than `cutoff`; returns the number removed.
+    pub async fn prune_audit_log(&self, cutoff: DateTime<Utc>) -> Result<u64, StorageError> {
+        let result = sqlx::query("DELETE FROM audit_log WHERE created_at < ?1")
+            .bind(cutoff.to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
     }
 
     // -- calendar ----------------------------------------------------------
@@ -925,12 +4125,12 @@ impl Storage {
               id, account_id, calendar_id, remote_id, title,
               description, location, timezone, starts_at, ends_at,
               all_day, recurrence_rule, attendees_json, organizer,
-              alarms_json, rsvp_status, updated_at
+              alarms_json, rsvp_status, online_meeting_url, updated_at
             ) VALUES (
               ?1, ?2, ?3, ?4, ?5,
               ?6, ?7, ?8, ?9, ?10,
               ?11, ?12, ?13, ?14,
-              ?15, ?16, ?17
+              ?15, ?16, ?17, ?18
             )
             ON CONFLICT(id) DO UPDATE SET
               account_id = excluded.account_id,
@@ -948,30 +4148,250 @@ impl Storage {
               organizer = excluded.organizer,
               alarms_json = excluded.alarms_json,
               rsvp_status = excluded.rsvp_status,
+              online_meeting_url = excluded.online_meeting_url,
+              updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(event.id.to_string())
+        .bind(event.account_id.to_string())
+        .bind(&event.calendar_id)
+        .bind(&event.remote_id)
+        .bind(&event.title)
+        .bind(&event.description)
+        .bind(&event.location)
+        .bind(&event.timezone)
+        .bind(event.starts_at.to_rfc3339())
+        .bind(event.ends_at.to_rfc3339())
+        .bind(if event.all_day { 1_i64 } else { 0_i64 })
+        .bind(&event.recurrence_rule)
+        .bind(serde_json::to_string(&event.attendees)?)
+        .bind(&event.organizer)
+        .bind(serde_json::to_string(&event.alarms)?)
+        .bind(&rsvp_str)
+        .bind(&event.online_meeting_url)
+        .bind(event.updated_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_calendar_event(
+        &self,
+        event_id: Uuid,
+    ) -> Result<Option<CalendarEvent>, StorageError> {
+        let row = sqlx::query("SELECT * FROM calendar_events WHERE id = ?1")
+            .bind(event_id.to_string())
+            .fetch_optional(&self.pool)
+            .await?;
+        row.map(Self::row_to_calendar_event).transpose()
+    }
+
+    /// Local id of an already-synced event, so re-syncs update the existing
+    /// row instead of colliding on `(account_id, calendar_id, remote_id)`.
+    pub async fn calendar_event_id_by_remote(
+        &self,
+        account_id: Uuid,
+        calendar_id: &str,
+        remote_id: &str,
+    ) -> Result<Option<Uuid>, StorageError> {
+        let row = sqlx::query(
+            "SELECT id FROM calendar_events WHERE account_id = ?1 AND calendar_id = ?2 AND remote_id = ?3",
+        )
+        .bind(account_id.to_string())
+        .bind(calendar_id)
+        .bind(remote_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| parse_uuid(&r.get::<String, _>("id"), "calendar_events.id"))
+            .transpose()
+    }
+
+    pub async fn delete_calendar_event_by_remote(
+        &self,
+        account_id: Uuid,
+        calendar_id: &str,
+        remote_id: &str,
+    ) -> Result<(), StorageError> {
+        sqlx::query(
+            "DELETE FROM calendar_events WHERE account_id = ?1 AND calendar_id = ?2 AND remote_id = ?3",
+        )
+        .bind(account_id.to_string())
+        .bind(calendar_id)
+        .bind(remote_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Stored incremental-sync token and the window it was issued for.
+    pub async fn calendar_delta_token(
+        &self,
+        account_id: Uuid,
+        calendar_id: &str,
+    ) -> Result<Option<(String, DateTime<Utc>, DateTime<Utc>)>, StorageError> {
+        let row = sqlx::query(
+            "SELECT delta_token, window_start, window_end FROM calendar_sync_state WHERE account_id = ?1 AND calendar_id = ?2",
+        )
+        .bind(account_id.to_string())
+        .bind(calendar_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|r| {
+            Ok((
+                r.try_get("delta_token")?,
+                parse_datetime(&r.try_get::<String, _>("window_start")?, "calendar_sync_state.window_start")?,
+                parse_datetime(&r.try_get::<String, _>("window_end")?, "calendar_sync_state.window_end")?,
+            ))
+        })
+        .transpose()
+    }
+
+    pub async fn set_calendar_delta_token(
+        &self,
+        account_id: Uuid,
+        calendar_id: &str,
+        delta_token: Option<&str>,
+        window_start: DateTime<Utc>,
+        window_end: DateTime<Utc>,
+    ) -> Result<(), StorageError> {
+        let Some(delta_token) = delta_token else {
+            sqlx::query("DELETE FROM calendar_sync_state WHERE account_id = ?1 AND calendar_id = ?2")
+                .bind(account_id.to_string())
+                .bind(calendar_id)
+                .execute(&self.pool)
+                .await?;
+            return Ok(());
+        };
+
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO calendar_sync_state
+              (account_id, calendar_id, delta_token, window_start, window_end, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+        )
+        .bind(account_id.to_string())
+        .bind(calendar_id)
+        .bind(delta_token)
+        .bind(window_start.to_rfc3339())
+        .bind(window_end.to_rfc3339())
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Find an event by iCalendar UID in any of the account's calendars.
+    pub async fn calendar_event_id_by_uid(
+        &self,
+        account_id: Uuid,
+        uid: &str,
+    ) -> Result<Option<Uuid>, StorageError> {
+        let row = sqlx::query("SELECT id FROM calendar_events WHERE account_id = ?1 AND remote_id = ?2")
+            .bind(account_id.to_string())
+            .bind(uid)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(|r| parse_uuid(&r.get::<String, _>("id"), "calendar_events.id"))
+            .transpose()
+    }
+
+    /// Record an attendee's response unless a newer one is already stored,
+    /// so replies processed out of order cannot roll a status back.
+    /// Returns whether the stored status changed.
+    pub async fn record_attendee_response(
+        &self,
+        response: &cove_core::AttendeeResponse,
+    ) -> Result<bool, StorageError> {
+        let status_str = serde_json::to_string(&response.status)?
+            .trim_matches('"')
+            .to_string();
+        let result = sqlx::query(
+            r#"
+            INSERT INTO calendar_attendee_status (event_id, email, status, updated_at)
+            VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT(event_id, email) DO UPDATE SET
+              status = excluded.status,
               updated_at = excluded.updated_at
+            WHERE excluded.updated_at > calendar_attendee_status.updated_at
+            "#,
+        )
+        .bind(response.event_id.to_string())
+        .bind(response.email.to_lowercase())
+        .bind(&status_str)
+        .bind(response.updated_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn list_attendee_responses(
+        &self,
+        event_id: Uuid,
+    ) -> Result<Vec<cove_core::AttendeeResponse>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT * FROM calendar_attendee_status WHERE event_id = ?1 ORDER BY email ASC",
+        )
+        .bind(event_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let status: String = row.try_get("status")?;
+                Ok(cove_core::AttendeeResponse {
+                    event_id: parse_uuid(&row.try_get::<String, _>("event_id")?, "calendar_attendee_status.event_id")?,
+                    email: row.try_get("email")?,
+                    status: parse_json(&format!("\"{status}\""), "calendar_attendee_status.status")?,
+                    updated_at: parse_datetime(&row.try_get::<String, _>("updated_at")?, "calendar_attendee_status.updated_at")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Stored `text/calendar` parts of the account's mail received since
+    /// `since`, oldest first.
+    pub async fn list_calendar_parts_since(
+        &self,
+        account_id: Uuid,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<Vec<u8>>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT c.attachment_id, c.content, m.attachments_json
+            FROM mail_attachment_content c
+            JOIN mail_messages m ON m.id = c.message_id
+            WHERE m.account_id = ?1 AND m.received_at >= ?2
+              AND m.attachments_json LIKE '%text/calendar%'
+            ORDER BY m.received_at ASC
             "#,
         )
-        .bind(event.id.to_string())
-        .bind(event.account_id.to_string())
-        .bind(&event.calendar_id)
-        .bind(&event.remote_id)
-        .bind(&event.title)
-        .bind(&event.description)
-        .bind(&event.location)
-        .bind(&event.timezone)
-        .bind(event.starts_at.to_rfc3339())
-        .bind(event.ends_at.to_rfc3339())
-        .bind(if event.all_day { 1_i64 } else { 0_i64 })
-        .bind(&event.recurrence_rule)
-        .bind(serde_json::to_string(&event.attendees)?)
-        .bind(&event.organizer)
-        .bind(serde_json::to_string(&event.alarms)?)
-        .bind(&rsvp_str)
-        .bind(event.updated_at.to_rfc3339())
-        .execute(&self.pool)
+        .bind(account_id.to_string())
+        .bind(since.to_rfc3339())
+        .fetch_all(&self.pool)
         .await?;
 
-        Ok(())
+        let mut parts = Vec::new();
+        for row in rows {
+            let attachment_id: String = row.try_get("attachment_id")?;
+            let attachments: Vec<cove_core::MailAttachment> = parse_json(
+                &row.try_get::<String, _>("attachments_json")?,
+                "mail_messages.attachments_json",
+            )?;
+            let is_calendar = attachments.iter().any(|attachment| {
+                attachment.id.to_string() == attachment_id
+                    && attachment.mime_type.eq_ignore_ascii_case("text/calendar")
+            });
+            if is_calendar {
+                parts.push(row.try_get("content")?);
+            }
+        }
+        Ok(parts)
     }
 
     pub async fn update_rsvp_status(
@@ -1019,15 +4439,18 @@ impl Storage {
             INSERT INTO reminder_tasks (
               id, account_id, list_id, remote_id, title,
               notes, due_at, completed_at, priority, status,
-              repeat_rule, parent_id, snoozed_until, created_at, updated_at
+              repeat_rule, parent_id, snoozed_until, created_at, updated_at,
+              estimate_minutes, list_name
             ) VALUES (
               ?1, ?2, ?3, ?4, ?5,
               ?6, ?7, ?8, ?9, ?10,
-              ?11, ?12, ?13, ?14, ?15
+              ?11, ?12, ?13, ?14, ?15,
+              ?16, ?17
             )
             ON CONFLICT(id) DO UPDATE SET
               account_id = excluded.account_id,
               list_id = excluded.list_id,
+              list_name = COALESCE(excluded.list_name, reminder_tasks.list_name),
               remote_id = excluded.remote_id,
               title = excluded.title,
               notes = excluded.notes,
@@ -1038,7 +4461,9 @@ impl Storage {
               repeat_rule = excluded.repeat_rule,
               parent_id = excluded.parent_id,
               snoozed_until = excluded.snoozed_until,
-              updated_at = excluded.updated_at
+              updated_at = excluded.updated_at,
+              -- Providers have no estimate field; keep the local one on sync.
+              estimate_minutes = COALESCE(excluded.estimate_minutes, reminder_tasks.estimate_minutes)
             "#,
         )
         .bind(task.id.to_string())
@@ -1056,64 +4481,590 @@ impl Storage {
         .bind(task.snoozed_until.map(|value| value.to_rfc3339()))
         .bind(task.created_at.to_rfc3339())
         .bind(task.updated_at.to_rfc3339())
+        .bind(task.estimate_minutes.map(i64::from))
+        .bind(&task.list_name)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Local id of a synced task, so re-syncs update rather than duplicate it.
+    pub async fn task_id_by_remote(
+        &self,
+        account_id: Uuid,
+        remote_id: &str,
+    ) -> Result<Option<Uuid>, StorageError> {
+        let row = sqlx::query("SELECT id FROM reminder_tasks WHERE account_id = ?1 AND remote_id = ?2")
+            .bind(account_id.to_string())
+            .bind(remote_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(|r| parse_uuid(&r.get::<String, _>("id"), "reminder_tasks.id"))
+            .transpose()
+    }
+
+    pub async fn delete_task(&self, task_id: Uuid) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM reminder_tasks WHERE id = ?1")
+            .bind(task_id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn list_tasks(&self, account_id: Uuid) -> Result<Vec<ReminderTask>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM reminder_tasks
+            WHERE account_id = ?1
+            ORDER BY due_at ASC
+            "#,
+        )
+        .bind(account_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(Self::row_to_task).collect()
+    }
+
+    /// List subtasks (tasks with a given parent_id).
+    pub async fn list_subtasks(&self, parent_id: Uuid) -> Result<Vec<ReminderTask>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT * FROM reminder_tasks WHERE parent_id = ?1 ORDER BY priority DESC, due_at ASC",
+        )
+        .bind(parent_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(Self::row_to_task).collect()
+    }
+
+    /// List top-level tasks (no parent) for an account, ordered by priority descending.
+    pub async fn list_tasks_by_priority(
+        &self,
+        account_id: Uuid,
+    ) -> Result<Vec<ReminderTask>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM reminder_tasks
+            WHERE account_id = ?1 AND (parent_id IS NULL OR parent_id = '')
+            ORDER BY
+                CASE priority
+                    WHEN 'critical' THEN 0
+                    WHEN 'high' THEN 1
+                    WHEN 'normal' THEN 2
+                    WHEN 'low' THEN 3
+                    ELSE 4
+                END,
+                due_at ASC
+            "#,
+        )
+        .bind(account_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(Self::row_to_task).collect()
+    }
+
+    pub async fn get_task(&self, task_id: Uuid) -> Result<Option<ReminderTask>, StorageError> {
+        let row = sqlx::query("SELECT * FROM reminder_tasks WHERE id = ?1")
+            .bind(task_id.to_string())
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(Self::row_to_task).transpose()
+    }
+
+    /// Set or clear a task's time estimate. Estimates are local, so task
+    /// syncs keep them.
+    pub async fn set_task_estimate(
+        &self,
+        task_id: Uuid,
+        minutes: Option<u32>,
+    ) -> Result<(), StorageError> {
+        sqlx::query("UPDATE reminder_tasks SET estimate_minutes = ?1 WHERE id = ?2")
+            .bind(minutes.map(i64::from))
+            .bind(task_id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // -- scheduling polls ----------------------------------------------------
+
+    pub async fn upsert_scheduling_poll(
+        &self,
+        poll: &cove_core::SchedulingPoll,
+    ) -> Result<(), StorageError> {
+        let status = serde_json::to_string(&poll.status)?
+            .trim_matches('"')
+            .to_string();
+        sqlx::query(
+            r#"
+            INSERT INTO scheduling_polls (
+              id, account_id, title, description, options_json, participants_json, token,
+              status, confirmed_option, event_id, created_at, updated_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+            ON CONFLICT(id) DO UPDATE SET
+              title = excluded.title,
+              description = excluded.description,
+              options_json = excluded.options_json,
+              participants_json = excluded.participants_json,
+              status = excluded.status,
+              confirmed_option = excluded.confirmed_option,
+              event_id = excluded.event_id,
+              updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(poll.id.to_string())
+        .bind(poll.account_id.to_string())
+        .bind(&poll.title)
+        .bind(&poll.description)
+        .bind(serde_json::to_string(&poll.options)?)
+        .bind(serde_json::to_string(&poll.participants)?)
+        .bind(&poll.token)
+        .bind(status)
+        .bind(poll.confirmed_option.map(|index| index as i64))
+        .bind(poll.event_id.map(|id| id.to_string()))
+        .bind(poll.created_at.to_rfc3339())
+        .bind(poll.updated_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_scheduling_poll(
+        &self,
+        poll_id: Uuid,
+    ) -> Result<Option<cove_core::SchedulingPoll>, StorageError> {
+        let row = sqlx::query("SELECT * FROM scheduling_polls WHERE id = ?1")
+            .bind(poll_id.to_string())
+            .fetch_optional(&self.pool)
+            .await?;
+        row.map(Self::row_to_scheduling_poll).transpose()
+    }
+
+    /// Polls of an account, newest first.
+    pub async fn list_scheduling_polls(
+        &self,
+        account_id: Uuid,
+    ) -> Result<Vec<cove_core::SchedulingPoll>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT * FROM scheduling_polls WHERE account_id = ?1 ORDER BY created_at DESC",
+        )
+        .bind(account_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(Self::row_to_scheduling_poll).collect()
+    }
+
+    /// Keep a participant's newest answer; older replies processed later do
+    /// not overwrite it. Returns whether the stored vote changed.
+    pub async fn record_poll_vote(
+        &self,
+        vote: &cove_core::PollVote,
+    ) -> Result<bool, StorageError> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO scheduling_poll_votes (poll_id, participant, choices_json, voted_at)
+            VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT(poll_id, participant) DO UPDATE SET
+              choices_json = excluded.choices_json,
+              voted_at = excluded.voted_at
+            WHERE excluded.voted_at > scheduling_poll_votes.voted_at
+            "#,
+        )
+        .bind(vote.poll_id.to_string())
+        .bind(vote.participant.to_lowercase())
+        .bind(serde_json::to_string(&vote.choices)?)
+        .bind(vote.voted_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn list_poll_votes(
+        &self,
+        poll_id: Uuid,
+    ) -> Result<Vec<cove_core::PollVote>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT * FROM scheduling_poll_votes WHERE poll_id = ?1 ORDER BY participant ASC",
+        )
+        .bind(poll_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter()
+            .map(|row| {
+                Ok(cove_core::PollVote {
+                    poll_id: parse_uuid(&row.try_get::<String, _>("poll_id")?, "scheduling_poll_votes.poll_id")?,
+                    participant: row.try_get("participant")?,
+                    choices: parse_json(&row.try_get::<String, _>("choices_json")?, "scheduling_poll_votes.choices_json")?,
+                    voted_at: parse_datetime(&row.try_get::<String, _>("voted_at")?, "scheduling_poll_votes.voted_at")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Messages whose subject carries a poll's token, oldest first.
+    pub async fn list_poll_reply_messages(
+        &self,
+        account_id: Uuid,
+        token: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<cove_core::MailMessage>, StorageError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM mail_messages
+            WHERE account_id = ?1 AND instr(subject, ?2) > 0 AND received_at >= ?3
+            ORDER BY received_at ASC
+            "#,
+        )
+        .bind(account_id.to_string())
+        .bind(token)
+        .bind(since.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(Self::row_to_mail_message).collect()
+    }
+
+    fn row_to_scheduling_poll(
+        row: sqlx::sqlite::SqliteRow,
+    ) -> Result<cove_core::SchedulingPoll, StorageError> {
+        let status: String = row.try_get("status")?;
+        let confirmed_option: Option<i64> = row.try_get("confirmed_option")?;
+        let event_id: Option<String> = row.try_get("event_id")?;
+        Ok(cove_core::SchedulingPoll {
+            id: parse_uuid(&row.try_get::<String, _>("id")?, "scheduling_polls.id")?,
+            account_id: parse_uuid(&row.try_get::<String, _>("account_id")?, "scheduling_polls.account_id")?,
+            title: row.try_get("title")?,
+            description: row.try_get("description")?,
+            options: parse_json(&row.try_get::<String, _>("options_json")?, "scheduling_polls.options_json")?,
+            participants: parse_json(&row.try_get::<String, _>("participants_json")?, "scheduling_polls.participants_json")?,
+            token: row.try_get("token")?,
+            status: parse_json(&format!("\"{status}\""), "scheduling_polls.status")?,
+            confirmed_option: confirmed_option.map(|index| index as usize),
+            event_id: event_id
+                .map(|id| parse_uuid(&id, "scheduling_polls.event_id"))
+                .transpose()?,
+            created_at: parse_datetime(&row.try_get::<String, _>("created_at")?, "scheduling_polls.created_at")?,
+            updated_at: parse_datetime(&row.try_get::<String, _>("updated_at")?, "scheduling_polls.updated_at")?,
+        })
+    }
+
+    // -- task lists ----------------------------------------------------------
+
+    /// Replace the stored lists for an account with a fresh discovery result.
+    pub async fn replace_task_lists(
+        &self,
+        account_id: Uuid,
+        lists: &[cove_core::TaskList],
+    ) -> Result<(), StorageError> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM task_lists WHERE account_id = ?1")
+            .bind(account_id.to_string())
+            .execute(&mut *tx)
+            .await?;
+        for list in lists {
+            sqlx::query(
+                r#"
+                INSERT OR REPLACE INTO task_lists (account_id, list_id, name, is_default, updated_at)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+                "#,
+            )
+            .bind(list.account_id.to_string())
+            .bind(&list.list_id)
+            .bind(&list.name)
+            .bind(list.is_default as i64)
+            .bind(list.updated_at.to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn upsert_task_list(&self, list: &cove_core::TaskList) -> Result<(), StorageError> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO task_lists (account_id, list_id, name, is_default, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            "#,
+        )
+        .bind(list.account_id.to_string())
+        .bind(&list.list_id)
+        .bind(&list.name)
+        .bind(list.is_default as i64)
+        .bind(list.updated_at.to_rfc3339())
+        .execute(&mut *tx)
+        .await?;
+        // Keep the denormalised name on tasks in step with a rename.
+        sqlx::query("UPDATE reminder_tasks SET list_name = ?1 WHERE account_id = ?2 AND list_id = ?3")
+            .bind(&list.name)
+            .bind(list.account_id.to_string())
+            .bind(&list.list_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn list_task_lists(
+        &self,
+        account_id: Uuid,
+    ) -> Result<Vec<cove_core::TaskList>, StorageError> {
+        let rows = sqlx::query(
+            "SELECT * FROM task_lists WHERE account_id = ?1 ORDER BY is_default DESC, name COLLATE NOCASE ASC",
+        )
+        .bind(account_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(cove_core::TaskList {
+                    account_id: parse_uuid(&row.try_get::<String, _>("account_id")?, "task_lists.account_id")?,
+                    list_id: row.try_get("list_id")?,
+                    name: row.try_get("name")?,
+                    is_default: row.try_get::<i64, _>("is_default")? != 0,
+                    updated_at: parse_datetime(&row.try_get::<String, _>("updated_at")?, "task_lists.updated_at")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Remove a list and its tasks; providers delete a list's tasks with it.
+    pub async fn delete_task_list(&self, account_id: Uuid, list_id: &str) -> Result<(), StorageError> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM reminder_tasks WHERE account_id = ?1 AND list_id = ?2")
+            .bind(account_id.to_string())
+            .bind(list_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM task_lists WHERE account_id = ?1 AND list_id = ?2")
+            .bind(account_id.to_string())
+            .bind(list_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // -- task time blocks ----------------------------------------------------
+
+    /// Link a task to its calendar block, replacing any earlier block.
+    pub async fn upsert_task_time_block(&self, block: &TaskTimeBlock) -> Result<(), StorageError> {
+        sqlx::query(
+            r#"
+            INSERT INTO task_time_blocks (
+              task_id, account_id, calendar_id, event_remote_id, completed, created_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            ON CONFLICT(task_id) DO UPDATE SET
+              account_id = excluded.account_id,
+              calendar_id = excluded.calendar_id,
+              event_remote_id = excluded.event_remote_id,
+              completed = excluded.completed,
+              created_at = excluded.created_at
+            "#,
+        )
+        .bind(block.task_id.to_string())
+        .bind(block.account_id.to_string())
+        .bind(&block.calendar_id)
+        .bind(&block.event_remote_id)
+        .bind(if block.completed { 1_i64 } else { 0_i64 })
+        .bind(block.created_at.to_rfc3339())
         .execute(&self.pool)
         .await?;
 
         Ok(())
     }
 
-    pub async fn list_tasks(&self, account_id: Uuid) -> Result<Vec<ReminderTask>, StorageError> {
-        let rows = sqlx::query(
+    pub async fn list_task_time_blocks(
+        &self,
+        account_id: Uuid,
+    ) -> Result<Vec<TaskTimeBlock>, StorageError> {
+        let rows = sqlx::query("SELECT * FROM task_time_blocks WHERE account_id = ?1")
+            .bind(account_id.to_string())
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter().map(Self::row_to_task_time_block).collect()
+    }
+
+    /// Record whether a task's time block is done and return the block's
+    /// calendar event, if the task has one and the event is stored locally.
+    pub async fn set_task_time_block_completed(
+        &self,
+        task_id: Uuid,
+        completed: bool,
+    ) -> Result<Option<CalendarEvent>, StorageError> {
+        sqlx::query("UPDATE task_time_blocks SET completed = ?1 WHERE task_id = ?2")
+            .bind(if completed { 1_i64 } else { 0_i64 })
+            .bind(task_id.to_string())
+            .execute(&self.pool)
+            .await?;
+
+        let row = sqlx::query(
             r#"
-            SELECT * FROM reminder_tasks
-            WHERE account_id = ?1
-            ORDER BY due_at ASC
+            SELECT e.* FROM calendar_events e
+            JOIN task_time_blocks b
+              ON b.account_id = e.account_id
+             AND b.calendar_id = e.calendar_id
+             AND b.event_remote_id = e.remote_id
+            WHERE b.task_id = ?1
             "#,
         )
-        .bind(account_id.to_string())
-        .fetch_all(&self.pool)
+        .bind(task_id.to_string())
+        .fetch_optional(&self.pool)
         .await?;
+        row.map(Self::row_to_calendar_event).transpose()
+    }
 
-        rows.into_iter().map(Self::row_to_task).collect()
+    /// If `event` is a task's time block, move the task's due date to the end
+    /// of the block. Returns the id of the task that was updated.
+    pub async fn sync_task_due_from_block(
+        &self,
+        event: &CalendarEvent,
+    ) -> Result<Option<Uuid>, StorageError> {
+        let row = sqlx::query(
+            r#"
+            SELECT task_id FROM task_time_blocks
+            WHERE account_id = ?1 AND calendar_id = ?2 AND event_remote_id = ?3
+            "#,
+        )
+        .bind(event.account_id.to_string())
+        .bind(&event.calendar_id)
+        .bind(&event.remote_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let task_id_raw: String = row.try_get("task_id")?;
+        let due = event.ends_at.to_rfc3339();
+
+        let result = sqlx::query(
+            r#"
+            UPDATE reminder_tasks SET due_at = ?1, updated_at = ?2
+            WHERE id = ?3 AND (due_at IS NULL OR due_at != ?1)
+            "#,
+        )
+        .bind(&due)
+        .bind(Utc::now().to_rfc3339())
+        .bind(&task_id_raw)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+        parse_uuid(&task_id_raw, "task_time_blocks.task_id").map(Some)
     }
 
-    /// List subtasks (tasks with a given parent_id).
-    pub async fn list_subtasks(&self, parent_id: Uuid) -> Result<Vec<ReminderTask>, StorageError> {
-        let rows = sqlx::query(
-            "SELECT * FROM reminder_tasks WHERE parent_id = ?1 ORDER BY priority DESC, due_at ASC",
+    // -- offline queue -------------------------------------------------------
+
+    /// Queue a change for replay. A queued change with the same kind and
+    /// target is replaced in place, keeping its position in the queue; a
+    /// failed or conflicting one is superseded and goes back to pending.
+    pub async fn enqueue_pending_operation(
+        &self,
+        op: &cove_core::PendingOperation,
+    ) -> Result<(), StorageError> {
+        let kind = serde_json::to_string(&op.kind)?;
+        let pending = serde_json::to_string(&cove_core::PendingOperationStatus::Pending)?;
+        if let Some(target_id) = op.target_id {
+            let replaced = sqlx::query(
+                r#"
+                UPDATE pending_operations
+                SET status = ?3, summary = ?4, payload_json = ?5, updated_at = ?6,
+                    last_error = ?8
+                WHERE account_id = ?7 AND kind = ?1 AND target_id = ?2
+                "#,
+            )
+            .bind(&kind)
+            .bind(target_id.to_string())
+            .bind(&pending)
+            .bind(&op.summary)
+            .bind(serde_json::to_string(&op.payload_json)?)
+            .bind(op.updated_at.to_rfc3339())
+            .bind(op.account_id.to_string())
+            .bind(&op.last_error)
+            .execute(&self.pool)
+            .await?;
+            if replaced.rows_affected() > 0 {
+                return Ok(());
+            }
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO pending_operations (
+              id, account_id, kind, status, target_id, summary, payload_json,
+              attempt_count, last_error, created_at, updated_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+            "#,
         )
-        .bind(parent_id.to_string())
-        .fetch_all(&self.pool)
+        .bind(op.id.to_string())
+        .bind(op.account_id.to_string())
+        .bind(&kind)
+        .bind(serde_json::to_string(&op.status)?)
+        .bind(op.target_id.map(|id| id.to_string()))
+        .bind(&op.summary)
+        .bind(serde_json::to_string(&op.payload_json)?)
+        .bind(i64::from(op.attempt_count))
+        .bind(&op.last_error)
+        .bind(op.created_at.to_rfc3339())
+        .bind(op.updated_at.to_rfc3339())
+        .execute(&self.pool)
         .await?;
+        Ok(())
+    }
 
-        rows.into_iter().map(Self::row_to_task).collect()
+    /// Every queued change, oldest first, whatever its status.
+    pub async fn list_pending_operations(
+        &self,
+    ) -> Result<Vec<cove_core::PendingOperation>, StorageError> {
+        let rows = sqlx::query("SELECT * FROM pending_operations ORDER BY created_at ASC")
+            .fetch_all(&self.pool)
+            .await?;
+        rows.into_iter().map(Self::row_to_pending_operation).collect()
     }
 
-    /// List top-level tasks (no parent) for an account, ordered by priority descending.
-    pub async fn list_tasks_by_priority(
+    pub async fn update_pending_operation_status(
         &self,
-        account_id: Uuid,
-    ) -> Result<Vec<ReminderTask>, StorageError> {
-        let rows = sqlx::query(
+        id: Uuid,
+        status: cove_core::PendingOperationStatus,
+        last_error: Option<String>,
+        attempt_count: Option<u32>,
+    ) -> Result<(), StorageError> {
+        sqlx::query(
             r#"
-            SELECT * FROM reminder_tasks
-            WHERE account_id = ?1 AND (parent_id IS NULL OR parent_id = '')
-            ORDER BY
-                CASE priority
-                    WHEN 'critical' THEN 0
-                    WHEN 'high' THEN 1
-                    WHEN 'normal' THEN 2
-                    WHEN 'low' THEN 3
-                    ELSE 4
-                END,
-                due_at ASC
+            UPDATE pending_operations
+            SET status = ?2,
+                last_error = ?3,
+                attempt_count = COALESCE(?4, attempt_count),
+                updated_at = ?5
+            WHERE id = ?1
             "#,
         )
-        .bind(account_id.to_string())
-        .fetch_all(&self.pool)
+        .bind(id.to_string())
+        .bind(serde_json::to_string(&status)?)
+        .bind(last_error)
+        .bind(attempt_count.map(i64::from))
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
         .await?;
+        Ok(())
+    }
 
-        rows.into_iter().map(Self::row_to_task).collect()
+    pub async fn delete_pending_operation(&self, id: Uuid) -> Result<(), StorageError> {
+        sqlx::query("DELETE FROM pending_operations WHERE id = ?1")
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
     }
 
     pub async fn enqueue_sync_job(&self, job: &SyncJob) -> Result<(), StorageError> {
@@ -1282,6 +5233,33 @@ impl Storage {
         })
     }
 
+    fn row_to_thread_summary(
+        row: sqlx::sqlite::SqliteRow,
+    ) -> Result<cove_core::MailThreadSummary, StorageError> {
+        let latest_raw: String = row.try_get("latest_at")?;
+        let mut participants: Vec<String> = parse_json(
+            &row.try_get::<String, _>("participants_json")?,
+            "thread_summaries.participants_json",
+        )?;
+        participants.sort();
+        participants.dedup();
+        let message_count: i64 = row.try_get("message_count")?;
+        let unread_count: i64 = row.try_get("unread_count")?;
+        let has_attachments: i64 = row.try_get("has_attachments")?;
+
+        Ok(cove_core::MailThreadSummary {
+            thread_id: row.try_get("thread_id")?,
+            subject: row.try_get("subject")?,
+            participants,
+            message_count: message_count as usize,
+            unread_count: unread_count as usize,
+            most_recent_at: parse_datetime(&latest_raw, "thread_summaries.latest_at")?,
+            snippet: row.try_get("snippet")?,
+            has_attachments: has_attachments != 0,
+            date_bucket: Default::default(),
+        })
+    }
+
     fn row_to_mail_message(
         row: sqlx::sqlite::SqliteRow,
     ) -> Result<cove_core::MailMessage, StorageError> {
@@ -1394,10 +5372,28 @@ impl Storage {
                 .flatten()
                 .and_then(|s| serde_json::from_str(&format!("\"{s}\"")).ok())
                 .unwrap_or_default(),
+            online_meeting_url: row.try_get("online_meeting_url")?,
             updated_at: parse_datetime(&updated_raw, "calendar_events.updated_at")?,
         })
     }
 
+    fn row_to_mail_merge(
+        row: sqlx::sqlite::SqliteRow,
+    ) -> Result<cove_core::MailMergeCampaign, StorageError> {
+        let id_raw: String = row.try_get("id")?;
+        let account_raw: String = row.try_get("account_id")?;
+        let created_raw: String = row.try_get("created_at")?;
+        Ok(cove_core::MailMergeCampaign {
+            id: parse_uuid(&id_raw, "mail_merge_campaigns.id")?,
+            account_id: parse_uuid(&account_raw, "mail_merge_campaigns.account_id")?,
+            name: row.try_get("name")?,
+            subject: row.try_get("subject")?,
+            body_text: row.try_get("body_text")?,
+            per_minute: row.try_get::<i64, _>("per_minute")?.clamp(1, i64::from(u32::MAX)) as u32,
+            created_at: parse_datetime(&created_raw, "mail_merge_campaigns.created_at")?,
+        })
+    }
+
     fn row_to_task(row: sqlx::sqlite::SqliteRow) -> Result<ReminderTask, StorageError> {
         let id_raw: String = row.try_get("id")?;
         let account_id_raw: String = row.try_get("account_id")?;
@@ -1412,6 +5408,7 @@ impl Storage {
             id: parse_uuid(&id_raw, "reminder_tasks.id")?,
             account_id: parse_uuid(&account_id_raw, "reminder_tasks.account_id")?,
             list_id: row.try_get("list_id")?,
+            list_name: row.try_get("list_name")?,
             remote_id: row.try_get("remote_id")?,
             title: row.try_get("title")?,
             notes: row.try_get("notes")?,
@@ -1442,6 +5439,56 @@ impl Storage {
                 .transpose()?,
             created_at: parse_datetime(&created_raw, "reminder_tasks.created_at")?,
             updated_at: parse_datetime(&updated_raw, "reminder_tasks.updated_at")?,
+            estimate_minutes: row
+                .try_get::<Option<i64>, _>("estimate_minutes")?
+                .map(|value| value.clamp(0, i64::from(u32::MAX)) as u32),
+        })
+    }
+
+    fn row_to_task_time_block(
+        row: sqlx::sqlite::SqliteRow,
+    ) -> Result<TaskTimeBlock, StorageError> {
+        let task_id_raw: String = row.try_get("task_id")?;
+        let account_id_raw: String = row.try_get("account_id")?;
+        let created_raw: String = row.try_get("created_at")?;
+        let completed: i64 = row.try_get("completed")?;
+
+        Ok(TaskTimeBlock {
+            task_id: parse_uuid(&task_id_raw, "task_time_blocks.task_id")?,
+            account_id: parse_uuid(&account_id_raw, "task_time_blocks.account_id")?,
+            calendar_id: row.try_get("calendar_id")?,
+            event_remote_id: row.try_get("event_remote_id")?,
+            completed: completed != 0,
+            created_at: parse_datetime(&created_raw, "task_time_blocks.created_at")?,
+        })
+    }
+
+    fn row_to_pending_operation(
+        row: sqlx::sqlite::SqliteRow,
+    ) -> Result<cove_core::PendingOperation, StorageError> {
+        let id_raw: String = row.try_get("id")?;
+        let account_id_raw: String = row.try_get("account_id")?;
+        let kind_raw: String = row.try_get("kind")?;
+        let status_raw: String = row.try_get("status")?;
+        let target_raw: Option<String> = row.try_get("target_id")?;
+        let payload_raw: String = row.try_get("payload_json")?;
+        let created_raw: String = row.try_get("created_at")?;
+        let updated_raw: String = row.try_get("updated_at")?;
+
+        Ok(cove_core::PendingOperation {
+            id: parse_uuid(&id_raw, "pending_operations.id")?,
+            account_id: parse_uuid(&account_id_raw, "pending_operations.account_id")?,
+            kind: parse_json(&kind_raw, "pending_operations.kind")?,
+            status: parse_json(&status_raw, "pending_operations.status")?,
+            target_id: target_raw
+                .map(|raw| parse_uuid(&raw, "pending_operations.target_id"))
+                .transpose()?,
+            summary: row.try_get("summary")?,
+            payload_json: parse_json(&payload_raw, "pending_operations.payload_json")?,
+            attempt_count: row.try_get::<i64, _>("attempt_count")? as u32,
+            last_error: row.try_get("last_error")?,
+            created_at: parse_datetime(&created_raw, "pending_operations.created_at")?,
+            updated_at: parse_datetime(&updated_raw, "pending_operations.updated_at")?,
         })
     }
 
@@ -1472,17 +5519,54 @@ impl Storage {
     }
 }
 
+/// Migration 0004 indexes a `tasks` table that no release created, and it
+/// cannot be edited without changing its checksum. Until it has applied, give
+/// it an empty placeholder to index; migration 0032 drops it again.
+async fn prepare_migration_0004(pool: &SqlitePool) -> Result<(), StorageError> {
+    let tracked: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master
+                         WHERE type = 'table' AND name = '_sqlx_migrations')",
+    )
+    .fetch_one(pool)
+    .await?;
+    let applied = tracked
+        && sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM _sqlx_migrations WHERE version = 4 AND success = 1)",
+        )
+        .fetch_one(pool)
+        .await?;
+    if !applied {
+        sqlx::query("CREATE TABLE IF NOT EXISTS tasks (parent_id TEXT, priority TEXT, due_at TEXT)")
+            .execute(pool)
+            .await?;
+    }
+    Ok(())
+}
+
 fn parse_uuid(raw: &str, field: &str) -> Result<Uuid, StorageError> {
     Uuid::parse_str(raw)
         .map_err(|err| StorageError::Data(format!("invalid uuid for {field}: {err}")))
 }
 
+/// Start and end of a UTC day as rfc3339 strings, for range filters.
+fn day_bounds(date: Option<chrono::NaiveDate>) -> (Option<String>, Option<String>) {
+    let bound = |hour, minute, second| {
+        date.and_then(|date| date.and_hms_opt(hour, minute, second))
+            .map(|moment| moment.and_utc().to_rfc3339())
+    };
+    (bound(0, 0, 0), bound(23, 59, 59))
+}
+
 fn parse_datetime(raw: &str, field: &str) -> Result<DateTime<Utc>, StorageError> {
     DateTime::parse_from_rfc3339(raw)
         .map(|dt| dt.with_timezone(&Utc))
         .map_err(|err| StorageError::Data(format!("invalid datetime for {field}: {err}")))
 }
 
+fn merge_status_str(status: &cove_core::MergeRecipientStatus) -> Result<String, StorageError> {
+    Ok(serde_json::to_string(status)?.trim_matches('"').to_string())
+}
+
 fn parse_json<T>(raw: &str, field: &str) -> Result<T, StorageError>
 where
     T: DeserializeOwned,
@@ -1490,3 +5574,470 @@ where
     serde_json::from_str(raw)
         .map_err(|err| StorageError::Data(format!("invalid json for {field}: {err}")))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sample::sample_mailbox;
+    use cove_core::MailMessage;
+    use chrono::Duration;
+
+    /// Storage in a fresh temporary directory, with the sample account so
+    /// messages satisfy the account foreign key.
+    async fn test_storage() -> (Storage, Uuid, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("cove-storage-test-{}", Uuid::new_v4()));
+        let storage = Storage::connect(&dir.join("cove.db"), &dir.join("search"), None)
+            .await
+            .unwrap();
+        let account = sample_mailbox(Utc::now()).account;
+        storage.upsert_account(&account).await.unwrap();
+        (storage, account.id, dir)
+    }
+
+    fn message(
+        account_id: Uuid,
+        folder: &str,
+        thread: &str,
+        received_at: DateTime<Utc>,
+    ) -> MailMessage {
+        let mut message = sample_mailbox(Utc::now()).messages.remove(0);
+        let id = Uuid::new_v4();
+        message.id = id;
+        message.account_id = account_id;
+        message.remote_id = id.to_string();
+        message.folder_path = folder.to_string();
+        message.thread_id = thread.to_string();
+        message.received_at = received_at;
+        message
+    }
+
+    #[tokio::test]
+    async fn migrations_index_reminder_tasks_not_the_placeholder() {
+        let (storage, _account_id, dir) = test_storage().await;
+        let names: Vec<String> = sqlx::query_scalar(
+            "SELECT name FROM sqlite_master
+              WHERE name IN ('tasks', 'idx_reminder_tasks_parent', 'idx_reminder_tasks_priority')
+              ORDER BY name",
+        )
+        .fetch_all(storage.pool())
+        .await
+        .unwrap();
+        assert_eq!(names, vec!["idx_reminder_tasks_parent", "idx_reminder_tasks_priority"]);
+
+        // Reopening an already-migrated database must not recreate it.
+        drop(storage);
+        let storage = Storage::connect(&dir.join("cove.db"), &dir.join("search2"), None)
+            .await
+            .unwrap();
+        let placeholder: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE name = 'tasks')",
+        )
+        .fetch_one(storage.pool())
+        .await
+        .unwrap();
+        assert!(!placeholder);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn purge_candidates_are_old_messages_of_one_folder() {
+        let (storage, account_id, dir) = test_storage().await;
+        let now = Utc::now();
+        let old = message(account_id, "Trash", "a", now - Duration::days(40));
+        let older = message(account_id, "Trash", "b", now - Duration::days(60));
+        let recent = message(account_id, "Trash", "c", now - Duration::days(5));
+        let elsewhere = message(account_id, "INBOX", "d", now - Duration::days(90));
+        for message in [&old, &older, &recent, &elsewhere] {
+            storage.upsert_mail_message(message).await.unwrap();
+        }
+
+        let cutoff = now - Duration::days(30);
+        let ids = |candidates: Vec<cove_core::PurgeCandidate>| {
+            candidates.into_iter().map(|c| c.message_id).collect::<Vec<_>>()
+        };
+        let found = storage
+            .list_purge_candidates(account_id, "Trash", cutoff, 10)
+            .await
+            .unwrap();
+        assert_eq!(ids(found), vec![older.id, old.id]);
+
+        let limited = storage
+            .list_purge_candidates(account_id, "Trash", cutoff, 1)
+            .await
+            .unwrap();
+        assert_eq!(ids(limited), vec![older.id]);
+
+        let other_account = storage
+            .list_purge_candidates(Uuid::new_v4(), "Trash", cutoff, 10)
+            .await
+            .unwrap();
+        assert!(other_account.is_empty());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn changed_uid_validity_drops_the_folder_cache() {
+        let (storage, account_id, dir) = test_storage().await;
+        let now = Utc::now();
+        let trash = message(account_id, "Trash", "a", now);
+        let inbox = message(account_id, "INBOX", "b", now);
+        storage.upsert_mail_message(&trash).await.unwrap();
+        storage.upsert_mail_message(&inbox).await.unwrap();
+
+        assert_eq!(storage.imap_uid_validity(account_id, "Trash").await.unwrap(), None);
+        assert!(!storage.record_imap_uid_validity(account_id, "Trash", 7).await.unwrap());
+        assert!(!storage.record_imap_uid_validity(account_id, "Trash", 7).await.unwrap());
+        assert_eq!(storage.imap_uid_validity(account_id, "Trash").await.unwrap(), Some(7));
+        assert!(storage.get_mail_message(trash.id).await.unwrap().is_some());
+
+        assert!(storage.record_imap_uid_validity(account_id, "Trash", 8).await.unwrap());
+        assert_eq!(storage.imap_uid_validity(account_id, "Trash").await.unwrap(), Some(8));
+        assert!(storage.get_mail_message(trash.id).await.unwrap().is_none());
+        assert!(storage.get_mail_message(inbox.id).await.unwrap().is_some());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn deletes_by_remote_id_within_one_folder() {
+        let (storage, account_id, dir) = test_storage().await;
+        let now = Utc::now();
+        let archived = message(account_id, "INBOX", "a", now);
+        let kept = message(account_id, "INBOX", "b", now);
+        let elsewhere = message(account_id, "Archive", "c", now);
+        for message in [&archived, &kept, &elsewhere] {
+            storage.upsert_mail_message(message).await.unwrap();
+        }
+
+        let remote_ids = vec![archived.remote_id.clone(), elsewhere.remote_id.clone()];
+        storage
+            .delete_mail_messages_by_remote_id(account_id, "INBOX", &remote_ids)
+            .await
+            .unwrap();
+        assert!(storage.get_mail_message(archived.id).await.unwrap().is_none());
+        assert!(storage.get_mail_message(kept.id).await.unwrap().is_some());
+        assert!(storage.get_mail_message(elsewhere.id).await.unwrap().is_some());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn auto_archive_mutes_are_per_account_and_compare_instants() {
+        let (storage, account_id, dir) = test_storage().await;
+        let mut other = sample_mailbox(Utc::now()).account;
+        other.id = Uuid::new_v4();
+        storage.upsert_account(&other).await.unwrap();
+
+        let muted_at: DateTime<Utc> = "2026-01-01T11:00:00Z".parse().unwrap();
+        storage
+            .mute_sender(&cove_core::SenderMute {
+                account_id,
+                email: "News@Example.com".to_string(),
+                muted_until: None,
+                auto_archive: true,
+                created_at: muted_at,
+            })
+            .await
+            .unwrap();
+
+        let from_news = |account_id: Uuid, received_at: DateTime<Utc>| {
+            let mut message = message(account_id, "INBOX", "a", received_at);
+            message.from[0].address = "news@example.com".to_string();
+            message
+        };
+        let after = from_news(account_id, muted_at + Duration::hours(1));
+        // 12:00+02:00 sorts after the mute as text but is an hour before it.
+        let before = from_news(account_id, muted_at);
+        let other_account = from_news(other.id, muted_at + Duration::hours(1));
+        for message in [&after, &before, &other_account] {
+            storage.upsert_mail_message(message).await.unwrap();
+        }
+        sqlx::query("UPDATE mail_messages SET received_at = ?1 WHERE id = ?2")
+            .bind("2026-01-01T12:00:00+02:00")
+            .bind(before.id.to_string())
+            .execute(storage.pool())
+            .await
+            .unwrap();
+
+        let candidates = storage
+            .list_auto_archive_candidates(account_id, "INBOX", 10)
+            .await
+            .unwrap();
+        assert_eq!(candidates.iter().map(|m| m.id).collect::<Vec<_>>(), vec![after.id]);
+        assert!(storage
+            .list_auto_archive_candidates(other.id, "INBOX", 10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        storage.unmute_sender(other.id, "news@example.com").await.unwrap();
+        assert_eq!(storage.list_sender_mutes().await.unwrap().len(), 1);
+        storage.unmute_sender(account_id, "news@example.com").await.unwrap();
+        assert!(storage.list_sender_mutes().await.unwrap().is_empty());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn thread_summaries_follow_inserts_flag_changes_and_deletes() {
+        let (storage, account_id, dir) = test_storage().await;
+        let now = Utc::now();
+        let summaries = || storage.list_thread_summaries(account_id, Some("INBOX"), 10, 0);
+
+        let mut first = message(account_id, "INBOX", "t", now - Duration::hours(2));
+        first.subject = "First".to_string();
+        first.flags.seen = false;
+        let mut second = message(account_id, "INBOX", "t", now - Duration::hours(1));
+        second.subject = "Second".to_string();
+        second.flags.seen = false;
+        let other = message(account_id, "INBOX", "u", now - Duration::hours(3));
+        for message in [&first, &second, &other] {
+            storage.upsert_mail_message(message).await.unwrap();
+        }
+
+        let listed = summaries().await.unwrap();
+        assert_eq!(
+            listed.iter().map(|t| t.thread_id.as_str()).collect::<Vec<_>>(),
+            ["t", "u"]
+        );
+        let thread = &listed[0];
+        assert_eq!((thread.message_count, thread.unread_count), (2, 2));
+        assert_eq!(thread.subject, "Second");
+        assert_eq!(thread.most_recent_at, second.received_at);
+
+        // A flag change through sync and through the read toggle.
+        first.flags.seen = true;
+        storage.upsert_mail_message(&first).await.unwrap();
+        assert_eq!(summaries().await.unwrap()[0].unread_count, 1);
+        storage.set_message_seen(second.id, true).await.unwrap();
+        assert_eq!(summaries().await.unwrap()[0].unread_count, 0);
+        storage.set_message_seen(second.id, false).await.unwrap();
+        assert_eq!(summaries().await.unwrap()[0].unread_count, 1);
+
+        // Deleting the newest message hands the thread to the one before.
+        storage.delete_mail_messages(&[second.id]).await.unwrap();
+        let thread = summaries().await.unwrap().remove(0);
+        assert_eq!((thread.message_count, thread.unread_count), (1, 0));
+        assert_eq!(thread.subject, "First");
+        assert_eq!(thread.most_recent_at, first.received_at);
+
+        // Deleting the last message drops the thread's row.
+        storage.delete_mail_messages(&[first.id]).await.unwrap();
+        let listed = summaries().await.unwrap();
+        assert_eq!(
+            listed.iter().map(|t| t.thread_id.as_str()).collect::<Vec<_>>(),
+            ["u"]
+        );
+        assert_eq!(listed[0].message_count, 1);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    async fn queued_for_indexing(storage: &Storage) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM search_index_queue")
+            .fetch_one(storage.pool())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn indexer_drains_the_queue_in_batches_and_counts_progress() {
+        let (storage, account_id, dir) = test_storage().await;
+        storage.pause_indexing();
+        let now = Utc::now();
+        let total = INDEX_BATCH_SIZE as usize + 20;
+        let mut messages: Vec<MailMessage> = (0..total)
+            .map(|n| message(account_id, "INBOX", &format!("t{n}"), now))
+            .collect();
+        messages[total - 1].subject = "Quokka itinerary".to_string();
+        storage.upsert_mail_messages(&messages).await.unwrap();
+        assert_eq!(queued_for_indexing(&storage).await, total as i64);
+
+        assert_eq!(storage.index_next_batch().await.unwrap(), INDEX_BATCH_SIZE as usize);
+        let progress = storage.index_progress();
+        assert_eq!((progress.indexed, progress.pending), (INDEX_BATCH_SIZE as u64, 20));
+        assert!(progress.paused);
+        assert_eq!(progress.total(), total as u64);
+
+        assert_eq!(storage.index_next_batch().await.unwrap(), 20);
+        let progress = storage.index_progress();
+        assert_eq!((progress.indexed, progress.pending), (total as u64, 0));
+        assert!(progress.is_idle());
+        assert_eq!(storage.index_next_batch().await.unwrap(), 0);
+
+        let hits = storage.search.search("quokka", 10).unwrap();
+        assert_eq!(hits, vec![messages[total - 1].id.to_string()]);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn interrupted_batch_is_indexed_again_without_duplicates() {
+        let (storage, account_id, dir) = test_storage().await;
+        storage.pause_indexing();
+        let mut queued = message(account_id, "INBOX", "a", Utc::now());
+        queued.subject = "Wombat census".to_string();
+        storage.upsert_mail_message(&queued).await.unwrap();
+
+        // A crash after the index commit but before the queue was cleared
+        // leaves the entry queued; indexing it again must not duplicate it.
+        storage.search.index_messages(&[queued.clone()]).await.unwrap();
+        assert_eq!(queued_for_indexing(&storage).await, 1);
+        assert_eq!(storage.index_next_batch().await.unwrap(), 1);
+        assert_eq!(queued_for_indexing(&storage).await, 0);
+        let hits = storage.search.search("wombat", 10).unwrap();
+        assert_eq!(hits, vec![queued.id.to_string()]);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn rebuild_clears_the_index_and_requeues_every_message() {
+        let (storage, account_id, dir) = test_storage().await;
+        storage.pause_indexing();
+        let mut first = message(account_id, "INBOX", "a", Utc::now());
+        first.subject = "Platypus report".to_string();
+        let second = message(account_id, "INBOX", "b", Utc::now());
+        storage.upsert_mail_messages(&[first.clone(), second]).await.unwrap();
+        while storage.index_next_batch().await.unwrap() > 0 {}
+        assert_eq!(storage.search.search("platypus", 10).unwrap().len(), 1);
+
+        let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM mail_messages")
+            .fetch_one(storage.pool())
+            .await
+            .unwrap();
+        assert_eq!(storage.rebuild_search_index().await.unwrap(), stored as u64);
+        assert!(storage.search.search("platypus", 10).unwrap().is_empty());
+        assert_eq!(queued_for_indexing(&storage).await, stored);
+
+        while storage.index_next_batch().await.unwrap() > 0 {}
+        let hits = storage.search.search("platypus", 10).unwrap();
+        assert_eq!(hits, vec![first.id.to_string()]);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn paused_worker_leaves_the_queue_until_resumed() {
+        let (storage, account_id, dir) = test_storage().await;
+        storage.pause_indexing();
+        let mut queued = message(account_id, "INBOX", "a", Utc::now());
+        queued.subject = "Echidna sighting".to_string();
+        storage.upsert_mail_message(&queued).await.unwrap();
+
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        assert_eq!(queued_for_indexing(&storage).await, 1);
+        assert!(storage.search.search("echidna", 10).unwrap().is_empty());
+
+        storage.resume_indexing();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        while queued_for_indexing(&storage).await > 0 {
+            assert!(std::time::Instant::now() < deadline, "worker did not drain the queue");
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert!(!storage.index_progress().paused);
+        let hits = storage.search.search("echidna", 10).unwrap();
+        assert_eq!(hits, vec![queued.id.to_string()]);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn task_estimate_survives_sync_and_can_be_cleared() {
+        let (storage, _account_id, dir) = test_storage().await;
+        let task = sample_mailbox(Utc::now()).tasks.remove(0);
+        storage.upsert_task(&task).await.unwrap();
+
+        storage.set_task_estimate(task.id, Some(45)).await.unwrap();
+        // Provider syncs carry no estimate and must not wipe the local one.
+        storage.upsert_task(&task).await.unwrap();
+        let stored = storage.get_task(task.id).await.unwrap().unwrap();
+        assert_eq!(stored.estimate_minutes, Some(45));
+
+        storage.set_task_estimate(task.id, None).await.unwrap();
+        let stored = storage.get_task(task.id).await.unwrap().unwrap();
+        assert_eq!(stored.estimate_minutes, None);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn completing_a_task_returns_its_time_block_event() {
+        let (storage, account_id, dir) = test_storage().await;
+        let mut sample = sample_mailbox(Utc::now());
+        let task = sample.tasks.remove(0);
+        let event = sample.events.remove(0);
+        storage.upsert_task(&task).await.unwrap();
+        storage.upsert_calendar_event(&event).await.unwrap();
+
+        // No block yet: nothing to mark.
+        let linked = storage.set_task_time_block_completed(task.id, true).await.unwrap();
+        assert!(linked.is_none());
+
+        storage
+            .upsert_task_time_block(&TaskTimeBlock {
+                task_id: task.id,
+                account_id,
+                calendar_id: event.calendar_id.clone(),
+                event_remote_id: event.remote_id.clone(),
+                completed: false,
+                created_at: Utc::now(),
+            })
+            .await
+            .unwrap();
+        let linked = storage.set_task_time_block_completed(task.id, true).await.unwrap();
+        assert_eq!(linked.map(|linked| linked.id), Some(event.id));
+        let blocks = storage.list_task_time_blocks(account_id).await.unwrap();
+        assert!(blocks[0].completed);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn queued_change_to_the_same_item_replaces_the_earlier_one() {
+        let (storage, account_id, dir) = test_storage().await;
+        let now = Utc::now();
+        let event_id = Uuid::new_v4();
+        let op = |summary: &str, at: DateTime<Utc>| cove_core::PendingOperation {
+            id: Uuid::new_v4(),
+            account_id,
+            kind: cove_core::PendingOperationKind::UpsertEvent,
+            status: cove_core::PendingOperationStatus::Pending,
+            target_id: Some(event_id),
+            summary: summary.to_string(),
+            payload_json: serde_json::json!({ "title": summary }),
+            attempt_count: 1,
+            last_error: None,
+            created_at: at,
+            updated_at: at,
+        };
+
+        let first = op("first", now);
+        storage.enqueue_pending_operation(&first).await.unwrap();
+        storage
+            .update_pending_operation_status(
+                first.id,
+                cove_core::PendingOperationStatus::Conflict,
+                Some("changed on the server".to_string()),
+                Some(2),
+            )
+            .await
+            .unwrap();
+        storage
+            .enqueue_pending_operation(&op("second", now + Duration::minutes(1)))
+            .await
+            .unwrap();
+
+        let queued = storage.list_pending_operations().await.unwrap();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].id, first.id);
+        assert_eq!(queued[0].summary, "second");
+        assert_eq!(queued[0].payload_json["title"], "second");
+        assert_eq!(queued[0].status, cove_core::PendingOperationStatus::Pending);
+        assert_eq!(queued[0].last_error, None);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
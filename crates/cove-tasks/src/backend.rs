@@ -1,5 +1,7 @@
 use crate::TaskError;
-use cove_core::{Account, ReminderTask, TaskPriority, TaskStatus};
+use cove_core::{
+    Account, HttpFailure, HttpFailureKind, ReminderTask, TaskList, TaskPriority, TaskStatus,
+};
 use async_trait::async_trait;
 use chrono::{DateTime, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Utc};
 use chrono_tz::Tz;
@@ -15,6 +17,16 @@ pub struct TaskSettings {
     pub list_id: String,
 }
 
+impl TaskSettings {
+    /// The same account settings pointed at another list.
+    pub fn for_list(&self, list_id: &str) -> Self {
+        Self {
+            list_id: list_id.to_string(),
+            ..self.clone()
+        }
+    }
+}
+
 #[async_trait]
 pub trait TaskBackend: Send + Sync {
     async fn sync_tasks(
@@ -29,6 +41,71 @@ pub trait TaskBackend: Send + Sync {
         settings: &TaskSettings,
         task: &ReminderTask,
     ) -> Result<(), TaskError>;
+
+    /// Lists on the account. Backends without list discovery report the
+    /// configured list only.
+    async fn list_task_lists(
+        &self,
+        account: &Account,
+        settings: &TaskSettings,
+    ) -> Result<Vec<TaskList>, TaskError> {
+        Ok(vec![TaskList {
+            account_id: account.id,
+            list_id: settings.list_id.clone(),
+            name: settings.list_id.clone(),
+            is_default: true,
+            updated_at: Utc::now(),
+        }])
+    }
+
+    async fn create_task_list(
+        &self,
+        _account: &Account,
+        _settings: &TaskSettings,
+        _name: &str,
+    ) -> Result<TaskList, TaskError> {
+        Err(TaskError::Unimplemented(
+            "task list creation is not supported for this provider".to_string(),
+        ))
+    }
+
+    async fn rename_task_list(
+        &self,
+        _account: &Account,
+        _settings: &TaskSettings,
+        _list_id: &str,
+        _name: &str,
+    ) -> Result<TaskList, TaskError> {
+        Err(TaskError::Unimplemented(
+            "task list rename is not supported for this provider".to_string(),
+        ))
+    }
+
+    async fn delete_task_list(
+        &self,
+        _account: &Account,
+        _settings: &TaskSettings,
+        _list_id: &str,
+    ) -> Result<(), TaskError> {
+        Err(TaskError::Unimplemented(
+            "task list deletion is not supported for this provider".to_string(),
+        ))
+    }
+
+    /// Move a task to another list and return its new remote id. Neither
+    /// Google Tasks nor Microsoft To Do can move across lists, so backends
+    /// recreate the task in the target list and delete the original.
+    async fn move_task(
+        &self,
+        _account: &Account,
+        _settings: &TaskSettings,
+        _task: &ReminderTask,
+        _to_list_id: &str,
+    ) -> Result<String, TaskError> {
+        Err(TaskError::Unimplemented(
+            "moving tasks between lists is not supported for this provider".to_string(),
+        ))
+    }
 }
 
 #[derive(Debug, Default)]
@@ -79,10 +156,7 @@ impl TaskBackend for CalDavTodoBackend {
 
         let response = request.send().await?;
         if !response.status().is_success() {
-            return Err(TaskError::Data(format!(
-                "CalDAV VTODO sync failed with status {}",
-                response.status()
-            )));
+            return Err(http_status_error("CalDAV VTODO sync", response.status()));
         }
 
         let payload = response.text().await?;
@@ -121,10 +195,7 @@ impl TaskBackend for CalDavTodoBackend {
 
         let response = request.send().await?;
         if !response.status().is_success() {
-            return Err(TaskError::Data(format!(
-                "CalDAV task upsert failed with status {}",
-                response.status()
-            )));
+            return Err(http_status_error("CalDAV task upsert", response.status()));
         }
 
         Ok(())
@@ -169,6 +240,27 @@ struct GraphTodoTask {
     recurrence: Option<serde_json::Value>,
 }
 
+#[derive(Debug, Deserialize)]
+struct GraphTodoListResponse {
+    value: Option<Vec<GraphTodoList>>,
+    #[serde(rename = "@odata.nextLink")]
+    next_link: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphTodoList {
+    id: String,
+    #[serde(rename = "displayName")]
+    display_name: Option<String>,
+    #[serde(rename = "wellknownListName")]
+    wellknown_list_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphCreatedItem {
+    id: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct GraphTaskBody {
     content: Option<String>,
@@ -205,10 +297,7 @@ impl TaskBackend for MicrosoftTodoBackend {
             .await?;
 
         if !response.status().is_success() {
-            return Err(TaskError::Data(format!(
-                "Graph tasks sync failed with status {}",
-                response.status()
-            )));
+            return Err(http_status_error("Graph tasks sync", response.status()));
         }
 
         let payload: GraphTodoResponse = response.json().await?;
@@ -241,6 +330,7 @@ impl TaskBackend for MicrosoftTodoBackend {
                 id: Uuid::new_v4(),
                 account_id: account.id,
                 list_id: settings.list_id.clone(),
+                list_name: None,
                 remote_id: item.id,
                 title,
                 notes,
@@ -256,6 +346,7 @@ impl TaskBackend for MicrosoftTodoBackend {
                 snoozed_until: None,
                 created_at,
                 updated_at,
+                estimate_minutes: None,
             });
         }
 
@@ -277,64 +368,7 @@ impl TaskBackend for MicrosoftTodoBackend {
             "https://graph.microsoft.com/v1.0/me/todo/lists/{}/tasks",
             settings.list_id
         );
-
-        let mut payload = serde_json::Map::new();
-        payload.insert(
-            "title".to_string(),
-            serde_json::Value::String(task.title.clone()),
-        );
-        payload.insert(
-            "status".to_string(),
-            serde_json::Value::String(task_status_to_graph_status(&task.status).to_string()),
-        );
-        payload.insert(
-            "importance".to_string(),
-            serde_json::Value::String(priority_to_graph_importance(&task.priority).to_string()),
-        );
-
-        if let Some(notes) = task.notes.as_deref() {
-            payload.insert(
-                "body".to_string(),
-                serde_json::json!({
-                    "contentType": "text",
-                    "content": notes,
-                }),
-            );
-        }
-
-        if let Some(due) = task.due_at {
-            payload.insert(
-                "dueDateTime".to_string(),
-                serde_json::json!({
-                    "dateTime": due.format("%Y-%m-%dT%H:%M:%S").to_string(),
-                    "timeZone": "UTC",
-                }),
-            );
-        }
-
-        if let Some(completed) = task.completed_at {
-            payload.insert(
-                "completedDateTime".to_string(),
-                serde_json::json!({
-                    "dateTime": completed.format("%Y-%m-%dT%H:%M:%S").to_string(),
-                    "timeZone": "UTC",
-                }),
-            );
-        }
-
-        if let Some(parent) = task.parent_id {
-            payload.insert(
-                "parentTaskId".to_string(),
-                serde_json::Value::String(parent.to_string()),
-            );
-        }
-
-        if let Some(recurrence) = task.repeat_rule.as_deref() {
-            payload.insert(
-                "recurrence".to_string(),
-                serde_json::json!({ "pattern": { "type": recurrence } }),
-            );
-        }
+        let payload = graph_task_payload(task);
 
         let response = if let Some(remote_id) = task.remote_id.as_deref() {
             self.http
@@ -353,14 +387,169 @@ impl TaskBackend for MicrosoftTodoBackend {
         };
 
         if !response.status().is_success() {
-            return Err(TaskError::Data(format!(
-                "Graph task upsert failed with status {}",
-                response.status()
-            )));
+            return Err(http_status_error("Graph task upsert", response.status()));
+        }
+
+        Ok(())
+    }
+
+    async fn list_task_lists(
+        &self,
+        account: &Account,
+        settings: &TaskSettings,
+    ) -> Result<Vec<TaskList>, TaskError> {
+        let token = graph_token(settings)?;
+        let now = Utc::now();
+        let mut lists = Vec::new();
+        let mut url = "https://graph.microsoft.com/v1.0/me/todo/lists".to_string();
+        loop {
+            let response = self.http.get(&url).bearer_auth(token).send().await?;
+            if !response.status().is_success() {
+                return Err(http_status_error("Graph task list discovery", response.status()));
+            }
+
+            let page: GraphTodoListResponse = response.json().await?;
+            for list in page.value.unwrap_or_default() {
+                lists.push(TaskList {
+                    account_id: account.id,
+                    name: list.display_name.unwrap_or_else(|| list.id.clone()),
+                    list_id: list.id,
+                    is_default: list.wellknown_list_name.as_deref() == Some("defaultList"),
+                    updated_at: now,
+                });
+            }
+            match page.next_link {
+                Some(next) => url = next,
+                None => break,
+            }
+        }
+
+        Ok(lists)
+    }
+
+    async fn create_task_list(
+        &self,
+        account: &Account,
+        settings: &TaskSettings,
+        name: &str,
+    ) -> Result<TaskList, TaskError> {
+        let token = graph_token(settings)?;
+        let response = self
+            .http
+            .post("https://graph.microsoft.com/v1.0/me/todo/lists")
+            .bearer_auth(token)
+            .json(&serde_json::json!({ "displayName": name }))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(http_status_error("Graph task list create", response.status()));
+        }
+
+        let created: GraphCreatedItem = response.json().await?;
+        Ok(TaskList {
+            account_id: account.id,
+            list_id: created.id,
+            name: name.to_string(),
+            is_default: false,
+            updated_at: Utc::now(),
+        })
+    }
+
+    async fn rename_task_list(
+        &self,
+        account: &Account,
+        settings: &TaskSettings,
+        list_id: &str,
+        name: &str,
+    ) -> Result<TaskList, TaskError> {
+        let token = graph_token(settings)?;
+        let response = self
+            .http
+            .patch(format!("https://graph.microsoft.com/v1.0/me/todo/lists/{list_id}"))
+            .bearer_auth(token)
+            .json(&serde_json::json!({ "displayName": name }))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(http_status_error("Graph task list rename", response.status()));
+        }
+
+        let renamed: GraphTodoList = response.json().await?;
+        Ok(TaskList {
+            account_id: account.id,
+            list_id: renamed.id,
+            name: renamed.display_name.unwrap_or_else(|| name.to_string()),
+            is_default: renamed.wellknown_list_name.as_deref() == Some("defaultList"),
+            updated_at: Utc::now(),
+        })
+    }
+
+    async fn delete_task_list(
+        &self,
+        _account: &Account,
+        settings: &TaskSettings,
+        list_id: &str,
+    ) -> Result<(), TaskError> {
+        let token = graph_token(settings)?;
+        let response = self
+            .http
+            .delete(format!("https://graph.microsoft.com/v1.0/me/todo/lists/{list_id}"))
+            .bearer_auth(token)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(http_status_error("Graph task list delete", response.status()));
         }
 
         Ok(())
     }
+
+    async fn move_task(
+        &self,
+        _account: &Account,
+        settings: &TaskSettings,
+        task: &ReminderTask,
+        to_list_id: &str,
+    ) -> Result<String, TaskError> {
+        let token = graph_token(settings)?;
+        let mut payload = graph_task_payload(task);
+        // Parent tasks do not follow the task into another list.
+        payload.remove("parentTaskId");
+
+        let response = self
+            .http
+            .post(format!(
+                "https://graph.microsoft.com/v1.0/me/todo/lists/{to_list_id}/tasks"
+            ))
+            .bearer_auth(token)
+            .json(&payload)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(http_status_error("Graph task move", response.status()));
+        }
+        let created: GraphCreatedItem = response.json().await?;
+
+        if let Some(remote_id) = task.remote_id.as_deref() {
+            let response = self
+                .http
+                .delete(format!(
+                    "https://graph.microsoft.com/v1.0/me/todo/lists/{}/tasks/{remote_id}",
+                    task.list_id
+                ))
+                .bearer_auth(token)
+                .send()
+                .await?;
+            if !response.status().is_success() {
+                return Err(TaskError::Data(format!(
+                    "Graph task move left the original in place: status {}",
+                    response.status()
+                )));
+            }
+        }
+
+        Ok(created.id)
+    }
 }
 
 #[derive(Debug, Default)]
@@ -381,6 +570,20 @@ struct GoogleTaskListResponse {
     items: Option<Vec<GoogleTaskItem>>,
 }
 
+#[derive(Debug, Deserialize)]
+struct GoogleTaskListsResponse {
+    items: Option<Vec<GoogleTaskListItem>>,
+    #[serde(rename = "nextPageToken")]
+    next_page_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GoogleTaskListItem {
+    id: String,
+    title: Option<String>,
+    updated: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct GoogleTaskItem {
     id: Option<String>,
@@ -421,10 +624,7 @@ impl TaskBackend for GoogleTasksBackend {
             .await?;
 
         if !response.status().is_success() {
-            return Err(TaskError::Data(format!(
-                "Google tasks sync failed with status {}",
-                response.status()
-            )));
+            return Err(http_status_error("Google tasks sync", response.status()));
         }
 
         let payload: GoogleTaskListResponse = response.json().await?;
@@ -457,6 +657,7 @@ impl TaskBackend for GoogleTasksBackend {
                 id: uuid::Uuid::new_v4(),
                 account_id: account.id,
                 list_id: settings.list_id.clone(),
+                list_name: None,
                 remote_id: item.id,
                 title,
                 notes: item.notes,
@@ -472,6 +673,7 @@ impl TaskBackend for GoogleTasksBackend {
                 snoozed_until: None,
                 created_at: updated_at,
                 updated_at,
+                estimate_minutes: None,
             });
         }
 
@@ -494,18 +696,7 @@ impl TaskBackend for GoogleTasksBackend {
             settings.list_id
         );
 
-        let body = serde_json::json!({
-            "title": task.title,
-            "notes": task.notes,
-            "due": task.due_at.map(|due| due.to_rfc3339()),
-            "status": match &task.status {
-                TaskStatus::Completed => "completed",
-                TaskStatus::NotStarted => "needsAction",
-                TaskStatus::InProgress => "needsAction",
-                TaskStatus::Canceled => "needsAction",
-            },
-            "parent": task.parent_id.map(|parent| parent.to_string()),
-        });
+        let body = google_task_body(task);
 
         let response = if let Some(remote_id) = &task.remote_id {
             self.http
@@ -524,14 +715,173 @@ impl TaskBackend for GoogleTasksBackend {
         };
 
         if !response.status().is_success() {
-            return Err(TaskError::Data(format!(
-                "Google task upsert failed with status {}",
-                response.status()
-            )));
+            return Err(http_status_error("Google task upsert", response.status()));
         }
 
         Ok(())
     }
+
+    async fn list_task_lists(
+        &self,
+        account: &Account,
+        settings: &TaskSettings,
+    ) -> Result<Vec<TaskList>, TaskError> {
+        let token = google_token(settings)?;
+        let now = Utc::now();
+        let mut lists = Vec::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut request = self
+                .http
+                .get("https://tasks.googleapis.com/tasks/v1/users/@me/lists")
+                .bearer_auth(token)
+                .query(&[("maxResults", "100")]);
+            if let Some(page_token) = &page_token {
+                request = request.query(&[("pageToken", page_token.as_str())]);
+            }
+
+            let response = request.send().await?;
+            if !response.status().is_success() {
+                return Err(http_status_error("Google task list discovery", response.status()));
+            }
+
+            let page: GoogleTaskListsResponse = response.json().await?;
+            for item in page.items.unwrap_or_default() {
+                lists.push(google_list_to_task_list(account, item, now));
+            }
+            page_token = page.next_page_token;
+            if page_token.is_none() {
+                break;
+            }
+        }
+
+        // Google has no default flag; the default list is always listed first.
+        if let Some(first) = lists.first_mut() {
+            first.is_default = true;
+        }
+        Ok(lists)
+    }
+
+    async fn create_task_list(
+        &self,
+        account: &Account,
+        settings: &TaskSettings,
+        name: &str,
+    ) -> Result<TaskList, TaskError> {
+        let token = google_token(settings)?;
+        let response = self
+            .http
+            .post("https://tasks.googleapis.com/tasks/v1/users/@me/lists")
+            .bearer_auth(token)
+            .json(&serde_json::json!({ "title": name }))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(http_status_error("Google task list create", response.status()));
+        }
+
+        let created: GoogleTaskListItem = response.json().await?;
+        Ok(google_list_to_task_list(account, created, Utc::now()))
+    }
+
+    async fn rename_task_list(
+        &self,
+        account: &Account,
+        settings: &TaskSettings,
+        list_id: &str,
+        name: &str,
+    ) -> Result<TaskList, TaskError> {
+        let token = google_token(settings)?;
+        let response = self
+            .http
+            .patch(format!(
+                "https://tasks.googleapis.com/tasks/v1/users/@me/lists/{list_id}"
+            ))
+            .bearer_auth(token)
+            .json(&serde_json::json!({ "title": name }))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(http_status_error("Google task list rename", response.status()));
+        }
+
+        let renamed: GoogleTaskListItem = response.json().await?;
+        Ok(google_list_to_task_list(account, renamed, Utc::now()))
+    }
+
+    async fn delete_task_list(
+        &self,
+        _account: &Account,
+        settings: &TaskSettings,
+        list_id: &str,
+    ) -> Result<(), TaskError> {
+        let token = google_token(settings)?;
+        let response = self
+            .http
+            .delete(format!(
+                "https://tasks.googleapis.com/tasks/v1/users/@me/lists/{list_id}"
+            ))
+            .bearer_auth(token)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(http_status_error("Google task list delete", response.status()));
+        }
+
+        Ok(())
+    }
+
+    async fn move_task(
+        &self,
+        _account: &Account,
+        settings: &TaskSettings,
+        task: &ReminderTask,
+        to_list_id: &str,
+    ) -> Result<String, TaskError> {
+        let token = google_token(settings)?;
+        let mut body = google_task_body(task);
+        // Parent tasks do not follow the task into another list.
+        if let Some(fields) = body.as_object_mut() {
+            fields.remove("parent");
+        }
+
+        let response = self
+            .http
+            .post(format!(
+                "https://tasks.googleapis.com/tasks/v1/lists/{to_list_id}/tasks"
+            ))
+            .bearer_auth(token)
+            .json(&body)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(http_status_error("Google task move", response.status()));
+        }
+        let created: GoogleTaskItem = response.json().await?;
+        let new_remote_id = created
+            .id
+            .ok_or_else(|| TaskError::Data("Google task move returned no id".to_string()))?;
+
+        if let Some(remote_id) = task.remote_id.as_deref() {
+            let response = self
+                .http
+                .delete(format!(
+                    "https://tasks.googleapis.com/tasks/v1/lists/{}/tasks/{remote_id}",
+                    task.list_id
+                ))
+                .bearer_auth(token)
+                .send()
+                .await?;
+            if !response.status().is_success() {
+                return Err(TaskError::Data(format!(
+                    "Google task move left the original in place: status {}",
+                    response.status()
+                )));
+            }
+        }
+
+        Ok(new_remote_id)
+    }
 }
 
 fn parse_caldav_vtodo_data(account_id: Uuid, list_id: &str, payload: &str) -> Vec<ReminderTask> {
@@ -604,6 +954,7 @@ fn parse_ical_vtodo_entries(
                 id: Uuid::new_v4(),
                 account_id,
                 list_id: list_id.to_string(),
+                list_name: None,
                 remote_id: uid.clone(),
                 title: title.clone().unwrap_or_else(|| "Untitled task".to_string()),
                 notes: notes.clone(),
@@ -616,6 +967,7 @@ fn parse_ical_vtodo_entries(
                 snoozed_until: None,
                 created_at: created,
                 updated_at: updated,
+                estimate_minutes: None,
             });
             continue;
         }
@@ -732,6 +1084,115 @@ fn render_single_vtodo_ics(task: &ReminderTask, remote_id: &str) -> String {
     out
 }
 
+fn graph_task_payload(task: &ReminderTask) -> serde_json::Map<String, serde_json::Value> {
+    let mut payload = serde_json::Map::new();
+    payload.insert(
+        "title".to_string(),
+        serde_json::Value::String(task.title.clone()),
+    );
+    payload.insert(
+        "status".to_string(),
+        serde_json::Value::String(task_status_to_graph_status(&task.status).to_string()),
+    );
+    payload.insert(
+        "importance".to_string(),
+        serde_json::Value::String(priority_to_graph_importance(&task.priority).to_string()),
+    );
+
+    if let Some(notes) = task.notes.as_deref() {
+        payload.insert(
+            "body".to_string(),
+            serde_json::json!({
+                "contentType": "text",
+                "content": notes,
+            }),
+        );
+    }
+
+    if let Some(due) = task.due_at {
+        payload.insert(
+            "dueDateTime".to_string(),
+            serde_json::json!({
+                "dateTime": due.format("%Y-%m-%dT%H:%M:%S").to_string(),
+                "timeZone": "UTC",
+            }),
+        );
+    }
+
+    if let Some(completed) = task.completed_at {
+        payload.insert(
+            "completedDateTime".to_string(),
+            serde_json::json!({
+                "dateTime": completed.format("%Y-%m-%dT%H:%M:%S").to_string(),
+                "timeZone": "UTC",
+            }),
+        );
+    }
+
+    if let Some(parent) = task.parent_id {
+        payload.insert(
+            "parentTaskId".to_string(),
+            serde_json::Value::String(parent.to_string()),
+        );
+    }
+
+    if let Some(recurrence) = task.repeat_rule.as_deref() {
+        payload.insert(
+            "recurrence".to_string(),
+            serde_json::json!({ "pattern": { "type": recurrence } }),
+        );
+    }
+
+    payload
+}
+
+fn google_task_body(task: &ReminderTask) -> serde_json::Value {
+    serde_json::json!({
+        "title": task.title,
+        "notes": task.notes,
+        "due": task.due_at.map(|due| due.to_rfc3339()),
+        "status": match &task.status {
+            TaskStatus::Completed => "completed",
+            TaskStatus::NotStarted => "needsAction",
+            TaskStatus::InProgress => "needsAction",
+            TaskStatus::Canceled => "needsAction",
+        },
+        "parent": task.parent_id.map(|parent| parent.to_string()),
+    })
+}
+
+fn google_list_to_task_list(
+    account: &Account,
+    item: GoogleTaskListItem,
+    fallback_updated: DateTime<Utc>,
+) -> TaskList {
+    TaskList {
+        account_id: account.id,
+        name: item.title.unwrap_or_else(|| item.id.clone()),
+        list_id: item.id,
+        is_default: false,
+        updated_at: item
+            .updated
+            .as_deref()
+            .and_then(parse_rfc3339)
+            .unwrap_or(fallback_updated),
+    }
+}
+
+fn graph_token(settings: &TaskSettings) -> Result<&str, TaskError> {
+    settings
+        .access_token
+        .as_deref()
+        .ok_or_else(|| TaskError::Data("missing Graph token".to_string()))
+}
+
+fn google_token(settings: &TaskSettings) -> Result<&str, TaskError> {
+    settings
+        .access_token
+        .as_deref()
+        .ok_or_else(|| TaskError::Data("missing Google token".to_string()))
+}
+
 fn parse_graph_datetime(value: &GraphDateTimeTimeZone) -> Option<DateTime<Utc>> {
     let raw = value.date_time.as_deref()?;
     if let Some(parsed) = parse_rfc3339(raw) {
@@ -936,3 +1397,11 @@ fn infer_priority(title: &str, notes: &str) -> TaskPriority {
         TaskPriority::Normal
     }
 }
+
+fn http_status_error(what: &str, status: reqwest::StatusCode) -> TaskError {
+    let failure = HttpFailure::from_status(what, status.as_u16());
+    match failure.kind {
+        HttpFailureKind::Unauthorized => TaskError::Auth(failure.message),
+        HttpFailureKind::Rejected => TaskError::Data(failure.message),
+    }
+}
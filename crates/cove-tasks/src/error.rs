@@ -6,6 +6,9 @@ pub enum TaskError {
     Storage(#[from] cove_storage::StorageError),
     #[error("http error: {0}")]
     Http(#[from] reqwest::Error),
+    /// The provider rejected the credentials or access token.
+    #[error("authentication failed: {0}")]
+    Auth(String),
     #[error("invalid data: {0}")]
     Data(String),
     #[error("unimplemented: {0}")]
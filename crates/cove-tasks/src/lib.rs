@@ -6,4 +6,4 @@ pub use backend::{
     CalDavTodoBackend, GoogleTasksBackend, MicrosoftTodoBackend, TaskBackend, TaskSettings,
 };
 pub use error::TaskError;
-pub use service::{NaturalTaskInput, TaskService};
+pub use service::{ActionItemDraft, NaturalTaskInput, TaskService, TaskSource};
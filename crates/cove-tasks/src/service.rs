@@ -2,10 +2,13 @@ use crate::{
     CalDavTodoBackend, GoogleTasksBackend, MicrosoftTodoBackend, TaskBackend, TaskError,
     TaskSettings,
 };
-use cove_core::{Account, Provider, ReminderTask, TaskPriority, TaskStatus};
+use cove_core::{
+    Account, MailMessage, Provider, ReminderTask, TaskList, TaskPriority, TaskStatus,
+};
 use cove_storage::Storage;
 use chrono::{DateTime, Duration, Utc};
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -16,6 +19,39 @@ pub struct NaturalTaskInput {
     pub list_id: String,
 }
 
+/// An extracted action item awaiting user review before it becomes a task.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionItemDraft {
+    pub title: String,
+    pub due_at: Option<DateTime<Utc>>,
+    pub priority: TaskPriority,
+}
+
+/// The message an action item was extracted from; recorded in the task notes
+/// so the task links back to its thread.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskSource {
+    pub account_id: Uuid,
+    pub thread_id: String,
+    pub message_id: Uuid,
+    pub subject: String,
+}
+
+impl TaskSource {
+    pub fn from_message(message: &MailMessage) -> Self {
+        Self {
+            account_id: message.account_id,
+            thread_id: message.thread_id.clone(),
+            message_id: message.id,
+            subject: message.subject.clone(),
+        }
+    }
+
+    pub fn link(&self) -> String {
+        format!("cove://thread/{}/{}", self.account_id, self.thread_id)
+    }
+}
+
 #[derive(Clone)]
 pub struct TaskService {
     storage: Storage,
@@ -34,15 +70,33 @@ impl TaskService {
         }
     }
 
+    /// Sync every list on the account, recording each task's list name.
     pub async fn sync_tasks(
         &self,
         account: &Account,
         settings: &TaskSettings,
     ) -> Result<Vec<ReminderTask>, TaskError> {
         let backend = self.backend_for(account);
-        let tasks = backend.sync_tasks(account, settings).await?;
-        for task in &tasks {
-            self.storage.upsert_task(task).await?;
+        let lists = self.discover_task_lists(account, settings).await?;
+
+        let mut tasks = Vec::new();
+        for list in &lists {
+            let list_settings = settings.for_list(&list.list_id);
+            for mut task in backend.sync_tasks(account, &list_settings).await? {
+                task.list_name = Some(list.name.clone());
+                if let Some(remote_id) = task.remote_id.as_deref() {
+                    if let Some(existing) =
+                        self.storage.task_id_by_remote(account.id, remote_id).await?
+                    {
+                        task.id = existing;
+                    }
+                }
+                self.storage.upsert_task(&task).await?;
+                self.storage
+                    .set_task_time_block_completed(task.id, task.completed_at.is_some())
+                    .await?;
+                tasks.push(task);
+            }
         }
         Ok(tasks)
     }
@@ -54,11 +108,136 @@ impl TaskService {
         task: &ReminderTask,
     ) -> Result<(), TaskError> {
         self.storage.upsert_task(task).await?;
+        // Completing a task marks its calendar time block as done.
+        self.storage
+            .set_task_time_block_completed(task.id, task.completed_at.is_some())
+            .await?;
         self.backend_for(account)
-            .upsert_task(account, settings, task)
+            .upsert_task(account, &settings.for_list(&task.list_id), task)
             .await
     }
 
+    /// Set or clear how long a task is expected to take, which sizes its
+    /// calendar time block. `Some(0)` clears the estimate.
+    pub async fn set_estimate(
+        &self,
+        task_id: Uuid,
+        minutes: Option<u32>,
+    ) -> Result<ReminderTask, TaskError> {
+        let minutes = minutes.filter(|minutes| *minutes > 0);
+        self.storage.set_task_estimate(task_id, minutes).await?;
+        self.storage
+            .get_task(task_id)
+            .await?
+            .ok_or_else(|| TaskError::Data("task not found".to_string()))
+    }
+
+    /// Fetch the account's lists from the provider and store them.
+    pub async fn discover_task_lists(
+        &self,
+        account: &Account,
+        settings: &TaskSettings,
+    ) -> Result<Vec<TaskList>, TaskError> {
+        let lists = self
+            .backend_for(account)
+            .list_task_lists(account, settings)
+            .await?;
+        self.storage.replace_task_lists(account.id, &lists).await?;
+        Ok(lists)
+    }
+
+    pub async fn create_task_list(
+        &self,
+        account: &Account,
+        settings: &TaskSettings,
+        name: &str,
+    ) -> Result<TaskList, TaskError> {
+        let name = non_empty_list_name(name)?;
+        let list = self
+            .backend_for(account)
+            .create_task_list(account, settings, name)
+            .await?;
+        self.storage.upsert_task_list(&list).await?;
+        Ok(list)
+    }
+
+    pub async fn rename_task_list(
+        &self,
+        account: &Account,
+        settings: &TaskSettings,
+        list_id: &str,
+        name: &str,
+    ) -> Result<TaskList, TaskError> {
+        let name = non_empty_list_name(name)?;
+        let list = self
+            .backend_for(account)
+            .rename_task_list(account, settings, list_id, name)
+            .await?;
+        self.storage.upsert_task_list(&list).await?;
+        Ok(list)
+    }
+
+    /// Delete a list and its tasks. The provider's default list is refused
+    /// up front since neither Google nor Microsoft allow deleting it.
+    pub async fn delete_task_list(
+        &self,
+        account: &Account,
+        settings: &TaskSettings,
+        list_id: &str,
+    ) -> Result<(), TaskError> {
+        let lists = self.storage.list_task_lists(account.id).await?;
+        if lists
+            .iter()
+            .any(|list| list.list_id == list_id && list.is_default)
+        {
+            return Err(TaskError::Data(
+                "the default task list cannot be deleted".to_string(),
+            ));
+        }
+
+        self.backend_for(account)
+            .delete_task_list(account, settings, list_id)
+            .await?;
+        self.storage.delete_task_list(account.id, list_id).await?;
+        Ok(())
+    }
+
+    /// Move a task into another list. Local-only tasks are just relabelled;
+    /// synced tasks are recreated on the provider and get a new remote id.
+    pub async fn move_task(
+        &self,
+        account: &Account,
+        settings: &TaskSettings,
+        task: &ReminderTask,
+        to_list_id: &str,
+    ) -> Result<ReminderTask, TaskError> {
+        if task.list_id == to_list_id {
+            return Ok(task.clone());
+        }
+
+        let lists = self.storage.list_task_lists(account.id).await?;
+        let target = lists
+            .iter()
+            .find(|list| list.list_id == to_list_id)
+            .ok_or_else(|| TaskError::Data(format!("unknown task list {to_list_id}")))?;
+
+        let mut moved = task.clone();
+        if task.remote_id.is_some() {
+            let remote_id = self
+                .backend_for(account)
+                .move_task(account, settings, task, to_list_id)
+                .await?;
+            moved.remote_id = Some(remote_id);
+            moved.parent_id = None;
+        }
+        moved.list_id = target.list_id.clone();
+        moved.list_name = Some(target.name.clone());
+        moved.updated_at = Utc::now();
+
+        self.storage.upsert_task(&moved).await?;
+        Ok(moved)
+    }
+
     pub async fn create_from_natural_language(
         &self,
         input: NaturalTaskInput,
@@ -70,6 +249,7 @@ impl TaskService {
             id: Uuid::new_v4(),
             account_id: input.account_id,
             list_id: input.list_id,
+            list_name: None,
             remote_id: None,
             title: parsed.title,
             notes: parsed.notes,
@@ -82,12 +262,72 @@ impl TaskService {
             snoozed_until: parsed.snooze_until,
             created_at: now,
             updated_at: now,
+            estimate_minutes: None,
         };
 
         self.storage.upsert_task(&task).await?;
         Ok(task)
     }
 
+    /// Pre-fill review drafts from raw AI action-item lines, picking up any
+    /// due date or priority hints in the text.
+    pub fn draft_action_items(&self, lines: &[String]) -> Vec<ActionItemDraft> {
+        lines
+            .iter()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| parse_natural_task(line).ok())
+            .map(|parsed| ActionItemDraft {
+                title: parsed.title,
+                due_at: parsed.due_at,
+                priority: parsed.priority,
+            })
+            .collect()
+    }
+
+    /// Create one task per reviewed draft, each linking back to `source`.
+    pub async fn create_from_action_items(
+        &self,
+        account_id: Uuid,
+        list_id: &str,
+        drafts: &[ActionItemDraft],
+        source: &TaskSource,
+    ) -> Result<Vec<ReminderTask>, TaskError> {
+        let now = Utc::now();
+        let notes = format!("From email: {}\n{}", source.subject, source.link());
+
+        let mut created = Vec::new();
+        for draft in drafts {
+            let title = draft.title.trim();
+            if title.is_empty() {
+                continue;
+            }
+
+            let task = ReminderTask {
+                id: Uuid::new_v4(),
+                account_id,
+                list_id: list_id.to_string(),
+                list_name: None,
+                remote_id: None,
+                title: title.to_string(),
+                notes: Some(notes.clone()),
+                due_at: draft.due_at,
+                completed_at: None,
+                priority: draft.priority.clone(),
+                status: TaskStatus::NotStarted,
+                repeat_rule: None,
+                parent_id: None,
+                snoozed_until: None,
+                created_at: now,
+                updated_at: now,
+                estimate_minutes: None,
+            };
+            self.storage.upsert_task(&task).await?;
+            created.push(task);
+        }
+
+        Ok(created)
+    }
+
     fn backend_for(&self, account: &Account) -> Arc<dyn TaskBackend> {
         match account.provider {
             Provider::Gmail => self.google.clone(),
@@ -101,6 +341,14 @@ impl TaskService {
     }
 }
 
+fn non_empty_list_name(name: &str) -> Result<&str, TaskError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(TaskError::Data("task list name is empty".to_string()));
+    }
+    Ok(name)
+}
+
 #[derive(Debug)]
 struct ParsedTask {
     title: String,
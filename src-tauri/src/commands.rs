@@ -1,18 +1,43 @@
-use crate::state::{AppState, PendingOAuthSession};
-use cove_calendar::CalendarSettings;
-use cove_config::AppConfig;
+use crate::state::{
+    AppState, CalendarUpdatedEvent, NewMessagesEvent, PendingOAuthSession, SyncProgressEvent,
+    EVENT_CALENDAR_UPDATED, EVENT_INITIAL_SYNC, EVENT_NEW_MESSAGES, EVENT_REMINDER,
+    EVENT_SYNC_PROGRESS,
+};
+use cove_ai::{
+    draft_meeting_agenda, DigestLimits, DigestThread, AGENDA_LOOKBACK_DAYS, AGENDA_SCAN_LIMIT,
+};
+use cove_calendar::{
+    CalendarError, CalendarSettings, MeetingRequest, PollOptionTally, PollRequest,
+    IMIP_REQUEST_MIME,
+};
+use cove_config::{AppConfig, OnboardingFacts, OnboardingItem, OnboardingStep};
 use cove_core::{
-    Account, AccountProtocol, AiMode, CloudAiProvider, DataProvenance, OAuthProfile, Provider,
-    SearchResult, SyncDomain, SyncJob, SyncStatus,
+    Account, AccountAiPolicy, AccountProtocol, AccountReauth, AiMode, AnalyticsFormat,
+    CloudAiProvider, ContactGroup, DataProvenance, DiskSpaceReport, DryRunReport,
+    EmailSignature, EmailTemplate, GroupExpansion, GroupSend, InitialSyncProgress, MailAddress,
+    MailAnalytics, MailRule, MuteDuration, OAuthProfile, OfflineQueueStatus, Provider, Reminder,
+    ReplayOutcome, RuleAction, SearchResult, SenderMute, SyncDomain, SyncJob, SyncStatus,
+};
+use cove_email::{
+    next_working_time, thread_to_markdown, unique_download_path, AttachmentPolicy,
+    AttachmentRelease, AttachmentVerdict, BackendCapabilities, EmailError, EmailService,
+    OutgoingAttachment, OutgoingMail, ProtocolLog, ProtocolSettings, ReplayError, SaveAllReport,
+    TranscriptFormat, BACKFILL_PAGE_SIZE,
 };
-use cove_email::{OutgoingMail, ProtocolSettings};
-use cove_security::{OAuthWorkflow, SecretKey, SecretStore};
-use cove_storage::Storage;
-use cove_tasks::NaturalTaskInput;
-use chrono::{Duration, Utc};
+use cove_security::{
+    refresh_stored_access_token, OAuthWorkflow, SecretBackend, SecretKey, SecretStore,
+    SecurityError,
+};
+use cove_storage::{
+    is_sample_account, render_analytics, weekly_report_due, weekly_report_slot,
+    weekly_report_subject, weekly_report_text, ReminderHandler, Storage,
+    WEEKLY_REPORT_AUDIT_KIND,
+};
+use cove_tasks::{ActionItemDraft, NaturalTaskInput, TaskError, TaskSettings, TaskSource};
+use chrono::{DateTime, Duration, Local, Utc};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::Value;
-use std::{collections::{HashMap, BTreeMap}, sync::Arc};
+use std::{collections::{HashMap, BTreeMap}, path::PathBuf, sync::Arc};
 use tauri::{Emitter, State};
 use tauri_plugin_notification::NotificationExt;
 use tokio::task::JoinSet;
@@ -90,6 +115,69 @@ pub struct ThreadMessagesPayload {
     pub thread_id: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ThreadLabelPayload {
+    pub account_id: Uuid,
+    pub thread_id: String,
+    pub label: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LabelledThreadsPayload {
+    pub label: String,
+    pub account_id: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportThreadPayload {
+    pub account_id: Uuid,
+    pub thread_id: String,
+    pub format: TranscriptFormat,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveAllAttachmentsPayload {
+    pub message_ids: Vec<Uuid>,
+    /// Overrides the configured download directory for this save.
+    #[serde(default)]
+    pub directory: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportAnalyticsPayload {
+    pub days: u32,
+    pub format: AnalyticsFormat,
+    /// Defaults to a new file in the download directory.
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MuteSenderPayload {
+    pub account_id: Uuid,
+    pub email: String,
+    pub duration: MuteDuration,
+    #[serde(default)]
+    pub auto_archive: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReleaseAttachmentPayload {
+    pub attachment_id: Uuid,
+    pub file_name: String,
+    /// The user confirmed the warning from an earlier attempt.
+    #[serde(default)]
+    pub confirmed: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportMessagesPayload {
+    pub path: String,
+    #[serde(default)]
+    pub filter: cove_core::MessageExportFilter,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ListFoldersPayload {
     pub account_id: Uuid,
@@ -119,6 +207,8 @@ pub struct ExportIcsPayload {
 
 #[derive(Debug, Deserialize)]
 pub struct AiPromptPayload {
+    /// Account that owns the content, checked against its AI policy.
+    pub account_id: Uuid,
     pub subject: String,
     pub body: String,
     pub mode: AiMode,
@@ -127,11 +217,19 @@ pub struct AiPromptPayload {
 
 #[derive(Debug, Deserialize)]
 pub struct AiActionPayload {
+    /// Account that owns the content, checked against its AI policy.
+    pub account_id: Uuid,
     pub body: String,
     pub mode: AiMode,
     pub cloud_provider: Option<CloudAiProvider>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct AiDigestPayload {
+    pub mode: AiMode,
+    pub cloud_provider: Option<CloudAiProvider>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct AiActionToTasksPayload {
     pub account_id: Uuid,
@@ -141,18 +239,73 @@ pub struct AiActionToTasksPayload {
     pub cloud_provider: Option<CloudAiProvider>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct AiThreadTasksPayload {
+    pub account_id: Uuid,
+    pub thread_id: String,
+    pub mode: AiMode,
+    pub cloud_provider: Option<CloudAiProvider>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MeetingAgendaPayload {
+    pub account_id: Uuid,
+    pub title: String,
+    pub attendees: Vec<String>,
+    /// Draft the items with AI instead of listing open thread subjects.
+    #[serde(default)]
+    pub use_ai: bool,
+    pub mode: AiMode,
+    pub cloud_provider: Option<CloudAiProvider>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTaskDraftsPayload {
+    pub account_id: Uuid,
+    pub list_id: String,
+    pub thread_id: String,
+    pub drafts: Vec<ActionItemDraft>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct AiResult {
     pub output: String,
     pub provenance: DataProvenance,
 }
 
+#[derive(Debug, Serialize)]
+pub struct AiTaskDraftResult {
+    pub drafts: Vec<ActionItemDraft>,
+    pub provenance: DataProvenance,
+}
+
 #[derive(Debug, Serialize)]
 pub struct AiTaskExtractionResult {
     pub created: Vec<cove_core::ReminderTask>,
     pub provenance: DataProvenance,
 }
 
+#[derive(Debug, Serialize)]
+pub struct SecretsStatus {
+    /// `keychain` or `encrypted_file`.
+    pub backend: &'static str,
+    pub locked: bool,
+    /// No encrypted file exists yet, so unlocking sets a new passphrase.
+    pub first_unlock: bool,
+    /// An encrypted file from a session without a keychain is waiting to be
+    /// moved into the keychain; unlocking it performs the move.
+    pub pending_migration: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UnlockSecretsPayload {
+    pub passphrase: String,
+    /// The passphrase typed again. Required on first use, when unlocking
+    /// sets the passphrase, so a typo cannot seal the secrets for good.
+    #[serde(default)]
+    pub confirmation: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct SetSecretPayload {
     pub namespace: String,
@@ -164,6 +317,18 @@ pub struct SetSecretPayload {
 pub struct SendMailPayload {
     pub account_id: Uuid,
     pub outgoing: OutgoingMail,
+    /// Send even when the working-hours guardrail would defer it.
+    #[serde(default)]
+    pub send_anyway: bool,
+    /// Contact groups to expand into recipients at send time.
+    #[serde(default)]
+    pub groups: Vec<GroupSend>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SendMailResult {
+    /// Set when the message went to the outbox to wait for working hours.
+    pub deferred_until: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -206,6 +371,21 @@ pub struct ValidateLocalAiRuntimeResponse {
     pub valid: bool,
     pub errors: Vec<String>,
 }
+
+#[derive(Debug, Serialize)]
+pub struct ProtocolLogStatus {
+    pub enabled: bool,
+    /// Exchanges kept per account.
+    pub capacity: usize,
+    /// Logins with recorded exchanges.
+    pub accounts: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetProtocolLoggingPayload {
+    pub enabled: bool,
+    pub capacity: Option<usize>,
+}
 #[tauri::command]
 pub async fn bootstrap(state: State<'_, AppState>) -> Result<BootstrapResponse, String> {
     let config = state.config().await;
@@ -323,6 +503,37 @@ pub fn set_secret(state: State<'_, AppState>, payload: SetSecretPayload) -> Resu
     set_secret_guarded(&state.secrets, key, &payload.value)
 }
 
+#[tauri::command]
+pub fn secrets_status(state: State<'_, AppState>) -> SecretsStatus {
+    SecretsStatus {
+        backend: match state.secrets.backend() {
+            SecretBackend::Keychain => "keychain",
+            SecretBackend::EncryptedFile => "encrypted_file",
+        },
+        locked: state.secrets.is_locked(),
+        first_unlock: state.secrets.is_first_unlock(),
+        pending_migration: state.secrets.has_pending_migration(),
+    }
+}
+
+/// Unlock the encrypted secrets file; returns how many secrets were moved
+/// into the OS keychain.
+#[tauri::command]
+pub fn unlock_secrets(
+    state: State<'_, AppState>,
+    payload: UnlockSecretsPayload,
+) -> Result<usize, String> {
+    if state.secrets.is_first_unlock()
+        && payload.confirmation.as_deref() != Some(payload.passphrase.as_str())
+    {
+        return Err("Passphrases do not match".to_string());
+    }
+    state
+        .secrets
+        .unlock(&payload.passphrase)
+        .map_err(to_error_string)
+}
+
 #[tauri::command]
 pub async fn begin_oauth_pkce(
     state: State<'_, AppState>,
@@ -362,6 +573,7 @@ pub async fn begin_oauth_pkce(
                 csrf_state: session.csrf_state.clone(),
                 pkce_verifier: session.pkce_verifier,
                 created_at: Utc::now(),
+                reauth_account: None,
             },
         );
     }
@@ -398,6 +610,12 @@ pub async fn complete_oauth_pkce(
         .await
         .map_err(to_error_string)?;
 
+    if let Some(account_id) = session.reauth_account {
+        let account = store_reauth_tokens(&state, account_id, &token).await?;
+        state.oauth_sessions.write().await.remove(&payload.session_id);
+        return Ok(CompleteOAuthResponse { account });
+    }
+
     let now = Utc::now();
     let account = Account {
         id: Uuid::new_v4(),
@@ -457,6 +675,104 @@ pub async fn complete_oauth_pkce(
     Ok(CompleteOAuthResponse { account })
 }
 
+/// Accounts waiting for the user to sign in again.
+#[tauri::command]
+pub async fn list_account_reauth(state: State<'_, AppState>) -> Result<Vec<AccountReauth>, String> {
+    state
+        .storage
+        .list_account_reauth()
+        .await
+        .map_err(to_error_string)
+}
+
+/// Start the PKCE consent flow again for an existing account, reusing its
+/// provider, client ID and redirect URL. Finish it with `complete_oauth_pkce`.
+#[tauri::command]
+pub async fn begin_reauth(
+    state: State<'_, AppState>,
+    account_id: Uuid,
+) -> Result<BeginOAuthResponse, String> {
+    let account = state
+        .storage
+        .list_accounts()
+        .await
+        .map_err(to_error_string)?
+        .into_iter()
+        .find(|account| account.id == account_id)
+        .ok_or_else(|| "account not found".to_string())?;
+    let profile = account
+        .oauth_profile
+        .clone()
+        .ok_or_else(|| "account does not sign in with OAuth".to_string())?;
+
+    let workflow = OAuthWorkflow::new(profile.clone()).map_err(to_error_string)?;
+    let session = workflow.begin_pkce_session().map_err(to_error_string)?;
+
+    let session_id = Uuid::new_v4();
+    state.oauth_sessions.write().await.insert(
+        session_id,
+        PendingOAuthSession {
+            provider: account.provider,
+            email_address: account.email_address,
+            display_name: account.display_name,
+            oauth_profile: profile,
+            csrf_state: session.csrf_state.clone(),
+            pkce_verifier: session.pkce_verifier,
+            created_at: Utc::now(),
+            reauth_account: Some(account_id),
+        },
+    );
+
+    Ok(BeginOAuthResponse {
+        session_id,
+        authorization_url: session.authorization_url,
+    })
+}
+
+/// Replace the stored tokens of a re-consented account and lift its
+/// re-auth flag.
+async fn store_reauth_tokens(
+    state: &AppState,
+    account_id: Uuid,
+    token: &cove_security::OAuthTokenResult,
+) -> Result<Account, String> {
+    let account = state
+        .storage
+        .list_accounts()
+        .await
+        .map_err(to_error_string)?
+        .into_iter()
+        .find(|account| account.id == account_id)
+        .ok_or_else(|| "account not found".to_string())?;
+
+    set_secret_guarded(
+        &state.secrets,
+        SecretKey {
+            namespace: "oauth_access_token".to_string(),
+            id: account_id.to_string(),
+        },
+        &token.access_token,
+    )?;
+    if let Some(refresh_token) = &token.refresh_token {
+        set_secret_guarded(
+            &state.secrets,
+            SecretKey {
+                namespace: "oauth_refresh_token".to_string(),
+                id: account_id.to_string(),
+            },
+            refresh_token,
+        )?;
+    }
+
+    state
+        .storage
+        .clear_account_reauth(account_id)
+        .await
+        .map_err(to_error_string)?;
+    tracing::info!(%account_id, "account re-authenticated");
+    Ok(account)
+}
+
 #[tauri::command]
 pub async fn queue_sync_job(
     state: State<'_, AppState>,
@@ -511,7 +827,10 @@ pub async fn run_sync_queue(
         return Ok(summary);
     }
 
+    let total_jobs = jobs.len();
+    let mut finished_jobs = 0_usize;
     let context = SyncExecutionContext {
+        app_handle: app_handle.clone(),
         storage: state.storage.clone(),
         email: state.email.clone(),
         calendar: state.calendar.clone(),
@@ -550,14 +869,35 @@ pub async fn run_sync_queue(
 
         let context_for_worker = context.clone();
         workers.spawn(async move {
+            let (job_id, domain) = (job.id, job.domain.clone());
             let worker_summary = run_sync_job(context_for_worker, job).await?;
-            Ok::<(SyncRunSummary, Uuid, u8), String>((worker_summary, account_id, domain_slot))
+            Ok::<_, String>((worker_summary, job_id, account_id, domain))
         });
     }
 
     while let Some(joined) = workers.join_next().await {
-        let (worker_summary, account_id, domain_slot) = joined
+        let (worker_summary, job_id, account_id, domain) = joined
             .map_err(|error| format!("sync worker join failed: {error}"))??;
+        let domain_slot = sync_domain_slot(&domain);
+        finished_jobs += 1;
+        let status = if worker_summary.completed_jobs > 0 {
+            SyncStatus::Completed
+        } else if worker_summary.retried_jobs > 0 {
+            SyncStatus::Queued
+        } else {
+            SyncStatus::Failed
+        };
+        let _ = app_handle.emit(
+            EVENT_SYNC_PROGRESS,
+            &SyncProgressEvent {
+                job_id,
+                account_id,
+                domain,
+                status,
+                finished_jobs,
+                total_jobs,
+            },
+        );
 
         if let Some(active) = active_by_account.get_mut(&account_id) {
             *active = active.saturating_sub(1);
@@ -590,8 +930,9 @@ pub async fn run_sync_queue(
 
             let context_for_worker = context.clone();
             workers.spawn(async move {
+                let (job_id, domain) = (job.id, job.domain.clone());
                 let worker_summary = run_sync_job(context_for_worker, job).await?;
-                Ok::<(SyncRunSummary, Uuid, u8), String>((worker_summary, account_id, domain_slot))
+                Ok::<_, String>((worker_summary, job_id, account_id, domain))
             });
         }
     }
@@ -647,6 +988,44 @@ pub async fn search_mail(
         .map_err(to_error_string)
 }
 
+/// Search-bar query across mail, calendar events (`on:`, `attendee:`) and
+/// tasks (`due:`).
+#[tauri::command]
+pub async fn search_all(
+    state: State<'_, AppState>,
+    payload: SearchPayload,
+) -> Result<cove_core::UnifiedSearchResults, String> {
+    state
+        .storage
+        .search_all(&payload.query, payload.limit.unwrap_or(50))
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn search_suggestions(
+    state: State<'_, AppState>,
+    payload: SearchPayload,
+) -> Result<Vec<cove_core::SearchSuggestion>, String> {
+    state
+        .storage
+        .search_suggestions(&payload.query, payload.limit.unwrap_or(8))
+        .await
+        .map_err(to_error_string)
+}
+
+/// The query builder edits operators as fields and writes them back as the
+/// same query string the search field takes.
+#[tauri::command]
+pub fn parse_search_query(query: String) -> cove_core::SearchQueryDraft {
+    cove_core::SearchQueryDraft::parse(&query)
+}
+
+#[tauri::command]
+pub fn build_search_query(draft: cove_core::SearchQueryDraft) -> String {
+    draft.to_query()
+}
+
 #[tauri::command]
 pub async fn list_mail(
     state: State<'_, AppState>,
@@ -736,90 +1115,1916 @@ pub async fn list_thread_messages(
 }
 
 #[tauri::command]
-pub async fn get_mail_message(
+pub async fn label_thread(
     state: State<'_, AppState>,
-    message_id: Uuid,
-) -> Result<Option<cove_core::MailMessage>, String> {
+    payload: ThreadLabelPayload,
+) -> Result<(), String> {
     state
-        .storage
-        .get_mail_message(message_id)
+        .email
+        .label_thread(payload.account_id, &payload.thread_id, &payload.label)
         .await
         .map_err(to_error_string)
 }
 
 #[tauri::command]
-pub async fn send_mail(state: State<'_, AppState>, payload: SendMailPayload) -> Result<(), String> {
-    let account = state
-        .storage
-        .list_accounts()
+pub async fn unlabel_thread(
+    state: State<'_, AppState>,
+    payload: ThreadLabelPayload,
+) -> Result<(), String> {
+    state
+        .email
+        .unlabel_thread(payload.account_id, &payload.thread_id, &payload.label)
         .await
-        .map_err(to_error_string)?
-        .into_iter()
-        .find(|account| account.id == payload.account_id)
-        .ok_or_else(|| "account not found".to_string())?;
+        .map_err(to_error_string)
+}
 
-    let settings = state
-        .storage
-        .account_protocol_settings(account.id)
+#[tauri::command]
+pub async fn list_thread_labels(
+    state: State<'_, AppState>,
+    payload: ThreadMessagesPayload,
+) -> Result<Vec<String>, String> {
+    state
+        .email
+        .thread_labels(payload.account_id, &payload.thread_id)
         .await
-        .map_err(to_error_string)?
-        .ok_or_else(|| "protocol settings missing".to_string())?;
-
-    let mut settings: ProtocolSettings =
-        parse_domain_settings(&settings, "email").map_err(to_error_string)?;
-    hydrate_email_secrets(account.id, &state.secrets, &mut settings)?;
+        .map_err(to_error_string)
+}
 
+#[tauri::command]
+pub async fn list_thread_label_folders(
+    state: State<'_, AppState>,
+) -> Result<Vec<cove_core::ThreadLabelFolder>, String> {
     state
         .email
-        .send(&account, &settings, &payload.outgoing)
+        .thread_label_folders()
         .await
         .map_err(to_error_string)
 }
 
 #[tauri::command]
-pub async fn list_tasks(
+pub async fn list_labelled_threads(
     state: State<'_, AppState>,
-    account_id: Uuid,
-) -> Result<Vec<cove_core::ReminderTask>, String> {
+    payload: LabelledThreadsPayload,
+) -> Result<Vec<cove_core::MailThreadSummary>, String> {
     state
-        .storage
-        .list_tasks(account_id)
+        .email
+        .list_labelled_threads(&payload.label, payload.account_id, payload.limit.unwrap_or(200))
         .await
         .map_err(to_error_string)
 }
 
 #[tauri::command]
-pub async fn create_task_from_text(
+pub async fn export_thread(
     state: State<'_, AppState>,
-    payload: NaturalTaskPayload,
-) -> Result<cove_core::ReminderTask, String> {
+    payload: ExportThreadPayload,
+) -> Result<Vec<u8>, String> {
     state
-        .tasks
-        .create_from_natural_language(NaturalTaskInput {
-            text: payload.text,
-            account_id: payload.account_id,
-            list_id: payload.list_id,
-        })
+        .email
+        .export_thread(payload.account_id, &payload.thread_id, payload.format)
         .await
         .map_err(to_error_string)
 }
 
+/// How the attachment policy treats a file name, for showing Save/Open or
+/// a blocked badge before anything is downloaded.
 #[tauri::command]
-pub async fn import_calendar_ics(
+pub async fn attachment_verdict(
     state: State<'_, AppState>,
-    payload: ImportIcsPayload,
-) -> Result<Vec<cove_core::CalendarEvent>, String> {
+    file_name: String,
+) -> Result<AttachmentVerdict, String> {
+    let config = state.config().await;
+    Ok(attachment_policy(&config).check_name(&file_name))
+}
+
+/// Attachment bytes for saving or opening, or the verdict that holds them
+/// back; a `confirm` verdict, or `blocked` with `can_override`, is released
+/// by repeating the call with `confirmed` set.
+#[tauri::command]
+pub async fn release_attachment(
+    state: State<'_, AppState>,
+    payload: ReleaseAttachmentPayload,
+) -> Result<AttachmentRelease, String> {
+    let config = state.config().await;
     state
-        .calendar
-        .import_ics(
-            payload.account_id,
-            &payload.calendar_id,
-            &payload.ics_payload,
+        .email
+        .release_attachment(
+            payload.attachment_id,
+            &payload.file_name,
+            &attachment_policy(&config),
+            payload.confirmed,
         )
         .await
         .map_err(to_error_string)
 }
 
+/// Save every attachment of the given messages (a message, or all of a
+/// thread) into the download directory, never overwriting existing files.
+#[tauri::command]
+pub async fn save_all_attachments(
+    state: State<'_, AppState>,
+    payload: SaveAllAttachmentsPayload,
+) -> Result<SaveAllReport, String> {
+    let config = state.config().await;
+    let directory = match payload.directory.filter(|dir| !dir.trim().is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => config
+            .attachments
+            .download_dir()
+            .ok_or_else(|| "no download directory configured".to_string())?,
+    };
+
+    let mut messages = Vec::with_capacity(payload.message_ids.len());
+    for message_id in payload.message_ids {
+        if let Some(message) = state
+            .storage
+            .get_mail_message(message_id)
+            .await
+            .map_err(to_error_string)?
+        {
+            messages.push(message);
+        }
+    }
+
+    state
+        .email
+        .save_all_attachments(&messages, &directory, &attachment_policy(&config))
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn recent_downloads(
+    state: State<'_, AppState>,
+    limit: Option<i64>,
+) -> Result<Vec<cove_core::AttachmentDownload>, String> {
+    state
+        .email
+        .recent_downloads(limit.unwrap_or(20))
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn mute_sender(
+    state: State<'_, AppState>,
+    payload: MuteSenderPayload,
+) -> Result<SenderMute, String> {
+    state
+        .email
+        .mute_sender(
+            payload.account_id,
+            &payload.email,
+            payload.duration,
+            payload.auto_archive,
+        )
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn unmute_sender(
+    state: State<'_, AppState>,
+    account_id: Uuid,
+    email: String,
+) -> Result<(), String> {
+    state
+        .email
+        .unmute_sender(account_id, &email)
+        .await
+        .map_err(to_error_string)
+}
+
+/// Server-side operations the account's backend supports; the UI greys out
+/// the rest.
+#[tauri::command]
+pub async fn account_capabilities(
+    state: State<'_, AppState>,
+    account_id: Uuid,
+) -> Result<BackendCapabilities, String> {
+    let account = state
+        .storage
+        .list_accounts()
+        .await
+        .map_err(to_error_string)?
+        .into_iter()
+        .find(|account| account.id == account_id)
+        .ok_or_else(|| "account not found".to_string())?;
+    Ok(state.email.capabilities(&account))
+}
+
+/// Where the account's initial sync and backfill of older mail stand.
+#[tauri::command]
+pub async fn initial_sync_progress(
+    state: State<'_, AppState>,
+    account_id: Uuid,
+) -> Result<InitialSyncProgress, String> {
+    state
+        .email
+        .initial_sync_progress(account_id)
+        .await
+        .map_err(to_error_string)
+}
+
+/// Pause or resume backfilling older mail; new mail keeps syncing either way.
+#[tauri::command]
+pub async fn set_backfill_paused(
+    state: State<'_, AppState>,
+    account_id: Uuid,
+    paused: bool,
+) -> Result<InitialSyncProgress, String> {
+    state
+        .email
+        .set_backfill_paused(account_id, paused)
+        .await
+        .map_err(to_error_string)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SnoozeMessagePayload {
+    pub message_id: Uuid,
+    pub until: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetPinnedPayload {
+    pub message_id: Uuid,
+    pub pinned: bool,
+}
+
+#[tauri::command]
+pub async fn snooze_message(
+    state: State<'_, AppState>,
+    payload: SnoozeMessagePayload,
+) -> Result<(), String> {
+    if payload.until <= Utc::now() {
+        return Err("snooze time must be in the future".to_string());
+    }
+    state
+        .email
+        .snooze_message(payload.message_id, payload.until)
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn unsnooze_message(state: State<'_, AppState>, message_id: Uuid) -> Result<(), String> {
+    state
+        .email
+        .unsnooze_message(message_id)
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn set_pinned(
+    state: State<'_, AppState>,
+    payload: SetPinnedPayload,
+) -> Result<(), String> {
+    state
+        .email
+        .set_pinned(payload.message_id, payload.pinned)
+        .await
+        .map_err(to_error_string)
+}
+
+/// Rules, signatures and templates are saved whole; a nil id creates a new
+/// record and the saved record is returned with its id filled in.
+#[derive(Debug, Deserialize)]
+pub struct SaveRulePayload {
+    pub rule: MailRule,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DryRunRulePayload {
+    pub rule: MailRule,
+    /// Only mail in this folder; all folders when unset.
+    #[serde(default)]
+    pub folder_path: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DryRunBulkPayload {
+    pub message_ids: Vec<Uuid>,
+    pub action: RuleAction,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveSignaturePayload {
+    pub signature: EmailSignature,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveTemplatePayload {
+    pub template: EmailTemplate,
+}
+
+#[tauri::command]
+pub async fn list_rules(state: State<'_, AppState>) -> Result<Vec<MailRule>, String> {
+    state.storage.list_rules().await.map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn save_rule(
+    state: State<'_, AppState>,
+    payload: SaveRulePayload,
+) -> Result<MailRule, String> {
+    let mut rule = payload.rule;
+    if rule.name.trim().is_empty() {
+        return Err("rule name is required".to_string());
+    }
+    if rule.actions.is_empty() {
+        return Err("a rule needs at least one action".to_string());
+    }
+    if rule.id.is_nil() {
+        rule.id = Uuid::new_v4();
+    }
+    state
+        .storage
+        .upsert_rule(&rule)
+        .await
+        .map_err(to_error_string)?;
+    Ok(rule)
+}
+
+/// Which stored messages `rule` would change, without saving or applying it.
+#[tauri::command]
+pub async fn dry_run_rule(
+    state: State<'_, AppState>,
+    payload: DryRunRulePayload,
+) -> Result<DryRunReport, String> {
+    state
+        .email
+        .dry_run_rule(&payload.rule, payload.folder_path.as_deref())
+        .await
+        .map_err(to_error_string)
+}
+
+/// Which of the selected messages a bulk action would change, without
+/// applying it.
+#[tauri::command]
+pub async fn dry_run_bulk_action(
+    state: State<'_, AppState>,
+    payload: DryRunBulkPayload,
+) -> Result<DryRunReport, String> {
+    state
+        .email
+        .dry_run_bulk_action(&payload.message_ids, &payload.action)
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn delete_rule(state: State<'_, AppState>, rule_id: Uuid) -> Result<(), String> {
+    state
+        .storage
+        .delete_rule(rule_id)
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn list_signatures(
+    state: State<'_, AppState>,
+    account_id: Option<Uuid>,
+) -> Result<Vec<EmailSignature>, String> {
+    state
+        .storage
+        .list_signatures(account_id)
+        .await
+        .map_err(to_error_string)
+}
+
+/// Saving a default signature clears the default flag on the others for
+/// the same account.
+#[tauri::command]
+pub async fn save_signature(
+    state: State<'_, AppState>,
+    payload: SaveSignaturePayload,
+) -> Result<EmailSignature, String> {
+    let mut signature = payload.signature;
+    if signature.name.trim().is_empty() {
+        return Err("signature name is required".to_string());
+    }
+    if signature.id.is_nil() {
+        signature.id = Uuid::new_v4();
+    }
+    if signature.is_default {
+        let siblings = state
+            .storage
+            .list_signatures(signature.account_id)
+            .await
+            .map_err(to_error_string)?;
+        for mut sibling in siblings {
+            if sibling.id != signature.id
+                && sibling.is_default
+                && sibling.account_id == signature.account_id
+            {
+                sibling.is_default = false;
+                state
+                    .storage
+                    .upsert_signature(&sibling)
+                    .await
+                    .map_err(to_error_string)?;
+            }
+        }
+    }
+    state
+        .storage
+        .upsert_signature(&signature)
+        .await
+        .map_err(to_error_string)?;
+    Ok(signature)
+}
+
+#[tauri::command]
+pub async fn delete_signature(
+    state: State<'_, AppState>,
+    signature_id: Uuid,
+) -> Result<(), String> {
+    state
+        .storage
+        .delete_signature(signature_id)
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn list_templates(state: State<'_, AppState>) -> Result<Vec<EmailTemplate>, String> {
+    state.storage.list_templates().await.map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn save_template(
+    state: State<'_, AppState>,
+    payload: SaveTemplatePayload,
+) -> Result<EmailTemplate, String> {
+    let mut template = payload.template;
+    if template.name.trim().is_empty() {
+        return Err("template name is required".to_string());
+    }
+    if template.id.is_nil() {
+        template.id = Uuid::new_v4();
+    }
+    state
+        .storage
+        .upsert_template(&template)
+        .await
+        .map_err(to_error_string)?;
+    Ok(template)
+}
+
+#[tauri::command]
+pub async fn delete_template(state: State<'_, AppState>, template_id: Uuid) -> Result<(), String> {
+    state
+        .storage
+        .delete_template(template_id)
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn list_sender_mutes(state: State<'_, AppState>) -> Result<Vec<SenderMute>, String> {
+    state
+        .email
+        .list_sender_mutes()
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn list_contact_groups(state: State<'_, AppState>) -> Result<Vec<ContactGroup>, String> {
+    state
+        .email
+        .list_contact_groups()
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn save_contact_group(
+    state: State<'_, AppState>,
+    group: ContactGroup,
+) -> Result<ContactGroup, String> {
+    state
+        .email
+        .save_contact_group(group)
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn delete_contact_group(state: State<'_, AppState>, id: Uuid) -> Result<(), String> {
+    state
+        .email
+        .delete_contact_group(id)
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn message_group_expansions(
+    state: State<'_, AppState>,
+    message_id: Uuid,
+) -> Result<Vec<GroupExpansion>, String> {
+    let Some(message) = state
+        .storage
+        .get_mail_message(message_id)
+        .await
+        .map_err(to_error_string)?
+    else {
+        return Ok(Vec::new());
+    };
+    state
+        .email
+        .message_group_expansions(&message)
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn get_mail_message(
+    state: State<'_, AppState>,
+    message_id: Uuid,
+) -> Result<Option<cove_core::MailMessage>, String> {
+    state
+        .storage
+        .get_mail_message(message_id)
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn related_messages(
+    state: State<'_, AppState>,
+    message_id: Uuid,
+    limit: Option<usize>,
+) -> Result<cove_core::RelatedMessages, String> {
+    let message = state
+        .storage
+        .get_mail_message(message_id)
+        .await
+        .map_err(to_error_string)?
+        .ok_or_else(|| "message not found".to_string())?;
+    state
+        .storage
+        .related_messages(&message, limit.unwrap_or(8))
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn send_mail(
+    state: State<'_, AppState>,
+    payload: SendMailPayload,
+) -> Result<SendMailResult, String> {
+    let account = state
+        .storage
+        .list_accounts()
+        .await
+        .map_err(to_error_string)?
+        .into_iter()
+        .find(|account| account.id == payload.account_id)
+        .ok_or_else(|| "account not found".to_string())?;
+
+    let settings = state
+        .storage
+        .account_protocol_settings(account.id)
+        .await
+        .map_err(to_error_string)?
+        .ok_or_else(|| "protocol settings missing".to_string())?;
+
+    let mut settings: ProtocolSettings =
+        parse_domain_settings(&settings, "email").map_err(to_error_string)?;
+    hydrate_email_secrets(account.id, &state.secrets, &mut settings)?;
+
+    let mut outgoing = payload.outgoing;
+    let expansions = state
+        .email
+        .expand_group_sends(&mut outgoing, &payload.groups)
+        .await
+        .map_err(to_error_string)?;
+
+    let guardrails = state.config().await.send_guardrails;
+    let deferred_until =
+        next_working_time(&guardrails, &Local::now()).filter(|_| !payload.send_anyway);
+    if let Some(send_at) = deferred_until {
+        state
+            .email
+            .schedule_outgoing(account.id, &outgoing, send_at)
+            .await
+            .map_err(to_error_string)?;
+    } else {
+        send_with_token_refresh(&state, &account, &mut settings, &outgoing).await?;
+    }
+
+    // The message is on its way; a failed log only costs search recall.
+    if let Err(err) = state
+        .email
+        .record_group_expansions(account.id, &outgoing, &expansions)
+        .await
+    {
+        tracing::warn!(account_id = %account.id, error = %err, "failed to log group send");
+    }
+    Ok(SendMailResult { deferred_until })
+}
+
+/// Send scheduled and deferred mail that is due, staying within each
+/// account's send throttle. Returns how many messages went out.
+pub async fn process_outbox(state: &AppState) -> Result<usize, String> {
+    let due = state
+        .storage
+        .due_scheduled_messages()
+        .await
+        .map_err(to_error_string)?;
+    if due.is_empty() {
+        return Ok(0);
+    }
+
+    let guardrails = state.config().await.send_guardrails;
+    let accounts = state.storage.list_accounts().await.map_err(to_error_string)?;
+    let mut budgets: HashMap<Uuid, usize> = HashMap::new();
+    let mut sent = 0;
+    for message in due {
+        let Some(account) = accounts.iter().find(|account| account.id == message.account_id)
+        else {
+            continue;
+        };
+        let budget = match budgets.get(&account.id) {
+            Some(budget) => *budget,
+            None => state
+                .email
+                .outbox_budget(account.id, &guardrails)
+                .await
+                .map_err(to_error_string)?,
+        };
+        budgets.insert(account.id, budget);
+        if budget == 0 {
+            // Throttled; the rest goes out on a later tick.
+            continue;
+        }
+
+        let fallback_from = MailAddress {
+            name: Some(account.display_name.clone()),
+            address: account.email_address.clone(),
+        };
+        let result = async {
+            let mut settings = email_account_settings(state, account).await?;
+            let outgoing = state
+                .email
+                .scheduled_outgoing(&message, fallback_from)
+                .await
+                .map_err(to_error_string)?;
+            send_with_token_refresh(state, account, &mut settings, &outgoing).await?;
+            state
+                .email
+                .finish_scheduled(&message)
+                .await
+                .map_err(to_error_string)
+        }
+        .await;
+        match result {
+            Ok(()) => {
+                sent += 1;
+                budgets.insert(account.id, budget - 1);
+            }
+            Err(err) => {
+                tracing::warn!(message_id = %message.id, error = %err, "scheduled send failed");
+            }
+        }
+    }
+    Ok(sent)
+}
+
+/// Send, or queue the message for replay when the server is unreachable.
+async fn send_with_token_refresh(
+    state: &AppState,
+    account: &Account,
+    settings: &mut ProtocolSettings,
+    outgoing: &OutgoingMail,
+) -> Result<(), String> {
+    match send_refreshing_token(state, account, settings, outgoing).await {
+        Err(err) if err.is_network() => {
+            tracing::info!(account_id = %account.id, %err, "server unreachable, queueing message");
+            state
+                .email
+                .queue_send(account.id, outgoing, &err)
+                .await
+                .map_err(to_error_string)
+        }
+        result => result.map_err(to_error_string),
+    }
+}
+
+async fn send_refreshing_token(
+    state: &AppState,
+    account: &Account,
+    settings: &mut ProtocolSettings,
+    outgoing: &OutgoingMail,
+) -> Result<(), EmailError> {
+    let result = state.email.send(account, settings, outgoing).await;
+    match (result, account.oauth_profile.clone()) {
+        (Err(EmailError::Auth(reason)), Some(profile)) => {
+            // Access tokens are short-lived; refresh once and retry.
+            tracing::info!(account_id = %account.id, %reason, "refreshing oauth token after auth failure");
+            let access_token =
+                refresh_access_token(&state.storage, &state.secrets, account, profile).await?;
+            settings.access_token = Some(access_token);
+            state.email.send(account, settings, outgoing).await
+        }
+        (result, _) => result,
+    }
+}
+
+/// Refresh `account`'s OAuth access token. A grant the provider revoked
+/// flags the account for re-authentication, so the UI can offer to sign in
+/// again rather than show the raw failure.
+async fn refresh_access_token(
+    storage: &Storage,
+    secrets: &SecretStore,
+    account: &Account,
+    profile: OAuthProfile,
+) -> Result<String, EmailError> {
+    match refresh_stored_access_token(secrets, &account.id.to_string(), profile).await {
+        Ok(access_token) => Ok(access_token),
+        Err(SecurityError::ReauthRequired(reason)) => {
+            tracing::warn!(account_id = %account.id, %reason, "oauth grant revoked");
+            storage.mark_account_reauth(account.id, &reason).await?;
+            Err(EmailError::Auth(format!(
+                "{} needs to sign in again",
+                account.email_address
+            )))
+        }
+        Err(err) => Err(EmailError::Auth(format!("token refresh failed: {err}"))),
+    }
+}
+
+#[tauri::command]
+pub async fn list_pending_operations(
+    state: State<'_, AppState>,
+) -> Result<Vec<cove_core::PendingOperation>, String> {
+    state
+        .storage
+        .list_pending_operations()
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn replay_pending_operations(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<OfflineQueueStatus, String> {
+    let status = replay_offline_queue(&state).await?;
+    let _ = app_handle.emit("offline://status", &status);
+    Ok(status)
+}
+
+/// Put a failed or conflicting change back in the queue.
+#[tauri::command]
+pub async fn retry_pending_operation(
+    state: State<'_, AppState>,
+    operation_id: Uuid,
+) -> Result<(), String> {
+    state
+        .storage
+        .update_pending_operation_status(
+            operation_id,
+            cove_core::PendingOperationStatus::Pending,
+            None,
+            None,
+        )
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn discard_pending_operation(
+    state: State<'_, AppState>,
+    operation_id: Uuid,
+) -> Result<(), String> {
+    state
+        .storage
+        .delete_pending_operation(operation_id)
+        .await
+        .map_err(to_error_string)
+}
+
+/// Replay queued changes oldest first. An account whose provider is still
+/// unreachable keeps its remaining changes queued, in order.
+pub async fn replay_offline_queue(state: &AppState) -> Result<OfflineQueueStatus, String> {
+    let mut replay = state.email.begin_replay().await.map_err(to_error_string)?;
+    while let Some(op) = replay.next_operation() {
+        let result = replay_operation(state, &op).await;
+        state
+            .email
+            .record_replay(&mut replay, &op, result)
+            .await
+            .map_err(to_error_string)?;
+    }
+    Ok(replay.finish())
+}
+
+/// Push one queued change to its provider.
+async fn replay_operation(
+    state: &AppState,
+    op: &cove_core::PendingOperation,
+) -> Result<ReplayOutcome, ReplayError> {
+    let account = find_account(state, op.account_id)
+        .await
+        .map_err(ReplayError::permanent)?;
+    match op.kind {
+        cove_core::PendingOperationKind::SendMail => {
+            let outgoing = EmailService::queued_mail(op)?;
+            let mut settings = email_account_settings(state, &account)
+                .await
+                .map_err(ReplayError::permanent)?;
+            send_refreshing_token(state, &account, &mut settings, &outgoing).await?;
+            Ok(ReplayOutcome::Applied)
+        }
+        cove_core::PendingOperationKind::ArchiveMessages
+        | cove_core::PendingOperationKind::DeleteMessages => {
+            let settings = email_account_settings(state, &account)
+                .await
+                .map_err(ReplayError::permanent)?;
+            Ok(state
+                .email
+                .replay_message_change(&account, &settings, op)
+                .await?)
+        }
+        cove_core::PendingOperationKind::UpsertEvent => {
+            let settings = calendar_account_settings(state, &account)
+                .await
+                .map_err(ReplayError::permanent)?;
+            state
+                .calendar
+                .replay_event(&account, &settings, op)
+                .await
+                .map_err(|err| ReplayError::new(err.is_network(), err.to_string()))
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RetentionPreviewPayload {
+    pub account_id: Option<Uuid>,
+}
+
+/// What the retention policy would purge now, for one account or all.
+#[tauri::command]
+pub async fn retention_preview(
+    state: State<'_, AppState>,
+    payload: RetentionPreviewPayload,
+) -> Result<Vec<cove_core::PurgeCandidate>, String> {
+    let policy = state.config().await.retention;
+    let accounts = match payload.account_id {
+        Some(account_id) => vec![find_account(&state, account_id).await?],
+        None => state.storage.list_accounts().await.map_err(to_error_string)?,
+    };
+    let mut candidates = Vec::new();
+    for account in accounts {
+        candidates.extend(
+            state
+                .email
+                .retention_preview(account.id, &policy)
+                .await
+                .map_err(to_error_string)?,
+        );
+    }
+    Ok(candidates)
+}
+
+#[tauri::command]
+pub async fn purge_expired_mail(state: State<'_, AppState>) -> Result<usize, String> {
+    run_retention(&state).await
+}
+
+/// Re-check free disk space, switching sync to headers only while it is
+/// low. Returns the report when the sync fidelity changed.
+pub async fn monitor_disk_space(state: &AppState) -> Result<Option<DiskSpaceReport>, String> {
+    let policy = state.config().await.disk_space;
+    let before = state.email.sync_fidelity();
+    let report = state
+        .email
+        .check_disk_space(&policy)
+        .await
+        .map_err(to_error_string)?;
+    Ok((report.fidelity != before).then_some(report))
+}
+
+#[tauri::command]
+pub async fn disk_space_status(state: State<'_, AppState>) -> Result<DiskSpaceReport, String> {
+    let policy = state.config().await.disk_space;
+    state
+        .email
+        .check_disk_space(&policy)
+        .await
+        .map_err(to_error_string)
+}
+
+/// Purge old Trash and Junk mail on every account. One account failing does
+/// not stop the others; its error is returned only when nothing was purged.
+pub async fn run_retention(state: &AppState) -> Result<usize, String> {
+    let policy = state.config().await.retention;
+    let accounts = state.storage.list_accounts().await.map_err(to_error_string)?;
+    let mut purged = 0;
+    let mut first_error = None;
+    for account in accounts {
+        let result = match email_account_settings(state, &account).await {
+            Ok(settings) => state
+                .email
+                .purge_expired(&account, &settings, &policy)
+                .await
+                .map_err(to_error_string),
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(count) => purged += count,
+            Err(err) => {
+                tracing::warn!(account_id = %account.id, error = %err, "retention purge failed");
+                first_error.get_or_insert(err);
+            }
+        }
+    }
+    match first_error {
+        Some(err) if purged == 0 => Err(err),
+        _ => Ok(purged),
+    }
+}
+
+/// Backfill one page of older mail on every account that still has some,
+/// reporting progress to the webview. Returns the messages fetched.
+pub async fn run_backfill(
+    state: &AppState,
+    app_handle: &tauri::AppHandle,
+) -> Result<u64, String> {
+    let accounts = state.storage.list_accounts().await.map_err(to_error_string)?;
+    let reauth = state
+        .storage
+        .list_account_reauth()
+        .await
+        .map_err(to_error_string)?;
+    let mut fetched = 0;
+    for account in accounts {
+        if is_sample_account(account.id) || reauth.iter().any(|item| item.account_id == account.id)
+        {
+            continue;
+        }
+        let before = state
+            .email
+            .initial_sync_progress(account.id)
+            .await
+            .map_err(to_error_string)?;
+        if before.completed || before.paused {
+            continue;
+        }
+        let result = match email_account_settings(state, &account).await {
+            Ok(settings) => state
+                .email
+                .backfill_next(&account, &settings, BACKFILL_PAGE_SIZE)
+                .await
+                .map_err(to_error_string),
+            Err(err) => Err(err),
+        };
+        if let Err(err) = result {
+            tracing::warn!(account_id = %account.id, error = %err, "mail backfill failed");
+            continue;
+        }
+        let progress = state
+            .email
+            .initial_sync_progress(account.id)
+            .await
+            .map_err(to_error_string)?;
+        fetched += progress.messages_fetched - before.messages_fetched;
+        let _ = app_handle.emit(EVENT_INITIAL_SYNC, &progress);
+    }
+    Ok(fetched)
+}
+
+/// Mail analytics for the last `days` days, a week by default.
+#[tauri::command]
+pub async fn mail_analytics(
+    state: State<'_, AppState>,
+    days: Option<u32>,
+) -> Result<MailAnalytics, String> {
+    analytics_for_days(&state, days.unwrap_or(7)).await
+}
+
+/// Write analytics to a CSV or JSON file and return its path.
+#[tauri::command]
+pub async fn export_analytics(
+    state: State<'_, AppState>,
+    payload: ExportAnalyticsPayload,
+) -> Result<String, String> {
+    let analytics = analytics_for_days(&state, payload.days).await?;
+    let rendered = render_analytics(&analytics, payload.format).map_err(to_error_string)?;
+    let path = match payload.path {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let dir = state
+                .config()
+                .await
+                .attachments
+                .download_dir()
+                .ok_or_else(|| "no download directory available".to_string())?;
+            tokio::fs::create_dir_all(&dir)
+                .await
+                .map_err(to_error_string)?;
+            let file_name = format!(
+                "cove-mail-analytics-{}.{}",
+                Local::now().format("%Y-%m-%d"),
+                payload.format.extension()
+            );
+            unique_download_path(&dir, &file_name)
+        }
+    };
+    tokio::fs::write(&path, rendered)
+        .await
+        .map_err(to_error_string)?;
+    Ok(path.display().to_string())
+}
+
+async fn analytics_for_days(state: &AppState, days: u32) -> Result<MailAnalytics, String> {
+    let top = state.config().await.weekly_report.top_correspondents as usize;
+    let until = Utc::now();
+    state
+        .storage
+        .mail_analytics(until - Duration::days(i64::from(days.max(1))), until, top)
+        .await
+        .map_err(to_error_string)
+}
+
+/// Mail the weekly analytics report to the first real account when one is
+/// due. Returns whether a report was sent.
+pub async fn deliver_weekly_report(state: &AppState) -> Result<bool, String> {
+    let policy = state.config().await.weekly_report;
+    let last_sent = state
+        .storage
+        .last_weekly_report()
+        .await
+        .map_err(to_error_string)?;
+    let now = Local::now();
+    if !weekly_report_due(&policy, last_sent, &now) {
+        return Ok(false);
+    }
+    let Some(until) = weekly_report_slot(&policy, &now) else {
+        return Ok(false);
+    };
+    let accounts = state.storage.list_accounts().await.map_err(to_error_string)?;
+    let Some(account) = accounts
+        .into_iter()
+        .find(|account| !is_sample_account(account.id))
+    else {
+        return Ok(false);
+    };
+
+    let analytics = state
+        .storage
+        .mail_analytics(
+            until - Duration::days(7),
+            until,
+            policy.top_correspondents as usize,
+        )
+        .await
+        .map_err(to_error_string)?;
+    let subject = weekly_report_subject(&analytics);
+    send_account_mail(
+        state,
+        &account,
+        &[account.email_address.clone()],
+        subject.clone(),
+        weekly_report_text(&analytics),
+        Vec::new(),
+    )
+    .await?;
+
+    let entry = cove_core::AuditEntry {
+        id: Uuid::new_v4(),
+        account_id: Some(account.id),
+        kind: WEEKLY_REPORT_AUDIT_KIND.to_string(),
+        success: true,
+        summary: subject,
+        detail: serde_json::json!({
+            "since": analytics.since,
+            "until": analytics.until,
+            "received": analytics.received,
+            "sent": analytics.sent,
+        }),
+        created_at: Utc::now(),
+    };
+    state
+        .storage
+        .append_audit_entry(&entry)
+        .await
+        .map_err(to_error_string)?;
+    Ok(true)
+}
+
+/// Fire due snoozes, follow-ups and event and task alerts, then plan the
+/// next ones. Returns how many reminders fired.
+pub async fn run_reminders(
+    state: &AppState,
+    app_handle: &tauri::AppHandle,
+) -> Result<usize, String> {
+    let notifications = state.config().await.notifications;
+    let mut handler = WebviewReminders {
+        app_handle,
+        alerts_enabled: notifications.reminder_enabled,
+    };
+    state
+        .reminders
+        .poll(Utc::now(), &notifications.reminder_minutes_before, &mut handler)
+        .await
+        .map_err(to_error_string)
+}
+
+/// Forwards due reminders to the webview and shows alerts as desktop
+/// notifications.
+struct WebviewReminders<'a> {
+    app_handle: &'a tauri::AppHandle,
+    alerts_enabled: bool,
+}
+
+impl WebviewReminders<'_> {
+    fn deliver(&self, reminder: &Reminder, notification: Option<(String, String)>) -> bool {
+        let _ = self.app_handle.emit(EVENT_REMINDER, reminder);
+        if let Some((title, body)) = notification {
+            let _ = self
+                .app_handle
+                .notification()
+                .builder()
+                .title(title)
+                .body(body)
+                .show();
+        }
+        true
+    }
+}
+
+impl ReminderHandler for WebviewReminders<'_> {
+    fn mail_snooze_ended(&mut self, reminder: &Reminder) -> bool {
+        self.deliver(reminder, None)
+    }
+
+    fn follow_up_due(&mut self, reminder: &Reminder) -> bool {
+        let notification = ("Time to reply".to_string(), reminder.title.clone());
+        self.deliver(reminder, Some(notification))
+    }
+
+    fn event_alert(&mut self, reminder: &Reminder) -> bool {
+        let notification = self.alerts_enabled.then(|| {
+            (
+                format!("{} - {}", reminder.title, reminder.lead_label()),
+                reminder.detail.clone().unwrap_or_default(),
+            )
+        });
+        self.deliver(reminder, notification)
+    }
+
+    fn task_due(&mut self, reminder: &Reminder) -> bool {
+        let notification = self.alerts_enabled.then(|| {
+            (
+                format!("Task due {}", reminder.lead_label()),
+                reminder.title.clone(),
+            )
+        });
+        self.deliver(reminder, notification)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScheduleMeetingPayload {
+    pub account_id: Uuid,
+    #[serde(flatten)]
+    pub meeting: MeetingRequest,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreatePollPayload {
+    pub account_id: Uuid,
+    #[serde(flatten)]
+    pub poll: PollRequest,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PollResults {
+    pub poll: cove_core::SchedulingPoll,
+    pub votes: Vec<cove_core::PollVote>,
+    /// Options with who can make them, best supported first.
+    pub tally: Vec<PollOptionTally>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TaskListPayload {
+    pub account_id: Uuid,
+    pub list_id: Option<String>,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MoveTaskPayload {
+    pub task_id: Uuid,
+    pub to_list_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TaskEstimatePayload {
+    pub task_id: Uuid,
+    /// Minutes; `None` or zero clears the estimate.
+    pub minutes: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListAuditLogPayload {
+    pub account_id: Option<Uuid>,
+    /// Dotted event prefix such as `mail.send`.
+    pub kind: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[tauri::command]
+pub async fn list_audit_log(
+    state: State<'_, AppState>,
+    payload: ListAuditLogPayload,
+) -> Result<Vec<cove_core::AuditEntry>, String> {
+    state
+        .storage
+        .list_audit_entries(
+            payload.account_id,
+            payload.kind.as_deref(),
+            payload.limit.unwrap_or(200).clamp(1, 1000),
+        )
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn search_index_progress(
+    state: State<'_, AppState>,
+) -> Result<cove_storage::IndexProgress, String> {
+    Ok(state.storage.index_progress())
+}
+
+#[tauri::command]
+pub async fn set_search_indexing_paused(
+    state: State<'_, AppState>,
+    paused: bool,
+) -> Result<cove_storage::IndexProgress, String> {
+    if paused {
+        state.storage.pause_indexing();
+    } else {
+        state.storage.resume_indexing();
+    }
+    Ok(state.storage.index_progress())
+}
+
+#[tauri::command]
+pub async fn rebuild_search_index(state: State<'_, AppState>) -> Result<u64, String> {
+    state
+        .storage
+        .rebuild_search_index()
+        .await
+        .map_err(to_error_string)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreatePlusAliasPayload {
+    pub account_id: Uuid,
+    /// Sender address or domain the alias is handed out to.
+    pub sender: String,
+    pub tag: Option<String>,
+}
+
+#[tauri::command]
+pub async fn create_plus_alias(
+    state: State<'_, AppState>,
+    payload: CreatePlusAliasPayload,
+) -> Result<cove_core::PlusAlias, String> {
+    let account = state
+        .storage
+        .list_accounts()
+        .await
+        .map_err(to_error_string)?
+        .into_iter()
+        .find(|account| account.id == payload.account_id)
+        .ok_or_else(|| "account not found".to_string())?;
+
+    state
+        .email
+        .create_plus_alias(&account, &payload.sender, payload.tag.as_deref())
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn plus_alias_report(
+    state: State<'_, AppState>,
+    account_id: Option<Uuid>,
+) -> Result<Vec<cove_core::PlusAliasUsage>, String> {
+    state
+        .email
+        .plus_alias_report(account_id)
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn delete_plus_alias(state: State<'_, AppState>, alias_id: Uuid) -> Result<(), String> {
+    state
+        .storage
+        .delete_plus_alias(alias_id)
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn list_send_identities(
+    state: State<'_, AppState>,
+) -> Result<Vec<cove_core::SendIdentity>, String> {
+    state
+        .email
+        .send_identities()
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn reply_identity(
+    state: State<'_, AppState>,
+    message_id: Uuid,
+) -> Result<cove_core::ReplyIdentity, String> {
+    state
+        .email
+        .reply_identity(message_id)
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn list_labels(state: State<'_, AppState>) -> Result<Vec<cove_core::MailLabel>, String> {
+    state.storage.list_labels().await.map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn upsert_label(
+    state: State<'_, AppState>,
+    label: cove_core::MailLabel,
+) -> Result<(), String> {
+    state.storage.upsert_label(&label).await.map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn delete_label(state: State<'_, AppState>, name: String) -> Result<(), String> {
+    state.storage.delete_label(&name).await.map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn export_triage_bundle(
+    state: State<'_, AppState>,
+) -> Result<cove_core::TriageBundle, String> {
+    state
+        .storage
+        .export_triage_bundle()
+        .await
+        .map_err(to_error_string)
+}
+
+/// Write matching messages to a newline-delimited JSON file and return how
+/// many were written.
+#[tauri::command]
+pub async fn export_messages_json(
+    state: State<'_, AppState>,
+    payload: ExportMessagesPayload,
+) -> Result<u64, String> {
+    state
+        .storage
+        .export_messages_json(&payload.filter, std::path::Path::new(&payload.path))
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn import_triage_bundle(
+    state: State<'_, AppState>,
+    bundle: cove_core::TriageBundle,
+) -> Result<cove_core::TriageImportSummary, String> {
+    state
+        .storage
+        .import_triage_bundle(&bundle)
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub fn protocol_log_status() -> ProtocolLogStatus {
+    let log = ProtocolLog::global();
+    ProtocolLogStatus {
+        enabled: log.is_enabled(),
+        capacity: log.capacity(),
+        accounts: log.accounts(),
+    }
+}
+
+/// Start or stop recording protocol exchanges. Recording always starts off
+/// after a restart.
+#[tauri::command]
+pub fn set_protocol_logging(payload: SetProtocolLoggingPayload) -> ProtocolLogStatus {
+    let log = ProtocolLog::global();
+    if let Some(capacity) = payload.capacity {
+        log.set_capacity(capacity);
+    }
+    log.set_enabled(payload.enabled);
+    protocol_log_status()
+}
+
+#[tauri::command]
+pub fn list_protocol_exchanges(account: Option<String>) -> Vec<cove_core::ProtocolExchange> {
+    ProtocolLog::global().exchanges(account.as_deref())
+}
+
+#[tauri::command]
+pub fn clear_protocol_log() {
+    ProtocolLog::global().clear();
+}
+
+#[tauri::command]
+pub fn export_support_bundle(account: Option<String>) -> cove_core::SupportBundle {
+    ProtocolLog::global().support_bundle(account.as_deref())
+}
+
+#[tauri::command]
+pub async fn onboarding_checklist(
+    state: State<'_, AppState>,
+) -> Result<Vec<OnboardingItem>, String> {
+    let config = state.config().await;
+    let accounts = state.storage.list_accounts().await.map_err(to_error_string)?;
+    let ai_key_configured = match &config.ai.cloud.default_provider {
+        Some(provider) => state
+            .secrets
+            .get(&SecretKey {
+                namespace: "ai_api_key".to_string(),
+                id: format!("{provider:?}").to_ascii_lowercase(),
+            })
+            .unwrap_or_default()
+            .is_some_and(|key| !key.trim().is_empty()),
+        None => false,
+    };
+    let facts = OnboardingFacts {
+        has_account: accounts.iter().any(|account| !is_sample_account(account.id)),
+        ai_key_configured,
+    };
+    Ok(config.onboarding_checklist(&facts))
+}
+
+#[tauri::command]
+pub async fn confirm_onboarding_step(
+    state: State<'_, AppState>,
+    step: OnboardingStep,
+) -> Result<(), String> {
+    let mut config = state.config().await;
+    config.confirm_onboarding_step(step);
+    state.set_config(config).await.map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn dismiss_onboarding(state: State<'_, AppState>) -> Result<(), String> {
+    let mut config = state.config().await;
+    config.onboarding.dismissed = true;
+    state.set_config(config).await.map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn account_ai_policies(
+    state: State<'_, AppState>,
+) -> Result<BTreeMap<String, AccountAiPolicy>, String> {
+    Ok(state.config().await.ai.account_policies)
+}
+
+#[tauri::command]
+pub async fn set_account_ai_policy(
+    state: State<'_, AppState>,
+    account_id: Uuid,
+    policy: AccountAiPolicy,
+) -> Result<(), String> {
+    let mut config = state.config().await;
+    config.ai.set_account_policy(&account_id.to_string(), policy);
+    state.set_config(config).await.map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn load_sample_data(
+    state: State<'_, AppState>,
+) -> Result<cove_core::SampleDataSummary, String> {
+    state
+        .storage
+        .seed_sample_data()
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn remove_sample_data(state: State<'_, AppState>) -> Result<(), String> {
+    state
+        .storage
+        .remove_sample_data()
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn list_tasks(
+    state: State<'_, AppState>,
+    account_id: Uuid,
+) -> Result<Vec<cove_core::ReminderTask>, String> {
+    state
+        .storage
+        .list_tasks(account_id)
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn list_task_lists(
+    state: State<'_, AppState>,
+    account_id: Uuid,
+) -> Result<Vec<cove_core::TaskList>, String> {
+    state
+        .storage
+        .list_task_lists(account_id)
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn refresh_task_lists(
+    state: State<'_, AppState>,
+    account_id: Uuid,
+) -> Result<Vec<cove_core::TaskList>, String> {
+    let (account, settings) = task_account_settings(&state, account_id).await?;
+    state
+        .tasks
+        .discover_task_lists(&account, &settings)
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn create_task_list(
+    state: State<'_, AppState>,
+    payload: TaskListPayload,
+) -> Result<cove_core::TaskList, String> {
+    let (account, settings) = task_account_settings(&state, payload.account_id).await?;
+    state
+        .tasks
+        .create_task_list(&account, &settings, payload.name.as_deref().unwrap_or_default())
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn rename_task_list(
+    state: State<'_, AppState>,
+    payload: TaskListPayload,
+) -> Result<cove_core::TaskList, String> {
+    let list_id = payload
+        .list_id
+        .ok_or_else(|| "list_id is required".to_string())?;
+    let (account, settings) = task_account_settings(&state, payload.account_id).await?;
+    state
+        .tasks
+        .rename_task_list(
+            &account,
+            &settings,
+            &list_id,
+            payload.name.as_deref().unwrap_or_default(),
+        )
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn delete_task_list(
+    state: State<'_, AppState>,
+    payload: TaskListPayload,
+) -> Result<(), String> {
+    let list_id = payload
+        .list_id
+        .ok_or_else(|| "list_id is required".to_string())?;
+    let (account, settings) = task_account_settings(&state, payload.account_id).await?;
+    state
+        .tasks
+        .delete_task_list(&account, &settings, &list_id)
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn move_task_to_list(
+    state: State<'_, AppState>,
+    payload: MoveTaskPayload,
+) -> Result<cove_core::ReminderTask, String> {
+    let task = state
+        .storage
+        .get_task(payload.task_id)
+        .await
+        .map_err(to_error_string)?
+        .ok_or_else(|| "task not found".to_string())?;
+    let (account, settings) = task_account_settings(&state, task.account_id).await?;
+    state
+        .tasks
+        .move_task(&account, &settings, &task, &payload.to_list_id)
+        .await
+        .map_err(to_error_string)
+}
+
+/// Set how long a task should take; time blocks are sized from it.
+#[tauri::command]
+pub async fn set_task_estimate(
+    state: State<'_, AppState>,
+    payload: TaskEstimatePayload,
+) -> Result<cove_core::ReminderTask, String> {
+    state
+        .tasks
+        .set_estimate(payload.task_id, payload.minutes)
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn create_task_from_text(
+    state: State<'_, AppState>,
+    payload: NaturalTaskPayload,
+) -> Result<cove_core::ReminderTask, String> {
+    state
+        .tasks
+        .create_from_natural_language(NaturalTaskInput {
+            text: payload.text,
+            account_id: payload.account_id,
+            list_id: payload.list_id,
+        })
+        .await
+        .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn import_calendar_ics(
+    state: State<'_, AppState>,
+    payload: ImportIcsPayload,
+) -> Result<Vec<cove_core::CalendarEvent>, String> {
+    state
+        .calendar
+        .import_ics(
+            payload.account_id,
+            &payload.calendar_id,
+            &payload.ics_payload,
+        )
+        .await
+        .map_err(to_error_string)
+}
+
+/// Create a meeting on the account's calendar; Microsoft 365 accounts get a
+/// Teams link in `online_meeting_url`.
+#[tauri::command]
+pub async fn schedule_meeting(
+    state: State<'_, AppState>,
+    payload: ScheduleMeetingPayload,
+) -> Result<cove_core::CalendarEvent, String> {
+    let account = find_account(&state, payload.account_id).await?;
+    let settings = calendar_account_settings(&state, &account).await?;
+
+    let event = state
+        .calendar
+        .schedule_meeting(&account, &settings, payload.meeting)
+        .await
+        .map_err(to_error_string)?;
+
+    send_imip_invitation(&state, &account, &event)
+        .await
+        .map_err(|err| format!("meeting created but invitations were not sent: {err}"))?;
+    Ok(event)
+}
+
+#[tauri::command]
+pub async fn create_scheduling_poll(
+    state: State<'_, AppState>,
+    payload: CreatePollPayload,
+) -> Result<cove_core::SchedulingPoll, String> {
+    let account = find_account(&state, payload.account_id).await?;
+    let (poll, email) = state
+        .calendar
+        .create_poll(&account, payload.poll)
+        .await
+        .map_err(to_error_string)?;
+    send_account_mail(&state, &account, &email.recipients, email.subject, email.body_text, vec![])
+        .await
+        .map_err(|err| format!("poll saved but the email was not sent: {err}"))?;
+    Ok(poll)
+}
+
+#[tauri::command]
+pub async fn list_scheduling_polls(
+    state: State<'_, AppState>,
+    account_id: Uuid,
+) -> Result<Vec<cove_core::SchedulingPoll>, String> {
+    state
+        .storage
+        .list_scheduling_polls(account_id)
+        .await
+        .map_err(to_error_string)
+}
+
+/// Read answers from poll replies received so far.
+#[tauri::command]
+pub async fn refresh_scheduling_poll(
+    state: State<'_, AppState>,
+    account_id: Uuid,
+    poll_id: Uuid,
+) -> Result<PollResults, String> {
+    let account = find_account(&state, account_id).await?;
+    let votes = state
+        .calendar
+        .collect_poll_votes(&account, poll_id)
+        .await
+        .map_err(to_error_string)?;
+    let poll = state
+        .storage
+        .get_scheduling_poll(poll_id)
+        .await
+        .map_err(to_error_string)?
+        .ok_or_else(|| "poll not found".to_string())?;
+    let tally = cove_calendar::tally(&poll, &votes);
+    Ok(PollResults { poll, votes, tally })
+}
+
+/// Create the meeting for the chosen time and tell participants.
+#[tauri::command]
+pub async fn confirm_scheduling_poll(
+    state: State<'_, AppState>,
+    account_id: Uuid,
+    poll_id: Uuid,
+    option_index: usize,
+) -> Result<cove_core::CalendarEvent, String> {
+    let account = find_account(&state, account_id).await?;
+    let settings = calendar_account_settings(&state, &account).await?;
+    let (_, event, email) = state
+        .calendar
+        .confirm_poll(&account, &settings, poll_id, option_index)
+        .await
+        .map_err(to_error_string)?;
+
+    send_account_mail(&state, &account, &email.recipients, email.subject, email.body_text, vec![])
+        .await
+        .map_err(|err| format!("meeting created but participants were not told: {err}"))?;
+    send_imip_invitation(&state, &account, &event)
+        .await
+        .map_err(|err| format!("meeting created but invitations were not sent: {err}"))?;
+    Ok(event)
+}
+
+#[tauri::command]
+pub async fn cancel_scheduling_poll(
+    state: State<'_, AppState>,
+    poll_id: Uuid,
+) -> Result<cove_core::SchedulingPoll, String> {
+    state
+        .calendar
+        .cancel_poll(poll_id)
+        .await
+        .map_err(to_error_string)
+}
+
+fn attachment_policy(config: &AppConfig) -> AttachmentPolicy {
+    let attachments = &config.attachments;
+    AttachmentPolicy::new(
+        cove_email::DEFAULT_BLOCKED_EXTENSIONS
+            .iter()
+            .copied()
+            .chain(attachments.blocked_extensions.iter().map(String::as_str)),
+        &attachments.allowed_extensions,
+        attachments.block_encrypted_archives,
+        attachments.allow_open_anyway,
+    )
+}
+
+async fn find_account(state: &AppState, account_id: Uuid) -> Result<Account, String> {
+    state
+        .storage
+        .list_accounts()
+        .await
+        .map_err(to_error_string)?
+        .into_iter()
+        .find(|account| account.id == account_id)
+        .ok_or_else(|| "account not found".to_string())
+}
+
+async fn email_account_settings(
+    state: &AppState,
+    account: &Account,
+) -> Result<ProtocolSettings, String> {
+    let raw = state
+        .storage
+        .account_protocol_settings(account.id)
+        .await
+        .map_err(to_error_string)?
+        .ok_or_else(|| "protocol settings missing".to_string())?;
+    let mut settings: ProtocolSettings =
+        parse_domain_settings(&raw, "email").map_err(to_error_string)?;
+    hydrate_email_secrets(account.id, &state.secrets, &mut settings)?;
+    Ok(settings)
+}
+
+async fn calendar_account_settings(
+    state: &AppState,
+    account: &Account,
+) -> Result<CalendarSettings, String> {
+    let raw = state
+        .storage
+        .account_protocol_settings(account.id)
+        .await
+        .map_err(to_error_string)?
+        .ok_or_else(|| "protocol settings missing".to_string())?;
+    let mut settings: CalendarSettings =
+        parse_domain_settings(&raw, "calendar").map_err(to_error_string)?;
+    hydrate_calendar_secrets(account.id, &state.secrets, &mut settings)?;
+    Ok(settings)
+}
+
+/// CalDAV servers are not relied on for scheduling; email the invites.
+async fn send_imip_invitation(
+    state: &AppState,
+    account: &Account,
+    event: &cove_core::CalendarEvent,
+) -> Result<(), String> {
+    let Some(invitation) = state.calendar.imip_invitation(account, event) else {
+        return Ok(());
+    };
+    let attachment =
+        OutgoingAttachment::from_bytes("invite.ics", IMIP_REQUEST_MIME, invitation.ics.as_bytes());
+    send_account_mail(
+        state,
+        account,
+        &invitation.recipients,
+        invitation.subject,
+        invitation.body_text,
+        vec![attachment],
+    )
+    .await
+}
+
+/// Send a plain-text message from `account`.
+async fn send_account_mail(
+    state: &AppState,
+    account: &Account,
+    recipients: &[String],
+    subject: String,
+    body_text: String,
+    attachments: Vec<OutgoingAttachment>,
+) -> Result<(), String> {
+    let raw = state
+        .storage
+        .account_protocol_settings(account.id)
+        .await
+        .map_err(to_error_string)?
+        .ok_or_else(|| "protocol settings missing".to_string())?;
+    let mut email_settings: ProtocolSettings =
+        parse_domain_settings(&raw, "email").map_err(to_error_string)?;
+    hydrate_email_secrets(account.id, &state.secrets, &mut email_settings)?;
+
+    let outgoing = OutgoingMail {
+        from: MailAddress {
+            name: Some(account.display_name.clone()),
+            address: account.email_address.clone(),
+        },
+        to: recipients
+            .iter()
+            .map(|address| MailAddress {
+                name: None,
+                address: address.clone(),
+            })
+            .collect(),
+        cc: vec![],
+        bcc: vec![],
+        reply_to: vec![],
+        subject,
+        body_text,
+        body_html: None,
+        attachments,
+        message_id: None,
+    };
+    send_with_token_refresh(state, account, &mut email_settings, &outgoing).await
+}
+
+#[tauri::command]
+pub async fn list_attendee_responses(
+    state: State<'_, AppState>,
+    event_id: Uuid,
+) -> Result<Vec<cove_core::AttendeeResponse>, String> {
+    state
+        .storage
+        .list_attendee_responses(event_id)
+        .await
+        .map_err(to_error_string)
+}
+
 #[tauri::command]
 pub async fn export_calendar_ics(
     state: State<'_, AppState>,
@@ -848,6 +3053,7 @@ pub async fn ai_summarize_email(
     let ai = state.ai.read().await;
     let (response, provenance) = ai
         .summarize_email(
+            payload.account_id,
             &payload.subject,
             &payload.body,
             payload.mode,
@@ -870,6 +3076,7 @@ pub async fn ai_suggest_reply(
     let ai = state.ai.read().await;
     let (response, provenance) = ai
         .suggest_reply(
+            payload.account_id,
             &payload.subject,
             &payload.body,
             payload.mode,
@@ -884,13 +3091,66 @@ pub async fn ai_suggest_reply(
     })
 }
 
+/// Unread inbox messages considered by one unread mail digest.
+const UNREAD_DIGEST_SCAN_LIMIT: i64 = 500;
+
+/// Summarize unread inbox mail into one digest. Stored thread summaries are
+/// reused until their thread gets new mail.
+#[tauri::command]
+pub async fn ai_summarize_unread_mail(
+    state: State<'_, AppState>,
+    payload: AiDigestPayload,
+) -> Result<cove_core::InboxDigest, String> {
+    let digest_config = state.config().await.ai.digest;
+    let limits = DigestLimits {
+        max_concurrency: digest_config.max_concurrency,
+        requests_per_minute: digest_config.requests_per_minute,
+        max_threads: digest_config.max_threads,
+        max_prompt_chars: digest_config.max_prompt_chars,
+    };
+    let unread = state
+        .storage
+        .list_unread_inbox_messages(UNREAD_DIGEST_SCAN_LIMIT)
+        .await
+        .map_err(to_error_string)?;
+    let cached = state
+        .storage
+        .list_thread_digests()
+        .await
+        .map_err(to_error_string)?;
+
+    let ai = state.ai.read().await.clone();
+    let digest = ai
+        .build_inbox_digest(
+            DigestThread::group(unread),
+            &cached,
+            &limits,
+            payload.mode,
+            payload.cloud_provider,
+        )
+        .await;
+    for entry in &digest.entries {
+        state
+            .storage
+            .upsert_thread_digest(entry)
+            .await
+            .map_err(to_error_string)?;
+    }
+    Ok(digest)
+}
+
 #[tauri::command]
 pub async fn ai_extract_action_items(
     state: State<'_, AppState>,
     payload: AiActionPayload,
 ) -> Result<(Vec<String>, DataProvenance), String> {
     let ai = state.ai.read().await;
-    ai.extract_action_items(&payload.body, payload.mode, payload.cloud_provider)
+    ai.extract_action_items(
+        payload.account_id,
+        &payload.body,
+        payload.mode,
+        payload.cloud_provider,
+    )
         .await
         .map_err(to_error_string)
 }
@@ -902,7 +3162,12 @@ pub async fn ai_create_tasks_from_email(
 ) -> Result<AiTaskExtractionResult, String> {
     let ai = state.ai.read().await;
     let (items, provenance) = ai
-        .extract_action_items(&payload.body, payload.mode, payload.cloud_provider)
+        .extract_action_items(
+            payload.account_id,
+            &payload.body,
+            payload.mode,
+            payload.cloud_provider,
+        )
         .await
         .map_err(to_error_string)?;
     drop(ai);
@@ -931,6 +3196,102 @@ pub async fn ai_create_tasks_from_email(
     })
 }
 
+#[tauri::command]
+pub async fn ai_draft_tasks_from_thread(
+    state: State<'_, AppState>,
+    payload: AiThreadTasksPayload,
+) -> Result<AiTaskDraftResult, String> {
+    let messages = state
+        .storage
+        .list_thread_messages(payload.account_id, &payload.thread_id)
+        .await
+        .map_err(to_error_string)?;
+    if messages.is_empty() {
+        return Err("thread not found".to_string());
+    }
+
+    let ai = state.ai.read().await;
+    let (items, provenance) = ai
+        .extract_action_items(
+            payload.account_id,
+            &thread_to_markdown(&messages),
+            payload.mode,
+            payload.cloud_provider,
+        )
+        .await
+        .map_err(to_error_string)?;
+
+    Ok(AiTaskDraftResult {
+        drafts: state.tasks.draft_action_items(&items),
+        provenance,
+    })
+}
+
+/// Agenda for a new meeting from recent threads with its attendees, to put
+/// into the event description.
+#[tauri::command]
+pub async fn suggest_meeting_agenda(
+    state: State<'_, AppState>,
+    payload: MeetingAgendaPayload,
+) -> Result<cove_core::MeetingAgenda, String> {
+    let since = Utc::now() - Duration::days(AGENDA_LOOKBACK_DAYS);
+    let messages = state
+        .storage
+        .messages_with_participants(
+            payload.account_id,
+            &payload.attendees,
+            since,
+            AGENDA_SCAN_LIMIT,
+        )
+        .await
+        .map_err(to_error_string)?;
+    let own_addresses: Vec<String> = state
+        .storage
+        .list_accounts()
+        .await
+        .map_err(to_error_string)?
+        .into_iter()
+        .map(|account| account.email_address)
+        .collect();
+    let threads = DigestThread::group(messages);
+    if !payload.use_ai {
+        return Ok(draft_meeting_agenda(&threads, &own_addresses));
+    }
+
+    let ai = state.ai.read().await;
+    ai.suggest_meeting_agenda(
+        payload.account_id,
+        &payload.title,
+        &threads,
+        &own_addresses,
+        payload.mode,
+        payload.cloud_provider,
+    )
+    .await
+    .map_err(to_error_string)
+}
+
+#[tauri::command]
+pub async fn create_tasks_from_drafts(
+    state: State<'_, AppState>,
+    payload: CreateTaskDraftsPayload,
+) -> Result<Vec<cove_core::ReminderTask>, String> {
+    let source = state
+        .storage
+        .list_thread_messages(payload.account_id, &payload.thread_id)
+        .await
+        .map_err(to_error_string)?
+        .last()
+        .map(TaskSource::from_message)
+        .ok_or_else(|| "thread not found".to_string())?;
+
+    state
+        .tasks
+        .create_from_action_items(payload.account_id, &payload.list_id, &payload.drafts, &source)
+        .await
+        .map_err(to_error_string)
+}
+
 #[tauri::command]
 pub async fn set_ai_api_key(
     state: State<'_, AppState>,
@@ -1018,6 +3379,7 @@ enum JobFailureDisposition {
 
 #[derive(Clone)]
 struct SyncExecutionContext {
+    app_handle: tauri::AppHandle,
     storage: Storage,
     email: cove_email::EmailService,
     calendar: cove_calendar::CalendarService,
@@ -1051,6 +3413,27 @@ async fn run_sync_job(
         }
     };
 
+    // Retrying cannot help until the user signs in again.
+    let reauth = context
+        .storage
+        .list_account_reauth()
+        .await
+        .map_err(to_error_string)?;
+    if reauth.iter().any(|item| item.account_id == account.id) {
+        context
+            .storage
+            .update_sync_job_status(
+                job.id,
+                SyncStatus::Failed,
+                Some(format!("{} needs to sign in again", account.email_address)),
+                Some(job.attempt_count),
+            )
+            .await
+            .map_err(to_error_string)?;
+        summary.failed_jobs += 1;
+        return Ok(summary);
+    }
+
     let settings = match context
         .storage
         .account_protocol_settings(account.id)
@@ -1078,26 +3461,81 @@ async fn run_sync_job(
             let mut protocol: ProtocolSettings =
                 parse_domain_settings(&settings, "email").map_err(to_error_string)?;
             hydrate_email_secrets(account.id, &context.secrets, &mut protocol)?;
-            context
+            let mut synced = context
                 .email
                 .sync_recent_mail(&account, &protocol, "INBOX", 100)
+                .await;
+            if let Some(profile) = account
+                .oauth_profile
+                .clone()
+                .filter(|_| matches!(synced, Err(EmailError::Auth(_))))
+            {
+                let refreshed = refresh_access_token(
+                    &context.storage,
+                    &context.secrets,
+                    &account,
+                    profile,
+                )
+                .await;
+                synced = match refreshed {
+                    Ok(access_token) => {
+                        protocol.access_token = Some(access_token);
+                        context
+                            .email
+                            .sync_recent_mail(&account, &protocol, "INBOX", 100)
+                            .await
+                    }
+                    Err(err) => Err(err),
+                };
+            }
+            let synced = synced.map_err(to_error_string)?;
+            // Move mail from senders muted with auto-archive out of the inbox.
+            if let Err(err) = context.email.auto_archive_muted(&account, &protocol).await {
+                tracing::warn!(account_id = %account.id, error = %err, "auto-archiving muted senders failed");
+            }
+            // Pick up attendee replies to invitations sent from this account.
+            if let Err(err) = context
+                .calendar
+                .apply_imip_replies(&account, Utc::now() - Duration::days(7))
                 .await
-                .map(SyncDomainResult::Email)
-                .map_err(to_error_string)
+            {
+                tracing::warn!(account_id = %account.id, error = %err, "processing iTIP replies failed");
+            }
+            Ok(SyncDomainResult::Email(synced))
         }
         SyncDomain::Calendar => {
             let mut calendar_settings: CalendarSettings =
                 parse_domain_settings(&settings, "calendar").map_err(to_error_string)?;
             hydrate_calendar_secrets(account.id, &context.secrets, &mut calendar_settings)?;
-            context
+            let (start, end) = (Utc::now() - Duration::days(30), Utc::now() + Duration::days(365));
+            let mut events = context
                 .calendar
-                .sync_range(
+                .sync_range(&account, &calendar_settings, start, end)
+                .await;
+            if let Some(profile) = account
+                .oauth_profile
+                .clone()
+                .filter(|_| matches!(events, Err(CalendarError::Auth(_))))
+            {
+                let refreshed = refresh_access_token(
+                    &context.storage,
+                    &context.secrets,
                     &account,
-                    &calendar_settings,
-                    Utc::now() - Duration::days(30),
-                    Utc::now() + Duration::days(365),
+                    profile,
                 )
-                .await
+                .await;
+                events = match refreshed {
+                    Ok(access_token) => {
+                        calendar_settings.access_token = Some(access_token);
+                        context
+                            .calendar
+                            .sync_range(&account, &calendar_settings, start, end)
+                            .await
+                    }
+                    Err(err) => Err(CalendarError::Auth(err.to_string())),
+                };
+            }
+            events
                 .map(|events| SyncDomainResult::Calendar(events.len()))
                 .map_err(to_error_string)
         }
@@ -1105,10 +3543,28 @@ async fn run_sync_job(
             let mut task_settings: cove_tasks::TaskSettings =
                 parse_domain_settings(&settings, "tasks").map_err(to_error_string)?;
             hydrate_task_secrets(account.id, &context.secrets, &mut task_settings)?;
-            context
-                .tasks
-                .sync_tasks(&account, &task_settings)
-                .await
+            let mut tasks = context.tasks.sync_tasks(&account, &task_settings).await;
+            if let Some(profile) = account
+                .oauth_profile
+                .clone()
+                .filter(|_| matches!(tasks, Err(TaskError::Auth(_))))
+            {
+                let refreshed = refresh_access_token(
+                    &context.storage,
+                    &context.secrets,
+                    &account,
+                    profile,
+                )
+                .await;
+                tasks = match refreshed {
+                    Ok(access_token) => {
+                        task_settings.access_token = Some(access_token);
+                        context.tasks.sync_tasks(&account, &task_settings).await
+                    }
+                    Err(err) => Err(TaskError::Auth(err.to_string())),
+                };
+            }
+            tasks
                 .map(|tasks| SyncDomainResult::Tasks(tasks.len()))
                 .map_err(to_error_string)
         }
@@ -1123,8 +3579,29 @@ async fn run_sync_job(
                 .map_err(to_error_string)?;
             summary.completed_jobs += 1;
             match domain_result {
-                SyncDomainResult::Email(count) => summary.email_messages_synced += count,
-                SyncDomainResult::Calendar(count) => summary.calendar_events_synced += count,
+                SyncDomainResult::Email(count) => {
+                    summary.email_messages_synced += count;
+                    if count > 0 {
+                        let _ = context.app_handle.emit(
+                            EVENT_NEW_MESSAGES,
+                            &NewMessagesEvent {
+                                account_id: account.id,
+                                folder_path: "INBOX".to_string(),
+                                changed: count,
+                            },
+                        );
+                    }
+                }
+                SyncDomainResult::Calendar(count) => {
+                    summary.calendar_events_synced += count;
+                    let _ = context.app_handle.emit(
+                        EVENT_CALENDAR_UPDATED,
+                        &CalendarUpdatedEvent {
+                            account_id: account.id,
+                            events_synced: count,
+                        },
+                    );
+                }
                 SyncDomainResult::Tasks(count) => summary.tasks_synced += count,
             }
         }
@@ -1274,6 +3751,32 @@ fn hydrate_calendar_secrets(
     Ok(())
 }
 
+async fn task_account_settings(
+    state: &AppState,
+    account_id: Uuid,
+) -> Result<(Account, TaskSettings), String> {
+    let account = state
+        .storage
+        .list_accounts()
+        .await
+        .map_err(to_error_string)?
+        .into_iter()
+        .find(|account| account.id == account_id)
+        .ok_or_else(|| "account not found".to_string())?;
+
+    let settings = state
+        .storage
+        .account_protocol_settings(account.id)
+        .await
+        .map_err(to_error_string)?
+        .ok_or_else(|| "protocol settings missing".to_string())?;
+    let mut settings: TaskSettings =
+        parse_domain_settings(&settings, "tasks").map_err(to_error_string)?;
+    hydrate_task_secrets(account.id, &state.secrets, &mut settings)?;
+
+    Ok((account, settings))
+}
+
 fn hydrate_task_secrets(
     account_id: Uuid,
     secrets: &SecretStore,
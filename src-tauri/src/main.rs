@@ -2,7 +2,7 @@ mod commands;
 mod state;
 
 use state::AppState;
-use tauri::Manager;
+use tauri::{Emitter, Manager};
 use tokio::time::{sleep, Duration};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
@@ -40,26 +40,123 @@ fn main() {
             commands::save_account,
             commands::delete_account,
             commands::set_secret,
+            commands::secrets_status,
+            commands::unlock_secrets,
             commands::begin_oauth_pkce,
             commands::complete_oauth_pkce,
+            commands::begin_reauth,
+            commands::list_account_reauth,
             commands::set_ai_api_key,
             commands::queue_sync_job,
             commands::run_sync_queue,
             commands::search_mail,
+            commands::search_all,
+            commands::search_suggestions,
+            commands::retention_preview,
+            commands::purge_expired_mail,
+            commands::parse_search_query,
+            commands::build_search_query,
             commands::list_mail,
             commands::list_mail_folders,
             commands::list_mail_threads,
+            commands::label_thread,
+            commands::unlabel_thread,
+            commands::list_thread_labels,
+            commands::list_thread_label_folders,
+            commands::list_labelled_threads,
             commands::list_thread_messages,
+            commands::export_thread,
+            commands::attachment_verdict,
+            commands::release_attachment,
+            commands::save_all_attachments,
+            commands::disk_space_status,
+            commands::recent_downloads,
+            commands::mute_sender,
+            commands::unmute_sender,
+            commands::account_capabilities,
+            commands::initial_sync_progress,
+            commands::set_backfill_paused,
+            commands::snooze_message,
+            commands::unsnooze_message,
+            commands::set_pinned,
+            commands::list_rules,
+            commands::save_rule,
+            commands::dry_run_rule,
+            commands::dry_run_bulk_action,
+            commands::delete_rule,
+            commands::list_signatures,
+            commands::save_signature,
+            commands::delete_signature,
+            commands::list_templates,
+            commands::save_template,
+            commands::delete_template,
+            commands::list_sender_mutes,
+            commands::list_contact_groups,
+            commands::save_contact_group,
+            commands::delete_contact_group,
+            commands::message_group_expansions,
+            commands::mail_analytics,
+            commands::export_analytics,
             commands::get_mail_message,
+            commands::related_messages,
             commands::send_mail,
+            commands::list_audit_log,
             commands::list_tasks,
             commands::create_task_from_text,
+            commands::list_task_lists,
+            commands::refresh_task_lists,
+            commands::create_task_list,
+            commands::rename_task_list,
+            commands::delete_task_list,
+            commands::move_task_to_list,
+            commands::set_task_estimate,
             commands::import_calendar_ics,
             commands::export_calendar_ics,
+            commands::schedule_meeting,
+            commands::create_scheduling_poll,
+            commands::list_scheduling_polls,
+            commands::refresh_scheduling_poll,
+            commands::confirm_scheduling_poll,
+            commands::cancel_scheduling_poll,
+            commands::list_attendee_responses,
+            commands::create_plus_alias,
+            commands::plus_alias_report,
+            commands::delete_plus_alias,
+            commands::list_send_identities,
+            commands::reply_identity,
+            commands::list_labels,
+            commands::upsert_label,
+            commands::delete_label,
+            commands::export_triage_bundle,
+            commands::import_triage_bundle,
+            commands::protocol_log_status,
+            commands::set_protocol_logging,
+            commands::list_protocol_exchanges,
+            commands::clear_protocol_log,
+            commands::export_support_bundle,
+            commands::export_messages_json,
+            commands::list_pending_operations,
+            commands::replay_pending_operations,
+            commands::retry_pending_operation,
+            commands::discard_pending_operation,
+            commands::onboarding_checklist,
+            commands::confirm_onboarding_step,
+            commands::dismiss_onboarding,
+            commands::account_ai_policies,
+            commands::set_account_ai_policy,
+            commands::load_sample_data,
+            commands::remove_sample_data,
+            commands::search_index_progress,
+            commands::set_search_indexing_paused,
+            commands::rebuild_search_index,
             commands::ai_summarize_email,
             commands::ai_suggest_reply,
+            commands::ai_summarize_unread_mail,
             commands::ai_extract_action_items,
             commands::ai_create_tasks_from_email,
+            commands::ai_draft_tasks_from_thread,
+            commands::suggest_meeting_agenda,
+            commands::create_tasks_from_drafts,
             commands::validate_local_ai_runtime,
             commands::ai_fetch_available_models,
         ])
@@ -91,6 +188,73 @@ async fn background_sync_loop(app_handle: tauri::AppHandle) {
             Err(err) => tracing::error!("background sync run failed: {err}"),
         }
 
+        // Backfill older mail a page at a time, newest first, until every
+        // folder is done; cursors are stored, so this resumes after restarts.
+        match commands::run_backfill(&app_handle.state::<AppState>(), &app_handle).await {
+            Ok(0) => {}
+            Ok(fetched) => tracing::debug!(fetched, "mail backfill page fetched"),
+            Err(err) => tracing::warn!("mail backfill failed: {err}"),
+        }
+
+        // Fall back to headers-only sync while the disk is nearly full.
+        if tick % 4 == 0 {
+            match commands::monitor_disk_space(&app_handle.state::<AppState>()).await {
+                Ok(Some(report)) => {
+                    let _ = app_handle.emit("storage://disk-space", &report);
+                }
+                Ok(None) => {}
+                Err(err) => tracing::warn!("disk space check failed: {err}"),
+            }
+        }
+
+        // Replay changes made while offline once providers answer again.
+        if tick % 4 == 0 {
+            match commands::replay_offline_queue(&app_handle.state::<AppState>()).await {
+                Ok(status) => {
+                    if status.pending > 0 || status.needs_attention > 0 || status.replayed > 0 {
+                        let _ = app_handle.emit("offline://status", &status);
+                    }
+                }
+                Err(err) => tracing::warn!("offline queue replay failed: {err}"),
+            }
+        }
+
+        // Send scheduled mail and mail deferred to working hours.
+        match commands::process_outbox(&app_handle.state::<AppState>()).await {
+            Ok(0) => {}
+            Ok(sent) => tracing::info!(sent, "outbox messages sent"),
+            Err(err) => tracing::warn!("outbox processing failed: {err}"),
+        }
+
+        // Snoozes, follow-ups, event alerts and task due times; stored, so
+        // anything that fell due while the app was closed fires now.
+        match commands::run_reminders(&app_handle.state::<AppState>(), &app_handle).await {
+            Ok(0) => {}
+            Ok(fired) => tracing::debug!(fired, "reminders fired"),
+            Err(err) => tracing::warn!("reminder poll failed: {err}"),
+        }
+
+        // Hourly maintenance: purge Trash and Junk past their retention.
+        if tick % 240 == 0 {
+            let state = app_handle.state::<AppState>();
+            if state.config().await.retention.enabled {
+                match commands::run_retention(&state).await {
+                    Ok(0) => {}
+                    Ok(purged) => tracing::info!(purged, "retention purge completed"),
+                    Err(err) => tracing::warn!("retention purge failed: {err}"),
+                }
+            }
+        }
+
+        // Weekly analytics report, checked every 15 minutes.
+        if tick % 60 == 0 {
+            match commands::deliver_weekly_report(&app_handle.state::<AppState>()).await {
+                Ok(true) => tracing::info!("weekly report sent"),
+                Ok(false) => {}
+                Err(err) => tracing::warn!("weekly report failed: {err}"),
+            }
+        }
+
         if tick % 12 == 0 {
             let state = app_handle.state::<AppState>();
             if let Err(err) = state.prime_idle_listeners().await {
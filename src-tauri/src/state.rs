@@ -1,18 +1,73 @@
-use cove_ai::{AiRuntimeConfig, AiService, CloudProviderRuntime, LocalRuntime};
+use cove_ai::{AiRuntimeConfig, AiService, CloudProviderRuntime, LocalRuntime, RedactionPolicy};
 use cove_calendar::CalendarService;
-use cove_config::{AppConfig, ConfigManager};
+use cove_config::{sample_data_requested, AppConfig, ConfigManager};
 use cove_core::{CloudAiProvider, OAuthProfile, Provider, SyncDomain, SyncJob, SyncStatus};
 use cove_email::{EmailService, ProtocolSettings};
 use cove_security::{SecretKey, SecretStore};
-use cove_storage::Storage;
+use cove_storage::{is_sample_account, ReminderScheduler, Storage};
 use cove_tasks::TaskService;
 use anyhow::Context;
 use chrono::{DateTime, Utc};
-use serde::de::DeserializeOwned;
+use serde::{de::DeserializeOwned, Serialize};
 use std::collections::{BTreeMap, BTreeSet, HashMap};
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+// Events emitted to the webview.
+//
+// The background loop in `main.rs` and the sync queue push these so the
+// frontend can update without polling. Every payload is a JSON object; the
+// frontend listens with `event.listen(name, ...)`.
+//
+// - `sync://progress` ([`SyncProgressEvent`]): after each job in a sync run
+//   finishes, with how many of the run's jobs are done.
+// - `sync://summary` (`SyncRunSummary`): once per run that did any work.
+// - `mail://new-messages` ([`NewMessagesEvent`]): after an email job stored
+//   messages, new or changed (flags, labels), so the folder and thread lists
+//   can reload. It is not a count of unseen mail.
+// - `calendar://updated` ([`CalendarUpdatedEvent`]): after a calendar job
+//   synced events.
+// - `sync://initial-progress` (`InitialSyncProgress`): after each page of
+//   older mail an account's backfill fetches.
+// - `offline://status` (`OfflineQueueStatus`): offline queue counts after a
+//   replay attempt.
+// - `storage://disk-space` (`DiskSpaceReport`): when sync fidelity changes
+//   because of free disk space.
+// - `reminder://due` (`Reminder`): when a snooze ends, a follow-up falls
+//   due or an event or task alert fires.
+
+pub const EVENT_SYNC_PROGRESS: &str = "sync://progress";
+pub const EVENT_NEW_MESSAGES: &str = "mail://new-messages";
+pub const EVENT_CALENDAR_UPDATED: &str = "calendar://updated";
+pub const EVENT_INITIAL_SYNC: &str = "sync://initial-progress";
+pub const EVENT_REMINDER: &str = "reminder://due";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncProgressEvent {
+    pub job_id: Uuid,
+    pub account_id: Uuid,
+    pub domain: SyncDomain,
+    /// `completed` or `failed`; a job that will be retried reports `queued`.
+    pub status: SyncStatus,
+    pub finished_jobs: usize,
+    pub total_jobs: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NewMessagesEvent {
+    pub account_id: Uuid,
+    pub folder_path: String,
+    /// Messages the sync stored: newly arrived ones and existing ones whose
+    /// flags or labels changed.
+    pub changed: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CalendarUpdatedEvent {
+    pub account_id: Uuid,
+    pub events_synced: usize,
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct PendingOAuthSession {
     pub provider: Provider,
@@ -22,6 +77,9 @@ pub(crate) struct PendingOAuthSession {
     pub csrf_state: String,
     pub pkce_verifier: String,
     pub created_at: DateTime<Utc>,
+    /// Set when signing an existing account in again; its tokens are
+    /// replaced instead of a new account being created.
+    pub reauth_account: Option<Uuid>,
 }
 
 pub struct AppState {
@@ -30,6 +88,7 @@ pub struct AppState {
     pub(crate) storage: Storage,
     pub(crate) secrets: SecretStore,
     pub(crate) email: EmailService,
+    pub(crate) reminders: ReminderScheduler,
     pub(crate) calendar: CalendarService,
     pub(crate) tasks: TaskService,
     pub(crate) ai: RwLock<AiService>,
@@ -41,13 +100,16 @@ impl AppState {
         let config_manager = ConfigManager::new().context("initialize config manager")?;
         let config = config_manager.load().context("load app config")?;
 
-        let secrets =
-            SecretStore::new_with_legacy("io.covemail.desktop", "io.aether.desktop");
+        // Portable mode keeps secrets encrypted in the data directory, off
+        // the keychain. A locked secrets file can still be unlocked from the
+        // UI later, unless the database itself needs a key from it.
+        let secrets = SecretStore::open_desktop(
+            config_manager.location().is_portable(),
+            config_manager.encrypted_secrets_file(),
+        )
+        .context("unlock secrets file")?;
         let db_key = secrets
-            .get(&SecretKey {
-                namespace: "database".to_string(),
-                id: "sqlcipher_key".to_string(),
-            })
+            .database_key(config.database.sqlcipher_enabled)
             .context("load sqlcipher key from keychain")?;
         let db_key = validate_sqlcipher_config(&config, db_key)?;
 
@@ -57,8 +119,16 @@ impl AppState {
         let storage = Storage::connect(&db_path, &search_path, db_key.as_deref())
             .await
             .context("initialize sqlite storage")?;
+        if sample_data_requested() {
+            storage
+                .seed_sample_data()
+                .await
+                .context("load sample data")?;
+        }
 
         let email = EmailService::new(storage.clone());
+        email.set_sanitizer_policy(config.sanitizer);
+        let reminders = ReminderScheduler::new(storage.clone());
         let calendar = CalendarService::new(storage.clone());
         let tasks = TaskService::new(storage.clone());
 
@@ -71,6 +141,7 @@ impl AppState {
             storage,
             secrets,
             email,
+            reminders,
             calendar,
             tasks,
             ai: RwLock::new(ai),
@@ -85,10 +156,7 @@ impl AppState {
     pub async fn set_config(&self, next: AppConfig) -> anyhow::Result<()> {
         let db_key = self
             .secrets
-            .get(&SecretKey {
-                namespace: "database".to_string(),
-                id: "sqlcipher_key".to_string(),
-            })
+            .database_key(next.database.sqlcipher_enabled)
             .context("load sqlcipher key from keychain")?;
         validate_sqlcipher_config(&next, db_key)?;
 
@@ -97,6 +165,7 @@ impl AppState {
             let mut guard = self.config.write().await;
             *guard = next.clone();
         }
+        self.email.set_sanitizer_policy(next.sanitizer);
         {
             let mut ai = self.ai.write().await;
             ai.update_config(ai_runtime_from_config(&next));
@@ -112,6 +181,9 @@ impl AppState {
         let mut scheduled = 0_usize;
 
         for account in &accounts {
+            if is_sample_account(account.id) {
+                continue;
+            }
             for domain in [SyncDomain::Email, SyncDomain::Calendar, SyncDomain::Tasks] {
                 if self
                     .storage
@@ -228,6 +300,18 @@ fn ai_runtime_from_config(config: &AppConfig) -> AiRuntimeConfig {
         cloud_enabled: config.ai.cloud.enabled,
         cloud_feature_opt_in,
         cloud,
+        cloud_redaction: config.ai.cloud.redaction.enabled.then(|| RedactionPolicy {
+            emails: config.ai.cloud.redaction.redact_emails,
+            phone_numbers: config.ai.cloud.redaction.redact_phone_numbers,
+            numeric_ids: config.ai.cloud.redaction.redact_numeric_ids,
+            custom_patterns: config.ai.cloud.redaction.custom_patterns.clone(),
+        }),
+        account_policies: config
+            .ai
+            .account_policies
+            .iter()
+            .filter_map(|(id, policy)| Some((Uuid::parse_str(id).ok()?, *policy)))
+            .collect(),
     }
 }
 
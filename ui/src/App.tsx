@@ -1,39 +1,104 @@
 import { useCallback, useEffect, useMemo, useRef, useState } from "react";
 import {
+  accountCapabilities,
   aiCreateTasksFromEmail,
   aiSuggestReply,
   aiSummarize,
   beginOAuthPkce,
+  beginReauth,
   bootstrap,
   completeOAuthPkce,
   createTaskFromText,
+  deleteContactGroup,
+  diskSpaceStatus,
+  exportAnalytics,
   exportIcs,
   importIcs,
+  initialSyncProgress,
+  labelThread,
+  listAccountReauth,
   listAccounts,
+  listContactGroups,
+  listLabelledThreads,
   listMailFolders,
   listMailThreads,
+  listSenderMutes,
   listTasks,
+  listThreadLabelFolders,
+  listThreadLabels,
   listThreadMessages,
+  mailAnalytics,
+  messageGroupExpansions,
+  muteSender,
   queueEmailSync,
+  replayPendingOperations,
+  retentionPreview,
   runSyncQueue,
   saveConfig,
-  searchMail,
+  buildSearchQuery,
+  parseSearchQuery,
+  purgeExpiredMail,
+  recentDownloads,
+  saveAllAttachments,
+  saveContactGroup,
+  searchAll,
+  searchSuggestions,
+  secretsStatus,
   sendMail,
+  setBackfillPaused,
+  setPinned,
+  setTaskEstimate,
+  snoozeMessage,
+  unlabelThread,
+  unlockSecrets,
+  unmuteSender,
+  unsnoozeMessage,
   validateLocalAiRuntime,
 } from "./lib/api";
 import type {
   Account,
+  AccountReauth,
+  AnalyticsFormat,
+  AttachmentDownload,
+  BackendCapabilities,
   BootstrapResponse,
+  CalendarEvent,
+  CalendarUpdatedEvent,
+  ContactGroup,
   DataProvenance,
+  DateBucket,
+  DiskSpacePolicy,
+  DiskSpaceReport,
+  GroupExpansion,
+  GroupSend,
+  InitialSyncProgress,
   MailAddress,
+  MailAnalytics,
   MailAttachment,
   MailFolder,
   MailMessage,
   MailThreadSummary,
+  MuteDuration,
+  NewMessagesEvent,
+  OfflineQueueStatus,
   OutgoingAttachment,
   Provider,
+  PurgeCandidate,
+  RecipientField,
+  Reminder,
   ReminderTask,
+  RetentionPolicy,
+  SanitizerLevel,
+  SanitizerPolicy,
+  SearchQueryDraft,
+  SearchSuggestion,
+  SecretsStatus,
+  SendGuardrails,
+  SenderMute,
+  SyncProgressEvent,
   SyncRunSummary,
+  ThreadLabelFolder,
+  WeeklyReportPolicy,
 } from "./types";
 
 type View = "inbox" | "calendar" | "tasks" | "ai" | "settings";
@@ -115,6 +180,37 @@ function formatDateTime(raw: string): string {
   });
 }
 
+const DATE_BUCKET_LABELS: Record<DateBucket, string> = {
+  today: "Today",
+  yesterday: "Yesterday",
+  this_week: "This week",
+  earlier: "Earlier",
+};
+
+/** Consecutive threads sharing a date bucket; the list arrives newest first. */
+function groupThreadsByBucket(threads: MailThreadSummary[]): { bucket: DateBucket; threads: MailThreadSummary[] }[] {
+  const sections: { bucket: DateBucket; threads: MailThreadSummary[] }[] = [];
+  for (const thread of threads) {
+    const last = sections[sections.length - 1];
+    if (last && last.bucket === thread.date_bucket) {
+      last.threads.push(thread);
+    } else {
+      sections.push({ bucket: thread.date_bucket, threads: [thread] });
+    }
+  }
+  return sections;
+}
+
+function monthKey(raw: string): string {
+  const date = new Date(raw);
+  return `${date.getFullYear()}-${String(date.getMonth() + 1).padStart(2, "0")}`;
+}
+
+function formatMonthKey(key: string): string {
+  const [year, month] = key.split("-").map(Number);
+  return new Date(year, month - 1, 1).toLocaleDateString(undefined, { month: "short", year: "2-digit" });
+}
+
 function taskStatusLabel(status: ReminderTask["status"]): string {
   switch (status) {
     case "not_started":
@@ -144,7 +240,18 @@ function formatBytes(value: number): string {
   if (value < 1024) return `${value} B`;
   const kb = value / 1024;
   if (kb < 1024) return `${kb.toFixed(1)} KB`;
-  return `${(kb / 1024).toFixed(1)} MB`;
+  const mb = kb / 1024;
+  if (mb < 1024) return `${mb.toFixed(1)} MB`;
+  return `${(mb / 1024).toFixed(1)} GB`;
+}
+
+function formatEta(seconds: number): string {
+  if (seconds < 90) return "about a minute";
+  const minutes = Math.round(seconds / 60);
+  if (minutes < 90) return `about ${minutes} minutes`;
+  const hours = Math.round(minutes / 60);
+  if (hours < 36) return `about ${hours} hours`;
+  return `about ${Math.round(hours / 24)} days`;
 }
 
 function parseRecipientList(raw: string): MailAddress[] {
@@ -202,15 +309,23 @@ export default function App() {
   const [threads, setThreads] = useState<MailThreadSummary[]>([]);
   const [selectedThreadId, setSelectedThreadId] = useState<string | null>(null);
   const [threadMessages, setThreadMessages] = useState<MailMessage[]>([]);
+  /** Bumped by `mail://new-messages` so the thread list reloads. */
+  const [mailRevision, setMailRevision] = useState(0);
   const [selectedMessageId, setSelectedMessageId] = useState<string | null>(null);
 
   const [mailQuery, setMailQuery] = useState("");
   const [mailResults, setMailResults] = useState<MailMessage[]>([]);
+  const [eventResults, setEventResults] = useState<CalendarEvent[]>([]);
+  const [taskResults, setTaskResults] = useState<ReminderTask[]>([]);
+  const [searchHints, setSearchHints] = useState<SearchSuggestion[]>([]);
+  const [queryDraft, setQueryDraft] = useState<SearchQueryDraft | null>(null);
 
   const [composeTo, setComposeTo] = useState("");
   const [composeSubject, setComposeSubject] = useState("");
   const [composeBody, setComposeBody] = useState("");
   const [composeAttachments, setComposeAttachments] = useState<OutgoingAttachment[]>([]);
+  const [composeSendAnyway, setComposeSendAnyway] = useState(false);
+  const [composeGroups, setComposeGroups] = useState<GroupSend[]>([]);
 
   const [taskText, setTaskText] = useState("");
   const [tasks, setTasks] = useState<ReminderTask[]>([]);
@@ -236,9 +351,40 @@ export default function App() {
   const [oauthTaskListId, setOauthTaskListId] = useState("@default");
   const [localLlamaBinary, setLocalLlamaBinary] = useState("");
   const [localModelPath, setLocalModelPath] = useState("");
+  const [retention, setRetention] = useState<RetentionPolicy | null>(null);
+  const [retentionOverrides, setRetentionOverrides] = useState("");
+  const [sendGuardrails, setSendGuardrails] = useState<SendGuardrails | null>(null);
+  const [sanitizer, setSanitizer] = useState<SanitizerPolicy>({ level: "standard", strip_css: false });
+  const [downloadDir, setDownloadDir] = useState("");
+  const [downloads, setDownloads] = useState<AttachmentDownload[]>([]);
+  const [senderMutes, setSenderMutes] = useState<SenderMute[]>([]);
+  const [contactGroups, setContactGroups] = useState<ContactGroup[]>([]);
+  const [groupDraft, setGroupDraft] = useState<{ id: string | null; name: string; members: string }>({
+    id: null,
+    name: "",
+    members: "",
+  });
+  const [groupExpansions, setGroupExpansions] = useState<GroupExpansion[]>([]);
+  const [projectFolders, setProjectFolders] = useState<ThreadLabelFolder[]>([]);
+  const [selectedProject, setSelectedProject] = useState<string | null>(null);
+  const [threadLabels, setThreadLabels] = useState<string[]>([]);
+  const [threadLabelDraft, setThreadLabelDraft] = useState("");
+  const [muteAutoArchive, setMuteAutoArchive] = useState(false);
+  const [capabilities, setCapabilities] = useState<BackendCapabilities | null>(null);
+  const [purgeCandidates, setPurgeCandidates] = useState<PurgeCandidate[] | null>(null);
   const [localAiValidationErrors, setLocalAiValidationErrors] = useState<string[]>([]);
 
   const [provenance, setProvenance] = useState<DataProvenance | null>(null);
+  const [offlineQueue, setOfflineQueue] = useState<OfflineQueueStatus | null>(null);
+  const [reauthAccounts, setReauthAccounts] = useState<AccountReauth[]>([]);
+  const [secrets, setSecrets] = useState<SecretsStatus | null>(null);
+  const [secretsPassphrase, setSecretsPassphrase] = useState("");
+  const [secretsConfirmation, setSecretsConfirmation] = useState("");
+  const [secretsPromptDismissed, setSecretsPromptDismissed] = useState(false);
+  const [diskSpace, setDiskSpace] = useState<DiskSpaceReport | null>(null);
+  const [initialSync, setInitialSync] = useState<Record<string, InitialSyncProgress>>({});
+  const [initialSyncHidden, setInitialSyncHidden] = useState<Record<string, boolean>>({});
+  const [analytics, setAnalytics] = useState<MailAnalytics | null>(null);
   const [status, setStatus] = useState("Ready");
   const [toasts, setToasts] = useState<ToastItem[]>([]);
 
@@ -246,6 +392,7 @@ export default function App() {
   const [resolvedTheme, setResolvedTheme] = useState<ResolvedTheme>("aurora");
 
   const toastCounter = useRef(0);
+  const threadListRef = useRef<HTMLDivElement>(null);
 
   const pushToast = useCallback((title: string, detail: string, tone: ToastTone = "info") => {
     const id = ++toastCounter.current;
@@ -259,6 +406,7 @@ export default function App() {
     () => accounts.find((account) => account.id === selectedAccountId) ?? null,
     [accounts, selectedAccountId]
   );
+  const selectedInitialSync = selectedAccountId ? initialSync[selectedAccountId] ?? null : null;
   const activeView = useMemo(() => views.find((entry) => entry.id === view) ?? views[0], [view]);
   const selectedMessage = useMemo(
     () =>
@@ -271,6 +419,17 @@ export default function App() {
     () => folders.find((folder) => folder.path === selectedFolderPath) ?? null,
     [folders, selectedFolderPath]
   );
+  const threadSections = useMemo(() => groupThreadsByBucket(threads), [threads]);
+  const threadMonths = useMemo(
+    () => Array.from(new Set(threads.map((thread) => monthKey(thread.most_recent_at)))),
+    [threads]
+  );
+
+  function jumpToMonth(key: string) {
+    threadListRef.current
+      ?.querySelector(`[data-month="${key}"]`)
+      ?.scrollIntoView({ block: "start", behavior: "smooth" });
+  }
 
   const openTaskCount = useMemo(() => tasks.filter((task) => task.status !== "completed").length, [tasks]);
   const criticalTaskCount = useMemo(
@@ -340,6 +499,23 @@ export default function App() {
     });
   }, []);
 
+  useEffect(() => {
+    let active = true;
+    const timer = window.setTimeout(() => {
+      void searchSuggestions(mailQuery)
+        .then((hints) => {
+          if (active) setSearchHints(hints);
+        })
+        .catch(() => {
+          if (active) setSearchHints([]);
+        });
+    }, 150);
+    return () => {
+      active = false;
+      window.clearTimeout(timer);
+    };
+  }, [mailQuery]);
+
   useEffect(() => {
     setStatus("Loading account context...");
     void bootstrap()
@@ -349,6 +525,17 @@ export default function App() {
         if (result.accounts[0]) setSelectedAccountId(result.accounts[0].id);
         setLocalLlamaBinary(result.config.ai.local.llama_cpp_binary ?? "");
         setLocalModelPath(result.config.ai.local.model_path ?? "");
+        if (result.config.retention) {
+          setRetention(result.config.retention);
+          setRetentionOverrides(
+            Object.entries(result.config.retention.folder_overrides)
+              .map(([folder, days]) => `${folder} = ${days}`)
+              .join("\n")
+          );
+        }
+        if (result.config.send_guardrails) setSendGuardrails(result.config.send_guardrails);
+        if (result.config.sanitizer) setSanitizer(result.config.sanitizer);
+        setDownloadDir(result.config.attachments?.download_dir ?? "");
         setAiMode(result.config.privacy.default_ai_mode);
         setStatus("Ready");
       })
@@ -359,6 +546,17 @@ export default function App() {
       });
   }, [pushToast]);
 
+  useEffect(() => {
+    void recentDownloads().then(setDownloads).catch(() => setDownloads([]));
+    void listSenderMutes().then(setSenderMutes).catch(() => setSenderMutes([]));
+    void listContactGroups().then(setContactGroups).catch(() => setContactGroups([]));
+    void listThreadLabelFolders().then(setProjectFolders).catch(() => setProjectFolders([]));
+    void mailAnalytics().then(setAnalytics).catch(() => setAnalytics(null));
+    void diskSpaceStatus().then(setDiskSpace).catch(() => setDiskSpace(null));
+    void listAccountReauth().then(setReauthAccounts).catch(() => setReauthAccounts([]));
+    void secretsStatus().then(setSecrets).catch(() => setSecrets(null));
+  }, []);
+
   useEffect(() => {
     if (!selectedAccountId) {
       setFolders([]);
@@ -367,6 +565,16 @@ export default function App() {
       return;
     }
 
+    void accountCapabilities(selectedAccountId)
+      .then(setCapabilities)
+      .catch(() => setCapabilities(null));
+
+    void initialSyncProgress(selectedAccountId)
+      .then((progress) => {
+        if (progress) setInitialSync((current) => ({ ...current, [progress.account_id]: progress }));
+      })
+      .catch(() => undefined);
+
     void listTasks(selectedAccountId)
       .then(setTasks)
       .catch((error: unknown) => {
@@ -388,7 +596,10 @@ export default function App() {
       return;
     }
 
-    void listMailThreads(selectedAccountId, selectedFolderPath)
+    const load = selectedProject
+      ? listLabelledThreads(selectedProject, selectedAccountId)
+      : listMailThreads(selectedAccountId, selectedFolderPath);
+    void load
       .then((result) => {
         setThreads(result);
         if (result.length === 0) {
@@ -409,7 +620,7 @@ export default function App() {
         setStatus(message);
         pushToast("Thread load failed", message, "error");
       });
-  }, [selectedAccountId, selectedFolderPath, pushToast]);
+  }, [selectedAccountId, selectedFolderPath, selectedProject, mailRevision, pushToast]);
 
   useEffect(() => {
     if (!selectedAccountId || !selectedThreadId) {
@@ -428,8 +639,21 @@ export default function App() {
         setStatus(message);
         pushToast("Message load failed", message, "error");
       });
+    void listThreadLabels(selectedAccountId, selectedThreadId)
+      .then(setThreadLabels)
+      .catch(() => setThreadLabels([]));
   }, [selectedAccountId, selectedThreadId, pushToast]);
 
+  useEffect(() => {
+    if (!selectedMessageId) {
+      setGroupExpansions([]);
+      return;
+    }
+    void messageGroupExpansions(selectedMessageId)
+      .then(setGroupExpansions)
+      .catch(() => setGroupExpansions([]));
+  }, [selectedMessageId]);
+
   useEffect(() => {
     let unlisten: (() => void) | null = null;
     let active = true;
@@ -437,7 +661,25 @@ export default function App() {
     void (async () => {
       try {
         const event = await import("@tauri-apps/api/event");
-        const off = await event.listen<SyncRunSummary>("sync://summary", ({ payload }) => {
+        const offOffline = await event.listen<OfflineQueueStatus>("offline://status", ({ payload }) => {
+          setOfflineQueue(payload);
+          if (payload.replayed > 0) {
+            pushToast("Back online", `${payload.replayed} queued change(s) sent`, "success");
+          }
+        });
+        const offDisk = await event.listen<DiskSpaceReport>("storage://disk-space", ({ payload }) => {
+          setDiskSpace(payload);
+          if (payload.fidelity === "headers_only") {
+            pushToast(
+              "Disk space low",
+              `${formatBytes(payload.available_bytes)} free; syncing headers only until space is freed`,
+              "warning"
+            );
+          } else {
+            pushToast("Disk space recovered", "Full message sync resumed", "success");
+          }
+        });
+        const offSync = await event.listen<SyncRunSummary>("sync://summary", ({ payload }) => {
           const syncMessage = summarizeSync(payload);
           setStatus(`Background sync: ${syncMessage}`);
           pushToast(
@@ -445,8 +687,41 @@ export default function App() {
             syncMessage,
             payload.failed_jobs > 0 ? "warning" : "success"
           );
+          void listAccountReauth().then(setReauthAccounts);
+        });
+
+        const offProgress = await event.listen<SyncProgressEvent>("sync://progress", ({ payload }) => {
+          setStatus(`Syncing ${payload.domain}: ${payload.finished_jobs}/${payload.total_jobs} jobs done`);
+        });
+        const offMail = await event.listen<NewMessagesEvent>("mail://new-messages", ({ payload }) => {
+          setMailRevision((revision) => revision + 1);
+          setStatus(`${payload.changed} message(s) synced to ${payload.folder_path}`);
+        });
+        const offCalendar = await event.listen<CalendarUpdatedEvent>("calendar://updated", ({ payload }) => {
+          setStatus(`Calendar updated: ${payload.events_synced} event(s) synced`);
+        });
+        const offReminder = await event.listen<Reminder>("reminder://due", ({ payload }) => {
+          if (payload.kind === "mail_snooze") {
+            setMailRevision((revision) => revision + 1);
+            setStatus(`Snoozed message back: ${payload.title}`);
+          } else if (payload.kind === "follow_up") {
+            pushToast("Time to reply", payload.title, "info");
+          }
+        });
+        const offInitial = await event.listen<InitialSyncProgress>("sync://initial-progress", ({ payload }) => {
+          setInitialSync((current) => ({ ...current, [payload.account_id]: payload }));
         });
 
+        const off = () => {
+          offOffline();
+          offDisk();
+          offSync();
+          offProgress();
+          offMail();
+          offCalendar();
+          offReminder();
+          offInitial();
+        };
         if (!active) {
           off();
           return;
@@ -470,13 +745,18 @@ export default function App() {
     setStatus("Searching local index...");
     setMailQuery(query);
     try {
-      const result = await searchMail(query);
+      const result = await searchAll(query);
       const filtered = selectedAccountId
-        ? result.items.filter((item) => item.account_id === selectedAccountId)
-        : result.items;
+        ? result.messages.filter((item) => item.account_id === selectedAccountId)
+        : result.messages;
       setMailResults(filtered);
-      setStatus(`Search returned ${filtered.length} messages`);
-      pushToast("Search complete", `${filtered.length} matches`, "success");
+      setEventResults(result.events);
+      setTaskResults(result.tasks);
+      const total = filtered.length + result.events.length + result.tasks.length;
+      setStatus(
+        `Search returned ${filtered.length} messages, ${result.events.length} events, ${result.tasks.length} tasks`
+      );
+      pushToast("Search complete", `${total} matches`, "success");
     } catch (error) {
       const message = String(error);
       setStatus(message);
@@ -484,6 +764,22 @@ export default function App() {
     }
   }
 
+  async function onToggleQueryBuilder() {
+    if (queryDraft) {
+      setQueryDraft(null);
+      return;
+    }
+    setQueryDraft(await parseSearchQuery(mailQuery));
+  }
+
+  async function onApplyQueryBuilder() {
+    if (!queryDraft) return;
+    const query = await buildSearchQuery(queryDraft);
+    setQueryDraft(null);
+    setMailQuery(query);
+    await executeMailSearch(query);
+  }
+
   async function onRunSync() {
     if (!selectedAccountId) return;
 
@@ -498,6 +794,7 @@ export default function App() {
         message,
         summary.failed_jobs > 0 ? "warning" : "success"
       );
+      setReauthAccounts(await listAccountReauth());
       await loadFolderState(selectedAccountId, false);
     } catch (error) {
       const message = String(error);
@@ -506,6 +803,24 @@ export default function App() {
     }
   }
 
+  async function onToggleBackfill(progress: InitialSyncProgress) {
+    try {
+      const updated = await setBackfillPaused(progress.account_id, !progress.paused);
+      if (updated) setInitialSync((current) => ({ ...current, [updated.account_id]: updated }));
+      setStatus(progress.paused ? "Backfill resumed" : "Backfill paused; new mail still syncs");
+    } catch (error) {
+      pushToast("Could not change backfill", String(error), "error");
+    }
+  }
+
+  async function onReplayOffline() {
+    try {
+      setOfflineQueue(await replayPendingOperations());
+    } catch (error) {
+      pushToast("Replay failed", String(error), "error");
+    }
+  }
+
   async function onCreateTask() {
     if (!selectedAccountId || !taskText.trim()) return;
 
@@ -522,6 +837,21 @@ export default function App() {
     }
   }
 
+  async function onSetTaskEstimate(task: ReminderTask, value: string) {
+    const minutes = value.trim() ? Math.max(0, Math.round(Number(value))) : 0;
+    if (Number.isNaN(minutes) || minutes === (task.estimate_minutes ?? 0)) return;
+
+    try {
+      const updated = await setTaskEstimate(task.id, minutes || null);
+      setTasks((prev) => prev.map((item) => (item.id === updated.id ? updated : item)));
+      setStatus(minutes ? `Estimate set to ${minutes} min` : "Estimate cleared");
+    } catch (error) {
+      const message = String(error);
+      setStatus(message);
+      pushToast("Estimate update failed", message, "error");
+    }
+  }
+
   async function onImportIcs() {
     if (!selectedAccountId) return;
     try {
@@ -550,8 +880,13 @@ export default function App() {
   }
 
   async function onAiSummarize() {
+    if (!selectedAccountId) {
+      pushToast("No account selected", "Select an account before summarizing", "warning");
+      return;
+    }
+
     try {
-      const response = await aiSummarize(aiSubject, aiBody, aiMode);
+      const response = await aiSummarize(selectedAccountId, aiSubject, aiBody, aiMode);
       setAiOutput(response.output);
       setProvenance(response.provenance);
       setStatus("Summary ready");
@@ -564,8 +899,13 @@ export default function App() {
   }
 
   async function onAiReply() {
+    if (!selectedAccountId) {
+      pushToast("No account selected", "Select an account before drafting a reply", "warning");
+      return;
+    }
+
     try {
-      const response = await aiSuggestReply(aiSubject, aiBody, aiMode);
+      const response = await aiSuggestReply(selectedAccountId, aiSubject, aiBody, aiMode);
       setAiOutput(response.output);
       setProvenance(response.provenance);
       setStatus("Draft reply generated");
@@ -625,32 +965,45 @@ export default function App() {
     if (!selectedAccountId || !selectedAccount) return;
 
     const to = parseRecipientList(composeTo);
-    if (to.length === 0) {
-      pushToast("Missing recipients", "Add at least one To address", "warning");
+    if (to.length === 0 && !composeGroups.some((send) => send.field === "to")) {
+      pushToast("Missing recipients", "Add at least one To address or group", "warning");
       return;
     }
 
     try {
-      await sendMail(selectedAccountId, {
-        from: {
-          name: selectedAccount.display_name,
-          address: selectedAccount.email_address,
+      const result = await sendMail(
+        selectedAccountId,
+        {
+          from: {
+            name: selectedAccount.display_name,
+            address: selectedAccount.email_address,
+          },
+          to,
+          cc: [],
+          bcc: [],
+          reply_to: [],
+          subject: composeSubject,
+          body_text: composeBody,
+          body_html: null,
+          attachments: composeAttachments,
         },
-        to,
-        cc: [],
-        bcc: [],
-        reply_to: [],
-        subject: composeSubject,
-        body_text: composeBody,
-        body_html: null,
-        attachments: composeAttachments,
-      });
+        composeSendAnyway,
+        composeGroups
+      );
 
+      setComposeGroups([]);
       setComposeSubject("");
       setComposeBody("");
       setComposeAttachments([]);
-      setStatus("Draft sent");
-      pushToast("Draft sent", "Message submitted to provider", "success");
+      setComposeSendAnyway(false);
+      if (result.deferred_until) {
+        const when = new Date(result.deferred_until).toLocaleString();
+        setStatus(`Scheduled for ${when}`);
+        pushToast("Outside working hours", `Message waits in the Outbox until ${when}`, "info");
+      } else {
+        setStatus("Draft sent");
+        pushToast("Draft sent", "Message submitted to provider", "success");
+      }
     } catch (error) {
       const message = String(error);
       setStatus(message);
@@ -685,6 +1038,55 @@ export default function App() {
     }
   }
 
+  async function onUnlockSecrets() {
+    if (!secrets || !secretsPassphrase) return;
+    if (secrets.first_unlock && secretsPassphrase !== secretsConfirmation) {
+      pushToast("Passphrases do not match", "Type the same passphrase twice", "warning");
+      return;
+    }
+    try {
+      const migrated = await unlockSecrets(
+        secretsPassphrase,
+        secrets.first_unlock ? secretsConfirmation : undefined
+      );
+      setSecretsPassphrase("");
+      setSecretsConfirmation("");
+      setSecrets(await secretsStatus());
+      pushToast(
+        "Secrets unlocked",
+        migrated > 0 ? `Moved ${migrated} secret(s) into the system keychain` : "Passwords and tokens are available",
+        "success"
+      );
+    } catch (error) {
+      pushToast("Unlock failed", String(error), "error");
+    }
+  }
+
+  async function onBeginReauth(accountId: string) {
+    const account = accounts.find((item) => item.id === accountId);
+    try {
+      const response = await beginReauth(accountId);
+      if (account) {
+        setOauthProvider(account.provider);
+        setOauthEmailAddress(account.email_address);
+        setOauthDisplayName(account.display_name);
+        if (account.oauth_profile) {
+          setOauthClientId(account.oauth_profile.client_id);
+          setOauthRedirectUrl(account.oauth_profile.redirect_url);
+        }
+      }
+      setOauthSessionId(response.session_id);
+      setOauthAuthorizationUrl(response.authorization_url);
+      setView("settings");
+      pushToast("Sign in again", "Approve access in the browser, then paste the callback URL", "info");
+      window.open(response.authorization_url, "_blank", "noopener,noreferrer");
+    } catch (error) {
+      const message = String(error);
+      setStatus(message);
+      pushToast("Sign-in failed to start", message, "error");
+    }
+  }
+
   async function onCompleteOAuth() {
     if (!oauthSessionId || !oauthCode.trim() || !oauthState.trim()) {
       pushToast("Missing OAuth completion fields", "Session, state, and code are required", "warning");
@@ -706,6 +1108,7 @@ export default function App() {
       setOauthSessionId(null);
       setOauthAuthorizationUrl("");
       setOauthCode("");
+      setReauthAccounts(await listAccountReauth());
       setStatus("OAuth account added");
       pushToast("Account linked", response.account.email_address, "success");
     } catch (error) {
@@ -734,6 +1137,304 @@ export default function App() {
     }
   }
 
+  async function onSaveRetention() {
+    if (!boot || !retention) return;
+
+    const folder_overrides: Record<string, number> = {};
+    for (const line of retentionOverrides.split("\n")) {
+      const [folder, days] = line.split("=").map((part) => part.trim());
+      if (folder && days && !Number.isNaN(Number(days))) folder_overrides[folder] = Number(days);
+    }
+    const nextConfig = { ...boot.config, retention: { ...retention, folder_overrides } };
+
+    try {
+      await saveConfig(nextConfig);
+      setBoot((previous) => (previous ? { ...previous, config: nextConfig } : previous));
+      setPurgeCandidates(null);
+      pushToast("Cleanup saved", "Trash and Junk retention updated", "success");
+    } catch (error) {
+      const message = String(error);
+      setStatus(message);
+      pushToast("Save failed", message, "error");
+    }
+  }
+
+  async function onSaveAllAttachments(messages: MailMessage[]) {
+    try {
+      const report = await saveAllAttachments(messages.map((message) => message.id));
+      setDownloads(await recentDownloads());
+      const skipped = report.skipped.length ? `, ${report.skipped.length} skipped` : "";
+      pushToast(
+        "Attachments saved",
+        `${report.saved.length} file(s) saved${skipped}`,
+        report.skipped.length ? "warning" : "success"
+      );
+      for (const item of report.skipped) {
+        pushToast(`Skipped ${item.file_name}`, item.reason, "warning");
+      }
+    } catch (error) {
+      const message = String(error);
+      setStatus(message);
+      pushToast("Save failed", message, "error");
+    }
+  }
+
+  async function onMuteSender(accountId: string, email: string, duration: MuteDuration) {
+    try {
+      const autoArchive = muteAutoArchive && capabilities?.move_messages !== false;
+      const mute = await muteSender(accountId, email, duration, autoArchive);
+      setSenderMutes(await listSenderMutes());
+      const until = mute.muted_until ? `until ${formatDateTime(mute.muted_until)}` : "until you unmute them";
+      pushToast("Sender muted", `${mute.email} is muted ${until}`, "success");
+    } catch (error) {
+      const message = String(error);
+      setStatus(message);
+      pushToast("Mute failed", message, "error");
+    }
+  }
+
+  function onAddComposeGroup(groupId: string) {
+    if (!groupId || composeGroups.some((send) => send.group_id === groupId)) return;
+    setComposeGroups((previous) => [...previous, { group_id: groupId, field: "to", excluded: [] }]);
+  }
+
+  function updateComposeGroup(groupId: string, update: (send: GroupSend) => GroupSend) {
+    setComposeGroups((previous) => previous.map((send) => (send.group_id === groupId ? update(send) : send)));
+  }
+
+  async function onSaveContactGroup() {
+    const name = groupDraft.name.trim();
+    const existing = contactGroups.find((group) => group.id === groupDraft.id);
+    // Keep display names for members that stayed in the list.
+    const members = parseRecipientList(groupDraft.members).map(
+      (member) =>
+        existing?.members.find((known) => known.address.toLowerCase() === member.address.toLowerCase()) ?? member
+    );
+    if (!name || members.length === 0) {
+      pushToast("Incomplete group", "Give the group a name and at least one member", "warning");
+      return;
+    }
+
+    try {
+      const now = new Date().toISOString();
+      const saved = await saveContactGroup({
+        id: existing?.id ?? "00000000-0000-0000-0000-000000000000",
+        name,
+        members,
+        created_at: existing?.created_at ?? now,
+        updated_at: now,
+      });
+      setContactGroups(await listContactGroups());
+      setGroupDraft({ id: null, name: "", members: "" });
+      pushToast("Group saved", `${saved.name} has ${saved.members.length} member(s)`, "success");
+    } catch (error) {
+      const message = String(error);
+      setStatus(message);
+      pushToast("Group save failed", message, "error");
+    }
+  }
+
+  async function onDeleteContactGroup(group: ContactGroup) {
+    try {
+      await deleteContactGroup(group.id);
+      setContactGroups(await listContactGroups());
+      setComposeGroups((previous) => previous.filter((send) => send.group_id !== group.id));
+      pushToast("Group deleted", group.name, "info");
+    } catch (error) {
+      const message = String(error);
+      setStatus(message);
+      pushToast("Group delete failed", message, "error");
+    }
+  }
+
+  async function onUnmuteSender(accountId: string, email: string) {
+    try {
+      await unmuteSender(accountId, email);
+      setSenderMutes(await listSenderMutes());
+      pushToast("Sender unmuted", email, "info");
+    } catch (error) {
+      const message = String(error);
+      setStatus(message);
+      pushToast("Unmute failed", message, "error");
+    }
+  }
+
+  function patchMessage(messageId: string, patch: Partial<MailMessage>) {
+    setThreadMessages((current) =>
+      current.map((message) => (message.id === messageId ? { ...message, ...patch } : message))
+    );
+  }
+
+  async function onTogglePinned(message: MailMessage) {
+    const pinned = !message.pinned;
+    try {
+      await setPinned(message.id, pinned);
+      patchMessage(message.id, { pinned });
+    } catch (error) {
+      const text = String(error);
+      setStatus(text);
+      pushToast("Pin failed", text, "error");
+    }
+  }
+
+  async function onSnoozeMessage(message: MailMessage, hours: number | null) {
+    try {
+      if (hours === null) {
+        await unsnoozeMessage(message.id);
+        patchMessage(message.id, { snoozed_until: null });
+        pushToast("Snooze cleared", message.subject || "(No subject)", "info");
+        return;
+      }
+      const until = new Date(Date.now() + hours * 3_600_000).toISOString();
+      await snoozeMessage(message.id, until);
+      patchMessage(message.id, { snoozed_until: until });
+      pushToast("Snoozed", `Back ${formatDateTime(until)}`, "success");
+    } catch (error) {
+      const text = String(error);
+      setStatus(text);
+      pushToast("Snooze failed", text, "error");
+    }
+  }
+
+  async function onLabelThread() {
+    const label = threadLabelDraft.trim();
+    if (!selectedAccountId || !selectedThreadId || !label) return;
+    try {
+      await labelThread(selectedAccountId, selectedThreadId, label);
+      setThreadLabelDraft("");
+      setThreadLabels(await listThreadLabels(selectedAccountId, selectedThreadId));
+      setProjectFolders(await listThreadLabelFolders());
+    } catch (error) {
+      const message = String(error);
+      setStatus(message);
+      pushToast("Label failed", message, "error");
+    }
+  }
+
+  async function onUnlabelThread(label: string) {
+    if (!selectedAccountId || !selectedThreadId) return;
+    try {
+      await unlabelThread(selectedAccountId, selectedThreadId, label);
+      setThreadLabels(await listThreadLabels(selectedAccountId, selectedThreadId));
+      setProjectFolders(await listThreadLabelFolders());
+    } catch (error) {
+      const message = String(error);
+      setStatus(message);
+      pushToast("Remove label failed", message, "error");
+    }
+  }
+
+  async function onExportAnalytics(format: AnalyticsFormat) {
+    try {
+      const path = await exportAnalytics(7, format);
+      pushToast("Analytics exported", path, "success");
+    } catch (error) {
+      const message = String(error);
+      setStatus(message);
+      pushToast("Export failed", message, "error");
+    }
+  }
+
+  async function onSaveWeeklyReport(policy: WeeklyReportPolicy) {
+    if (!boot) return;
+
+    const nextConfig = { ...boot.config, weekly_report: policy };
+    try {
+      await saveConfig(nextConfig);
+      setBoot((previous) => (previous ? { ...previous, config: nextConfig } : previous));
+    } catch (error) {
+      const message = String(error);
+      setStatus(message);
+      pushToast("Save failed", message, "error");
+    }
+  }
+
+  async function onSaveDiskSpace(policy: DiskSpacePolicy) {
+    if (!boot) return;
+
+    const nextConfig = { ...boot.config, disk_space: policy };
+    try {
+      await saveConfig(nextConfig);
+      setBoot((previous) => (previous ? { ...previous, config: nextConfig } : previous));
+      setDiskSpace(await diskSpaceStatus());
+    } catch (error) {
+      const message = String(error);
+      setStatus(message);
+      pushToast("Save failed", message, "error");
+    }
+  }
+
+  async function onSaveDownloadDir() {
+    if (!boot) return;
+
+    const attachments = {
+      blocked_extensions: [],
+      allowed_extensions: [],
+      block_encrypted_archives: false,
+      allow_open_anyway: false,
+      ...boot.config.attachments,
+      download_dir: downloadDir.trim() || null,
+    };
+    const nextConfig = { ...boot.config, attachments };
+    try {
+      await saveConfig(nextConfig);
+      setBoot((previous) => (previous ? { ...previous, config: nextConfig } : previous));
+      pushToast("Downloads saved", downloadDir.trim() || "Using the system downloads folder", "success");
+    } catch (error) {
+      const message = String(error);
+      setStatus(message);
+      pushToast("Save failed", message, "error");
+    }
+  }
+
+  async function onSaveSendGuardrails() {
+    if (!boot || !sendGuardrails) return;
+
+    const nextConfig = { ...boot.config, send_guardrails: sendGuardrails };
+    try {
+      await saveConfig(nextConfig);
+      setBoot((previous) => (previous ? { ...previous, config: nextConfig } : previous));
+      pushToast("Guardrails saved", "Outbox send rules updated", "success");
+    } catch (error) {
+      const message = String(error);
+      setStatus(message);
+      pushToast("Save failed", message, "error");
+    }
+  }
+
+  async function onSaveSanitizer() {
+    if (!boot) return;
+
+    const nextConfig = { ...boot.config, sanitizer };
+    try {
+      await saveConfig(nextConfig);
+      setBoot((previous) => (previous ? { ...previous, config: nextConfig } : previous));
+      pushToast("HTML policy saved", "Applies to mail synced from now on", "success");
+    } catch (error) {
+      const message = String(error);
+      setStatus(message);
+      pushToast("Save failed", message, "error");
+    }
+  }
+
+  async function onPreviewRetention() {
+    try {
+      setPurgeCandidates(await retentionPreview());
+    } catch (error) {
+      pushToast("Preview failed", String(error), "error");
+    }
+  }
+
+  async function onPurgeExpired() {
+    try {
+      const purged = await purgeExpiredMail();
+      setPurgeCandidates(null);
+      pushToast("Cleanup complete", `${purged} old messages removed`, "success");
+    } catch (error) {
+      pushToast("Cleanup failed", String(error), "error");
+    }
+  }
+
   async function onSaveLocalAiRuntime() {
     if (!boot) return;
 
@@ -852,6 +1553,147 @@ export default function App() {
           </div>
         </header>
 
+        {offlineQueue && (offlineQueue.pending > 0 || offlineQueue.needs_attention > 0) && (
+          <section className="offline-banner">
+            <strong>{offlineQueue.offline ? "Offline" : "Pending changes"}</strong> —{" "}
+            {offlineQueue.pending} pending change{offlineQueue.pending === 1 ? "" : "s"}
+            {offlineQueue.needs_attention > 0 && `, ${offlineQueue.needs_attention} need attention`}
+            <button className="btn-primary" onClick={() => void onReplayOffline()}>
+              Retry now
+            </button>
+          </section>
+        )}
+
+        {secrets && (secrets.locked || secrets.pending_migration) && !secretsPromptDismissed && (
+          <section className="offline-banner">
+            <strong>
+              {secrets.locked ? "Secrets are locked" : "A system keychain is available again"}
+            </strong>{" "}
+            —{" "}
+            {secrets.locked
+              ? secrets.first_unlock
+                ? "choose a passphrase for the encrypted file that keeps passwords and tokens."
+                : "enter the passphrase of the encrypted file that keeps passwords and tokens."
+              : "enter your secrets passphrase to move them into it."}
+            <input
+              type="password"
+              value={secretsPassphrase}
+              onChange={(event) => setSecretsPassphrase(event.target.value)}
+              placeholder={secrets.first_unlock ? "New passphrase" : "Passphrase"}
+            />
+            {secrets.first_unlock && (
+              <input
+                type="password"
+                value={secretsConfirmation}
+                onChange={(event) => setSecretsConfirmation(event.target.value)}
+                placeholder="Repeat passphrase"
+              />
+            )}
+            <button
+              className="btn-primary"
+              disabled={!secretsPassphrase}
+              onClick={() => void onUnlockSecrets()}
+            >
+              {secrets.locked ? "Unlock" : "Move to keychain"}
+            </button>
+            <button onClick={() => setSecretsPromptDismissed(true)}>Later</button>
+          </section>
+        )}
+
+        {reauthAccounts.map((item) => {
+          const account = accounts.find((candidate) => candidate.id === item.account_id);
+          return (
+            <section key={item.account_id} className="offline-banner">
+              <strong>{account?.email_address ?? "An account"} needs you to sign in again</strong> — the provider
+              no longer accepts its saved sign-in. Sync is paused until you reconnect.
+              <button className="btn-primary" onClick={() => void onBeginReauth(item.account_id)}>
+                Sign in again
+              </button>
+            </section>
+          );
+        })}
+
+        {selectedInitialSync && !selectedInitialSync.completed && (
+          initialSyncHidden[selectedInitialSync.account_id] ? (
+            <section className="backfill-banner">
+              <strong>{selectedInitialSync.paused ? "Backfill paused" : "Backfilling older mail"}</strong> —{" "}
+              {selectedInitialSync.messages_fetched.toLocaleString()} message(s) so far
+              <button onClick={() => void onToggleBackfill(selectedInitialSync)}>
+                {selectedInitialSync.paused ? "Resume" : "Pause"}
+              </button>
+            </section>
+          ) : (
+            <section className="initial-sync">
+              <div className="initial-sync-head">
+                <h3>Setting up {selectedAccount?.email_address ?? "your mailbox"}</h3>
+                <p>
+                  Newest mail arrives first; older mail keeps downloading in the background, even after a
+                  restart.
+                </p>
+              </div>
+              <progress
+                value={selectedInitialSync.messages_fetched}
+                max={selectedInitialSync.messages_total ?? undefined}
+              />
+              <dl className="initial-sync-stats">
+                <div>
+                  <dt>Messages</dt>
+                  <dd>
+                    {selectedInitialSync.messages_fetched.toLocaleString()}
+                    {selectedInitialSync.messages_total !== null &&
+                      ` of ${selectedInitialSync.messages_total.toLocaleString()}`}
+                  </dd>
+                </div>
+                <div>
+                  <dt>Folders</dt>
+                  <dd>
+                    {selectedInitialSync.folders_total > 0
+                      ? `${selectedInitialSync.folders_done} of ${selectedInitialSync.folders_total}`
+                      : "Listing folders…"}
+                  </dd>
+                </div>
+                <div>
+                  <dt>Time left</dt>
+                  <dd>
+                    {selectedInitialSync.paused
+                      ? "Paused"
+                      : selectedInitialSync.eta_seconds !== null
+                        ? formatEta(selectedInitialSync.eta_seconds)
+                        : "Estimating…"}
+                  </dd>
+                </div>
+                {selectedInitialSync.current_folder && (
+                  <div>
+                    <dt>Now</dt>
+                    <dd>{selectedInitialSync.current_folder}</dd>
+                  </div>
+                )}
+              </dl>
+              <div className="initial-sync-actions">
+                <button onClick={() => void onToggleBackfill(selectedInitialSync)}>
+                  {selectedInitialSync.paused ? "Resume backfill" : "Pause backfill"}
+                </button>
+                <button
+                  className="btn-primary"
+                  onClick={() =>
+                    setInitialSyncHidden((current) => ({ ...current, [selectedInitialSync.account_id]: true }))
+                  }
+                >
+                  Continue in background
+                </button>
+              </div>
+            </section>
+          )
+        )}
+
+        {diskSpace?.fidelity === "headers_only" && (
+          <section className="disk-banner">
+            <strong>Low disk space</strong> — {formatBytes(diskSpace.available_bytes)} free. Syncing headers only and
+            not caching attachments. Full sync needs about {formatBytes(diskSpace.projected_need_bytes)} over the
+            next 30 days.
+          </section>
+        )}
+
         {provenance && (
           <section className="provenance-banner">
             <strong>Data provenance:</strong> {provenance.feature} via {provenance.destination} (
@@ -911,17 +1753,102 @@ export default function App() {
                   onKeyDown={(event) => {
                     if (event.key === "Enter") void executeMailSearch(mailQuery);
                   }}
-                  placeholder="Search local index"
+                  placeholder="Search local index (try from: or label:)"
                 />
                 <button onClick={() => void executeMailSearch(mailQuery)}>Search</button>
+                <button onClick={() => void onToggleQueryBuilder()}>Builder</button>
               </div>
 
+              {searchHints.length > 0 && (
+                <div className="search-suggestions">
+                  {searchHints.map((hint) => (
+                    <button key={hint.completion} onClick={() => setMailQuery(hint.completion)}>
+                      {hint.label}
+                    </button>
+                  ))}
+                </div>
+              )}
+
+              {queryDraft && (
+                <div className="query-builder">
+                  {(["from", "to", "subject", "label", "attendee", "text"] as const).map((field) => (
+                    <label key={field}>
+                      {field === "text" ? "Words" : field[0].toUpperCase() + field.slice(1)}
+                      <input
+                        value={queryDraft[field]}
+                        onChange={(event) => setQueryDraft({ ...queryDraft, [field]: event.target.value })}
+                      />
+                    </label>
+                  ))}
+                  <div className="row">
+                    <label>
+                      After
+                      <input
+                        type="date"
+                        value={queryDraft.after ?? ""}
+                        onChange={(event) => setQueryDraft({ ...queryDraft, after: event.target.value || null })}
+                      />
+                    </label>
+                    <label>
+                      Before
+                      <input
+                        type="date"
+                        value={queryDraft.before ?? ""}
+                        onChange={(event) => setQueryDraft({ ...queryDraft, before: event.target.value || null })}
+                      />
+                    </label>
+                    <label>
+                      Event on
+                      <input
+                        type="date"
+                        value={queryDraft.on ?? ""}
+                        onChange={(event) => setQueryDraft({ ...queryDraft, on: event.target.value || null })}
+                      />
+                    </label>
+                    <label>
+                      Task due
+                      <input
+                        type="date"
+                        value={queryDraft.due ?? ""}
+                        onChange={(event) => setQueryDraft({ ...queryDraft, due: event.target.value || null })}
+                      />
+                    </label>
+                  </div>
+                  <div className="row">
+                    {(
+                      [
+                        ["has_attachment", "Has attachment"],
+                        ["unread", "Unread"],
+                        ["pinned", "Pinned"],
+                      ] as const
+                    ).map(([field, text]) => (
+                      <label key={field} className="inline-check">
+                        <input
+                          type="checkbox"
+                          checked={queryDraft[field]}
+                          onChange={(event) => setQueryDraft({ ...queryDraft, [field]: event.target.checked })}
+                        />
+                        {text}
+                      </label>
+                    ))}
+                  </div>
+                  <button className="btn-primary" onClick={() => void onApplyQueryBuilder()}>
+                    Apply
+                  </button>
+                </div>
+              )}
+
               <div className="folder-list">
                 {folders.map((folder) => (
                   <button
                     key={folder.path}
-                    className={selectedFolderPath === folder.path ? "folder-item selected" : "folder-item"}
-                    onClick={() => setSelectedFolderPath(folder.path)}
+                    className={
+                      !selectedProject && selectedFolderPath === folder.path ? "folder-item selected" : "folder-item"
+                    }
+                    onClick={() => {
+                      setSelectedProject(null);
+                      setSelectedFolderPath(folder.path);
+                    }}
                   >
                     <span>{folder.path}</span>
                     <small>{folder.unread_count}/{folder.total_count}</small>
@@ -930,12 +1857,29 @@ export default function App() {
                 {folders.length === 0 && <p className="muted">No folders cached yet. Run sync.</p>}
               </div>
 
+              {projectFolders.length > 0 && (
+                <div className="folder-list">
+                  <h4>Projects</h4>
+                  {projectFolders.map((project) => (
+                    <button
+                      key={project.label}
+                      className={selectedProject === project.label ? "folder-item selected" : "folder-item"}
+                      onClick={() => setSelectedProject(project.label)}
+                    >
+                      <span>{project.label}</span>
+                      <small>{project.unread_count}/{project.thread_count}</small>
+                    </button>
+                  ))}
+                </div>
+              )}
+
               <div className="search-hit-list">
                 {mailResults.slice(0, 8).map((message) => (
                   <button
                     key={message.id}
                     className="search-hit"
                     onClick={() => {
+                      setSelectedProject(null);
                       setSelectedFolderPath(message.folder_path);
                       setSelectedThreadId(message.thread_id);
                     }}
@@ -944,27 +1888,66 @@ export default function App() {
                     <span>{senderFromMessage(message)}</span>
                   </button>
                 ))}
+                {eventResults.slice(0, 8).map((event) => (
+                  <button key={event.id} className="search-hit" onClick={() => setView("calendar")}>
+                    <strong>{event.title || "(Untitled event)"}</strong>
+                    <span>Event · {formatDateTime(event.starts_at)}</span>
+                  </button>
+                ))}
+                {taskResults.slice(0, 8).map((task) => (
+                  <button key={task.id} className="search-hit" onClick={() => setView("tasks")}>
+                    <strong>{task.title}</strong>
+                    <span>Task · due {task.due_at ? formatDateTime(task.due_at) : "—"}</span>
+                  </button>
+                ))}
               </div>
             </article>
 
             <article className="card thread-list-card list-card">
-              <h3>{activeFolder ? `Threads · ${activeFolder.path}` : "Threads"}</h3>
-              <div className="thread-list">
-                {threads.map((thread) => (
-                  <button
-                    key={thread.thread_id}
-                    className={selectedThreadId === thread.thread_id ? "thread-item selected" : "thread-item"}
-                    onClick={() => setSelectedThreadId(thread.thread_id)}
-                  >
-                    <div className="mail-topline">
-                      <strong>{thread.subject || "(No subject)"}</strong>
-                      <time>{formatDateTime(thread.most_recent_at)}</time>
-                    </div>
-                    <span>{thread.participants.slice(0, 2).join(", ") || "No participants"}</span>
-                    <span>{thread.unread_count} unread · {thread.message_count} messages</span>
-                  </button>
-                ))}
-                {threads.length === 0 && <p className="muted">No threads in this folder yet.</p>}
+              <h3>
+                {selectedProject
+                  ? `Threads · ${selectedProject}`
+                  : activeFolder
+                    ? `Threads · ${activeFolder.path}`
+                    : "Threads"}
+              </h3>
+              <div className="thread-list-body">
+                <div className="thread-list" ref={threadListRef}>
+                  {threadSections.map((section) => (
+                    <section key={section.bucket} className="thread-section">
+                      <h4 className="thread-section-head">{DATE_BUCKET_LABELS[section.bucket]}</h4>
+                      {section.threads.map((thread) => (
+                        <button
+                          key={thread.thread_id}
+                          data-month={monthKey(thread.most_recent_at)}
+                          className={selectedThreadId === thread.thread_id ? "thread-item selected" : "thread-item"}
+                          onClick={() => setSelectedThreadId(thread.thread_id)}
+                        >
+                          <div className="mail-topline">
+                            <strong>{thread.subject || "(No subject)"}</strong>
+                            <time>{formatDateTime(thread.most_recent_at)}</time>
+                          </div>
+                          <span>{thread.participants.slice(0, 2).join(", ") || "No participants"}</span>
+                          {thread.snippet && <span className="muted">{thread.snippet}</span>}
+                          <span>
+                            {thread.unread_count} unread · {thread.message_count} messages
+                            {thread.has_attachments && " · 📎"}
+                          </span>
+                        </button>
+                      ))}
+                    </section>
+                  ))}
+                  {threads.length === 0 && <p className="muted">No threads in this folder yet.</p>}
+                </div>
+                {threadMonths.length > 1 && (
+                  <nav className="date-jump" aria-label="Jump to month">
+                    {threadMonths.map((key) => (
+                      <button key={key} onClick={() => jumpToMonth(key)}>
+                        {formatMonthKey(key)}
+                      </button>
+                    ))}
+                  </nav>
+                )}
               </div>
             </article>
 
@@ -990,6 +1973,89 @@ export default function App() {
                     </div>
                   </dl>
 
+                  <div className="thread-labels">
+                    {threadLabels.map((label) => (
+                      <span key={label} className="info-pill">
+                        {label}
+                        <button aria-label={`Remove ${label}`} onClick={() => void onUnlabelThread(label)}>
+                          ×
+                        </button>
+                      </span>
+                    ))}
+                    <input
+                      value={threadLabelDraft}
+                      placeholder="Label thread…"
+                      onChange={(event) => setThreadLabelDraft(event.target.value)}
+                      onKeyDown={(event) => {
+                        if (event.key === "Enter") void onLabelThread();
+                      }}
+                    />
+                  </div>
+
+                  <div className="mute-actions">
+                    <button onClick={() => void onTogglePinned(selectedMessage)}>
+                      {selectedMessage.pinned ? "Unpin" : "Pin"}
+                    </button>
+                    {selectedMessage.snoozed_until ? (
+                      <button onClick={() => void onSnoozeMessage(selectedMessage, null)}>
+                        Unsnooze ({formatDateTime(selectedMessage.snoozed_until)})
+                      </button>
+                    ) : (
+                      <>
+                        <span
+                          className="muted"
+                          title={capabilities?.server_snooze ? undefined : "Snooze is kept on this device only"}
+                        >
+                          Snooze
+                        </span>
+                        <button onClick={() => void onSnoozeMessage(selectedMessage, 3)}>3 hours</button>
+                        <button onClick={() => void onSnoozeMessage(selectedMessage, 24)}>Tomorrow</button>
+                        <button onClick={() => void onSnoozeMessage(selectedMessage, 24 * 7)}>Next week</button>
+                      </>
+                    )}
+                  </div>
+
+                  {selectedMessage.from[0] && (
+                    <div className="mute-actions">
+                      {senderMutes.some(
+                        (mute) =>
+                          mute.account_id === selectedMessage.account_id &&
+                          mute.email === selectedMessage.from[0].address.toLowerCase()
+                      ) ? (
+                        <button onClick={() => void onUnmuteSender(selectedMessage.account_id, selectedMessage.from[0].address)}>
+                          Unmute sender
+                        </button>
+                      ) : (
+                        <>
+                          <span className="muted">Mute sender for</span>
+                          <button onClick={() => void onMuteSender(selectedMessage.account_id, selectedMessage.from[0].address, "week")}>7 days</button>
+                          <button onClick={() => void onMuteSender(selectedMessage.account_id, selectedMessage.from[0].address, "month")}>30 days</button>
+                          <button onClick={() => void onMuteSender(selectedMessage.account_id, selectedMessage.from[0].address, "forever")}>Forever</button>
+                          <label
+                            className="inline-check"
+                            title={capabilities?.move_messages === false ? "This account cannot move mail on the server" : undefined}
+                          >
+                            <input
+                              type="checkbox"
+                              disabled={capabilities?.move_messages === false}
+                              checked={muteAutoArchive && capabilities?.move_messages !== false}
+                              onChange={(event) => setMuteAutoArchive(event.target.checked)}
+                            />
+                            Archive their mail
+                          </label>
+                        </>
+                      )}
+                    </div>
+                  )}
+
+                  {groupExpansions.map((expansion) => (
+                    <p key={expansion.group_id} className="muted group-expansion">
+                      Sent to group <strong>{expansion.group_name}</strong> ({expansion.field.toUpperCase()}):{" "}
+                      {expansion.recipients.map((recipient) => recipient.address).join(", ")}
+                      {expansion.excluded.length > 0 && ` · left out ${expansion.excluded.join(", ")}`}
+                    </p>
+                  ))}
+
                   {selectedMessage.attachments.length > 0 && (
                     <div className="attachment-list">
                       {selectedMessage.attachments.map((attachment: MailAttachment) => (
@@ -998,6 +2064,10 @@ export default function App() {
                           <small>{formatBytes(attachment.size)}</small>
                         </div>
                       ))}
+                      <button onClick={() => void onSaveAllAttachments([selectedMessage])}>Save all</button>
+                      {threadMessages.filter((message) => message.attachments.length > 0).length > 1 && (
+                        <button onClick={() => void onSaveAllAttachments(threadMessages)}>Save all in thread</button>
+                      )}
                     </div>
                   )}
 
@@ -1017,6 +2087,69 @@ export default function App() {
                   onChange={(event) => setComposeTo(event.target.value)}
                   placeholder="to@example.com, other@example.com"
                 />
+                {contactGroups.length > 0 && (
+                  <select value="" onChange={(event) => onAddComposeGroup(event.target.value)}>
+                    <option value="">Add a group…</option>
+                    {contactGroups
+                      .filter((group) => !composeGroups.some((send) => send.group_id === group.id))
+                      .map((group) => (
+                        <option key={group.id} value={group.id}>
+                          {group.name} ({group.members.length})
+                        </option>
+                      ))}
+                  </select>
+                )}
+                {composeGroups.map((send) => {
+                  const group = contactGroups.find((candidate) => candidate.id === send.group_id);
+                  if (!group) return null;
+                  return (
+                    <div key={send.group_id} className="compose-group">
+                      <div className="compose-group-header">
+                        <strong>{group.name}</strong>
+                        <select
+                          value={send.field}
+                          onChange={(event) =>
+                            updateComposeGroup(send.group_id, (current) => ({
+                              ...current,
+                              field: event.target.value as RecipientField,
+                            }))
+                          }
+                        >
+                          <option value="to">To</option>
+                          <option value="cc">Cc</option>
+                          <option value="bcc">Bcc</option>
+                        </select>
+                        <button
+                          onClick={() =>
+                            setComposeGroups((previous) => previous.filter((item) => item.group_id !== send.group_id))
+                          }
+                        >
+                          Remove
+                        </button>
+                      </div>
+                      {group.members.map((member) => {
+                        const key = member.address.toLowerCase();
+                        return (
+                          <label key={key} className="inline-check">
+                            <input
+                              type="checkbox"
+                              checked={!send.excluded.includes(key)}
+                              onChange={(event) =>
+                                updateComposeGroup(send.group_id, (current) => ({
+                                  ...current,
+                                  excluded: event.target.checked
+                                    ? current.excluded.filter((address) => address !== key)
+                                    : [...current.excluded, key],
+                                }))
+                              }
+                            />
+                            {member.name ? `${member.name} <${member.address}>` : member.address}
+                          </label>
+                        );
+                      })}
+                    </div>
+                  );
+                })}
                 <input
                   value={composeSubject}
                   onChange={(event) => setComposeSubject(event.target.value)}
@@ -1047,6 +2180,16 @@ export default function App() {
                     ))}
                   </div>
                 )}
+                {sendGuardrails?.defer_outside_working_hours && (
+                  <label className="inline-check">
+                    <input
+                      type="checkbox"
+                      checked={composeSendAnyway}
+                      onChange={(event) => setComposeSendAnyway(event.target.checked)}
+                    />
+                    Send now even outside working hours
+                  </label>
+                )}
                 <button className="btn-primary" onClick={onSendMail}>Send Draft</button>
               </section>
             </article>
@@ -1100,6 +2243,17 @@ export default function App() {
                         {taskStatusLabel(task.status)}
                       </span>
                       {task.due_at ? <time>Due {formatDateTime(task.due_at)}</time> : <span>No due date</span>}
+                      <label className="task-estimate">
+                        <input
+                          type="number"
+                          min={0}
+                          step={15}
+                          defaultValue={task.estimate_minutes ?? ""}
+                          placeholder="Est."
+                          onBlur={(event) => void onSetTaskEstimate(task, event.target.value)}
+                        />
+                        <span>min</span>
+                      </label>
                     </div>
                   </li>
                 ))}
@@ -1218,6 +2372,373 @@ export default function App() {
               )}
             </article>
 
+            {boot?.config.disk_space && (
+              <article className="card">
+                <h3>Disk Space</h3>
+                <p className="muted">
+                  {diskSpace
+                    ? `${formatBytes(diskSpace.available_bytes)} free of ${formatBytes(diskSpace.total_bytes)}; mail uses ${formatBytes(diskSpace.mail_bytes)} and grows about ${formatBytes(diskSpace.daily_growth_bytes)} a day.`
+                    : "Disk usage is measured by the desktop app."}
+                </p>
+                <label className="inline-check">
+                  <input
+                    type="checkbox"
+                    checked={boot.config.disk_space.degrade_when_low}
+                    onChange={(event) =>
+                      void onSaveDiskSpace({ ...boot.config.disk_space!, degrade_when_low: event.target.checked })
+                    }
+                  />
+                  Sync headers only when free space is low
+                </label>
+                <label>
+                  Minimum free space (MB)
+                  <input
+                    type="number"
+                    min={0}
+                    defaultValue={boot.config.disk_space.min_free_mb}
+                    onBlur={(event) =>
+                      void onSaveDiskSpace({ ...boot.config.disk_space!, min_free_mb: Number(event.target.value) })
+                    }
+                  />
+                </label>
+              </article>
+            )}
+
+            <article className="card">
+              <h3>Mail Analytics</h3>
+              {analytics ? (
+                <>
+                  <p className="muted">
+                    Last 7 days: {analytics.received} received, {analytics.sent} sent, {analytics.replies} replies
+                    {analytics.median_response_hours !== null &&
+                      ` (median response ${analytics.median_response_hours.toFixed(1)} h)`}
+                    .
+                  </p>
+                  <ul className="flat-list">
+                    {analytics.top_correspondents.map((person) => (
+                      <li key={person.address}>
+                        <strong>{person.name || person.address}</strong>{" "}
+                        <small>
+                          {person.received} received · {person.sent} sent
+                        </small>
+                      </li>
+                    ))}
+                  </ul>
+                </>
+              ) : (
+                <p className="muted">Analytics are computed by the desktop app.</p>
+              )}
+              <div className="row">
+                <button onClick={() => void onExportAnalytics("csv")}>Export CSV</button>
+                <button onClick={() => void onExportAnalytics("json")}>Export JSON</button>
+              </div>
+
+              {boot?.config.weekly_report && (
+                <>
+                  <label className="inline-check">
+                    <input
+                      type="checkbox"
+                      checked={boot.config.weekly_report.enabled}
+                      onChange={(event) =>
+                        void onSaveWeeklyReport({ ...boot.config.weekly_report!, enabled: event.target.checked })
+                      }
+                    />
+                    Email me a weekly report
+                  </label>
+                  <label>
+                    Send on
+                    <select
+                      value={boot.config.weekly_report.weekday}
+                      onChange={(event) =>
+                        void onSaveWeeklyReport({
+                          ...boot.config.weekly_report!,
+                          weekday: event.target.value as WeeklyReportPolicy["weekday"],
+                        })
+                      }
+                    >
+                      {(["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"] as const).map((day) => (
+                        <option key={day} value={day}>
+                          {day}
+                        </option>
+                      ))}
+                    </select>
+                  </label>
+                  <label>
+                    At hour
+                    <input
+                      type="number"
+                      min={0}
+                      max={23}
+                      defaultValue={boot.config.weekly_report.hour}
+                      onBlur={(event) =>
+                        void onSaveWeeklyReport({ ...boot.config.weekly_report!, hour: Number(event.target.value) })
+                      }
+                    />
+                  </label>
+                </>
+              )}
+            </article>
+
+            <article className="card">
+              <h3>Downloads</h3>
+              <p className="muted">"Save all" writes attachments here; existing files are never overwritten.</p>
+              <label>
+                Download folder
+                <input
+                  value={downloadDir}
+                  onChange={(event) => setDownloadDir(event.target.value)}
+                  placeholder="System downloads folder"
+                />
+              </label>
+              <button className="btn-primary" onClick={() => void onSaveDownloadDir()}>Save</button>
+
+              <ul className="flat-list">
+                {downloads.length === 0 && <li>No attachments saved yet.</li>}
+                {downloads.map((download) => (
+                  <li key={download.id} title={download.path}>
+                    <strong>{download.file_name}</strong> <small>{formatDateTime(download.saved_at)}</small>
+                    <br />
+                    <small className="muted">{download.path}</small>
+                  </li>
+                ))}
+              </ul>
+            </article>
+
+            <article className="card">
+              <h3>Muted Senders</h3>
+              <p className="muted">
+                Muted senders raise no notifications on the account they were muted on; mutes lift on their own when
+                they expire.
+              </p>
+              <ul className="flat-list">
+                {senderMutes.length === 0 && <li>No muted senders.</li>}
+                {senderMutes.map((mute) => (
+                  <li key={`${mute.account_id}:${mute.email}`}>
+                    <strong>{mute.email}</strong>{" "}
+                    <small>
+                      on {accounts.find((account) => account.id === mute.account_id)?.email_address ?? "a removed account"}
+                      {" · "}
+                      {mute.muted_until ? `until ${formatDateTime(mute.muted_until)}` : "forever"}
+                      {mute.auto_archive ? " · archiving" : ""}
+                    </small>{" "}
+                    <button onClick={() => void onUnmuteSender(mute.account_id, mute.email)}>Unmute</button>
+                  </li>
+                ))}
+              </ul>
+            </article>
+
+            <article className="card">
+              <h3>Contact Groups</h3>
+              <p className="muted">Groups expand into their members when you send; Bcc keeps the list private.</p>
+              <ul className="flat-list">
+                {contactGroups.length === 0 && <li>No contact groups yet.</li>}
+                {contactGroups.map((group) => (
+                  <li key={group.id}>
+                    <strong>{group.name}</strong> <small>{group.members.length} member(s)</small>{" "}
+                    <button
+                      onClick={() =>
+                        setGroupDraft({
+                          id: group.id,
+                          name: group.name,
+                          members: group.members.map((member) => member.address).join(", "),
+                        })
+                      }
+                    >
+                      Edit
+                    </button>{" "}
+                    <button onClick={() => void onDeleteContactGroup(group)}>Delete</button>
+                  </li>
+                ))}
+              </ul>
+              <input
+                value={groupDraft.name}
+                onChange={(event) => setGroupDraft((draft) => ({ ...draft, name: event.target.value }))}
+                placeholder="Group name"
+              />
+              <textarea
+                value={groupDraft.members}
+                onChange={(event) => setGroupDraft((draft) => ({ ...draft, members: event.target.value }))}
+                placeholder="one@example.com, two@example.com"
+              />
+              <button className="btn-primary" onClick={() => void onSaveContactGroup()}>
+                {groupDraft.id ? "Update group" : "Create group"}
+              </button>
+            </article>
+
+            {sendGuardrails && (
+              <article className="card">
+                <h3>Send Guardrails</h3>
+                <p className="muted">Mail written outside working hours waits in the Outbox until the next workday.</p>
+
+                <label className="inline-check">
+                  <input
+                    type="checkbox"
+                    checked={sendGuardrails.defer_outside_working_hours}
+                    onChange={(event) =>
+                      setSendGuardrails({ ...sendGuardrails, defer_outside_working_hours: event.target.checked })
+                    }
+                  />
+                  Hold mail until working hours
+                </label>
+
+                <div className="row">
+                  <label>
+                    Workday starts (hour)
+                    <input
+                      type="number"
+                      min={0}
+                      max={23}
+                      value={sendGuardrails.workday_start_hour}
+                      onChange={(event) =>
+                        setSendGuardrails({ ...sendGuardrails, workday_start_hour: Number(event.target.value) })
+                      }
+                    />
+                  </label>
+                  <label>
+                    Workday ends (hour)
+                    <input
+                      type="number"
+                      min={1}
+                      max={24}
+                      value={sendGuardrails.workday_end_hour}
+                      onChange={(event) =>
+                        setSendGuardrails({ ...sendGuardrails, workday_end_hour: Number(event.target.value) })
+                      }
+                    />
+                  </label>
+                </div>
+
+                <label className="inline-check">
+                  <input
+                    type="checkbox"
+                    checked={sendGuardrails.skip_weekends}
+                    onChange={(event) => setSendGuardrails({ ...sendGuardrails, skip_weekends: event.target.checked })}
+                  />
+                  Skip weekends
+                </label>
+
+                <label className="inline-check">
+                  <input
+                    type="checkbox"
+                    checked={sendGuardrails.throttle_sends}
+                    onChange={(event) => setSendGuardrails({ ...sendGuardrails, throttle_sends: event.target.checked })}
+                  />
+                  Throttle bulk sends
+                </label>
+
+                <label>
+                  Messages per minute, per account
+                  <input
+                    type="number"
+                    min={1}
+                    value={sendGuardrails.max_sends_per_minute}
+                    onChange={(event) =>
+                      setSendGuardrails({ ...sendGuardrails, max_sends_per_minute: Number(event.target.value) })
+                    }
+                  />
+                </label>
+
+                <button className="btn-primary" onClick={() => void onSaveSendGuardrails()}>Save</button>
+              </article>
+            )}
+
+            <article className="card">
+              <h3>Message HTML</h3>
+              <p className="muted">Scripts, forms and event handlers are always removed; the level decides how much layout survives.</p>
+              <select
+                value={sanitizer.level}
+                onChange={(event) => setSanitizer({ ...sanitizer, level: event.target.value as SanitizerLevel })}
+              >
+                <option value="strict">Strict: text formatting and links only</option>
+                <option value="standard">Standard: layout, images and safe inline CSS</option>
+                <option value="permissive">Permissive: also legacy font and table styling</option>
+              </select>
+              <label className="inline-check">
+                <input
+                  type="checkbox"
+                  checked={sanitizer.strip_css}
+                  onChange={(event) => setSanitizer({ ...sanitizer, strip_css: event.target.checked })}
+                />
+                Strip all CSS
+              </label>
+              <button className="btn-primary" onClick={() => void onSaveSanitizer()}>Save</button>
+            </article>
+
+            {retention && (
+              <article className="card">
+                <h3>Trash &amp; Junk Cleanup</h3>
+                <p className="muted">Old messages are deleted on the server and locally. 0 days keeps mail forever.</p>
+
+                <label className="inline-check">
+                  <input
+                    type="checkbox"
+                    checked={retention.enabled}
+                    onChange={(event) => setRetention({ ...retention, enabled: event.target.checked })}
+                  />
+                  Clean up automatically
+                </label>
+
+                <div className="row">
+                  <label>
+                    Trash (days)
+                    <input
+                      type="number"
+                      min={0}
+                      value={retention.trash_days}
+                      onChange={(event) => setRetention({ ...retention, trash_days: Number(event.target.value) })}
+                    />
+                  </label>
+                  <label>
+                    Junk (days)
+                    <input
+                      type="number"
+                      min={0}
+                      value={retention.junk_days}
+                      onChange={(event) => setRetention({ ...retention, junk_days: Number(event.target.value) })}
+                    />
+                  </label>
+                </div>
+
+                <label>
+                  Folder overrides (one "folder = days" per line)
+                  <textarea
+                    value={retentionOverrides}
+                    onChange={(event) => setRetentionOverrides(event.target.value)}
+                    placeholder="[Gmail]/Spam = 7"
+                  />
+                </label>
+
+                <div className="row">
+                  <button className="btn-primary" onClick={() => void onSaveRetention()}>Save</button>
+                  <button disabled={!boot?.config.retention?.enabled} onClick={() => void onPreviewRetention()}>
+                    Preview
+                  </button>
+                  <button disabled={!boot?.config.retention?.enabled} onClick={() => void onPurgeExpired()}>
+                    Clean up now
+                  </button>
+                </div>
+                {!boot?.config.retention?.enabled && (
+                  <p className="muted">Turn on cleanup and save to preview or clean up now.</p>
+                )}
+
+                {purgeCandidates && (
+                  <ul className="flat-list">
+                    {purgeCandidates.length === 0 && <li>Nothing is old enough to remove.</li>}
+                    {Object.entries(
+                      purgeCandidates.reduce<Record<string, number>>((counts, candidate) => {
+                        counts[candidate.folder_path] = (counts[candidate.folder_path] ?? 0) + 1;
+                        return counts;
+                      }, {})
+                    ).map(([folder, count]) => (
+                      <li key={folder}>
+                        {folder}: {count} message{count === 1 ? "" : "s"}
+                      </li>
+                    ))}
+                  </ul>
+                )}
+              </article>
+            )}
+
             <article className="card oauth-card">
               <h3>Account Onboarding (OAuth PKCE)</h3>
               <p className="muted">Data goes only to selected providers. Tokens are stored in OS keychain.</p>
@@ -1,20 +1,52 @@
 import type {
   Account,
+  AccountReauth,
   AiTaskExtractionResult,
+  AnalyticsFormat,
   AppConfig,
+  AttachmentDownload,
+  BackendCapabilities,
   BeginOAuthResponse,
   BootstrapResponse,
   CompleteOAuthResponse,
+  ContactGroup,
   DataProvenance,
+  DiskSpaceReport,
+  DryRunReport,
+  EmailSignature,
+  EmailTemplate,
+  GroupExpansion,
+  GroupSend,
+  InitialSyncProgress,
+  MailAnalytics,
   MailFolder,
   MailMessage,
+  MailRule,
   MailThreadSummary,
+  MeetingAgenda,
+  MuteDuration,
   OAuthBeginPayload,
   OAuthCompletePayload,
+  OfflineQueueStatus,
   OutgoingMail,
+  ProtocolExchange,
+  ProtocolLogStatus,
+  PurgeCandidate,
   ReminderTask,
+  ReplyIdentity,
+  RuleAction,
+  SaveAllReport,
+  SearchQueryDraft,
   SearchResult,
+  SecretsStatus,
+  SearchSuggestion,
+  SendIdentity,
+  SendMailResult,
+  SenderMute,
+  SupportBundle,
   SyncRunSummary,
+  ThreadLabelFolder,
+  UnifiedSearchResults,
   ValidateLocalAiRuntimePayload,
   ValidateLocalAiRuntimeResponse,
 } from "../types";
@@ -87,6 +119,265 @@ export async function saveConfig(config: AppConfig): Promise<void> {
   await invoke("save_config", { config });
 }
 
+export async function retentionPreview(accountId?: string): Promise<PurgeCandidate[]> {
+  const invoke = await getInvoke();
+  if (!invoke) return [];
+
+  return invoke("retention_preview", {
+    payload: {
+      account_id: accountId ?? null,
+    },
+  });
+}
+
+export async function saveAllAttachments(messageIds: string[], directory?: string): Promise<SaveAllReport> {
+  const invoke = await getInvoke();
+  if (!invoke) {
+    throw new Error("Saving attachments requires the Tauri runtime");
+  }
+
+  return invoke("save_all_attachments", {
+    payload: {
+      message_ids: messageIds,
+      directory: directory ?? null,
+    },
+  });
+}
+
+export async function recentDownloads(limit = 20): Promise<AttachmentDownload[]> {
+  const invoke = await getInvoke();
+  if (!invoke) return [];
+
+  return invoke("recent_downloads", { limit });
+}
+
+export async function muteSender(
+  accountId: string,
+  email: string,
+  duration: MuteDuration,
+  autoArchive: boolean,
+): Promise<SenderMute> {
+  const invoke = await getInvoke();
+  if (!invoke) {
+    throw new Error("Muting senders requires the Tauri runtime");
+  }
+
+  return invoke("mute_sender", {
+    payload: {
+      account_id: accountId,
+      email,
+      duration,
+      auto_archive: autoArchive,
+    },
+  });
+}
+
+export async function unmuteSender(accountId: string, email: string): Promise<void> {
+  const invoke = await getInvoke();
+  if (!invoke) return;
+
+  await invoke("unmute_sender", { accountId, email });
+}
+
+export async function accountCapabilities(accountId: string): Promise<BackendCapabilities | null> {
+  const invoke = await getInvoke();
+  if (!invoke) return null;
+
+  return invoke("account_capabilities", { accountId });
+}
+
+export async function initialSyncProgress(accountId: string): Promise<InitialSyncProgress | null> {
+  const invoke = await getInvoke();
+  if (!invoke) return null;
+
+  return invoke("initial_sync_progress", { accountId });
+}
+
+export async function setBackfillPaused(accountId: string, paused: boolean): Promise<InitialSyncProgress | null> {
+  const invoke = await getInvoke();
+  if (!invoke) return null;
+
+  return invoke("set_backfill_paused", { accountId, paused });
+}
+
+export async function snoozeMessage(messageId: string, until: string): Promise<void> {
+  const invoke = await getInvoke();
+  if (!invoke) return;
+
+  await invoke("snooze_message", { payload: { message_id: messageId, until } });
+}
+
+export async function unsnoozeMessage(messageId: string): Promise<void> {
+  const invoke = await getInvoke();
+  if (!invoke) return;
+
+  await invoke("unsnooze_message", { messageId });
+}
+
+export async function setPinned(messageId: string, pinned: boolean): Promise<void> {
+  const invoke = await getInvoke();
+  if (!invoke) return;
+
+  await invoke("set_pinned", { payload: { message_id: messageId, pinned } });
+}
+
+export async function listRules(): Promise<MailRule[]> {
+  const invoke = await getInvoke();
+  if (!invoke) return [];
+
+  return invoke("list_rules");
+}
+
+export async function saveRule(rule: MailRule): Promise<MailRule> {
+  const invoke = await getInvoke();
+  if (!invoke) {
+    throw new Error("Saving rules requires the Tauri runtime");
+  }
+
+  return invoke("save_rule", { payload: { rule } });
+}
+
+/** Which stored messages `rule` would change; nothing is saved or applied. */
+export async function dryRunRule(rule: MailRule, folderPath?: string): Promise<DryRunReport> {
+  const invoke = await getInvoke();
+  if (!invoke) {
+    throw new Error("Rule previews require the Tauri runtime");
+  }
+
+  return invoke("dry_run_rule", { payload: { rule, folder_path: folderPath ?? null } });
+}
+
+/** Which of `messageIds` a bulk action would change; nothing is applied. */
+export async function dryRunBulkAction(messageIds: string[], action: RuleAction): Promise<DryRunReport> {
+  const invoke = await getInvoke();
+  if (!invoke) {
+    throw new Error("Bulk action previews require the Tauri runtime");
+  }
+
+  return invoke("dry_run_bulk_action", { payload: { message_ids: messageIds, action } });
+}
+
+export async function deleteRule(ruleId: string): Promise<void> {
+  const invoke = await getInvoke();
+  if (!invoke) return;
+
+  await invoke("delete_rule", { ruleId });
+}
+
+export async function listSignatures(accountId: string | null): Promise<EmailSignature[]> {
+  const invoke = await getInvoke();
+  if (!invoke) return [];
+
+  return invoke("list_signatures", { accountId });
+}
+
+export async function saveSignature(signature: EmailSignature): Promise<EmailSignature> {
+  const invoke = await getInvoke();
+  if (!invoke) {
+    throw new Error("Saving signatures requires the Tauri runtime");
+  }
+
+  return invoke("save_signature", { payload: { signature } });
+}
+
+export async function deleteSignature(signatureId: string): Promise<void> {
+  const invoke = await getInvoke();
+  if (!invoke) return;
+
+  await invoke("delete_signature", { signatureId });
+}
+
+export async function listTemplates(): Promise<EmailTemplate[]> {
+  const invoke = await getInvoke();
+  if (!invoke) return [];
+
+  return invoke("list_templates");
+}
+
+export async function saveTemplate(template: EmailTemplate): Promise<EmailTemplate> {
+  const invoke = await getInvoke();
+  if (!invoke) {
+    throw new Error("Saving templates requires the Tauri runtime");
+  }
+
+  return invoke("save_template", { payload: { template } });
+}
+
+export async function deleteTemplate(templateId: string): Promise<void> {
+  const invoke = await getInvoke();
+  if (!invoke) return;
+
+  await invoke("delete_template", { templateId });
+}
+
+export async function listSenderMutes(): Promise<SenderMute[]> {
+  const invoke = await getInvoke();
+  if (!invoke) return [];
+
+  return invoke("list_sender_mutes");
+}
+
+export async function listContactGroups(): Promise<ContactGroup[]> {
+  const invoke = await getInvoke();
+  if (!invoke) return [];
+
+  return invoke("list_contact_groups");
+}
+
+export async function saveContactGroup(group: ContactGroup): Promise<ContactGroup> {
+  const invoke = await getInvoke();
+  if (!invoke) {
+    throw new Error("Saving contact groups requires the Tauri runtime");
+  }
+
+  return invoke("save_contact_group", { group });
+}
+
+export async function deleteContactGroup(id: string): Promise<void> {
+  const invoke = await getInvoke();
+  if (!invoke) return;
+
+  await invoke("delete_contact_group", { id });
+}
+
+export async function messageGroupExpansions(messageId: string): Promise<GroupExpansion[]> {
+  const invoke = await getInvoke();
+  if (!invoke) return [];
+
+  return invoke("message_group_expansions", { messageId });
+}
+
+export async function mailAnalytics(days = 7): Promise<MailAnalytics | null> {
+  const invoke = await getInvoke();
+  if (!invoke) return null;
+
+  return invoke("mail_analytics", { days });
+}
+
+/** Writes the export to the download folder and returns its path. */
+export async function exportAnalytics(days: number, format: AnalyticsFormat): Promise<string> {
+  const invoke = await getInvoke();
+  if (!invoke) {
+    throw new Error("Exporting analytics requires the Tauri runtime");
+  }
+
+  return invoke("export_analytics", { payload: { days, format } });
+}
+
+export async function diskSpaceStatus(): Promise<DiskSpaceReport | null> {
+  const invoke = await getInvoke();
+  if (!invoke) return null;
+
+  return invoke("disk_space_status");
+}
+
+export async function purgeExpiredMail(): Promise<number> {
+  const invoke = await getInvoke();
+  if (!invoke) return 0;
+
+  return invoke("purge_expired_mail");
+}
+
 export async function listAccounts(): Promise<Account[]> {
   const invoke = await getInvoke();
   return invoke ? invoke<Account[]>("list_accounts") : [];
@@ -120,6 +411,13 @@ export async function runSyncQueue(): Promise<SyncRunSummary> {
       };
 }
 
+export async function replayPendingOperations(): Promise<OfflineQueueStatus> {
+  const invoke = await getInvoke();
+  return invoke
+    ? invoke<OfflineQueueStatus>("replay_pending_operations")
+    : { pending: 0, needs_attention: 0, replayed: 0, offline: false };
+}
+
 export async function listMailFolders(
   accountId: string,
   refreshRemote = false
@@ -166,20 +464,109 @@ export async function listThreadMessages(accountId: string, threadId: string): P
   });
 }
 
-export async function sendMail(accountId: string, outgoing: OutgoingMail): Promise<void> {
+export async function listThreadLabels(accountId: string, threadId: string): Promise<string[]> {
+  const invoke = await getInvoke();
+  if (!invoke) return [];
+
+  return invoke("list_thread_labels", {
+    payload: {
+      account_id: accountId,
+      thread_id: threadId,
+    },
+  });
+}
+
+export async function labelThread(accountId: string, threadId: string, label: string): Promise<void> {
+  const invoke = await getInvoke();
+  if (!invoke) {
+    throw new Error("Labelling threads requires the Tauri runtime");
+  }
+
+  await invoke("label_thread", {
+    payload: {
+      account_id: accountId,
+      thread_id: threadId,
+      label,
+    },
+  });
+}
+
+export async function unlabelThread(accountId: string, threadId: string, label: string): Promise<void> {
+  const invoke = await getInvoke();
+  if (!invoke) return;
+
+  await invoke("unlabel_thread", {
+    payload: {
+      account_id: accountId,
+      thread_id: threadId,
+      label,
+    },
+  });
+}
+
+export async function listThreadLabelFolders(): Promise<ThreadLabelFolder[]> {
+  const invoke = await getInvoke();
+  if (!invoke) return [];
+
+  return invoke("list_thread_label_folders");
+}
+
+export async function listLabelledThreads(
+  label: string,
+  accountId?: string,
+  limit = 200
+): Promise<MailThreadSummary[]> {
+  const invoke = await getInvoke();
+  if (!invoke) return [];
+
+  return invoke("list_labelled_threads", {
+    payload: {
+      label,
+      account_id: accountId ?? null,
+      limit,
+    },
+  });
+}
+
+export async function sendMail(
+  accountId: string,
+  outgoing: OutgoingMail,
+  sendAnyway = false,
+  groups: GroupSend[] = []
+): Promise<SendMailResult> {
   const invoke = await getInvoke();
   if (!invoke) {
     throw new Error("Sending mail requires the Tauri runtime");
   }
 
-  await invoke("send_mail", {
+  return invoke("send_mail", {
     payload: {
       account_id: accountId,
       outgoing,
+      send_anyway: sendAnyway,
+      groups,
     },
   });
 }
 
+/** Account addresses and plus-address aliases mail can be sent from. */
+export async function listSendIdentities(): Promise<SendIdentity[]> {
+  const invoke = await getInvoke();
+  if (!invoke) return [];
+
+  return invoke("list_send_identities");
+}
+
+/** The identity to reply from, picked from the address the message was delivered to. */
+export async function replyIdentity(messageId: string): Promise<ReplyIdentity> {
+  const invoke = await getInvoke();
+  if (!invoke) {
+    throw new Error("Reply identities require the Tauri runtime");
+  }
+
+  return invoke("reply_identity", { messageId });
+}
+
 export async function searchMail(query: string): Promise<SearchResult<MailMessage>> {
   const invoke = await getInvoke();
   if (!invoke) return { total: 0, items: [] };
@@ -192,6 +579,44 @@ export async function searchMail(query: string): Promise<SearchResult<MailMessag
   });
 }
 
+export async function searchAll(query: string): Promise<UnifiedSearchResults> {
+  const invoke = await getInvoke();
+  if (!invoke) return { messages: [], events: [], tasks: [] };
+
+  return invoke("search_all", {
+    payload: {
+      query,
+      limit: 40,
+    },
+  });
+}
+
+export async function searchSuggestions(query: string): Promise<SearchSuggestion[]> {
+  const invoke = await getInvoke();
+  if (!invoke) return [];
+
+  return invoke("search_suggestions", {
+    payload: {
+      query,
+      limit: 8,
+    },
+  });
+}
+
+export async function parseSearchQuery(query: string): Promise<SearchQueryDraft | null> {
+  const invoke = await getInvoke();
+  if (!invoke) return null;
+
+  return invoke("parse_search_query", { query });
+}
+
+export async function buildSearchQuery(draft: SearchQueryDraft): Promise<string> {
+  const invoke = await getInvoke();
+  if (!invoke) return "";
+
+  return invoke("build_search_query", { draft });
+}
+
 export async function listTasks(accountId: string): Promise<ReminderTask[]> {
   const invoke = await getInvoke();
   if (!invoke) return [];
@@ -213,6 +638,23 @@ export async function createTaskFromText(accountId: string, text: string): Promi
   });
 }
 
+export async function setTaskEstimate(
+  taskId: string,
+  minutes: number | null
+): Promise<ReminderTask> {
+  const invoke = await getInvoke();
+  if (!invoke) {
+    throw new Error("Task estimates require the Tauri runtime");
+  }
+
+  return invoke("set_task_estimate", {
+    payload: {
+      task_id: taskId,
+      minutes,
+    },
+  });
+}
+
 export async function importIcs(accountId: string, icsPayload: string): Promise<number> {
   const invoke = await getInvoke();
   if (!invoke) return 0;
@@ -245,7 +687,9 @@ export async function exportIcs(accountId: string): Promise<string> {
   });
 }
 
+/** `accountId` owns the content; its AI policy decides whether `mode` is allowed. */
 export async function aiSummarize(
+  accountId: string,
   subject: string,
   body: string,
   mode: "local" | "cloud"
@@ -265,6 +709,7 @@ export async function aiSummarize(
 
   return invoke("ai_summarize_email", {
     payload: {
+      account_id: accountId,
       subject,
       body,
       mode,
@@ -282,6 +727,40 @@ export async function beginOAuthPkce(payload: OAuthBeginPayload): Promise<BeginO
   return invoke("begin_oauth_pkce", { payload });
 }
 
+export async function secretsStatus(): Promise<SecretsStatus | null> {
+  const invoke = await getInvoke();
+  if (!invoke) return null;
+
+  return invoke("secrets_status");
+}
+
+/** Unlock the encrypted secrets file; `confirmation` is required on first use. */
+export async function unlockSecrets(passphrase: string, confirmation?: string): Promise<number> {
+  const invoke = await getInvoke();
+  if (!invoke) {
+    throw new Error("Unlocking secrets requires the Tauri runtime");
+  }
+
+  return invoke("unlock_secrets", { payload: { passphrase, confirmation: confirmation ?? null } });
+}
+
+export async function listAccountReauth(): Promise<AccountReauth[]> {
+  const invoke = await getInvoke();
+  if (!invoke) return [];
+
+  return invoke("list_account_reauth");
+}
+
+/** Start consent again for an existing account; finish with completeOAuthPkce. */
+export async function beginReauth(accountId: string): Promise<BeginOAuthResponse> {
+  const invoke = await getInvoke();
+  if (!invoke) {
+    throw new Error("Signing in again requires the Tauri runtime");
+  }
+
+  return invoke("begin_reauth", { accountId });
+}
+
 export async function completeOAuthPkce(
   payload: OAuthCompletePayload
 ): Promise<CompleteOAuthResponse> {
@@ -293,7 +772,9 @@ export async function completeOAuthPkce(
   return invoke("complete_oauth_pkce", { payload });
 }
 
+/** `accountId` owns the content; its AI policy decides whether `mode` is allowed. */
 export async function aiSuggestReply(
+  accountId: string,
   subject: string,
   body: string,
   mode: "local" | "cloud"
@@ -313,6 +794,7 @@ export async function aiSuggestReply(
 
   return invoke("ai_suggest_reply", {
     payload: {
+      account_id: accountId,
       subject,
       body,
       mode,
@@ -343,6 +825,31 @@ export async function aiCreateTasksFromEmail(
   });
 }
 
+/** Agenda for a new meeting from recent threads with `attendees`; AI drafts it when `useAi`. */
+export async function suggestMeetingAgenda(
+  accountId: string,
+  title: string,
+  attendees: string[],
+  useAi: boolean,
+  mode: "local" | "cloud"
+): Promise<MeetingAgenda> {
+  const invoke = await getInvoke();
+  if (!invoke) {
+    return { items: [], sources: [], provenance: null, markdown: "" };
+  }
+
+  return invoke("suggest_meeting_agenda", {
+    payload: {
+      account_id: accountId,
+      title,
+      attendees,
+      use_ai: useAi,
+      mode,
+      cloud_provider: mode === "cloud" ? "open_ai" : null,
+    },
+  });
+}
+
 export async function validateLocalAiRuntime(
   payload: ValidateLocalAiRuntimePayload
 ): Promise<ValidateLocalAiRuntimeResponse> {
@@ -356,3 +863,44 @@ export async function validateLocalAiRuntime(
 
   return invoke("validate_local_ai_runtime", { payload });
 }
+
+export async function protocolLogStatus(): Promise<ProtocolLogStatus> {
+  const invoke = await getInvoke();
+  if (!invoke) return { enabled: false, capacity: 0, accounts: [] };
+
+  return invoke("protocol_log_status");
+}
+
+/** Start or stop recording protocol exchanges for the debug console. */
+export async function setProtocolLogging(enabled: boolean, capacity?: number): Promise<ProtocolLogStatus> {
+  const invoke = await getInvoke();
+  if (!invoke) {
+    throw new Error("Protocol logging requires the Tauri runtime");
+  }
+
+  return invoke("set_protocol_logging", { payload: { enabled, capacity: capacity ?? null } });
+}
+
+export async function listProtocolExchanges(account?: string): Promise<ProtocolExchange[]> {
+  const invoke = await getInvoke();
+  if (!invoke) return [];
+
+  return invoke("list_protocol_exchanges", { account: account ?? null });
+}
+
+export async function clearProtocolLog(): Promise<void> {
+  const invoke = await getInvoke();
+  if (!invoke) return;
+
+  await invoke("clear_protocol_log");
+}
+
+/** Recorded exchanges with app version and OS, to attach to a support request. */
+export async function exportSupportBundle(account?: string): Promise<SupportBundle> {
+  const invoke = await getInvoke();
+  if (!invoke) {
+    throw new Error("Support bundles require the Tauri runtime");
+  }
+
+  return invoke("export_support_bundle", { account: account ?? null });
+}
@@ -27,6 +27,37 @@ export interface Account {
   updated_at: string;
 }
 
+/** An address mail can be sent from: an account's own or one of its aliases. */
+export interface SendIdentity {
+  account_id: string;
+  name: string;
+  address: string;
+}
+
+/** The suggested From for a reply and the account that received the message. */
+export interface ReplyIdentity {
+  suggested: SendIdentity;
+  delivered_to: string | null;
+  received_by: SendIdentity;
+}
+
+/** An account whose OAuth grant was revoked; sync waits for a new sign-in. */
+export interface SecretsStatus {
+  /** `keychain` or `encrypted_file`. */
+  backend: "keychain" | "encrypted_file";
+  locked: boolean;
+  /** No encrypted file exists yet, so unlocking sets a new passphrase. */
+  first_unlock: boolean;
+  /** An encrypted file is waiting to be moved into the keychain. */
+  pending_migration: boolean;
+}
+
+export interface AccountReauth {
+  account_id: string;
+  reason: string;
+  detected_at: string;
+}
+
 export interface MailFolder {
   account_id: string;
   remote_id: string;
@@ -43,6 +74,20 @@ export interface MailThreadSummary {
   message_count: number;
   unread_count: number;
   most_recent_at: string;
+  /** Preview of the most recent message. */
+  snippet: string;
+  has_attachments: boolean;
+  date_bucket: DateBucket;
+}
+
+export type DateBucket = "today" | "yesterday" | "this_week" | "earlier";
+
+/** A project smart folder: every thread carrying one thread label. */
+export interface ThreadLabelFolder {
+  label: string;
+  color: string | null;
+  thread_count: number;
+  unread_count: number;
 }
 
 export interface MailAddress {
@@ -88,6 +133,87 @@ export interface MailMessage {
   attachments: MailAttachment[];
   sent_at?: string | null;
   received_at: string;
+  snoozed_until?: string | null;
+  pinned?: boolean;
+}
+
+export type RuleField = "from" | "to" | "subject" | "body" | "has_attachment";
+export type RuleOperator = "contains" | "not_contains" | "equals" | "starts_with" | "ends_with" | "matches";
+
+export interface RuleCondition {
+  field: RuleField;
+  operator: RuleOperator;
+  value: string;
+}
+
+export type RuleAction =
+  | { move_to: string }
+  | { label: string }
+  | { label_thread: string }
+  | "mark_read"
+  | "archive"
+  | "delete"
+  | "pin"
+  | "flag";
+
+/** Saved with a nil id ("00000000-…") to create; the saved rule comes back with its id. */
+export interface MailRule {
+  id: string;
+  account_id: string | null;
+  name: string;
+  enabled: boolean;
+  conditions: RuleCondition[];
+  match_all: boolean;
+  actions: RuleAction[];
+  stop_processing: boolean;
+  order: number;
+}
+
+/** A message a dry run found, with the actions that would change it. */
+export interface DryRunSample {
+  message_id: string;
+  account_id: string;
+  thread_id: string;
+  folder_path: string;
+  subject: string;
+  sender: string;
+  received_at: string;
+  actions: RuleAction[];
+}
+
+/** What a rule or bulk action would do, worked out without changing anything. */
+export interface DryRunReport {
+  scanned: number;
+  matched: number;
+  affected: number;
+  per_action: Array<{ action: RuleAction; messages: number }>;
+  samples: DryRunSample[];
+}
+
+/** Server-side operations an account's backend supports. */
+export interface BackendCapabilities {
+  move_messages: boolean;
+  server_labels: boolean;
+  server_snooze: boolean;
+  push: boolean;
+  headers_only_sync: boolean;
+}
+
+export interface EmailSignature {
+  id: string;
+  account_id: string | null;
+  name: string;
+  body_html: string;
+  body_text: string;
+  is_default: boolean;
+}
+
+export interface EmailTemplate {
+  id: string;
+  name: string;
+  subject: string;
+  body_html: string;
+  body_text: string;
 }
 
 export interface SearchResult<T> {
@@ -95,6 +221,98 @@ export interface SearchResult<T> {
   items: T[];
 }
 
+export interface SearchSuggestion {
+  label: string;
+  completion: string;
+}
+
+export interface SearchQueryDraft {
+  text: string;
+  from: string;
+  to: string;
+  subject: string;
+  label: string;
+  has_attachment: boolean;
+  unread: boolean;
+  pinned: boolean;
+  after: string | null;
+  before: string | null;
+  on: string | null;
+  attendee: string;
+  due: string | null;
+}
+
+/** Payload of `sync://progress`, sent as each job of a sync run finishes. */
+export interface SyncProgressEvent {
+  job_id: string;
+  account_id: string;
+  domain: "email" | "calendar" | "tasks";
+  status: "queued" | "running" | "failed" | "completed";
+  finished_jobs: number;
+  total_jobs: number;
+}
+
+/** Payload of `mail://new-messages`. */
+export interface NewMessagesEvent {
+  account_id: string;
+  folder_path: string;
+  /** Messages stored by the sync, new or with changed flags; not unseen mail. */
+  changed: number;
+}
+
+export type ReminderKind = "mail_snooze" | "follow_up" | "event_alert" | "task_due";
+
+/** Payload of `reminder://due`. */
+export interface Reminder {
+  id: string;
+  kind: ReminderKind;
+  target_id: string;
+  account_id: string;
+  trigger_at: string;
+  expires_at: string | null;
+  lead_minutes: number;
+  title: string;
+  detail: string | null;
+}
+
+/** Payload of `calendar://updated`. */
+export interface CalendarUpdatedEvent {
+  account_id: string;
+  events_synced: number;
+}
+
+/** Initial sync and backfill progress; also the payload of `sync://initial-progress`. */
+export interface InitialSyncProgress {
+  account_id: string;
+  messages_fetched: number;
+  messages_total: number | null;
+  folders_done: number;
+  folders_total: number;
+  paused: boolean;
+  completed: boolean;
+  eta_seconds: number | null;
+  current_folder: string | null;
+}
+
+export interface CalendarEvent {
+  id: string;
+  account_id: string;
+  calendar_id: string;
+  title: string;
+  location: string | null;
+  starts_at: string;
+  ends_at: string;
+  all_day: boolean;
+  attendees: string[];
+}
+
+/** One search-bar query: `on:`/`attendee:` find events, `due:` finds tasks. */
+export interface UnifiedSearchResults {
+  messages: MailMessage[];
+  events: CalendarEvent[];
+  tasks: ReminderTask[];
+}
+
 export interface ReminderTask {
   id: string;
   account_id: string;
@@ -103,6 +321,7 @@ export interface ReminderTask {
   due_at: string | null;
   priority: "low" | "normal" | "high" | "critical";
   status: "not_started" | "in_progress" | "completed" | "canceled";
+  estimate_minutes: number | null;
 }
 
 export interface AppConfig {
@@ -145,6 +364,126 @@ export interface AppConfig {
     default_start_page: string;
     timezone: string | null;
   };
+  attachments?: AttachmentConfig;
+  retention?: RetentionPolicy;
+  send_guardrails?: SendGuardrails;
+  disk_space?: DiskSpacePolicy;
+  weekly_report?: WeeklyReportPolicy;
+  sanitizer?: SanitizerPolicy;
+}
+
+export type SanitizerLevel = "strict" | "standard" | "permissive";
+
+export interface SanitizerPolicy {
+  level: SanitizerLevel;
+  strip_css: boolean;
+}
+
+export type Weekday = "Mon" | "Tue" | "Wed" | "Thu" | "Fri" | "Sat" | "Sun";
+
+export interface WeeklyReportPolicy {
+  enabled: boolean;
+  weekday: Weekday;
+  hour: number;
+  top_correspondents: number;
+}
+
+export type AnalyticsFormat = "csv" | "json";
+
+export interface CorrespondentStats {
+  address: string;
+  name: string | null;
+  received: number;
+  sent: number;
+}
+
+export interface MailAnalytics {
+  since: string;
+  until: string;
+  received: number;
+  sent: number;
+  daily: { date: string; received: number; sent: number }[];
+  replies: number;
+  avg_response_hours: number | null;
+  median_response_hours: number | null;
+  top_correspondents: CorrespondentStats[];
+}
+
+export interface DiskSpacePolicy {
+  degrade_when_low: boolean;
+  min_free_mb: number;
+}
+
+export interface DiskSpaceReport {
+  available_bytes: number;
+  total_bytes: number;
+  mail_bytes: number;
+  daily_growth_bytes: number;
+  projected_need_bytes: number;
+  fidelity: "full" | "headers_only";
+}
+
+export interface AttachmentConfig {
+  blocked_extensions: string[];
+  allowed_extensions: string[];
+  block_encrypted_archives: boolean;
+  allow_open_anyway: boolean;
+  download_dir: string | null;
+}
+
+export interface AttachmentDownload {
+  id: string;
+  account_id: string;
+  message_id: string;
+  attachment_id: string;
+  file_name: string;
+  path: string;
+  saved_at: string;
+}
+
+export interface SaveAllReport {
+  saved: AttachmentDownload[];
+  skipped: { message_id: string; file_name: string; reason: string }[];
+}
+
+export type MuteDuration = "week" | "month" | "forever";
+
+export interface SenderMute {
+  account_id: string;
+  email: string;
+  /** null mutes forever. */
+  muted_until: string | null;
+  auto_archive: boolean;
+  created_at: string;
+}
+
+export interface RetentionPolicy {
+  enabled: boolean;
+  trash_days: number;
+  junk_days: number;
+  folder_overrides: Record<string, number>;
+}
+
+export interface SendGuardrails {
+  defer_outside_working_hours: boolean;
+  workday_start_hour: number;
+  workday_end_hour: number;
+  skip_weekends: boolean;
+  throttle_sends: boolean;
+  max_sends_per_minute: number;
+}
+
+export interface SendMailResult {
+  deferred_until: string | null;
+}
+
+export interface PurgeCandidate {
+  message_id: string;
+  account_id: string;
+  remote_id: string;
+  folder_path: string;
+  subject: string;
+  received_at: string;
 }
 
 export interface BootstrapResponse {
@@ -162,6 +501,13 @@ export interface SyncRunSummary {
   tasks_synced: number;
 }
 
+export interface OfflineQueueStatus {
+  pending: number;
+  needs_attention: number;
+  replayed: number;
+  offline: boolean;
+}
+
 export interface DataProvenance {
   feature: string;
   mode: "local" | "cloud";
@@ -211,6 +557,33 @@ export interface OutgoingMail {
   body_text: string;
   body_html: string | null;
   attachments: OutgoingAttachment[];
+  /** Angle brackets included; generated when a group is used. */
+  message_id?: string | null;
+}
+
+export type RecipientField = "to" | "cc" | "bcc";
+
+export interface ContactGroup {
+  id: string;
+  name: string;
+  members: MailAddress[];
+  created_at: string;
+  updated_at: string;
+}
+
+export interface GroupSend {
+  group_id: string;
+  field: RecipientField;
+  /** Member addresses left out of this one message. */
+  excluded: string[];
+}
+
+export interface GroupExpansion {
+  group_id: string;
+  group_name: string;
+  field: RecipientField;
+  recipients: MailAddress[];
+  excluded: string[];
 }
 
 export interface ValidateLocalAiRuntimePayload {
@@ -223,7 +596,49 @@ export interface ValidateLocalAiRuntimeResponse {
   errors: string[];
 }
 
+/** A recent thread with meeting attendees that an agenda item came from. */
+export interface AgendaSource {
+  thread_id: string;
+  subject: string;
+  last_sender: string;
+  last_message_at: string;
+}
+
+/** Suggested agenda for a new meeting; `markdown` goes into the event description. */
+export interface MeetingAgenda {
+  items: string[];
+  sources: AgendaSource[];
+  provenance: DataProvenance | null;
+  markdown: string;
+}
+
 export interface AiTaskExtractionResult {
   created: ReminderTask[];
   provenance: DataProvenance;
 }
+
+export type ProtocolKind = "imap" | "jmap" | "ews" | "gmail";
+
+/** One request to an account's server and how it ended, with credentials removed. */
+export interface ProtocolExchange {
+  account: string;
+  protocol: ProtocolKind;
+  started_at: string;
+  request: string;
+  response: string | null;
+  duration_ms: number | null;
+}
+
+export interface ProtocolLogStatus {
+  enabled: boolean;
+  capacity: number;
+  accounts: string[];
+}
+
+/** Recorded protocol exchanges packaged for a support request. */
+export interface SupportBundle {
+  generated_at: string;
+  app_version: string;
+  os: string;
+  exchanges: ProtocolExchange[];
+}